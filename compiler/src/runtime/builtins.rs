@@ -0,0 +1,1617 @@
+//! The functions and methods implemented by the runtime.
+//!
+//! Only a small part of the standard library is here, enough for scripts to print values,
+//! work with strings and arrays, handle exceptions and read attributes through reflection.
+
+use std::{cmp::Ordering, io::Write, rc::Rc};
+
+use crate::syntax::ast::{
+    attribute::{AttributeFlags, AttributeTarget},
+    types::BuiltinType,
+};
+
+use super::{
+    class::Class,
+    compile::AttributeDecl,
+    enums::BackingValue,
+    value::{format_float_repr, Array, ArrayKey, Internal, Object, PhpString, Value},
+    vm::{generator, Abort, Code, Native, NativeCall, Resume, Vm},
+};
+
+type NativeResult = Result<Value, Abort>;
+
+const fn native(name: &'static str, run: fn(&mut Vm, &mut NativeCall) -> NativeResult) -> Native {
+    Native {
+        name,
+        run,
+        by_ref: &[],
+        params: &[],
+    }
+}
+
+const fn by_ref(
+    name: &'static str,
+    run: fn(&mut Vm, &mut NativeCall) -> NativeResult,
+    by_ref: &'static [usize],
+) -> Native {
+    Native {
+        name,
+        run,
+        by_ref,
+        params: &[],
+    }
+}
+
+const fn typed(
+    name: &'static str,
+    run: fn(&mut Vm, &mut NativeCall) -> NativeResult,
+    params: &'static [(&'static str, &'static [BuiltinType])],
+) -> Native {
+    Native {
+        name,
+        run,
+        by_ref: &[],
+        params,
+    }
+}
+
+const STRING: &[BuiltinType] = &[BuiltinType::String];
+const INT: &[BuiltinType] = &[BuiltinType::Int];
+const NUMBER: &[BuiltinType] = &[BuiltinType::Int, BuiltinType::Float];
+const TRIM: &[(&str, &[BuiltinType])] = &[("string", STRING), ("characters", STRING)];
+
+/// Every builtin function, by lowercase name.
+pub(crate) const FUNCTIONS: &[Native] = &[
+    typed("strlen", strlen, &[("string", STRING)]),
+    native("count", count),
+    native("var_dump", var_dump),
+    native("print_r", print_r),
+    native("var_export", var_export),
+    native("gettype", gettype),
+    native("get_debug_type", get_debug_type),
+    native("get_class", get_class),
+    native("get_parent_class", get_parent_class),
+    native("is_null", |_, call| Ok(Value::Bool(call.arg(0).is_null()))),
+    native("is_bool", |_, call| {
+        Ok(Value::Bool(matches!(call.arg(0), Value::Bool(_))))
+    }),
+    native("is_int", |_, call| {
+        Ok(Value::Bool(matches!(call.arg(0), Value::Int(_))))
+    }),
+    native("is_float", |_, call| {
+        Ok(Value::Bool(matches!(call.arg(0), Value::Float(_))))
+    }),
+    native("is_string", |_, call| {
+        Ok(Value::Bool(matches!(call.arg(0), Value::String(_))))
+    }),
+    native("is_array", |_, call| {
+        Ok(Value::Bool(matches!(call.arg(0), Value::Array(_))))
+    }),
+    native("is_object", |_, call| {
+        Ok(Value::Bool(matches!(call.arg(0), Value::Object(_))))
+    }),
+    native("is_numeric", |_, call| {
+        Ok(Value::Bool(match call.arg(0) {
+            Value::Int(_) | Value::Float(_) => true,
+            Value::String(s) => super::value::parse_numeric(&s).is_some(),
+            _ => false,
+        }))
+    }),
+    native("is_callable", |vm, call| {
+        Ok(Value::Bool(vm.is_callable(&call.arg(0))))
+    }),
+    native("intval", |_, call| Ok(Value::Int(call.arg(0).to_int()))),
+    native("floatval", |_, call| {
+        Ok(Value::Float(call.arg(0).to_float()))
+    }),
+    native("boolval", |_, call| Ok(Value::Bool(call.arg(0).to_bool()))),
+    native("strval", |vm, call| {
+        Ok(Value::String(vm.stringify(&call.arg(0))?))
+    }),
+    native("implode", implode),
+    typed(
+        "explode",
+        explode,
+        &[("separator", STRING), ("string", STRING), ("limit", INT)],
+    ),
+    typed(
+        "str_repeat",
+        str_repeat,
+        &[("string", STRING), ("times", INT)],
+    ),
+    typed(
+        "strtolower",
+        |vm, call| {
+            Ok(Value::string(
+                vm.stringify(&call.arg(0))?.to_ascii_lowercase(),
+            ))
+        },
+        &[("string", STRING)],
+    ),
+    typed(
+        "strtoupper",
+        |vm, call| {
+            Ok(Value::string(
+                vm.stringify(&call.arg(0))?.to_ascii_uppercase(),
+            ))
+        },
+        &[("string", STRING)],
+    ),
+    typed(
+        "ucfirst",
+        |vm, call| {
+            let mut s = vm.stringify(&call.arg(0))?.into_bytes();
+            if let Some(first) = s.first_mut() {
+                first.make_ascii_uppercase();
+            }
+            Ok(Value::string(s))
+        },
+        &[("string", STRING)],
+    ),
+    typed("trim", |vm, call| trim(vm, call, true, true), TRIM),
+    typed("ltrim", |vm, call| trim(vm, call, true, false), TRIM),
+    typed("rtrim", |vm, call| trim(vm, call, false, true), TRIM),
+    typed(
+        "str_contains",
+        |vm, call| {
+            let (haystack, needle) = (vm.stringify(&call.arg(0))?, vm.stringify(&call.arg(1))?);
+            Ok(Value::Bool(find(&haystack, &needle).is_some()))
+        },
+        &[("haystack", STRING), ("needle", STRING)],
+    ),
+    typed(
+        "str_starts_with",
+        |vm, call| {
+            let (haystack, needle) = (vm.stringify(&call.arg(0))?, vm.stringify(&call.arg(1))?);
+            Ok(Value::Bool(haystack.starts_with(&needle)))
+        },
+        &[("haystack", STRING), ("needle", STRING)],
+    ),
+    typed(
+        "str_ends_with",
+        |vm, call| {
+            let (haystack, needle) = (vm.stringify(&call.arg(0))?, vm.stringify(&call.arg(1))?);
+            Ok(Value::Bool(haystack.ends_with(&needle)))
+        },
+        &[("haystack", STRING), ("needle", STRING)],
+    ),
+    typed(
+        "strpos",
+        strpos,
+        &[("haystack", STRING), ("needle", STRING), ("offset", INT)],
+    ),
+    native("substr", substr),
+    native("str_replace", str_replace),
+    native("sprintf", |vm, call| {
+        Ok(Value::string(sprintf(vm, &call.args)?))
+    }),
+    native("printf", |vm, call| {
+        let text = sprintf(vm, &call.args)?;
+        vm.echo(&text);
+        Ok(Value::Int(text.len() as i64))
+    }),
+    native("abs", |_, call| {
+        Ok(match call.arg(0).to_number().unwrap_or(Value::Int(0)) {
+            Value::Int(i) => i
+                .checked_abs()
+                .map_or(Value::Float((i as f64).abs()), Value::Int),
+            number => Value::Float(number.to_float().abs()),
+        })
+    }),
+    native("max", |vm, call| extreme(vm, call, Ordering::Greater)),
+    native("min", |vm, call| extreme(vm, call, Ordering::Less)),
+    typed(
+        "floor",
+        |_, call| Ok(Value::Float(call.arg(0).to_float().floor())),
+        &[("num", NUMBER)],
+    ),
+    typed(
+        "ceil",
+        |_, call| Ok(Value::Float(call.arg(0).to_float().ceil())),
+        &[("num", NUMBER)],
+    ),
+    typed("round", round, &[("num", NUMBER), ("precision", INT)]),
+    typed("intdiv", intdiv, &[("num1", INT), ("num2", INT)]),
+    native("array_keys", |vm, call| {
+        let array = array_arg(vm, call, 0, "array_keys")?;
+        Ok(Value::array(
+            array.iter().map(|(key, _)| key.to_value()).collect(),
+        ))
+    }),
+    native("array_values", |vm, call| {
+        let array = array_arg(vm, call, 0, "array_values")?;
+        Ok(Value::array(array.values().cloned().collect()))
+    }),
+    native("array_merge", array_merge),
+    native("array_map", array_map),
+    native("array_filter", array_filter),
+    native("array_sum", |vm, call| {
+        let array = array_arg(vm, call, 0, "array_sum")?;
+        let mut sum = Value::Int(0);
+        for value in array.values() {
+            sum = super::ops::binary(crate::syntax::ast::expr::BinaryOp::Add, &sum, value)
+                .map_err(|e| vm.error(e.0, e.1))?;
+        }
+        Ok(sum)
+    }),
+    native("in_array", |vm, call| {
+        let array = array_arg(vm, call, 1, "in_array")?;
+        let (needle, strict) = (call.arg(0), call.arg(2).to_bool());
+        let found = array.values().any(|value| match strict {
+            true => value.identical(&needle),
+            false => value.loose_eq(&needle),
+        });
+        Ok(Value::Bool(found))
+    }),
+    native("array_key_exists", |vm, call| {
+        let array = array_arg(vm, call, 1, "array_key_exists")?;
+        let key = ArrayKey::from_value(&call.arg(0));
+        Ok(Value::Bool(key.is_some_and(|key| array.contains_key(&key))))
+    }),
+    by_ref("array_push", array_push, &[0]),
+    by_ref("array_pop", array_pop, &[0]),
+    by_ref("array_shift", array_shift, &[0]),
+    by_ref("sort", |vm, call| sort(vm, call, false, false), &[0]),
+    by_ref("rsort", |vm, call| sort(vm, call, true, false), &[0]),
+    by_ref("usort", |vm, call| sort(vm, call, false, true), &[0]),
+    native("range", range),
+    native("iterator_to_array", iterator_to_array),
+    native("func_get_args", |vm, _| {
+        Ok(Value::array(vm.current_args().into_iter().collect()))
+    }),
+    native("call_user_func", |vm, call| {
+        let args = call.args.iter().skip(1).cloned().collect();
+        vm.call_value(&call.arg(0), args)
+    }),
+    native("call_user_func_array", |vm, call| {
+        let args = array_arg(vm, call, 1, "call_user_func_array")?;
+        vm.call_value(&call.arg(0), args.values().cloned().collect())
+    }),
+    native("set_exception_handler", |vm, call| {
+        let handler = call.arg(0);
+        let handler = (!handler.is_null()).then_some(handler);
+        Ok(std::mem::replace(&mut vm.exception_handler, handler).unwrap_or_default())
+    }),
+    native("restore_exception_handler", |vm, _| {
+        vm.exception_handler = None;
+        Ok(Value::Bool(true))
+    }),
+    native("register_tick_function", |vm, call| {
+        let callback = call.arg(0);
+        if !vm.is_callable(&callback) {
+            let message =
+                "register_tick_function(): Argument #1 ($callback) must be a valid callback";
+            return Err(vm.error("TypeError", message));
+        }
+        let args = call.args.iter().skip(1).cloned().collect();
+        vm.tick_functions.push((callback, args));
+        Ok(Value::Bool(true))
+    }),
+    native("unregister_tick_function", |vm, call| {
+        let callback = call.arg(0);
+        vm.tick_functions
+            .retain(|(registered, _)| !registered.identical(&callback));
+        Ok(Value::Null)
+    }),
+    native("function_exists", |vm, call| {
+        let name = vm.stringify(&call.arg(0))?.to_string();
+        Ok(Value::Bool(vm.has_function(&name)))
+    }),
+    native("class_exists", |vm, call| {
+        let name = vm.stringify(&call.arg(0))?.to_string();
+        Ok(Value::Bool(vm.class(&name).is_some()))
+    }),
+    native("method_exists", |vm, call| {
+        let class = match call.arg(0) {
+            Value::Object(object) => Some(object.class.clone()),
+            value => {
+                let name = vm.stringify(&value)?.to_string();
+                vm.class(&name)
+            }
+        };
+        let method = vm.stringify(&call.arg(1))?.to_string();
+        Ok(Value::Bool(
+            class.is_some_and(|class| class.find_method(&method).is_some()),
+        ))
+    }),
+    native("spl_object_id", |vm, call| match call.arg(0) {
+        Value::Object(object) => Ok(Value::Int(object.id as i64)),
+        value => Err(type_error(vm, "spl_object_id", 1, "object", &value)),
+    }),
+    native("define", |vm, call| {
+        let name = vm.stringify(&call.arg(0))?.to_string();
+        Ok(Value::Bool(vm.define(&name, call.arg(1))))
+    }),
+    native("defined", |vm, call| {
+        let name = vm.stringify(&call.arg(0))?.to_string();
+        Ok(Value::Bool(vm.constant_value(&name).is_some()))
+    }),
+];
+
+/// The constants every script starts with.
+pub(crate) fn constant(name: &str) -> Option<Value> {
+    Some(match name {
+        "PHP_EOL" => Value::string("\n"),
+        "PHP_INT_MAX" => Value::Int(i64::MAX),
+        "PHP_INT_MIN" => Value::Int(i64::MIN),
+        "PHP_INT_SIZE" => Value::Int(8),
+        "PHP_FLOAT_EPSILON" => Value::Float(f64::EPSILON),
+        "NAN" => Value::Float(f64::NAN),
+        "INF" => Value::Float(f64::INFINITY),
+        "M_PI" => Value::Float(std::f64::consts::PI),
+        _ if name.eq_ignore_ascii_case("true") => Value::Bool(true),
+        _ if name.eq_ignore_ascii_case("false") => Value::Bool(false),
+        _ if name.eq_ignore_ascii_case("null") => Value::Null,
+        _ => return None,
+    })
+}
+
+/// The constants a builtin class declares itself.
+pub(crate) fn class_constants(class: &str) -> Vec<(&'static str, Value)> {
+    match class {
+        "Attribute" => {
+            let mut constants: Vec<_> = AttributeTarget::ALL
+                .into_iter()
+                .map(|target| (target.constant(), Value::Int(target as i64)))
+                .collect();
+            constants.push(("TARGET_ALL", Value::Int(AttributeFlags::TARGET_ALL.into())));
+            constants.push((
+                "IS_REPEATABLE",
+                Value::Int(AttributeFlags::IS_REPEATABLE.into()),
+            ));
+            constants
+        }
+        "ReflectionAttribute" => vec![("IS_INSTANCEOF", Value::Int(IS_INSTANCEOF))],
+        _ => Vec::new(),
+    }
+}
+
+/// The methods a builtin class declares itself.
+pub(crate) fn class_methods(class: &str) -> Vec<(&'static str, Native)> {
+    match class {
+        "Exception" | "Error" => vec![
+            ("__construct", native("__construct", throwable_construct)),
+            (
+                "getMessage",
+                native("getMessage", |_, call| Ok(this(call).get("message"))),
+            ),
+            (
+                "getCode",
+                native("getCode", |_, call| Ok(this(call).get("code"))),
+            ),
+            (
+                "getPrevious",
+                native("getPrevious", |_, call| Ok(this(call).get("previous"))),
+            ),
+            (
+                "getFile",
+                native("getFile", |_, call| Ok(this(call).get("file"))),
+            ),
+            (
+                "getLine",
+                native("getLine", |_, call| Ok(this(call).get("line"))),
+            ),
+            (
+                "getTrace",
+                native("getTrace", |_, _| Ok(Value::array(Array::new()))),
+            ),
+            (
+                "getTraceAsString",
+                native("getTraceAsString", |_, _| Ok(Value::string("#0 {main}"))),
+            ),
+            ("__toString", native("__toString", throwable_to_string)),
+        ],
+        "Attribute" => vec![("__construct", native("__construct", attribute_construct))],
+        "ReflectionClass" => vec![
+            (
+                "__construct",
+                native("__construct", reflection_class_construct),
+            ),
+            (
+                "getName",
+                native("getName", |_, call| Ok(this(call).get("name"))),
+            ),
+            (
+                "getAttributes",
+                native("getAttributes", reflection_class_attributes),
+            ),
+            ("getMethod", native("getMethod", reflection_class_method)),
+        ],
+        "ReflectionMethod" => vec![
+            (
+                "__construct",
+                native("__construct", reflection_method_construct),
+            ),
+            (
+                "getName",
+                native("getName", |_, call| Ok(this(call).get("name"))),
+            ),
+            (
+                "getAttributes",
+                native("getAttributes", reflection_method_attributes),
+            ),
+        ],
+        "ReflectionFunction" => vec![
+            (
+                "__construct",
+                native("__construct", reflection_function_construct),
+            ),
+            (
+                "getName",
+                native("getName", |_, call| Ok(this(call).get("name"))),
+            ),
+            (
+                "getAttributes",
+                native("getAttributes", reflection_function_attributes),
+            ),
+        ],
+        "ReflectionAttribute" => vec![
+            ("getName", native("getName", reflection_attribute_name)),
+            (
+                "getArguments",
+                native("getArguments", reflection_attribute_arguments),
+            ),
+            (
+                "newInstance",
+                native("newInstance", reflection_attribute_instance),
+            ),
+        ],
+        "Generator" => vec![
+            ("current", native("current", generator_current)),
+            ("key", native("key", generator_key)),
+            ("next", native("next", generator_next)),
+            ("send", native("send", generator_send)),
+            ("throw", native("throw", generator_throw)),
+            ("valid", native("valid", generator_valid)),
+            ("rewind", native("rewind", generator_rewind)),
+            ("getReturn", native("getReturn", generator_get_return)),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// The static methods every enum has, `from()` and `tryFrom()` only when it is backed.
+pub(crate) fn enum_methods(backed: bool) -> Vec<(&'static str, Native)> {
+    let mut methods = vec![("cases", native("cases", enum_cases))];
+    if backed {
+        methods.push((
+            "from",
+            native("from", |vm, call| enum_from(vm, call, false)),
+        ));
+        methods.push((
+            "tryFrom",
+            native("tryFrom", |vm, call| enum_from(vm, call, true)),
+        ));
+    }
+    methods
+}
+
+fn enum_cases(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let class = call.class.clone().expect("a static method has a class");
+    let enumeration = class.enumeration.clone().expect("only enums have cases()");
+    let mut cases = Array::new();
+    for case in enumeration.cases() {
+        cases.push(vm.class_const(&class, case.name())?);
+    }
+    Ok(Value::array(cases))
+}
+
+/// `from()` and `tryFrom()`, which look up the case with a backing value.
+fn enum_from(vm: &mut Vm, call: &mut NativeCall, try_from: bool) -> NativeResult {
+    let class = call.class.clone().expect("a static method has a class");
+    let enumeration = class.enumeration.clone().expect("only enums have from()");
+    let value = match call.arg(0) {
+        Value::Int(value) => BackingValue::Int(value),
+        Value::String(value) => BackingValue::String(value.into_bytes()),
+        Value::Bool(value) => BackingValue::Int(i64::from(value)),
+        Value::Float(value) if value.fract() == 0.0 => BackingValue::Int(value as i64),
+        value => {
+            let message = format!(
+                "{}::{}(): Argument #1 ($value) must be of type {}, {} given",
+                enumeration.name(),
+                if try_from { "tryFrom" } else { "from" },
+                enumeration.backing_type().map_or("int", |ty| ty.as_str()),
+                debug_type(&value)
+            );
+            return Err(vm.error("TypeError", message));
+        }
+    };
+    let case = match try_from {
+        true => enumeration.try_from(value),
+        false => enumeration.from(value).map(Some),
+    };
+    match case {
+        Ok(Some(case)) => vm.class_const(&class, case.name()),
+        Ok(None) => Ok(Value::Null),
+        Err(error) => Err(vm.runtime_error(error)),
+    }
+}
+
+/// `ReflectionAttribute::IS_INSTANCEOF`, which makes `getAttributes()` match subclasses too.
+const IS_INSTANCEOF: i64 = 2;
+
+fn attribute_construct(_: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let flags = match call.args.first() {
+        Some(flags) => flags.to_int(),
+        None => AttributeFlags::TARGET_ALL.into(),
+    };
+    this(call).set("flags", Value::Int(flags));
+    Ok(Value::Null)
+}
+
+fn reflection_error(vm: &mut Vm, message: String) -> Abort {
+    vm.error("ReflectionException", message)
+}
+
+/// The class a `ReflectionClass` or `ReflectionMethod` is constructed with, by name or from
+/// an object.
+fn reflected_class(vm: &mut Vm, value: &Value) -> Result<Rc<Class>, Abort> {
+    let name = match value {
+        Value::Object(object) => return Ok(object.class.clone()),
+        value => vm.stringify(value)?.to_string(),
+    };
+    match vm.class(&name) {
+        Some(class) => Ok(class),
+        None => Err(reflection_error(
+            vm,
+            format!("Class \"{}\" does not exist", name),
+        )),
+    }
+}
+
+fn reflection_class_construct(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let class = reflected_class(vm, &call.arg(0))?;
+    this(call).set("name", Value::string(class.name.as_str()));
+    Ok(Value::Null)
+}
+
+/// The class a `ReflectionClass` reflects, or a `ReflectionMethod` belongs to.
+fn this_class(vm: &mut Vm, call: &NativeCall, prop: &str) -> Result<Rc<Class>, Abort> {
+    let name = this(call).get(prop);
+    reflected_class(vm, &name)
+}
+
+fn reflection_class_attributes(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let class = this_class(vm, call, "name")?;
+    let attributes = class.attributes.clone();
+    attribute_objects(vm, call, &attributes, Some(class))
+}
+
+/// A `ReflectionMethod` for a method of a class, named by its declaring class.
+fn reflection_method(vm: &mut Vm, class: &Rc<Class>, name: &str) -> NativeResult {
+    let Some(method) = class.find_method(name).cloned() else {
+        let message = format!("Method {}::{}() does not exist", class.name, name);
+        return Err(reflection_error(vm, message));
+    };
+    let scope = vm.class(&method.scope).expect("methods belong to a class");
+    let reflection = vm
+        .class("ReflectionMethod")
+        .expect("ReflectionMethod is builtin");
+    let mut props = Array::new();
+    props.insert(ArrayKey::from("name"), Value::string(method.name.as_str()));
+    props.insert(ArrayKey::from("class"), Value::string(scope.name.as_str()));
+    let id = vm.next_object_id();
+    Ok(Value::Object(Rc::new(Object::new(
+        reflection,
+        props,
+        Internal::None,
+        id,
+    ))))
+}
+
+fn reflection_class_method(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let class = this_class(vm, call, "name")?;
+    let name = vm.stringify(&call.arg(0))?.to_string();
+    reflection_method(vm, &class, &name)
+}
+
+fn reflection_method_construct(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    // `new ReflectionMethod('Foo::bar')` as well as `new ReflectionMethod('Foo', 'bar')`.
+    let (class, name) = match call.args.get(1) {
+        Some(name) => (call.arg(0), vm.stringify(name)?.to_string()),
+        None => {
+            let target = vm.stringify(&call.arg(0))?.to_string();
+            let Some((class, name)) = target.split_once("::") else {
+                let message = "ReflectionMethod::__construct(): Argument #1 ($objectOrMethod) must be a valid method name";
+                return Err(reflection_error(vm, message.to_string()));
+            };
+            (Value::string(class), name.to_string())
+        }
+    };
+    let class = reflected_class(vm, &class)?;
+    let Value::Object(method) = reflection_method(vm, &class, &name)? else {
+        unreachable!("reflection_method creates an object");
+    };
+    let this = this(call);
+    this.set("name", method.get("name"));
+    this.set("class", method.get("class"));
+    Ok(Value::Null)
+}
+
+fn reflection_method_attributes(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let class = this_class(vm, call, "class")?;
+    let name = this(call).get("name");
+    let name = vm.stringify(&name)?.to_string();
+    let attributes = match class
+        .find_method(&name)
+        .and_then(|method| method.body.clone())
+    {
+        Some(Code::User(func)) => func.attributes.clone(),
+        _ => Vec::new(),
+    };
+    attribute_objects(vm, call, &attributes, Some(class))
+}
+
+fn reflection_function_construct(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let name = vm.stringify(&call.arg(0))?.to_string();
+    let declared = match vm.function(&name) {
+        Some(Code::User(func)) => func.name.clone(),
+        Some(Code::Native(native)) => native.name.to_string(),
+        None => {
+            let message = format!("Function {}() does not exist", name);
+            return Err(reflection_error(vm, message));
+        }
+    };
+    this(call).set("name", Value::string(declared));
+    Ok(Value::Null)
+}
+
+fn reflection_function_attributes(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let name = this(call).get("name");
+    let name = vm.stringify(&name)?.to_string();
+    let attributes = match vm.function(&name) {
+        Some(Code::User(func)) => func.attributes.clone(),
+        _ => Vec::new(),
+    };
+    attribute_objects(vm, call, &attributes, None)
+}
+
+/// The `ReflectionAttribute`s `getAttributes(?string $name = null, int $flags = 0)` gives,
+/// those of the class `$name` or, with `IS_INSTANCEOF`, of its subclasses.
+fn attribute_objects(
+    vm: &mut Vm,
+    call: &NativeCall,
+    attributes: &[Rc<AttributeDecl>],
+    scope: Option<Rc<Class>>,
+) -> NativeResult {
+    let filter = match call.arg(0) {
+        Value::Null => None,
+        name => Some(vm.stringify(&name)?.to_string()),
+    };
+    let instance_of = call.arg(1).to_int() & IS_INSTANCEOF != 0;
+    let filter = filter.map(|name| name.trim_start_matches('\\').to_ascii_lowercase());
+    let class = vm
+        .class("ReflectionAttribute")
+        .expect("ReflectionAttribute is builtin");
+    let mut objects = Array::new();
+    for attribute in attributes {
+        let matches = match &filter {
+            None => true,
+            Some(filter) if instance_of => vm
+                .class(&attribute.name)
+                .is_some_and(|class| class.is_subclass_of(filter)),
+            Some(filter) => attribute.name.eq_ignore_ascii_case(filter),
+        };
+        if matches {
+            let internal = Internal::Attribute(attribute.clone(), scope.clone());
+            let id = vm.next_object_id();
+            let object = Object::new(class.clone(), Array::new(), internal, id);
+            objects.push(Value::Object(Rc::new(object)));
+        }
+    }
+    Ok(Value::array(objects))
+}
+
+/// The attribute a `ReflectionAttribute` method is called on, with the class its arguments
+/// are evaluated in. Only `getAttributes()` creates these objects.
+fn reflected_attribute(
+    vm: &mut Vm,
+    call: &NativeCall,
+) -> Result<(Rc<AttributeDecl>, Option<Rc<Class>>), Abort> {
+    match &this(call).internal {
+        Internal::Attribute(attribute, scope) => Ok((attribute.clone(), scope.clone())),
+        _ => Err(vm.error(
+            "Error",
+            "Internal error: Failed to retrieve the reflection object",
+        )),
+    }
+}
+
+fn reflection_attribute_name(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let (attribute, _) = reflected_attribute(vm, call)?;
+    Ok(Value::string(attribute.name.as_str()))
+}
+
+fn reflection_attribute_arguments(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let (attribute, scope) = reflected_attribute(vm, call)?;
+    vm.run_thunk(&attribute.args, scope)
+}
+
+fn reflection_attribute_instance(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let (attribute, scope) = reflected_attribute(vm, call)?;
+    let message = match vm.class(&attribute.name) {
+        Some(class) if class.is_attribute() => return vm.run_thunk(&attribute.instance, scope),
+        Some(_) => format!(
+            "Attempting to use non-attribute class \"{}\" as attribute",
+            attribute.name
+        ),
+        None => format!("Attribute class \"{}\" not found", attribute.name),
+    };
+    Err(vm.error("Error", message))
+}
+
+/// The generator a `Generator` method is called on, run up to its first `yield`.
+fn started(vm: &mut Vm, call: &NativeCall) -> Result<Rc<Object>, Abort> {
+    let object = this(call);
+    vm.start_generator(&object)?;
+    Ok(object)
+}
+
+fn generator_current(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = started(vm, call)?;
+    let current = generator(&object).current();
+    Ok(current.map(|(_, value)| value).unwrap_or_default())
+}
+
+fn generator_key(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = started(vm, call)?;
+    let current = generator(&object).current();
+    Ok(current.map(|(key, _)| key).unwrap_or_default())
+}
+
+fn generator_next(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = started(vm, call)?;
+    vm.resume_generator(&object, Resume::Send(Value::Null))?;
+    Ok(Value::Null)
+}
+
+fn generator_send(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = started(vm, call)?;
+    vm.resume_generator(&object, Resume::Send(call.arg(0)))?;
+    let current = generator(&object).current();
+    Ok(current.map(|(_, value)| value).unwrap_or_default())
+}
+
+fn generator_throw(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let exception = match call.arg(0) {
+        Value::Object(exception) if exception.instance_of("throwable") => exception,
+        value => return Err(type_error(vm, "Generator::throw", 1, "Throwable", &value)),
+    };
+    let object = started(vm, call)?;
+    vm.resume_generator(&object, Resume::Throw(exception))?;
+    let current = generator(&object).current();
+    Ok(current.map(|(_, value)| value).unwrap_or_default())
+}
+
+fn generator_valid(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = started(vm, call)?;
+    Ok(Value::Bool(!generator(&object).finished()))
+}
+
+fn generator_rewind(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = started(vm, call)?;
+    if generator(&object).advanced() {
+        return Err(vm.error(
+            "Exception",
+            "Cannot rewind a generator that was already run",
+        ));
+    }
+    Ok(Value::Null)
+}
+
+fn generator_get_return(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let object = this(call);
+    match generator(&object).returned() {
+        Some(value) => Ok(value),
+        None => {
+            let message = "Cannot get return value of a generator that hasn't returned";
+            Err(vm.error("Exception", message))
+        }
+    }
+}
+
+fn this(call: &NativeCall) -> Rc<Object> {
+    call.this.clone().expect("an instance method has an object")
+}
+
+fn type_error(
+    vm: &mut Vm,
+    function: &str,
+    position: usize,
+    expected: &str,
+    value: &Value,
+) -> Abort {
+    let message = format!(
+        "{}(): Argument #{} must be of type {}, {} given",
+        function,
+        position,
+        expected,
+        debug_type(value)
+    );
+    vm.error("TypeError", message)
+}
+
+fn array_arg(
+    vm: &mut Vm,
+    call: &NativeCall,
+    index: usize,
+    function: &str,
+) -> Result<Rc<Array>, Abort> {
+    match call.arg(index) {
+        Value::Array(array) => Ok(array),
+        value => Err(type_error(vm, function, index + 1, "array", &value)),
+    }
+}
+
+fn iterator_to_array(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let preserve_keys = call.args.get(1).is_none_or(Value::to_bool);
+    let mut object = match call.arg(0) {
+        Value::Array(array) if preserve_keys => return Ok(Value::Array(array)),
+        Value::Array(array) => return Ok(Value::array(array.values().cloned().collect())),
+        Value::Object(object) if object.instance_of("traversable") => object,
+        value => {
+            return Err(type_error(
+                vm,
+                "iterator_to_array",
+                1,
+                "Traversable|array",
+                &value,
+            ))
+        }
+    };
+    while object.instance_of("iteratoraggregate") {
+        object = match vm.call_method(object, "getIterator", Vec::new())? {
+            Value::Object(inner) if inner.instance_of("traversable") => inner,
+            _ => return Err(vm.error("TypeError", "getIterator() must return a Traversable")),
+        };
+    }
+    let mut array = Array::new();
+    vm.call_method(object.clone(), "rewind", Vec::new())?;
+    while vm
+        .call_method(object.clone(), "valid", Vec::new())?
+        .to_bool()
+    {
+        let value = vm.call_method(object.clone(), "current", Vec::new())?;
+        match preserve_keys {
+            true => {
+                let key = vm.call_method(object.clone(), "key", Vec::new())?;
+                let Some(key) = ArrayKey::from_value(&key) else {
+                    let message =
+                        format!("Cannot access offset of type {} on array", key.type_name());
+                    return Err(vm.error("TypeError", message));
+                };
+                array.insert(key, value);
+            }
+            false => array.push(value),
+        }
+        vm.call_method(object.clone(), "next", Vec::new())?;
+    }
+    Ok(Value::array(array))
+}
+
+fn throwable_construct(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let this = this(call);
+    if let Some(message) = call.args.first() {
+        this.set("message", Value::String(vm.stringify(message)?));
+    }
+    if let Some(code) = call.args.get(1) {
+        this.set("code", Value::Int(code.to_int()));
+    }
+    if let Some(previous) = call.args.get(2) {
+        this.set("previous", previous.clone());
+    }
+    Ok(Value::Null)
+}
+
+fn throwable_to_string(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let this = this(call);
+    let message = vm.stringify(&this.get("message"))?;
+    let mut text = this.class.name.clone().into_bytes();
+    if !message.is_empty() {
+        text.extend_from_slice(b": ");
+        text.extend_from_slice(&message);
+    }
+    let _ = write!(
+        text,
+        " in {}:{}\nStack trace:\n#0 {{main}}",
+        vm.stringify(&this.get("file"))?,
+        this.get("line").to_int()
+    );
+    Ok(Value::string(text))
+}
+
+fn strlen(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    Ok(Value::Int(vm.stringify(&call.arg(0))?.len() as i64))
+}
+
+fn count(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    match call.arg(0) {
+        Value::Array(array) => Ok(Value::Int(array.len() as i64)),
+        Value::Object(object) if object.instance_of("countable") => {
+            vm.call_method(object, "count", Vec::new())
+        }
+        value => Err(type_error(vm, "count", 1, "Countable|array", &value)),
+    }
+}
+
+/// The name of the type of a value, as `get_debug_type` gives it.
+fn debug_type(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Int(_) => "int".to_string(),
+        Value::Float(_) => "float".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Array(_) => "array".to_string(),
+        Value::Object(object) => object.class.name.clone(),
+    }
+}
+
+fn gettype(_: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    Ok(Value::string(match call.arg(0) {
+        Value::Null => "NULL",
+        Value::Bool(_) => "boolean",
+        Value::Int(_) => "integer",
+        Value::Float(_) => "double",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }))
+}
+
+fn get_debug_type(_: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    Ok(Value::string(debug_type(&call.arg(0))))
+}
+
+fn get_class(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    match call.arg(0) {
+        Value::Object(object) => Ok(Value::string(object.class.name.as_str())),
+        value => Err(type_error(vm, "get_class", 1, "object", &value)),
+    }
+}
+
+fn get_parent_class(_: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    Ok(match call.arg(0) {
+        Value::Object(object) => match &object.class.parent {
+            Some(parent) => Value::string(parent.name.as_str()),
+            None => Value::Bool(false),
+        },
+        _ => Value::Bool(false),
+    })
+}
+
+fn var_dump(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let mut text = Vec::new();
+    for value in &call.args {
+        dump(&mut text, value, 0);
+    }
+    vm.echo(&text);
+    Ok(Value::Null)
+}
+
+fn dump(out: &mut Vec<u8>, value: &Value, depth: usize) {
+    let pad = "  ".repeat(depth);
+    let _ = match value {
+        Value::Null => writeln!(out, "NULL"),
+        Value::Bool(b) => writeln!(out, "bool({})", b),
+        Value::Int(i) => writeln!(out, "int({})", i),
+        Value::Float(f) => writeln!(out, "float({})", format_float_repr(*f)),
+        Value::String(s) => {
+            let _ = write!(out, "string({}) \"", s.len());
+            out.extend_from_slice(s);
+            writeln!(out, "\"")
+        }
+        Value::Array(array) => {
+            let _ = writeln!(out, "array({}) {{", array.len());
+            dump_entries(out, array, depth);
+            writeln!(out, "{}}}", pad)
+        }
+        Value::Object(object) => {
+            let props = object.props.borrow();
+            let _ = writeln!(
+                out,
+                "object({})#{} ({}) {{",
+                object.class.name,
+                object.id,
+                props.len()
+            );
+            for (key, value) in props.iter() {
+                dump_key(out, key, &pad);
+                dump(out, value, depth + 1);
+            }
+            writeln!(out, "{}}}", pad)
+        }
+    };
+}
+
+fn dump_entries(out: &mut Vec<u8>, array: &Array, depth: usize) {
+    let pad = "  ".repeat(depth);
+    for (key, value) in array.iter() {
+        dump_key(out, key, &pad);
+        dump(out, value, depth + 1);
+    }
+}
+
+/// The `["key"]=>` line of an entry, and the padding of the value on the next one.
+fn dump_key(out: &mut Vec<u8>, key: &ArrayKey, pad: &str) {
+    let _ = match key {
+        ArrayKey::Int(i) => writeln!(out, "{}  [{}]=>", pad, i),
+        ArrayKey::String(s) => {
+            let _ = write!(out, "{}  [\"", pad);
+            out.extend_from_slice(s);
+            writeln!(out, "\"]=>")
+        }
+    };
+    let _ = write!(out, "{}  ", pad);
+}
+
+fn print_r(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let mut text = Vec::new();
+    print_value(vm, &mut text, &call.arg(0), 0)?;
+    match call.arg(1).to_bool() {
+        true => Ok(Value::string(text)),
+        false => {
+            vm.echo(&text);
+            Ok(Value::Bool(true))
+        }
+    }
+}
+
+fn print_value(vm: &mut Vm, out: &mut Vec<u8>, value: &Value, depth: usize) -> Result<(), Abort> {
+    let (header, entries) = match value {
+        Value::Array(array) => ("Array".to_string(), (**array).clone()),
+        Value::Object(object) if !matches!(object.internal, Internal::Closure(_)) => (
+            format!("{} Object", object.class.name),
+            object.props.borrow().clone(),
+        ),
+        Value::Object(object) => (format!("{} Object", object.class.name), Array::new()),
+        value => {
+            out.extend_from_slice(&vm.stringify(value)?);
+            return Ok(());
+        }
+    };
+    let pad = "    ".repeat(depth * 2);
+    let _ = write!(out, "{}\n{}(\n", header, pad);
+    for (key, value) in entries.iter() {
+        let _ = write!(out, "{}    [", pad);
+        match key {
+            ArrayKey::Int(i) => {
+                let _ = write!(out, "{}", i);
+            }
+            ArrayKey::String(s) => out.extend_from_slice(s),
+        }
+        out.extend_from_slice(b"] => ");
+        print_value(vm, out, value, depth + 1)?;
+        out.push(b'\n');
+    }
+    let _ = writeln!(out, "{})", pad);
+    Ok(())
+}
+
+fn var_export(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let mut text = Vec::new();
+    export(&mut text, &call.arg(0), 0);
+    match call.arg(1).to_bool() {
+        true => Ok(Value::string(text)),
+        false => {
+            vm.echo(&text);
+            Ok(Value::Null)
+        }
+    }
+}
+
+fn export(out: &mut Vec<u8>, value: &Value, depth: usize) {
+    let pad = "  ".repeat(depth);
+    let _ = match value {
+        Value::Null => write!(out, "NULL"),
+        Value::Bool(b) => write!(out, "{}", b),
+        Value::Int(i) => write!(out, "{}", i),
+        Value::Float(f) => {
+            let repr = format_float_repr(*f);
+            match f.is_finite() && !repr.contains(['.', 'E', 'e']) {
+                true => write!(out, "{}.0", repr),
+                false => write!(out, "{}", repr),
+            }
+        }
+        Value::String(s) => {
+            export_string(out, s);
+            Ok(())
+        }
+        Value::Array(array) => {
+            let _ = writeln!(out, "array (");
+            for (key, value) in array.iter() {
+                let _ = write!(out, "{}  ", pad);
+                match key {
+                    ArrayKey::Int(i) => {
+                        let _ = write!(out, "{}", i);
+                    }
+                    ArrayKey::String(s) => export_string(out, s),
+                }
+                let _ = write!(out, " => ");
+                if matches!(value, Value::Array(_) | Value::Object(_)) {
+                    let _ = write!(out, "\n{}  ", pad);
+                }
+                export(out, value, depth + 1);
+                let _ = writeln!(out, ",");
+            }
+            write!(out, "{})", pad)
+        }
+        Value::Object(object) => {
+            let _ = writeln!(out, "\\{}::__set_state(array(", object.class.name);
+            for (key, value) in object.props.borrow().iter() {
+                let _ = write!(out, "{}   '{}' => ", pad, key);
+                export(out, value, depth + 1);
+                let _ = writeln!(out, ",");
+            }
+            write!(out, "{}))", pad)
+        }
+    };
+}
+
+/// A single quoted string literal, with `\` and `'` escaped.
+fn export_string(out: &mut Vec<u8>, s: &[u8]) {
+    out.push(b'\'');
+    for &byte in s {
+        if matches!(byte, b'\\' | b'\'') {
+            out.push(b'\\');
+        }
+        out.push(byte);
+    }
+    out.push(b'\'');
+}
+
+fn implode(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let (separator, pieces) = match (call.arg(0), call.arg(1)) {
+        (Value::Array(pieces), Value::Null) => (PhpString::default(), pieces),
+        (separator, Value::Array(pieces)) => (vm.stringify(&separator)?, pieces),
+        (Value::Array(pieces), separator) => (vm.stringify(&separator)?, pieces),
+        (_, value) => return Err(type_error(vm, "implode", 2, "?array", &value)),
+    };
+    let mut parts = Vec::new();
+    for value in pieces.values() {
+        parts.push(vm.stringify(value)?.into_bytes());
+    }
+    Ok(Value::string(parts.join(separator.as_bytes())))
+}
+
+fn explode(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let separator = vm.stringify(&call.arg(0))?;
+    let text = vm.stringify(&call.arg(1))?;
+    if separator.is_empty() {
+        let message = "explode(): Argument #1 ($separator) cannot be empty";
+        return Err(vm.error("ValueError", message));
+    }
+    let mut pieces = Array::new();
+    let mut rest = text.as_bytes();
+    while let Some(i) = find(rest, &separator) {
+        pieces.push(Value::string(&rest[..i]));
+        rest = &rest[i + separator.len()..];
+    }
+    pieces.push(Value::string(rest));
+    Ok(Value::array(pieces))
+}
+
+fn str_repeat(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let text = vm.stringify(&call.arg(0))?;
+    let times = call.arg(1).to_int();
+    if times < 0 {
+        let message = "str_repeat(): Argument #2 ($times) must be greater than or equal to 0";
+        return Err(vm.error("ValueError", message));
+    }
+    Ok(Value::string(text.repeat(times as usize)))
+}
+
+fn trim(vm: &mut Vm, call: &mut NativeCall, start: bool, end: bool) -> NativeResult {
+    let text = vm.stringify(&call.arg(0))?;
+    let characters = match call.args.get(1) {
+        Some(characters) => vm.stringify(characters)?,
+        None => PhpString::from(" \t\n\r\0\x0B"),
+    };
+    let is_kept = |byte: &u8| !characters.contains(byte);
+    let mut result = text.as_bytes();
+    if start {
+        let first = result.iter().position(is_kept).unwrap_or(result.len());
+        result = &result[first..];
+    }
+    if end {
+        let last = result.iter().rposition(is_kept).map_or(0, |i| i + 1);
+        result = &result[..last];
+    }
+    Ok(Value::string(result))
+}
+
+fn strpos(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let haystack = vm.stringify(&call.arg(0))?;
+    let needle = vm.stringify(&call.arg(1))?;
+    let offset = call.arg(2).to_int().clamp(0, haystack.len() as i64) as usize;
+    Ok(match find(&haystack[offset..], &needle) {
+        Some(position) => Value::Int((offset + position) as i64),
+        None => Value::Bool(false),
+    })
+}
+
+fn substr(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let text = vm.stringify(&call.arg(0))?;
+    let len = text.len() as i64;
+    let start = call.arg(1).to_int();
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start.min(len)
+    };
+    let end = match call.arg(2) {
+        Value::Null => len,
+        length => {
+            let length = length.to_int();
+            if length < 0 {
+                (len + length).max(start)
+            } else {
+                (start + length).min(len)
+            }
+        }
+    };
+    Ok(Value::string(&text[start as usize..end as usize]))
+}
+
+fn str_replace(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let pairs: Vec<(Value, Value)> = match (call.arg(0), call.arg(1)) {
+        (Value::Array(search), Value::Array(replace)) => search
+            .values()
+            .enumerate()
+            .map(|(i, s)| {
+                (
+                    s.clone(),
+                    replace
+                        .values()
+                        .nth(i)
+                        .cloned()
+                        .unwrap_or(Value::string("")),
+                )
+            })
+            .collect(),
+        (Value::Array(search), replace) => search
+            .values()
+            .map(|s| (s.clone(), replace.clone()))
+            .collect(),
+        (search, replace) => vec![(search, replace)],
+    };
+    let mut text = vm.stringify(&call.arg(2))?.into_bytes();
+    for (search, replace) in pairs {
+        let search = vm.stringify(&search)?;
+        if search.is_empty() {
+            continue;
+        }
+        let replace = vm.stringify(&replace)?;
+        let mut replaced = Vec::with_capacity(text.len());
+        let mut rest = text.as_slice();
+        while let Some(i) = find(rest, &search) {
+            replaced.extend_from_slice(&rest[..i]);
+            replaced.extend_from_slice(&replace);
+            rest = &rest[i + search.len()..];
+        }
+        replaced.extend_from_slice(rest);
+        text = replaced;
+    }
+    Ok(Value::string(text))
+}
+
+/// The position of the first `needle` in `haystack`, an empty needle is found at the start.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// `sprintf` with the `%s`, `%d`, `%f`, `%x`, `%b` and `%%` conversions, padding and
+/// precision.
+fn sprintf(vm: &mut Vm, args: &[Value]) -> Result<Vec<u8>, Abort> {
+    let format = vm.stringify(args.first().unwrap_or(&Value::Null))?;
+    let mut out = Vec::new();
+    let mut bytes = format.iter().copied().peekable();
+    let mut next = 1;
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            out.push(byte);
+            continue;
+        }
+        if bytes.peek() == Some(&b'%') {
+            bytes.next();
+            out.push(b'%');
+            continue;
+        }
+        let mut left = false;
+        let mut pad = b' ';
+        loop {
+            match bytes.peek() {
+                Some(b'-') => left = true,
+                Some(b'0') => pad = b'0',
+                Some(b'\'') => {
+                    bytes.next();
+                    pad = bytes.peek().copied().unwrap_or(b' ');
+                }
+                _ => break,
+            }
+            bytes.next();
+        }
+        let digit = |byte: Option<&u8>| byte.filter(|b| b.is_ascii_digit()).map(|b| b - b'0');
+        let mut width = 0;
+        while let Some(digit) = digit(bytes.peek()) {
+            width = width * 10 + digit as usize;
+            bytes.next();
+        }
+        let mut precision = None;
+        if bytes.peek() == Some(&b'.') {
+            bytes.next();
+            let mut digits = 0;
+            while let Some(digit) = digit(bytes.peek()) {
+                digits = digits * 10 + digit as usize;
+                bytes.next();
+            }
+            precision = Some(digits);
+        }
+        let Some(conversion) = bytes.next() else {
+            break;
+        };
+        let Some(arg) = args.get(next) else {
+            let message = format!("{} arguments are required, {} given", next + 1, args.len());
+            return Err(vm.error("ArgumentCountError", message));
+        };
+        next += 1;
+        let text = match conversion {
+            b'd' | b'i' => arg.to_int().to_string().into_bytes(),
+            b'u' => (arg.to_int() as u64).to_string().into_bytes(),
+            b'f' | b'F' => format!("{:.*}", precision.unwrap_or(6), arg.to_float()).into_bytes(),
+            b'x' => format!("{:x}", arg.to_int()).into_bytes(),
+            b'X' => format!("{:X}", arg.to_int()).into_bytes(),
+            b'o' => format!("{:o}", arg.to_int()).into_bytes(),
+            b'b' => format!("{:b}", arg.to_int()).into_bytes(),
+            b'c' => vec![arg.to_int() as u8],
+            b'e' => format!("{:.*e}", precision.unwrap_or(6), arg.to_float())
+                .replace('e', "e+")
+                .into_bytes(),
+            _ => {
+                let mut text = vm.stringify(arg)?.into_bytes();
+                if let Some(precision) = precision {
+                    text.truncate(precision);
+                }
+                text
+            }
+        };
+        let fill = width.saturating_sub(text.len());
+        match left {
+            true => {
+                out.extend_from_slice(&text);
+                let pad = if pad == b'0' { b' ' } else { pad };
+                out.extend(std::iter::repeat_n(pad, fill));
+            }
+            false if pad == b'0' && text.starts_with(b"-") => {
+                out.push(b'-');
+                out.extend(std::iter::repeat_n(pad, fill));
+                out.extend_from_slice(&text[1..]);
+            }
+            false => {
+                out.extend(std::iter::repeat_n(pad, fill));
+                out.extend_from_slice(&text);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn extreme(vm: &mut Vm, call: &mut NativeCall, wanted: Ordering) -> NativeResult {
+    let values: Vec<Value> = match call.args.as_slice() {
+        [Value::Array(array)] => array.values().cloned().collect(),
+        args => args.to_vec(),
+    };
+    let mut values = values.into_iter();
+    let Some(mut best) = values.next() else {
+        let name = if wanted == Ordering::Greater {
+            "max"
+        } else {
+            "min"
+        };
+        let message = format!(
+            "{}(): Argument #1 ($value) must contain at least one element",
+            name
+        );
+        return Err(vm.error("ValueError", message));
+    };
+    for value in values {
+        if value.compare(&best) == Some(wanted) {
+            best = value;
+        }
+    }
+    Ok(best)
+}
+
+fn round(_: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let value = call.arg(0).to_float();
+    let factor = 10f64.powi(call.arg(1).to_int() as i32);
+    Ok(Value::Float((value * factor).round() / factor))
+}
+
+fn intdiv(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let (a, b) = (call.arg(0).to_int(), call.arg(1).to_int());
+    if b == 0 {
+        return Err(vm.error("DivisionByZeroError", "Division by zero"));
+    }
+    match a.checked_div(b) {
+        Some(result) => Ok(Value::Int(result)),
+        None => Err(vm.error(
+            "ArithmeticError",
+            "Division of PHP_INT_MIN by -1 is not an integer",
+        )),
+    }
+}
+
+fn array_merge(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let mut merged = Array::new();
+    for index in 0..call.args.len() {
+        let array = array_arg(vm, call, index, "array_merge")?;
+        for (key, value) in array.iter() {
+            match key {
+                ArrayKey::Int(_) => merged.push(value.clone()),
+                ArrayKey::String(_) => merged.insert(key.clone(), value.clone()),
+            }
+        }
+    }
+    Ok(Value::array(merged))
+}
+
+fn array_map(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let array = array_arg(vm, call, 1, "array_map")?;
+    let callback = call.arg(0);
+    let mut mapped = Array::new();
+    for (key, value) in array.iter() {
+        let value = match callback.is_null() {
+            true => value.clone(),
+            false => vm.call_value(&callback, vec![value.clone()])?,
+        };
+        mapped.insert(key.clone(), value);
+    }
+    Ok(Value::array(mapped))
+}
+
+fn array_filter(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let array = array_arg(vm, call, 0, "array_filter")?;
+    let callback = call.arg(1);
+    let mut filtered = Array::new();
+    for (key, value) in array.iter() {
+        let keep = match callback.is_null() {
+            true => value.to_bool(),
+            false => vm.call_value(&callback, vec![value.clone()])?.to_bool(),
+        };
+        if keep {
+            filtered.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(Value::array(filtered))
+}
+
+/// The array passed by reference as the first argument, to change in place.
+fn array_ref<'a>(
+    vm: &mut Vm,
+    call: &'a mut NativeCall,
+    function: &str,
+) -> Result<&'a mut Array, Abort> {
+    match call.args.first_mut() {
+        Some(Value::Array(array)) => Ok(Rc::make_mut(array)),
+        value => {
+            let value = value.cloned().unwrap_or_default();
+            Err(type_error(vm, function, 1, "array", &value))
+        }
+    }
+}
+
+fn array_push(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let values: Vec<Value> = call.args.iter().skip(1).cloned().collect();
+    let array = array_ref(vm, call, "array_push")?;
+    for value in values {
+        array.push(value);
+    }
+    Ok(Value::Int(array.len() as i64))
+}
+
+fn array_pop(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let array = array_ref(vm, call, "array_pop")?;
+    let Some((key, _)) = array.entry_at(array.len().wrapping_sub(1)) else {
+        return Ok(Value::Null);
+    };
+    let key = key.clone();
+    let popped = array.remove(&key).unwrap_or_default();
+    // rebuilt so the next index goes back and pushing again reuses the key.
+    *array = array
+        .iter()
+        .fold(Array::new(), |mut rebuilt, (key, value)| {
+            rebuilt.insert(key.clone(), value.clone());
+            rebuilt
+        });
+    Ok(popped)
+}
+
+fn array_shift(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let array = array_ref(vm, call, "array_shift")?;
+    let Some((key, _)) = array.entry_at(0) else {
+        return Ok(Value::Null);
+    };
+    let key = key.clone();
+    let shifted = array.remove(&key).unwrap_or_default();
+    let mut renumbered = Array::new();
+    for (key, value) in array.iter() {
+        match key {
+            ArrayKey::Int(_) => renumbered.push(value.clone()),
+            ArrayKey::String(_) => renumbered.insert(key.clone(), value.clone()),
+        }
+    }
+    *array = renumbered;
+    Ok(shifted)
+}
+
+fn sort(vm: &mut Vm, call: &mut NativeCall, reverse: bool, user: bool) -> NativeResult {
+    let callback = call.arg(1);
+    let name = if user {
+        "usort"
+    } else if reverse {
+        "rsort"
+    } else {
+        "sort"
+    };
+    let mut values: Vec<Value> = array_ref(vm, call, name)?.values().cloned().collect();
+    let mut failed = None;
+    values.sort_by(|a, b| {
+        if failed.is_some() {
+            return Ordering::Equal;
+        }
+        let ordering = match user {
+            true => match vm.call_value(&callback, vec![a.clone(), b.clone()]) {
+                Ok(result) => result.to_int().cmp(&0),
+                Err(abort) => {
+                    failed = Some(abort);
+                    Ordering::Equal
+                }
+            },
+            false => a.compare(b).unwrap_or(Ordering::Equal),
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    if let Some(abort) = failed {
+        return Err(abort);
+    }
+    *array_ref(vm, call, name)? = values.into_iter().collect();
+    Ok(Value::Bool(true))
+}
+
+fn range(vm: &mut Vm, call: &mut NativeCall) -> NativeResult {
+    let (start, end) = (call.arg(0), call.arg(1));
+    let step = match call.arg(2) {
+        Value::Null => Value::Int(1),
+        step => step,
+    };
+    if let (Value::Int(start), Value::Int(end), Value::Int(step)) = (&start, &end, &step) {
+        let step = step.unsigned_abs().max(1) as usize;
+        let values: Vec<i64> = match start <= end {
+            true => (*start..=*end).step_by(step).collect(),
+            false => (*end..=*start).rev().step_by(step).collect(),
+        };
+        return Ok(Value::array(values.into_iter().map(Value::Int).collect()));
+    }
+    let (start, end, step) = (start.to_float(), end.to_float(), step.to_float().abs());
+    if step == 0.0 {
+        let message = "range(): Argument #3 ($step) cannot be 0";
+        return Err(vm.error("ValueError", message));
+    }
+    let count = ((end - start).abs() / step).floor() as usize;
+    let sign = if start <= end { 1.0 } else { -1.0 };
+    Ok(Value::array(
+        (0..=count)
+            .map(|i| Value::Float(start + sign * step * i as f64))
+            .collect(),
+    ))
+}
@@ -0,0 +1,297 @@
+//! Classes, interfaces and traits once they are declared.
+//!
+//! A class holds everything it inherits, so looking up a method or constant never walks the
+//! parents. Constants and property defaults are compiled to small functions that are only run
+//! when first needed, as they may refer to classes declared later in the script.
+
+use std::{
+    cell::{Cell as Flag, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use crate::syntax::ast::{
+    class::{has_modifier, ClassKind, Modifier},
+    keyword::Keyword,
+};
+
+use super::{
+    compile::{AttributeDecl, Func},
+    enums::EnumClass,
+    value::{Array, Value},
+    vm::{Cell, Code},
+};
+
+#[derive(Debug)]
+pub struct Class {
+    /// The fully qualified name as declared, without a leading `\`.
+    pub name: String,
+    pub kind: ClassKind,
+    pub is_abstract: bool,
+    /// Whether the class cannot be extended.
+    pub is_final: bool,
+    pub parent: Option<Rc<Class>>,
+    /// The lowercase names of every interface the class implements, directly or through its
+    /// parents and other interfaces.
+    pub interfaces: Vec<String>,
+    /// The methods by lowercase name, including the inherited ones.
+    pub methods: HashMap<String, Rc<Method>>,
+    /// The constants and enum cases by name, including the inherited ones.
+    pub consts: HashMap<String, Rc<ClassConst>>,
+    /// The instance properties in declaration order, parent properties first.
+    pub props: Vec<(String, Option<Rc<Func>>)>,
+    /// The static properties by name. An inherited static property is the same cell as in
+    /// the parent, unless the class declares it again.
+    pub static_props: HashMap<String, Rc<StaticProp>>,
+    /// The properties that are not public, instance and static, with the lowercase name of
+    /// the class declaring each.
+    pub hidden_props: HashMap<String, (Visibility, String)>,
+    /// The cases of an enum.
+    pub enumeration: Option<Rc<EnumClass>>,
+    /// The attributes of the class itself, for reflection.
+    pub attributes: Vec<Rc<AttributeDecl>>,
+    /// The evaluated property defaults, filled in on the first `new`.
+    defaults: RefCell<Option<Array>>,
+}
+
+impl Class {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: String,
+        kind: ClassKind,
+        is_abstract: bool,
+        is_final: bool,
+        parent: Option<Rc<Class>>,
+        interfaces: Vec<String>,
+        methods: HashMap<String, Rc<Method>>,
+        consts: HashMap<String, Rc<ClassConst>>,
+        props: Vec<(String, Option<Rc<Func>>)>,
+        static_props: HashMap<String, Rc<StaticProp>>,
+        hidden_props: HashMap<String, (Visibility, String)>,
+        enumeration: Option<Rc<EnumClass>>,
+        attributes: Vec<Rc<AttributeDecl>>,
+    ) -> Self {
+        Self {
+            name,
+            kind,
+            is_abstract,
+            is_final,
+            parent,
+            interfaces,
+            methods,
+            consts,
+            props,
+            static_props,
+            hidden_props,
+            enumeration,
+            attributes,
+            defaults: RefCell::new(None),
+        }
+    }
+
+    /// The lowercase name, which classes are looked up by.
+    pub fn lname(&self) -> String {
+        self.name.to_ascii_lowercase()
+    }
+
+    /// Whether the class is declared with `#[Attribute]`, so it can be used as one, as
+    /// `Attribute` itself can.
+    pub fn is_attribute(&self) -> bool {
+        self.name == "Attribute"
+            || self
+                .attributes
+                .iter()
+                .any(|attribute| attribute.name.eq_ignore_ascii_case("Attribute"))
+    }
+
+    /// Whether this class is `name`, extends it or implements it. `name` is lowercase.
+    pub fn is_subclass_of(&self, name: &str) -> bool {
+        let mut class = Some(self);
+        while let Some(current) = class {
+            if current.name.eq_ignore_ascii_case(name) {
+                return true;
+            }
+            class = current.parent.as_deref();
+        }
+        self.interfaces.iter().any(|interface| interface == name)
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<&Rc<Method>> {
+        self.methods.get(&name.to_ascii_lowercase())
+    }
+
+    pub fn defaults(&self) -> Option<Array> {
+        self.defaults.borrow().clone()
+    }
+
+    pub fn set_defaults(&self, defaults: Array) {
+        *self.defaults.borrow_mut() = Some(defaults);
+    }
+}
+
+/// A method, either compiled from the script or implemented by the runtime.
+#[derive(Debug)]
+pub struct Method {
+    pub name: String,
+    /// The lowercase name of the class that declares the method, `self::` inside it.
+    pub scope: String,
+    pub is_static: bool,
+    pub is_final: bool,
+    /// `None` for abstract methods.
+    pub body: Option<Code>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Protected,
+    Private,
+}
+
+impl Visibility {
+    /// The visibility given by the modifiers of a member, public without one.
+    pub fn of(modifiers: &[Modifier]) -> Self {
+        if has_modifier(modifiers, Keyword::Private) {
+            Self::Private
+        } else if has_modifier(modifiers, Keyword::Protected) {
+            Self::Protected
+        } else {
+            Self::Public
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Protected => "protected",
+            Self::Private => "private",
+        }
+    }
+}
+
+/// A class constant, evaluated once when it is first read.
+#[derive(Debug)]
+pub struct ClassConst {
+    /// The lowercase name of the class that declares the constant.
+    pub scope: String,
+    pub init: ConstInit,
+    pub state: RefCell<ConstState>,
+}
+
+#[derive(Debug)]
+pub enum ConstInit {
+    Expr(Rc<Func>),
+    /// An enum case, created from the `enumeration` of the class declaring it.
+    Case,
+    /// A constant of a builtin class, evaluated from the start.
+    Builtin,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConstState {
+    Pending,
+    /// Being evaluated, reading it again means the constant refers to itself.
+    Evaluating,
+    Done(Value),
+}
+
+impl ClassConst {
+    pub fn new(scope: String, init: ConstInit) -> Self {
+        Self {
+            scope,
+            init,
+            state: RefCell::new(ConstState::Pending),
+        }
+    }
+}
+
+/// A static property, its default evaluated when the property is first used.
+#[derive(Debug)]
+pub struct StaticProp {
+    pub scope: String,
+    pub init: Option<Rc<Func>>,
+    pub ready: Flag<bool>,
+    pub cell: Cell,
+}
+
+impl StaticProp {
+    pub fn new(scope: String, init: Option<Rc<Func>>) -> Self {
+        Self {
+            scope,
+            ready: Flag::new(init.is_none()),
+            init,
+            cell: Cell::default(),
+        }
+    }
+}
+
+/// The classes every script starts with, as `(name, parent, interfaces)`, parents first.
+pub(crate) const BUILTIN_CLASSES: &[(&str, Option<&str>, &[&str])] = &[
+    ("Traversable", None, &[]),
+    ("Iterator", None, &["Traversable"]),
+    ("IteratorAggregate", None, &["Traversable"]),
+    ("ArrayAccess", None, &[]),
+    ("Countable", None, &[]),
+    ("Stringable", None, &[]),
+    ("UnitEnum", None, &[]),
+    ("BackedEnum", None, &["UnitEnum"]),
+    ("Throwable", None, &["Stringable"]),
+    ("stdClass", None, &[]),
+    ("Closure", None, &[]),
+    ("Generator", None, &["Iterator"]),
+    ("Exception", None, &["Throwable"]),
+    ("ErrorException", Some("Exception"), &[]),
+    ("Error", None, &["Throwable"]),
+    ("CompileError", Some("Error"), &[]),
+    ("ParseError", Some("CompileError"), &[]),
+    ("TypeError", Some("Error"), &[]),
+    ("ArgumentCountError", Some("TypeError"), &[]),
+    ("ValueError", Some("Error"), &[]),
+    ("ArithmeticError", Some("Error"), &[]),
+    ("DivisionByZeroError", Some("ArithmeticError"), &[]),
+    ("UnhandledMatchError", Some("Error"), &[]),
+    ("LogicException", Some("Exception"), &[]),
+    ("BadFunctionCallException", Some("LogicException"), &[]),
+    (
+        "BadMethodCallException",
+        Some("BadFunctionCallException"),
+        &[],
+    ),
+    ("DomainException", Some("LogicException"), &[]),
+    ("InvalidArgumentException", Some("LogicException"), &[]),
+    ("LengthException", Some("LogicException"), &[]),
+    ("OutOfRangeException", Some("LogicException"), &[]),
+    ("RuntimeException", Some("Exception"), &[]),
+    ("OutOfBoundsException", Some("RuntimeException"), &[]),
+    ("OverflowException", Some("RuntimeException"), &[]),
+    ("RangeException", Some("RuntimeException"), &[]),
+    ("UnderflowException", Some("RuntimeException"), &[]),
+    ("UnexpectedValueException", Some("RuntimeException"), &[]),
+    ("ReflectionException", Some("Exception"), &[]),
+    ("ReflectionClass", None, &[]),
+    ("ReflectionFunction", None, &[]),
+    ("ReflectionMethod", None, &[]),
+    ("ReflectionAttribute", None, &[]),
+    ("Attribute", None, &[]),
+];
+
+/// The builtin interfaces, which cannot be instantiated.
+pub(crate) fn is_builtin_interface(name: &str) -> bool {
+    matches!(
+        name,
+        "Traversable"
+            | "Iterator"
+            | "IteratorAggregate"
+            | "ArrayAccess"
+            | "Countable"
+            | "Stringable"
+            | "UnitEnum"
+            | "BackedEnum"
+            | "Throwable"
+    )
+}
+
+/// The builtin classes which cannot be extended, as the runtime creates their objects itself.
+pub(crate) fn is_builtin_final(name: &str) -> bool {
+    matches!(name, "Closure" | "Generator" | "ReflectionAttribute")
+}
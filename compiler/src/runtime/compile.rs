@@ -0,0 +1,2013 @@
+//! Compiles a resolved script into functions of simple operations that the `vm` runs.
+//!
+//! Every function, method, closure and class constant becomes a `Func`: a list of operations
+//! over a stack of values, with local variables in numbered slots. Control flow is made of
+//! jumps, and `try` statements are recorded as regions of the code that the VM consults when
+//! something is thrown, returned or jumped out of.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::syntax::{
+    ast::{
+        attribute::AttributeGroup,
+        class::{ClassKind, ClassLike, MemberKind},
+        expr::{
+            Arg, ArrayItem, BinaryOp, CallArgs, CastKind, ClassRef, Expr, ExprKind, IncDecOp,
+            Literal, MemberName, StringPart, UnaryOp,
+        },
+        function::Param as ParamDecl,
+        keyword::Keyword,
+        name::{Ident, Name, ResolvedName},
+        reserved::{ReservedCall, ReservedIdent},
+        stmt::{Block, Catch, DeclareDirective, Script, Stmt, StmtKind},
+        types::{BuiltinType, Type, TypeHint},
+        visit::{self, Visitor},
+    },
+    diagnostic::Diagnostic,
+    lex::token::Numeric,
+    lower::lower_match,
+    source::{SourceFile, Span},
+};
+
+use super::{class::Visibility, enums::EnumClass, value::Value, vm::Cell};
+
+/// A compiled function, method, closure or the main body of a script.
+#[derive(Debug, Default)]
+pub struct Func {
+    /// The name used in messages, `foo`, `Foo::bar` or `{closure}`.
+    pub name: String,
+    pub params: Vec<Param>,
+    /// The names of the local variables by slot, the parameters come first.
+    pub locals: Vec<String>,
+    pub code: Vec<Op>,
+    /// The span of the code each operation was compiled from.
+    pub spans: Vec<Span>,
+    /// The `try` statements, inner ones before the ones enclosing them.
+    pub regions: Vec<Region>,
+    /// The slots that the variables captured by a closure are bound to, in order.
+    pub captures: Vec<u32>,
+    /// A static method or closure, which has no `$this`.
+    pub is_static: bool,
+    /// Contains `yield`, so calling it creates a `Generator` instead of running the body.
+    pub generator: bool,
+    /// Declared `async`, so calling it creates a `Generator` that `await` runs to completion.
+    pub is_async: bool,
+    /// The cells of the `static` variables, created the first time their statement runs.
+    pub statics: RefCell<HashMap<u32, Cell>>,
+    /// The declared return type.
+    pub returns: Option<Type>,
+    /// Compiled from a file with `declare(strict_types=1)`, which decides how the calls it
+    /// makes pass their arguments and how it returns its value.
+    pub strict: bool,
+    /// The attributes of a function or method, for reflection.
+    pub attributes: Vec<Rc<AttributeDecl>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub by_ref: bool,
+    pub variadic: bool,
+    /// Whether the parameter has no default value and must be passed.
+    pub required: bool,
+    pub ty: Option<Type>,
+}
+
+/// A `try` statement. The code is laid out as the `try` block, the `catch` blocks, then the
+/// `finally` block ending in `EndFinally`.
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub try_start: usize,
+    pub try_end: usize,
+    pub catches: Vec<CatchTarget>,
+    pub catch_end: usize,
+    /// The start of the `finally` block and the position of its `EndFinally`.
+    pub finally: Option<(usize, usize)>,
+    /// How many `foreach` loops enclose the statement.
+    pub iters: u32,
+}
+
+impl Region {
+    /// Whether `pc` is in the `try` block or one of the `catch` blocks.
+    pub fn guards(&self, pc: usize) -> bool {
+        (self.try_start..self.catch_end).contains(&pc)
+    }
+
+    /// Whether `pc` is in the `finally` block, before its `EndFinally`.
+    pub fn in_finally(&self, pc: usize) -> bool {
+        self.finally
+            .is_some_and(|(start, end)| (start..end).contains(&pc))
+    }
+
+    /// Whether `target` is inside the statement, so jumping there does not leave it.
+    pub fn contains(&self, target: usize) -> bool {
+        let end = self.finally.map_or(self.catch_end, |(_, end)| end + 1);
+        (self.try_start..end).contains(&target)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchTarget {
+    /// The lowercase fully qualified names of the caught classes.
+    pub types: Vec<String>,
+    pub slot: Option<u32>,
+    pub start: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Op {
+    Const(Value),
+    Pop,
+    Dup,
+    Load(u32),
+    /// Reads the variable named by the value on the stack, `$$a`.
+    LoadDyn,
+    LoadThis,
+    /// Assigns to a place, its operands and then the value are on the stack. Pushes the
+    /// result of the assignment.
+    Store(Box<Place>, AssignKind),
+    /// `$a = &$b`, makes the first slot share the cell of the second.
+    BindRef(u32, u32),
+    Unset(Box<Place>),
+    FetchDim,
+    /// `$a->b`, the name is on the stack when it is not known.
+    FetchProp(Option<String>),
+    FetchStaticProp(ClassSpec, String),
+    FetchClassConst(ClassSpec, String),
+    /// `static::class` and the like, a name only known at runtime.
+    ClassName(ClassSpec),
+    FetchConst(QualifiedName),
+    DeclareConst(String),
+    NewArray,
+    ArrayPush,
+    ArrayInsert,
+    /// `...$a` in an array or argument list.
+    ArraySpread,
+    /// Concatenates the strings of the given number of values.
+    Interpolate(u32),
+    Binary(BinaryOp),
+    Unary(UnaryOp),
+    Cast(CastKind),
+    /// Whether the value is not `null`.
+    IsSet,
+    InstanceOf(ClassSpec),
+    Clone,
+    Echo,
+    Exit,
+    Jump(usize),
+    JumpIfFalse(usize),
+    JumpIfTrue(usize),
+    JumpIfNotNull(usize),
+    /// Jumps when the value is `null`, leaving it on the stack, for `?->`.
+    JumpIfNull(usize),
+    /// Jumps when the parameter was passed, over the code computing its default.
+    JumpIfArg(u32, usize),
+    Call(Box<Call>),
+    New(ClassSpec, Args),
+    /// Creates a closure, binding the given slots of the current function by value or by
+    /// reference.
+    MakeClosure(Rc<Func>, Vec<(u32, bool)>),
+    DeclareFunction(Rc<Func>),
+    /// Declares a class, `true` for a declaration hoisted to the start of the script, which is
+    /// skipped when the classes it needs do not exist yet.
+    DeclareClass(Rc<ClassDecl>, bool),
+    Return,
+    Throw,
+    /// `break`, `continue` or `goto`, to a target with the given number of open `foreach`
+    /// loops, running the `finally` blocks on the way.
+    Leave(usize, u32),
+    /// Runs a `finally` block at the target after its `try` or `catch` block completed.
+    EnterFinally(usize),
+    /// The end of a `finally` block, which resumes whatever was pending when it started.
+    EndFinally,
+    /// Starts a `foreach` over the value on the stack. By reference it iterates the array in
+    /// the first slot, writing the variable in the second slot back into it.
+    IterInit(Option<(u32, u32)>),
+    /// Pushes the next value, and the key when asked to, or jumps when done.
+    IterNext(usize, bool),
+    IterPop,
+    /// Binds a `static` variable, jumping over its initializer when it already exists.
+    BindStatic(u32, usize),
+    /// Binds a local slot to the global variable of the same name.
+    BindGlobal(u32),
+    /// Suspends the generator with the value on the stack, and the key under it when set.
+    /// Pushes the value sent in when it resumes.
+    Yield(bool),
+    /// Delegates to the generator, `Traversable` or array on the stack until it is done,
+    /// then pushes its return value.
+    YieldFrom,
+    /// Runs the `Generator` on the stack to completion and pushes its return value, other
+    /// values are left as they are.
+    Await,
+    /// Counts a statement under `declare(ticks=N)`, running the tick functions every N.
+    Tick(u32),
+}
+
+/// A function or constant name, with the global name to fall back to for an unqualified name
+/// inside a namespace.
+#[derive(Debug, Clone)]
+pub struct QualifiedName {
+    pub name: String,
+    pub fallback: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub callee: Callee,
+    pub args: Args,
+}
+
+#[derive(Debug, Clone)]
+pub enum Callee {
+    Function(QualifiedName),
+    /// A closure or callable value, on the stack before the arguments.
+    Value,
+    /// A method of the object on the stack, followed by the name when it is not known.
+    Method(Option<String>),
+    Static(ClassSpec, Option<String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Args {
+    /// The given number of values, with the slot of each argument that is a plain variable
+    /// so it can be passed by reference.
+    Stack(u32, Vec<Option<u32>>),
+    /// A single array of arguments, with string keys for named arguments.
+    Array,
+    /// `foo(...)`, which creates a closure instead of calling.
+    Callable,
+}
+
+/// The class of a static access.
+#[derive(Debug, Clone)]
+pub enum ClassSpec {
+    Named(String),
+    /// `self`
+    Current,
+    Parent,
+    Static,
+    /// A class name or object on the stack.
+    Dynamic,
+}
+
+/// Something that can be assigned to, with the keys of any `[...]` after it.
+#[derive(Debug, Clone)]
+pub struct Place {
+    pub root: Root,
+    /// Whether each `[...]` has a key on the stack, `[]` appends.
+    pub dims: Vec<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Root {
+    Local(u32),
+    /// A variable named by the value on the stack.
+    Dyn,
+    /// A property of the object on the stack, followed by its name when not known.
+    Prop(Option<String>),
+    StaticProp(ClassSpec, String),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AssignKind {
+    Set,
+    Op(BinaryOp),
+    PreInc,
+    PreDec,
+    PostInc,
+    PostDec,
+}
+
+/// An attribute kept for reflection. Its arguments are only evaluated when reflection asks
+/// for them, as `ReflectionAttribute::getArguments()` and `newInstance()` do.
+#[derive(Debug)]
+pub struct AttributeDecl {
+    /// The fully qualified name of the attribute class, without a leading `\`.
+    pub name: String,
+    /// Builds the array of the arguments, the named ones by name.
+    pub args: Rc<Func>,
+    /// Creates the attribute, `new Name(...)` with the same arguments.
+    pub instance: Rc<Func>,
+}
+
+/// A class, interface, trait or enum ready to be declared.
+#[derive(Debug)]
+pub struct ClassDecl {
+    pub name: String,
+    pub kind: ClassKind,
+    pub is_abstract: bool,
+    pub is_final: bool,
+    pub parent: Option<String>,
+    pub interfaces: Vec<String>,
+    pub traits: Vec<String>,
+    pub methods: Vec<MethodDecl>,
+    pub consts: Vec<(String, ConstDecl)>,
+    pub props: Vec<PropDecl>,
+    /// The cases of an enum, with their values evaluated as it is compiled.
+    pub enumeration: Option<Rc<EnumClass>>,
+    pub attributes: Vec<Rc<AttributeDecl>>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct MethodDecl {
+    pub name: String,
+    pub is_static: bool,
+    pub is_final: bool,
+    /// `None` for abstract and interface methods.
+    pub func: Option<Rc<Func>>,
+}
+
+#[derive(Debug)]
+pub enum ConstDecl {
+    Expr(Rc<Func>),
+    /// An enum case, found in the `enumeration` of the class.
+    Case,
+}
+
+#[derive(Debug)]
+pub struct PropDecl {
+    pub name: String,
+    pub is_static: bool,
+    pub visibility: Visibility,
+    pub default: Option<Rc<Func>>,
+}
+
+/// Compiles a script whose names are resolved into its main function.
+pub fn compile(script: &Script, file: &SourceFile) -> Result<Rc<Func>, Vec<Diagnostic>> {
+    let mut compiler = Compiler {
+        file,
+        namespace: Vec::new(),
+        class: None,
+        function: String::new(),
+        b: Builder::new("{main}"),
+        diagnostics: Vec::new(),
+        anonymous: 0,
+        at: script.span,
+        top: true,
+        hoisted: HashMap::new(),
+        strict: false,
+        ticks: 0,
+    };
+    for stmt in &script.stmts {
+        match &stmt.kind {
+            StmtKind::Declare { directives, .. } => {
+                if let Some(strict) = directive(directives, "strict_types") {
+                    compiler.strict = strict == 1;
+                }
+            }
+            _ => break,
+        }
+    }
+    compiler.b.func.strict = compiler.strict;
+    compiler.hoist(&script.stmts);
+    compiler.ticks = 0;
+    compiler.stmts(&script.stmts);
+    compiler.emit(Op::Const(Value::Int(1)));
+    compiler.emit(Op::Return);
+    let main = compiler.finish_builder();
+    match compiler.diagnostics.is_empty() {
+        true => Ok(Rc::new(main)),
+        false => Err(compiler.diagnostics),
+    }
+}
+
+/// A function being compiled.
+struct Builder {
+    func: Func,
+    slots: HashMap<String, u32>,
+    loops: Vec<Loop>,
+    /// How many `foreach` loops enclose the current statement.
+    iters: u32,
+    labels: HashMap<String, (usize, u32)>,
+    gotos: Vec<(usize, Ident)>,
+}
+
+/// A loop or `switch`, with the `break` and `continue` jumps to patch once its end is known.
+struct Loop {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+    /// The open `foreach` loops outside and inside of it.
+    outer_iters: u32,
+    inner_iters: u32,
+}
+
+impl Builder {
+    fn new(name: impl Into<String>) -> Self {
+        Self {
+            func: Func {
+                name: name.into(),
+                ..Func::default()
+            },
+            slots: HashMap::new(),
+            loops: Vec::new(),
+            iters: 0,
+            labels: HashMap::new(),
+            gotos: Vec::new(),
+        }
+    }
+}
+
+struct ClassContext {
+    name: String,
+    kind: ClassKind,
+}
+
+struct Compiler<'a> {
+    file: &'a SourceFile,
+    namespace: Vec<String>,
+    class: Option<ClassContext>,
+    /// The name of the current function, for `__FUNCTION__`.
+    function: String,
+    b: Builder,
+    diagnostics: Vec<Diagnostic>,
+    anonymous: usize,
+    /// The span of the code being compiled, recorded for each operation.
+    at: Span,
+    /// Whether the statements being compiled are directly in the script or a namespace.
+    top: bool,
+    /// The classes declared at the top level by the position of their statement, compiled
+    /// once when they are hoisted.
+    hoisted: HashMap<usize, Rc<ClassDecl>>,
+    /// `declare(strict_types=1)` at the start of the script.
+    strict: bool,
+    /// The `ticks` declared for the statements being compiled, 0 when there are none.
+    ticks: u32,
+}
+
+impl Compiler<'_> {
+    fn emit(&mut self, op: Op) -> usize {
+        self.b.func.code.push(op);
+        self.b.func.spans.push(self.at);
+        self.b.func.code.len() - 1
+    }
+
+    fn pc(&self) -> usize {
+        self.b.func.code.len()
+    }
+
+    /// Points the jump at `at` to the current position.
+    fn patch(&mut self, at: usize) {
+        let target = self.pc();
+        self.patch_to(at, target);
+    }
+
+    fn patch_to(&mut self, at: usize, target: usize) {
+        match &mut self.b.func.code[at] {
+            Op::Jump(t)
+            | Op::JumpIfFalse(t)
+            | Op::JumpIfTrue(t)
+            | Op::JumpIfNotNull(t)
+            | Op::JumpIfNull(t)
+            | Op::JumpIfArg(_, t)
+            | Op::Leave(t, _)
+            | Op::EnterFinally(t)
+            | Op::IterNext(t, _)
+            | Op::BindStatic(_, t) => *t = target,
+            op => unreachable!("{:?} is not a jump", op),
+        }
+    }
+
+    fn error(&mut self, message: impl Into<String>, span: Span) {
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+
+    /// The slot of a local variable, created on first use.
+    fn slot(&mut self, name: &str) -> u32 {
+        if let Some(slot) = self.b.slots.get(name) {
+            return *slot;
+        }
+        let slot = self.b.func.locals.len() as u32;
+        self.b.func.locals.push(name.to_string());
+        self.b.slots.insert(name.to_string(), slot);
+        slot
+    }
+
+    /// Compiles a function into a builder of its own, `body` emits its code.
+    fn function(
+        &mut self,
+        name: String,
+        params: &[ParamDecl],
+        returns: Option<&TypeHint>,
+        body: impl FnOnce(&mut Self),
+    ) -> Func {
+        let outer = std::mem::replace(&mut self.b, Builder::new(name));
+        let outer_function = self.function.clone();
+        self.b.func.strict = self.strict;
+        self.b.func.returns = returns.map(runtime_type);
+        for param in params {
+            self.slot(&param.name.name);
+            let mut ty = param.ty.as_ref().map(runtime_type);
+            // `int $a = null` is an implicitly nullable `?int`.
+            if let (Some(inner), Some(default)) = (&ty, &param.default) {
+                if matches!(default.kind, ExprKind::Literal(Literal::Null)) && !inner.allows_null()
+                {
+                    ty = Some(Type::Union(vec![
+                        inner.clone(),
+                        Type::Builtin(BuiltinType::Null),
+                    ]));
+                }
+            }
+            self.b.func.params.push(Param {
+                name: param.name.name.clone(),
+                by_ref: param.by_ref,
+                variadic: param.variadic,
+                required: param.default.is_none() && !param.variadic,
+                ty,
+            });
+        }
+        for (i, param) in params.iter().enumerate() {
+            if let Some(default) = &param.default {
+                let skip = self.emit(Op::JumpIfArg(i as u32, 0));
+                self.expr(default);
+                self.emit(Op::Store(Box::new(local(i as u32)), AssignKind::Set));
+                self.emit(Op::Pop);
+                self.patch(skip);
+            }
+        }
+        body(self);
+        if let (true, Some(returns)) = (self.b.func.generator, returns) {
+            let ty = runtime_type(returns);
+            let iterable = match &ty {
+                Type::Builtin(builtin) => matches!(
+                    builtin,
+                    BuiltinType::Iterable | BuiltinType::Object | BuiltinType::Mixed
+                ),
+                Type::Class(name) => ["Generator", "Iterator", "Traversable"]
+                    .iter()
+                    .any(|class| name.eq_ignore_ascii_case(class)),
+                Type::Union(_) | Type::Intersection(_) => false,
+            };
+            if !iterable {
+                let message = format!(
+                    "Generator return type must be a supertype of Generator, {} given",
+                    ty
+                );
+                self.error(message, returns.span);
+            }
+        }
+        let func = self.finish_builder();
+        self.b = outer;
+        self.function = outer_function;
+        func
+    }
+
+    /// Resolves the `goto`s of the current function and takes its code.
+    fn finish_builder(&mut self) -> Func {
+        for (at, label) in std::mem::take(&mut self.b.gotos) {
+            match self.b.labels.get(&label.name) {
+                Some(&(target, iters)) => self.b.func.code[at] = Op::Leave(target, iters),
+                None => self.error(
+                    format!("'goto' to undefined label '{}'", label.name),
+                    label.span,
+                ),
+            }
+        }
+        std::mem::take(&mut self.b.func)
+    }
+
+    /// Declares the functions and classes at the top level of the script before it runs, as
+    /// PHP does, so they can be used before their declaration.
+    fn hoist(&mut self, stmts: &[Stmt]) {
+        let namespace = self.namespace.clone();
+        for stmt in stmts {
+            self.at = stmt.span;
+            match &stmt.kind {
+                StmtKind::Namespace { name, body } => {
+                    self.namespace = name.as_ref().map_or_else(Vec::new, |n| n.parts.clone());
+                    if let Some(body) = body {
+                        self.hoist(&body.stmts);
+                        self.namespace = namespace.clone();
+                    }
+                }
+                StmtKind::Function(function) => {
+                    let name = self.qualify(&function.name.name);
+                    let returns = function.return_type.as_ref();
+                    let func = self.function(name.clone(), &function.params, returns, |c| {
+                        c.function = name;
+                        c.b.func.is_async = function.is_async;
+                        c.b.func.attributes = c.attributes(&function.attributes);
+                        if let Some(body) = &function.body {
+                            c.block(body);
+                        }
+                        c.emit(Op::Const(Value::Null));
+                        c.emit(Op::Return);
+                    });
+                    self.emit(Op::DeclareFunction(Rc::new(func)));
+                }
+                StmtKind::ClassLike(class) => {
+                    let decl = self.class_decl(class);
+                    self.hoisted.insert(stmt.span.start, decl.clone());
+                    self.emit(Op::DeclareClass(decl, true));
+                }
+                // the functions after `declare(ticks=N);` tick as well.
+                StmtKind::Declare {
+                    directives,
+                    body: None,
+                } => {
+                    if let Some(ticks) = directive(directives, "ticks") {
+                        self.ticks = ticks.max(0) as u32;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.namespace = namespace;
+    }
+
+    fn qualify(&self, name: &str) -> String {
+        let mut parts = self.namespace.clone();
+        parts.push(name.to_string());
+        parts.join("\\")
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+            if self.ticks > 0 && !matches!(stmt.kind, StmtKind::Block(_) | StmtKind::Label(_)) {
+                self.emit(Op::Tick(self.ticks));
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        let top = std::mem::replace(&mut self.top, false);
+        self.stmts(&block.stmts);
+        self.top = top;
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        self.at = stmt.span;
+        match &stmt.kind {
+            StmtKind::Expr(expr) => {
+                self.expr(expr);
+                self.emit(Op::Pop);
+            }
+            StmtKind::Echo(exprs) => {
+                for expr in exprs {
+                    self.expr(expr);
+                    self.emit(Op::Echo);
+                }
+            }
+            StmtKind::InlineHtml(html) => {
+                self.emit(Op::Const(Value::string(html.as_str())));
+                self.emit(Op::Echo);
+            }
+            StmtKind::Return(value) => {
+                match value {
+                    Some(value) => self.expr(value),
+                    None => {
+                        self.emit(Op::Const(Value::Null));
+                    }
+                }
+                self.emit(Op::Return);
+            }
+            StmtKind::If {
+                cond,
+                then,
+                elseifs,
+                otherwise,
+            } => {
+                let mut ends = Vec::new();
+                let branches = std::iter::once((cond, then))
+                    .chain(elseifs.iter().map(|elseif| (&elseif.cond, &elseif.body)));
+                for (cond, body) in branches {
+                    self.expr(cond);
+                    let next = self.emit(Op::JumpIfFalse(0));
+                    self.block(body);
+                    ends.push(self.emit(Op::Jump(0)));
+                    self.patch(next);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.block(otherwise);
+                }
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+            StmtKind::While { cond, body } => {
+                let start = self.pc();
+                self.expr(cond);
+                let exit = self.emit(Op::JumpIfFalse(0));
+                self.loop_body(body, start);
+                self.emit(Op::Jump(start));
+                self.patch(exit);
+                self.end_loop(None);
+            }
+            StmtKind::DoWhile { body, cond } => {
+                let start = self.pc();
+                self.enter_loop();
+                self.block(body);
+                let cont = self.pc();
+                self.expr(cond);
+                self.emit(Op::JumpIfTrue(start));
+                self.end_loop(Some(cont));
+            }
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            } => {
+                for expr in init {
+                    self.expr(expr);
+                    self.emit(Op::Pop);
+                }
+                let start = self.pc();
+                let exit = match cond.split_last() {
+                    Some((last, rest)) => {
+                        for expr in rest {
+                            self.expr(expr);
+                            self.emit(Op::Pop);
+                        }
+                        self.expr(last);
+                        Some(self.emit(Op::JumpIfFalse(0)))
+                    }
+                    None => None,
+                };
+                self.enter_loop();
+                self.block(body);
+                let cont = self.pc();
+                for expr in step {
+                    self.expr(expr);
+                    self.emit(Op::Pop);
+                }
+                self.emit(Op::Jump(start));
+                if let Some(exit) = exit {
+                    self.patch(exit);
+                }
+                self.end_loop(Some(cont));
+            }
+            StmtKind::Foreach {
+                subject,
+                key,
+                value,
+                by_ref,
+                body,
+            } => self.foreach(subject, key.as_deref(), value, *by_ref, body),
+            StmtKind::Switch { subject, cases } => {
+                let temp = self.slot(&format!("switch@{}", stmt.span.start));
+                self.expr(subject);
+                self.emit(Op::Store(Box::new(local(temp)), AssignKind::Set));
+                self.emit(Op::Pop);
+                let mut jumps = Vec::new();
+                for case in cases {
+                    if let Some(cond) = &case.cond {
+                        self.emit(Op::Load(temp));
+                        self.expr(cond);
+                        self.emit(Op::Binary(BinaryOp::Equal));
+                        jumps.push(Some(self.emit(Op::JumpIfTrue(0))));
+                    } else {
+                        jumps.push(None);
+                    }
+                }
+                let default = self.emit(Op::Jump(0));
+                self.enter_loop();
+                let mut default_target = None;
+                for (case, jump) in cases.iter().zip(jumps) {
+                    match jump {
+                        Some(jump) => self.patch(jump),
+                        None => default_target = Some(self.pc()),
+                    }
+                    self.stmts(&case.body);
+                }
+                match default_target {
+                    Some(target) => self.patch_to(default, target),
+                    None => self.patch(default),
+                }
+                self.end_loop(None);
+            }
+            StmtKind::Break(levels) | StmtKind::Continue(levels) => {
+                let levels = levels.unwrap_or(1) as usize;
+                let is_break = matches!(stmt.kind, StmtKind::Break(_));
+                let Some(index) = self.b.loops.len().checked_sub(levels) else {
+                    // the parser already reported it.
+                    return;
+                };
+                let target = &self.b.loops[index];
+                let iters = match is_break {
+                    true => target.outer_iters,
+                    false => target.inner_iters,
+                };
+                let at = self.emit(Op::Leave(0, iters));
+                let target = &mut self.b.loops[index];
+                match is_break {
+                    true => target.breaks.push(at),
+                    false => target.continues.push(at),
+                }
+            }
+            StmtKind::Global(vars) => {
+                for var in vars {
+                    match &var.kind {
+                        ExprKind::Variable(name) => {
+                            let slot = self.slot(name);
+                            self.emit(Op::BindGlobal(slot));
+                        }
+                        _ => self.error("only simple variables can be made global", var.span),
+                    }
+                }
+            }
+            StmtKind::Static(vars) => {
+                for var in vars {
+                    let slot = self.slot(&var.name.name);
+                    let bind = self.emit(Op::BindStatic(slot, 0));
+                    if let Some(default) = &var.default {
+                        self.expr(default);
+                        self.emit(Op::Store(Box::new(local(slot)), AssignKind::Set));
+                        self.emit(Op::Pop);
+                    }
+                    self.patch(bind);
+                }
+            }
+            StmtKind::Unset(vars) => {
+                for var in vars {
+                    if let Some(place) = self.place(var) {
+                        self.emit(Op::Unset(Box::new(place)));
+                    }
+                }
+            }
+            StmtKind::Declare { directives, body } => {
+                let ticks = self.ticks;
+                if let Some(n) = directive(directives, "ticks") {
+                    self.ticks = n.max(0) as u32;
+                }
+                if let Some(body) = body {
+                    self.block(body);
+                    self.ticks = ticks;
+                }
+            }
+            StmtKind::Goto(label) => {
+                let at = self.emit(Op::Leave(0, 0));
+                self.b.gotos.push((at, label.clone()));
+            }
+            StmtKind::Label(label) => {
+                let target = (self.pc(), self.b.iters);
+                self.b.labels.insert(label.name.clone(), target);
+            }
+            StmtKind::Namespace { name, body } => {
+                self.namespace = name.as_ref().map_or_else(Vec::new, |n| n.parts.clone());
+                if let Some(body) = body {
+                    self.stmts(&body.stmts);
+                    self.namespace.clear();
+                }
+            }
+            StmtKind::Use { .. } | StmtKind::GroupUse { .. } | StmtKind::Nop => {}
+            StmtKind::Const(items) => {
+                for item in items {
+                    self.expr(&item.value);
+                    let name = self.qualify(&item.name.name);
+                    self.emit(Op::DeclareConst(name));
+                }
+            }
+            StmtKind::Function(function) => {
+                // top level functions are hoisted, the others are declared when reached.
+                if !self.top {
+                    let name = self.qualify(&function.name.name);
+                    let returns = function.return_type.as_ref();
+                    let func = self.function(name.clone(), &function.params, returns, |c| {
+                        c.function = name;
+                        c.b.func.is_async = function.is_async;
+                        c.b.func.attributes = c.attributes(&function.attributes);
+                        if let Some(body) = &function.body {
+                            c.block(body);
+                        }
+                        c.emit(Op::Const(Value::Null));
+                        c.emit(Op::Return);
+                    });
+                    self.emit(Op::DeclareFunction(Rc::new(func)));
+                }
+            }
+            StmtKind::ClassLike(class) => {
+                let decl = match self.hoisted.remove(&stmt.span.start) {
+                    Some(decl) => decl,
+                    None => self.class_decl(class),
+                };
+                self.emit(Op::DeclareClass(decl, false));
+            }
+            StmtKind::Try {
+                body,
+                catches,
+                finally,
+            } => self.try_stmt(body, catches, finally.as_ref()),
+            StmtKind::Block(block) => self.block(block),
+            StmtKind::HaltCompiler(_) => {
+                self.emit(Op::Const(Value::Null));
+                self.emit(Op::Exit);
+            }
+            StmtKind::Error => {}
+        }
+    }
+
+    fn enter_loop(&mut self) {
+        self.b.loops.push(Loop {
+            breaks: Vec::new(),
+            continues: Vec::new(),
+            outer_iters: self.b.iters,
+            inner_iters: self.b.iters,
+        });
+    }
+
+    fn loop_body(&mut self, body: &Block, cont: usize) {
+        self.enter_loop();
+        self.block(body);
+        let target = self.b.loops.last_mut().expect("just entered");
+        for at in std::mem::take(&mut target.continues) {
+            self.patch_to(at, cont);
+        }
+    }
+
+    /// Patches the jumps of the innermost loop, `continue` goes to `cont` or to the end.
+    fn end_loop(&mut self, cont: Option<usize>) {
+        let target = self.b.loops.pop().expect("inside a loop");
+        let end = self.pc();
+        for at in target.breaks {
+            self.patch_to(at, end);
+        }
+        for at in target.continues {
+            self.patch_to(at, cont.unwrap_or(end));
+        }
+    }
+
+    fn foreach(
+        &mut self,
+        subject: &Expr,
+        key: Option<&Expr>,
+        value: &Expr,
+        by_ref: bool,
+        body: &Block,
+    ) {
+        let by_ref = match (by_ref, &subject.kind, &value.kind) {
+            (false, ..) => None,
+            (true, ExprKind::Variable(array), ExprKind::Variable(var)) => {
+                Some((self.slot(array), self.slot(var)))
+            }
+            (true, ..) => {
+                self.error(
+                    "iterating by reference is only supported over a variable, into a variable",
+                    value.span,
+                );
+                None
+            }
+        };
+        self.expr(subject);
+        self.emit(Op::IterInit(by_ref));
+        let start = self.pc();
+        let next = self.emit(Op::IterNext(0, key.is_some()));
+        if let Some(key) = key {
+            self.assign_from_stack(key);
+        }
+        if by_ref.is_some() {
+            self.emit(Op::Pop);
+        } else {
+            self.assign_from_stack(value);
+        }
+
+        self.b.iters += 1;
+        self.enter_loop();
+        let target = self.b.loops.last_mut().expect("just entered");
+        target.outer_iters -= 1;
+        target.inner_iters = self.b.iters;
+        self.block(body);
+        self.emit(Op::Jump(start));
+        self.patch(next);
+        self.emit(Op::IterPop);
+        self.b.iters -= 1;
+        self.end_loop(Some(start));
+    }
+
+    fn try_stmt(&mut self, body: &Block, catches: &[Catch], finally: Option<&Block>) {
+        let try_start = self.pc();
+        self.block(body);
+        let mut exits = vec![self.emit(match finally {
+            Some(_) => Op::EnterFinally(0),
+            None => Op::Jump(0),
+        })];
+        let try_end = self.pc();
+
+        let mut targets = Vec::new();
+        for catch in catches {
+            self.at = catch.span;
+            let start = self.pc();
+            let slot = catch.var.as_ref().map(|var| self.slot(&var.name));
+            self.block(&catch.body);
+            exits.push(self.emit(match finally {
+                Some(_) => Op::EnterFinally(0),
+                None => Op::Jump(0),
+            }));
+            targets.push(CatchTarget {
+                types: catch
+                    .types
+                    .iter()
+                    .map(|name| class_name(name).to_ascii_lowercase())
+                    .collect(),
+                slot,
+                start,
+            });
+        }
+        let catch_end = self.pc();
+
+        let finally = finally.map(|finally| {
+            let start = self.pc();
+            for exit in exits.drain(..) {
+                self.patch(exit);
+            }
+            self.block(finally);
+            (start, self.emit(Op::EndFinally))
+        });
+        for exit in exits {
+            self.patch(exit);
+        }
+        self.b.func.regions.push(Region {
+            try_start,
+            try_end,
+            catches: targets,
+            catch_end,
+            finally,
+            iters: self.b.iters,
+        });
+    }
+
+    /// Assigns the value on the stack to a target and pops it.
+    fn assign_from_stack(&mut self, target: &Expr) {
+        match &target.kind {
+            ExprKind::Variable(name) if name != "this" => {
+                let slot = self.slot(name);
+                self.emit(Op::Store(Box::new(local(slot)), AssignKind::Set));
+            }
+            ExprKind::List { items, .. } => self.destructure(items),
+            _ => {
+                // the operands of the place come before the value, so it waits in a temporary.
+                let temp = self.slot(&format!("assign@{}", target.span.start));
+                self.emit(Op::Store(Box::new(local(temp)), AssignKind::Set));
+                self.emit(Op::Pop);
+                if let Some(place) = self.place(target) {
+                    self.emit(Op::Load(temp));
+                    self.emit(Op::Store(Box::new(place), AssignKind::Set));
+                }
+            }
+        }
+        self.emit(Op::Pop);
+    }
+
+    /// Assigns the elements of the array on the stack to the items of a `list()`, leaving
+    /// the array on the stack.
+    fn destructure(&mut self, items: &[Option<ArrayItem>]) {
+        let mut index = 0;
+        for item in items {
+            let Some(item) = item else {
+                index += 1;
+                continue;
+            };
+            if item.by_ref {
+                self.error("destructuring by reference is not supported", item.span);
+            }
+            self.emit(Op::Dup);
+            match &item.key {
+                Some(key) => self.expr(key),
+                None => {
+                    self.emit(Op::Const(Value::Int(index)));
+                    index += 1;
+                }
+            }
+            self.emit(Op::FetchDim);
+            self.assign_from_stack(&item.value);
+        }
+    }
+
+    /// Compiles the operands of an assignable expression.
+    fn place(&mut self, expr: &Expr) -> Option<Place> {
+        let root = match &expr.kind {
+            ExprKind::Variable(name) if name == "this" => {
+                self.error("cannot re-assign $this", expr.span);
+                return None;
+            }
+            ExprKind::Variable(name) => Root::Local(self.slot(name)),
+            ExprKind::VariableVariable(name) => {
+                self.expr(name);
+                Root::Dyn
+            }
+            ExprKind::ArrayDim { array, index } => {
+                let mut place = self.place(array)?;
+                if let Some(index) = index {
+                    self.expr(index);
+                }
+                place.dims.push(index.is_some());
+                return Some(place);
+            }
+            ExprKind::PropertyFetch {
+                object, property, ..
+            } => {
+                self.expr(object);
+                Root::Prop(self.member_name(property))
+            }
+            ExprKind::StaticPropertyFetch { class, property } => {
+                let class = self.class_spec(class);
+                match &property.kind {
+                    ExprKind::Variable(name) => Root::StaticProp(class, name.clone()),
+                    _ => {
+                        self.error("dynamic static property names are not supported", expr.span);
+                        return None;
+                    }
+                }
+            }
+            _ => {
+                self.error("cannot assign to this expression", expr.span);
+                return None;
+            }
+        };
+        Some(Place {
+            root,
+            dims: Vec::new(),
+        })
+    }
+
+    /// A member name known at compile time, or `None` after compiling the expression giving
+    /// it.
+    fn member_name(&mut self, member: &MemberName) -> Option<String> {
+        match member {
+            MemberName::Ident(ident) => Some(ident.name.clone()),
+            MemberName::Expr(expr) => {
+                self.expr(expr);
+                None
+            }
+        }
+    }
+
+    fn class_spec(&mut self, class: &ClassRef) -> ClassSpec {
+        match class {
+            ClassRef::Name(name) if name.is_special_class() => {
+                match name.last().to_ascii_lowercase().as_str() {
+                    "self" => ClassSpec::Current,
+                    "parent" => ClassSpec::Parent,
+                    _ => ClassSpec::Static,
+                }
+            }
+            ClassRef::Name(name) => ClassSpec::Named(class_name(name)),
+            ClassRef::Expr(expr) => {
+                self.expr(expr);
+                ClassSpec::Dynamic
+            }
+            ClassRef::Anonymous(class) => {
+                let decl = self.class_decl(class);
+                let name = decl.name.clone();
+                self.emit(Op::DeclareClass(decl, false));
+                ClassSpec::Named(name)
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        let outer = std::mem::replace(&mut self.at, expr.span);
+        self.expr_kind(expr);
+        self.at = outer;
+    }
+
+    fn expr_kind(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Literal(literal) => {
+                let value = match literal {
+                    Literal::Null => Value::Null,
+                    Literal::Bool(b) => Value::Bool(*b),
+                    Literal::Numeric(Numeric::Int(i)) => Value::Int(*i),
+                    Literal::Numeric(Numeric::Float(f)) => Value::Float(*f),
+                    Literal::Numeric(Numeric::LInt(i)) => match i64::try_from(*i) {
+                        Ok(i) => Value::Int(i),
+                        Err(_) => Value::Float(*i as f64),
+                    },
+                    Literal::String(s) => Value::string(s.as_slice()),
+                };
+                self.emit(Op::Const(value));
+            }
+            ExprKind::Interpolated { parts, .. } => self.interpolated(parts),
+            ExprKind::ShellExec(_) => self.error("shell execution is not supported", expr.span),
+            ExprKind::Variable(name) if name == "this" => {
+                self.emit(Op::LoadThis);
+            }
+            ExprKind::Variable(name) => {
+                let slot = self.slot(name);
+                self.emit(Op::Load(slot));
+            }
+            ExprKind::VariableVariable(name) => {
+                self.expr(name);
+                self.emit(Op::LoadDyn);
+            }
+            ExprKind::Array { items, .. } => {
+                self.emit(Op::NewArray);
+                for item in items {
+                    self.array_item(item);
+                }
+            }
+            ExprKind::List { .. } => {
+                self.error("cannot use list() outside of an assignment", expr.span)
+            }
+            ExprKind::ConstFetch(name) => {
+                let name = qualified_name(name);
+                self.emit(Op::FetchConst(name));
+            }
+            ExprKind::ReservedConst(ident) => {
+                let value = self.reserved_const(*ident, expr.span);
+                self.emit(Op::Const(value));
+            }
+            ExprKind::ClassConstFetch { class, constant } => match constant {
+                MemberName::Ident(constant) if constant.name.eq_ignore_ascii_case("class") => {
+                    match self.class_spec(class) {
+                        ClassSpec::Named(name) => {
+                            self.emit(Op::Const(Value::string(name)));
+                        }
+                        ClassSpec::Current if self.class_is_known() => {
+                            let name = self.class.as_ref().map(|c| c.name.clone());
+                            self.emit(Op::Const(Value::string(name.unwrap_or_default())));
+                        }
+                        spec => {
+                            self.emit(Op::ClassName(spec));
+                        }
+                    }
+                }
+                MemberName::Ident(constant) => {
+                    let class = self.class_spec(class);
+                    self.emit(Op::FetchClassConst(class, constant.name.clone()));
+                }
+                MemberName::Expr(_) => {
+                    self.error("dynamic class constant names are not supported", expr.span)
+                }
+            },
+            ExprKind::StaticPropertyFetch { .. } => {
+                if let Some(place) = self.place(expr) {
+                    let Root::StaticProp(class, name) = place.root else {
+                        unreachable!("a static property is a static property place");
+                    };
+                    self.emit(Op::FetchStaticProp(class, name));
+                }
+            }
+            ExprKind::PropertyFetch {
+                object,
+                property,
+                nullsafe,
+            } => {
+                self.expr(object);
+                let skip = nullsafe.then(|| self.emit(Op::JumpIfNull(0)));
+                let name = self.member_name(property);
+                self.emit(Op::FetchProp(name));
+                if let Some(skip) = skip {
+                    self.patch(skip);
+                }
+            }
+            ExprKind::ArrayDim { array, index } => match index {
+                Some(index) => {
+                    self.expr(array);
+                    self.expr(index);
+                    self.emit(Op::FetchDim);
+                }
+                None => self.error("cannot use [] for reading", expr.span),
+            },
+            ExprKind::Call { callee, args } => {
+                let callee = match &callee.kind {
+                    ExprKind::ConstFetch(name) => Callee::Function(qualified_name(name)),
+                    _ => {
+                        self.expr(callee);
+                        Callee::Value
+                    }
+                };
+                self.call(callee, args);
+            }
+            ExprKind::MethodCall {
+                object,
+                method,
+                args,
+                nullsafe,
+            } => {
+                self.expr(object);
+                let skip = nullsafe.then(|| self.emit(Op::JumpIfNull(0)));
+                let name = self.member_name(method);
+                self.call(Callee::Method(name), args);
+                if let Some(skip) = skip {
+                    self.patch(skip);
+                }
+            }
+            ExprKind::StaticCall {
+                class,
+                method,
+                args,
+            } => {
+                let class = self.class_spec(class);
+                let name = self.member_name(method);
+                self.call(Callee::Static(class, name), args);
+            }
+            ExprKind::New { class, args } => {
+                let class = self.class_spec(class);
+                let args = self.args(args.as_deref().unwrap_or_default());
+                self.emit(Op::New(class, args));
+            }
+            ExprKind::Unary { op, expr } => {
+                self.expr(expr);
+                self.emit(Op::Unary(*op));
+            }
+            ExprKind::Binary { op, left, right } => self.binary(*op, left, right),
+            ExprKind::IncDec { op, expr: target } => {
+                let kind = match op {
+                    IncDecOp::PreIncrement => AssignKind::PreInc,
+                    IncDecOp::PreDecrement => AssignKind::PreDec,
+                    IncDecOp::PostIncrement => AssignKind::PostInc,
+                    IncDecOp::PostDecrement => AssignKind::PostDec,
+                };
+                match self.place(target) {
+                    Some(place) => {
+                        self.emit(Op::Store(Box::new(place), kind));
+                    }
+                    None => {
+                        self.emit(Op::Const(Value::Null));
+                    }
+                }
+            }
+            ExprKind::Assign { target, value } => match &target.kind {
+                ExprKind::List { items, .. } => {
+                    self.expr(value);
+                    self.destructure(items);
+                }
+                _ => match self.place(target) {
+                    Some(place) => {
+                        self.expr(value);
+                        self.emit(Op::Store(Box::new(place), AssignKind::Set));
+                    }
+                    None => self.expr(value),
+                },
+            },
+            ExprKind::AssignRef { target, value } => match (&target.kind, &value.kind) {
+                (ExprKind::Variable(target), ExprKind::Variable(source))
+                    if target != "this" && source != "this" =>
+                {
+                    let target = self.slot(target);
+                    let source = self.slot(source);
+                    self.emit(Op::BindRef(target, source));
+                    self.emit(Op::Load(target));
+                }
+                _ => self.error(
+                    "references are only supported between plain variables",
+                    expr.span,
+                ),
+            },
+            ExprKind::AssignOp {
+                op: BinaryOp::Coalesce,
+                target,
+                value,
+            } => {
+                self.expr(target);
+                self.emit(Op::Dup);
+                let end = self.emit(Op::JumpIfNotNull(0));
+                self.emit(Op::Pop);
+                if let Some(place) = self.place(target) {
+                    self.expr(value);
+                    self.emit(Op::Store(Box::new(place), AssignKind::Set));
+                }
+                self.patch(end);
+            }
+            ExprKind::AssignOp { op, target, value } => {
+                if let Some(place) = self.place(target) {
+                    self.expr(value);
+                    self.emit(Op::Store(Box::new(place), AssignKind::Op(*op)));
+                }
+            }
+            ExprKind::Ternary {
+                cond,
+                then,
+                otherwise,
+            } => match then {
+                Some(then) => {
+                    self.expr(cond);
+                    let other = self.emit(Op::JumpIfFalse(0));
+                    self.expr(then);
+                    let end = self.emit(Op::Jump(0));
+                    self.patch(other);
+                    self.expr(otherwise);
+                    self.patch(end);
+                }
+                None => {
+                    self.expr(cond);
+                    self.emit(Op::Dup);
+                    let end = self.emit(Op::JumpIfTrue(0));
+                    self.emit(Op::Pop);
+                    self.expr(otherwise);
+                    self.patch(end);
+                }
+            },
+            ExprKind::Cast { kind, expr: inner } => {
+                if *kind == CastKind::Unset {
+                    self.error("the (unset) cast is no longer supported", expr.span);
+                }
+                self.expr(inner);
+                self.emit(Op::Cast(*kind));
+            }
+            ExprKind::InstanceOf { expr: inner, class } => {
+                self.expr(inner);
+                let class = self.class_spec(class);
+                self.emit(Op::InstanceOf(class));
+            }
+            ExprKind::Clone(inner) => {
+                self.expr(inner);
+                self.emit(Op::Clone);
+            }
+            ExprKind::Print(inner) => {
+                self.expr(inner);
+                self.emit(Op::Echo);
+                self.emit(Op::Const(Value::Int(1)));
+            }
+            ExprKind::ErrorSuppress(inner) => self.expr(inner),
+            ExprKind::Construct { construct, args } => self.construct(*construct, args, expr.span),
+            ExprKind::Include { keyword, .. } => {
+                self.error(format!("'{}' is not supported", keyword), expr.span)
+            }
+            ExprKind::Closure(closure) => {
+                let captured: Vec<_> = closure
+                    .uses
+                    .iter()
+                    .map(|use_| (use_.name.name.clone(), use_.by_ref))
+                    .collect();
+                let body = &closure.body;
+                let returns = closure.return_type.as_ref();
+                self.closure(&closure.params, returns, captured, closure.is_static, |c| {
+                    c.b.func.is_async = closure.is_async;
+                    c.block(body);
+                    c.emit(Op::Const(Value::Null));
+                    c.emit(Op::Return);
+                });
+            }
+            ExprKind::ArrowFunction(function) => {
+                let mut free = FreeVariables::default();
+                free.visit_expr(&function.body);
+                let captured = free
+                    .names
+                    .into_iter()
+                    .filter(|name| !function.params.iter().any(|p| p.name.name == *name))
+                    .map(|name| (name, false))
+                    .collect();
+                let body = &function.body;
+                let returns = function.return_type.as_ref();
+                self.closure(
+                    &function.params,
+                    returns,
+                    captured,
+                    function.is_static,
+                    |c| {
+                        c.b.func.is_async = function.is_async;
+                        c.expr(body);
+                        c.emit(Op::Return);
+                    },
+                );
+            }
+            ExprKind::Match { subject, arms } => {
+                let lowered = lower_match((**subject).clone(), arms.clone(), expr.span);
+                self.expr(&lowered);
+            }
+            ExprKind::Throw(inner) => {
+                self.expr(inner);
+                self.emit(Op::Throw);
+            }
+            ExprKind::Yield { key, value } => {
+                self.generator(expr.span);
+                if let Some(key) = key {
+                    self.expr(key);
+                }
+                match value {
+                    Some(value) => self.expr(value),
+                    None => {
+                        self.emit(Op::Const(Value::Null));
+                    }
+                }
+                self.emit(Op::Yield(key.is_some()));
+            }
+            ExprKind::YieldFrom(inner) => {
+                self.generator(expr.span);
+                self.expr(inner);
+                self.emit(Op::YieldFrom);
+            }
+            ExprKind::Await(inner) => {
+                if !self.function.is_empty() && !self.b.func.is_async {
+                    self.error(
+                        "the \"await\" expression can only be used inside an async function \
+                         or at the top level of a script",
+                        expr.span,
+                    );
+                }
+                self.expr(inner);
+                self.emit(Op::Await);
+            }
+        }
+    }
+
+    /// Makes the function being compiled a generator.
+    fn generator(&mut self, span: Span) {
+        if self.function.is_empty() {
+            self.error(
+                "the \"yield\" expression can only be used inside a function",
+                span,
+            );
+        } else if self.b.func.is_async {
+            self.error(
+                "the \"yield\" expression can not be used inside an async function",
+                span,
+            );
+        } else {
+            self.b.func.generator = true;
+        }
+    }
+
+    fn class_is_known(&self) -> bool {
+        self.class
+            .as_ref()
+            .is_some_and(|class| class.kind != ClassKind::Trait)
+    }
+
+    fn array_item(&mut self, item: &ArrayItem) {
+        if item.by_ref {
+            self.error("array elements by reference are not supported", item.span);
+        }
+        if item.unpack {
+            self.expr(&item.value);
+            self.emit(Op::ArraySpread);
+            return;
+        }
+        match &item.key {
+            Some(key) => {
+                self.expr(key);
+                self.expr(&item.value);
+                self.emit(Op::ArrayInsert);
+            }
+            None => {
+                self.expr(&item.value);
+                self.emit(Op::ArrayPush);
+            }
+        }
+    }
+
+    fn interpolated(&mut self, parts: &[StringPart]) {
+        for part in parts {
+            match part {
+                StringPart::Literal(text, _) => {
+                    self.emit(Op::Const(Value::string(text.as_slice())));
+                }
+                StringPart::Expr(expr) => self.expr(expr),
+            }
+        }
+        self.emit(Op::Interpolate(parts.len() as u32));
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: &Expr, right: &Expr) {
+        match op {
+            BinaryOp::BooleanAnd
+            | BinaryOp::LogicalAnd
+            | BinaryOp::BooleanOr
+            | BinaryOp::LogicalOr => {
+                let is_and = matches!(op, BinaryOp::BooleanAnd | BinaryOp::LogicalAnd);
+                self.expr(left);
+                let short = self.emit(match is_and {
+                    true => Op::JumpIfFalse(0),
+                    false => Op::JumpIfTrue(0),
+                });
+                self.expr(right);
+                self.emit(Op::Cast(CastKind::Bool));
+                let end = self.emit(Op::Jump(0));
+                self.patch(short);
+                self.emit(Op::Const(Value::Bool(!is_and)));
+                self.patch(end);
+            }
+            BinaryOp::Coalesce => {
+                self.expr(left);
+                self.emit(Op::Dup);
+                let end = self.emit(Op::JumpIfNotNull(0));
+                self.emit(Op::Pop);
+                self.expr(right);
+                self.patch(end);
+            }
+            _ => {
+                self.expr(left);
+                self.expr(right);
+                self.emit(Op::Binary(op));
+            }
+        }
+    }
+
+    fn construct(&mut self, construct: ReservedCall, args: &[Expr], span: Span) {
+        match construct {
+            ReservedCall::IsSet => {
+                // `isset($a, $b)` is `isset($a) && isset($b)`.
+                let mut unset = Vec::new();
+                for (i, arg) in args.iter().enumerate() {
+                    self.expr(arg);
+                    self.emit(Op::IsSet);
+                    if i + 1 < args.len() {
+                        unset.push(self.emit(Op::JumpIfFalse(0)));
+                    }
+                }
+                if !unset.is_empty() {
+                    let end = self.emit(Op::Jump(0));
+                    for at in unset {
+                        self.patch(at);
+                    }
+                    self.emit(Op::Const(Value::Bool(false)));
+                    self.patch(end);
+                }
+            }
+            ReservedCall::Empty => {
+                match args.first() {
+                    Some(arg) => self.expr(arg),
+                    None => {
+                        self.emit(Op::Const(Value::Null));
+                    }
+                }
+                self.emit(Op::Unary(UnaryOp::Not));
+            }
+            ReservedCall::Exit | ReservedCall::Die => {
+                match args.first() {
+                    Some(arg) => self.expr(arg),
+                    None => {
+                        self.emit(Op::Const(Value::Null));
+                    }
+                }
+                self.emit(Op::Exit);
+            }
+            _ => self.error(format!("'{}' is not supported", construct.as_str()), span),
+        }
+    }
+
+    fn call(&mut self, callee: Callee, args: &CallArgs) {
+        let args = match args {
+            CallArgs::List(args) => self.args(args),
+            CallArgs::FirstClassCallable(_) => Args::Callable,
+        };
+        self.emit(Op::Call(Box::new(Call { callee, args })));
+    }
+
+    fn args(&mut self, args: &[Arg]) -> Args {
+        if args.iter().any(|arg| arg.unpack || arg.name.is_some()) {
+            self.emit(Op::NewArray);
+            for arg in args {
+                match (&arg.name, arg.unpack) {
+                    (_, true) => {
+                        self.expr(&arg.value);
+                        self.emit(Op::ArraySpread);
+                    }
+                    (Some(name), false) => {
+                        self.emit(Op::Const(Value::string(name.name.as_str())));
+                        self.expr(&arg.value);
+                        self.emit(Op::ArrayInsert);
+                    }
+                    (None, false) => {
+                        self.expr(&arg.value);
+                        self.emit(Op::ArrayPush);
+                    }
+                }
+            }
+            return Args::Array;
+        }
+        let mut slots = Vec::new();
+        for arg in args {
+            self.expr(&arg.value);
+            slots.push(match &arg.value.kind {
+                ExprKind::Variable(name) if name != "this" => Some(self.slot(name)),
+                _ => None,
+            });
+        }
+        Args::Stack(args.len() as u32, slots)
+    }
+
+    fn closure(
+        &mut self,
+        params: &[ParamDecl],
+        returns: Option<&TypeHint>,
+        captured: Vec<(String, bool)>,
+        is_static: bool,
+        body: impl FnOnce(&mut Self),
+    ) {
+        let bindings: Vec<_> = captured
+            .iter()
+            .map(|(name, by_ref)| (self.slot(name), *by_ref))
+            .collect();
+        let outer = std::mem::replace(&mut self.function, "{closure}".to_string());
+        let mut func = self.function("{closure}".to_string(), params, returns, |c| {
+            let captures = captured.iter().map(|(name, _)| c.slot(name)).collect();
+            c.b.func.captures = captures;
+            body(c);
+        });
+        self.function = outer;
+        func.is_static = is_static;
+        self.emit(Op::MakeClosure(Rc::new(func), bindings));
+    }
+
+    fn reserved_const(&mut self, ident: ReservedIdent, span: Span) -> Value {
+        let class = || self.class.as_ref().map(|class| class.name.clone());
+        match ident {
+            ReservedIdent::MagicLine => Value::Int(self.file.line_index(span.start) as i64 + 1),
+            ReservedIdent::MagicFile => Value::string(self.file.name()),
+            ReservedIdent::MagicDir => {
+                let name = self.file.name();
+                Value::string(name.rsplit_once('/').map_or(".", |(dir, _)| dir))
+            }
+            ReservedIdent::MagicClass => Value::string(class().unwrap_or_default()),
+            ReservedIdent::MagicTrait => match &self.class {
+                Some(class) if class.kind == ClassKind::Trait => Value::string(class.name.as_str()),
+                _ => Value::string(""),
+            },
+            ReservedIdent::MagicFunction => Value::string(self.function.as_str()),
+            ReservedIdent::MagicMethod => match class() {
+                Some(class) if self.function != "{closure}" => {
+                    Value::string(format!("{}::{}", class, self.function))
+                }
+                _ => Value::string(self.function.as_str()),
+            },
+            ReservedIdent::MagicNamespace => Value::string(self.namespace.join("\\")),
+            ReservedIdent::PhpVersion => Value::string("8.3.0"),
+            ReservedIdent::PhpMajorVersion => Value::Int(8),
+            ReservedIdent::PhpMinorVersion => Value::Int(3),
+            ReservedIdent::PhpReleaseVersion => Value::Int(0),
+            ReservedIdent::PhpVersionId => Value::Int(80300),
+            ReservedIdent::PhpExtraVersion => Value::string(""),
+            ReservedIdent::PhpZts | ReservedIdent::PhpDebug => Value::Int(0),
+            ReservedIdent::PhpMaxPathLen => Value::Int(4096),
+            ReservedIdent::PhpOs => Value::string("Linux"),
+            ReservedIdent::PhpOsFamily => Value::string("Linux"),
+            ReservedIdent::PhpSapi => Value::string("cli"),
+            ReservedIdent::PhpEol => Value::string("\n"),
+            ReservedIdent::PhpIntMax => Value::Int(i64::MAX),
+            ReservedIdent::PhpIntMin => Value::Int(i64::MIN),
+            ReservedIdent::PhpFloatDig => Value::Int(15),
+            ReservedIdent::PhpFloatEpsilon => Value::Float(f64::EPSILON),
+            ReservedIdent::PhpFloatMin => Value::Float(f64::MIN_POSITIVE),
+            ReservedIdent::PhpFloatMax => Value::Float(f64::MAX),
+            ReservedIdent::PhpShLibSuffix => Value::string("so"),
+            ReservedIdent::PhpFdSetSize => Value::Int(1024),
+            _ => Value::string(""),
+        }
+    }
+
+    fn class_decl(&mut self, class: &ClassLike) -> Rc<ClassDecl> {
+        let name = match &class.name {
+            Some(name) => self.qualify(&name.name),
+            None => {
+                self.anonymous += 1;
+                format!("class@anonymous#{}", self.anonymous)
+            }
+        };
+        let (parent, interfaces) = match class.kind {
+            ClassKind::Interface => (None, class.extends.iter().map(class_name).collect()),
+            _ => (
+                class.extends.first().map(class_name),
+                class.implements.iter().map(class_name).collect(),
+            ),
+        };
+        let outer_class = self.class.replace(ClassContext {
+            name: name.clone(),
+            kind: class.kind,
+        });
+        let outer_function = std::mem::take(&mut self.function);
+        let mut decl = ClassDecl {
+            name: name.clone(),
+            kind: class.kind,
+            is_abstract: class.has_modifier(Keyword::Abstract),
+            is_final: class.has_modifier(Keyword::Final),
+            parent,
+            interfaces,
+            traits: Vec::new(),
+            methods: Vec::new(),
+            consts: Vec::new(),
+            props: Vec::new(),
+            enumeration: None,
+            attributes: Vec::new(),
+            span: class.span,
+        };
+        decl.attributes = self.attributes(&class.attributes);
+        if class.kind == ClassKind::Enum {
+            match EnumClass::new(class) {
+                Ok(enumeration) => decl.enumeration = Some(Rc::new(enumeration)),
+                Err(diagnostic) => self.diagnostics.push(diagnostic),
+            }
+        }
+
+        for member in &class.members {
+            self.at = member.span;
+            match &member.kind {
+                MemberKind::Property(property) => {
+                    let is_static = property
+                        .modifiers
+                        .iter()
+                        .any(|m| m.keyword == Keyword::Static);
+                    let visibility = Visibility::of(&property.modifiers);
+                    for item in &property.props {
+                        let default = item.default.as_ref().map(|default| {
+                            self.thunk(format!("{}::${}", name, item.name), default)
+                        });
+                        decl.props.push(PropDecl {
+                            name: item.name.name.clone(),
+                            is_static,
+                            visibility,
+                            default,
+                        });
+                    }
+                }
+                MemberKind::Const(consts) => {
+                    for item in &consts.consts {
+                        let func = self.thunk(format!("{}::{}", name, item.name), &item.value);
+                        decl.consts
+                            .push((item.name.name.clone(), ConstDecl::Expr(func)));
+                    }
+                }
+                MemberKind::EnumCase(case) => {
+                    decl.consts.push((case.name.name.clone(), ConstDecl::Case));
+                }
+                MemberKind::Method(method) => {
+                    let function = &method.function;
+                    let is_static = method
+                        .modifiers
+                        .iter()
+                        .any(|m| m.keyword == Keyword::Static);
+                    let promoted: Vec<_> = function
+                        .params
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, param)| param.is_promoted())
+                        .map(|(i, param)| (i as u32, param.name.name.clone()))
+                        .collect();
+                    for param in function.params.iter().filter(|p| p.is_promoted()) {
+                        decl.props.push(PropDecl {
+                            name: param.name.name.clone(),
+                            is_static: false,
+                            visibility: Visibility::of(&param.modifiers),
+                            default: None,
+                        });
+                    }
+                    let func = function.body.as_ref().map(|body| {
+                        let mut func = self.function(
+                            format!("{}::{}", name, function.name),
+                            &function.params,
+                            function.return_type.as_ref(),
+                            |c| {
+                                c.function = function.name.name.clone();
+                                c.b.func.is_async = function.is_async;
+                                c.b.func.attributes = c.attributes(&function.attributes);
+                                for (slot, prop) in &promoted {
+                                    c.emit(Op::LoadThis);
+                                    c.emit(Op::Load(*slot));
+                                    let place = Place {
+                                        root: Root::Prop(Some(prop.clone())),
+                                        dims: Vec::new(),
+                                    };
+                                    c.emit(Op::Store(Box::new(place), AssignKind::Set));
+                                    c.emit(Op::Pop);
+                                }
+                                c.block(body);
+                                c.emit(Op::Const(Value::Null));
+                                c.emit(Op::Return);
+                            },
+                        );
+                        func.is_static = is_static;
+                        Rc::new(func)
+                    });
+                    decl.methods.push(MethodDecl {
+                        name: function.name.name.clone(),
+                        is_static,
+                        is_final: method.modifiers.iter().any(|m| m.keyword == Keyword::Final),
+                        func,
+                    });
+                }
+                MemberKind::TraitUse(trait_use) => {
+                    decl.traits.extend(trait_use.traits.iter().map(class_name));
+                    if !trait_use.adaptations.is_empty() {
+                        self.error("trait adaptations are not supported", member.span);
+                    }
+                }
+                MemberKind::Error => {}
+            }
+        }
+
+        self.class = outer_class;
+        self.function = outer_function;
+        Rc::new(decl)
+    }
+
+    fn attributes(&mut self, groups: &[AttributeGroup]) -> Vec<Rc<AttributeDecl>> {
+        let mut attributes = Vec::new();
+        for attribute in groups.iter().flat_map(|group| &group.attributes) {
+            let name = class_name(&attribute.name);
+            let args = Rc::new(self.function(format!("#[{}]", name), &[], None, |c| {
+                c.emit(Op::NewArray);
+                for arg in &attribute.args {
+                    if let Some(key) = &arg.name {
+                        c.emit(Op::Const(Value::string(key.name.as_str())));
+                        c.expr(&arg.value);
+                        c.emit(Op::ArrayInsert);
+                    } else {
+                        c.expr(&arg.value);
+                        c.emit(Op::ArrayPush);
+                    }
+                }
+                c.emit(Op::Return);
+            }));
+            let instance = Rc::new(self.function(format!("#[{}]", name), &[], None, |c| {
+                let args = c.args(&attribute.args);
+                c.emit(Op::New(ClassSpec::Named(name.clone()), args));
+                c.emit(Op::Return);
+            }));
+            attributes.push(Rc::new(AttributeDecl {
+                name,
+                args,
+                instance,
+            }));
+        }
+        attributes
+    }
+
+    /// A function returning the value of a constant expression, for class constants and
+    /// property defaults.
+    fn thunk(&mut self, name: String, value: &Expr) -> Rc<Func> {
+        Rc::new(self.function(name, &[], None, |c| {
+            c.expr(value);
+            c.emit(Op::Return);
+        }))
+    }
+}
+
+fn local(slot: u32) -> Place {
+    Place {
+        root: Root::Local(slot),
+        dims: Vec::new(),
+    }
+}
+
+/// The integer value of a `declare` directive.
+fn directive(directives: &[DeclareDirective], name: &str) -> Option<i64> {
+    let directive = directives
+        .iter()
+        .find(|d| d.name.name.eq_ignore_ascii_case(name))?;
+    match directive.value.kind {
+        ExprKind::Literal(Literal::Numeric(Numeric::Int(n))) => Some(n),
+        _ => None,
+    }
+}
+
+/// A declared type with the class names as the VM knows them, without a leading `\`.
+fn runtime_type(hint: &TypeHint) -> Type {
+    fn strip(ty: Type) -> Type {
+        match ty {
+            Type::Class(name) => Type::Class(name.trim_start_matches('\\').to_string()),
+            Type::Union(types) => Type::Union(types.into_iter().map(strip).collect()),
+            Type::Intersection(types) => Type::Intersection(types.into_iter().map(strip).collect()),
+            ty => ty,
+        }
+    }
+    strip(hint.to_type())
+}
+
+/// The fully qualified name of a class, as the resolver found it.
+fn class_name(name: &Name) -> String {
+    match name.resolved.as_deref() {
+        Some(ResolvedName::FullyQualified(parts)) => parts.join("\\"),
+        Some(ResolvedName::Fallback { namespaced, .. }) => namespaced.join("\\"),
+        None => name.joined(),
+    }
+}
+
+fn qualified_name(name: &Name) -> QualifiedName {
+    match name.resolved.as_deref() {
+        Some(ResolvedName::FullyQualified(parts)) => QualifiedName {
+            name: parts.join("\\"),
+            fallback: None,
+        },
+        Some(ResolvedName::Fallback { namespaced, global }) => QualifiedName {
+            name: namespaced.join("\\"),
+            fallback: Some(global.clone()),
+        },
+        None => QualifiedName {
+            name: name.joined(),
+            fallback: None,
+        },
+    }
+}
+
+/// The variables an arrow function uses, which it captures from the enclosing scope.
+#[derive(Default)]
+struct FreeVariables {
+    names: Vec<String>,
+}
+
+impl<'ast> Visitor<'ast> for FreeVariables {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let ExprKind::Variable(name) = &expr.kind {
+            if name != "this" && !self.names.contains(name) {
+                self.names.push(name.clone());
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_closure(&mut self, closure: &'ast crate::syntax::ast::function::Closure) {
+        // a closure only sees the variables it imports.
+        for use_ in &closure.uses {
+            if !self.names.contains(&use_.name.name) {
+                self.names.push(use_.name.name.clone());
+            }
+        }
+    }
+
+    fn visit_class_like(&mut self, _class: &'ast ClassLike) {}
+
+    fn visit_param(&mut self, _param: &'ast ParamDecl) {}
+}
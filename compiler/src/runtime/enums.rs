@@ -0,0 +1,336 @@
+use std::fmt;
+
+use crate::syntax::{
+    ast::{
+        class::{ClassKind, ClassLike, MemberKind},
+        expr::{BinaryOp, ClassRef, Expr, ExprKind, Literal, MemberName, UnaryOp},
+        types::{BuiltinType, Type},
+    },
+    diagnostic::Diagnostic,
+    lex::token::Numeric,
+};
+
+use super::RuntimeError;
+
+/// How deep constants may refer to other constants while evaluating a case value, which
+/// stops `const A = self::B; const B = self::A;` from recursing forever.
+const MAX_CONST_DEPTH: usize = 32;
+
+/// The value behind a case of a backed enum.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BackingValue {
+    Int(i64),
+    String(Vec<u8>),
+}
+
+impl BackingValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            BackingValue::Int(_) => "int",
+            BackingValue::String(_) => "string",
+        }
+    }
+}
+
+impl fmt::Display for BackingValue {
+    /// The value as PHP quotes it in error messages, `5` or `"H"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackingValue::Int(value) => write!(f, "{}", value),
+            BackingValue::String(value) => write!(f, "\"{}\"", String::from_utf8_lossy(value)),
+        }
+    }
+}
+
+/// An enum as it exists at runtime, with the values of its cases evaluated once when it is
+/// declared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumClass {
+    name: String,
+    backing_type: Option<BuiltinType>,
+    cases: Vec<(String, Option<BackingValue>)>,
+}
+
+impl EnumClass {
+    /// Evaluates the cases of an enum declaration, rejecting values that are not constant,
+    /// do not match the backing type or are used by two cases.
+    pub fn new(decl: &ClassLike) -> Result<Self, Diagnostic> {
+        let name = decl.name.as_ref().map_or("", |name| name.as_str());
+        if decl.kind != ClassKind::Enum {
+            return Err(Diagnostic::error(
+                format!("{} is not an enum", name),
+                decl.span,
+            ));
+        }
+        let backing_type = decl
+            .backing_type
+            .as_ref()
+            .and_then(|ty| match ty.to_type() {
+                Type::Builtin(builtin) => Some(builtin),
+                _ => None,
+            });
+
+        let mut cases: Vec<(String, Option<BackingValue>)> = Vec::new();
+        for member in &decl.members {
+            let MemberKind::EnumCase(case) = &member.kind else {
+                continue;
+            };
+            let value = match &case.value {
+                Some(value) => Some(Self::evaluate(decl, value, 0)?),
+                None => None,
+            };
+            if let (Some(value), Some(backing_type)) = (&value, backing_type) {
+                if value.type_name() != backing_type.as_str() {
+                    return Err(Diagnostic::error(
+                        format!(
+                            "enum case type {} does not match enum backing type {}",
+                            value.type_name(),
+                            backing_type.as_str()
+                        ),
+                        member.span,
+                    ));
+                }
+                if let Some((other, _)) = cases.iter().find(|(_, v)| v.as_ref() == Some(value)) {
+                    return Err(Diagnostic::error(
+                        format!(
+                            "duplicate value in enum {} for cases {} and {}",
+                            name, other, case.name
+                        ),
+                        member.span,
+                    ));
+                }
+            }
+            cases.push((case.name.name.clone(), value));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            backing_type,
+            cases,
+        })
+    }
+
+    /// A case value, which may be built from literals and the constants of the enum.
+    fn evaluate(decl: &ClassLike, expr: &Expr, depth: usize) -> Result<BackingValue, Diagnostic> {
+        let not_constant = || {
+            Diagnostic::error(
+                "enum case value must be compile-time evaluatable",
+                expr.span,
+            )
+        };
+        match &expr.kind {
+            ExprKind::Literal(Literal::Numeric(Numeric::Int(value))) => {
+                Ok(BackingValue::Int(*value))
+            }
+            ExprKind::Literal(Literal::String(value)) => Ok(BackingValue::String(value.clone())),
+            ExprKind::Unary {
+                op: UnaryOp::Minus,
+                expr: inner,
+            } => match Self::evaluate(decl, inner, depth)? {
+                BackingValue::Int(value) => Ok(BackingValue::Int(value.wrapping_neg())),
+                BackingValue::String(_) => Err(not_constant()),
+            },
+            ExprKind::Binary {
+                op: BinaryOp::Concat,
+                left,
+                right,
+            } => {
+                let bytes = |value| match value {
+                    BackingValue::Int(value) => value.to_string().into_bytes(),
+                    BackingValue::String(value) => value,
+                };
+                let mut left = bytes(Self::evaluate(decl, left, depth)?);
+                left.extend(bytes(Self::evaluate(decl, right, depth)?));
+                Ok(BackingValue::String(left))
+            }
+            ExprKind::ClassConstFetch {
+                class: ClassRef::Name(class),
+                constant: MemberName::Ident(constant),
+            } if depth < MAX_CONST_DEPTH
+                && (class.last().eq_ignore_ascii_case("self")
+                    || decl
+                        .name
+                        .as_ref()
+                        .is_some_and(|name| class.last().eq_ignore_ascii_case(name.as_str()))) =>
+            {
+                let value = decl.members.iter().find_map(|member| match &member.kind {
+                    MemberKind::Const(consts) => consts
+                        .consts
+                        .iter()
+                        .find(|item| item.name.name == constant.name)
+                        .map(|item| &item.value),
+                    _ => None,
+                });
+                match value {
+                    Some(value) => Self::evaluate(decl, value, depth + 1),
+                    None => Err(not_constant()),
+                }
+            }
+            _ => Err(not_constant()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// `int` or `string` for a backed enum.
+    pub fn backing_type(&self) -> Option<BuiltinType> {
+        self.backing_type
+    }
+
+    /// Whether the enum has a backing type, and so `from()` and `tryFrom()`.
+    pub fn is_backed(&self) -> bool {
+        self.backing_type.is_some()
+    }
+
+    /// `Suit::cases()`, every case in declaration order.
+    pub fn cases(&self) -> Vec<EnumCase<'_>> {
+        (0..self.cases.len())
+            .map(|index| EnumCase { class: self, index })
+            .collect()
+    }
+
+    /// `Suit::Hearts`, case names are case-sensitive like constants.
+    pub fn case(&self, name: &str) -> Option<EnumCase<'_>> {
+        self.cases
+            .iter()
+            .position(|(case, _)| case == name)
+            .map(|index| EnumCase { class: self, index })
+    }
+
+    /// `Suit::from($value)`, which throws a `ValueError` when no case has the value.
+    pub fn from(&self, value: BackingValue) -> Result<EnumCase<'_>, RuntimeError> {
+        let value = self.coerce("from", value)?;
+        self.find(&value).ok_or_else(|| {
+            RuntimeError::new(
+                "ValueError",
+                format!(
+                    "{} is not a valid backing value for enum {}",
+                    value, self.name
+                ),
+            )
+        })
+    }
+
+    /// `Suit::tryFrom($value)`, which returns `null` when no case has the value.
+    pub fn try_from(&self, value: BackingValue) -> Result<Option<EnumCase<'_>>, RuntimeError> {
+        let value = self.coerce("tryFrom", value)?;
+        Ok(self.find(&value))
+    }
+
+    fn find(&self, value: &BackingValue) -> Option<EnumCase<'_>> {
+        self.cases
+            .iter()
+            .position(|(_, case)| case.as_ref() == Some(value))
+            .map(|index| EnumCase { class: self, index })
+    }
+
+    /// Converts the argument of `from()` or `tryFrom()` to the backing type, as weak mode
+    /// does for an `int` or `string` parameter.
+    fn coerce(&self, method: &str, value: BackingValue) -> Result<BackingValue, RuntimeError> {
+        let type_error = |value: &BackingValue, expected: &str| {
+            RuntimeError::new(
+                "TypeError",
+                format!(
+                    "{}::{}(): Argument #1 ($value) must be of type {}, {} given",
+                    self.name,
+                    method,
+                    expected,
+                    value.type_name()
+                ),
+            )
+        };
+        match (self.backing_type, value) {
+            (None, _) => Err(RuntimeError::new(
+                "Error",
+                format!("Call to undefined method {}::{}()", self.name, method),
+            )),
+            (Some(BuiltinType::String), BackingValue::Int(value)) => {
+                Ok(BackingValue::String(value.to_string().into_bytes()))
+            }
+            (Some(BuiltinType::Int), BackingValue::String(text)) => {
+                match std::str::from_utf8(text.trim_ascii()).map(str::parse) {
+                    Ok(Ok(value)) => Ok(BackingValue::Int(value)),
+                    _ => Err(type_error(&BackingValue::String(text), "int")),
+                }
+            }
+            (_, value) => Ok(value),
+        }
+    }
+}
+
+/// A case of an enum. Cases are singletons, so two cases are identical, `===`, only when
+/// they are the same case of the same enum.
+#[derive(Debug, Clone, Copy)]
+pub struct EnumCase<'a> {
+    class: &'a EnumClass,
+    index: usize,
+}
+
+impl<'a> EnumCase<'a> {
+    pub fn class(&self) -> &'a EnumClass {
+        self.class
+    }
+
+    /// The `name` property.
+    pub fn name(&self) -> &'a str {
+        &self.class.cases[self.index].0
+    }
+
+    /// The `value` property, only present on the cases of backed enums.
+    pub fn value(&self) -> Option<&'a BackingValue> {
+        self.class.cases[self.index].1.as_ref()
+    }
+
+    fn is_readonly(&self, property: &str) -> bool {
+        property == "name" || (property == "value" && self.class.is_backed())
+    }
+
+    /// Assigning to a property of a case, which always fails as `name` and `value` are
+    /// readonly and cases can not have any other properties.
+    pub fn set_property(&self, property: &str) -> Result<(), RuntimeError> {
+        let message = if self.is_readonly(property) {
+            format!(
+                "Cannot modify readonly property {}::${}",
+                self.class.name, property
+            )
+        } else {
+            format!(
+                "Cannot create dynamic property {}::${}",
+                self.class.name, property
+            )
+        };
+        Err(RuntimeError::new("Error", message))
+    }
+
+    /// Unsetting a property of a case, which fails for `name` and `value` and does nothing
+    /// for the properties a case does not have.
+    pub fn unset_property(&self, property: &str) -> Result<(), RuntimeError> {
+        match self.is_readonly(property) {
+            true => Err(RuntimeError::new(
+                "Error",
+                format!(
+                    "Cannot unset readonly property {}::${}",
+                    self.class.name, property
+                ),
+            )),
+            false => Ok(()),
+        }
+    }
+}
+
+impl PartialEq for EnumCase<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.class, other.class) && self.index == other.index
+    }
+}
+
+impl Eq for EnumCase<'_> {}
+
+impl fmt::Display for EnumCase<'_> {
+    /// `Suit::Hearts`, as `var_export` writes a case.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}", self.class.name, self.name())
+    }
+}
@@ -0,0 +1,36 @@
+//! The behaviour of PHP values and language features once a script runs.
+
+use std::fmt;
+
+mod builtins;
+pub mod class;
+pub mod compile;
+pub mod enums;
+pub mod ops;
+pub mod value;
+pub mod vm;
+
+/// An exception or error thrown by the runtime, such as a `ValueError`, by the name of its
+/// class and its message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub class: String,
+    pub message: String,
+}
+
+impl RuntimeError {
+    pub fn new(class: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            class: class.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.class, self.message)
+    }
+}
+
+impl std::error::Error for RuntimeError {}
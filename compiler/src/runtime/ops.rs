@@ -0,0 +1,198 @@
+//! The operators on values that do not need the rest of the runtime.
+//!
+//! Errors are returned as the name of the class to throw and its message, the VM turns them
+//! into exceptions.
+
+use std::cmp::Ordering;
+
+use crate::syntax::ast::expr::{BinaryOp, UnaryOp};
+
+use super::value::{float_to_int, parse_numeric, Value};
+
+pub type OpResult = Result<Value, (&'static str, String)>;
+
+pub fn binary(op: BinaryOp, left: &Value, right: &Value) -> OpResult {
+    use BinaryOp::*;
+    Ok(match op {
+        Add | Sub | Mul | Div | Mod | Pow => return arithmetic(op, left, right),
+        Concat => {
+            let (Some(mut left), Some(right)) = (left.to_php_string(), right.to_php_string())
+            else {
+                return Err(("Error", "Array to string conversion".to_string()));
+            };
+            left.push_bytes(&right);
+            Value::String(left)
+        }
+        BitAnd | BitOr | BitXor | ShiftLeft | ShiftRight => return bitwise(op, left, right),
+        BooleanAnd | LogicalAnd => Value::Bool(left.to_bool() && right.to_bool()),
+        BooleanOr | LogicalOr => Value::Bool(left.to_bool() || right.to_bool()),
+        LogicalXor => Value::Bool(left.to_bool() ^ right.to_bool()),
+        Equal => Value::Bool(left.loose_eq(right)),
+        NotEqual => Value::Bool(!left.loose_eq(right)),
+        Identical => Value::Bool(left.identical(right)),
+        NotIdentical => Value::Bool(!left.identical(right)),
+        Less => Value::Bool(left.compare(right) == Some(Ordering::Less)),
+        LessOrEqual => Value::Bool(matches!(
+            left.compare(right),
+            Some(Ordering::Less | Ordering::Equal)
+        )),
+        Greater => Value::Bool(left.compare(right) == Some(Ordering::Greater)),
+        GreaterOrEqual => Value::Bool(matches!(
+            left.compare(right),
+            Some(Ordering::Greater | Ordering::Equal)
+        )),
+        Spaceship => Value::Int(match left.compare(right) {
+            Some(Ordering::Less) => -1,
+            Some(Ordering::Equal) => 0,
+            _ => 1,
+        }),
+        Coalesce => match left {
+            Value::Null => right.clone(),
+            _ => left.clone(),
+        },
+    })
+}
+
+fn unsupported(op: BinaryOp, left: &Value, right: &Value) -> (&'static str, String) {
+    (
+        "TypeError",
+        format!(
+            "Unsupported operand types: {} {} {}",
+            left.type_name(),
+            op.as_str(),
+            right.type_name()
+        ),
+    )
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> OpResult {
+    if let (Value::Array(a), Value::Array(b), BinaryOp::Add) = (left, right, op) {
+        // the union of two arrays, keys of the left one win.
+        let mut union = (**a).clone();
+        for (key, value) in b.iter() {
+            if !union.contains_key(key) {
+                union.insert(key.clone(), value.clone());
+            }
+        }
+        return Ok(Value::array(union));
+    }
+    let (Some(a), Some(b)) = (left.to_number(), right.to_number()) else {
+        return Err(unsupported(op, left, right));
+    };
+    if op == BinaryOp::Mod {
+        let (a, b) = (a.to_int(), b.to_int());
+        if b == 0 {
+            return Err(("DivisionByZeroError", "Modulo by zero".to_string()));
+        }
+        return Ok(Value::Int(a.checked_rem(b).unwrap_or(0)));
+    }
+    if let (Value::Int(a), Value::Int(b)) = (&a, &b) {
+        let (a, b) = (*a, *b);
+        let result = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Sub => a.checked_sub(b),
+            BinaryOp::Mul => a.checked_mul(b),
+            BinaryOp::Div if b == 0 => {
+                return Err(("DivisionByZeroError", "Division by zero".to_string()))
+            }
+            BinaryOp::Div => match a.checked_rem(b) {
+                Some(0) => a.checked_div(b),
+                _ => None,
+            },
+            BinaryOp::Pow if b >= 0 => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(Value::Int(result));
+        }
+    }
+    let (a, b) = (a.to_float(), b.to_float());
+    Ok(Value::Float(match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Sub => a - b,
+        BinaryOp::Mul => a * b,
+        BinaryOp::Div if b == 0.0 => {
+            return Err(("DivisionByZeroError", "Division by zero".to_string()))
+        }
+        BinaryOp::Div => a / b,
+        _ => a.powf(b),
+    }))
+}
+
+fn bitwise(op: BinaryOp, left: &Value, right: &Value) -> OpResult {
+    let (Some(a), Some(b)) = (left.to_number(), right.to_number()) else {
+        return Err(unsupported(op, left, right));
+    };
+    let (a, b) = (a.to_int(), b.to_int());
+    Ok(Value::Int(match op {
+        BinaryOp::BitAnd => a & b,
+        BinaryOp::BitOr => a | b,
+        BinaryOp::BitXor => a ^ b,
+        _ if b < 0 => {
+            return Err((
+                "ArithmeticError",
+                "Bit shift by negative number".to_string(),
+            ))
+        }
+        BinaryOp::ShiftLeft if b >= 64 => 0,
+        BinaryOp::ShiftLeft => a << b,
+        _ if b >= 64 => a >> 63,
+        _ => a >> b,
+    }))
+}
+
+pub fn unary(op: UnaryOp, value: &Value) -> OpResult {
+    match op {
+        UnaryOp::Not => Ok(Value::Bool(!value.to_bool())),
+        UnaryOp::Plus => arithmetic(BinaryOp::Mul, value, &Value::Int(1)),
+        UnaryOp::Minus => arithmetic(BinaryOp::Mul, value, &Value::Int(-1)),
+        UnaryOp::BitNot => match value {
+            Value::Int(i) => Ok(Value::Int(!i)),
+            Value::Float(f) => Ok(Value::Int(!float_to_int(*f))),
+            _ => Err((
+                "TypeError",
+                format!("Cannot perform bitwise not on {}", value.type_name()),
+            )),
+        },
+    }
+}
+
+/// `++` and `--`, which also work on `null` and, for `++`, on alphanumeric strings.
+pub fn increment(value: &Value, up: bool) -> OpResult {
+    match (value, up) {
+        (Value::Null, true) => Ok(Value::Int(1)),
+        (Value::Null, false) => Ok(Value::Null),
+        (Value::String(s), true) if !s.is_empty() && parse_numeric(s).is_none() => {
+            Ok(Value::string(increment_string(s)))
+        }
+        _ => arithmetic(
+            if up { BinaryOp::Add } else { BinaryOp::Sub },
+            value,
+            &Value::Int(1),
+        ),
+    }
+}
+
+/// `"a"` becomes `"b"`, `"Az"` becomes `"Ba"` and `"zz"` becomes `"aaa"`, like Perl.
+fn increment_string(s: &[u8]) -> Vec<u8> {
+    let mut bytes = s.to_vec();
+    let mut i = bytes.len();
+    while i > 0 {
+        i -= 1;
+        let (wrapped, first) = match bytes[i] {
+            b'z' => (b'a', b'a'),
+            b'Z' => (b'A', b'A'),
+            b'9' => (b'0', b'1'),
+            b if b.is_ascii_alphanumeric() => {
+                bytes[i] = b + 1;
+                return bytes;
+            }
+            _ => return bytes,
+        };
+        bytes[i] = wrapped;
+        if i == 0 {
+            bytes.insert(0, first);
+        }
+    }
+    bytes
+}
@@ -0,0 +1,621 @@
+//! Values as a running script sees them.
+//!
+//! Scalars and arrays are values, arrays are shared until one of their holders changes them.
+//! Objects are handles, every copy refers to the same object. Conversions and comparisons
+//! follow PHP 8, for instance `0 == "a"` is false.
+
+use std::{
+    borrow::Cow, cell::RefCell, cmp::Ordering, collections::HashMap, fmt, ops::Deref, rc::Rc,
+};
+
+use super::{
+    class::Class,
+    compile::AttributeDecl,
+    vm::{Closure, Generator},
+};
+
+#[derive(Debug, Clone, Default)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(PhpString),
+    Array(Rc<Array>),
+    Object(Rc<Object>),
+}
+
+impl Value {
+    pub fn string(s: impl Into<PhpString>) -> Value {
+        Value::String(s.into())
+    }
+
+    pub fn array(array: Array) -> Value {
+        Value::Array(Rc::new(array))
+    }
+
+    /// The type as `gettype` names it, `int` rather than `integer` as in error messages.
+    pub fn type_name(&self) -> &str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(object) => &object.class.name,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn to_bool(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Int(i) => *i != 0,
+            Value::Float(f) => *f != 0.0,
+            Value::String(s) => !(s.is_empty() || s == "0"),
+            Value::Array(array) => !array.is_empty(),
+            Value::Object(_) => true,
+        }
+    }
+
+    /// The value as a string, `None` for arrays and objects, which need the runtime.
+    pub fn to_php_string(&self) -> Option<PhpString> {
+        Some(match self {
+            Value::Null | Value::Bool(false) => PhpString::default(),
+            Value::Bool(true) => PhpString::from("1"),
+            Value::Int(i) => PhpString::from(i.to_string()),
+            Value::Float(f) => PhpString::from(format_float(*f)),
+            Value::String(s) => s.clone(),
+            Value::Array(_) | Value::Object(_) => return None,
+        })
+    }
+
+    /// The value as a number for arithmetic, `None` when it is not numeric at all. Strings
+    /// with trailing garbage, like `"12abc"`, give their leading number.
+    pub fn to_number(&self) -> Option<Value> {
+        match self {
+            Value::Null | Value::Bool(false) => Some(Value::Int(0)),
+            Value::Bool(true) => Some(Value::Int(1)),
+            Value::Int(_) | Value::Float(_) => Some(self.clone()),
+            Value::String(s) => parse_numeric_prefix(s).map(|(number, _)| number),
+            Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+
+    pub fn to_int(&self) -> i64 {
+        match self.to_number() {
+            Some(Value::Int(i)) => i,
+            Some(Value::Float(f)) => float_to_int(f),
+            _ => i64::from(self.to_bool()),
+        }
+    }
+
+    pub fn to_float(&self) -> f64 {
+        match self.to_number() {
+            Some(Value::Int(i)) => i as f64,
+            Some(Value::Float(f)) => f,
+            _ => f64::from(u8::from(self.to_bool())),
+        }
+    }
+
+    /// `===`
+    pub fn identical(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.identical(vb))
+            }
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// `==`
+    pub fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Object(a), Value::Object(b)) => {
+                Rc::ptr_eq(a, b)
+                    || Rc::ptr_eq(&a.class, &b.class)
+                        && a.props.borrow().loose_eq(&b.props.borrow())
+            }
+            (Value::Array(a), Value::Array(b)) => a.loose_eq(b),
+            _ => self.compare(other) == Some(Ordering::Equal),
+        }
+    }
+
+    /// `<=>`, `None` for values that cannot be ordered, such as an array and an int.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        use Value::*;
+        match (self, other) {
+            (String(a), String(b)) => Some(match (parse_numeric(a), parse_numeric(b)) {
+                (Some(a), Some(b)) => compare_numbers(&a, &b),
+                _ => a.cmp(b),
+            }),
+            (Null, String(s)) => Some(PhpString::default().cmp(s)),
+            (String(s), Null) => Some(s.cmp(&PhpString::default())),
+            (Bool(_) | Null, _) | (_, Bool(_) | Null) => Some(self.to_bool().cmp(&other.to_bool())),
+            (Int(_) | Float(_), Int(_) | Float(_)) => Some(compare_numbers(self, other)),
+            // A number and a numeric string compare as numbers, otherwise as strings.
+            (Int(_) | Float(_), String(s)) => Some(match parse_numeric(s) {
+                Some(n) => compare_numbers(self, &n),
+                None => self.to_php_string()?.cmp(s),
+            }),
+            (String(_), Int(_) | Float(_)) => other.compare(self).map(Ordering::reverse),
+            (Array(a), Array(b)) => a.compare(b),
+            (Array(_), _) => Some(Ordering::Greater),
+            (_, Array(_)) => Some(Ordering::Less),
+            (Object(a), Object(b)) if Rc::ptr_eq(a, b) => Some(Ordering::Equal),
+            (Object(a), Object(b)) if Rc::ptr_eq(&a.class, &b.class) => {
+                a.props.borrow().compare(&b.props.borrow())
+            }
+            (Object(_), _) => Some(Ordering::Greater),
+            (_, Object(_)) => Some(Ordering::Less),
+        }
+    }
+}
+
+fn compare_numbers(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        _ => a
+            .to_float()
+            .partial_cmp(&b.to_float())
+            .unwrap_or(Ordering::Less),
+    }
+}
+
+/// Converts a float to an int the way PHP 8 does on 64 bit platforms, out of range values
+/// and NaN become `0`.
+pub fn float_to_int(f: f64) -> i64 {
+    if f.is_finite() && f > i64::MIN as f64 && f < i64::MAX as f64 {
+        f as i64
+    } else {
+        0
+    }
+}
+
+/// A whole numeric string, with optional surrounding whitespace, `" 12"` or `"1.5e3"`.
+pub fn parse_numeric(s: &[u8]) -> Option<Value> {
+    match parse_numeric_prefix(s) {
+        Some((number, rest)) if rest.iter().all(is_whitespace) => Some(number),
+        _ => None,
+    }
+}
+
+/// The number at the start of a string and the bytes after it.
+fn parse_numeric_prefix(s: &[u8]) -> Option<(Value, &[u8])> {
+    let start = s.iter().position(|b| !is_whitespace(b)).unwrap_or(s.len());
+    let bytes = &s[start..];
+    let mut end = 0;
+    if matches!(bytes.first(), Some(b'+' | b'-')) {
+        end += 1;
+    }
+    let digits_start = end;
+    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+        end += 1;
+    }
+    let mut is_float = false;
+    let mut has_digits = end > digits_start;
+    if bytes.get(end) == Some(&b'.') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit)
+        || bytes.get(end) == Some(&b'.') && has_digits
+    {
+        is_float = true;
+        end += 1;
+        while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+            has_digits = true;
+        }
+    }
+    if !has_digits {
+        return None;
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exp = end + 1;
+        if matches!(bytes.get(exp), Some(b'+' | b'-')) {
+            exp += 1;
+        }
+        if bytes.get(exp).is_some_and(u8::is_ascii_digit) {
+            while bytes.get(exp).is_some_and(u8::is_ascii_digit) {
+                exp += 1;
+            }
+            is_float = true;
+            end = exp;
+        }
+    }
+    // the number is ASCII.
+    let text = std::str::from_utf8(&bytes[..end]).ok()?;
+    let number = match is_float {
+        false => match text.parse::<i64>() {
+            Ok(i) => Value::Int(i),
+            Err(_) => Value::Float(text.parse().ok()?),
+        },
+        true => Value::Float(text.parse().ok()?),
+    };
+    Some((number, &bytes[end..]))
+}
+
+/// The whitespace allowed around a numeric string.
+fn is_whitespace(b: &u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'\x0B' | b'\x0C')
+}
+
+/// Formats a float like `echo` does, with 14 significant digits, `0.3`, `1.0E+25` or `-INF`.
+pub fn format_float(f: f64) -> String {
+    format_float_with(f, Some(14))
+}
+
+/// Formats a float like `var_dump` and `var_export` do, with the shortest digits that read
+/// back as the same float, `0.30000000000000004`.
+pub fn format_float_repr(f: f64) -> String {
+    format_float_with(f, None)
+}
+
+fn format_float_with(f: f64, precision: Option<usize>) -> String {
+    if f.is_nan() {
+        return "NAN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+    if f == 0.0 {
+        return if f.is_sign_negative() { "-0" } else { "0" }.to_string();
+    }
+    let scientific = match precision {
+        Some(precision) => format!("{:.*e}", precision - 1, f),
+        None => format!("{:e}", f),
+    };
+    let (mantissa, exponent) = scientific.split_once('e').expect("always has an exponent");
+    let exponent: i32 = exponent.parse().expect("the exponent is an integer");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .trim_end_matches('0')
+        .to_string();
+    let digits = if digits.is_empty() {
+        "0".to_string()
+    } else {
+        digits
+    };
+    let limit = precision.map_or(15, |precision| precision as i32);
+    if exponent < -4 || exponent >= limit {
+        let (first, rest) = digits.split_at(1);
+        let rest = if rest.is_empty() { "0" } else { rest };
+        let exp_sign = if exponent < 0 { '-' } else { '+' };
+        return format!("{}{}.{}E{}{}", sign, first, rest, exp_sign, exponent.abs());
+    }
+    let text = if exponent < 0 {
+        format!("0.{}{}", "0".repeat((-exponent - 1) as usize), digits)
+    } else {
+        let point = exponent as usize + 1;
+        if digits.len() <= point {
+            format!("{}{}", digits, "0".repeat(point - digits.len()))
+        } else {
+            format!("{}.{}", &digits[..point], &digits[point..])
+        }
+    };
+    format!("{}{}", sign, text)
+}
+
+/// The bytes of a PHP string. They are usually, but not always, UTF-8: `"\xff"` is one byte,
+/// and indexing or measuring a string works on bytes rather than chars.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PhpString(Vec<u8>);
+
+impl PhpString {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// The string as text, with the bytes that are not UTF-8 replaced.
+    pub fn to_str(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+impl Deref for PhpString {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for PhpString {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8]> for PhpString {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl From<String> for PhpString {
+    fn from(s: String) -> Self {
+        Self(s.into_bytes())
+    }
+}
+
+impl From<&str> for PhpString {
+    fn from(s: &str) -> Self {
+        Self(s.as_bytes().to_vec())
+    }
+}
+
+impl From<&String> for PhpString {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl PartialEq<str> for PhpString {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for PhpString {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl fmt::Display for PhpString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_str())
+    }
+}
+
+/// The key of an array element. Integer-like strings are stored as ints, as in PHP.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ArrayKey {
+    Int(i64),
+    String(PhpString),
+}
+
+impl ArrayKey {
+    /// The key a value stands for, `None` for arrays and objects, which cannot be keys.
+    pub fn from_value(value: &Value) -> Option<ArrayKey> {
+        Some(match value {
+            Value::Null => ArrayKey::String(PhpString::default()),
+            Value::Bool(b) => ArrayKey::Int(i64::from(*b)),
+            Value::Int(i) => ArrayKey::Int(*i),
+            Value::Float(f) => ArrayKey::Int(float_to_int(*f)),
+            Value::String(s) => ArrayKey::from(s.as_bytes()),
+            Value::Array(_) | Value::Object(_) => return None,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            ArrayKey::Int(i) => Value::Int(*i),
+            ArrayKey::String(s) => Value::String(s.clone()),
+        }
+    }
+}
+
+impl From<&[u8]> for ArrayKey {
+    fn from(s: &[u8]) -> Self {
+        // only canonical decimal integers, `"08"` and `"1.0"` stay strings.
+        let canonical = s == b"0"
+            || !s.starts_with(b"0")
+                && !s.starts_with(b"-0")
+                && !s.is_empty()
+                && s != b"-"
+                && s.strip_prefix(b"-")
+                    .unwrap_or(s)
+                    .iter()
+                    .all(u8::is_ascii_digit);
+        let int = canonical
+            .then(|| std::str::from_utf8(s).ok()?.parse().ok())
+            .flatten();
+        match int {
+            Some(i) => ArrayKey::Int(i),
+            None => ArrayKey::String(PhpString::from(s)),
+        }
+    }
+}
+
+impl From<&str> for ArrayKey {
+    fn from(s: &str) -> Self {
+        ArrayKey::from(s.as_bytes())
+    }
+}
+
+impl fmt::Display for ArrayKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrayKey::Int(i) => write!(f, "{}", i),
+            ArrayKey::String(s) => s.fmt(f),
+        }
+    }
+}
+
+/// An ordered map, PHP's only collection.
+#[derive(Debug, Clone, Default)]
+pub struct Array {
+    entries: Vec<(ArrayKey, Value)>,
+    index: HashMap<ArrayKey, usize>,
+    next_index: i64,
+}
+
+impl Array {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &ArrayKey) -> Option<&Value> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &ArrayKey) -> Option<&mut Value> {
+        self.index.get(key).map(|&i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &ArrayKey) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Sets the value of a key, keeping its position when it already exists.
+    pub fn insert(&mut self, key: ArrayKey, value: Value) {
+        if let ArrayKey::Int(i) = key {
+            if i >= self.next_index {
+                self.next_index = i.saturating_add(1);
+            }
+        }
+        match self.index.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    /// Appends a value with the next integer key, `$a[] = $value`.
+    pub fn push(&mut self, value: Value) {
+        self.insert(ArrayKey::Int(self.next_index), value);
+    }
+
+    pub fn remove(&mut self, key: &ArrayKey) -> Option<Value> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for position in self.index.values_mut() {
+            if *position > i {
+                *position -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    /// The entry at a position, in insertion order.
+    pub fn entry_at(&self, position: usize) -> Option<(&ArrayKey, &Value)> {
+        self.entries.get(position).map(|(key, value)| (key, value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ArrayKey, &Value)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    fn loose_eq(&self, other: &Array) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|v| value.loose_eq(v)))
+    }
+
+    fn compare(&self, other: &Array) -> Option<Ordering> {
+        match self.len().cmp(&other.len()) {
+            Ordering::Equal => {}
+            ordering => return Some(ordering),
+        }
+        for (key, value) in self.iter() {
+            match value.compare(other.get(key)?)? {
+                Ordering::Equal => {}
+                ordering => return Some(ordering),
+            }
+        }
+        Some(Ordering::Equal)
+    }
+}
+
+impl FromIterator<Value> for Array {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        let mut array = Array::new();
+        for value in iter {
+            array.push(value);
+        }
+        array
+    }
+}
+
+/// An instance of a class. Its properties are an array keyed by name.
+#[derive(Debug)]
+pub struct Object {
+    pub class: Rc<Class>,
+    pub props: RefCell<Array>,
+    /// The handle of the object, unique while it is alive, which `spl_object_id` returns.
+    pub id: usize,
+    /// State that only the runtime can see, such as the function of a closure.
+    pub internal: Internal,
+}
+
+#[derive(Debug)]
+pub enum Internal {
+    None,
+    Closure(Closure),
+    Generator(Box<Generator>),
+    /// A `ReflectionAttribute`, with the class its arguments are evaluated in.
+    Attribute(Rc<AttributeDecl>, Option<Rc<Class>>),
+}
+
+impl Object {
+    pub fn new(class: Rc<Class>, props: Array, internal: Internal, id: usize) -> Self {
+        Self {
+            class,
+            props: RefCell::new(props),
+            id,
+            internal,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Value {
+        self.props
+            .borrow()
+            .get(&ArrayKey::String(PhpString::from(name)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, name: &str, value: Value) {
+        self.props
+            .borrow_mut()
+            .insert(ArrayKey::String(PhpString::from(name)), value);
+    }
+
+    /// Whether the object is an instance of the class or interface `name`, given in
+    /// lowercase and without a leading `\`.
+    pub fn instance_of(&self, name: &str) -> bool {
+        self.class.is_subclass_of(name)
+    }
+}
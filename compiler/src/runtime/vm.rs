@@ -0,0 +1,3136 @@
+//! Runs compiled scripts.
+//!
+//! Calls between functions of the script push frames on a stack and never recurse in Rust,
+//! only the runtime calling back into the script does, such as `array_map` calling a closure
+//! or a class constant being evaluated. Throwing unwinds the frames one by one, catching in
+//! the first `catch` that matches and running every `finally` on the way; see `unwind` and
+//! `complete`.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
+
+use crate::syntax::{
+    ast::{
+        class::ClassKind,
+        expr::{BinaryOp, CastKind},
+        stmt::Script,
+        types::{BuiltinType, Type},
+    },
+    diagnostic::Diagnostic,
+    experiments::Experiments,
+    parse::{parse_script_with, parse_snippet_with},
+    resolve::resolve_names,
+    source::SourceFile,
+};
+
+use super::{
+    builtins,
+    class::{
+        is_builtin_final, is_builtin_interface, Class, ClassConst, ConstInit, ConstState, Method,
+        StaticProp, Visibility, BUILTIN_CLASSES,
+    },
+    compile::{
+        compile, Args, AssignKind, Call, Callee, ClassDecl, ClassSpec, ConstDecl, Func, Op, Place,
+        QualifiedName, Root,
+    },
+    enums::{BackingValue, EnumCase, EnumClass},
+    ops,
+    value::{parse_numeric, Array, ArrayKey, Internal, Object, PhpString, Value},
+    RuntimeError,
+};
+
+/// A variable, shared between the scopes that refer to it by reference.
+pub type Cell = Rc<RefCell<Value>>;
+
+fn cell(value: Value) -> Cell {
+    Rc::new(RefCell::new(value))
+}
+
+/// A function or method implemented by the runtime.
+#[derive(Clone, Copy)]
+pub struct Native {
+    pub name: &'static str,
+    pub run: fn(&mut Vm, &mut NativeCall) -> Result<Value, Abort>,
+    /// The parameters taken by reference, which are written back to the variables passed.
+    pub by_ref: &'static [usize],
+    /// The names and types of the leading parameters, each type a union of builtin types.
+    /// Arguments are checked against them as for functions of the script.
+    pub params: &'static [(&'static str, &'static [BuiltinType])],
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Native({})", self.name)
+    }
+}
+
+/// The arguments of a call to a native function or method.
+pub struct NativeCall {
+    pub this: Option<Rc<Object>>,
+    /// The class a static method was called on.
+    pub class: Option<Rc<Class>>,
+    pub args: Vec<Value>,
+}
+
+impl NativeCall {
+    pub fn arg(&self, index: usize) -> Value {
+        self.args.get(index).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Code {
+    User(Rc<Func>),
+    Native(Native),
+}
+
+/// The function of a `Closure` object, with the variables it captured.
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub code: Code,
+    /// The captured variables, with whether each is captured by reference.
+    pub bound: Vec<(Cell, bool)>,
+    pub this: Option<Rc<Object>>,
+    pub scope: Option<Rc<Class>>,
+    pub static_class: Option<Rc<Class>>,
+}
+
+/// The state of a `Generator` object, which keeps the frame of its function while it is
+/// suspended at a `yield`.
+pub struct Generator {
+    state: RefCell<GeneratorState>,
+    /// Where the frame goes when the generator is destroyed while suspended in a `try` with a
+    /// `finally`, for the VM to run the `finally` blocks. `None` while they run.
+    destroyed: Option<Destroyed>,
+}
+
+/// The frames of the generators destroyed while suspended in a `try`, shared by the VM and
+/// every generator it created.
+type Destroyed = Rc<RefCell<Vec<Frame>>>;
+
+struct GeneratorState {
+    /// The suspended frame, taken out while the generator runs and gone once it finished.
+    frame: Option<Frame>,
+    started: bool,
+    running: bool,
+    /// Whether it was resumed after its first `yield`, so it can no longer be rewound.
+    advanced: bool,
+    /// The key and value of the `yield` it is suspended at.
+    current: Option<(Value, Value)>,
+    /// The largest integer key so far, which `yield $value` counts up from.
+    auto_key: i64,
+    /// What a `yield from` that is not done yet delegates to.
+    delegate: Option<Delegate>,
+    returned: Option<Value>,
+}
+
+enum Delegate {
+    Array(Rc<Array>, usize),
+    Generator(Rc<Object>),
+    /// Any other `Iterator`, already rewound.
+    Iterator(Rc<Object>),
+}
+
+/// How a suspended generator is resumed.
+pub(crate) enum Resume {
+    /// Continues with the value as the result of the `yield`, `null` for `next()`.
+    Send(Value),
+    /// Throws the exception from the `yield`.
+    Throw(Rc<Object>),
+}
+
+impl Generator {
+    fn new(frame: Frame, destroyed: Option<Destroyed>) -> Self {
+        Self {
+            state: RefCell::new(GeneratorState {
+                frame: Some(frame),
+                started: false,
+                running: false,
+                advanced: false,
+                current: None,
+                auto_key: -1,
+                delegate: None,
+                returned: None,
+            }),
+            destroyed,
+        }
+    }
+
+    /// The key and value of the current `yield`, `None` once the generator finished.
+    pub(crate) fn current(&self) -> Option<(Value, Value)> {
+        self.state.borrow().current.clone()
+    }
+
+    pub(crate) fn finished(&self) -> bool {
+        let state = self.state.borrow();
+        state.started && !state.running && state.frame.is_none()
+    }
+
+    pub(crate) fn advanced(&self) -> bool {
+        self.state.borrow().advanced
+    }
+
+    /// The value the function returned, `None` until it did.
+    pub(crate) fn returned(&self) -> Option<Value> {
+        self.state.borrow().returned.clone()
+    }
+}
+
+impl Drop for Generator {
+    fn drop(&mut self) {
+        let Some(destroyed) = &self.destroyed else {
+            return;
+        };
+        let state = self.state.get_mut();
+        let Some(frame) = state.frame.take() else {
+            return;
+        };
+        let at = frame.pc.saturating_sub(1);
+        let guarded =
+            |region: &super::compile::Region| region.finally.is_some() && region.guards(at);
+        if state.started && frame.func.regions.iter().any(guarded) {
+            destroyed.borrow_mut().push(frame);
+        }
+    }
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Generator")
+            .field("finished", &self.finished())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The generator state of a `Generator` object.
+pub(crate) fn generator(object: &Object) -> &Generator {
+    match &object.internal {
+        Internal::Generator(generator) => generator,
+        _ => unreachable!("only the runtime creates Generator objects"),
+    }
+}
+
+/// Why the script stopped running the current code.
+#[derive(Debug)]
+pub enum Abort {
+    Throw(Rc<Object>),
+    /// `exit()`, which does not run `finally` blocks.
+    Exit,
+}
+
+/// Why a script failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    Compile(Vec<Diagnostic>),
+    Uncaught(RuntimeError),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Compile(diagnostics) => match diagnostics.first() {
+                Some(diagnostic) => write!(f, "{}", diagnostic),
+                None => f.write_str("compile error"),
+            },
+            ScriptError::Uncaught(error) => write!(f, "Uncaught {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// What a frame is waiting to do once its current `finally` block ends.
+#[derive(Debug)]
+enum Pending {
+    /// The `try` or `catch` block completed.
+    Normal,
+    Throw(Rc<Object>),
+    Return(Value),
+    Jump(usize, u32),
+}
+
+/// What happens to the value a frame returns.
+enum Ret {
+    Value,
+    /// A constructor, which evaluates to the new object.
+    Object(Rc<Object>),
+}
+
+enum Flow {
+    Next,
+    Return(Value),
+    /// The generator running in the current frame was suspended.
+    Yield,
+}
+
+enum Iter {
+    Array {
+        array: Rc<Array>,
+        pos: usize,
+    },
+    /// `foreach ($array as &$value)`, over the array in the first slot.
+    ByRef {
+        array: u32,
+        value: u32,
+        pos: usize,
+        key: Option<ArrayKey>,
+    },
+    Object {
+        object: Rc<Object>,
+        started: bool,
+    },
+}
+
+struct Frame {
+    func: Rc<Func>,
+    pc: usize,
+    locals: Vec<Cell>,
+    /// Variables named at runtime that the function never mentions, `$$name`.
+    extra: HashMap<String, Cell>,
+    stack: Vec<Value>,
+    iters: Vec<Iter>,
+    pending: Vec<Pending>,
+    this: Option<Rc<Object>>,
+    scope: Option<Rc<Class>>,
+    static_class: Option<Rc<Class>>,
+    args: Vec<Value>,
+    /// Whether each parameter was passed.
+    passed: Vec<bool>,
+    ret: Ret,
+}
+
+impl Frame {
+    fn new(
+        func: Rc<Func>,
+        this: Option<Rc<Object>>,
+        scope: Option<Rc<Class>>,
+        static_class: Option<Rc<Class>>,
+    ) -> Self {
+        Self {
+            locals: (0..func.locals.len()).map(|_| Cell::default()).collect(),
+            passed: vec![false; func.params.len()],
+            func,
+            pc: 0,
+            extra: HashMap::new(),
+            stack: Vec::new(),
+            iters: Vec::new(),
+            pending: Vec::new(),
+            this,
+            scope,
+            static_class,
+            args: Vec::new(),
+            ret: Ret::Value,
+        }
+    }
+
+    /// Closes the `foreach` loops above the given number, writing back the last element of a
+    /// loop by reference.
+    fn truncate_iters(&mut self, len: u32) {
+        while self.iters.len() > len as usize {
+            if let Some(iter) = self.iters.pop() {
+                self.write_back(&iter);
+            }
+        }
+    }
+
+    fn write_back(&mut self, iter: &Iter) {
+        if let Iter::ByRef {
+            array,
+            value,
+            key: Some(key),
+            ..
+        } = iter
+        {
+            let value = self.locals[*value as usize].borrow().clone();
+            if let Value::Array(array) = &mut *self.locals[*array as usize].borrow_mut() {
+                Rc::make_mut(array).insert(key.clone(), value);
+            }
+        }
+    }
+
+    fn var(&mut self, name: &str, create: bool) -> Option<Cell> {
+        if let Some(slot) = self.func.locals.iter().position(|local| local == name) {
+            return Some(self.locals[slot].clone());
+        }
+        match create {
+            true => Some(self.extra.entry(name.to_string()).or_default().clone()),
+            false => self.extra.get(name).cloned(),
+        }
+    }
+}
+
+/// A resolved callee.
+struct Target {
+    code: Code,
+    this: Option<Rc<Object>>,
+    scope: Option<Rc<Class>>,
+    static_class: Option<Rc<Class>>,
+    bound: Vec<(Cell, bool)>,
+    /// The method name passed to `__call` or `__callStatic` when this is one of them.
+    magic: Option<String>,
+}
+
+impl Target {
+    fn function(code: Code) -> Self {
+        Self {
+            code,
+            this: None,
+            scope: None,
+            static_class: None,
+            bound: Vec::new(),
+            magic: None,
+        }
+    }
+}
+
+pub struct Vm {
+    file: SourceFile,
+    output: Vec<u8>,
+    frames: Vec<Frame>,
+    main: Option<Rc<Func>>,
+    /// The global variables once the main function has returned, for the exception handler.
+    globals: HashMap<String, Cell>,
+    functions: HashMap<String, Code>,
+    classes: HashMap<String, Rc<Class>>,
+    constants: HashMap<String, Value>,
+    /// The class declarations already run, by address, so hoisted ones are not run twice.
+    declared: HashSet<usize>,
+    pub(crate) exception_handler: Option<Value>,
+    /// The generators being run, innermost last, whose frames a `yield` suspends.
+    generators: Vec<Rc<Object>>,
+    /// The generators destroyed since their `finally` blocks last ran.
+    destroyed: Destroyed,
+    /// The callbacks `register_tick_function` registered, with their arguments.
+    pub(crate) tick_functions: Vec<(Value, Vec<Value>)>,
+    /// The statements run under `declare(ticks=N)` since the tick functions last ran.
+    ticks: u32,
+    /// Set while the tick functions run, so their own statements do not tick.
+    ticking: bool,
+    next_object_id: usize,
+    /// The language extensions the scripts it runs may use.
+    experiments: Experiments,
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        let mut vm = Self {
+            file: SourceFile::new("", ""),
+            output: Vec::new(),
+            frames: Vec::new(),
+            main: None,
+            globals: HashMap::new(),
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            constants: HashMap::new(),
+            declared: HashSet::new(),
+            exception_handler: None,
+            generators: Vec::new(),
+            destroyed: Destroyed::default(),
+            tick_functions: Vec::new(),
+            ticks: 0,
+            ticking: false,
+            next_object_id: 1,
+            experiments: Experiments::default(),
+        };
+        for native in builtins::FUNCTIONS {
+            vm.functions
+                .insert(native.name.to_string(), Code::Native(*native));
+        }
+        for (name, parent, interfaces) in BUILTIN_CLASSES {
+            let lname = name.to_ascii_lowercase();
+            let parent = parent.map(|parent| vm.classes[&parent.to_ascii_lowercase()].clone());
+            let mut all = parent
+                .as_ref()
+                .map_or_else(Vec::new, |parent| parent.interfaces.clone());
+            for interface in *interfaces {
+                let interface = &vm.classes[&interface.to_ascii_lowercase()];
+                all.push(interface.lname());
+                all.extend(interface.interfaces.iter().cloned());
+            }
+            let mut methods = parent
+                .as_ref()
+                .map_or_else(HashMap::new, |parent| parent.methods.clone());
+            for (method, native) in builtins::class_methods(name) {
+                methods.insert(
+                    method.to_ascii_lowercase(),
+                    Rc::new(Method {
+                        name: method.to_string(),
+                        scope: lname.clone(),
+                        is_static: false,
+                        is_final: false,
+                        body: Some(Code::Native(native)),
+                    }),
+                );
+            }
+            let kind = match is_builtin_interface(name) {
+                true => ClassKind::Interface,
+                false => ClassKind::Class,
+            };
+            let consts = builtins::class_constants(name)
+                .into_iter()
+                .map(|(constant, value)| {
+                    let init = ClassConst::new(lname.clone(), ConstInit::Builtin);
+                    *init.state.borrow_mut() = ConstState::Done(value);
+                    (constant.to_string(), Rc::new(init))
+                })
+                .collect();
+            let class = Class::new(
+                name.to_string(),
+                kind,
+                false,
+                is_builtin_final(name),
+                parent,
+                all,
+                methods,
+                consts,
+                Vec::new(),
+                HashMap::new(),
+                HashMap::new(),
+                None,
+                Vec::new(),
+            );
+            vm.classes.insert(lname, Rc::new(class));
+        }
+        vm
+    }
+
+    /// Lets the scripts it runs use the enabled `experiments`.
+    pub fn with_experiments(mut self, experiments: Experiments) -> Self {
+        self.experiments = experiments;
+        self
+    }
+
+    /// Compiles and runs a script, which starts with inline HTML like a PHP file. Whatever it
+    /// echoed is in `output`, even when it fails.
+    pub fn run(&mut self, file: SourceFile) -> Result<(), ScriptError> {
+        let parsed = parse_script_with(file.src(), self.experiments);
+        self.run_parsed(file, parsed)
+    }
+
+    /// Runs PHP code that is not preceded by `<?php`, as `php -r` does, like `run` otherwise.
+    pub fn run_snippet(&mut self, file: SourceFile) -> Result<(), ScriptError> {
+        let parsed = parse_snippet_with(file.src(), self.experiments);
+        self.run_parsed(file, parsed)
+    }
+
+    fn run_parsed(
+        &mut self,
+        file: SourceFile,
+        (mut script, mut diagnostics): (Script, Vec<Diagnostic>),
+    ) -> Result<(), ScriptError> {
+        if diagnostics.iter().all(|d| !d.is_error()) {
+            diagnostics.extend(resolve_names(&mut script));
+        }
+        diagnostics.retain(Diagnostic::is_error);
+        if !diagnostics.is_empty() {
+            return Err(ScriptError::Compile(diagnostics));
+        }
+        let main = compile(&script, &file).map_err(ScriptError::Compile)?;
+        self.file = file;
+        self.main = Some(main.clone());
+        self.frames.push(Frame::new(main, None, None, None));
+        let result = self.execute(0);
+        if let Some(main) = self.frames.first_mut() {
+            for (slot, name) in main.func.locals.iter().enumerate() {
+                self.globals.insert(name.clone(), main.locals[slot].clone());
+            }
+            self.globals.extend(main.extra.drain());
+        }
+        self.frames.clear();
+        let result = result.and_then(|_| {
+            self.globals.clear();
+            self.finish_destroyed_generators()
+        });
+        match result {
+            Ok(_) | Err(Abort::Exit) => Ok(()),
+            Err(Abort::Throw(exception)) => self.uncaught(exception),
+        }
+    }
+
+    /// Everything the script echoed so far, as bytes since strings need not be UTF-8.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Hands an exception nobody caught to the handler set with `set_exception_handler`,
+    /// which ends the script either way.
+    fn uncaught(&mut self, exception: Rc<Object>) -> Result<(), ScriptError> {
+        let exception = match self.exception_handler.take() {
+            Some(handler) => match self.call_value(&handler, vec![Value::Object(exception)]) {
+                Ok(_) | Err(Abort::Exit) => return Ok(()),
+                Err(Abort::Throw(exception)) => exception,
+            },
+            None => exception,
+        };
+        let message = exception.get("message").to_php_string().unwrap_or_default();
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            exception.class.name.clone(),
+            message.to_string(),
+        )))
+    }
+
+    pub(crate) fn echo(&mut self, text: &[u8]) {
+        self.output.extend_from_slice(text);
+    }
+
+    pub(crate) fn next_object_id(&mut self) -> usize {
+        let id = self.next_object_id;
+        self.next_object_id += 1;
+        id
+    }
+
+    pub(crate) fn class(&self, name: &str) -> Option<Rc<Class>> {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+        self.classes.get(&name.to_ascii_lowercase()).cloned()
+    }
+
+    pub(crate) fn function(&self, name: &str) -> Option<Code> {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+        self.functions.get(&name.to_ascii_lowercase()).cloned()
+    }
+
+    pub(crate) fn has_function(&self, name: &str) -> bool {
+        self.function(name).is_some()
+    }
+
+    /// The arguments passed to the function running, for `func_get_args`.
+    pub(crate) fn current_args(&self) -> Vec<Value> {
+        self.frames
+            .last()
+            .map_or_else(Vec::new, |frame| frame.args.clone())
+    }
+
+    pub(crate) fn define(&mut self, name: &str, value: Value) -> bool {
+        if self.constants.contains_key(name) {
+            return false;
+        }
+        self.constants.insert(name.to_string(), value);
+        true
+    }
+
+    pub(crate) fn constant_value(&self, name: &str) -> Option<Value> {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+        self.constants
+            .get(name)
+            .cloned()
+            .or_else(|| builtins::constant(name))
+    }
+
+    /// The line of the code running.
+    fn line(&self) -> i64 {
+        let Some(frame) = self.frames.last() else {
+            return 0;
+        };
+        let span = frame.func.spans[frame.pc.saturating_sub(1)];
+        self.file.line_index(span.start) as i64 + 1
+    }
+
+    /// An exception of a builtin class, thrown by the runtime itself.
+    pub(crate) fn error(&mut self, class: &str, message: impl Into<String>) -> Abort {
+        let class = self
+            .class(class)
+            .unwrap_or_else(|| panic!("{} is a builtin class", class));
+        let object = self.new_throwable(class);
+        object.set("message", Value::string(message.into()));
+        Abort::Throw(object)
+    }
+
+    /// Throws an error the runtime reported without access to the VM.
+    pub(crate) fn runtime_error(&mut self, error: RuntimeError) -> Abort {
+        self.error(&error.class, error.message)
+    }
+
+    fn new_throwable(&mut self, class: Rc<Class>) -> Rc<Object> {
+        let mut props = Array::new();
+        props.insert(ArrayKey::from("message"), Value::string(""));
+        props.insert(ArrayKey::from("code"), Value::Int(0));
+        props.insert(ArrayKey::from("file"), Value::string(self.file.name()));
+        props.insert(ArrayKey::from("line"), Value::Int(self.line()));
+        props.insert(ArrayKey::from("previous"), Value::Null);
+        let id = self.next_object_id();
+        Rc::new(Object::new(class, props, Internal::None, id))
+    }
+
+    /// Runs frames until the one at `base` returns, giving its value. A throw that is not
+    /// caught above `base` is returned to the caller, which unwinds its own frames.
+    fn execute(&mut self, base: usize) -> Result<Value, Abort> {
+        loop {
+            let flow = match self.finish_destroyed_generators() {
+                Ok(()) => self.step(),
+                Err(abort) => Err(abort),
+            };
+            match flow {
+                Ok(Flow::Next) => {}
+                Ok(Flow::Return(value)) => {
+                    let frame = self.frames.pop().expect("a frame returned");
+                    let value = match frame.ret {
+                        Ret::Value => value,
+                        Ret::Object(object) => Value::Object(object),
+                    };
+                    if self.frames.len() <= base {
+                        return Ok(value);
+                    }
+                    self.push(value);
+                }
+                // only the frame of a generator yields, and it is always the one at `base`.
+                Ok(Flow::Yield) => return Ok(Value::Null),
+                Err(Abort::Throw(exception)) => self.unwind(exception, base)?,
+                Err(Abort::Exit) => return Err(Abort::Exit),
+            }
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("a frame is running")
+    }
+
+    fn push(&mut self, value: Value) {
+        self.frame().stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.frame().stack.pop().expect("the stack has a value")
+    }
+
+    /// Finds the `catch` or `finally` for an exception, from the innermost `try` of the
+    /// current frame outwards and then in the callers, popping the frames it leaves.
+    fn unwind(&mut self, exception: Rc<Object>, base: usize) -> Result<(), Abort> {
+        loop {
+            let frame = self.frame();
+            let at = frame.pc.saturating_sub(1);
+            let func = frame.func.clone();
+            for region in &func.regions {
+                if region.in_finally(at) {
+                    // an exception thrown by a finally block replaces whatever it was doing.
+                    frame.pending.pop();
+                    continue;
+                }
+                if (region.try_start..region.try_end).contains(&at) {
+                    let catch = region
+                        .catches
+                        .iter()
+                        .find(|catch| catch.types.iter().any(|name| exception.instance_of(name)));
+                    if let Some(catch) = catch {
+                        frame.stack.clear();
+                        frame.truncate_iters(region.iters);
+                        if let Some(slot) = catch.slot {
+                            *frame.locals[slot as usize].borrow_mut() = Value::Object(exception);
+                        }
+                        frame.pc = catch.start;
+                        return Ok(());
+                    }
+                }
+                if let (true, Some((start, _))) = (region.guards(at), region.finally) {
+                    frame.stack.clear();
+                    frame.truncate_iters(region.iters);
+                    frame.pending.push(Pending::Throw(exception));
+                    frame.pc = start;
+                    return Ok(());
+                }
+            }
+            self.frames.pop();
+            if self.frames.len() <= base {
+                return Err(Abort::Throw(exception));
+            }
+        }
+    }
+
+    /// Returns or jumps from the current position, running the `finally` blocks of the `try`
+    /// statements it leaves first. A `return` inside a `finally` block discards what the
+    /// block was doing, including an exception.
+    fn complete(&mut self, action: Pending) -> Result<Flow, Abort> {
+        let (value, target) = match action {
+            Pending::Normal => return Ok(Flow::Next),
+            Pending::Throw(exception) => return Err(Abort::Throw(exception)),
+            Pending::Return(value) => (Some(value), None),
+            Pending::Jump(target, iters) => (None, Some((target, iters))),
+        };
+        let frame = self.frame();
+        let at = frame.pc - 1;
+        let func = frame.func.clone();
+        let stays_inside = |region: &super::compile::Region| {
+            target.is_some_and(|(target, _)| region.contains(target))
+        };
+        for region in &func.regions {
+            if region.in_finally(at) {
+                if stays_inside(region) {
+                    break;
+                }
+                frame.pending.pop();
+                continue;
+            }
+            if !region.guards(at) {
+                continue;
+            }
+            if stays_inside(region) {
+                break;
+            }
+            let Some((start, _)) = region.finally else {
+                continue;
+            };
+            frame.stack.clear();
+            frame.truncate_iters(region.iters);
+            frame.pending.push(match (value, target) {
+                (Some(value), _) => Pending::Return(value),
+                (None, Some((target, iters))) => Pending::Jump(target, iters),
+                (None, None) => unreachable!("either a return or a jump"),
+            });
+            frame.pc = start;
+            return Ok(Flow::Next);
+        }
+        match (value, target) {
+            (Some(value), _) => {
+                frame.truncate_iters(0);
+                Ok(Flow::Return(value))
+            }
+            (None, Some((target, iters))) => {
+                frame.truncate_iters(iters);
+                frame.pc = target;
+                Ok(Flow::Next)
+            }
+            (None, None) => unreachable!("either a return or a jump"),
+        }
+    }
+
+    fn step(&mut self) -> Result<Flow, Abort> {
+        let frame = self.frame();
+        let func = frame.func.clone();
+        let pc = frame.pc;
+        frame.pc += 1;
+        match &func.code[pc] {
+            Op::Const(value) => self.push(value.clone()),
+            Op::Pop => {
+                self.pop();
+            }
+            Op::Dup => {
+                let value = self.frame().stack.last().cloned().unwrap_or_default();
+                self.push(value);
+            }
+            Op::Load(slot) => {
+                let value = self.frame().locals[*slot as usize].borrow().clone();
+                self.push(value);
+            }
+            Op::LoadDyn => {
+                let name = self.pop();
+                let name = self.stringify(&name)?.to_string();
+                let value = match name.as_str() {
+                    "this" => self.this()?,
+                    _ => match self.frame().var(&name, false) {
+                        Some(cell) => cell.borrow().clone(),
+                        None => Value::Null,
+                    },
+                };
+                self.push(value);
+            }
+            Op::LoadThis => {
+                let this = self.this()?;
+                self.push(this);
+            }
+            Op::Store(place, kind) => {
+                let value = self.store(place, *kind)?;
+                self.push(value);
+            }
+            Op::BindRef(target, source) => {
+                let frame = self.frame();
+                frame.locals[*target as usize] = frame.locals[*source as usize].clone();
+            }
+            Op::Unset(place) => self.unset(place)?,
+            Op::FetchDim => {
+                let key = self.pop();
+                let container = self.pop();
+                let value = self.fetch_dim(container, key)?;
+                self.push(value);
+            }
+            Op::FetchProp(name) => {
+                let name = match name {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.pop();
+                        self.stringify(&name)?.to_string()
+                    }
+                };
+                let object = self.pop();
+                let value = self.fetch_prop(object, &name)?;
+                self.push(value);
+            }
+            Op::FetchStaticProp(spec, name) => {
+                let class = self.resolve_class(spec)?;
+                let cell = self.static_prop(&class, name)?;
+                let value = cell.borrow().clone();
+                self.push(value);
+            }
+            Op::FetchClassConst(spec, name) => {
+                let class = self.resolve_class(spec)?;
+                let value = self.class_const(&class, name)?;
+                self.push(value);
+            }
+            Op::ClassName(spec) => {
+                let class = self.resolve_class(spec)?;
+                self.push(Value::string(class.name.as_str()));
+            }
+            Op::FetchConst(name) => {
+                let value = self.constant(name)?;
+                self.push(value);
+            }
+            Op::DeclareConst(name) => {
+                let value = self.pop();
+                self.define(name, value);
+            }
+            Op::NewArray => self.push(Value::array(Array::new())),
+            Op::ArrayPush => {
+                let value = self.pop();
+                self.with_array(|array| array.push(value));
+            }
+            Op::ArrayInsert => {
+                let value = self.pop();
+                let key = self.pop();
+                let key = self.array_key(&key)?;
+                self.with_array(|array| array.insert(key, value));
+            }
+            Op::ArraySpread => {
+                let value = self.pop();
+                let Value::Array(items) = value else {
+                    return Err(self.error("Error", "Only arrays can be unpacked"));
+                };
+                self.with_array(|array| {
+                    for (key, value) in items.iter() {
+                        match key {
+                            ArrayKey::Int(_) => array.push(value.clone()),
+                            ArrayKey::String(_) => array.insert(key.clone(), value.clone()),
+                        }
+                    }
+                });
+            }
+            Op::Interpolate(count) => {
+                let at = self.frame().stack.len() - *count as usize;
+                let parts = self.frame().stack.split_off(at);
+                let mut text = PhpString::default();
+                for part in &parts {
+                    text.push_bytes(&self.stringify(part)?);
+                }
+                self.push(Value::String(text));
+            }
+            Op::Binary(op) => {
+                let right = self.pop();
+                let left = self.pop();
+                let value = self.binary(*op, left, right)?;
+                self.push(value);
+            }
+            Op::Unary(op) => {
+                let value = self.pop();
+                let value = ops::unary(*op, &value).map_err(|e| self.error(e.0, e.1))?;
+                self.push(value);
+            }
+            Op::Cast(kind) => {
+                let value = self.pop();
+                let value = self.cast(value, *kind)?;
+                self.push(value);
+            }
+            Op::IsSet => {
+                let value = self.pop();
+                self.push(Value::Bool(!value.is_null()));
+            }
+            Op::InstanceOf(spec) => {
+                let name = match spec {
+                    ClassSpec::Named(name) => Some(name.to_ascii_lowercase()),
+                    ClassSpec::Dynamic => match self.pop() {
+                        Value::Object(object) => Some(object.class.lname()),
+                        Value::String(name) => {
+                            Some(name.to_str().trim_start_matches('\\').to_lowercase())
+                        }
+                        _ => {
+                            return Err(self
+                                .error("Error", "Class name must be a valid object or a string"))
+                        }
+                    },
+                    spec => Some(self.resolve_class(spec)?.lname()),
+                };
+                let value = self.pop();
+                let result = match (value, name) {
+                    (Value::Object(object), Some(name)) => object.instance_of(&name),
+                    _ => false,
+                };
+                self.push(Value::Bool(result));
+            }
+            Op::Clone => {
+                let value = self.pop();
+                let Value::Object(object) = value else {
+                    return Err(self.error("Error", "__clone method called on non-object"));
+                };
+                let internal = match &object.internal {
+                    Internal::Closure(closure) => Internal::Closure(closure.clone()),
+                    Internal::Generator(_) | Internal::Attribute(..) => {
+                        let message = format!(
+                            "Trying to clone an uncloneable object of class {}",
+                            object.class.name
+                        );
+                        return Err(self.error("Error", message));
+                    }
+                    Internal::None => Internal::None,
+                };
+                let id = self.next_object_id();
+                let copy = Rc::new(Object::new(
+                    object.class.clone(),
+                    object.props.borrow().clone(),
+                    internal,
+                    id,
+                ));
+                match object.class.find_method("__clone").cloned() {
+                    Some(method) => {
+                        let target = self.method_target_of(&method, copy.clone())?;
+                        self.invoke(target, Vec::new(), Vec::new(), &[], Ret::Object(copy))?;
+                    }
+                    None => self.push(Value::Object(copy)),
+                }
+            }
+            Op::Echo => {
+                let value = self.pop();
+                let text = self.stringify(&value)?;
+                self.output.extend_from_slice(&text);
+            }
+            Op::Exit => {
+                let value = self.pop();
+                if let Value::String(text) = value {
+                    self.output.extend_from_slice(&text);
+                }
+                return Err(Abort::Exit);
+            }
+            Op::Jump(target) => self.frame().pc = *target,
+            Op::JumpIfFalse(target) => {
+                if !self.pop().to_bool() {
+                    self.frame().pc = *target;
+                }
+            }
+            Op::JumpIfTrue(target) => {
+                if self.pop().to_bool() {
+                    self.frame().pc = *target;
+                }
+            }
+            Op::JumpIfNotNull(target) => {
+                if !self.pop().is_null() {
+                    self.frame().pc = *target;
+                }
+            }
+            Op::JumpIfNull(target) => {
+                let frame = self.frame();
+                if frame.stack.last().is_none_or(Value::is_null) {
+                    frame.pc = *target;
+                }
+            }
+            Op::JumpIfArg(param, target) => {
+                let frame = self.frame();
+                if frame.passed.get(*param as usize) == Some(&true) {
+                    frame.pc = *target;
+                }
+            }
+            Op::Call(call) => self.call(call)?,
+            Op::New(spec, args) => self.new_object(spec, args)?,
+            Op::MakeClosure(func, bindings) => {
+                let frame = self.frame();
+                let bound = bindings
+                    .iter()
+                    .map(|(slot, by_ref)| {
+                        let captured = &frame.locals[*slot as usize];
+                        match by_ref {
+                            true => (captured.clone(), true),
+                            false => (cell(captured.borrow().clone()), false),
+                        }
+                    })
+                    .collect();
+                let closure = Closure {
+                    code: Code::User(func.clone()),
+                    bound,
+                    this: frame.this.clone().filter(|_| !func.is_static),
+                    scope: frame.scope.clone(),
+                    static_class: frame.static_class.clone(),
+                };
+                let value = self.closure_object(closure);
+                self.push(value);
+            }
+            Op::DeclareFunction(func) => {
+                let name = func.name.to_ascii_lowercase();
+                if self.functions.contains_key(&name) {
+                    return Err(self.error("Error", format!("Cannot redeclare {}()", func.name)));
+                }
+                self.functions.insert(name, Code::User(func.clone()));
+            }
+            Op::DeclareClass(decl, hoisted) => self.declare_class(decl, *hoisted)?,
+            Op::Return => {
+                let mut value = self.pop();
+                if let (Some(ty), false) = (&func.returns, func.generator) {
+                    value = self.check_return(&func, ty, value)?;
+                }
+                return self.complete(Pending::Return(value));
+            }
+            Op::Throw => {
+                let value = self.pop();
+                return match value {
+                    Value::Object(object) if object.instance_of("throwable") => {
+                        Err(Abort::Throw(object))
+                    }
+                    _ => Err(self.error("Error", "Can only throw objects")),
+                };
+            }
+            Op::Leave(target, iters) => return self.complete(Pending::Jump(*target, *iters)),
+            Op::EnterFinally(target) => {
+                let frame = self.frame();
+                frame.pending.push(Pending::Normal);
+                frame.pc = *target;
+            }
+            Op::EndFinally => {
+                let pending = self.frame().pending.pop().unwrap_or(Pending::Normal);
+                return self.complete(pending);
+            }
+            Op::IterInit(by_ref) => self.iter_init(*by_ref)?,
+            Op::IterNext(exit, with_key) => self.iter_next(*exit, *with_key)?,
+            Op::IterPop => {
+                let frame = self.frame();
+                frame.truncate_iters(frame.iters.len() as u32 - 1);
+            }
+            Op::BindStatic(slot, skip) => {
+                let existing = func.statics.borrow().get(slot).cloned();
+                let frame = self.frame();
+                match existing {
+                    Some(existing) => {
+                        frame.locals[*slot as usize] = existing;
+                        frame.pc = *skip;
+                    }
+                    None => {
+                        let created = Cell::default();
+                        func.statics.borrow_mut().insert(*slot, created.clone());
+                        frame.locals[*slot as usize] = created;
+                    }
+                }
+            }
+            Op::BindGlobal(slot) => {
+                let name = func.locals[*slot as usize].clone();
+                let global = self.global(&name);
+                self.frame().locals[*slot as usize] = global;
+            }
+            Op::Yield(with_key) => {
+                let value = self.pop();
+                let key = with_key.then(|| self.pop());
+                let object = self.generators.last().expect("a generator is running");
+                let mut state = generator(object).state.borrow_mut();
+                let key = match key {
+                    Some(key) => {
+                        if let Value::Int(i) = key {
+                            state.auto_key = state.auto_key.max(i);
+                        }
+                        key
+                    }
+                    None => {
+                        state.auto_key += 1;
+                        Value::Int(state.auto_key)
+                    }
+                };
+                state.current = Some((key, value));
+                return Ok(Flow::Yield);
+            }
+            Op::Await => {
+                let value = self.pop();
+                let value = self.await_value(value)?;
+                self.push(value);
+            }
+            Op::YieldFrom => {
+                let source = self.pop();
+                let mut delegate = self.delegate(source)?;
+                match self.delegate_current(&mut delegate)? {
+                    Some(current) => {
+                        let object = self.generators.last().expect("a generator is running");
+                        let mut state = generator(object).state.borrow_mut();
+                        state.delegate = Some(delegate);
+                        state.current = Some(current);
+                        return Ok(Flow::Yield);
+                    }
+                    None => {
+                        let result = delegate_result(&delegate);
+                        self.push(result);
+                    }
+                }
+            }
+            Op::Tick(every) => {
+                self.ticks += 1;
+                if self.ticks >= *every && !self.ticking {
+                    self.ticks = 0;
+                    self.ticking = true;
+                    let result =
+                        self.tick_functions
+                            .clone()
+                            .into_iter()
+                            .try_for_each(|(callback, args)| {
+                                self.call_value(&callback, args).map(drop)
+                            });
+                    self.ticking = false;
+                    result?;
+                }
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn this(&mut self) -> Result<Value, Abort> {
+        match self.frame().this.clone() {
+            Some(this) => Ok(Value::Object(this)),
+            None => Err(self.error("Error", "Using $this when not in object context")),
+        }
+    }
+
+    /// The global variable of a name, created when it does not exist.
+    fn global(&mut self, name: &str) -> Cell {
+        let main = self.main.clone();
+        match self.frames.first_mut() {
+            Some(frame) if main.is_some_and(|main| Rc::ptr_eq(&main, &frame.func)) => {
+                frame.var(name, true).expect("created when missing")
+            }
+            _ => self.globals.entry(name.to_string()).or_default().clone(),
+        }
+    }
+
+    /// Changes the array on top of the stack.
+    fn with_array(&mut self, change: impl FnOnce(&mut Array)) {
+        if let Some(Value::Array(array)) = self.frame().stack.last_mut() {
+            change(Rc::make_mut(array));
+        }
+    }
+
+    fn array_key(&mut self, key: &Value) -> Result<ArrayKey, Abort> {
+        ArrayKey::from_value(key).ok_or_else(|| {
+            let message = format!("Cannot access offset of type {} on array", key.type_name());
+            self.error("TypeError", message)
+        })
+    }
+
+    /// Converts a value to a string, calling `__toString` on objects.
+    pub(crate) fn stringify(&mut self, value: &Value) -> Result<PhpString, Abort> {
+        match value {
+            Value::Array(_) => Ok(PhpString::from("Array")),
+            Value::Object(object) => {
+                if object.class.find_method("__tostring").is_none() {
+                    let message = format!(
+                        "Object of class {} could not be converted to string",
+                        object.class.name
+                    );
+                    return Err(self.error("Error", message));
+                }
+                let result = self.call_method(object.clone(), "__toString", Vec::new())?;
+                Ok(result.to_php_string().unwrap_or_default())
+            }
+            _ => Ok(value.to_php_string().unwrap_or_default()),
+        }
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: Value, right: Value) -> Result<Value, Abort> {
+        let (left, right) = match op {
+            BinaryOp::Concat => (
+                Value::String(self.stringify(&left)?),
+                Value::String(self.stringify(&right)?),
+            ),
+            _ => (left, right),
+        };
+        ops::binary(op, &left, &right).map_err(|e| self.error(e.0, e.1))
+    }
+
+    fn cast(&mut self, value: Value, kind: CastKind) -> Result<Value, Abort> {
+        Ok(match kind {
+            CastKind::Int => Value::Int(value.to_int()),
+            CastKind::Float => Value::Float(value.to_float()),
+            CastKind::Bool => Value::Bool(value.to_bool()),
+            CastKind::String => Value::String(self.stringify(&value)?),
+            CastKind::Array => match value {
+                Value::Array(_) => value,
+                Value::Null => Value::array(Array::new()),
+                Value::Object(object) if !matches!(object.internal, Internal::Closure(_)) => {
+                    Value::array(object.props.borrow().clone())
+                }
+                value => Value::array(std::iter::once(value).collect()),
+            },
+            CastKind::Object => match value {
+                Value::Object(_) => value,
+                value => {
+                    let props = match value {
+                        Value::Array(array) => {
+                            let mut props = Array::new();
+                            for (key, value) in array.iter() {
+                                let name = match key {
+                                    ArrayKey::Int(i) => PhpString::from(i.to_string()),
+                                    ArrayKey::String(name) => name.clone(),
+                                };
+                                props.insert(ArrayKey::String(name), value.clone());
+                            }
+                            props
+                        }
+                        Value::Null => Array::new(),
+                        value => {
+                            let mut props = Array::new();
+                            props.insert(ArrayKey::from("scalar"), value);
+                            props
+                        }
+                    };
+                    let class = self.class("stdClass").expect("stdClass is builtin");
+                    let id = self.next_object_id();
+                    Value::Object(Rc::new(Object::new(class, props, Internal::None, id)))
+                }
+            },
+            CastKind::Unset => Value::Null,
+        })
+    }
+
+    fn fetch_dim(&mut self, container: Value, key: Value) -> Result<Value, Abort> {
+        match container {
+            Value::Array(array) => {
+                let key = self.array_key(&key)?;
+                Ok(array.get(&key).cloned().unwrap_or_default())
+            }
+            Value::String(text) => {
+                let len = text.len() as i64;
+                let index = key.to_int();
+                let index = if index < 0 { len + index } else { index };
+                Ok(match (0..len).contains(&index) {
+                    true => Value::string(&text[index as usize..=index as usize]),
+                    false => Value::string(""),
+                })
+            }
+            Value::Object(object) if object.instance_of("arrayaccess") => {
+                self.call_method(object, "offsetGet", vec![key])
+            }
+            Value::Object(object) => {
+                let message = format!("Cannot use object of type {} as array", object.class.name);
+                Err(self.error("Error", message))
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    fn fetch_prop(&mut self, object: Value, name: &str) -> Result<Value, Abort> {
+        let Value::Object(object) = object else {
+            return Ok(Value::Null);
+        };
+        if let Some(visibility) = self.hidden_prop(&object.class, name) {
+            if object.class.find_method("__get").is_none() {
+                return Err(self.prop_access_error(&object.class, name, visibility));
+            }
+            return self.call_method(object, "__get", vec![Value::string(name)]);
+        }
+        let key = ArrayKey::String(PhpString::from(name));
+        if let Some(value) = object.props.borrow().get(&key) {
+            return Ok(value.clone());
+        }
+        match object.class.find_method("__get") {
+            Some(_) => self.call_method(object, "__get", vec![Value::string(name)]),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Assigns to a place, giving the result of the assignment.
+    fn store(&mut self, place: &Place, kind: AssignKind) -> Result<Value, Abort> {
+        let mut value = match kind {
+            AssignKind::Set | AssignKind::Op(_) => self.pop(),
+            _ => Value::Null,
+        };
+        if let AssignKind::Op(BinaryOp::Concat) = kind {
+            value = Value::String(self.stringify(&value)?);
+        }
+        let path = self.pop_path(place)?;
+        let root = match &place.root {
+            Root::Local(slot) => self.frame().locals[*slot as usize].clone(),
+            Root::Dyn => {
+                let name = self.pop();
+                let name = self.stringify(&name)?.to_string();
+                self.frame().var(&name, true).expect("created when missing")
+            }
+            Root::StaticProp(spec, name) => {
+                let class = self.resolve_class(spec)?;
+                self.static_prop(&class, name)?
+            }
+            Root::Prop(name) => {
+                let name = match name {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.pop();
+                        self.stringify(&name)?.to_string()
+                    }
+                };
+                let object = self.pop();
+                let Value::Object(object) = object else {
+                    let message = format!(
+                        "Attempt to assign property \"{}\" on {}",
+                        name,
+                        object.type_name()
+                    );
+                    return Err(self.error("Error", message));
+                };
+                if let Some(enumeration) = &object.class.enumeration {
+                    let error = case_of(enumeration, &object).set_property(&name);
+                    return Err(self.runtime_error(error.unwrap_err()));
+                }
+                if let Some(visibility) = self.hidden_prop(&object.class, &name) {
+                    return Err(self.prop_access_error(&object.class, &name, visibility));
+                }
+                // properties are not cells, so the value is taken out, changed and put back.
+                let key = ArrayKey::String(PhpString::from(name));
+                let mut current = object
+                    .props
+                    .borrow_mut()
+                    .get_mut(&key)
+                    .map(std::mem::take)
+                    .unwrap_or_default();
+                let result = assign_path(&mut current, &path, kind, value);
+                object.props.borrow_mut().insert(key, current);
+                return result.map_err(|e| self.error(e.0, e.1));
+            }
+        };
+        let result = assign_path(&mut root.borrow_mut(), &path, kind, value);
+        result.map_err(|e| self.error(e.0, e.1))
+    }
+
+    /// Pops the keys of the `[...]` of a place, `None` for `[]`.
+    fn pop_path(&mut self, place: &Place) -> Result<Vec<Option<ArrayKey>>, Abort> {
+        let count = place.dims.iter().filter(|has_key| **has_key).count();
+        let at = self.frame().stack.len() - count;
+        let mut keys = self.frame().stack.split_off(at).into_iter();
+        let mut path = Vec::new();
+        for has_key in &place.dims {
+            path.push(match has_key {
+                true => Some(self.array_key(&keys.next().expect("counted"))?),
+                false => None,
+            });
+        }
+        Ok(path)
+    }
+
+    fn unset(&mut self, place: &Place) -> Result<(), Abort> {
+        let path = self.pop_path(place)?;
+        if path.iter().any(Option::is_none) {
+            return Err(self.error("Error", "Cannot use [] for unsetting"));
+        }
+        let path: Vec<ArrayKey> = path.into_iter().flatten().collect();
+        let root = match &place.root {
+            Root::Local(slot) if path.is_empty() => {
+                self.frame().locals[*slot as usize] = Cell::default();
+                return Ok(());
+            }
+            Root::Local(slot) => self.frame().locals[*slot as usize].clone(),
+            Root::Dyn => {
+                let name = self.pop();
+                let name = self.stringify(&name)?.to_string();
+                match self.frame().var(&name, false) {
+                    Some(cell) => cell,
+                    None => return Ok(()),
+                }
+            }
+            Root::StaticProp(..) if path.is_empty() => {
+                return Err(self.error("Error", "Attempt to unset static property"));
+            }
+            Root::StaticProp(spec, name) => {
+                let class = self.resolve_class(spec)?;
+                self.static_prop(&class, name)?
+            }
+            Root::Prop(name) => {
+                let name = match name {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.pop();
+                        self.stringify(&name)?.to_string()
+                    }
+                };
+                if let Value::Object(object) = self.pop() {
+                    if let Some(enumeration) = &object.class.enumeration {
+                        let result = case_of(enumeration, &object).unset_property(&name);
+                        return result.map_err(|e| self.runtime_error(e));
+                    }
+                    if let Some(visibility) = self.hidden_prop(&object.class, &name) {
+                        return Err(self.prop_access_error(&object.class, &name, visibility));
+                    }
+                    let key = ArrayKey::String(PhpString::from(name));
+                    let mut props = object.props.borrow_mut();
+                    match path.split_last() {
+                        None => {
+                            props.remove(&key);
+                        }
+                        Some(_) => {
+                            if let Some(value) = props.get_mut(&key) {
+                                unset_path(value, &path);
+                            }
+                        }
+                    }
+                }
+                return Ok(());
+            }
+        };
+        unset_path(&mut root.borrow_mut(), &path);
+        Ok(())
+    }
+
+    fn resolve_class(&mut self, spec: &ClassSpec) -> Result<Rc<Class>, Abort> {
+        match spec {
+            ClassSpec::Named(name) => self.find_class(name),
+            ClassSpec::Current => match self.frame().scope.clone() {
+                Some(class) => Ok(class),
+                None => {
+                    Err(self.error("Error", "Cannot use \"self\" when no class scope is active"))
+                }
+            },
+            ClassSpec::Parent => match self.frame().scope.clone() {
+                Some(class) => match class.parent.clone() {
+                    Some(parent) => Ok(parent),
+                    None => Err(self.error(
+                        "Error",
+                        "Cannot use \"parent\" when current class scope has no parent",
+                    )),
+                },
+                None => Err(self.error(
+                    "Error",
+                    "Cannot use \"parent\" when no class scope is active",
+                )),
+            },
+            ClassSpec::Static => match self.frame().static_class.clone() {
+                Some(class) => Ok(class),
+                None => Err(self.error(
+                    "Error",
+                    "Cannot use \"static\" when no class scope is active",
+                )),
+            },
+            ClassSpec::Dynamic => match self.pop() {
+                Value::Object(object) => Ok(object.class.clone()),
+                Value::String(name) => self.find_class(&name.to_str()),
+                _ => Err(self.error("Error", "Cannot use a value of this type as a class name")),
+            },
+        }
+    }
+
+    fn find_class(&mut self, name: &str) -> Result<Rc<Class>, Abort> {
+        match self.class(name) {
+            Some(class) => Ok(class),
+            None => Err(self.error("Error", format!("Class \"{}\" not found", name))),
+        }
+    }
+
+    fn constant(&mut self, name: &QualifiedName) -> Result<Value, Abort> {
+        let found = self.constant_value(&name.name).or_else(|| {
+            name.fallback
+                .as_ref()
+                .and_then(|fallback| self.constant_value(fallback))
+        });
+        match found {
+            Some(value) => Ok(value),
+            None => {
+                let shown = name.fallback.as_ref().unwrap_or(&name.name);
+                Err(self.error("Error", format!("Undefined constant \"{}\"", shown)))
+            }
+        }
+    }
+
+    pub(crate) fn class_const(&mut self, class: &Rc<Class>, name: &str) -> Result<Value, Abort> {
+        let Some(constant) = class.consts.get(name).cloned() else {
+            let message = format!("Undefined constant {}::{}", class.name, name);
+            return Err(self.error("Error", message));
+        };
+        let state = constant.state.borrow().clone();
+        match state {
+            ConstState::Done(value) => Ok(value),
+            ConstState::Evaluating => {
+                let message = format!(
+                    "Cannot declare self-referencing constant {}::{}",
+                    class.name, name
+                );
+                Err(self.error("Error", message))
+            }
+            ConstState::Pending => {
+                *constant.state.borrow_mut() = ConstState::Evaluating;
+                let scope = self.class(&constant.scope);
+                let result = match &constant.init {
+                    ConstInit::Expr(func) => self.run_thunk(func, scope),
+                    ConstInit::Case => self.enum_case(name, scope),
+                    ConstInit::Builtin => unreachable!("builtin constants start out evaluated"),
+                };
+                *constant.state.borrow_mut() = match &result {
+                    Ok(value) => ConstState::Done(value.clone()),
+                    Err(_) => ConstState::Pending,
+                };
+                result
+            }
+        }
+    }
+
+    /// The object of an enum case, created once as the case is cached like a constant.
+    fn enum_case(&mut self, name: &str, class: Option<Rc<Class>>) -> Result<Value, Abort> {
+        let class = class.expect("an enum declares its cases");
+        let enumeration = class.enumeration.as_ref().expect("only enums have cases");
+        let case = enumeration.case(name).expect("the case is declared");
+        let mut props = Array::new();
+        props.insert(ArrayKey::from("name"), Value::string(name));
+        if let Some(value) = case.value() {
+            let value = match value {
+                BackingValue::Int(value) => Value::Int(*value),
+                BackingValue::String(value) => Value::string(value.as_slice()),
+            };
+            props.insert(ArrayKey::from("value"), value);
+        }
+        let id = self.next_object_id();
+        Ok(Value::Object(Rc::new(Object::new(
+            class,
+            props,
+            Internal::None,
+            id,
+        ))))
+    }
+
+    /// Runs the function of a constant expression in the scope of a class.
+    pub(crate) fn run_thunk(
+        &mut self,
+        func: &Rc<Func>,
+        scope: Option<Rc<Class>>,
+    ) -> Result<Value, Abort> {
+        let base = self.frames.len();
+        self.frames
+            .push(Frame::new(func.clone(), None, scope.clone(), scope));
+        self.execute(base)
+    }
+
+    fn static_prop(&mut self, class: &Rc<Class>, name: &str) -> Result<Cell, Abort> {
+        let Some(prop) = class.static_props.get(name).cloned() else {
+            let message = format!(
+                "Access to undeclared static property {}::${}",
+                class.name, name
+            );
+            return Err(self.error("Error", message));
+        };
+        if let Some(visibility) = self.hidden_prop(class, name) {
+            return Err(self.prop_access_error(class, name, visibility));
+        }
+        if !prop.ready.get() {
+            prop.ready.set(true);
+            if let Some(init) = &prop.init {
+                let scope = self.class(&prop.scope);
+                let value = self.run_thunk(init, scope)?;
+                *prop.cell.borrow_mut() = value;
+            }
+        }
+        Ok(prop.cell.clone())
+    }
+
+    /// The visibility of the property `name` of `class` when the running code cannot access
+    /// it: a private one outside the class declaring it, a protected one outside its family.
+    fn hidden_prop(&mut self, class: &Class, name: &str) -> Option<Visibility> {
+        let (visibility, declaring) = class.hidden_props.get(name)?;
+        let scope = self.frame().scope.clone();
+        let allowed = scope.is_some_and(|scope| match visibility {
+            Visibility::Private => scope.lname() == *declaring,
+            _ => {
+                scope.is_subclass_of(declaring)
+                    || self
+                        .class(declaring)
+                        .is_some_and(|declaring| declaring.is_subclass_of(&scope.lname()))
+            }
+        });
+        (!allowed).then_some(*visibility)
+    }
+
+    fn prop_access_error(&mut self, class: &Class, name: &str, visibility: Visibility) -> Abort {
+        let message = format!(
+            "Cannot access {} property {}::${}",
+            visibility.as_str(),
+            class.name,
+            name
+        );
+        self.error("Error", message)
+    }
+
+    fn defaults(&mut self, class: &Rc<Class>) -> Result<Array, Abort> {
+        if let Some(defaults) = class.defaults() {
+            return Ok(defaults);
+        }
+        let mut props = Array::new();
+        for (name, init) in &class.props {
+            let value = match init {
+                Some(init) => self.run_thunk(init, Some(class.clone()))?,
+                None => Value::Null,
+            };
+            props.insert(ArrayKey::String(PhpString::from(name)), value);
+        }
+        class.set_defaults(props.clone());
+        Ok(props)
+    }
+
+    fn instantiate(&mut self, class: &Rc<Class>) -> Result<Rc<Object>, Abort> {
+        let what = match class.kind {
+            ClassKind::Interface => Some("interface"),
+            ClassKind::Trait => Some("trait"),
+            ClassKind::Enum => Some("enum"),
+            ClassKind::Class if class.is_abstract => Some("abstract class"),
+            ClassKind::Class => None,
+        };
+        if let Some(what) = what {
+            let message = format!("Cannot instantiate {} {}", what, class.name);
+            return Err(self.error("Error", message));
+        }
+        if class.name == "Generator" {
+            let message = "The \"Generator\" class is reserved for internal use and cannot be manually instantiated";
+            return Err(self.error("Error", message));
+        }
+        if class.name == "ReflectionAttribute" {
+            let from = match &self.frame().scope {
+                Some(scope) => format!("scope {}", scope.name),
+                None => "global scope".to_string(),
+            };
+            let message = format!(
+                "Call to private ReflectionAttribute::__construct() from {}",
+                from
+            );
+            return Err(self.error("Error", message));
+        }
+        let props = self.defaults(class)?;
+        if class.is_subclass_of("throwable") {
+            let object = self.new_throwable(class.clone());
+            for (key, value) in props.iter() {
+                object.props.borrow_mut().insert(key.clone(), value.clone());
+            }
+            return Ok(object);
+        }
+        let id = self.next_object_id();
+        Ok(Rc::new(Object::new(
+            class.clone(),
+            props,
+            Internal::None,
+            id,
+        )))
+    }
+
+    fn new_object(&mut self, spec: &ClassSpec, args: &Args) -> Result<(), Abort> {
+        let (args, named, refs) = self.pop_args(args)?;
+        let class = self.resolve_class(spec)?;
+        let object = self.instantiate(&class)?;
+        match class.find_method("__construct").cloned() {
+            Some(method) => {
+                let mut target = self.method_target_of(&method, object.clone())?;
+                target.static_class = Some(class);
+                self.invoke(target, args, named, &refs, Ret::Object(object))
+            }
+            None => {
+                self.push(Value::Object(object));
+                Ok(())
+            }
+        }
+    }
+
+    fn generator_object(&mut self, frame: Frame) -> Value {
+        let class = self.class("Generator").expect("Generator is builtin");
+        let id = self.next_object_id();
+        Value::Object(Rc::new(Object::new(
+            class,
+            Array::new(),
+            Internal::Generator(Box::new(Generator::new(
+                frame,
+                Some(self.destroyed.clone()),
+            ))),
+            id,
+        )))
+    }
+
+    /// Runs a `Generator` to completion for `await`, giving what it returned. Any other value
+    /// is the result itself.
+    fn await_value(&mut self, value: Value) -> Result<Value, Abort> {
+        let Value::Object(object) = &value else {
+            return Ok(value);
+        };
+        if !matches!(object.internal, Internal::Generator(_)) {
+            return Ok(value);
+        }
+        self.start_generator(object)?;
+        while generator(object).current().is_some() {
+            self.resume_generator(object, Resume::Send(Value::Null))?;
+        }
+        Ok(generator(object).returned().unwrap_or_default())
+    }
+
+    /// Runs a generator up to its first `yield`, unless it already started.
+    pub(crate) fn start_generator(&mut self, object: &Rc<Object>) -> Result<(), Abort> {
+        let frame = {
+            let mut state = generator(object).state.borrow_mut();
+            if state.started {
+                return Ok(());
+            }
+            state.started = true;
+            state.running = true;
+            state
+                .frame
+                .take()
+                .expect("a generator has a frame until it runs")
+        };
+        self.run_generator(object, frame, None)
+    }
+
+    /// Resumes a started generator from the `yield` it is suspended at, up to the next one.
+    /// A `yield from` passes what it is resumed with on to what it delegates to.
+    pub(crate) fn resume_generator(
+        &mut self,
+        object: &Rc<Object>,
+        resume: Resume,
+    ) -> Result<(), Abort> {
+        let (mut frame, delegate) = {
+            let mut state = generator(object).state.borrow_mut();
+            if state.running {
+                drop(state);
+                return Err(self.error("Error", "Cannot resume an already running generator"));
+            }
+            let Some(frame) = state.frame.take() else {
+                // a finished generator throws in the caller instead.
+                return match resume {
+                    Resume::Send(_) => Ok(()),
+                    Resume::Throw(exception) => Err(Abort::Throw(exception)),
+                };
+            };
+            state.running = true;
+            state.advanced = true;
+            (frame, state.delegate.take())
+        };
+        let Some(mut delegate) = delegate else {
+            return match resume {
+                Resume::Send(value) => {
+                    frame.stack.push(value);
+                    self.run_generator(object, frame, None)
+                }
+                Resume::Throw(exception) => self.run_generator(object, frame, Some(exception)),
+            };
+        };
+        let advanced = match (resume, &delegate) {
+            (Resume::Throw(exception), Delegate::Generator(inner)) => {
+                let inner = inner.clone();
+                self.resume_generator(&inner, Resume::Throw(exception))
+            }
+            (Resume::Throw(exception), _) => Err(Abort::Throw(exception)),
+            (Resume::Send(value), _) => self.delegate_advance(&mut delegate, value),
+        };
+        let current = advanced.and_then(|()| self.delegate_current(&mut delegate));
+        match current {
+            Ok(Some(current)) => {
+                let mut state = generator(object).state.borrow_mut();
+                state.frame = Some(frame);
+                state.delegate = Some(delegate);
+                state.current = Some(current);
+                state.running = false;
+                Ok(())
+            }
+            Ok(None) => {
+                frame.stack.push(delegate_result(&delegate));
+                self.run_generator(object, frame, None)
+            }
+            Err(Abort::Throw(exception)) => self.run_generator(object, frame, Some(exception)),
+            Err(Abort::Exit) => Err(Abort::Exit),
+        }
+    }
+
+    /// Runs the frame of a generator until it yields, returns or throws, throwing the
+    /// exception at the `yield` it is suspended at first when there is one.
+    fn run_generator(
+        &mut self,
+        object: &Rc<Object>,
+        frame: Frame,
+        exception: Option<Rc<Object>>,
+    ) -> Result<(), Abort> {
+        let base = self.frames.len();
+        self.frames.push(frame);
+        self.generators.push(object.clone());
+        let result = match exception {
+            Some(exception) => self
+                .unwind(exception, base)
+                .and_then(|()| self.execute(base)),
+            None => self.execute(base),
+        };
+        self.generators.pop();
+        let mut state = generator(object).state.borrow_mut();
+        state.running = false;
+        match result {
+            Ok(_) if self.frames.len() > base => {
+                state.frame = self.frames.pop();
+                Ok(())
+            }
+            Ok(value) => {
+                state.current = None;
+                state.returned = Some(value);
+                Ok(())
+            }
+            Err(abort) => {
+                self.frames.truncate(base);
+                state.current = None;
+                Err(abort)
+            }
+        }
+    }
+
+    /// Runs the `finally` blocks of the generators destroyed while suspended in a `try`, as if
+    /// they returned from the `yield` they were suspended at. A generator that yields again
+    /// from a `finally` block is left there.
+    fn finish_destroyed_generators(&mut self) -> Result<(), Abort> {
+        loop {
+            let Some(frame) = self.destroyed.borrow_mut().pop() else {
+                return Ok(());
+            };
+            let class = self.class("Generator").expect("Generator is builtin");
+            let id = self.next_object_id();
+            let object = Rc::new(Object::new(
+                class,
+                Array::new(),
+                Internal::Generator(Box::new(Generator::new(frame, None))),
+                id,
+            ));
+            let frame = {
+                let mut state = generator(&object).state.borrow_mut();
+                state.started = true;
+                state.running = true;
+                state.frame.take().expect("the frame was just put there")
+            };
+            let base = self.frames.len();
+            self.frames.push(frame);
+            self.generators.push(object);
+            let result = match self.complete(Pending::Return(Value::Null)) {
+                Ok(Flow::Next) => self.execute(base).map(drop),
+                Ok(_) => Ok(()),
+                Err(abort) => Err(abort),
+            };
+            self.generators.pop();
+            self.frames.truncate(base);
+            result?;
+        }
+    }
+
+    /// Starts delegating to the value of a `yield from`.
+    fn delegate(&mut self, source: Value) -> Result<Delegate, Abort> {
+        let mut object = match source {
+            Value::Array(array) => return Ok(Delegate::Array(array, 0)),
+            Value::Object(object) if object.instance_of("traversable") => object,
+            _ => {
+                let message = "Can use \"yield from\" only with arrays and Traversables";
+                return Err(self.error("Error", message));
+            }
+        };
+        while object.instance_of("iteratoraggregate") {
+            match self.call_method(object.clone(), "getIterator", Vec::new())? {
+                Value::Object(inner) if inner.instance_of("traversable") => object = inner,
+                _ => return Err(self.error("TypeError", "getIterator() must return a Traversable")),
+            }
+        }
+        if let Internal::Generator(_) = object.internal {
+            self.start_generator(&object)?;
+            return Ok(Delegate::Generator(object));
+        }
+        self.call_method(object.clone(), "rewind", Vec::new())?;
+        Ok(Delegate::Iterator(object))
+    }
+
+    /// The key and value a `yield from` yields next, `None` when its source is done.
+    fn delegate_current(
+        &mut self,
+        delegate: &mut Delegate,
+    ) -> Result<Option<(Value, Value)>, Abort> {
+        match delegate {
+            Delegate::Array(array, pos) => Ok(array
+                .entry_at(*pos)
+                .map(|(key, value)| (key.to_value(), value.clone()))),
+            Delegate::Generator(inner) => Ok(generator(inner).current()),
+            Delegate::Iterator(object) => {
+                let object = object.clone();
+                if !self
+                    .call_method(object.clone(), "valid", Vec::new())?
+                    .to_bool()
+                {
+                    return Ok(None);
+                }
+                let value = self.call_method(object.clone(), "current", Vec::new())?;
+                let key = self.call_method(object, "key", Vec::new())?;
+                Ok(Some((key, value)))
+            }
+        }
+    }
+
+    fn delegate_advance(&mut self, delegate: &mut Delegate, sent: Value) -> Result<(), Abort> {
+        match delegate {
+            Delegate::Array(_, pos) => {
+                *pos += 1;
+                Ok(())
+            }
+            Delegate::Generator(inner) => {
+                let inner = inner.clone();
+                self.resume_generator(&inner, Resume::Send(sent))
+            }
+            Delegate::Iterator(object) => {
+                let object = object.clone();
+                self.call_method(object, "next", Vec::new()).map(drop)
+            }
+        }
+    }
+
+    pub(crate) fn closure_object(&mut self, closure: Closure) -> Value {
+        let class = self.class("Closure").expect("Closure is builtin");
+        let id = self.next_object_id();
+        Value::Object(Rc::new(Object::new(
+            class,
+            Array::new(),
+            Internal::Closure(closure),
+            id,
+        )))
+    }
+
+    fn declare_class(&mut self, decl: &Rc<ClassDecl>, hoisted: bool) -> Result<(), Abort> {
+        let address = Rc::as_ptr(decl) as usize;
+        if self.declared.contains(&address) {
+            return Ok(());
+        }
+        let lname = decl.name.to_ascii_lowercase();
+        let needed = decl
+            .parent
+            .iter()
+            .map(|name| ("Class", name))
+            .chain(decl.interfaces.iter().map(|name| ("Interface", name)))
+            .chain(decl.traits.iter().map(|name| ("Trait", name)));
+        for (what, name) in needed {
+            if self.class(name).is_none() {
+                if hoisted {
+                    return Ok(());
+                }
+                let message = format!("{} \"{}\" not found", what, name);
+                return Err(self.error("Error", message));
+            }
+        }
+        if self.classes.contains_key(&lname) {
+            if hoisted {
+                return Ok(());
+            }
+            let message = format!(
+                "Cannot declare {} {}, because the name is already in use",
+                decl.kind.as_str(),
+                decl.name
+            );
+            return Err(self.error("Error", message));
+        }
+
+        let parent = decl.parent.as_ref().and_then(|name| self.class(name));
+        if let Some(parent) = parent.as_ref().filter(|p| p.kind != ClassKind::Class) {
+            let message = format!(
+                "Class {} cannot extend {} {}",
+                decl.name,
+                parent.kind.as_str(),
+                parent.name
+            );
+            return Err(self.error("Error", message));
+        }
+        if let Some(parent) = parent.as_ref().filter(|p| p.is_final) {
+            let message = format!(
+                "Class {} cannot extend final class {}",
+                decl.name, parent.name
+            );
+            return Err(self.error("Error", message));
+        }
+        let mut methods = parent
+            .as_ref()
+            .map_or_else(HashMap::new, |parent| parent.methods.clone());
+        let mut consts = parent
+            .as_ref()
+            .map_or_else(HashMap::new, |parent| parent.consts.clone());
+        let mut props = parent
+            .as_ref()
+            .map_or_else(Vec::new, |parent| parent.props.clone());
+        let mut static_props = parent
+            .as_ref()
+            .map_or_else(HashMap::new, |parent| parent.static_props.clone());
+        let mut interfaces = parent
+            .as_ref()
+            .map_or_else(Vec::new, |parent| parent.interfaces.clone());
+        let mut hidden_props = parent
+            .as_ref()
+            .map_or_else(HashMap::new, |parent| parent.hidden_props.clone());
+
+        for name in &decl.interfaces {
+            let interface = self.class(name).expect("checked above");
+            interfaces.push(interface.lname());
+            interfaces.extend(interface.interfaces.iter().cloned());
+            for (name, constant) in &interface.consts {
+                consts
+                    .entry(name.clone())
+                    .or_insert_with(|| constant.clone());
+            }
+            for (name, method) in &interface.methods {
+                methods
+                    .entry(name.clone())
+                    .or_insert_with(|| method.clone());
+            }
+        }
+        if let Some(enumeration) = &decl.enumeration {
+            interfaces.push("unitenum".to_string());
+            if enumeration.is_backed() {
+                interfaces.push("backedenum".to_string());
+            }
+            for (method, native) in builtins::enum_methods(enumeration.is_backed()) {
+                methods.insert(
+                    method.to_ascii_lowercase(),
+                    Rc::new(Method {
+                        name: method.to_string(),
+                        scope: lname.clone(),
+                        is_static: true,
+                        is_final: false,
+                        body: Some(Code::Native(native)),
+                    }),
+                );
+            }
+        }
+        for name in &decl.traits {
+            let used = self.class(name).expect("checked above");
+            if used.kind != ClassKind::Trait {
+                let message = format!("{} cannot use {} - it is not a trait", decl.name, used.name);
+                return Err(self.error("Error", message));
+            }
+            for (name, method) in &used.methods {
+                // an abstract trait method is satisfied by an inherited one.
+                if method.body.is_none() && methods.get(name).is_some_and(|m| m.body.is_some()) {
+                    continue;
+                }
+                self.check_override(parent.as_ref(), name)?;
+                methods.insert(
+                    name.clone(),
+                    Rc::new(Method {
+                        name: method.name.clone(),
+                        scope: lname.clone(),
+                        is_static: method.is_static,
+                        is_final: method.is_final,
+                        body: method.body.clone(),
+                    }),
+                );
+            }
+            for (name, default) in &used.props {
+                set_prop(&mut props, name, default.clone());
+                hidden_props.remove(name);
+            }
+            for (name, (visibility, _)) in &used.hidden_props {
+                hidden_props.insert(name.clone(), (*visibility, lname.clone()));
+            }
+            for (name, prop) in &used.static_props {
+                let prop = StaticProp::new(lname.clone(), prop.init.clone());
+                static_props.insert(name.clone(), Rc::new(prop));
+            }
+            for (name, constant) in &used.consts {
+                consts.insert(name.clone(), constant.clone());
+            }
+        }
+
+        for method in &decl.methods {
+            let name = method.name.to_ascii_lowercase();
+            self.check_override(parent.as_ref(), &name)?;
+            methods.insert(
+                name,
+                Rc::new(Method {
+                    name: method.name.clone(),
+                    scope: lname.clone(),
+                    is_static: method.is_static,
+                    is_final: method.is_final,
+                    body: method.func.clone().map(Code::User),
+                }),
+            );
+        }
+        for (name, constant) in &decl.consts {
+            let init = match constant {
+                ConstDecl::Expr(func) => ConstInit::Expr(func.clone()),
+                ConstDecl::Case => ConstInit::Case,
+            };
+            consts.insert(name.clone(), Rc::new(ClassConst::new(lname.clone(), init)));
+        }
+        for prop in &decl.props {
+            match prop.is_static {
+                true => {
+                    let static_prop = StaticProp::new(lname.clone(), prop.default.clone());
+                    static_props.insert(prop.name.clone(), Rc::new(static_prop));
+                }
+                false => set_prop(&mut props, &prop.name, prop.default.clone()),
+            }
+            match prop.visibility {
+                Visibility::Public => hidden_props.remove(&prop.name),
+                visibility => hidden_props.insert(prop.name.clone(), (visibility, lname.clone())),
+            };
+        }
+        let concrete = match decl.kind {
+            ClassKind::Class => !decl.is_abstract,
+            ClassKind::Enum => true,
+            ClassKind::Interface | ClassKind::Trait => false,
+        };
+        if concrete {
+            let mut missing: Vec<String> = methods
+                .values()
+                .filter(|method| method.body.is_none())
+                .map(|method| {
+                    let class = match method.scope == lname {
+                        true => decl.name.clone(),
+                        false => self
+                            .class(&method.scope)
+                            .map_or_else(|| method.scope.clone(), |class| class.name.clone()),
+                    };
+                    format!("{}::{}", class, method.name)
+                })
+                .collect();
+            if !missing.is_empty() {
+                missing.sort();
+                let count = missing.len();
+                let mut listed = missing[..count.min(3)].join(", ");
+                if count > 3 {
+                    listed.push_str(", ...");
+                }
+                let kind = match decl.kind {
+                    ClassKind::Enum => "Enum",
+                    _ => "Class",
+                };
+                let message = format!(
+                    "{} {} contains {} abstract method{} and must therefore be declared \
+                     abstract or implement the remaining methods ({})",
+                    kind,
+                    decl.name,
+                    count,
+                    if count == 1 { "" } else { "s" },
+                    listed
+                );
+                return Err(self.error("Error", message));
+            }
+        }
+
+        let class = Class::new(
+            decl.name.clone(),
+            decl.kind,
+            decl.is_abstract,
+            decl.is_final,
+            parent,
+            interfaces,
+            methods,
+            consts,
+            props,
+            static_props,
+            hidden_props,
+            decl.enumeration.clone(),
+            decl.attributes.clone(),
+        );
+        self.classes.insert(lname, Rc::new(class));
+        self.declared.insert(address);
+        Ok(())
+    }
+
+    /// Fails when a class declaring the method `name`, lowercase, would override a final
+    /// method of its `parent`.
+    fn check_override(&mut self, parent: Option<&Rc<Class>>, name: &str) -> Result<(), Abort> {
+        let Some(method) = parent.and_then(|parent| parent.methods.get(name)) else {
+            return Ok(());
+        };
+        if !method.is_final {
+            return Ok(());
+        }
+        let class = self
+            .class(&method.scope)
+            .expect("declared before its subclasses");
+        let message = format!(
+            "Cannot override final method {}::{}()",
+            class.name, method.name
+        );
+        Err(self.error("Error", message))
+    }
+
+    /// Pops the arguments of a call, the positional ones and the named ones, with the slots
+    /// of the variables passed.
+    #[allow(clippy::type_complexity)]
+    fn pop_args(
+        &mut self,
+        args: &Args,
+    ) -> Result<(Vec<Value>, Vec<(String, Value)>, Vec<Option<u32>>), Abort> {
+        Ok(match args {
+            Args::Stack(count, slots) => {
+                let at = self.frame().stack.len() - *count as usize;
+                (self.frame().stack.split_off(at), Vec::new(), slots.clone())
+            }
+            Args::Array => {
+                let Value::Array(array) = self.pop() else {
+                    unreachable!("the arguments are built as an array");
+                };
+                let mut positional = Vec::new();
+                let mut named = Vec::new();
+                for (key, value) in array.iter() {
+                    match key {
+                        ArrayKey::Int(_) => positional.push(value.clone()),
+                        ArrayKey::String(name) => named.push((name.to_string(), value.clone())),
+                    }
+                }
+                (positional, named, Vec::new())
+            }
+            Args::Callable => (Vec::new(), Vec::new(), Vec::new()),
+        })
+    }
+
+    fn call(&mut self, call: &Call) -> Result<(), Abort> {
+        let (args, named, refs) = self.pop_args(&call.args)?;
+        let target = match &call.callee {
+            Callee::Function(name) => self.function_target(name)?,
+            Callee::Value => {
+                let callee = self.pop();
+                self.callable(&callee)?
+            }
+            Callee::Method(name) => {
+                let name = self.member_name(name)?;
+                let object = self.pop();
+                self.method_target(object, &name)?
+            }
+            Callee::Static(spec, name) => {
+                let name = self.member_name(name)?;
+                let class = self.resolve_class(spec)?;
+                let forward = matches!(spec, ClassSpec::Current | ClassSpec::Parent);
+                self.static_target(class, &name, forward)?
+            }
+        };
+        if let Args::Callable = call.args {
+            let closure = Closure {
+                code: target.code,
+                bound: target.bound,
+                this: target.this,
+                scope: target.scope,
+                static_class: target.static_class,
+            };
+            let value = self.closure_object(closure);
+            self.push(value);
+            return Ok(());
+        }
+        self.invoke(target, args, named, &refs, Ret::Value)
+    }
+
+    fn member_name(&mut self, name: &Option<String>) -> Result<String, Abort> {
+        match name {
+            Some(name) => Ok(name.clone()),
+            None => {
+                let name = self.pop();
+                Ok(self.stringify(&name)?.to_string())
+            }
+        }
+    }
+
+    fn function_target(&mut self, name: &QualifiedName) -> Result<Target, Abort> {
+        let found = std::iter::once(&name.name)
+            .chain(&name.fallback)
+            .find_map(|name| self.functions.get(&name.to_ascii_lowercase()).cloned());
+        match found {
+            Some(code) => Ok(Target::function(code)),
+            None => {
+                let shown = name.fallback.as_ref().unwrap_or(&name.name);
+                Err(self.error("Error", format!("Call to undefined function {}()", shown)))
+            }
+        }
+    }
+
+    fn method_target_of(&mut self, method: &Method, object: Rc<Object>) -> Result<Target, Abort> {
+        let Some(code) = method.body.clone() else {
+            let class = self
+                .class(&method.scope)
+                .map_or_else(String::new, |c| c.name.clone());
+            let message = format!("Cannot call abstract method {}::{}()", class, method.name);
+            return Err(self.error("Error", message));
+        };
+        Ok(Target {
+            code,
+            scope: self.class(&method.scope),
+            static_class: Some(object.class.clone()),
+            this: (!method.is_static).then_some(object),
+            bound: Vec::new(),
+            magic: None,
+        })
+    }
+
+    fn method_target(&mut self, object: Value, name: &str) -> Result<Target, Abort> {
+        let Value::Object(object) = object else {
+            let message = format!(
+                "Call to a member function {}() on {}",
+                name,
+                object.type_name()
+            );
+            return Err(self.error("Error", message));
+        };
+        if let Internal::Closure(closure) = &object.internal {
+            if name.eq_ignore_ascii_case("__invoke") {
+                return Ok(closure_target(closure));
+            }
+        }
+        if let Some(method) = object.class.find_method(name).cloned() {
+            return self.method_target_of(&method, object);
+        }
+        if let Some(method) = object.class.find_method("__call").cloned() {
+            let mut target = self.method_target_of(&method, object)?;
+            target.magic = Some(name.to_string());
+            return Ok(target);
+        }
+        let message = format!("Call to undefined method {}::{}()", object.class.name, name);
+        Err(self.error("Error", message))
+    }
+
+    /// `Foo::bar()`. `self::` and `parent::` forward `$this` and the late static binding
+    /// class, as does calling a parent's instance method from a subclass.
+    fn static_target(
+        &mut self,
+        class: Rc<Class>,
+        name: &str,
+        forward: bool,
+    ) -> Result<Target, Abort> {
+        let (method, magic) = match class.find_method(name).cloned() {
+            Some(method) => (method, None),
+            None => match class.find_method("__callStatic").cloned() {
+                Some(method) => (method, Some(name.to_string())),
+                None => {
+                    let message = format!("Call to undefined method {}::{}()", class.name, name);
+                    return Err(self.error("Error", message));
+                }
+            },
+        };
+        let caller = self.frames.last();
+        let this = caller
+            .and_then(|frame| frame.this.clone())
+            .filter(|this| !method.is_static && this.class.is_subclass_of(&class.lname()));
+        let static_class = match (&this, forward) {
+            (Some(this), _) => Some(this.class.clone()),
+            (None, true) => caller
+                .and_then(|frame| frame.static_class.clone())
+                .or(Some(class.clone())),
+            (None, false) => Some(class.clone()),
+        };
+        if !method.is_static && this.is_none() {
+            let message = format!(
+                "Non-static method {}::{}() cannot be called statically",
+                class.name, method.name
+            );
+            return Err(self.error("Error", message));
+        }
+        let Some(code) = method.body.clone() else {
+            let message = format!("Cannot call abstract method {}::{}()", class.name, name);
+            return Err(self.error("Error", message));
+        };
+        Ok(Target {
+            code,
+            this,
+            scope: self.class(&method.scope),
+            static_class,
+            bound: Vec::new(),
+            magic,
+        })
+    }
+
+    /// The target of a callable value: a closure, an invokable object, a function name,
+    /// `"Foo::bar"`, `[$object, "bar"]` or `["Foo", "bar"]`.
+    fn callable(&mut self, value: &Value) -> Result<Target, Abort> {
+        match value {
+            Value::Object(object) => match &object.internal {
+                Internal::Closure(closure) => Ok(closure_target(closure)),
+                _ => self.method_target(value.clone(), "__invoke"),
+            },
+            Value::String(name) => {
+                let name = name.to_str();
+                match name.split_once("::") {
+                    Some((class, method)) => {
+                        let class = self.find_class(class)?;
+                        self.static_target(class, method, false)
+                    }
+                    None => {
+                        let name = QualifiedName {
+                            name: name.trim_start_matches('\\').to_string(),
+                            fallback: None,
+                        };
+                        self.function_target(&name)
+                    }
+                }
+            }
+            Value::Array(array) if array.len() == 2 => {
+                let (Some(first), Some(Value::String(method))) =
+                    (array.get(&ArrayKey::Int(0)), array.get(&ArrayKey::Int(1)))
+                else {
+                    return Err(
+                        self.error("Error", "Array callback must have exactly two elements")
+                    );
+                };
+                match first {
+                    Value::String(class) => {
+                        let class = self.find_class(&class.to_str())?;
+                        self.static_target(class, &method.to_str(), false)
+                    }
+                    object => self.method_target(object.clone(), &method.to_str()),
+                }
+            }
+            _ => Err(self.error("Error", "Value not callable")),
+        }
+    }
+
+    pub(crate) fn is_callable(&mut self, value: &Value) -> bool {
+        let depth = self.frames.len();
+        let callable = self.callable(value).is_ok();
+        self.frames.truncate(depth);
+        callable
+    }
+
+    /// Calls a callable value and runs it to completion, for the runtime calling back into
+    /// the script.
+    pub fn call_value(&mut self, callable: &Value, args: Vec<Value>) -> Result<Value, Abort> {
+        let target = self.callable(callable)?;
+        self.call_target(target, args)
+    }
+
+    pub(crate) fn call_method(
+        &mut self,
+        object: Rc<Object>,
+        name: &str,
+        args: Vec<Value>,
+    ) -> Result<Value, Abort> {
+        let target = self.method_target(Value::Object(object), name)?;
+        self.call_target(target, args)
+    }
+
+    fn call_target(&mut self, target: Target, args: Vec<Value>) -> Result<Value, Abort> {
+        let base = self.frames.len();
+        let (target, args) = magic_args(target, args);
+        match target.code.clone() {
+            Code::User(func) => {
+                // the runtime passes arguments in weak mode, whatever the script declares.
+                let frame = self.new_frame(func.clone(), target, args, Vec::new(), &[], false)?;
+                if func.generator || func.is_async {
+                    return Ok(self.generator_object(frame));
+                }
+                self.frames.push(frame);
+                self.execute(base)
+            }
+            Code::Native(native) => {
+                let args = self.check_native_args(&native, args, false)?;
+                let mut call = NativeCall {
+                    this: target.this,
+                    class: target.static_class,
+                    args,
+                };
+                (native.run)(self, &mut call)
+            }
+        }
+    }
+
+    /// Calls a target from the running frame, a function of the script is pushed as a frame
+    /// and its result is pushed when it returns.
+    fn invoke(
+        &mut self,
+        target: Target,
+        args: Vec<Value>,
+        named: Vec<(String, Value)>,
+        refs: &[Option<u32>],
+        ret: Ret,
+    ) -> Result<(), Abort> {
+        let (target, args) = magic_args(target, args);
+        match target.code.clone() {
+            Code::User(func) => {
+                let strict = self.frames.last().is_some_and(|caller| caller.func.strict);
+                let mut frame = self.new_frame(func.clone(), target, args, named, refs, strict)?;
+                if func.generator || func.is_async {
+                    let value = self.generator_object(frame);
+                    self.push(value);
+                    return Ok(());
+                }
+                frame.ret = ret;
+                self.frames.push(frame);
+            }
+            Code::Native(native) => {
+                let mut args = args;
+                args.extend(named.into_iter().map(|(_, value)| value));
+                let strict = self.frames.last().is_some_and(|caller| caller.func.strict);
+                let args = self.check_native_args(&native, args, strict)?;
+                let mut call = NativeCall {
+                    this: target.this,
+                    class: target.static_class,
+                    args,
+                };
+                let value = (native.run)(self, &mut call)?;
+                let frame = self.frame();
+                for &index in native.by_ref {
+                    if let (Some(Some(slot)), Some(value)) = (refs.get(index), call.args.get(index))
+                    {
+                        *frame.locals[*slot as usize].borrow_mut() = value.clone();
+                    }
+                }
+                self.push(match ret {
+                    Ret::Value => value,
+                    Ret::Object(object) => Value::Object(object),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn new_frame(
+        &mut self,
+        func: Rc<Func>,
+        target: Target,
+        args: Vec<Value>,
+        mut named: Vec<(String, Value)>,
+        refs: &[Option<u32>],
+        strict: bool,
+    ) -> Result<Frame, Abort> {
+        let mut frame = Frame::new(
+            func.clone(),
+            target.this.filter(|_| !func.is_static),
+            target.scope,
+            target.static_class,
+        );
+        for (i, param) in func.params.iter().enumerate() {
+            if param.variadic {
+                let mut rest: Array = args.iter().skip(i).cloned().collect();
+                for (name, value) in named.drain(..) {
+                    rest.insert(ArrayKey::String(PhpString::from(name)), value);
+                }
+                *frame.locals[i].borrow_mut() = Value::array(rest);
+                frame.passed[i] = true;
+                break;
+            }
+            if let Some(arg) = args.get(i) {
+                let caller_cell = match (param.by_ref, refs.get(i), self.frames.last()) {
+                    (true, Some(Some(slot)), Some(caller)) => {
+                        Some(caller.locals[*slot as usize].clone())
+                    }
+                    _ => None,
+                };
+                match caller_cell {
+                    Some(shared) => frame.locals[i] = shared,
+                    None => *frame.locals[i].borrow_mut() = arg.clone(),
+                }
+                frame.passed[i] = true;
+            } else if let Some(index) = named.iter().position(|(name, _)| *name == param.name) {
+                let (_, value) = named.remove(index);
+                *frame.locals[i].borrow_mut() = value;
+                frame.passed[i] = true;
+            } else if param.required {
+                let required = func.params.iter().filter(|p| p.required).count();
+                let exactly = required == func.params.len();
+                let message = format!(
+                    "Too few arguments to function {}(), {} passed and {} {} expected",
+                    func.name,
+                    args.len(),
+                    if exactly { "exactly" } else { "at least" },
+                    required
+                );
+                return Err(self.error("ArgumentCountError", message));
+            }
+        }
+        if let Some((name, _)) = named.first() {
+            return Err(self.error("Error", format!("Unknown named parameter ${}", name)));
+        }
+        for (i, param) in func.params.iter().enumerate() {
+            let Some(ty) = &param.ty else { continue };
+            if !frame.passed[i] {
+                continue;
+            }
+            let value = frame.locals[i].borrow().clone();
+            let value = match (param.variadic, value) {
+                (true, Value::Array(rest)) => {
+                    let mut checked = Array::new();
+                    for (n, (key, value)) in rest.iter().enumerate() {
+                        let value = self.check_arg(&frame, i + n, ty, strict, value)?;
+                        checked.insert(key.clone(), value);
+                    }
+                    Value::array(checked)
+                }
+                (_, value) => self.check_arg(&frame, i, ty, strict, &value)?,
+            };
+            *frame.locals[i].borrow_mut() = value;
+        }
+        for (slot, (captured, by_ref)) in func.captures.iter().zip(&target.bound) {
+            frame.locals[*slot as usize] = match by_ref {
+                true => captured.clone(),
+                false => cell(captured.borrow().clone()),
+            };
+        }
+        frame.args = args;
+        Ok(frame)
+    }
+
+    /// The value of the argument at `index`, coerced to the type of its parameter.
+    fn check_arg(
+        &mut self,
+        frame: &Frame,
+        index: usize,
+        ty: &Type,
+        strict: bool,
+        value: &Value,
+    ) -> Result<Value, Abort> {
+        let (scope, static_class) = (frame.scope.clone(), frame.static_class.clone());
+        if let Some(value) = self.coerce(value, ty, strict, scope, static_class)? {
+            return Ok(value);
+        }
+        let param = &frame.func.params[index.min(frame.func.params.len() - 1)];
+        let message = format!(
+            "{}(): Argument #{} (${}) must be of type {}, {} given",
+            frame.func.name,
+            index + 1,
+            param.name,
+            ty,
+            value.type_name()
+        );
+        Err(self.error("TypeError", message))
+    }
+
+    /// The arguments of a call to a native function, coerced to the types of its parameters.
+    fn check_native_args(
+        &mut self,
+        native: &Native,
+        mut args: Vec<Value>,
+        strict: bool,
+    ) -> Result<Vec<Value>, Abort> {
+        for (index, ((name, types), value)) in native.params.iter().zip(&mut args).enumerate() {
+            // in weak mode PHP still passes null to scalar parameters of builtin functions.
+            if !strict && value.is_null() {
+                continue;
+            }
+            let ty = match types {
+                [builtin] => Type::Builtin(*builtin),
+                _ => Type::Union(types.iter().copied().map(Type::Builtin).collect()),
+            };
+            if let Some(coerced) = self.coerce(value, &ty, strict, None, None)? {
+                *value = coerced;
+                continue;
+            }
+            let message = format!(
+                "{}(): Argument #{} (${}) must be of type {}, {} given",
+                native.name,
+                index + 1,
+                name,
+                ty,
+                value.type_name()
+            );
+            return Err(self.error("TypeError", message));
+        }
+        Ok(args)
+    }
+
+    /// The returned value, coerced to the return type of the running function.
+    fn check_return(&mut self, func: &Func, ty: &Type, value: Value) -> Result<Value, Abort> {
+        let message = match ty {
+            Type::Builtin(BuiltinType::Void) => return Ok(value),
+            Type::Builtin(BuiltinType::Never) => {
+                format!(
+                    "{}(): never-returning function must not implicitly return",
+                    func.name
+                )
+            }
+            _ => {
+                let frame = self.frame();
+                let (scope, static_class) = (frame.scope.clone(), frame.static_class.clone());
+                if let Some(value) = self.coerce(&value, ty, func.strict, scope, static_class)? {
+                    return Ok(value);
+                }
+                format!(
+                    "{}(): Return value must be of type {}, {} returned",
+                    func.name,
+                    ty,
+                    value.type_name()
+                )
+            }
+        };
+        Err(self.error("TypeError", message))
+    }
+
+    /// Converts a value to a declared type, `None` when it can not be.
+    ///
+    /// A value already of the type is kept and an int is widened to a float. In weak mode
+    /// scalars are then converted to the first of int, float, string and bool that the type
+    /// allows, and objects with `__toString` to strings; strict mode stops before that.
+    fn coerce(
+        &mut self,
+        value: &Value,
+        ty: &Type,
+        strict: bool,
+        scope: Option<Rc<Class>>,
+        static_class: Option<Rc<Class>>,
+    ) -> Result<Option<Value>, Abort> {
+        if self.has_type(value, ty, &scope, &static_class) {
+            return Ok(Some(value.clone()));
+        }
+        let allows = |builtin| ty.contains(builtin);
+        if let Value::Int(i) = value {
+            if allows(BuiltinType::Float) {
+                return Ok(Some(Value::Float(*i as f64)));
+            }
+        }
+        if strict {
+            return Ok(None);
+        }
+        let number = match value {
+            Value::String(s) => parse_numeric(s),
+            Value::Bool(b) => Some(Value::Int(i64::from(*b))),
+            Value::Int(_) | Value::Float(_) => Some(value.clone()),
+            Value::Null | Value::Array(_) | Value::Object(_) => None,
+        };
+        if allows(BuiltinType::Int) {
+            match number {
+                Some(Value::Int(i)) => return Ok(Some(Value::Int(i))),
+                Some(Value::Float(f)) if f.fract() == 0.0 && f.is_finite() => {
+                    return Ok(Some(Value::Int(f as i64)))
+                }
+                // PHP deprecates dropping the fraction, but still does it.
+                Some(Value::Float(f))
+                    if f.is_finite()
+                        && !allows(BuiltinType::Float)
+                        && !allows(BuiltinType::String) =>
+                {
+                    return Ok(Some(Value::Int(f as i64)))
+                }
+                _ => {}
+            }
+        }
+        if allows(BuiltinType::Float) {
+            if let Some(number) = &number {
+                return Ok(Some(Value::Float(number.to_float())));
+            }
+        }
+        if allows(BuiltinType::String) {
+            match value {
+                Value::Bool(_) | Value::Int(_) | Value::Float(_) => {
+                    return Ok(value.to_php_string().map(Value::String))
+                }
+                Value::Object(object) if object.class.find_method("__tostring").is_some() => {
+                    return Ok(Some(Value::String(self.stringify(value)?)))
+                }
+                _ => {}
+            }
+        }
+        if allows(BuiltinType::Bool) {
+            if let Value::Int(_) | Value::Float(_) | Value::String(_) = value {
+                return Ok(Some(Value::Bool(value.to_bool())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether a value is of a declared type without any conversion.
+    fn has_type(
+        &mut self,
+        value: &Value,
+        ty: &Type,
+        scope: &Option<Rc<Class>>,
+        static_class: &Option<Rc<Class>>,
+    ) -> bool {
+        let instance_of = |class: Option<&Rc<Class>>| match (value, class) {
+            (Value::Object(object), Some(class)) => object.instance_of(&class.lname()),
+            _ => false,
+        };
+        match ty {
+            Type::Builtin(builtin) => match builtin {
+                BuiltinType::Int => matches!(value, Value::Int(_)),
+                BuiltinType::Float => matches!(value, Value::Float(_)),
+                BuiltinType::String => matches!(value, Value::String(_)),
+                BuiltinType::Bool => matches!(value, Value::Bool(_)),
+                BuiltinType::True => matches!(value, Value::Bool(true)),
+                BuiltinType::False => matches!(value, Value::Bool(false)),
+                BuiltinType::Null | BuiltinType::Void => value.is_null(),
+                BuiltinType::Array => matches!(value, Value::Array(_)),
+                BuiltinType::Object => matches!(value, Value::Object(_)),
+                BuiltinType::Iterable => match value {
+                    Value::Array(_) => true,
+                    Value::Object(object) => object.instance_of("traversable"),
+                    _ => false,
+                },
+                BuiltinType::Callable => self.is_callable(value),
+                BuiltinType::Mixed => true,
+                BuiltinType::Never => false,
+                BuiltinType::SelfClass => instance_of(scope.as_ref()),
+                BuiltinType::Parent => {
+                    instance_of(scope.as_ref().and_then(|scope| scope.parent.as_ref()))
+                }
+                BuiltinType::Static => instance_of(static_class.as_ref()),
+            },
+            Type::Class(name) => match value {
+                Value::Object(object) => object.instance_of(&name.to_ascii_lowercase()),
+                _ => false,
+            },
+            Type::Union(types) => types
+                .iter()
+                .any(|ty| self.has_type(value, ty, scope, static_class)),
+            Type::Intersection(types) => types
+                .iter()
+                .all(|ty| self.has_type(value, ty, scope, static_class)),
+        }
+    }
+
+    fn iter_init(&mut self, by_ref: Option<(u32, u32)>) -> Result<(), Abort> {
+        let subject = self.pop();
+        let iter = match (by_ref, subject) {
+            (Some((array, value)), _) => {
+                let frame = self.frame();
+                let mut source = frame.locals[array as usize].borrow_mut();
+                if source.is_null() {
+                    *source = Value::array(Array::new());
+                }
+                Iter::ByRef {
+                    array,
+                    value,
+                    pos: 0,
+                    key: None,
+                }
+            }
+            (None, Value::Array(array)) => Iter::Array { array, pos: 0 },
+            (None, Value::Object(mut object)) => {
+                while object.instance_of("iteratoraggregate") {
+                    match self.call_method(object.clone(), "getIterator", Vec::new())? {
+                        Value::Object(inner) => object = inner,
+                        _ => {
+                            return Err(
+                                self.error("TypeError", "getIterator() must return a Traversable")
+                            )
+                        }
+                    }
+                }
+                match object.instance_of("iterator") {
+                    true => Iter::Object {
+                        object,
+                        started: false,
+                    },
+                    false => Iter::Array {
+                        array: Rc::new(object.props.borrow().clone()),
+                        pos: 0,
+                    },
+                }
+            }
+            (None, _) => Iter::Array {
+                array: Rc::new(Array::new()),
+                pos: 0,
+            },
+        };
+        self.frame().iters.push(iter);
+        Ok(())
+    }
+
+    fn iter_next(&mut self, exit: usize, with_key: bool) -> Result<(), Abort> {
+        let frame = self.frame();
+        let next = match frame.iters.last_mut().expect("inside a foreach") {
+            Iter::Array { array, pos } => {
+                let entry = array
+                    .entry_at(*pos)
+                    .map(|(key, value)| (key.to_value(), value.clone()));
+                *pos += 1;
+                entry
+            }
+            Iter::ByRef {
+                array,
+                value,
+                pos,
+                key,
+            } => {
+                let (array, value, position) = (*array, *value, *pos);
+                *pos += 1;
+                let previous = Iter::ByRef {
+                    array,
+                    value,
+                    pos: position,
+                    key: key.take(),
+                };
+                frame.write_back(&previous);
+                let entry = match &*frame.locals[array as usize].borrow() {
+                    Value::Array(items) => items
+                        .entry_at(position)
+                        .map(|(key, value)| (key.clone(), value.clone())),
+                    _ => None,
+                };
+                match entry {
+                    Some((entry_key, entry_value)) => {
+                        *frame.locals[value as usize].borrow_mut() = entry_value.clone();
+                        if let Some(Iter::ByRef { key, .. }) = frame.iters.last_mut() {
+                            *key = Some(entry_key.clone());
+                        }
+                        Some((entry_key.to_value(), entry_value))
+                    }
+                    None => None,
+                }
+            }
+            Iter::Object { object, started } => {
+                let object = object.clone();
+                let was_started = std::mem::replace(started, true);
+                let step = if was_started { "next" } else { "rewind" };
+                self.call_method(object.clone(), step, Vec::new())?;
+                match self
+                    .call_method(object.clone(), "valid", Vec::new())?
+                    .to_bool()
+                {
+                    true => {
+                        let value = self.call_method(object.clone(), "current", Vec::new())?;
+                        let key = match with_key {
+                            true => self.call_method(object, "key", Vec::new())?,
+                            false => Value::Null,
+                        };
+                        Some((key, value))
+                    }
+                    false => None,
+                }
+            }
+        };
+        match next {
+            Some((key, value)) => {
+                self.push(value);
+                if with_key {
+                    self.push(key);
+                }
+            }
+            None => self.frame().pc = exit,
+        }
+        Ok(())
+    }
+}
+
+/// The value of a finished `yield from`, what the generator it delegated to returned.
+fn delegate_result(delegate: &Delegate) -> Value {
+    match delegate {
+        Delegate::Generator(inner) => generator(inner).returned().unwrap_or_default(),
+        Delegate::Array(..) | Delegate::Iterator(_) => Value::Null,
+    }
+}
+
+fn closure_target(closure: &Closure) -> Target {
+    Target {
+        code: closure.code.clone(),
+        this: closure.this.clone(),
+        scope: closure.scope.clone(),
+        static_class: closure.static_class.clone(),
+        bound: closure.bound.clone(),
+        magic: None,
+    }
+}
+
+/// `__call` and `__callStatic` take the name of the method and an array of its arguments.
+fn magic_args(mut target: Target, args: Vec<Value>) -> (Target, Vec<Value>) {
+    match target.magic.take() {
+        Some(name) => (
+            target,
+            vec![
+                Value::string(name),
+                Value::array(args.into_iter().collect()),
+            ],
+        ),
+        None => (target, args),
+    }
+}
+
+/// The case an object of an enum is, by its `name`.
+fn case_of<'a>(enumeration: &'a EnumClass, object: &Object) -> EnumCase<'a> {
+    let Value::String(name) = object.get("name") else {
+        unreachable!("the name of a case is readonly");
+    };
+    enumeration
+        .case(&name.to_str())
+        .expect("the object is a case")
+}
+
+fn set_prop(props: &mut Vec<(String, Option<Rc<Func>>)>, name: &str, default: Option<Rc<Func>>) {
+    match props.iter_mut().find(|(prop, _)| prop == name) {
+        Some((_, existing)) => *existing = default,
+        None => props.push((name.to_string(), default)),
+    }
+}
+
+/// Assigns through the `[...]` of a place, creating arrays for `null` on the way.
+fn assign_path(
+    slot: &mut Value,
+    path: &[Option<ArrayKey>],
+    kind: AssignKind,
+    value: Value,
+) -> ops::OpResult {
+    let Some((first, rest)) = path.split_first() else {
+        let (result, new) = match kind {
+            AssignKind::Set => (value.clone(), value),
+            AssignKind::Op(op) => {
+                let new = ops::binary(op, slot, &value)?;
+                (new.clone(), new)
+            }
+            AssignKind::PreInc | AssignKind::PreDec => {
+                let new = ops::increment(slot, matches!(kind, AssignKind::PreInc))?;
+                (new.clone(), new)
+            }
+            AssignKind::PostInc | AssignKind::PostDec => {
+                let new = ops::increment(slot, matches!(kind, AssignKind::PostInc))?;
+                (slot.clone(), new)
+            }
+        };
+        *slot = new;
+        return Ok(result);
+    };
+    match slot {
+        Value::Null | Value::Bool(false) => *slot = Value::array(Array::new()),
+        Value::Array(_) => {}
+        Value::String(text) if rest.is_empty() && matches!(kind, AssignKind::Set) => {
+            let Some(ArrayKey::Int(index)) = first else {
+                return Err((
+                    "Error",
+                    "Cannot access offset of type string on string".into(),
+                ));
+            };
+            let mut bytes = text.clone().into_bytes();
+            let index = if *index < 0 {
+                bytes.len() as i64 + index
+            } else {
+                *index
+            };
+            if index < 0 {
+                return Err(("Error", format!("Illegal string offset {}", index)));
+            }
+            let index = index as usize;
+            if bytes.len() <= index {
+                bytes.resize(index + 1, b' ');
+            }
+            let replacement = value.to_php_string().unwrap_or_default();
+            bytes[index] = replacement.first().copied().unwrap_or(b' ');
+            *text = PhpString::from(bytes);
+            return Ok(Value::string(&replacement[..replacement.len().min(1)]));
+        }
+        Value::Object(object) => {
+            return Err((
+                "Error",
+                format!("Cannot use object of type {} as array", object.class.name),
+            ))
+        }
+        _ => return Err(("Error", "Cannot use a scalar value as an array".to_string())),
+    }
+    let Value::Array(array) = slot else {
+        unreachable!("made an array above");
+    };
+    let array = Rc::make_mut(array);
+    let key = match first {
+        Some(key) => key.clone(),
+        None => {
+            array.push(Value::Null);
+            let (key, _) = array.entry_at(array.len() - 1).expect("just pushed");
+            key.clone()
+        }
+    };
+    if !array.contains_key(&key) {
+        array.insert(key.clone(), Value::Null);
+    }
+    let entry = array.get_mut(&key).expect("inserted above");
+    assign_path(entry, rest, kind, value)
+}
+
+fn unset_path(slot: &mut Value, path: &[ArrayKey]) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    let Value::Array(array) = slot else {
+        return;
+    };
+    if !array.contains_key(first) {
+        return;
+    }
+    let array = Rc::make_mut(array);
+    match rest.is_empty() {
+        true => {
+            array.remove(first);
+        }
+        false => {
+            if let Some(entry) = array.get_mut(first) {
+                unset_path(entry, rest);
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::syntax::source::Span;
+
+use super::{expr::Arg, name::Name};
+
+/// A group of attributes, `#[A, B(1)]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeGroup {
+    pub attributes: Vec<Attribute>,
+    pub span: Span,
+}
+
+/// A single attribute within a group, the arguments must be constant expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: Name,
+    pub args: Vec<Arg>,
+    pub span: Span,
+}
+
+/// The kinds of declarations an attribute can be applied to, with the values of the
+/// matching `Attribute::TARGET_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeTarget {
+    Class = 1,
+    Function = 2,
+    Method = 4,
+    Property = 8,
+    ClassConstant = 16,
+    Parameter = 32,
+}
+
+impl AttributeTarget {
+    pub const ALL: [AttributeTarget; 6] = [
+        AttributeTarget::Class,
+        AttributeTarget::Function,
+        AttributeTarget::Method,
+        AttributeTarget::Property,
+        AttributeTarget::ClassConstant,
+        AttributeTarget::Parameter,
+    ];
+
+    /// The target as PHP names it in error messages.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttributeTarget::Class => "class",
+            AttributeTarget::Function => "function",
+            AttributeTarget::Method => "method",
+            AttributeTarget::Property => "property",
+            AttributeTarget::ClassConstant => "class constant",
+            AttributeTarget::Parameter => "parameter",
+        }
+    }
+
+    /// The name of the constant on the `Attribute` class, `TARGET_CLASS_CONSTANT`.
+    pub fn constant(self) -> &'static str {
+        match self {
+            AttributeTarget::Class => "TARGET_CLASS",
+            AttributeTarget::Function => "TARGET_FUNCTION",
+            AttributeTarget::Method => "TARGET_METHOD",
+            AttributeTarget::Property => "TARGET_PROPERTY",
+            AttributeTarget::ClassConstant => "TARGET_CLASS_CONSTANT",
+            AttributeTarget::Parameter => "TARGET_PARAMETER",
+        }
+    }
+}
+
+/// The flags given to `#[Attribute(...)]` on an attribute class, a bit set of
+/// `Attribute::TARGET_*` values and `Attribute::IS_REPEATABLE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeFlags(pub u32);
+
+impl AttributeFlags {
+    pub const TARGET_ALL: u32 = 63;
+    pub const IS_REPEATABLE: u32 = 64;
+
+    /// The value of an `Attribute::*` constant.
+    pub fn constant(name: &str) -> Option<u32> {
+        match name {
+            "TARGET_ALL" => Some(Self::TARGET_ALL),
+            "IS_REPEATABLE" => Some(Self::IS_REPEATABLE),
+            _ => AttributeTarget::ALL
+                .into_iter()
+                .find(|target| target.constant() == name)
+                .map(|target| target as u32),
+        }
+    }
+
+    /// The flags of the attributes built into PHP that can be applied to user code.
+    pub fn builtin(class: &str) -> Option<Self> {
+        let target = match class.to_ascii_lowercase().as_str() {
+            "attribute" | "allowdynamicproperties" => AttributeTarget::Class,
+            "returntypewillchange" | "override" => AttributeTarget::Method,
+            "sensitiveparameter" => AttributeTarget::Parameter,
+            _ => return None,
+        };
+        Some(Self(target as u32))
+    }
+
+    pub fn allows(self, target: AttributeTarget) -> bool {
+        self.0 & target as u32 != 0
+    }
+
+    pub fn is_repeatable(self) -> bool {
+        self.0 & Self::IS_REPEATABLE != 0
+    }
+
+    /// The targets allowed, as PHP lists them in error messages, `class, method`.
+    pub fn targets(self) -> String {
+        AttributeTarget::ALL
+            .into_iter()
+            .filter(|target| self.allows(*target))
+            .map(AttributeTarget::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
@@ -0,0 +1,174 @@
+use crate::syntax::source::Span;
+
+use super::{
+    attribute::AttributeGroup,
+    expr::Expr,
+    function::FunctionDecl,
+    keyword::Keyword,
+    name::{Ident, Name},
+    stmt::ConstItem,
+    types::TypeHint,
+};
+
+/// The kinds of class-like declarations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassKind {
+    Class,
+    Interface,
+    Trait,
+    Enum,
+}
+
+impl ClassKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ClassKind::Class => "class",
+            ClassKind::Interface => "interface",
+            ClassKind::Trait => "trait",
+            ClassKind::Enum => "enum",
+        }
+    }
+
+    /// The kind with its indefinite article, `a class` or `an interface`.
+    pub fn with_article(self) -> &'static str {
+        match self {
+            ClassKind::Class => "a class",
+            ClassKind::Interface => "an interface",
+            ClassKind::Trait => "a trait",
+            ClassKind::Enum => "an enum",
+        }
+    }
+}
+
+/// A class, interface, trait or enum declaration. Also used for anonymous classes,
+/// which have no name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassLike {
+    pub kind: ClassKind,
+    pub name: Option<Ident>,
+    /// `abstract`, `final` and `readonly`.
+    pub modifiers: Vec<Modifier>,
+    /// The parent class, or the parent interfaces of an interface.
+    pub extends: Vec<Name>,
+    pub implements: Vec<Name>,
+    /// The backing type of an enum, `enum Suit: string`.
+    pub backing_type: Option<TypeHint>,
+    pub members: Vec<ClassMember>,
+    pub attributes: Vec<AttributeGroup>,
+    pub span: Span,
+}
+
+impl ClassLike {
+    pub fn has_modifier(&self, keyword: Keyword) -> bool {
+        has_modifier(&self.modifiers, keyword)
+    }
+}
+
+/// A modifier keyword, one of `public`, `protected`, `private`, `static`, `abstract`,
+/// `final`, `readonly` or `var`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Modifier {
+    pub keyword: Keyword,
+    pub span: Span,
+}
+
+impl Modifier {
+    pub fn is_visibility(&self) -> bool {
+        matches!(
+            self.keyword,
+            Keyword::Public | Keyword::Protected | Keyword::Private | Keyword::Var
+        )
+    }
+}
+
+pub fn has_modifier(modifiers: &[Modifier], keyword: Keyword) -> bool {
+    modifiers.iter().any(|m| m.keyword == keyword)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassMember {
+    pub kind: MemberKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberKind {
+    Property(Property),
+    Const(ClassConst),
+    Method(Method),
+    TraitUse(TraitUse),
+    EnumCase(EnumCase),
+    /// A member that failed to parse, covering the tokens skipped to recover from it.
+    Error,
+}
+
+/// `public int $a = 1, $b;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub modifiers: Vec<Modifier>,
+    pub ty: Option<TypeHint>,
+    pub props: Vec<PropertyItem>,
+    pub attributes: Vec<AttributeGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyItem {
+    /// The name of the property, without the `$`.
+    pub name: Ident,
+    pub default: Option<Expr>,
+    pub span: Span,
+}
+
+/// `final public const int A = 1, B = 2;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassConst {
+    pub modifiers: Vec<Modifier>,
+    pub ty: Option<TypeHint>,
+    pub consts: Vec<ConstItem>,
+    pub attributes: Vec<AttributeGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Method {
+    pub modifiers: Vec<Modifier>,
+    pub function: FunctionDecl,
+}
+
+/// `use A, B { A::foo insteadof B; B::foo as protected bar; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitUse {
+    pub traits: Vec<Name>,
+    pub adaptations: Vec<TraitAdaptation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitAdaptation {
+    pub kind: AdaptationKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdaptationKind {
+    /// `A::foo insteadof B, C;`
+    InsteadOf {
+        trait_name: Name,
+        method: Ident,
+        instead_of: Vec<Name>,
+    },
+    /// `A::foo as protected bar;`, `foo as bar;` or `foo as private;`
+    Alias {
+        trait_name: Option<Name>,
+        method: Ident,
+        visibility: Option<Modifier>,
+        alias: Option<Ident>,
+    },
+}
+
+/// `case Hearts = 'H';`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumCase {
+    pub name: Ident,
+    /// The value of a backed enum case.
+    pub value: Option<Expr>,
+    pub attributes: Vec<AttributeGroup>,
+}
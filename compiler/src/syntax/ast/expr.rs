@@ -0,0 +1,429 @@
+use crate::syntax::{
+    lex::token::{Numeric, StringType},
+    source::Span,
+};
+
+use super::{
+    class::ClassLike,
+    function::{ArrowFunction, Closure},
+    keyword::Keyword,
+    name::{Ident, Name},
+    reserved::{ReservedCall, ReservedIdent},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Literal(Literal),
+
+    /// A double quoted string, heredoc or backtick string containing variables or `{$expr}`
+    /// parts. Strings without any interpolation are a `Literal::String` instead.
+    Interpolated {
+        kind: StringType,
+        parts: Vec<StringPart>,
+    },
+
+    /// A backtick string, which is executed as a shell command.
+    ShellExec(Vec<StringPart>),
+
+    /// `$name`, the name is stored without the `$`.
+    Variable(String),
+
+    /// `$$name` or `${expr}`.
+    VariableVariable(Box<Expr>),
+
+    /// `[1, 2]` or `array(1, 2)`, `short` is false for the `array()` form.
+    Array {
+        items: Vec<ArrayItem>,
+        short: bool,
+    },
+
+    /// A destructuring target, `list($a, $b)` or `[$a, , $b]`. Skipped positions are `None`.
+    List {
+        items: Vec<Option<ArrayItem>>,
+        short: bool,
+    },
+
+    /// A reference to a constant, `FOO` or `\Foo\BAR`.
+    ConstFetch(Name),
+
+    /// A constant or magic constant reserved by the language, `PHP_VERSION` or `__LINE__`.
+    ReservedConst(ReservedIdent),
+
+    /// `Foo::BAR`, `Foo::class` or `Foo::{$name}`.
+    ClassConstFetch {
+        class: ClassRef,
+        constant: MemberName,
+    },
+
+    /// `Foo::$bar`, the property is a `Variable` or `VariableVariable`.
+    StaticPropertyFetch {
+        class: ClassRef,
+        property: Box<Expr>,
+    },
+
+    /// `$a->b`, or `$a?->b` when `nullsafe` is set.
+    PropertyFetch {
+        object: Box<Expr>,
+        property: MemberName,
+        nullsafe: bool,
+    },
+
+    /// `$a[b]`, the index is `None` for the append form `$a[]`.
+    ArrayDim {
+        array: Box<Expr>,
+        index: Option<Box<Expr>>,
+    },
+
+    /// `foo()`, `$fn()` or `(expr)()`.
+    Call {
+        callee: Box<Expr>,
+        args: CallArgs,
+    },
+
+    /// `$a->b()`, or `$a?->b()` when `nullsafe` is set.
+    MethodCall {
+        object: Box<Expr>,
+        method: MemberName,
+        args: CallArgs,
+        nullsafe: bool,
+    },
+
+    /// `Foo::bar()`.
+    StaticCall {
+        class: ClassRef,
+        method: MemberName,
+        args: CallArgs,
+    },
+
+    /// `new Foo(...)`, `args` is `None` when the parenthesis are omitted.
+    New {
+        class: ClassRef,
+        args: Option<Vec<Arg>>,
+    },
+
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+
+    /// `++$a`, `$a--` and friends.
+    IncDec {
+        op: IncDecOp,
+        expr: Box<Expr>,
+    },
+
+    /// `$a = b`
+    Assign {
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+
+    /// `$a = &b`
+    AssignRef {
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+
+    /// `$a += b`, `$a ??= b` and every other compound assignment.
+    AssignOp {
+        op: BinaryOp,
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+
+    /// `a ? b : c`, or `a ?: c` when `then` is `None`.
+    Ternary {
+        cond: Box<Expr>,
+        then: Option<Box<Expr>>,
+        otherwise: Box<Expr>,
+    },
+
+    /// `(int) $a`
+    Cast {
+        kind: CastKind,
+        expr: Box<Expr>,
+    },
+
+    /// `$a instanceof Foo`
+    InstanceOf {
+        expr: Box<Expr>,
+        class: ClassRef,
+    },
+
+    /// `clone $a`
+    Clone(Box<Expr>),
+
+    /// `print $a`, which is an expression that always evaluates to `1`.
+    Print(Box<Expr>),
+
+    /// `@$a`, which suppresses any diagnostics raised while evaluating `$a`.
+    ErrorSuppress(Box<Expr>),
+
+    /// `isset()`, `empty()`, `eval()`, `exit()` and `die()`. The other reserved calls have a
+    /// node of their own.
+    Construct {
+        construct: ReservedCall,
+        args: Vec<Expr>,
+    },
+
+    /// `include`, `include_once`, `require` or `require_once`, kept as the `Keyword` used.
+    Include {
+        keyword: Keyword,
+        path: Box<Expr>,
+    },
+
+    /// `function () use ($a) { ... }`
+    Closure(Box<Closure>),
+
+    /// `fn ($a) => $a`
+    ArrowFunction(Box<ArrowFunction>),
+
+    /// `match ($a) { ... }`
+    Match {
+        subject: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+
+    /// `throw $e`, an expression since PHP 8.
+    Throw(Box<Expr>),
+
+    /// `yield`, `yield $v` or `yield $k => $v`.
+    Yield {
+        key: Option<Box<Expr>>,
+        value: Option<Box<Expr>>,
+    },
+
+    /// `yield from $a`
+    YieldFrom(Box<Expr>),
+
+    /// `await $a`, with the `async-await` experiment.
+    Await(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Null,
+    Bool(bool),
+    Numeric(Numeric),
+    /// The bytes of the string, escape sequences such as `"\xff"` need not give UTF-8.
+    String(Vec<u8>),
+}
+
+/// A part of an interpolated string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    /// Bytes with escape sequences already processed, and the span of the text as written.
+    Literal(Vec<u8>, Span),
+    /// `$a`, `$a[0]`, `$a->b`, `{$expr}` or `${expr}`.
+    Expr(Expr),
+}
+
+/// An element of an array literal or `list()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayItem {
+    pub key: Option<Expr>,
+    pub value: Expr,
+    /// `&$a`
+    pub by_ref: bool,
+    /// `...$a`
+    pub unpack: bool,
+    pub span: Span,
+}
+
+/// The class of a static access, `new` or `instanceof`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassRef {
+    /// `Foo`, `\Foo\Bar`, or one of `self`, `static` and `parent`.
+    Name(Name),
+    /// `$class::FOO` or `new $class`.
+    Expr(Box<Expr>),
+    /// `new class { ... }`, only valid for `new`.
+    Anonymous(Box<ClassLike>),
+}
+
+/// The name of a property, method or class constant after `->` or `::`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberName {
+    /// `$a->b`
+    Ident(Ident),
+    /// `$a->$b` or `$a->{expr}`
+    Expr(Box<Expr>),
+}
+
+/// The arguments of a call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallArgs {
+    List(Vec<Arg>),
+    /// `foo(...)`, the first-class callable syntax which creates a `Closure`.
+    FirstClassCallable(Span),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    /// The name of a named argument, `foo(name: $a)`.
+    pub name: Option<Ident>,
+    pub value: Expr,
+    /// `foo(...$args)`
+    pub unpack: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    /// The conditions of the arm, `None` for the `default` arm.
+    pub conditions: Option<Vec<Expr>>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// `+$a`
+    Plus,
+    /// `-$a`
+    Minus,
+    /// `!$a`
+    Not,
+    /// `~$a`
+    BitNot,
+}
+
+impl UnaryOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnaryOp::Plus => "+",
+            UnaryOp::Minus => "-",
+            UnaryOp::Not => "!",
+            UnaryOp::BitNot => "~",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncDecOp {
+    PreIncrement,
+    PreDecrement,
+    PostIncrement,
+    PostDecrement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    /// `.`
+    Concat,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    /// `&&`
+    BooleanAnd,
+    /// `||`
+    BooleanOr,
+    /// `and`, which has a lower precedence than assignment.
+    LogicalAnd,
+    /// `or`, which has a lower precedence than assignment.
+    LogicalOr,
+    /// `xor`
+    LogicalXor,
+    /// `==`
+    Equal,
+    /// `!=` or `<>`
+    NotEqual,
+    /// `===`
+    Identical,
+    /// `!==`
+    NotIdentical,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// `<=>`
+    Spaceship,
+    /// `??`
+    Coalesce,
+}
+
+impl BinaryOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Pow => "**",
+            BinaryOp::Concat => ".",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::ShiftLeft => "<<",
+            BinaryOp::ShiftRight => ">>",
+            BinaryOp::BooleanAnd => "&&",
+            BinaryOp::BooleanOr => "||",
+            BinaryOp::LogicalAnd => "and",
+            BinaryOp::LogicalOr => "or",
+            BinaryOp::LogicalXor => "xor",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Identical => "===",
+            BinaryOp::NotIdentical => "!==",
+            BinaryOp::Less => "<",
+            BinaryOp::LessOrEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterOrEqual => ">=",
+            BinaryOp::Spaceship => "<=>",
+            BinaryOp::Coalesce => "??",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastKind {
+    Int,
+    Float,
+    String,
+    Bool,
+    Array,
+    Object,
+    /// `(unset)`, removed in PHP 8 and only kept so it can be reported.
+    Unset,
+}
+
+impl CastKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CastKind::Int => "int",
+            CastKind::Float => "float",
+            CastKind::String => "string",
+            CastKind::Bool => "bool",
+            CastKind::Array => "array",
+            CastKind::Object => "object",
+            CastKind::Unset => "unset",
+        }
+    }
+}
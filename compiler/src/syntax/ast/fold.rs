@@ -0,0 +1,338 @@
+//! Rebuilding the tree, where a pass may replace any node by another of the same type, such
+//! as a constant folder turning `1 + 2` into `3` or a codemod rewriting calls.
+//!
+//! Each `Fold` method takes a node by value and returns what should stand in its place. By
+//! default it folds the children of the node with the function of the same name and returns
+//! the node itself. The children are reached through `visit_mut`, so only the kinds of node
+//! that are worth replacing on their own have a method here.
+
+use std::mem;
+
+use super::{
+    attribute::Attribute,
+    class::{ClassKind, ClassLike, ClassMember, MemberKind},
+    expr::{Arg, Expr, ExprKind, Literal, MatchArm},
+    function::{ArrowFunction, Closure, FunctionDecl, Param},
+    name::{Ident, Name, NameKind},
+    stmt::{Block, Script, Stmt, StmtKind},
+    types::{TypeHint, TypeHintKind},
+    visit_mut::{self, VisitorMut},
+};
+use crate::syntax::source::Span;
+
+pub trait Fold {
+    fn fold_script(&mut self, script: Script) -> Script {
+        fold_script(self, script)
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        fold_stmt(self, stmt)
+    }
+
+    fn fold_block(&mut self, block: Block) -> Block {
+        fold_block(self, block)
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        fold_expr(self, expr)
+    }
+
+    fn fold_arg(&mut self, arg: Arg) -> Arg {
+        fold_arg(self, arg)
+    }
+
+    fn fold_match_arm(&mut self, arm: MatchArm) -> MatchArm {
+        fold_match_arm(self, arm)
+    }
+
+    fn fold_function_decl(&mut self, function: FunctionDecl) -> FunctionDecl {
+        fold_function_decl(self, function)
+    }
+
+    fn fold_param(&mut self, param: Param) -> Param {
+        fold_param(self, param)
+    }
+
+    fn fold_closure(&mut self, closure: Closure) -> Closure {
+        fold_closure(self, closure)
+    }
+
+    fn fold_arrow_function(&mut self, function: ArrowFunction) -> ArrowFunction {
+        fold_arrow_function(self, function)
+    }
+
+    fn fold_class_like(&mut self, class: ClassLike) -> ClassLike {
+        fold_class_like(self, class)
+    }
+
+    fn fold_class_member(&mut self, member: ClassMember) -> ClassMember {
+        fold_class_member(self, member)
+    }
+
+    fn fold_attribute(&mut self, attribute: Attribute) -> Attribute {
+        fold_attribute(self, attribute)
+    }
+
+    fn fold_type_hint(&mut self, ty: TypeHint) -> TypeHint {
+        fold_type_hint(self, ty)
+    }
+
+    fn fold_name(&mut self, name: Name) -> Name {
+        name
+    }
+
+    fn fold_ident(&mut self, ident: Ident) -> Ident {
+        ident
+    }
+}
+
+/// Visits the children of a node, handing each one that `Fold` has a method for to the
+/// folder and putting the result in its place.
+struct Children<'a, F: ?Sized>(&'a mut F);
+
+/// Moves a node out of the tree, leaving a cheap placeholder until the folded node is put
+/// back.
+fn take<T>(node: &mut T, placeholder: impl FnOnce(Span) -> T, span: Span) -> T {
+    mem::replace(node, placeholder(span))
+}
+
+impl<F: Fold + ?Sized> VisitorMut for Children<'_, F> {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        let old = take(stmt, |span| Stmt::new(StmtKind::Nop, span), stmt.span);
+        *stmt = self.0.fold_stmt(old);
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        let empty = |span| Block {
+            stmts: Vec::new(),
+            span,
+        };
+        let old = take(block, empty, block.span);
+        *block = self.0.fold_block(old);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        let null = |span| Expr::new(ExprKind::Literal(Literal::Null), span);
+        let old = take(expr, null, expr.span);
+        *expr = self.0.fold_expr(old);
+    }
+
+    fn visit_arg(&mut self, arg: &mut Arg) {
+        let old = take(arg, empty_arg, arg.span);
+        *arg = self.0.fold_arg(old);
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        let empty = |span| MatchArm {
+            conditions: None,
+            body: empty_arg(span).value,
+            span,
+        };
+        let old = take(arm, empty, arm.span);
+        *arm = self.0.fold_match_arm(old);
+    }
+
+    fn visit_function_decl(&mut self, function: &mut FunctionDecl) {
+        let empty = |span| FunctionDecl {
+            name: Ident::new("", span),
+            is_async: false,
+            by_ref: false,
+            params: Vec::new(),
+            return_type: None,
+            body: None,
+            attributes: Vec::new(),
+            span,
+        };
+        let old = take(function, empty, function.span);
+        *function = self.0.fold_function_decl(old);
+    }
+
+    fn visit_param(&mut self, param: &mut Param) {
+        let empty = |span| Param {
+            name: Ident::new("", span),
+            ty: None,
+            default: None,
+            by_ref: false,
+            variadic: false,
+            modifiers: Vec::new(),
+            attributes: Vec::new(),
+            span,
+        };
+        let old = take(param, empty, param.span);
+        *param = self.0.fold_param(old);
+    }
+
+    fn visit_closure(&mut self, closure: &mut Closure) {
+        let empty = |span| Closure {
+            is_static: false,
+            is_async: false,
+            by_ref: false,
+            params: Vec::new(),
+            uses: Vec::new(),
+            return_type: None,
+            body: Block {
+                stmts: Vec::new(),
+                span,
+            },
+            attributes: Vec::new(),
+            span,
+        };
+        let old = take(closure, empty, closure.span);
+        *closure = self.0.fold_closure(old);
+    }
+
+    fn visit_arrow_function(&mut self, function: &mut ArrowFunction) {
+        let empty = |span| ArrowFunction {
+            is_static: false,
+            is_async: false,
+            by_ref: false,
+            params: Vec::new(),
+            return_type: None,
+            body: Box::new(empty_arg(span).value),
+            attributes: Vec::new(),
+            span,
+        };
+        let old = take(function, empty, function.span);
+        *function = self.0.fold_arrow_function(old);
+    }
+
+    fn visit_class_like(&mut self, class: &mut ClassLike) {
+        let empty = |span| ClassLike {
+            kind: ClassKind::Class,
+            name: None,
+            modifiers: Vec::new(),
+            extends: Vec::new(),
+            implements: Vec::new(),
+            backing_type: None,
+            members: Vec::new(),
+            attributes: Vec::new(),
+            span,
+        };
+        let old = take(class, empty, class.span);
+        *class = self.0.fold_class_like(old);
+    }
+
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+        let empty = |span| ClassMember {
+            kind: MemberKind::Error,
+            span,
+        };
+        let old = take(member, empty, member.span);
+        *member = self.0.fold_class_member(old);
+    }
+
+    fn visit_attribute(&mut self, attribute: &mut Attribute) {
+        let empty = |span| Attribute {
+            name: empty_name(span),
+            args: Vec::new(),
+            span,
+        };
+        let old = take(attribute, empty, attribute.span);
+        *attribute = self.0.fold_attribute(old);
+    }
+
+    fn visit_type_hint(&mut self, ty: &mut TypeHint) {
+        let empty = |span| TypeHint {
+            kind: TypeHintKind::Named(empty_name(span)),
+            span,
+        };
+        let old = take(ty, empty, ty.span);
+        *ty = self.0.fold_type_hint(old);
+    }
+
+    fn visit_name(&mut self, name: &mut Name) {
+        let old = take(name, empty_name, name.span);
+        *name = self.0.fold_name(old);
+    }
+
+    fn visit_ident(&mut self, ident: &mut Ident) {
+        let old = take(ident, |span| Ident::new("", span), ident.span);
+        *ident = self.0.fold_ident(old);
+    }
+}
+
+fn empty_arg(span: Span) -> Arg {
+    Arg {
+        name: None,
+        value: Expr::new(ExprKind::Literal(Literal::Null), span),
+        unpack: false,
+        span,
+    }
+}
+
+fn empty_name(span: Span) -> Name {
+    Name::new(Vec::new(), NameKind::Unqualified, span)
+}
+
+pub fn fold_script<F: Fold + ?Sized>(f: &mut F, mut script: Script) -> Script {
+    visit_mut::walk_script(&mut Children(f), &mut script);
+    script
+}
+
+pub fn fold_stmt<F: Fold + ?Sized>(f: &mut F, mut stmt: Stmt) -> Stmt {
+    visit_mut::walk_stmt(&mut Children(f), &mut stmt);
+    stmt
+}
+
+pub fn fold_block<F: Fold + ?Sized>(f: &mut F, mut block: Block) -> Block {
+    visit_mut::walk_block(&mut Children(f), &mut block);
+    block
+}
+
+pub fn fold_expr<F: Fold + ?Sized>(f: &mut F, mut expr: Expr) -> Expr {
+    visit_mut::walk_expr(&mut Children(f), &mut expr);
+    expr
+}
+
+pub fn fold_arg<F: Fold + ?Sized>(f: &mut F, mut arg: Arg) -> Arg {
+    visit_mut::walk_arg(&mut Children(f), &mut arg);
+    arg
+}
+
+pub fn fold_match_arm<F: Fold + ?Sized>(f: &mut F, mut arm: MatchArm) -> MatchArm {
+    visit_mut::walk_match_arm(&mut Children(f), &mut arm);
+    arm
+}
+
+pub fn fold_function_decl<F: Fold + ?Sized>(f: &mut F, mut function: FunctionDecl) -> FunctionDecl {
+    visit_mut::walk_function_decl(&mut Children(f), &mut function);
+    function
+}
+
+pub fn fold_param<F: Fold + ?Sized>(f: &mut F, mut param: Param) -> Param {
+    visit_mut::walk_param(&mut Children(f), &mut param);
+    param
+}
+
+pub fn fold_closure<F: Fold + ?Sized>(f: &mut F, mut closure: Closure) -> Closure {
+    visit_mut::walk_closure(&mut Children(f), &mut closure);
+    closure
+}
+
+pub fn fold_arrow_function<F: Fold + ?Sized>(
+    f: &mut F,
+    mut function: ArrowFunction,
+) -> ArrowFunction {
+    visit_mut::walk_arrow_function(&mut Children(f), &mut function);
+    function
+}
+
+pub fn fold_class_like<F: Fold + ?Sized>(f: &mut F, mut class: ClassLike) -> ClassLike {
+    visit_mut::walk_class_like(&mut Children(f), &mut class);
+    class
+}
+
+pub fn fold_class_member<F: Fold + ?Sized>(f: &mut F, mut member: ClassMember) -> ClassMember {
+    visit_mut::walk_class_member(&mut Children(f), &mut member);
+    member
+}
+
+pub fn fold_attribute<F: Fold + ?Sized>(f: &mut F, mut attribute: Attribute) -> Attribute {
+    visit_mut::walk_attribute(&mut Children(f), &mut attribute);
+    attribute
+}
+
+pub fn fold_type_hint<F: Fold + ?Sized>(f: &mut F, mut ty: TypeHint) -> TypeHint {
+    visit_mut::walk_type_hint(&mut Children(f), &mut ty);
+    ty
+}
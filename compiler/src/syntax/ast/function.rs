@@ -0,0 +1,82 @@
+use crate::syntax::source::Span;
+
+use super::{
+    attribute::AttributeGroup, class::Modifier, expr::Expr, name::Ident, stmt::Block,
+    types::TypeHint,
+};
+
+/// A named function, or a method when wrapped in a `Method`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDecl {
+    pub name: Ident,
+    /// `async function foo()`, with the `async-await` experiment.
+    pub is_async: bool,
+    /// `function &foo()`, returns by reference.
+    pub by_ref: bool,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeHint>,
+    /// `None` for abstract and interface methods.
+    pub body: Option<Block>,
+    pub attributes: Vec<AttributeGroup>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    /// The name of the parameter, without the `$`.
+    pub name: Ident,
+    pub ty: Option<TypeHint>,
+    pub default: Option<Expr>,
+    /// `&$a`
+    pub by_ref: bool,
+    /// `...$a`
+    pub variadic: bool,
+    /// Visibility and `readonly` modifiers of a promoted constructor parameter.
+    pub modifiers: Vec<Modifier>,
+    pub attributes: Vec<AttributeGroup>,
+    pub span: Span,
+}
+
+impl Param {
+    /// Whether this is a promoted constructor property, `public function __construct(private $a)`.
+    pub fn is_promoted(&self) -> bool {
+        !self.modifiers.is_empty()
+    }
+}
+
+/// An anonymous function, `function ($a) use ($b) { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure {
+    /// `static function () {}`, which is not bound to `$this`.
+    pub is_static: bool,
+    pub is_async: bool,
+    pub by_ref: bool,
+    pub params: Vec<Param>,
+    pub uses: Vec<ClosureUse>,
+    pub return_type: Option<TypeHint>,
+    pub body: Block,
+    pub attributes: Vec<AttributeGroup>,
+    pub span: Span,
+}
+
+/// A variable captured by a closure, `use ($a, &$b)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureUse {
+    /// The captured variable, without the `$`.
+    pub name: Ident,
+    pub by_ref: bool,
+    pub span: Span,
+}
+
+/// `fn ($a) => $a + $b`, which captures the variables it uses from the parent scope by value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrowFunction {
+    pub is_static: bool,
+    pub is_async: bool,
+    pub by_ref: bool,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeHint>,
+    pub body: Box<Expr>,
+    pub attributes: Vec<AttributeGroup>,
+    pub span: Span,
+}
@@ -8,7 +8,7 @@ pub const MAX_KEYWORD_LENGTH: usize = 11;
 /// ! as reserved calls!
 ///
 /// For more information regarding reserved keywords, visit: https://www.php.net/manual/en/reserved.keywords.php
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Keyword {
     /// Abstract, used to define "interface" classes and
     /// are not stand-alone initializable.
@@ -30,26 +30,25 @@ pub enum Keyword {
 
     /// Async, used to defined asynchronous functions.
     /// Php does NOT natively support this behavior and is therefore gated behind
-    /// a flag during runtime. HOWEVER scripts with this keyword WILL pass tokenization!
+    /// the `async-await` experiment, see `Experiments`. Without it `async` is lexed as a name.
     ///
     /// !! **FIBERS ARE NOT SUPPORTED WITH THIS FEATURE FLAG** !!
     ///
+    /// Calling an async function returns a suspended `Generator`, its body only runs once
+    /// it is awaited.
+    ///
     /// Usage:
     /// ```php
-    /// use std::Generics;
-    /// use std::async::Future;
-    /// use std::async::set_timeout;
-    ///
-    /// async function foo(): \Future<int> {
-    ///    return set_timeout(function() {
-    ///       return 0;
-    ///    }, 1000).poll();
+    /// async function foo(): int {
+    ///    return 0;
     /// }
     ///
     /// $x = await foo(); // 0
     /// ```
     Async,
 
+    /// Await, runs an async function's `Generator` to completion and gives its return value.
+    /// Gated behind the `async-await` experiment like `Async`.
     Await,
 
     /// Break, a control operator,
@@ -102,10 +101,10 @@ pub enum Keyword {
     /// Const, Defines a immutable (non-changable) variable within a class.
     ///
     /// By default, this will follow PHP's implementation where constants are only allowed
-    /// within classes.
+    /// within classes and at the top level of a script or namespace.
     ///
-    /// The alternative behavior, where constants can be created outside
-    /// a statement is possible with an experiements flag.
+    /// The alternative behavior, where constants can be declared inside functions and
+    /// blocks is possible with the `local-const` experiment.
     Const,
 
     /// Continue, a control word to skip the current execution tree if within a loop iteration and continue
@@ -174,6 +173,19 @@ pub enum Keyword {
     /// Functions identically to EndDeclare.
     EndWhile,
 
+    /// Enum, declares an enumeration, a type with a fixed set of cases.
+    /// Like in PHP it is only a keyword when a name follows, so `enum` is still a valid
+    /// function, class or constant name.
+    ///
+    /// Usage:
+    /// ```php
+    /// enum Suit: string {
+    ///     case Hearts = 'H';
+    ///     case Spades = 'S';
+    /// }
+    /// ```
+    Enum,
+
     /// Extends, A keyword used to "extend", "derive", or "inherit" the methods, constants and properties
     /// from the label following this keyword. For instance, `Foo` would extend `Bar` if `Foo extends Bar`
     /// is written.
@@ -279,6 +291,9 @@ pub enum Keyword {
 
     While,
 
+    /// Xor, the exclusive logical OP, with the same low precedence as `and` and `or`.
+    Xor,
+
     Yield,
 
     /// Use in `yield from` for generators.
@@ -312,6 +327,7 @@ impl Keyword {
             Keyword::EndIf => "endif",
             Keyword::EndSwitch => "endswitch",
             Keyword::EndWhile => "endwhile",
+            Keyword::Enum => "enum",
             Keyword::Extends => "extends",
             Keyword::Final => "final",
             Keyword::Finally => "finally",
@@ -347,6 +363,7 @@ impl Keyword {
             Keyword::Use => "use",
             Keyword::Var => "var",
             Keyword::While => "while",
+            Keyword::Xor => "xor",
             Keyword::Yield => "yield",
             Keyword::From => "from",
         }
@@ -384,6 +401,7 @@ impl FromStr for Keyword {
             "endif" => Ok(Self::EndIf),
             "endswitch" => Ok(Self::EndSwitch),
             "endwhile" => Ok(Self::EndWhile),
+            "enum" => Ok(Self::Enum),
             "extends" => Ok(Self::Extends),
             "final" => Ok(Self::Final),
             "finally" => Ok(Self::Finally),
@@ -419,6 +437,7 @@ impl FromStr for Keyword {
             "use" => Ok(Self::Use),
             "var" => Ok(Self::Var),
             "while" => Ok(Self::While),
+            "xor" => Ok(Self::Xor),
             "yield" => Ok(Self::Yield),
             "from" => Ok(Self::From),
             _ => Err(KeywordErr),
@@ -1,2 +1,12 @@
+pub mod attribute;
+pub mod class;
+pub mod expr;
+pub mod fold;
+pub mod function;
 pub mod keyword;
+pub mod name;
 pub mod reserved;
+pub mod stmt;
+pub mod types;
+pub mod visit;
+pub mod visit_mut;
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::syntax::source::Span;
+
+/// A single identifier, such as the name of a function, class, constant, member or label.
+///
+/// Variable names are also stored as identifiers, *without* the leading `$`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+impl Ident {
+    pub fn new(name: impl Into<String>, span: Span) -> Self {
+        Self {
+            name: name.into(),
+            span,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// How a name was written, which decides how it is resolved against the current namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    /// `Foo`, resolved through imports and the current namespace, functions and constants
+    /// fall back to the global namespace.
+    Unqualified,
+    /// `Foo\Bar`, the first part may be an imported alias.
+    Qualified,
+    /// `\Foo\Bar`, used as is.
+    FullyQualified,
+    /// `namespace\Foo`, relative to the current namespace only.
+    Relative,
+}
+
+/// A possibly namespaced name of a class, function or constant.
+///
+/// The parts are stored without separators, `\Foo\Bar` has the parts `["Foo", "Bar"]` and
+/// the kind `FullyQualified`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Name {
+    pub parts: Vec<String>,
+    pub kind: NameKind,
+    pub span: Span,
+    /// The fully qualified form of the name, filled in by `syntax::resolve`. Names that are
+    /// never resolved, such as `self` or builtin types, stay `None`.
+    pub resolved: Option<Box<ResolvedName>>,
+}
+
+impl Name {
+    pub fn new(parts: Vec<String>, kind: NameKind, span: Span) -> Self {
+        Self {
+            parts,
+            kind,
+            span,
+            resolved: None,
+        }
+    }
+
+    /// The last part of the name, `Bar` in `Foo\Bar`.
+    pub fn last(&self) -> &str {
+        self.parts.last().map_or("", String::as_str)
+    }
+
+    /// Whether this is one of the class names that refer to the current scope,
+    /// `self`, `static` or `parent`.
+    pub fn is_special_class(&self) -> bool {
+        self.kind == NameKind::Unqualified
+            && matches!(
+                self.last().to_ascii_lowercase().as_str(),
+                "self" | "static" | "parent"
+            )
+    }
+
+    /// The parts joined with `\`, without any leading separator.
+    pub fn joined(&self) -> String {
+        self.parts.join("\\")
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            NameKind::FullyQualified => f.write_str("\\")?,
+            NameKind::Relative => f.write_str("namespace\\")?,
+            _ => {}
+        }
+        f.write_str(&self.joined())
+    }
+}
+
+/// What a name refers to once imports and the current namespace are taken into account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedName {
+    /// The parts of the fully qualified name, `Foo\Bar` is `["Foo", "Bar"]`.
+    FullyQualified(Vec<String>),
+    /// An unqualified function or constant inside a namespace, which is looked up in the
+    /// namespace first and in the global namespace when it does not exist there, at runtime.
+    Fallback {
+        namespaced: Vec<String>,
+        global: String,
+    },
+}
+
+impl fmt::Display for ResolvedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolvedName::FullyQualified(parts) => write!(f, "\\{}", parts.join("\\")),
+            ResolvedName::Fallback { namespaced, global } => {
+                write!(f, "\\{} or \\{}", namespaced.join("\\"), global)
+            }
+        }
+    }
+}
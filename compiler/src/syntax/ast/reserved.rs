@@ -1,7 +1,9 @@
+use std::str::FromStr;
+
 /// The reserved constants or identifiers that php does NOT allow users to define themselves.
 /// Please not that NONE of the `E_*` constants are supported with this compiler.
 /// To retrieve these constants use the `\ErrorConstants::E_*` respectively.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReservedIdent {
     PhpVersion,
     PhpMajorVersion,
@@ -50,7 +52,130 @@ pub enum ReservedIdent {
     MagicTrait,
 }
 
-#[derive(Debug, Clone)]
+impl ReservedIdent {
+    /// Gets the identifier as it is written in a script.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReservedIdent::PhpVersion => "PHP_VERSION",
+            ReservedIdent::PhpMajorVersion => "PHP_MAJOR_VERSION",
+            ReservedIdent::PhpMinorVersion => "PHP_MINOR_VERSION",
+            ReservedIdent::PhpReleaseVersion => "PHP_RELEASE_VERSION",
+            ReservedIdent::PhpVersionId => "PHP_VERSION_ID",
+            ReservedIdent::PhpExtraVersion => "PHP_EXTRA_VERSION",
+            ReservedIdent::PhpZts => "PHP_ZTS",
+            ReservedIdent::PhpDebug => "PHP_DEBUG",
+            ReservedIdent::PhpMaxPathLen => "PHP_MAXPATHLEN",
+            ReservedIdent::PhpOs => "PHP_OS",
+            ReservedIdent::PhpOsFamily => "PHP_OS_FAMILY",
+            ReservedIdent::PhpSapi => "PHP_SAPI",
+            ReservedIdent::PhpEol => "PHP_EOL",
+            ReservedIdent::PhpIntMax => "PHP_INT_MAX",
+            ReservedIdent::PhpIntMin => "PHP_INT_MIN",
+            ReservedIdent::PhpFloatDig => "PHP_FLOAT_DIG",
+            ReservedIdent::PhpFloatEpsilon => "PHP_FLOAT_EPSILON",
+            ReservedIdent::PhpFloatMin => "PHP_FLOAT_MIN",
+            ReservedIdent::PhpFloatMax => "PHP_FLOAT_MAX",
+            ReservedIdent::DefaultIncludePath => "DEFAULT_INCLUDE_PATH",
+            ReservedIdent::PearInstallDir => "PEAR_INSTALL_DIR",
+            ReservedIdent::PearExtensionDir => "PEAR_EXTENSION_DIR",
+            ReservedIdent::PhpExtensionDir => "PHP_EXTENSION_DIR",
+            ReservedIdent::PhpPrefix => "PHP_PREFIX",
+            ReservedIdent::PhpBinDir => "PHP_BINDIR",
+            ReservedIdent::PhpBinary => "PHP_BINARY",
+            ReservedIdent::PhpManDir => "PHP_MANDIR",
+            ReservedIdent::PhpLibDir => "PHP_LIBDIR",
+            ReservedIdent::PhpDataDir => "PHP_DATADIR",
+            ReservedIdent::PhpLocaleStateDir => "PHP_LOCALSTATEDIR",
+            ReservedIdent::PhpConfigFilePath => "PHP_CONFIG_FILE_PATH",
+            ReservedIdent::PhpConfigFileScanDir => "PHP_CONFIG_FILE_SCAN_DIR",
+            ReservedIdent::PhpShLibSuffix => "PHP_SHLIB_SUFFIX",
+            ReservedIdent::PhpFdSetSize => "PHP_FD_SETSIZE",
+            ReservedIdent::MagicClass => "__CLASS__",
+            ReservedIdent::MagicDir => "__DIR__",
+            ReservedIdent::MagicFile => "__FILE__",
+            ReservedIdent::MagicFunction => "__FUNCTION__",
+            ReservedIdent::MagicLine => "__LINE__",
+            ReservedIdent::MagicMethod => "__METHOD__",
+            ReservedIdent::MagicNamespace => "__NAMESPACE__",
+            ReservedIdent::MagicTrait => "__TRAIT__",
+        }
+    }
+
+    /// Whether this is one of the compile time "magic" constants, like `__LINE__`.
+    pub fn is_magic(self) -> bool {
+        matches!(
+            self,
+            ReservedIdent::MagicClass
+                | ReservedIdent::MagicDir
+                | ReservedIdent::MagicFile
+                | ReservedIdent::MagicFunction
+                | ReservedIdent::MagicLine
+                | ReservedIdent::MagicMethod
+                | ReservedIdent::MagicNamespace
+                | ReservedIdent::MagicTrait
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReservedIdentErr;
+
+impl FromStr for ReservedIdent {
+    type Err = ReservedIdentErr;
+
+    /// Constants are case sensitive, while magic constants are not.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PHP_VERSION" => Ok(Self::PhpVersion),
+            "PHP_MAJOR_VERSION" => Ok(Self::PhpMajorVersion),
+            "PHP_MINOR_VERSION" => Ok(Self::PhpMinorVersion),
+            "PHP_RELEASE_VERSION" => Ok(Self::PhpReleaseVersion),
+            "PHP_VERSION_ID" => Ok(Self::PhpVersionId),
+            "PHP_EXTRA_VERSION" => Ok(Self::PhpExtraVersion),
+            "PHP_ZTS" => Ok(Self::PhpZts),
+            "PHP_DEBUG" => Ok(Self::PhpDebug),
+            "PHP_MAXPATHLEN" => Ok(Self::PhpMaxPathLen),
+            "PHP_OS" => Ok(Self::PhpOs),
+            "PHP_OS_FAMILY" => Ok(Self::PhpOsFamily),
+            "PHP_SAPI" => Ok(Self::PhpSapi),
+            "PHP_EOL" => Ok(Self::PhpEol),
+            "PHP_INT_MAX" => Ok(Self::PhpIntMax),
+            "PHP_INT_MIN" => Ok(Self::PhpIntMin),
+            "PHP_FLOAT_DIG" => Ok(Self::PhpFloatDig),
+            "PHP_FLOAT_EPSILON" => Ok(Self::PhpFloatEpsilon),
+            "PHP_FLOAT_MIN" => Ok(Self::PhpFloatMin),
+            "PHP_FLOAT_MAX" => Ok(Self::PhpFloatMax),
+            "DEFAULT_INCLUDE_PATH" => Ok(Self::DefaultIncludePath),
+            "PEAR_INSTALL_DIR" => Ok(Self::PearInstallDir),
+            "PEAR_EXTENSION_DIR" => Ok(Self::PearExtensionDir),
+            "PHP_EXTENSION_DIR" => Ok(Self::PhpExtensionDir),
+            "PHP_PREFIX" => Ok(Self::PhpPrefix),
+            "PHP_BINDIR" => Ok(Self::PhpBinDir),
+            "PHP_BINARY" => Ok(Self::PhpBinary),
+            "PHP_MANDIR" => Ok(Self::PhpManDir),
+            "PHP_LIBDIR" => Ok(Self::PhpLibDir),
+            "PHP_DATADIR" => Ok(Self::PhpDataDir),
+            "PHP_LOCALSTATEDIR" => Ok(Self::PhpLocaleStateDir),
+            "PHP_CONFIG_FILE_PATH" => Ok(Self::PhpConfigFilePath),
+            "PHP_CONFIG_FILE_SCAN_DIR" => Ok(Self::PhpConfigFileScanDir),
+            "PHP_SHLIB_SUFFIX" => Ok(Self::PhpShLibSuffix),
+            "PHP_FD_SETSIZE" => Ok(Self::PhpFdSetSize),
+            _ => match s.to_ascii_uppercase().as_str() {
+                "__CLASS__" => Ok(Self::MagicClass),
+                "__DIR__" => Ok(Self::MagicDir),
+                "__FILE__" => Ok(Self::MagicFile),
+                "__FUNCTION__" => Ok(Self::MagicFunction),
+                "__LINE__" => Ok(Self::MagicLine),
+                "__METHOD__" => Ok(Self::MagicMethod),
+                "__NAMESPACE__" => Ok(Self::MagicNamespace),
+                "__TRAIT__" => Ok(Self::MagicTrait),
+                _ => Err(ReservedIdentErr),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReservedCall {
     /// Represents the `__halt_compiler()` function
     HaltCompiler,
@@ -64,3 +189,42 @@ pub enum ReservedCall {
     List,
     Unset,
 }
+
+impl ReservedCall {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReservedCall::HaltCompiler => "__halt_compiler",
+            ReservedCall::Array => "array",
+            ReservedCall::Die => "die",
+            ReservedCall::Empty => "empty",
+            ReservedCall::Eval => "eval",
+            ReservedCall::Exit => "exit",
+            ReservedCall::IsSet => "isset",
+            ReservedCall::List => "list",
+            ReservedCall::Unset => "unset",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReservedCallErr;
+
+impl FromStr for ReservedCall {
+    type Err = ReservedCallErr;
+
+    /// Like all function names, reserved calls are case insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "__halt_compiler" => Ok(Self::HaltCompiler),
+            "array" => Ok(Self::Array),
+            "die" => Ok(Self::Die),
+            "empty" => Ok(Self::Empty),
+            "eval" => Ok(Self::Eval),
+            "exit" => Ok(Self::Exit),
+            "isset" => Ok(Self::IsSet),
+            "list" => Ok(Self::List),
+            "unset" => Ok(Self::Unset),
+            _ => Err(ReservedCallErr),
+        }
+    }
+}
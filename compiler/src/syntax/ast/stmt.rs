@@ -0,0 +1,226 @@
+use crate::syntax::source::Span;
+
+use super::{
+    class::ClassLike,
+    expr::Expr,
+    function::FunctionDecl,
+    name::{Ident, Name},
+};
+
+/// A whole script, the root of the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+/// A list of statements, either `{ ... }` or the body of an alternative syntax statement,
+/// such as `if (...): ... endif;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    /// An expression followed by `;`.
+    Expr(Expr),
+
+    /// `echo a, b;`, also produced by the `<?=` open tag.
+    Echo(Vec<Expr>),
+
+    /// Text outside of the PHP tags.
+    InlineHtml(String),
+
+    Return(Option<Expr>),
+
+    If {
+        cond: Expr,
+        then: Block,
+        elseifs: Vec<ElseIf>,
+        otherwise: Option<Block>,
+    },
+
+    While {
+        cond: Expr,
+        body: Block,
+    },
+
+    DoWhile {
+        body: Block,
+        cond: Expr,
+    },
+
+    /// `for (init; cond; step)`, each part is a comma separated list of expressions.
+    For {
+        init: Vec<Expr>,
+        cond: Vec<Expr>,
+        step: Vec<Expr>,
+        body: Block,
+    },
+
+    /// `foreach ($subject as $key => &$value)`
+    Foreach {
+        subject: Expr,
+        key: Option<Box<Expr>>,
+        value: Expr,
+        by_ref: bool,
+        body: Block,
+    },
+
+    Switch {
+        subject: Expr,
+        cases: Vec<SwitchCase>,
+    },
+
+    /// `break;` or `break 2;`
+    Break(Option<u32>),
+
+    /// `continue;` or `continue 2;`
+    Continue(Option<u32>),
+
+    /// `global $a, $b;`
+    Global(Vec<Expr>),
+
+    /// `static $a = 1, $b;` inside a function.
+    Static(Vec<StaticVar>),
+
+    /// `unset($a, $b);`
+    Unset(Vec<Expr>),
+
+    /// `declare(strict_types=1);`, or the block forms when `body` is set.
+    Declare {
+        directives: Vec<DeclareDirective>,
+        body: Option<Block>,
+    },
+
+    /// `goto a;`
+    Goto(Ident),
+
+    /// `a:`
+    Label(Ident),
+
+    /// `namespace Foo;` when `body` is `None`, or `namespace Foo { ... }`.
+    /// The name is `None` for the global namespace block, `namespace { ... }`.
+    Namespace {
+        name: Option<Name>,
+        body: Option<Block>,
+    },
+
+    /// `use Foo\Bar as Baz, function foo;`
+    Use {
+        kind: UseKind,
+        uses: Vec<UseItem>,
+    },
+
+    /// `use Foo\{Bar, function baz};`
+    GroupUse {
+        prefix: Name,
+        kind: UseKind,
+        uses: Vec<UseItem>,
+    },
+
+    /// `const A = 1, B = 2;` outside of a class.
+    Const(Vec<ConstItem>),
+
+    Function(FunctionDecl),
+
+    ClassLike(ClassLike),
+
+    Try {
+        body: Block,
+        catches: Vec<Catch>,
+        finally: Option<Block>,
+    },
+
+    /// A nested `{ ... }` block.
+    Block(Block),
+
+    /// An empty statement, `;`.
+    Nop,
+
+    /// `__halt_compiler();`, the text after it is kept as is.
+    HaltCompiler(String),
+
+    /// A statement that failed to parse, covering the tokens skipped to recover from it.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElseIf {
+    pub cond: Expr,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchCase {
+    /// The value compared against, `None` for `default:`.
+    pub cond: Option<Expr>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticVar {
+    /// The name of the variable, without the `$`.
+    pub name: Ident,
+    pub default: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclareDirective {
+    pub name: Ident,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// What a `use` statement imports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UseKind {
+    /// Classes, interfaces, traits, enums and namespaces.
+    Normal,
+    Function,
+    Const,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UseItem {
+    pub name: Name,
+    pub alias: Option<Ident>,
+    /// The kind of a single import in a mixed group use, `use Foo\{Bar, function baz}`.
+    pub kind: Option<UseKind>,
+    pub span: Span,
+}
+
+/// `A = 1`, in a `const` statement or a class constant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstItem {
+    pub name: Ident,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// `catch (A | B $e) { ... }`, the variable is optional since PHP 8.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catch {
+    pub types: Vec<Name>,
+    /// The name of the variable, without the `$`.
+    pub var: Option<Ident>,
+    pub body: Block,
+    pub span: Span,
+}
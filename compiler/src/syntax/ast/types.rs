@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::syntax::source::Span;
+
+use super::name::{Name, NameKind};
+
+/// A type declaration, as written on a parameter, property, class constant or return type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeHint {
+    pub kind: TypeHintKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeHintKind {
+    /// A single class or builtin type, such as `Foo`, `int` or `self`.
+    Named(Name),
+    /// `?T`
+    Nullable(Box<TypeHint>),
+    /// `A|B`, the members may be intersections for DNF types, `(A&B)|null`.
+    Union(Vec<TypeHint>),
+    /// `A&B`
+    Intersection(Vec<TypeHint>),
+}
+
+impl TypeHint {
+    /// The meaning of the declaration, with `?T` spelled out as `T|null` and builtin types
+    /// told apart from class names.
+    pub fn to_type(&self) -> Type {
+        match &self.kind {
+            TypeHintKind::Named(name) => match BuiltinType::from_name(name) {
+                Some(builtin) => Type::Builtin(builtin),
+                None => Type::Class(match &name.resolved {
+                    Some(resolved) => resolved.to_string(),
+                    None => name.to_string(),
+                }),
+            },
+            TypeHintKind::Nullable(inner) => {
+                Type::Union(vec![inner.to_type(), Type::Builtin(BuiltinType::Null)])
+            }
+            TypeHintKind::Union(types) => Type::Union(types.iter().map(Self::to_type).collect()),
+            TypeHintKind::Intersection(types) => {
+                Type::Intersection(types.iter().map(Self::to_type).collect())
+            }
+        }
+    }
+}
+
+/// A type as the compiler understands it, shared by the parser's checks and anything that
+/// reasons about values later on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Builtin(BuiltinType),
+    /// A class, interface or enum, by its resolved name when names have been resolved and
+    /// as written otherwise.
+    Class(String),
+    /// `A|B`, nullable types are unions with `null`.
+    Union(Vec<Type>),
+    /// `A&B`, only ever made of class types.
+    Intersection(Vec<Type>),
+}
+
+impl Type {
+    /// Whether `null` is a valid value of the type.
+    pub fn allows_null(&self) -> bool {
+        match self {
+            Type::Builtin(builtin) => matches!(builtin, BuiltinType::Null | BuiltinType::Mixed),
+            Type::Class(_) | Type::Intersection(_) => false,
+            Type::Union(types) => types.iter().any(Type::allows_null),
+        }
+    }
+
+    /// Whether the type is the given builtin type or a union that contains it.
+    pub fn contains(&self, builtin: BuiltinType) -> bool {
+        match self {
+            Type::Builtin(b) => *b == builtin,
+            Type::Union(types) => types.iter().any(|ty| ty.contains(builtin)),
+            Type::Class(_) | Type::Intersection(_) => false,
+        }
+    }
+
+    /// The class names of a class type or an intersection, lowercased for comparison.
+    fn class_names(&self) -> Option<Vec<String>> {
+        match self {
+            Type::Class(name) => Some(vec![name.to_ascii_lowercase()]),
+            Type::Intersection(types) => types
+                .iter()
+                .map(|ty| match ty {
+                    Type::Class(name) => Some(name.to_ascii_lowercase()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// Whether every value of `self` is also a value of `other`, as far as can be told
+    /// without knowing how classes relate to each other.
+    pub fn is_subtype_of(&self, other: &Type) -> bool {
+        if let Type::Union(types) = self {
+            return types.iter().all(|ty| ty.is_subtype_of(other));
+        }
+        match (self, other) {
+            (_, Type::Builtin(BuiltinType::Mixed)) => !self.contains(BuiltinType::Void),
+            (_, Type::Union(types)) => types.iter().any(|ty| self.is_subtype_of(ty)),
+            (
+                Type::Builtin(BuiltinType::True | BuiltinType::False),
+                Type::Builtin(BuiltinType::Bool),
+            ) => true,
+            (Type::Builtin(BuiltinType::Array), Type::Builtin(BuiltinType::Iterable)) => true,
+            (Type::Class(_) | Type::Intersection(_), Type::Builtin(BuiltinType::Object)) => true,
+            (Type::Builtin(a), Type::Builtin(b)) => a == b,
+            _ => match (self.class_names(), other.class_names()) {
+                // `A&B` is narrower than `A`.
+                (Some(narrow), Some(wide)) => wide.iter().all(|name| narrow.contains(name)),
+                _ => false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Builtin(builtin) => f.write_str(builtin.as_str()),
+            Type::Class(name) => f.write_str(name),
+            Type::Union(types) => {
+                // PHP writes a single type with `null` as `?T`.
+                if let [ty @ (Type::Builtin(_) | Type::Class(_)), Type::Builtin(BuiltinType::Null)] =
+                    types.as_slice()
+                {
+                    if !matches!(ty, Type::Builtin(BuiltinType::Null | BuiltinType::Mixed)) {
+                        return write!(f, "?{}", ty);
+                    }
+                }
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("|")?;
+                    }
+                    match ty {
+                        Type::Intersection(_) => write!(f, "({})", ty)?,
+                        _ => write!(f, "{}", ty)?,
+                    }
+                }
+                Ok(())
+            }
+            Type::Intersection(types) => {
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("&")?;
+                    }
+                    write!(f, "{}", ty)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The types that are part of the language rather than classes. Their names are
+/// case-insensitive and only mean the builtin type when written unqualified, `\int` is a
+/// class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinType {
+    Int,
+    Float,
+    String,
+    Bool,
+    Array,
+    Object,
+    Iterable,
+    Callable,
+    Mixed,
+    Void,
+    Never,
+    Null,
+    False,
+    True,
+    /// `self`, the class the declaration is written in.
+    SelfClass,
+    /// `static`, the class the method was called on, only allowed as a return type.
+    Static,
+    /// `parent`, the parent of the class the declaration is written in.
+    Parent,
+}
+
+impl BuiltinType {
+    pub const ALL: [BuiltinType; 17] = [
+        BuiltinType::Int,
+        BuiltinType::Float,
+        BuiltinType::String,
+        BuiltinType::Bool,
+        BuiltinType::Array,
+        BuiltinType::Object,
+        BuiltinType::Iterable,
+        BuiltinType::Callable,
+        BuiltinType::Mixed,
+        BuiltinType::Void,
+        BuiltinType::Never,
+        BuiltinType::Null,
+        BuiltinType::False,
+        BuiltinType::True,
+        BuiltinType::SelfClass,
+        BuiltinType::Static,
+        BuiltinType::Parent,
+    ];
+
+    /// The builtin type a name refers to, if any.
+    pub fn from_name(name: &Name) -> Option<Self> {
+        if name.kind != NameKind::Unqualified {
+            return None;
+        }
+        Self::ALL
+            .into_iter()
+            .find(|builtin| builtin.as_str().eq_ignore_ascii_case(name.last()))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BuiltinType::Int => "int",
+            BuiltinType::Float => "float",
+            BuiltinType::String => "string",
+            BuiltinType::Bool => "bool",
+            BuiltinType::Array => "array",
+            BuiltinType::Object => "object",
+            BuiltinType::Iterable => "iterable",
+            BuiltinType::Callable => "callable",
+            BuiltinType::Mixed => "mixed",
+            BuiltinType::Void => "void",
+            BuiltinType::Never => "never",
+            BuiltinType::Null => "null",
+            BuiltinType::False => "false",
+            BuiltinType::True => "true",
+            BuiltinType::SelfClass => "self",
+            BuiltinType::Static => "static",
+            BuiltinType::Parent => "parent",
+        }
+    }
+
+    /// `mixed`, `void` and `never` can not be combined with any other type.
+    pub fn is_standalone(self) -> bool {
+        matches!(
+            self,
+            BuiltinType::Mixed | BuiltinType::Void | BuiltinType::Never
+        )
+    }
+}
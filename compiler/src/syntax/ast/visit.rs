@@ -0,0 +1,765 @@
+//! Read-only traversal of the tree.
+//!
+//! `Visitor` has one method per kind of node, each of which calls the `walk_*` function of the
+//! same name by default to visit the children of the node in source order. A pass overrides
+//! the methods for the nodes it cares about and calls the matching `walk_*` function wherever
+//! it still wants the children visited. `visit_mut` is the same for a mutable tree.
+
+use super::{
+    attribute::{Attribute, AttributeGroup},
+    class::{
+        AdaptationKind, ClassConst, ClassLike, ClassMember, EnumCase, MemberKind, Method, Property,
+        PropertyItem, TraitAdaptation, TraitUse,
+    },
+    expr::{Arg, ArrayItem, CallArgs, ClassRef, Expr, ExprKind, MatchArm, MemberName, StringPart},
+    function::{ArrowFunction, Closure, ClosureUse, FunctionDecl, Param},
+    name::{Ident, Name},
+    stmt::{
+        Block, Catch, ConstItem, DeclareDirective, ElseIf, Script, StaticVar, Stmt, StmtKind,
+        SwitchCase, UseItem,
+    },
+    types::{TypeHint, TypeHintKind},
+};
+
+pub trait Visitor<'ast> {
+    fn visit_script(&mut self, script: &'ast Script) {
+        walk_script(self, script)
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_else_if(&mut self, else_if: &'ast ElseIf) {
+        walk_else_if(self, else_if)
+    }
+
+    fn visit_switch_case(&mut self, case: &'ast SwitchCase) {
+        walk_switch_case(self, case)
+    }
+
+    fn visit_static_var(&mut self, var: &'ast StaticVar) {
+        walk_static_var(self, var)
+    }
+
+    fn visit_declare_directive(&mut self, directive: &'ast DeclareDirective) {
+        walk_declare_directive(self, directive)
+    }
+
+    fn visit_use_item(&mut self, item: &'ast UseItem) {
+        walk_use_item(self, item)
+    }
+
+    fn visit_const_item(&mut self, item: &'ast ConstItem) {
+        walk_const_item(self, item)
+    }
+
+    fn visit_catch(&mut self, catch: &'ast Catch) {
+        walk_catch(self, catch)
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_string_part(&mut self, part: &'ast StringPart) {
+        walk_string_part(self, part)
+    }
+
+    fn visit_array_item(&mut self, item: &'ast ArrayItem) {
+        walk_array_item(self, item)
+    }
+
+    fn visit_class_ref(&mut self, class: &'ast ClassRef) {
+        walk_class_ref(self, class)
+    }
+
+    fn visit_member_name(&mut self, member: &'ast MemberName) {
+        walk_member_name(self, member)
+    }
+
+    fn visit_call_args(&mut self, args: &'ast CallArgs) {
+        walk_call_args(self, args)
+    }
+
+    fn visit_arg(&mut self, arg: &'ast Arg) {
+        walk_arg(self, arg)
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_function_decl(&mut self, function: &'ast FunctionDecl) {
+        walk_function_decl(self, function)
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        walk_param(self, param)
+    }
+
+    fn visit_closure(&mut self, closure: &'ast Closure) {
+        walk_closure(self, closure)
+    }
+
+    fn visit_closure_use(&mut self, closure_use: &'ast ClosureUse) {
+        walk_closure_use(self, closure_use)
+    }
+
+    fn visit_arrow_function(&mut self, function: &'ast ArrowFunction) {
+        walk_arrow_function(self, function)
+    }
+
+    fn visit_class_like(&mut self, class: &'ast ClassLike) {
+        walk_class_like(self, class)
+    }
+
+    fn visit_class_member(&mut self, member: &'ast ClassMember) {
+        walk_class_member(self, member)
+    }
+
+    fn visit_property(&mut self, property: &'ast Property) {
+        walk_property(self, property)
+    }
+
+    fn visit_property_item(&mut self, item: &'ast PropertyItem) {
+        walk_property_item(self, item)
+    }
+
+    fn visit_class_const(&mut self, constant: &'ast ClassConst) {
+        walk_class_const(self, constant)
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        walk_method(self, method)
+    }
+
+    fn visit_trait_use(&mut self, trait_use: &'ast TraitUse) {
+        walk_trait_use(self, trait_use)
+    }
+
+    fn visit_trait_adaptation(&mut self, adaptation: &'ast TraitAdaptation) {
+        walk_trait_adaptation(self, adaptation)
+    }
+
+    fn visit_enum_case(&mut self, case: &'ast EnumCase) {
+        walk_enum_case(self, case)
+    }
+
+    fn visit_attribute_group(&mut self, group: &'ast AttributeGroup) {
+        walk_attribute_group(self, group)
+    }
+
+    fn visit_attribute(&mut self, attribute: &'ast Attribute) {
+        walk_attribute(self, attribute)
+    }
+
+    fn visit_type_hint(&mut self, ty: &'ast TypeHint) {
+        walk_type_hint(self, ty)
+    }
+
+    /// A class, function, constant or namespace name. What it names depends on where it
+    /// is, which the method visiting its parent can tell.
+    fn visit_name(&mut self, _name: &'ast Name) {}
+
+    /// The name of a declaration, label, variable or member.
+    fn visit_ident(&mut self, _ident: &'ast Ident) {}
+}
+
+pub fn walk_script<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, script: &'ast Script) {
+    for stmt in &script.stmts {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, stmt: &'ast Stmt) {
+    match &stmt.kind {
+        StmtKind::Expr(expr) => v.visit_expr(expr),
+        StmtKind::Echo(exprs) | StmtKind::Global(exprs) | StmtKind::Unset(exprs) => {
+            for expr in exprs {
+                v.visit_expr(expr);
+            }
+        }
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        StmtKind::If {
+            cond,
+            then,
+            elseifs,
+            otherwise,
+        } => {
+            v.visit_expr(cond);
+            v.visit_block(then);
+            for else_if in elseifs {
+                v.visit_else_if(else_if);
+            }
+            if let Some(otherwise) = otherwise {
+                v.visit_block(otherwise);
+            }
+        }
+        StmtKind::While { cond, body } => {
+            v.visit_expr(cond);
+            v.visit_block(body);
+        }
+        StmtKind::DoWhile { body, cond } => {
+            v.visit_block(body);
+            v.visit_expr(cond);
+        }
+        StmtKind::For {
+            init,
+            cond,
+            step,
+            body,
+        } => {
+            for expr in init.iter().chain(cond).chain(step) {
+                v.visit_expr(expr);
+            }
+            v.visit_block(body);
+        }
+        StmtKind::Foreach {
+            subject,
+            key,
+            value,
+            body,
+            ..
+        } => {
+            v.visit_expr(subject);
+            if let Some(key) = key {
+                v.visit_expr(key);
+            }
+            v.visit_expr(value);
+            v.visit_block(body);
+        }
+        StmtKind::Switch { subject, cases } => {
+            v.visit_expr(subject);
+            for case in cases {
+                v.visit_switch_case(case);
+            }
+        }
+        StmtKind::Static(vars) => {
+            for var in vars {
+                v.visit_static_var(var);
+            }
+        }
+        StmtKind::Declare { directives, body } => {
+            for directive in directives {
+                v.visit_declare_directive(directive);
+            }
+            if let Some(body) = body {
+                v.visit_block(body);
+            }
+        }
+        StmtKind::Goto(label) | StmtKind::Label(label) => v.visit_ident(label),
+        StmtKind::Namespace { name, body } => {
+            if let Some(name) = name {
+                v.visit_name(name);
+            }
+            if let Some(body) = body {
+                v.visit_block(body);
+            }
+        }
+        StmtKind::Use { uses, .. } => {
+            for item in uses {
+                v.visit_use_item(item);
+            }
+        }
+        StmtKind::GroupUse { prefix, uses, .. } => {
+            v.visit_name(prefix);
+            for item in uses {
+                v.visit_use_item(item);
+            }
+        }
+        StmtKind::Const(items) => {
+            for item in items {
+                v.visit_const_item(item);
+            }
+        }
+        StmtKind::Function(function) => v.visit_function_decl(function),
+        StmtKind::ClassLike(class) => v.visit_class_like(class),
+        StmtKind::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            v.visit_block(body);
+            for catch in catches {
+                v.visit_catch(catch);
+            }
+            if let Some(finally) = finally {
+                v.visit_block(finally);
+            }
+        }
+        StmtKind::Block(block) => v.visit_block(block),
+        StmtKind::InlineHtml(_)
+        | StmtKind::Break(_)
+        | StmtKind::Continue(_)
+        | StmtKind::Nop
+        | StmtKind::HaltCompiler(_)
+        | StmtKind::Error => {}
+    }
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, block: &'ast Block) {
+    for stmt in &block.stmts {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_else_if<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, else_if: &'ast ElseIf) {
+    v.visit_expr(&else_if.cond);
+    v.visit_block(&else_if.body);
+}
+
+pub fn walk_switch_case<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, case: &'ast SwitchCase) {
+    if let Some(cond) = &case.cond {
+        v.visit_expr(cond);
+    }
+    for stmt in &case.body {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_static_var<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, var: &'ast StaticVar) {
+    v.visit_ident(&var.name);
+    if let Some(default) = &var.default {
+        v.visit_expr(default);
+    }
+}
+
+pub fn walk_declare_directive<'ast, V: Visitor<'ast> + ?Sized>(
+    v: &mut V,
+    directive: &'ast DeclareDirective,
+) {
+    v.visit_ident(&directive.name);
+    v.visit_expr(&directive.value);
+}
+
+pub fn walk_use_item<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, item: &'ast UseItem) {
+    v.visit_name(&item.name);
+    if let Some(alias) = &item.alias {
+        v.visit_ident(alias);
+    }
+}
+
+pub fn walk_const_item<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, item: &'ast ConstItem) {
+    v.visit_ident(&item.name);
+    v.visit_expr(&item.value);
+}
+
+pub fn walk_catch<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, catch: &'ast Catch) {
+    for ty in &catch.types {
+        v.visit_name(ty);
+    }
+    if let Some(var) = &catch.var {
+        v.visit_ident(var);
+    }
+    v.visit_block(&catch.body);
+}
+
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, expr: &'ast Expr) {
+    match &expr.kind {
+        ExprKind::Interpolated { parts, .. } | ExprKind::ShellExec(parts) => {
+            for part in parts {
+                v.visit_string_part(part);
+            }
+        }
+        ExprKind::VariableVariable(expr)
+        | ExprKind::Unary { expr, .. }
+        | ExprKind::IncDec { expr, .. }
+        | ExprKind::Cast { expr, .. }
+        | ExprKind::Clone(expr)
+        | ExprKind::Print(expr)
+        | ExprKind::ErrorSuppress(expr)
+        | ExprKind::Include { path: expr, .. }
+        | ExprKind::Throw(expr)
+        | ExprKind::YieldFrom(expr)
+        | ExprKind::Await(expr) => v.visit_expr(expr),
+        ExprKind::Array { items, .. } => {
+            for item in items {
+                v.visit_array_item(item);
+            }
+        }
+        ExprKind::List { items, .. } => {
+            for item in items.iter().flatten() {
+                v.visit_array_item(item);
+            }
+        }
+        ExprKind::ConstFetch(name) => v.visit_name(name),
+        ExprKind::ClassConstFetch { class, constant } => {
+            v.visit_class_ref(class);
+            v.visit_member_name(constant);
+        }
+        ExprKind::StaticPropertyFetch { class, property } => {
+            v.visit_class_ref(class);
+            v.visit_expr(property);
+        }
+        ExprKind::PropertyFetch {
+            object, property, ..
+        } => {
+            v.visit_expr(object);
+            v.visit_member_name(property);
+        }
+        ExprKind::ArrayDim { array, index } => {
+            v.visit_expr(array);
+            if let Some(index) = index {
+                v.visit_expr(index);
+            }
+        }
+        ExprKind::Call { callee, args } => {
+            v.visit_expr(callee);
+            v.visit_call_args(args);
+        }
+        ExprKind::MethodCall {
+            object,
+            method,
+            args,
+            ..
+        } => {
+            v.visit_expr(object);
+            v.visit_member_name(method);
+            v.visit_call_args(args);
+        }
+        ExprKind::StaticCall {
+            class,
+            method,
+            args,
+        } => {
+            v.visit_class_ref(class);
+            v.visit_member_name(method);
+            v.visit_call_args(args);
+        }
+        ExprKind::New { class, args } => {
+            v.visit_class_ref(class);
+            if let Some(args) = args {
+                for arg in args {
+                    v.visit_arg(arg);
+                }
+            }
+        }
+        ExprKind::Binary { left, right, .. }
+        | ExprKind::Assign {
+            target: left,
+            value: right,
+        }
+        | ExprKind::AssignRef {
+            target: left,
+            value: right,
+        }
+        | ExprKind::AssignOp {
+            target: left,
+            value: right,
+            ..
+        } => {
+            v.visit_expr(left);
+            v.visit_expr(right);
+        }
+        ExprKind::Ternary {
+            cond,
+            then,
+            otherwise,
+        } => {
+            v.visit_expr(cond);
+            if let Some(then) = then {
+                v.visit_expr(then);
+            }
+            v.visit_expr(otherwise);
+        }
+        ExprKind::InstanceOf { expr, class } => {
+            v.visit_expr(expr);
+            v.visit_class_ref(class);
+        }
+        ExprKind::Construct { args, .. } => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Closure(closure) => v.visit_closure(closure),
+        ExprKind::ArrowFunction(function) => v.visit_arrow_function(function),
+        ExprKind::Match { subject, arms } => {
+            v.visit_expr(subject);
+            for arm in arms {
+                v.visit_match_arm(arm);
+            }
+        }
+        ExprKind::Yield { key, value } => {
+            if let Some(key) = key {
+                v.visit_expr(key);
+            }
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        ExprKind::Literal(_) | ExprKind::Variable(_) | ExprKind::ReservedConst(_) => {}
+    }
+}
+
+pub fn walk_string_part<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, part: &'ast StringPart) {
+    if let StringPart::Expr(expr) = part {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_array_item<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, item: &'ast ArrayItem) {
+    if let Some(key) = &item.key {
+        v.visit_expr(key);
+    }
+    v.visit_expr(&item.value);
+}
+
+pub fn walk_class_ref<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, class: &'ast ClassRef) {
+    match class {
+        ClassRef::Name(name) => v.visit_name(name),
+        ClassRef::Expr(expr) => v.visit_expr(expr),
+        ClassRef::Anonymous(class) => v.visit_class_like(class),
+    }
+}
+
+pub fn walk_member_name<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, member: &'ast MemberName) {
+    match member {
+        MemberName::Ident(ident) => v.visit_ident(ident),
+        MemberName::Expr(expr) => v.visit_expr(expr),
+    }
+}
+
+pub fn walk_call_args<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, args: &'ast CallArgs) {
+    if let CallArgs::List(args) = args {
+        for arg in args {
+            v.visit_arg(arg);
+        }
+    }
+}
+
+pub fn walk_arg<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, arg: &'ast Arg) {
+    if let Some(name) = &arg.name {
+        v.visit_ident(name);
+    }
+    v.visit_expr(&arg.value);
+}
+
+pub fn walk_match_arm<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, arm: &'ast MatchArm) {
+    if let Some(conditions) = &arm.conditions {
+        for condition in conditions {
+            v.visit_expr(condition);
+        }
+    }
+    v.visit_expr(&arm.body);
+}
+
+pub fn walk_function_decl<'ast, V: Visitor<'ast> + ?Sized>(
+    v: &mut V,
+    function: &'ast FunctionDecl,
+) {
+    for group in &function.attributes {
+        v.visit_attribute_group(group);
+    }
+    v.visit_ident(&function.name);
+    for param in &function.params {
+        v.visit_param(param);
+    }
+    if let Some(ty) = &function.return_type {
+        v.visit_type_hint(ty);
+    }
+    if let Some(body) = &function.body {
+        v.visit_block(body);
+    }
+}
+
+pub fn walk_param<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, param: &'ast Param) {
+    for group in &param.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(ty) = &param.ty {
+        v.visit_type_hint(ty);
+    }
+    v.visit_ident(&param.name);
+    if let Some(default) = &param.default {
+        v.visit_expr(default);
+    }
+}
+
+pub fn walk_closure<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, closure: &'ast Closure) {
+    for group in &closure.attributes {
+        v.visit_attribute_group(group);
+    }
+    for param in &closure.params {
+        v.visit_param(param);
+    }
+    for closure_use in &closure.uses {
+        v.visit_closure_use(closure_use);
+    }
+    if let Some(ty) = &closure.return_type {
+        v.visit_type_hint(ty);
+    }
+    v.visit_block(&closure.body);
+}
+
+pub fn walk_closure_use<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, closure_use: &'ast ClosureUse) {
+    v.visit_ident(&closure_use.name);
+}
+
+pub fn walk_arrow_function<'ast, V: Visitor<'ast> + ?Sized>(
+    v: &mut V,
+    function: &'ast ArrowFunction,
+) {
+    for group in &function.attributes {
+        v.visit_attribute_group(group);
+    }
+    for param in &function.params {
+        v.visit_param(param);
+    }
+    if let Some(ty) = &function.return_type {
+        v.visit_type_hint(ty);
+    }
+    v.visit_expr(&function.body);
+}
+
+pub fn walk_class_like<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, class: &'ast ClassLike) {
+    for group in &class.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(name) = &class.name {
+        v.visit_ident(name);
+    }
+    if let Some(ty) = &class.backing_type {
+        v.visit_type_hint(ty);
+    }
+    for name in class.extends.iter().chain(&class.implements) {
+        v.visit_name(name);
+    }
+    for member in &class.members {
+        v.visit_class_member(member);
+    }
+}
+
+pub fn walk_class_member<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, member: &'ast ClassMember) {
+    match &member.kind {
+        MemberKind::Property(property) => v.visit_property(property),
+        MemberKind::Const(constant) => v.visit_class_const(constant),
+        MemberKind::Method(method) => v.visit_method(method),
+        MemberKind::TraitUse(trait_use) => v.visit_trait_use(trait_use),
+        MemberKind::EnumCase(case) => v.visit_enum_case(case),
+        MemberKind::Error => {}
+    }
+}
+
+pub fn walk_property<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, property: &'ast Property) {
+    for group in &property.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(ty) = &property.ty {
+        v.visit_type_hint(ty);
+    }
+    for item in &property.props {
+        v.visit_property_item(item);
+    }
+}
+
+pub fn walk_property_item<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, item: &'ast PropertyItem) {
+    v.visit_ident(&item.name);
+    if let Some(default) = &item.default {
+        v.visit_expr(default);
+    }
+}
+
+pub fn walk_class_const<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, constant: &'ast ClassConst) {
+    for group in &constant.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(ty) = &constant.ty {
+        v.visit_type_hint(ty);
+    }
+    for item in &constant.consts {
+        v.visit_const_item(item);
+    }
+}
+
+pub fn walk_method<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, method: &'ast Method) {
+    v.visit_function_decl(&method.function);
+}
+
+pub fn walk_trait_use<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, trait_use: &'ast TraitUse) {
+    for name in &trait_use.traits {
+        v.visit_name(name);
+    }
+    for adaptation in &trait_use.adaptations {
+        v.visit_trait_adaptation(adaptation);
+    }
+}
+
+pub fn walk_trait_adaptation<'ast, V: Visitor<'ast> + ?Sized>(
+    v: &mut V,
+    adaptation: &'ast TraitAdaptation,
+) {
+    match &adaptation.kind {
+        AdaptationKind::InsteadOf {
+            trait_name,
+            method,
+            instead_of,
+        } => {
+            v.visit_name(trait_name);
+            v.visit_ident(method);
+            for name in instead_of {
+                v.visit_name(name);
+            }
+        }
+        AdaptationKind::Alias {
+            trait_name,
+            method,
+            alias,
+            ..
+        } => {
+            if let Some(trait_name) = trait_name {
+                v.visit_name(trait_name);
+            }
+            v.visit_ident(method);
+            if let Some(alias) = alias {
+                v.visit_ident(alias);
+            }
+        }
+    }
+}
+
+pub fn walk_enum_case<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, case: &'ast EnumCase) {
+    for group in &case.attributes {
+        v.visit_attribute_group(group);
+    }
+    v.visit_ident(&case.name);
+    if let Some(value) = &case.value {
+        v.visit_expr(value);
+    }
+}
+
+pub fn walk_attribute_group<'ast, V: Visitor<'ast> + ?Sized>(
+    v: &mut V,
+    group: &'ast AttributeGroup,
+) {
+    for attribute in &group.attributes {
+        v.visit_attribute(attribute);
+    }
+}
+
+pub fn walk_attribute<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, attribute: &'ast Attribute) {
+    v.visit_name(&attribute.name);
+    for arg in &attribute.args {
+        v.visit_arg(arg);
+    }
+}
+
+pub fn walk_type_hint<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, ty: &'ast TypeHint) {
+    match &ty.kind {
+        TypeHintKind::Named(name) => v.visit_name(name),
+        TypeHintKind::Nullable(inner) => v.visit_type_hint(inner),
+        TypeHintKind::Union(types) | TypeHintKind::Intersection(types) => {
+            for ty in types {
+                v.visit_type_hint(ty);
+            }
+        }
+    }
+}
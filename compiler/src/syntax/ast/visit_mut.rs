@@ -0,0 +1,748 @@
+//! Traversal of the tree that may change nodes in place, the mutable twin of `visit`.
+//!
+//! `VisitorMut` and the `walk_*` functions here mirror `Visitor` and `visit::walk_*` exactly.
+//! Nodes can be edited but not replaced by a node of another kind, `fold` does that.
+
+use super::{
+    attribute::{Attribute, AttributeGroup},
+    class::{
+        AdaptationKind, ClassConst, ClassLike, ClassMember, EnumCase, MemberKind, Method, Property,
+        PropertyItem, TraitAdaptation, TraitUse,
+    },
+    expr::{Arg, ArrayItem, CallArgs, ClassRef, Expr, ExprKind, MatchArm, MemberName, StringPart},
+    function::{ArrowFunction, Closure, ClosureUse, FunctionDecl, Param},
+    name::{Ident, Name},
+    stmt::{
+        Block, Catch, ConstItem, DeclareDirective, ElseIf, Script, StaticVar, Stmt, StmtKind,
+        SwitchCase, UseItem,
+    },
+    types::{TypeHint, TypeHintKind},
+};
+
+pub trait VisitorMut {
+    fn visit_script(&mut self, script: &mut Script) {
+        walk_script(self, script)
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_else_if(&mut self, else_if: &mut ElseIf) {
+        walk_else_if(self, else_if)
+    }
+
+    fn visit_switch_case(&mut self, case: &mut SwitchCase) {
+        walk_switch_case(self, case)
+    }
+
+    fn visit_static_var(&mut self, var: &mut StaticVar) {
+        walk_static_var(self, var)
+    }
+
+    fn visit_declare_directive(&mut self, directive: &mut DeclareDirective) {
+        walk_declare_directive(self, directive)
+    }
+
+    fn visit_use_item(&mut self, item: &mut UseItem) {
+        walk_use_item(self, item)
+    }
+
+    fn visit_const_item(&mut self, item: &mut ConstItem) {
+        walk_const_item(self, item)
+    }
+
+    fn visit_catch(&mut self, catch: &mut Catch) {
+        walk_catch(self, catch)
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_string_part(&mut self, part: &mut StringPart) {
+        walk_string_part(self, part)
+    }
+
+    fn visit_array_item(&mut self, item: &mut ArrayItem) {
+        walk_array_item(self, item)
+    }
+
+    fn visit_class_ref(&mut self, class: &mut ClassRef) {
+        walk_class_ref(self, class)
+    }
+
+    fn visit_member_name(&mut self, member: &mut MemberName) {
+        walk_member_name(self, member)
+    }
+
+    fn visit_call_args(&mut self, args: &mut CallArgs) {
+        walk_call_args(self, args)
+    }
+
+    fn visit_arg(&mut self, arg: &mut Arg) {
+        walk_arg(self, arg)
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_function_decl(&mut self, function: &mut FunctionDecl) {
+        walk_function_decl(self, function)
+    }
+
+    fn visit_param(&mut self, param: &mut Param) {
+        walk_param(self, param)
+    }
+
+    fn visit_closure(&mut self, closure: &mut Closure) {
+        walk_closure(self, closure)
+    }
+
+    fn visit_closure_use(&mut self, closure_use: &mut ClosureUse) {
+        walk_closure_use(self, closure_use)
+    }
+
+    fn visit_arrow_function(&mut self, function: &mut ArrowFunction) {
+        walk_arrow_function(self, function)
+    }
+
+    fn visit_class_like(&mut self, class: &mut ClassLike) {
+        walk_class_like(self, class)
+    }
+
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+        walk_class_member(self, member)
+    }
+
+    fn visit_property(&mut self, property: &mut Property) {
+        walk_property(self, property)
+    }
+
+    fn visit_property_item(&mut self, item: &mut PropertyItem) {
+        walk_property_item(self, item)
+    }
+
+    fn visit_class_const(&mut self, constant: &mut ClassConst) {
+        walk_class_const(self, constant)
+    }
+
+    fn visit_method(&mut self, method: &mut Method) {
+        walk_method(self, method)
+    }
+
+    fn visit_trait_use(&mut self, trait_use: &mut TraitUse) {
+        walk_trait_use(self, trait_use)
+    }
+
+    fn visit_trait_adaptation(&mut self, adaptation: &mut TraitAdaptation) {
+        walk_trait_adaptation(self, adaptation)
+    }
+
+    fn visit_enum_case(&mut self, case: &mut EnumCase) {
+        walk_enum_case(self, case)
+    }
+
+    fn visit_attribute_group(&mut self, group: &mut AttributeGroup) {
+        walk_attribute_group(self, group)
+    }
+
+    fn visit_attribute(&mut self, attribute: &mut Attribute) {
+        walk_attribute(self, attribute)
+    }
+
+    fn visit_type_hint(&mut self, ty: &mut TypeHint) {
+        walk_type_hint(self, ty)
+    }
+
+    /// A class, function, constant or namespace name. What it names depends on where it
+    /// is, which the method visiting its parent can tell.
+    fn visit_name(&mut self, _name: &mut Name) {}
+
+    /// The name of a declaration, label, variable or member.
+    fn visit_ident(&mut self, _ident: &mut Ident) {}
+}
+
+pub fn walk_script<V: VisitorMut + ?Sized>(v: &mut V, script: &mut Script) {
+    for stmt in &mut script.stmts {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<V: VisitorMut + ?Sized>(v: &mut V, stmt: &mut Stmt) {
+    match &mut stmt.kind {
+        StmtKind::Expr(expr) => v.visit_expr(expr),
+        StmtKind::Echo(exprs) | StmtKind::Global(exprs) | StmtKind::Unset(exprs) => {
+            for expr in exprs {
+                v.visit_expr(expr);
+            }
+        }
+        StmtKind::Return(expr) => {
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        StmtKind::If {
+            cond,
+            then,
+            elseifs,
+            otherwise,
+        } => {
+            v.visit_expr(cond);
+            v.visit_block(then);
+            for else_if in elseifs {
+                v.visit_else_if(else_if);
+            }
+            if let Some(otherwise) = otherwise {
+                v.visit_block(otherwise);
+            }
+        }
+        StmtKind::While { cond, body } => {
+            v.visit_expr(cond);
+            v.visit_block(body);
+        }
+        StmtKind::DoWhile { body, cond } => {
+            v.visit_block(body);
+            v.visit_expr(cond);
+        }
+        StmtKind::For {
+            init,
+            cond,
+            step,
+            body,
+        } => {
+            for expr in init.iter_mut().chain(cond).chain(step) {
+                v.visit_expr(expr);
+            }
+            v.visit_block(body);
+        }
+        StmtKind::Foreach {
+            subject,
+            key,
+            value,
+            body,
+            ..
+        } => {
+            v.visit_expr(subject);
+            if let Some(key) = key {
+                v.visit_expr(key);
+            }
+            v.visit_expr(value);
+            v.visit_block(body);
+        }
+        StmtKind::Switch { subject, cases } => {
+            v.visit_expr(subject);
+            for case in cases {
+                v.visit_switch_case(case);
+            }
+        }
+        StmtKind::Static(vars) => {
+            for var in vars {
+                v.visit_static_var(var);
+            }
+        }
+        StmtKind::Declare { directives, body } => {
+            for directive in directives {
+                v.visit_declare_directive(directive);
+            }
+            if let Some(body) = body {
+                v.visit_block(body);
+            }
+        }
+        StmtKind::Goto(label) | StmtKind::Label(label) => v.visit_ident(label),
+        StmtKind::Namespace { name, body } => {
+            if let Some(name) = name {
+                v.visit_name(name);
+            }
+            if let Some(body) = body {
+                v.visit_block(body);
+            }
+        }
+        StmtKind::Use { uses, .. } => {
+            for item in uses {
+                v.visit_use_item(item);
+            }
+        }
+        StmtKind::GroupUse { prefix, uses, .. } => {
+            v.visit_name(prefix);
+            for item in uses {
+                v.visit_use_item(item);
+            }
+        }
+        StmtKind::Const(items) => {
+            for item in items {
+                v.visit_const_item(item);
+            }
+        }
+        StmtKind::Function(function) => v.visit_function_decl(function),
+        StmtKind::ClassLike(class) => v.visit_class_like(class),
+        StmtKind::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            v.visit_block(body);
+            for catch in catches {
+                v.visit_catch(catch);
+            }
+            if let Some(finally) = finally {
+                v.visit_block(finally);
+            }
+        }
+        StmtKind::Block(block) => v.visit_block(block),
+        StmtKind::InlineHtml(_)
+        | StmtKind::Break(_)
+        | StmtKind::Continue(_)
+        | StmtKind::Nop
+        | StmtKind::HaltCompiler(_)
+        | StmtKind::Error => {}
+    }
+}
+
+pub fn walk_block<V: VisitorMut + ?Sized>(v: &mut V, block: &mut Block) {
+    for stmt in &mut block.stmts {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_else_if<V: VisitorMut + ?Sized>(v: &mut V, else_if: &mut ElseIf) {
+    v.visit_expr(&mut else_if.cond);
+    v.visit_block(&mut else_if.body);
+}
+
+pub fn walk_switch_case<V: VisitorMut + ?Sized>(v: &mut V, case: &mut SwitchCase) {
+    if let Some(cond) = &mut case.cond {
+        v.visit_expr(cond);
+    }
+    for stmt in &mut case.body {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_static_var<V: VisitorMut + ?Sized>(v: &mut V, var: &mut StaticVar) {
+    v.visit_ident(&mut var.name);
+    if let Some(default) = &mut var.default {
+        v.visit_expr(default);
+    }
+}
+
+pub fn walk_declare_directive<V: VisitorMut + ?Sized>(v: &mut V, directive: &mut DeclareDirective) {
+    v.visit_ident(&mut directive.name);
+    v.visit_expr(&mut directive.value);
+}
+
+pub fn walk_use_item<V: VisitorMut + ?Sized>(v: &mut V, item: &mut UseItem) {
+    v.visit_name(&mut item.name);
+    if let Some(alias) = &mut item.alias {
+        v.visit_ident(alias);
+    }
+}
+
+pub fn walk_const_item<V: VisitorMut + ?Sized>(v: &mut V, item: &mut ConstItem) {
+    v.visit_ident(&mut item.name);
+    v.visit_expr(&mut item.value);
+}
+
+pub fn walk_catch<V: VisitorMut + ?Sized>(v: &mut V, catch: &mut Catch) {
+    for ty in &mut catch.types {
+        v.visit_name(ty);
+    }
+    if let Some(var) = &mut catch.var {
+        v.visit_ident(var);
+    }
+    v.visit_block(&mut catch.body);
+}
+
+pub fn walk_expr<V: VisitorMut + ?Sized>(v: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Interpolated { parts, .. } | ExprKind::ShellExec(parts) => {
+            for part in parts {
+                v.visit_string_part(part);
+            }
+        }
+        ExprKind::VariableVariable(expr)
+        | ExprKind::Unary { expr, .. }
+        | ExprKind::IncDec { expr, .. }
+        | ExprKind::Cast { expr, .. }
+        | ExprKind::Clone(expr)
+        | ExprKind::Print(expr)
+        | ExprKind::ErrorSuppress(expr)
+        | ExprKind::Include { path: expr, .. }
+        | ExprKind::Throw(expr)
+        | ExprKind::YieldFrom(expr)
+        | ExprKind::Await(expr) => v.visit_expr(expr),
+        ExprKind::Array { items, .. } => {
+            for item in items {
+                v.visit_array_item(item);
+            }
+        }
+        ExprKind::List { items, .. } => {
+            for item in items.iter_mut().flatten() {
+                v.visit_array_item(item);
+            }
+        }
+        ExprKind::ConstFetch(name) => v.visit_name(name),
+        ExprKind::ClassConstFetch { class, constant } => {
+            v.visit_class_ref(class);
+            v.visit_member_name(constant);
+        }
+        ExprKind::StaticPropertyFetch { class, property } => {
+            v.visit_class_ref(class);
+            v.visit_expr(property);
+        }
+        ExprKind::PropertyFetch {
+            object, property, ..
+        } => {
+            v.visit_expr(object);
+            v.visit_member_name(property);
+        }
+        ExprKind::ArrayDim { array, index } => {
+            v.visit_expr(array);
+            if let Some(index) = index {
+                v.visit_expr(index);
+            }
+        }
+        ExprKind::Call { callee, args } => {
+            v.visit_expr(callee);
+            v.visit_call_args(args);
+        }
+        ExprKind::MethodCall {
+            object,
+            method,
+            args,
+            ..
+        } => {
+            v.visit_expr(object);
+            v.visit_member_name(method);
+            v.visit_call_args(args);
+        }
+        ExprKind::StaticCall {
+            class,
+            method,
+            args,
+        } => {
+            v.visit_class_ref(class);
+            v.visit_member_name(method);
+            v.visit_call_args(args);
+        }
+        ExprKind::New { class, args } => {
+            v.visit_class_ref(class);
+            if let Some(args) = args {
+                for arg in args {
+                    v.visit_arg(arg);
+                }
+            }
+        }
+        ExprKind::Binary { left, right, .. }
+        | ExprKind::Assign {
+            target: left,
+            value: right,
+        }
+        | ExprKind::AssignRef {
+            target: left,
+            value: right,
+        }
+        | ExprKind::AssignOp {
+            target: left,
+            value: right,
+            ..
+        } => {
+            v.visit_expr(left);
+            v.visit_expr(right);
+        }
+        ExprKind::Ternary {
+            cond,
+            then,
+            otherwise,
+        } => {
+            v.visit_expr(cond);
+            if let Some(then) = then {
+                v.visit_expr(then);
+            }
+            v.visit_expr(otherwise);
+        }
+        ExprKind::InstanceOf { expr, class } => {
+            v.visit_expr(expr);
+            v.visit_class_ref(class);
+        }
+        ExprKind::Construct { args, .. } => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Closure(closure) => v.visit_closure(closure),
+        ExprKind::ArrowFunction(function) => v.visit_arrow_function(function),
+        ExprKind::Match { subject, arms } => {
+            v.visit_expr(subject);
+            for arm in arms {
+                v.visit_match_arm(arm);
+            }
+        }
+        ExprKind::Yield { key, value } => {
+            if let Some(key) = key {
+                v.visit_expr(key);
+            }
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        ExprKind::Literal(_) | ExprKind::Variable(_) | ExprKind::ReservedConst(_) => {}
+    }
+}
+
+pub fn walk_string_part<V: VisitorMut + ?Sized>(v: &mut V, part: &mut StringPart) {
+    if let StringPart::Expr(expr) = part {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_array_item<V: VisitorMut + ?Sized>(v: &mut V, item: &mut ArrayItem) {
+    if let Some(key) = &mut item.key {
+        v.visit_expr(key);
+    }
+    v.visit_expr(&mut item.value);
+}
+
+pub fn walk_class_ref<V: VisitorMut + ?Sized>(v: &mut V, class: &mut ClassRef) {
+    match class {
+        ClassRef::Name(name) => v.visit_name(name),
+        ClassRef::Expr(expr) => v.visit_expr(expr),
+        ClassRef::Anonymous(class) => v.visit_class_like(class),
+    }
+}
+
+pub fn walk_member_name<V: VisitorMut + ?Sized>(v: &mut V, member: &mut MemberName) {
+    match member {
+        MemberName::Ident(ident) => v.visit_ident(ident),
+        MemberName::Expr(expr) => v.visit_expr(expr),
+    }
+}
+
+pub fn walk_call_args<V: VisitorMut + ?Sized>(v: &mut V, args: &mut CallArgs) {
+    if let CallArgs::List(args) = args {
+        for arg in args {
+            v.visit_arg(arg);
+        }
+    }
+}
+
+pub fn walk_arg<V: VisitorMut + ?Sized>(v: &mut V, arg: &mut Arg) {
+    if let Some(name) = &mut arg.name {
+        v.visit_ident(name);
+    }
+    v.visit_expr(&mut arg.value);
+}
+
+pub fn walk_match_arm<V: VisitorMut + ?Sized>(v: &mut V, arm: &mut MatchArm) {
+    if let Some(conditions) = &mut arm.conditions {
+        for condition in conditions {
+            v.visit_expr(condition);
+        }
+    }
+    v.visit_expr(&mut arm.body);
+}
+
+pub fn walk_function_decl<V: VisitorMut + ?Sized>(v: &mut V, function: &mut FunctionDecl) {
+    for group in &mut function.attributes {
+        v.visit_attribute_group(group);
+    }
+    v.visit_ident(&mut function.name);
+    for param in &mut function.params {
+        v.visit_param(param);
+    }
+    if let Some(ty) = &mut function.return_type {
+        v.visit_type_hint(ty);
+    }
+    if let Some(body) = &mut function.body {
+        v.visit_block(body);
+    }
+}
+
+pub fn walk_param<V: VisitorMut + ?Sized>(v: &mut V, param: &mut Param) {
+    for group in &mut param.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(ty) = &mut param.ty {
+        v.visit_type_hint(ty);
+    }
+    v.visit_ident(&mut param.name);
+    if let Some(default) = &mut param.default {
+        v.visit_expr(default);
+    }
+}
+
+pub fn walk_closure<V: VisitorMut + ?Sized>(v: &mut V, closure: &mut Closure) {
+    for group in &mut closure.attributes {
+        v.visit_attribute_group(group);
+    }
+    for param in &mut closure.params {
+        v.visit_param(param);
+    }
+    for closure_use in &mut closure.uses {
+        v.visit_closure_use(closure_use);
+    }
+    if let Some(ty) = &mut closure.return_type {
+        v.visit_type_hint(ty);
+    }
+    v.visit_block(&mut closure.body);
+}
+
+pub fn walk_closure_use<V: VisitorMut + ?Sized>(v: &mut V, closure_use: &mut ClosureUse) {
+    v.visit_ident(&mut closure_use.name);
+}
+
+pub fn walk_arrow_function<V: VisitorMut + ?Sized>(v: &mut V, function: &mut ArrowFunction) {
+    for group in &mut function.attributes {
+        v.visit_attribute_group(group);
+    }
+    for param in &mut function.params {
+        v.visit_param(param);
+    }
+    if let Some(ty) = &mut function.return_type {
+        v.visit_type_hint(ty);
+    }
+    v.visit_expr(&mut function.body);
+}
+
+pub fn walk_class_like<V: VisitorMut + ?Sized>(v: &mut V, class: &mut ClassLike) {
+    for group in &mut class.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(name) = &mut class.name {
+        v.visit_ident(name);
+    }
+    if let Some(ty) = &mut class.backing_type {
+        v.visit_type_hint(ty);
+    }
+    for name in class.extends.iter_mut().chain(&mut class.implements) {
+        v.visit_name(name);
+    }
+    for member in &mut class.members {
+        v.visit_class_member(member);
+    }
+}
+
+pub fn walk_class_member<V: VisitorMut + ?Sized>(v: &mut V, member: &mut ClassMember) {
+    match &mut member.kind {
+        MemberKind::Property(property) => v.visit_property(property),
+        MemberKind::Const(constant) => v.visit_class_const(constant),
+        MemberKind::Method(method) => v.visit_method(method),
+        MemberKind::TraitUse(trait_use) => v.visit_trait_use(trait_use),
+        MemberKind::EnumCase(case) => v.visit_enum_case(case),
+        MemberKind::Error => {}
+    }
+}
+
+pub fn walk_property<V: VisitorMut + ?Sized>(v: &mut V, property: &mut Property) {
+    for group in &mut property.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(ty) = &mut property.ty {
+        v.visit_type_hint(ty);
+    }
+    for item in &mut property.props {
+        v.visit_property_item(item);
+    }
+}
+
+pub fn walk_property_item<V: VisitorMut + ?Sized>(v: &mut V, item: &mut PropertyItem) {
+    v.visit_ident(&mut item.name);
+    if let Some(default) = &mut item.default {
+        v.visit_expr(default);
+    }
+}
+
+pub fn walk_class_const<V: VisitorMut + ?Sized>(v: &mut V, constant: &mut ClassConst) {
+    for group in &mut constant.attributes {
+        v.visit_attribute_group(group);
+    }
+    if let Some(ty) = &mut constant.ty {
+        v.visit_type_hint(ty);
+    }
+    for item in &mut constant.consts {
+        v.visit_const_item(item);
+    }
+}
+
+pub fn walk_method<V: VisitorMut + ?Sized>(v: &mut V, method: &mut Method) {
+    v.visit_function_decl(&mut method.function);
+}
+
+pub fn walk_trait_use<V: VisitorMut + ?Sized>(v: &mut V, trait_use: &mut TraitUse) {
+    for name in &mut trait_use.traits {
+        v.visit_name(name);
+    }
+    for adaptation in &mut trait_use.adaptations {
+        v.visit_trait_adaptation(adaptation);
+    }
+}
+
+pub fn walk_trait_adaptation<V: VisitorMut + ?Sized>(v: &mut V, adaptation: &mut TraitAdaptation) {
+    match &mut adaptation.kind {
+        AdaptationKind::InsteadOf {
+            trait_name,
+            method,
+            instead_of,
+        } => {
+            v.visit_name(trait_name);
+            v.visit_ident(method);
+            for name in instead_of {
+                v.visit_name(name);
+            }
+        }
+        AdaptationKind::Alias {
+            trait_name,
+            method,
+            alias,
+            ..
+        } => {
+            if let Some(trait_name) = trait_name {
+                v.visit_name(trait_name);
+            }
+            v.visit_ident(method);
+            if let Some(alias) = alias {
+                v.visit_ident(alias);
+            }
+        }
+    }
+}
+
+pub fn walk_enum_case<V: VisitorMut + ?Sized>(v: &mut V, case: &mut EnumCase) {
+    for group in &mut case.attributes {
+        v.visit_attribute_group(group);
+    }
+    v.visit_ident(&mut case.name);
+    if let Some(value) = &mut case.value {
+        v.visit_expr(value);
+    }
+}
+
+pub fn walk_attribute_group<V: VisitorMut + ?Sized>(v: &mut V, group: &mut AttributeGroup) {
+    for attribute in &mut group.attributes {
+        v.visit_attribute(attribute);
+    }
+}
+
+pub fn walk_attribute<V: VisitorMut + ?Sized>(v: &mut V, attribute: &mut Attribute) {
+    v.visit_name(&mut attribute.name);
+    for arg in &mut attribute.args {
+        v.visit_arg(arg);
+    }
+}
+
+pub fn walk_type_hint<V: VisitorMut + ?Sized>(v: &mut V, ty: &mut TypeHint) {
+    match &mut ty.kind {
+        TypeHintKind::Named(name) => v.visit_name(name),
+        TypeHintKind::Nullable(inner) => v.visit_type_hint(inner),
+        TypeHintKind::Union(types) | TypeHintKind::Intersection(types) => {
+            for ty in types {
+                v.visit_type_hint(ty);
+            }
+        }
+    }
+}
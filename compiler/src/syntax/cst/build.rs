@@ -0,0 +1,130 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use crate::syntax::{ast::stmt::Script, experiments::Experiments, lex::Lexer, source::Span};
+
+use super::{view, GreenElement, GreenNode, GreenToken, SyntaxKind};
+
+/// Every token of `text` as `lexer` gives them, trivia included. Whatever the lexer fails on
+/// is kept as one token without a type, so the tokens always add up to `text`.
+pub(super) fn tokens(text: &str, lexer: Lexer<'_>, experiments: Experiments) -> Vec<GreenToken> {
+    let mut lexer = lexer.with_experiments(experiments);
+    let mut tokens = Vec::new();
+    let mut end = 0;
+    while let Ok(Some(token)) = lexer.next() {
+        let span = token.1;
+        if span.start > end {
+            tokens.push(GreenToken::new(None, &text[end..span.start]));
+        }
+        tokens.push(GreenToken::new(Some(token.0), &text[span.start..span.end]));
+        end = span.end;
+    }
+    if end < text.len() {
+        tokens.push(GreenToken::new(None, &text[end..]));
+    }
+    tokens
+}
+
+/// Which typed node each syntax node was built from, and the other way around. Green nodes
+/// are known by their address, and typed nodes by their kind and address, as a node and its
+/// first field may share one. The typed nodes below the script live on the heap, so their
+/// addresses hold for as long as the script is not changed.
+#[derive(Debug, Default)]
+pub(super) struct Links {
+    pub(super) ast: HashMap<usize, usize>,
+    pub(super) syntax: HashMap<(SyntaxKind, usize), usize>,
+}
+
+/// An open node, with the children found so far and the address of its typed node.
+struct Open {
+    kind: SyntaxKind,
+    end: usize,
+    ast: usize,
+    children: Vec<GreenElement>,
+}
+
+/// Nests the tokens of `text` under the nodes of `script`, linking each node to the typed
+/// node it was built from.
+///
+/// A node is opened at the first token of its span and closed before the first token past
+/// it. Nodes whose span does not start and end on the boundaries of tokens, such as the parts
+/// of a string, or that cross the end of an enclosing node are left out, and their tokens go to
+/// the enclosing node.
+pub(super) fn build(text: &str, script: &Script, experiments: Experiments) -> (GreenNode, Links) {
+    let tokens = tokens(text, Lexer::new(text), experiments);
+    let mut spans = Vec::with_capacity(tokens.len());
+    let mut offset = 0;
+    for token in &tokens {
+        spans.push(Span::new(offset, offset + token.text().len()));
+        offset += token.text().len();
+    }
+    let starts: HashSet<usize> = spans.iter().map(|span| span.start).collect();
+    let ends: HashSet<usize> = spans.iter().map(|span| span.end).collect();
+
+    let mut nodes: Vec<_> = view::nodes(script)
+        .into_iter()
+        .map(|node| (node.kind(), node.span(), node.address()))
+        .filter(|(kind, span, _)| {
+            *kind != SyntaxKind::Script
+                && span.start < span.end
+                && starts.contains(&span.start)
+                && ends.contains(&span.end)
+        })
+        .collect();
+    // The sort is stable, so a parent stays before a child with the same span.
+    nodes.sort_by_key(|(_, span, _)| (span.start, Reverse(span.end)));
+    let mut nodes = nodes.into_iter().peekable();
+
+    let mut links = Links::default();
+    let mut stack = vec![Open {
+        kind: SyntaxKind::Script,
+        end: text.len(),
+        ast: 0,
+        children: Vec::new(),
+    }];
+    for (token, span) in tokens.into_iter().zip(spans) {
+        while stack.len() > 1 && stack.last().is_some_and(|open| open.end <= span.start) {
+            close(&mut stack, &mut links);
+        }
+        if !token.is_trivia() {
+            while let Some(&(kind, node, ast)) = nodes.peek() {
+                if node.start > span.start {
+                    break;
+                }
+                nodes.next();
+                let parent_end = stack.last().map_or(text.len(), |open| open.end);
+                if node.start == span.start && node.end <= parent_end {
+                    stack.push(Open {
+                        kind,
+                        end: node.end,
+                        ast,
+                        children: Vec::new(),
+                    });
+                }
+            }
+        }
+        if let Some(open) = stack.last_mut() {
+            open.children.push(GreenElement::Token(Rc::new(token)));
+        }
+    }
+    while stack.len() > 1 {
+        close(&mut stack, &mut links);
+    }
+    let root = stack.pop().expect("the root is never closed");
+    (GreenNode::new(root.kind, root.children), links)
+}
+
+fn close(stack: &mut Vec<Open>, links: &mut Links) {
+    if let Some(open) = stack.pop() {
+        let green = Rc::new(GreenNode::new(open.kind, open.children));
+        let address = Rc::as_ptr(&green) as usize;
+        links.ast.insert(address, open.ast);
+        links.syntax.insert((open.kind, open.ast), address);
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(GreenElement::Node(green));
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! A lossless concrete syntax tree, for tooling that edits source rather than compiling it.
+//!
+//! The tree keeps every token of a script, whitespace, line breaks and comments included, so
+//! its text is exactly the text it was built from, even when the script does not parse. It
+//! comes in two layers. `GreenNode`s are immutable and shared, they only know their kind, their
+//! length and their children, so an edit rebuilds the nodes from the edited one up to the root
+//! and shares everything else. `SyntaxNode`s are created on demand on top of them and add the
+//! parent and the offset into the script, for navigation.
+//!
+//! The shape follows the typed tree: every node of the typed tree that has a span is a node
+//! covering the tokens of that span, and `SyntaxTree::ast` gives the typed node a syntax node is
+//! a view of. The two are linked when the tree is built, so nodes with the same kind and span
+//! are told apart, and the nodes of an edited tree have no typed node. Trivia between two tokens belongs to the innermost node that contains both, so a
+//! node never starts or ends with whitespace or a comment.
+
+use std::{fmt, rc::Rc};
+
+use self::view::AstRef;
+
+use super::{
+    ast::stmt::Script,
+    diagnostic::Diagnostic,
+    experiments::Experiments,
+    lex::{token::TokenType, Lexer},
+    parse::parse_script_with,
+    source::Span,
+};
+
+mod build;
+pub mod view;
+
+/// The kind of a node, one for each node of the typed tree that has a span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Script,
+    Stmt,
+    Block,
+    ElseIf,
+    SwitchCase,
+    StaticVar,
+    DeclareDirective,
+    UseItem,
+    ConstItem,
+    Catch,
+    Expr,
+    ArrayItem,
+    Arg,
+    MatchArm,
+    FunctionDecl,
+    Param,
+    Closure,
+    ClosureUse,
+    ArrowFunction,
+    ClassLike,
+    ClassMember,
+    PropertyItem,
+    TraitAdaptation,
+    AttributeGroup,
+    Attribute,
+    TypeHint,
+    Name,
+}
+
+/// A token and its exact text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenToken {
+    /// `None` for text the lexer could not make a token of, which is kept as is.
+    token: Option<TokenType>,
+    text: String,
+}
+
+impl GreenToken {
+    pub fn new(token: Option<TokenType>, text: impl Into<String>) -> Self {
+        Self {
+            token,
+            text: text.into(),
+        }
+    }
+
+    pub fn token(&self) -> Option<&TokenType> {
+        self.token.as_ref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the token is whitespace, a line break or a comment.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.token,
+            Some(TokenType::Whitespace | TokenType::LF(_) | TokenType::Comment)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    /// The length of the text of the element in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for GreenElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GreenElement::Node(node) => node.fmt(f),
+            GreenElement::Token(token) => f.write_str(&token.text),
+        }
+    }
+}
+
+/// A node without position, which may be shared between trees. `to_string` gives its text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        Self {
+            kind,
+            len: children.iter().map(GreenElement::len).sum(),
+            children,
+        }
+    }
+
+    /// A node holding the tokens of `text`, PHP code without a `<?php`, for writing new code
+    /// into a tree parsed with the same `experiments`, `SyntaxTree::experiments`.
+    pub fn lex(kind: SyntaxKind, text: &str, experiments: Experiments) -> Self {
+        let children = build::tokens(text, Lexer::snippet(text), experiments)
+            .into_iter()
+            .map(|token| GreenElement::Token(Rc::new(token)))
+            .collect();
+        Self::new(kind, children)
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// The length of the text of the node in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    /// A copy of the node with the child at `index` replaced.
+    pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+        let mut children = self.children.clone();
+        children[index] = child;
+        Self::new(self.kind, children)
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.children.iter().try_for_each(|child| child.fmt(f))
+    }
+}
+
+/// A node in a tree, which knows its parent and where it starts in the script.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    /// The position of the node among the children of its parent.
+    index: usize,
+    offset: usize,
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.len)
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// The node itself, then its parent, up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> {
+        let parent = self.clone();
+        let mut offset = self.0.offset;
+        (0..self.0.green.children.len()).map(move |index| {
+            let start = offset;
+            let child = &parent.0.green.children[index];
+            offset += child.len();
+            match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    parent: Some(parent.clone()),
+                    index,
+                    offset: start,
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    parent: parent.clone(),
+                    index,
+                    offset: start,
+                }),
+            }
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The node itself and every node below it, in source order.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let children: Vec<_> = node.children().collect();
+            stack.extend(children.into_iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Every token below the node, trivia included, in source order.
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken> {
+        let mut stack = vec![SyntaxElement::Node(self.clone())];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                SyntaxElement::Token(token) => return Some(token),
+                SyntaxElement::Node(node) => {
+                    let children: Vec<_> = node.children_with_tokens().collect();
+                    stack.extend(children.into_iter().rev());
+                }
+            }
+        })
+    }
+
+    /// Puts `replacement` in the place of the node, and returns the root of the edited tree.
+    /// The rest of the tree is shared with this one, so its text is unchanged.
+    pub fn replace_with(&self, replacement: GreenNode) -> GreenNode {
+        match &self.0.parent {
+            None => replacement,
+            Some(parent) => parent.replace_with(
+                parent
+                    .0
+                    .green
+                    .replace_child(self.0.index, GreenElement::Node(Rc::new(replacement))),
+            ),
+        }
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.kind(), span.start, span.end)
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+
+/// A token in a tree, which knows its parent and where it starts in the script.
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+impl SyntaxToken {
+    pub fn green(&self) -> &Rc<GreenToken> {
+        &self.green
+    }
+
+    pub fn token(&self) -> Option<&TokenType> {
+        self.green.token()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        self.green.is_trivia()
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.text.len())
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// Puts `replacement` in the place of the token, and returns the root of the edited tree.
+    pub fn replace_with(&self, replacement: GreenToken) -> GreenNode {
+        self.parent.replace_with(
+            self.parent
+                .0
+                .green
+                .replace_child(self.index, GreenElement::Token(Rc::new(replacement))),
+        )
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl Eq for SyntaxToken {}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.text(), span.start, span.end)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn span(&self) -> Span {
+        match self {
+            SyntaxElement::Node(node) => node.span(),
+            SyntaxElement::Token(token) => token.span(),
+        }
+    }
+}
+
+/// A script as a concrete syntax tree, along with the typed tree it was built from.
+///
+/// Edits give a new root, whose text can be parsed again for a tree that matches it.
+#[derive(Debug)]
+pub struct SyntaxTree {
+    green: Rc<GreenNode>,
+    script: Script,
+    diagnostics: Vec<Diagnostic>,
+    links: build::Links,
+    experiments: Experiments,
+}
+
+impl SyntaxTree {
+    /// Parses a script, recovering from syntax errors, and builds its tree. The text of the
+    /// tree is always `text`, whatever errors it has.
+    pub fn parse(text: &str) -> Self {
+        Self::parse_with(text, Experiments::default())
+    }
+
+    /// Parses a script like `parse`, accepting the syntax of the enabled `experiments` as well.
+    pub fn parse_with(text: &str, experiments: Experiments) -> Self {
+        let (script, diagnostics) = parse_script_with(text, experiments);
+        let (green, links) = build::build(text, &script, experiments);
+        Self {
+            green: Rc::new(green),
+            script,
+            diagnostics,
+            links,
+            experiments,
+        }
+    }
+
+    pub fn root(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.green
+    }
+
+    /// The typed tree.
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    /// The experiments the script was parsed with, which edits to it are lexed with too.
+    pub fn experiments(&self) -> Experiments {
+        self.experiments
+    }
+
+    /// The syntax errors in the script, in the order they appear.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The typed node that `node` is a view of, if `node` is a node of this tree and not of
+    /// one edited from it.
+    pub fn ast(&self, node: &SyntaxNode) -> Option<AstRef<'_>> {
+        let root = node.ancestors().last()?;
+        if !Rc::ptr_eq(root.green(), &self.green) {
+            return None;
+        }
+        if node.kind() == SyntaxKind::Script {
+            return Some(AstRef::Script(&self.script));
+        }
+        let address = self.links.ast.get(&(Rc::as_ptr(node.green()) as usize))?;
+        view::find(&self.script, node.kind(), *address, node.span())
+    }
+
+    /// The typed node that `node` is a view of, if it is a `T`.
+    pub fn view<'a, T>(&'a self, node: &SyntaxNode) -> Option<&'a T>
+    where
+        &'a T: TryFrom<AstRef<'a>>,
+    {
+        self.ast(node)?.try_into().ok()
+    }
+
+    /// The syntax node of a typed node of this tree.
+    pub fn syntax<'a>(&self, ast: impl Into<AstRef<'a>>) -> Option<SyntaxNode> {
+        let ast = ast.into();
+        if ast.kind() == SyntaxKind::Script {
+            return Some(self.root());
+        }
+        let green = *self.links.syntax.get(&(ast.kind(), ast.address()))?;
+        let span = ast.span();
+        let mut node = self.root();
+        while Rc::as_ptr(node.green()) as usize != green {
+            node = node.children().find(|child| {
+                let child = child.span();
+                child.start <= span.start && span.end <= child.end
+            })?;
+        }
+        Some(node)
+    }
+}
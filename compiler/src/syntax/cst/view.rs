@@ -0,0 +1,201 @@
+//! Typed views of syntax nodes.
+//!
+//! `AstRef` is a reference to any node of the typed tree that has a span, and so a syntax node
+//! of its own. `SyntaxTree::ast` and `SyntaxTree::syntax` go from one to the other, through
+//! the links recorded when the tree was built.
+
+use crate::syntax::{
+    ast::{
+        attribute::{Attribute, AttributeGroup},
+        class::{ClassLike, ClassMember, PropertyItem, TraitAdaptation},
+        expr::{Arg, ArrayItem, Expr, MatchArm},
+        function::{ArrowFunction, Closure, ClosureUse, FunctionDecl, Param},
+        name::Name,
+        stmt::{
+            Block, Catch, ConstItem, DeclareDirective, ElseIf, Script, StaticVar, Stmt, SwitchCase,
+            UseItem,
+        },
+        types::TypeHint,
+        visit::{self, Visitor},
+    },
+    source::Span,
+};
+
+use super::SyntaxKind;
+
+macro_rules! ast_ref {
+    ($($node:ident => $visit:ident, $walk:ident;)*) => {
+        /// A node of the typed tree that has a syntax node of its own.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub enum AstRef<'a> {
+            $($node(&'a $node),)*
+            Name(&'a Name),
+        }
+
+        impl AstRef<'_> {
+            pub fn kind(&self) -> SyntaxKind {
+                match self {
+                    $(AstRef::$node(_) => SyntaxKind::$node,)*
+                    AstRef::Name(_) => SyntaxKind::Name,
+                }
+            }
+
+            pub fn span(&self) -> Span {
+                match self {
+                    $(AstRef::$node(node) => node.span,)*
+                    AstRef::Name(name) => name.span,
+                }
+            }
+
+            /// Where the node lives, which tells it apart from other nodes of the same kind
+            /// for as long as the typed tree is not changed.
+            pub(super) fn address(&self) -> usize {
+                match self {
+                    $(AstRef::$node(node) => *node as *const $node as usize,)*
+                    AstRef::Name(name) => *name as *const Name as usize,
+                }
+            }
+        }
+
+        $(
+            impl<'a> From<&'a $node> for AstRef<'a> {
+                fn from(node: &'a $node) -> Self {
+                    AstRef::$node(node)
+                }
+            }
+
+            impl<'a> TryFrom<AstRef<'a>> for &'a $node {
+                type Error = AstRef<'a>;
+
+                fn try_from(node: AstRef<'a>) -> Result<Self, Self::Error> {
+                    match node {
+                        AstRef::$node(node) => Ok(node),
+                        _ => Err(node),
+                    }
+                }
+            }
+        )*
+
+        impl<'ast> Visitor<'ast> for Nodes<'ast> {
+            $(
+                fn $visit(&mut self, node: &'ast $node) {
+                    self.0.push(AstRef::$node(node));
+                    visit::$walk(self, node);
+                }
+            )*
+
+            fn visit_name(&mut self, name: &'ast Name) {
+                self.0.push(AstRef::Name(name));
+            }
+        }
+
+        impl<'ast> Visitor<'ast> for Find<'ast> {
+            $(
+                fn $visit(&mut self, node: &'ast $node) {
+                    if self.visit(AstRef::$node(node)) {
+                        visit::$walk(self, node);
+                    }
+                }
+            )*
+
+            fn visit_name(&mut self, name: &'ast Name) {
+                self.visit(AstRef::Name(name));
+            }
+        }
+    };
+}
+
+ast_ref! {
+    Script => visit_script, walk_script;
+    Stmt => visit_stmt, walk_stmt;
+    Block => visit_block, walk_block;
+    ElseIf => visit_else_if, walk_else_if;
+    SwitchCase => visit_switch_case, walk_switch_case;
+    StaticVar => visit_static_var, walk_static_var;
+    DeclareDirective => visit_declare_directive, walk_declare_directive;
+    UseItem => visit_use_item, walk_use_item;
+    ConstItem => visit_const_item, walk_const_item;
+    Catch => visit_catch, walk_catch;
+    Expr => visit_expr, walk_expr;
+    ArrayItem => visit_array_item, walk_array_item;
+    Arg => visit_arg, walk_arg;
+    MatchArm => visit_match_arm, walk_match_arm;
+    FunctionDecl => visit_function_decl, walk_function_decl;
+    Param => visit_param, walk_param;
+    Closure => visit_closure, walk_closure;
+    ClosureUse => visit_closure_use, walk_closure_use;
+    ArrowFunction => visit_arrow_function, walk_arrow_function;
+    ClassLike => visit_class_like, walk_class_like;
+    ClassMember => visit_class_member, walk_class_member;
+    PropertyItem => visit_property_item, walk_property_item;
+    TraitAdaptation => visit_trait_adaptation, walk_trait_adaptation;
+    AttributeGroup => visit_attribute_group, walk_attribute_group;
+    Attribute => visit_attribute, walk_attribute;
+    TypeHint => visit_type_hint, walk_type_hint;
+}
+
+impl<'a> TryFrom<AstRef<'a>> for &'a Name {
+    type Error = AstRef<'a>;
+
+    fn try_from(node: AstRef<'a>) -> Result<Self, Self::Error> {
+        match node {
+            AstRef::Name(name) => Ok(name),
+            _ => Err(node),
+        }
+    }
+}
+
+impl<'a> From<&'a Name> for AstRef<'a> {
+    fn from(name: &'a Name) -> Self {
+        AstRef::Name(name)
+    }
+}
+
+/// Every node of a script that has a syntax node, parents before their children.
+struct Nodes<'ast>(Vec<AstRef<'ast>>);
+
+pub(super) fn nodes(script: &Script) -> Vec<AstRef<'_>> {
+    let mut nodes = Nodes(Vec::new());
+    nodes.visit_script(script);
+    nodes.0
+}
+
+/// Looks for the node of `kind` at `address`, only going into nodes that contain `span`.
+struct Find<'ast> {
+    kind: SyntaxKind,
+    address: usize,
+    span: Span,
+    found: Option<AstRef<'ast>>,
+}
+
+impl<'ast> Find<'ast> {
+    /// Whether the children of `node` may hold the node looked for.
+    fn visit(&mut self, node: AstRef<'ast>) -> bool {
+        let span = node.span();
+        if self.found.is_some() || span.start > self.span.start || span.end < self.span.end {
+            return false;
+        }
+        if node.kind() == self.kind && node.address() == self.address {
+            self.found = Some(node);
+            return false;
+        }
+        true
+    }
+}
+
+/// The node of `kind` at `address`, whose span is `span`.
+pub(super) fn find(
+    script: &Script,
+    kind: SyntaxKind,
+    address: usize,
+    span: Span,
+) -> Option<AstRef<'_>> {
+    let mut find = Find {
+        kind,
+        address,
+        span,
+        found: None,
+    };
+    find.visit_script(script);
+    find.found
+}
@@ -0,0 +1,97 @@
+//! Language extensions that are not part of PHP.
+//!
+//! Every extension is off by default, so a script is lexed, parsed and run exactly as PHP
+//! would. A script that uses an extension which is not enabled fails with a diagnostic that
+//! names the experiment to enable.
+
+use std::{fmt, str::FromStr};
+
+/// A single language extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Experiment {
+    /// `async function`, `async fn` and `await`. Calling an async function returns a
+    /// suspended `Generator`, which `await` runs to completion.
+    AsyncAwait,
+    /// `const` declarations inside functions and blocks, which PHP only allows at the top
+    /// level of a script or namespace. They are declared when reached.
+    LocalConst,
+    /// Integer literals with an `n` suffix, `123n`, lexed as `Numeric::LInt`.
+    LongInt,
+}
+
+impl Experiment {
+    pub const ALL: [Experiment; 3] = [
+        Experiment::AsyncAwait,
+        Experiment::LocalConst,
+        Experiment::LongInt,
+    ];
+
+    /// The name the experiment is enabled by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Experiment::AsyncAwait => "async-await",
+            Experiment::LocalConst => "local-const",
+            Experiment::LongInt => "long-int",
+        }
+    }
+
+    /// The message for using `what` while the experiment is off.
+    pub(crate) fn required(self, what: &str) -> String {
+        format!(
+            "{} require the `{}` experiment, which is not enabled",
+            what,
+            self.name()
+        )
+    }
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+impl fmt::Display for Experiment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExperimentErr;
+
+impl FromStr for Experiment {
+    type Err = ExperimentErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Experiment::ALL
+            .into_iter()
+            .find(|experiment| experiment.name() == s)
+            .ok_or(ExperimentErr)
+    }
+}
+
+/// The set of experiments enabled for a script, passed to the lexer, the parser and the `Vm`.
+/// The default enables none of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Experiments(u32);
+
+impl Experiments {
+    /// Every experiment enabled.
+    pub fn all() -> Self {
+        Experiment::ALL
+            .into_iter()
+            .fold(Self::default(), Experiments::with)
+    }
+
+    pub fn with(mut self, experiment: Experiment) -> Self {
+        self.enable(experiment);
+        self
+    }
+
+    pub fn enable(&mut self, experiment: Experiment) {
+        self.0 |= experiment.bit();
+    }
+
+    pub fn is_enabled(self, experiment: Experiment) -> bool {
+        self.0 & experiment.bit() != 0
+    }
+}
@@ -0,0 +1,1357 @@
+//! Export of the tree as JSON, in the format of nikic/PHP-Parser 5.
+//!
+//! Every node is an object with its `nodeType`, its sub nodes in the order PHP-Parser declares
+//! them, and its `attributes`, which are `startLine`, `startFilePos`, `endLine` and `endFilePos`
+//! with the same meaning as in PHP-Parser: lines count from one and `endFilePos` is the offset
+//! of the last byte of the node. Token positions and comments are not included. The output is
+//! laid out like `json_encode` with `JSON_PRETTY_PRINT`, so it can be compared as text with the
+//! output of `php-parse --json-dump`.
+//!
+//! Where the two trees differ, the export follows PHP-Parser: statements after `namespace Foo;`
+//! are nested in the namespace, `true`, `false` and `null` are constant fetches, and magic
+//! constants have node types of their own. Statements and class members that failed to parse
+//! are left out. Nodes that have no span of their own, like the text parts of an interpolated
+//! string or the number of a `break`, get the attributes of their parent.
+
+use std::fmt::Write;
+
+use super::{
+    ast::{
+        attribute::AttributeGroup,
+        class::{AdaptationKind, ClassKind, ClassLike, ClassMember, MemberKind, Modifier},
+        expr::{
+            Arg, ArrayItem, BinaryOp, CallArgs, CastKind, ClassRef, Expr, ExprKind, IncDecOp,
+            Literal, MemberName, StringPart, UnaryOp,
+        },
+        function::{FunctionDecl, Param},
+        keyword::Keyword,
+        name::{Ident, Name, NameKind},
+        reserved::{ReservedCall, ReservedIdent},
+        stmt::{Block, Script, Stmt, StmtKind, UseItem, UseKind},
+        types::{BuiltinType, TypeHint, TypeHintKind},
+    },
+    lex::token::Numeric,
+    source::{SourceFile, Span},
+};
+
+/// Exports a whole script as the JSON array of its statements. `source` is the file the
+/// script was parsed from, for line numbers.
+pub fn export_script(script: &Script, source: &SourceFile) -> String {
+    Exporter { source }.stmts(&script.stmts).to_string()
+}
+
+/// Exports a single expression as a JSON object.
+pub fn export_expr(expr: &Expr, source: &SourceFile) -> String {
+    Exporter { source }.expr(expr).to_string()
+}
+
+/// A JSON value, the members of an object are kept in the order they were added.
+enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn str(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+
+    /// The bytes of a string value, JSON strings hold only UTF-8.
+    fn bytes(s: &[u8]) -> Json {
+        Json::String(String::from_utf8_lossy(s).into_owned())
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Int(i) => {
+                let _ = write!(out, "{i}");
+            }
+            Json::Float(f) => write_float(out, *f),
+            Json::String(s) => write_string(out, s),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    indent(out, depth + 1);
+                    item.write(out, depth + 1);
+                }
+                out.push('\n');
+                indent(out, depth);
+                out.push(']');
+            }
+            Json::Object(members) if members.is_empty() => out.push_str("{}"),
+            Json::Object(members) => {
+                out.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    indent(out, depth + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, depth + 1);
+                }
+                out.push('\n');
+                indent(out, depth);
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        f.write_str(&out)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("    ");
+    }
+}
+
+/// Writes a string the way `json_encode` does by default, with `/` and every non-ASCII char
+/// escaped.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '/' => out.push_str("\\/"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ' '..='~' => out.push(c),
+            _ => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    let _ = write!(out, "\\u{unit:04x}");
+                }
+            }
+        }
+    }
+    out.push('"');
+}
+
+/// Writes a float the way `json_encode` does, `1.0`, `0.5` or `1.0e+25`. JSON has no infinity
+/// or NaN, which are written as `null`.
+fn write_float(out: &mut String, f: f64) {
+    if !f.is_finite() {
+        out.push_str("null");
+        return;
+    }
+    let repr = format!("{f:?}");
+    match repr.split_once('e') {
+        Some((mantissa, exponent)) => {
+            out.push_str(mantissa);
+            if !mantissa.contains('.') {
+                out.push_str(".0");
+            }
+            out.push('e');
+            if !exponent.starts_with('-') {
+                out.push('+');
+            }
+            out.push_str(exponent);
+        }
+        None => out.push_str(&repr),
+    }
+}
+
+struct Exporter<'a> {
+    source: &'a SourceFile,
+}
+
+impl Exporter<'_> {
+    fn node(&self, node_type: &str, span: Span, fields: Vec<(&'static str, Json)>) -> Json {
+        let mut members = Vec::with_capacity(fields.len() + 2);
+        members.push(("nodeType", Json::str(node_type)));
+        members.extend(fields);
+        members.push(("attributes", self.attributes(span)));
+        Json::Object(members)
+    }
+
+    fn attributes(&self, span: Span) -> Json {
+        let last = span.end.saturating_sub(1).max(span.start);
+        Json::Object(vec![
+            (
+                "startLine",
+                Json::Int(self.source.line_index(span.start) as i64 + 1),
+            ),
+            ("startFilePos", Json::Int(span.start as i64)),
+            (
+                "endLine",
+                Json::Int(self.source.line_index(last) as i64 + 1),
+            ),
+            ("endFilePos", Json::Int(span.end as i64 - 1)),
+        ])
+    }
+
+    /// The text of a span, if it is part of the source.
+    fn text(&self, span: Span) -> Option<&str> {
+        self.source.src().get(span.range())
+    }
+
+    fn stmts(&self, stmts: &[Stmt]) -> Json {
+        let mut out = Vec::with_capacity(stmts.len());
+        let mut i = 0;
+        while i < stmts.len() {
+            let stmt = &stmts[i];
+            i += 1;
+            // PHP-Parser nests the statements that follow `namespace Foo;` in the namespace.
+            if let StmtKind::Namespace { name, body: None } = &stmt.kind {
+                let end = stmts[i..]
+                    .iter()
+                    .position(|stmt| matches!(stmt.kind, StmtKind::Namespace { .. }))
+                    .map_or(stmts.len(), |n| i + n);
+                let body = &stmts[i..end];
+                let span = body
+                    .last()
+                    .map_or(stmt.span, |last| stmt.span.to(last.span));
+                out.push(self.node(
+                    "Stmt_Namespace",
+                    span,
+                    vec![
+                        ("name", name.as_ref().map(|name| self.name(name)).into()),
+                        ("stmts", self.stmts(body)),
+                    ],
+                ));
+                i = end;
+            } else if let Some(stmt) = self.stmt(stmt) {
+                out.push(stmt);
+            }
+        }
+        Json::Array(out)
+    }
+
+    fn block(&self, block: &Block) -> Json {
+        self.stmts(&block.stmts)
+    }
+
+    fn exprs(&self, exprs: &[Expr]) -> Json {
+        Json::Array(exprs.iter().map(|expr| self.expr(expr)).collect())
+    }
+
+    fn opt_expr(&self, expr: Option<&Expr>) -> Json {
+        expr.map(|expr| self.expr(expr)).into()
+    }
+
+    fn stmt(&self, stmt: &Stmt) -> Option<Json> {
+        let span = stmt.span;
+        let (node_type, fields) = match &stmt.kind {
+            StmtKind::Expr(expr) => ("Stmt_Expression", vec![("expr", self.expr(expr))]),
+            StmtKind::Echo(exprs) => ("Stmt_Echo", vec![("exprs", self.exprs(exprs))]),
+            StmtKind::InlineHtml(html) => ("Stmt_InlineHTML", vec![("value", Json::str(html))]),
+            StmtKind::Return(expr) => ("Stmt_Return", vec![("expr", self.opt_expr(expr.as_ref()))]),
+            StmtKind::If {
+                cond,
+                then,
+                elseifs,
+                otherwise,
+            } => {
+                let elseifs = elseifs
+                    .iter()
+                    .map(|elseif| {
+                        self.node(
+                            "Stmt_ElseIf",
+                            elseif.span,
+                            vec![
+                                ("cond", self.expr(&elseif.cond)),
+                                ("stmts", self.block(&elseif.body)),
+                            ],
+                        )
+                    })
+                    .collect();
+                let otherwise = otherwise.as_ref().map(|block| {
+                    self.node("Stmt_Else", block.span, vec![("stmts", self.block(block))])
+                });
+                (
+                    "Stmt_If",
+                    vec![
+                        ("cond", self.expr(cond)),
+                        ("stmts", self.block(then)),
+                        ("elseifs", Json::Array(elseifs)),
+                        ("else", otherwise.into()),
+                    ],
+                )
+            }
+            StmtKind::While { cond, body } => (
+                "Stmt_While",
+                vec![("cond", self.expr(cond)), ("stmts", self.block(body))],
+            ),
+            StmtKind::DoWhile { body, cond } => (
+                "Stmt_Do",
+                vec![("stmts", self.block(body)), ("cond", self.expr(cond))],
+            ),
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            } => (
+                "Stmt_For",
+                vec![
+                    ("init", self.exprs(init)),
+                    ("cond", self.exprs(cond)),
+                    ("loop", self.exprs(step)),
+                    ("stmts", self.block(body)),
+                ],
+            ),
+            StmtKind::Foreach {
+                subject,
+                key,
+                value,
+                by_ref,
+                body,
+            } => (
+                "Stmt_Foreach",
+                vec![
+                    ("expr", self.expr(subject)),
+                    ("keyVar", self.opt_expr(key.as_deref())),
+                    ("byRef", Json::Bool(*by_ref)),
+                    ("valueVar", self.expr(value)),
+                    ("stmts", self.block(body)),
+                ],
+            ),
+            StmtKind::Switch { subject, cases } => {
+                let cases = cases
+                    .iter()
+                    .map(|case| {
+                        self.node(
+                            "Stmt_Case",
+                            case.span,
+                            vec![
+                                ("cond", self.opt_expr(case.cond.as_ref())),
+                                ("stmts", self.stmts(&case.body)),
+                            ],
+                        )
+                    })
+                    .collect();
+                (
+                    "Stmt_Switch",
+                    vec![("cond", self.expr(subject)), ("cases", Json::Array(cases))],
+                )
+            }
+            StmtKind::Break(num) => ("Stmt_Break", vec![("num", self.num(*num, span))]),
+            StmtKind::Continue(num) => ("Stmt_Continue", vec![("num", self.num(*num, span))]),
+            StmtKind::Global(vars) => ("Stmt_Global", vec![("vars", self.exprs(vars))]),
+            StmtKind::Static(vars) => {
+                let vars = vars
+                    .iter()
+                    .map(|var| {
+                        self.node(
+                            "StaticVar",
+                            var.span,
+                            vec![
+                                ("var", self.variable(&var.name)),
+                                ("default", self.opt_expr(var.default.as_ref())),
+                            ],
+                        )
+                    })
+                    .collect();
+                ("Stmt_Static", vec![("vars", Json::Array(vars))])
+            }
+            StmtKind::Unset(vars) => ("Stmt_Unset", vec![("vars", self.exprs(vars))]),
+            StmtKind::Declare { directives, body } => {
+                let declares = directives
+                    .iter()
+                    .map(|directive| {
+                        self.node(
+                            "DeclareItem",
+                            directive.span,
+                            vec![
+                                ("key", self.ident(&directive.name)),
+                                ("value", self.expr(&directive.value)),
+                            ],
+                        )
+                    })
+                    .collect();
+                (
+                    "Stmt_Declare",
+                    vec![
+                        ("declares", Json::Array(declares)),
+                        ("stmts", body.as_ref().map(|body| self.block(body)).into()),
+                    ],
+                )
+            }
+            StmtKind::Goto(label) => ("Stmt_Goto", vec![("name", self.ident(label))]),
+            StmtKind::Label(label) => ("Stmt_Label", vec![("name", self.ident(label))]),
+            StmtKind::Namespace { name, body } => (
+                "Stmt_Namespace",
+                vec![
+                    ("name", name.as_ref().map(|name| self.name(name)).into()),
+                    ("stmts", body.as_ref().map(|body| self.block(body)).into()),
+                ],
+            ),
+            StmtKind::Use { kind, uses } => (
+                "Stmt_Use",
+                vec![
+                    ("type", Json::Int(use_type(*kind))),
+                    ("uses", self.use_items(uses)),
+                ],
+            ),
+            StmtKind::GroupUse { prefix, kind, uses } => (
+                "Stmt_GroupUse",
+                vec![
+                    ("type", Json::Int(use_type(*kind))),
+                    ("prefix", self.name(prefix)),
+                    ("uses", self.use_items(uses)),
+                ],
+            ),
+            StmtKind::Const(consts) => (
+                "Stmt_Const",
+                vec![(
+                    "consts",
+                    Json::Array(
+                        consts
+                            .iter()
+                            .map(|item| self.const_item(&item.name, &item.value, item.span))
+                            .collect(),
+                    ),
+                )],
+            ),
+            StmtKind::Function(function) => ("Stmt_Function", self.function(function)),
+            StmtKind::ClassLike(class) => return Some(self.class_like(class)),
+            StmtKind::Try {
+                body,
+                catches,
+                finally,
+            } => {
+                let catches = catches
+                    .iter()
+                    .map(|catch| {
+                        self.node(
+                            "Stmt_Catch",
+                            catch.span,
+                            vec![
+                                (
+                                    "types",
+                                    Json::Array(
+                                        catch.types.iter().map(|ty| self.name(ty)).collect(),
+                                    ),
+                                ),
+                                (
+                                    "var",
+                                    catch.var.as_ref().map(|var| self.variable(var)).into(),
+                                ),
+                                ("stmts", self.block(&catch.body)),
+                            ],
+                        )
+                    })
+                    .collect();
+                let finally = finally.as_ref().map(|block| {
+                    self.node(
+                        "Stmt_Finally",
+                        block.span,
+                        vec![("stmts", self.block(block))],
+                    )
+                });
+                (
+                    "Stmt_TryCatch",
+                    vec![
+                        ("stmts", self.block(body)),
+                        ("catches", Json::Array(catches)),
+                        ("finally", finally.into()),
+                    ],
+                )
+            }
+            StmtKind::Block(block) => ("Stmt_Block", vec![("stmts", self.block(block))]),
+            StmtKind::Nop => ("Stmt_Nop", vec![]),
+            StmtKind::HaltCompiler(rest) => {
+                ("Stmt_HaltCompiler", vec![("remaining", Json::str(rest))])
+            }
+            StmtKind::Error => return None,
+        };
+        Some(self.node(node_type, span, fields))
+    }
+
+    /// The level of a `break` or `continue`, which PHP-Parser keeps as an integer literal.
+    fn num(&self, num: Option<u32>, span: Span) -> Json {
+        num.map(|num| self.node("Scalar_Int", span, vec![("value", Json::Int(num.into()))]))
+            .into()
+    }
+
+    fn use_items(&self, uses: &[UseItem]) -> Json {
+        let uses = uses
+            .iter()
+            .map(|item| {
+                self.node(
+                    "UseItem",
+                    item.span,
+                    vec![
+                        ("type", Json::Int(item.kind.map_or(0, use_type))),
+                        ("name", self.name(&item.name)),
+                        (
+                            "alias",
+                            item.alias.as_ref().map(|alias| self.ident(alias)).into(),
+                        ),
+                    ],
+                )
+            })
+            .collect();
+        Json::Array(uses)
+    }
+
+    fn const_item(&self, name: &Ident, value: &Expr, span: Span) -> Json {
+        self.node(
+            "Const",
+            span,
+            vec![("name", self.ident(name)), ("value", self.expr(value))],
+        )
+    }
+
+    fn ident(&self, ident: &Ident) -> Json {
+        self.node(
+            "Identifier",
+            ident.span,
+            vec![("name", Json::str(&ident.name))],
+        )
+    }
+
+    /// A variable written as a plain identifier, such as a parameter or a `catch` variable.
+    fn variable(&self, ident: &Ident) -> Json {
+        self.node(
+            "Expr_Variable",
+            ident.span,
+            vec![("name", Json::str(&ident.name))],
+        )
+    }
+
+    fn name(&self, name: &Name) -> Json {
+        let node_type = match name.kind {
+            NameKind::Unqualified | NameKind::Qualified => "Name",
+            NameKind::FullyQualified => "Name_FullyQualified",
+            NameKind::Relative => "Name_Relative",
+        };
+        self.node(
+            node_type,
+            name.span,
+            vec![("name", Json::String(name.joined()))],
+        )
+    }
+
+    fn names(&self, names: &[Name]) -> Json {
+        Json::Array(names.iter().map(|name| self.name(name)).collect())
+    }
+
+    fn type_hint(&self, ty: &TypeHint) -> Json {
+        match &ty.kind {
+            TypeHintKind::Named(name) => match BuiltinType::from_name(name) {
+                // PHP-Parser keeps `self` and `parent` as names, like classes.
+                Some(BuiltinType::SelfClass | BuiltinType::Parent) | None => self.name(name),
+                Some(_) => self.node(
+                    "Identifier",
+                    ty.span,
+                    vec![("name", Json::str(name.last()))],
+                ),
+            },
+            TypeHintKind::Nullable(inner) => self.node(
+                "NullableType",
+                ty.span,
+                vec![("type", self.type_hint(inner))],
+            ),
+            TypeHintKind::Union(types) => self.node(
+                "UnionType",
+                ty.span,
+                vec![("types", self.type_hints(types))],
+            ),
+            TypeHintKind::Intersection(types) => self.node(
+                "IntersectionType",
+                ty.span,
+                vec![("types", self.type_hints(types))],
+            ),
+        }
+    }
+
+    fn type_hints(&self, types: &[TypeHint]) -> Json {
+        Json::Array(types.iter().map(|ty| self.type_hint(ty)).collect())
+    }
+
+    fn opt_type_hint(&self, ty: Option<&TypeHint>) -> Json {
+        ty.map(|ty| self.type_hint(ty)).into()
+    }
+
+    fn attr_groups(&self, groups: &[AttributeGroup]) -> Json {
+        let groups = groups
+            .iter()
+            .map(|group| {
+                let attrs = group
+                    .attributes
+                    .iter()
+                    .map(|attribute| {
+                        self.node(
+                            "Attribute",
+                            attribute.span,
+                            vec![
+                                ("name", self.name(&attribute.name)),
+                                ("args", self.arg_list(&attribute.args)),
+                            ],
+                        )
+                    })
+                    .collect();
+                self.node(
+                    "AttributeGroup",
+                    group.span,
+                    vec![("attrs", Json::Array(attrs))],
+                )
+            })
+            .collect();
+        Json::Array(groups)
+    }
+
+    fn arg_list(&self, args: &[Arg]) -> Json {
+        let args = args
+            .iter()
+            .map(|arg| {
+                self.node(
+                    "Arg",
+                    arg.span,
+                    vec![
+                        (
+                            "name",
+                            arg.name.as_ref().map(|name| self.ident(name)).into(),
+                        ),
+                        ("value", self.expr(&arg.value)),
+                        ("byRef", Json::Bool(false)),
+                        ("unpack", Json::Bool(arg.unpack)),
+                    ],
+                )
+            })
+            .collect();
+        Json::Array(args)
+    }
+
+    fn args(&self, args: &CallArgs) -> Json {
+        match args {
+            CallArgs::List(args) => self.arg_list(args),
+            CallArgs::FirstClassCallable(span) => {
+                Json::Array(vec![self.node("VariadicPlaceholder", *span, vec![])])
+            }
+        }
+    }
+
+    fn params(&self, params: &[Param]) -> Json {
+        let params = params
+            .iter()
+            .map(|param| {
+                self.node(
+                    "Param",
+                    param.span,
+                    vec![
+                        ("attrGroups", self.attr_groups(&param.attributes)),
+                        ("flags", flags(&param.modifiers)),
+                        ("type", self.opt_type_hint(param.ty.as_ref())),
+                        ("byRef", Json::Bool(param.by_ref)),
+                        ("variadic", Json::Bool(param.variadic)),
+                        ("var", self.variable(&param.name)),
+                        ("default", self.opt_expr(param.default.as_ref())),
+                    ],
+                )
+            })
+            .collect();
+        Json::Array(params)
+    }
+
+    fn function(&self, function: &FunctionDecl) -> Vec<(&'static str, Json)> {
+        vec![
+            ("attrGroups", self.attr_groups(&function.attributes)),
+            ("byRef", Json::Bool(function.by_ref)),
+            ("name", self.ident(&function.name)),
+            ("params", self.params(&function.params)),
+            (
+                "returnType",
+                self.opt_type_hint(function.return_type.as_ref()),
+            ),
+            (
+                "stmts",
+                function.body.as_ref().map(|body| self.block(body)).into(),
+            ),
+        ]
+    }
+
+    fn class_like(&self, class: &ClassLike) -> Json {
+        let attr_groups = ("attrGroups", self.attr_groups(&class.attributes));
+        let name = (
+            "name",
+            class.name.as_ref().map(|name| self.ident(name)).into(),
+        );
+        let stmts = (
+            "stmts",
+            Json::Array(
+                class
+                    .members
+                    .iter()
+                    .filter_map(|member| self.member(member))
+                    .collect(),
+            ),
+        );
+        let (node_type, fields) = match class.kind {
+            ClassKind::Class => (
+                "Stmt_Class",
+                vec![
+                    attr_groups,
+                    ("flags", flags(&class.modifiers)),
+                    name,
+                    (
+                        "extends",
+                        class.extends.first().map(|name| self.name(name)).into(),
+                    ),
+                    ("implements", self.names(&class.implements)),
+                    stmts,
+                ],
+            ),
+            ClassKind::Interface => (
+                "Stmt_Interface",
+                vec![
+                    attr_groups,
+                    name,
+                    ("extends", self.names(&class.extends)),
+                    stmts,
+                ],
+            ),
+            ClassKind::Trait => ("Stmt_Trait", vec![attr_groups, name, stmts]),
+            ClassKind::Enum => (
+                "Stmt_Enum",
+                vec![
+                    attr_groups,
+                    name,
+                    (
+                        "scalarType",
+                        self.opt_type_hint(class.backing_type.as_ref()),
+                    ),
+                    ("implements", self.names(&class.implements)),
+                    stmts,
+                ],
+            ),
+        };
+        self.node(node_type, class.span, fields)
+    }
+
+    fn member(&self, member: &ClassMember) -> Option<Json> {
+        let (node_type, fields) = match &member.kind {
+            MemberKind::Property(property) => {
+                let props = property
+                    .props
+                    .iter()
+                    .map(|item| {
+                        self.node(
+                            "PropertyItem",
+                            item.span,
+                            vec![
+                                (
+                                    "name",
+                                    self.node(
+                                        "VarLikeIdentifier",
+                                        item.name.span,
+                                        vec![("name", Json::str(&item.name.name))],
+                                    ),
+                                ),
+                                ("default", self.opt_expr(item.default.as_ref())),
+                            ],
+                        )
+                    })
+                    .collect();
+                (
+                    "Stmt_Property",
+                    vec![
+                        ("attrGroups", self.attr_groups(&property.attributes)),
+                        ("flags", flags(&property.modifiers)),
+                        ("type", self.opt_type_hint(property.ty.as_ref())),
+                        ("props", Json::Array(props)),
+                    ],
+                )
+            }
+            MemberKind::Const(constant) => (
+                "Stmt_ClassConst",
+                vec![
+                    ("attrGroups", self.attr_groups(&constant.attributes)),
+                    ("flags", flags(&constant.modifiers)),
+                    ("type", self.opt_type_hint(constant.ty.as_ref())),
+                    (
+                        "consts",
+                        Json::Array(
+                            constant
+                                .consts
+                                .iter()
+                                .map(|item| self.const_item(&item.name, &item.value, item.span))
+                                .collect(),
+                        ),
+                    ),
+                ],
+            ),
+            MemberKind::Method(method) => {
+                let mut fields = self.function(&method.function);
+                fields.insert(1, ("flags", flags(&method.modifiers)));
+                ("Stmt_ClassMethod", fields)
+            }
+            MemberKind::TraitUse(trait_use) => {
+                let adaptations = trait_use
+                    .adaptations
+                    .iter()
+                    .map(|adaptation| match &adaptation.kind {
+                        AdaptationKind::InsteadOf {
+                            trait_name,
+                            method,
+                            instead_of,
+                        } => self.node(
+                            "Stmt_TraitUseAdaptation_Precedence",
+                            adaptation.span,
+                            vec![
+                                ("trait", self.name(trait_name)),
+                                ("method", self.ident(method)),
+                                ("insteadof", self.names(instead_of)),
+                            ],
+                        ),
+                        AdaptationKind::Alias {
+                            trait_name,
+                            method,
+                            visibility,
+                            alias,
+                        } => self.node(
+                            "Stmt_TraitUseAdaptation_Alias",
+                            adaptation.span,
+                            vec![
+                                (
+                                    "trait",
+                                    trait_name.as_ref().map(|name| self.name(name)).into(),
+                                ),
+                                ("method", self.ident(method)),
+                                (
+                                    "newModifier",
+                                    visibility
+                                        .map(|modifier| flags(std::slice::from_ref(&modifier)))
+                                        .into(),
+                                ),
+                                (
+                                    "newName",
+                                    alias.as_ref().map(|alias| self.ident(alias)).into(),
+                                ),
+                            ],
+                        ),
+                    })
+                    .collect();
+                (
+                    "Stmt_TraitUse",
+                    vec![
+                        ("traits", self.names(&trait_use.traits)),
+                        ("adaptations", Json::Array(adaptations)),
+                    ],
+                )
+            }
+            MemberKind::EnumCase(case) => (
+                "Stmt_EnumCase",
+                vec![
+                    ("attrGroups", self.attr_groups(&case.attributes)),
+                    ("name", self.ident(&case.name)),
+                    ("expr", self.opt_expr(case.value.as_ref())),
+                ],
+            ),
+            MemberKind::Error => return None,
+        };
+        Some(self.node(node_type, member.span, fields))
+    }
+
+    fn array_item(&self, item: &ArrayItem) -> Json {
+        self.node(
+            "ArrayItem",
+            item.span,
+            vec![
+                ("key", self.opt_expr(item.key.as_ref())),
+                ("value", self.expr(&item.value)),
+                ("byRef", Json::Bool(item.by_ref)),
+                ("unpack", Json::Bool(item.unpack)),
+            ],
+        )
+    }
+
+    fn string_parts(&self, parts: &[StringPart]) -> Json {
+        let parts = parts
+            .iter()
+            .map(|part| match part {
+                StringPart::Literal(text, span) => self.node(
+                    "InterpolatedStringPart",
+                    *span,
+                    vec![("value", Json::bytes(text))],
+                ),
+                StringPart::Expr(expr) => self.expr(expr),
+            })
+            .collect();
+        Json::Array(parts)
+    }
+
+    fn class_ref(&self, class: &ClassRef) -> Json {
+        match class {
+            ClassRef::Name(name) => self.name(name),
+            ClassRef::Expr(expr) => self.expr(expr),
+            ClassRef::Anonymous(class) => self.class_like(class),
+        }
+    }
+
+    fn member_name(&self, member: &MemberName) -> Json {
+        match member {
+            MemberName::Ident(ident) => self.ident(ident),
+            MemberName::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    /// A constant fetch of `name`, which is how PHP-Parser represents `true`, `false` and
+    /// `null` too.
+    fn const_fetch(&self, name: &str, span: Span) -> Json {
+        let name = self.node("Name", span, vec![("name", Json::str(name))]);
+        self.node("Expr_ConstFetch", span, vec![("name", name)])
+    }
+
+    fn expr(&self, expr: &Expr) -> Json {
+        let span = expr.span;
+        let (node_type, fields): (&str, _) = match &expr.kind {
+            ExprKind::Literal(literal) => match literal {
+                Literal::Null | Literal::Bool(_) => {
+                    let written = match literal {
+                        Literal::Bool(true) => "true",
+                        Literal::Bool(false) => "false",
+                        _ => "null",
+                    };
+                    // The name keeps the case it was written in.
+                    let name = self
+                        .text(span)
+                        .filter(|text| text.eq_ignore_ascii_case(written))
+                        .unwrap_or(written);
+                    return self.const_fetch(name, span);
+                }
+                Literal::Numeric(Numeric::Int(i)) => ("Scalar_Int", vec![("value", Json::Int(*i))]),
+                Literal::Numeric(Numeric::Float(f)) => {
+                    ("Scalar_Float", vec![("value", Json::Float(*f))])
+                }
+                // PHP has no integers this wide, they overflow to floats.
+                Literal::Numeric(Numeric::LInt(i)) => {
+                    ("Scalar_Float", vec![("value", Json::Float(*i as f64))])
+                }
+                Literal::String(s) => ("Scalar_String", vec![("value", Json::bytes(s))]),
+            },
+            ExprKind::Interpolated { parts, .. } => (
+                "Scalar_InterpolatedString",
+                vec![("parts", self.string_parts(parts))],
+            ),
+            ExprKind::ShellExec(parts) => {
+                ("Expr_ShellExec", vec![("parts", self.string_parts(parts))])
+            }
+            ExprKind::Variable(name) => ("Expr_Variable", vec![("name", Json::str(name))]),
+            ExprKind::VariableVariable(inner) => {
+                ("Expr_Variable", vec![("name", self.expr(inner))])
+            }
+            ExprKind::Array { items, .. } => (
+                "Expr_Array",
+                vec![(
+                    "items",
+                    Json::Array(items.iter().map(|item| self.array_item(item)).collect()),
+                )],
+            ),
+            ExprKind::List { items, .. } => (
+                "Expr_List",
+                vec![(
+                    "items",
+                    Json::Array(
+                        items
+                            .iter()
+                            .map(|item| item.as_ref().map(|item| self.array_item(item)).into())
+                            .collect(),
+                    ),
+                )],
+            ),
+            ExprKind::ConstFetch(name) => ("Expr_ConstFetch", vec![("name", self.name(name))]),
+            ExprKind::ReservedConst(reserved) => match magic_const(*reserved) {
+                Some(node_type) => (node_type, vec![]),
+                None => return self.const_fetch(reserved.as_str(), span),
+            },
+            ExprKind::ClassConstFetch { class, constant } => (
+                "Expr_ClassConstFetch",
+                vec![
+                    ("class", self.class_ref(class)),
+                    ("name", self.member_name(constant)),
+                ],
+            ),
+            ExprKind::StaticPropertyFetch { class, property } => {
+                let name = match &property.kind {
+                    ExprKind::Variable(name) => self.node(
+                        "VarLikeIdentifier",
+                        property.span,
+                        vec![("name", Json::str(name))],
+                    ),
+                    ExprKind::VariableVariable(inner) => self.expr(inner),
+                    _ => self.expr(property),
+                };
+                (
+                    "Expr_StaticPropertyFetch",
+                    vec![("class", self.class_ref(class)), ("name", name)],
+                )
+            }
+            ExprKind::PropertyFetch {
+                object,
+                property,
+                nullsafe,
+            } => (
+                if *nullsafe {
+                    "Expr_NullsafePropertyFetch"
+                } else {
+                    "Expr_PropertyFetch"
+                },
+                vec![
+                    ("var", self.expr(object)),
+                    ("name", self.member_name(property)),
+                ],
+            ),
+            ExprKind::ArrayDim { array, index } => (
+                "Expr_ArrayDimFetch",
+                vec![
+                    ("var", self.expr(array)),
+                    ("dim", self.opt_expr(index.as_deref())),
+                ],
+            ),
+            ExprKind::Call { callee, args } => {
+                let name = match &callee.kind {
+                    ExprKind::ConstFetch(name) => self.name(name),
+                    _ => self.expr(callee),
+                };
+                (
+                    "Expr_FuncCall",
+                    vec![("name", name), ("args", self.args(args))],
+                )
+            }
+            ExprKind::MethodCall {
+                object,
+                method,
+                args,
+                nullsafe,
+            } => (
+                if *nullsafe {
+                    "Expr_NullsafeMethodCall"
+                } else {
+                    "Expr_MethodCall"
+                },
+                vec![
+                    ("var", self.expr(object)),
+                    ("name", self.member_name(method)),
+                    ("args", self.args(args)),
+                ],
+            ),
+            ExprKind::StaticCall {
+                class,
+                method,
+                args,
+            } => (
+                "Expr_StaticCall",
+                vec![
+                    ("class", self.class_ref(class)),
+                    ("name", self.member_name(method)),
+                    ("args", self.args(args)),
+                ],
+            ),
+            ExprKind::New { class, args } => (
+                "Expr_New",
+                vec![
+                    ("class", self.class_ref(class)),
+                    (
+                        "args",
+                        args.as_ref()
+                            .map_or(Json::Array(Vec::new()), |args| self.arg_list(args)),
+                    ),
+                ],
+            ),
+            ExprKind::Unary { op, expr } => (
+                match op {
+                    UnaryOp::Plus => "Expr_UnaryPlus",
+                    UnaryOp::Minus => "Expr_UnaryMinus",
+                    UnaryOp::Not => "Expr_BooleanNot",
+                    UnaryOp::BitNot => "Expr_BitwiseNot",
+                },
+                vec![("expr", self.expr(expr))],
+            ),
+            ExprKind::Binary { op, left, right } => {
+                return self.node(
+                    &format!("Expr_BinaryOp_{}", binary_op(*op)),
+                    span,
+                    vec![("left", self.expr(left)), ("right", self.expr(right))],
+                )
+            }
+            ExprKind::IncDec { op, expr } => (
+                match op {
+                    IncDecOp::PreIncrement => "Expr_PreInc",
+                    IncDecOp::PreDecrement => "Expr_PreDec",
+                    IncDecOp::PostIncrement => "Expr_PostInc",
+                    IncDecOp::PostDecrement => "Expr_PostDec",
+                },
+                vec![("var", self.expr(expr))],
+            ),
+            ExprKind::Assign { target, value } => (
+                "Expr_Assign",
+                vec![("var", self.expr(target)), ("expr", self.expr(value))],
+            ),
+            ExprKind::AssignRef { target, value } => (
+                "Expr_AssignRef",
+                vec![("var", self.expr(target)), ("expr", self.expr(value))],
+            ),
+            ExprKind::AssignOp { op, target, value } => {
+                return self.node(
+                    &format!("Expr_AssignOp_{}", binary_op(*op)),
+                    span,
+                    vec![("var", self.expr(target)), ("expr", self.expr(value))],
+                )
+            }
+            ExprKind::Ternary {
+                cond,
+                then,
+                otherwise,
+            } => (
+                "Expr_Ternary",
+                vec![
+                    ("cond", self.expr(cond)),
+                    ("if", self.opt_expr(then.as_deref())),
+                    ("else", self.expr(otherwise)),
+                ],
+            ),
+            ExprKind::Cast { kind, expr } => (
+                match kind {
+                    CastKind::Int => "Expr_Cast_Int",
+                    CastKind::Float => "Expr_Cast_Double",
+                    CastKind::String => "Expr_Cast_String",
+                    CastKind::Bool => "Expr_Cast_Bool",
+                    CastKind::Array => "Expr_Cast_Array",
+                    CastKind::Object => "Expr_Cast_Object",
+                    CastKind::Unset => "Expr_Cast_Unset",
+                },
+                vec![("expr", self.expr(expr))],
+            ),
+            ExprKind::InstanceOf { expr, class } => (
+                "Expr_Instanceof",
+                vec![("expr", self.expr(expr)), ("class", self.class_ref(class))],
+            ),
+            ExprKind::Clone(expr) => ("Expr_Clone", vec![("expr", self.expr(expr))]),
+            ExprKind::Print(expr) => ("Expr_Print", vec![("expr", self.expr(expr))]),
+            ExprKind::ErrorSuppress(expr) => {
+                ("Expr_ErrorSuppress", vec![("expr", self.expr(expr))])
+            }
+            ExprKind::Construct { construct, args } => match construct {
+                ReservedCall::IsSet => ("Expr_Isset", vec![("vars", self.exprs(args))]),
+                ReservedCall::Empty => ("Expr_Empty", vec![("expr", self.opt_expr(args.first()))]),
+                ReservedCall::Eval => ("Expr_Eval", vec![("expr", self.opt_expr(args.first()))]),
+                ReservedCall::Exit | ReservedCall::Die => {
+                    ("Expr_Exit", vec![("expr", self.opt_expr(args.first()))])
+                }
+                // The other reserved calls have nodes of their own and never get here.
+                ReservedCall::HaltCompiler
+                | ReservedCall::Array
+                | ReservedCall::List
+                | ReservedCall::Unset => {
+                    let name =
+                        self.node("Name", span, vec![("name", Json::str(construct.as_str()))]);
+                    let args = args
+                        .iter()
+                        .map(|arg| {
+                            self.node(
+                                "Arg",
+                                arg.span,
+                                vec![
+                                    ("name", Json::Null),
+                                    ("value", self.expr(arg)),
+                                    ("byRef", Json::Bool(false)),
+                                    ("unpack", Json::Bool(false)),
+                                ],
+                            )
+                        })
+                        .collect();
+                    (
+                        "Expr_FuncCall",
+                        vec![("name", name), ("args", Json::Array(args))],
+                    )
+                }
+            },
+            ExprKind::Include { keyword, path } => (
+                "Expr_Include",
+                vec![
+                    ("expr", self.expr(path)),
+                    (
+                        "type",
+                        Json::Int(match keyword {
+                            Keyword::IncludeOnce => 2,
+                            Keyword::Require => 3,
+                            Keyword::RequireOnce => 4,
+                            _ => 1,
+                        }),
+                    ),
+                ],
+            ),
+            ExprKind::Closure(closure) => {
+                let uses = closure
+                    .uses
+                    .iter()
+                    .map(|closure_use| {
+                        self.node(
+                            "ClosureUse",
+                            closure_use.span,
+                            vec![
+                                ("var", self.variable(&closure_use.name)),
+                                ("byRef", Json::Bool(closure_use.by_ref)),
+                            ],
+                        )
+                    })
+                    .collect();
+                (
+                    "Expr_Closure",
+                    vec![
+                        ("attrGroups", self.attr_groups(&closure.attributes)),
+                        ("static", Json::Bool(closure.is_static)),
+                        ("byRef", Json::Bool(closure.by_ref)),
+                        ("params", self.params(&closure.params)),
+                        ("uses", Json::Array(uses)),
+                        (
+                            "returnType",
+                            self.opt_type_hint(closure.return_type.as_ref()),
+                        ),
+                        ("stmts", self.block(&closure.body)),
+                    ],
+                )
+            }
+            ExprKind::ArrowFunction(function) => (
+                "Expr_ArrowFunction",
+                vec![
+                    ("attrGroups", self.attr_groups(&function.attributes)),
+                    ("static", Json::Bool(function.is_static)),
+                    ("byRef", Json::Bool(function.by_ref)),
+                    ("params", self.params(&function.params)),
+                    (
+                        "returnType",
+                        self.opt_type_hint(function.return_type.as_ref()),
+                    ),
+                    ("expr", self.expr(&function.body)),
+                ],
+            ),
+            ExprKind::Match { subject, arms } => {
+                let arms = arms
+                    .iter()
+                    .map(|arm| {
+                        self.node(
+                            "MatchArm",
+                            arm.span,
+                            vec![
+                                (
+                                    "conds",
+                                    arm.conditions
+                                        .as_ref()
+                                        .map(|conds| self.exprs(conds))
+                                        .into(),
+                                ),
+                                ("body", self.expr(&arm.body)),
+                            ],
+                        )
+                    })
+                    .collect();
+                (
+                    "Expr_Match",
+                    vec![("cond", self.expr(subject)), ("arms", Json::Array(arms))],
+                )
+            }
+            ExprKind::Throw(expr) => ("Expr_Throw", vec![("expr", self.expr(expr))]),
+            ExprKind::Yield { key, value } => (
+                "Expr_Yield",
+                vec![
+                    ("key", self.opt_expr(key.as_deref())),
+                    ("value", self.opt_expr(value.as_deref())),
+                ],
+            ),
+            ExprKind::YieldFrom(expr) => ("Expr_YieldFrom", vec![("expr", self.expr(expr))]),
+            // not in PHP, named the way PHP-Parser would name it.
+            ExprKind::Await(expr) => ("Expr_Await", vec![("expr", self.expr(expr))]),
+        };
+        self.node(node_type, span, fields)
+    }
+}
+
+/// The `type` of a `use` statement, `Stmt\Use_::TYPE_NORMAL` and so on.
+fn use_type(kind: UseKind) -> i64 {
+    match kind {
+        UseKind::Normal => 1,
+        UseKind::Function => 2,
+        UseKind::Const => 3,
+    }
+}
+
+/// The `Modifiers::*` bit mask of a list of modifiers, `var` adds nothing.
+fn flags(modifiers: &[Modifier]) -> Json {
+    let flags = modifiers
+        .iter()
+        .map(|modifier| match modifier.keyword {
+            Keyword::Public => 1,
+            Keyword::Protected => 2,
+            Keyword::Private => 4,
+            Keyword::Static => 8,
+            Keyword::Abstract => 16,
+            Keyword::Final => 32,
+            Keyword::ReadOnly => 64,
+            _ => 0,
+        })
+        .fold(0, |flags, flag| flags | flag);
+    Json::Int(flags)
+}
+
+/// The node type suffix PHP-Parser uses for a binary or compound assignment operator.
+fn binary_op(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "Plus",
+        BinaryOp::Sub => "Minus",
+        BinaryOp::Mul => "Mul",
+        BinaryOp::Div => "Div",
+        BinaryOp::Mod => "Mod",
+        BinaryOp::Pow => "Pow",
+        BinaryOp::Concat => "Concat",
+        BinaryOp::BitAnd => "BitwiseAnd",
+        BinaryOp::BitOr => "BitwiseOr",
+        BinaryOp::BitXor => "BitwiseXor",
+        BinaryOp::ShiftLeft => "ShiftLeft",
+        BinaryOp::ShiftRight => "ShiftRight",
+        BinaryOp::BooleanAnd => "BooleanAnd",
+        BinaryOp::BooleanOr => "BooleanOr",
+        BinaryOp::LogicalAnd => "LogicalAnd",
+        BinaryOp::LogicalOr => "LogicalOr",
+        BinaryOp::LogicalXor => "LogicalXor",
+        BinaryOp::Equal => "Equal",
+        BinaryOp::NotEqual => "NotEqual",
+        BinaryOp::Identical => "Identical",
+        BinaryOp::NotIdentical => "NotIdentical",
+        BinaryOp::Less => "Smaller",
+        BinaryOp::LessOrEqual => "SmallerOrEqual",
+        BinaryOp::Greater => "Greater",
+        BinaryOp::GreaterOrEqual => "GreaterOrEqual",
+        BinaryOp::Spaceship => "Spaceship",
+        BinaryOp::Coalesce => "Coalesce",
+    }
+}
+
+fn magic_const(reserved: ReservedIdent) -> Option<&'static str> {
+    Some(match reserved {
+        ReservedIdent::MagicClass => "Scalar_MagicConst_Class",
+        ReservedIdent::MagicDir => "Scalar_MagicConst_Dir",
+        ReservedIdent::MagicFile => "Scalar_MagicConst_File",
+        ReservedIdent::MagicFunction => "Scalar_MagicConst_Function",
+        ReservedIdent::MagicLine => "Scalar_MagicConst_Line",
+        ReservedIdent::MagicMethod => "Scalar_MagicConst_Method",
+        ReservedIdent::MagicNamespace => "Scalar_MagicConst_Namespace",
+        ReservedIdent::MagicTrait => "Scalar_MagicConst_Trait",
+        _ => return None,
+    })
+}
@@ -1,50 +1,71 @@
-use std::{io::Error, io::ErrorKind, str::Chars};
+use std::{borrow::Cow, io::Error, io::ErrorKind, str::Chars};
 
-pub const END_OF_FILE: char = '\0';
+use crate::syntax::experiments::Experiments;
 
-/// A struct that handles a stream of chars
+/// A struct that handles a stream of chars.
+///
+/// The position of the cursor is tracked in bytes, so that any position it reports can be
+/// used to slice the original input.
 pub struct Cursor<'a> {
-    ilen: usize,
-    chars: Chars<'a>,
-    prev: char,
-    /// the current index in the chars buffer.
+    input: Cow<'a, str>,
+    /// the current byte offset into `input`.
     index: usize,
+    /// the zero based line of the cursor, `\r\n`, `\n` and a lone `\r` each end a line.
+    line: usize,
+    /// The experiments whose syntax is lexed, see `Experiments`.
+    pub(super) experiments: Experiments,
+    /// Whether the cursor is outside of PHP code, after a `?>`.
+    pub(super) html: bool,
 }
 
 impl<'a> Cursor<'a> {
-    pub fn new(input: &'a str) -> Cursor<'a> {
+    pub fn new(input: impl Into<Cow<'a, str>>) -> Cursor<'a> {
         Cursor {
-            ilen: input.len(),
-            chars: input.chars(),
-            prev: END_OF_FILE,
+            input: input.into(),
             index: 0,
+            line: 0,
+            experiments: Experiments::default(),
+            html: false,
         }
     }
 
-    pub fn peek(&mut self) -> Option<char> {
-        match self.chars.next() {
-            Some(c) => {
-                self.prev = c;
+    /// Sets the line the input starts on, for input that continues a previous one.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
 
-                if self.is_eof() {
-                    return None;
-                }
+    /// Lexes the syntax of the enabled `experiments` as well.
+    pub fn with_experiments(mut self, experiments: Experiments) -> Self {
+        self.experiments = experiments;
+        self
+    }
 
-                self.index += 1;
+    /// Sets whether the input starts outside of PHP code, for input that continues a previous
+    /// one.
+    pub fn in_html(mut self, html: bool) -> Self {
+        self.html = html;
+        self
+    }
 
-                Some(c)
-            }
-            None => None,
-        }
+    /// Whether the cursor is outside of PHP code.
+    pub fn is_html(&self) -> bool {
+        self.html
     }
 
-    pub fn unpeek(&mut self) -> char {
-        self.chars.nth(self.eaten() - 1).unwrap_or(END_OF_FILE)
+    /// Consumes the next char.
+    pub fn peek(&mut self) -> Option<char> {
+        let c = self.chars().next()?;
+        self.index += c.len_utf8();
+        if c == '\n' || (c == '\r' && !self.rest().starts_with('\n')) {
+            self.line += 1;
+        }
+        Some(c)
     }
 
     /// Is End of file?
     pub fn is_eof(&self) -> bool {
-        self.chars.as_str().is_empty()
+        self.index >= self.input.len()
     }
 
     // Grabs the next char without consuming it.
@@ -58,74 +79,80 @@ impl<'a> Cursor<'a> {
     }
 
     /// Returns the `nth_char` releative to the current cursor pos
-    /// If the position given doesn't exist, `END_OF_FILE` is returned.
+    /// If the position given doesn't exist, an `UnexpectedEof` error is returned.
     pub fn nth_char(&self, amt: usize) -> Result<char, Error> {
         self.chars()
             .nth(amt)
             .ok_or_else(|| Error::from(ErrorKind::UnexpectedEof))
     }
 
-    /// Copies the current chars in the cursor.
-    pub fn chars(&self) -> Chars<'a> {
-        self.chars.clone()
+    /// Copies the remaining chars in the cursor.
+    pub fn chars(&self) -> Chars<'_> {
+        self.rest().chars()
     }
 
-    pub fn get_pos(&self) -> usize {
-        self.index.clone()
+    /// The whole input of the cursor.
+    pub fn input(&self) -> &str {
+        &self.input
     }
 
-    pub fn get_prev(&self) -> char {
-        self.prev.clone()
+    /// The input that has not been consumed yet.
+    pub fn rest(&self) -> &str {
+        &self.input[self.index..]
     }
 
-    pub fn peek_get_pos(&mut self) -> usize {
-        self.peek();
-        self.index.clone()
+    /// The byte offset of the cursor.
+    pub fn get_pos(&self) -> usize {
+        self.index
     }
 
-    /// Increment current position by `x` and return the new position.
-    ///
-    /// ! DOES NOT PEEK, FOR PEEK INCREMENTING USE `peek_inc`
-    pub fn ipeek_get_pos(&mut self, x: usize) -> usize {
-        self.peek_inc(x);
-        self.index.clone()
+    /// The zero based line of the cursor.
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    /// The input between `start` and the current position.
+    pub fn slice_from(&self, start: usize) -> &str {
+        &self.input[start..self.index]
     }
 
     /// Increments the current buffer with the given one.
-    /// Peeks `x` times
+    /// Peeks `x + 1` times, consuming every char up to and including the `x`th char.
     pub fn peek_inc(&mut self, x: usize) {
-        let mut i = 0;
-        while !self.is_eof() && i <= x {
-            self.peek();
-            i += 1;
+        for _ in 0..=x {
+            if self.peek().is_none() {
+                break;
+            }
         }
     }
 
-    /// Shows how many chars have been consumed by the cursor.
-    pub fn eaten(&self) -> usize {
-        self.ilen - self.chars.as_str().len()
+    /// Consumes `n` bytes of input, `n` must end on a char boundary.
+    pub fn peek_bytes(&mut self, n: usize) {
+        let end = self.index + n;
+        while self.index < end && self.peek().is_some() {}
     }
 
     pub fn eat_while(&mut self, mut pred: impl FnMut(char) -> bool) -> Result<String, Error> {
-        let mut segment = String::new();
-        while !self.is_eof() && pred(self.first()?) == true {
-            segment.push(self.peek().unwrap_or(END_OF_FILE));
-        }
-        Ok(segment)
-    }
-
-    pub fn eat_while_cursor(
-        &mut self,
-        mut pred: impl FnMut(&mut Cursor<'a>, char) -> bool,
-    ) -> Result<String, Error> {
-        let mut segment = String::new();
-        while !self.is_eof() && pred(self, self.first()?) == true {
-            segment.push(self.peek().unwrap_or(END_OF_FILE));
+        let start = self.index;
+        while !self.is_eof() && pred(self.first()?) {
+            self.peek();
         }
-        Ok(segment)
+        Ok(self.slice_from(start).to_string())
     }
 }
 
+/// Whether `c` starts a line ending, `\r\n` is treated as a single line ending by the lexer.
 pub fn is_line_ending(c: char) -> bool {
-    c == '\n'
+    c == '\n' || c == '\r'
+}
+
+/// The length in bytes of the line ending at the start of `input`, if there is one.
+pub fn line_ending_len(input: &str) -> Option<usize> {
+    if input.starts_with("\r\n") {
+        Some(2)
+    } else if input.starts_with(is_line_ending) {
+        Some(1)
+    } else {
+        None
+    }
 }
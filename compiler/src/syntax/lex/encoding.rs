@@ -0,0 +1,196 @@
+use std::{
+    borrow::Cow,
+    io::{Error, ErrorKind},
+};
+
+/// The UTF-8 byte order mark, some editors write this to the start of every file.
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// The encodings a script may be written in.
+///
+/// Scripts are assumed to be UTF-8 unless they say otherwise with `declare(encoding='...')`.
+/// Bytes that are not valid UTF-8 in an undeclared script are read as ISO-8859-1, which is
+/// what PHP itself does in practice, as it never looks at the bytes of a string literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    /// ISO-8859-1, also known as Latin-1. Every byte maps to the code point of the same value.
+    Latin1,
+    /// Windows-1252, Latin-1 with printable characters in `0x80..=0x9F`.
+    Windows1252,
+}
+
+impl Encoding {
+    /// Gets the encoding for a name used in `declare(encoding='...')`, names are case insensitive.
+    pub fn from_label(label: &str) -> Option<Encoding> {
+        match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Encoding::Utf8),
+            "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" => Some(Encoding::Latin1),
+            "windows-1252" | "cp1252" => Some(Encoding::Windows1252),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn decode_byte(self, byte: u8) -> char {
+        match (self, byte) {
+            (Encoding::Windows1252, 0x80..=0x9F) => WINDOWS_1252[(byte - 0x80) as usize],
+            _ => char::from(byte),
+        }
+    }
+}
+
+/// Windows-1252 code points for `0x80..=0x9F`, the five undefined bytes keep their C1 code point.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// The original bytes of a decoded script, and what is needed to map the decoded text back to them.
+///
+/// Spans produced by the lexer are offsets into the decoded text, use `raw_offset` or `raw` to
+/// find the bytes they came from.
+#[derive(Debug, Clone)]
+pub struct RawSource<'a> {
+    pub raw: &'a [u8],
+    pub encoding: Encoding,
+    /// Whether a UTF-8 byte order mark was stripped from the start of the script.
+    pub bom: bool,
+    /// `(text offset, raw offset)` pairs at every point the two offsets move apart.
+    shifts: Vec<(usize, usize)>,
+}
+
+impl<'a> RawSource<'a> {
+    pub fn utf8(script: &'a str) -> Self {
+        Self {
+            raw: script.as_bytes(),
+            encoding: Encoding::Utf8,
+            bom: false,
+            shifts: Vec::new(),
+        }
+    }
+
+    /// Decodes the bytes of a script to text, stripping a leading byte order mark.
+    ///
+    /// Fails if the script declares an encoding that is not supported.
+    pub fn decode(raw: &'a [u8]) -> Result<(Cow<'a, str>, Self), Error> {
+        let bom = raw.starts_with(UTF8_BOM);
+        let body = if bom { &raw[UTF8_BOM.len()..] } else { raw };
+
+        let encoding = match declared_encoding(body) {
+            Some(label) => Encoding::from_label(&label).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unsupported encoding [{}] in declare(encoding=...)", label),
+                )
+            })?,
+            None => Encoding::Utf8,
+        };
+
+        let mut source = Self {
+            raw,
+            encoding,
+            bom,
+            shifts: vec![(0, raw.len() - body.len())],
+        };
+
+        let text = match (encoding, std::str::from_utf8(body)) {
+            (Encoding::Utf8, Ok(text)) => Cow::Borrowed(text),
+            (Encoding::Utf8, Err(_)) => Cow::Owned(source.decode_utf8_lossless(body)),
+            (_, Ok(text)) if text.is_ascii() => Cow::Borrowed(text),
+            _ => Cow::Owned(source.decode_single_byte(body)),
+        };
+
+        Ok((text, source))
+    }
+
+    /// Decodes valid UTF-8 sequences as they are, and every other byte as Latin-1.
+    fn decode_utf8_lossless(&mut self, mut body: &[u8]) -> String {
+        let mut text = String::with_capacity(body.len());
+        let mut raw_offset = self.shifts[0].1;
+
+        while !body.is_empty() {
+            match std::str::from_utf8(body) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    // `valid_up_to` bytes are known to be UTF-8.
+                    text.push_str(std::str::from_utf8(&body[..valid]).unwrap());
+                    text.push(Encoding::Latin1.decode_byte(body[valid]));
+                    raw_offset += valid + 1;
+                    self.shifts.push((text.len(), raw_offset));
+                    body = &body[valid + 1..];
+                }
+            }
+        }
+
+        text
+    }
+
+    fn decode_single_byte(&mut self, body: &[u8]) -> String {
+        let mut text = String::with_capacity(body.len());
+        let mut raw_offset = self.shifts[0].1;
+
+        for &byte in body {
+            text.push(self.encoding.decode_byte(byte));
+            raw_offset += 1;
+            if !byte.is_ascii() {
+                self.shifts.push((text.len(), raw_offset));
+            }
+        }
+
+        text
+    }
+
+    /// Maps an offset into the decoded text back to an offset into the original bytes.
+    pub fn raw_offset(&self, offset: usize) -> usize {
+        let i = self.shifts.partition_point(|&(text, _)| text <= offset);
+        match i.checked_sub(1).map(|i| self.shifts[i]) {
+            Some((text, raw)) => raw + (offset - text),
+            None => offset,
+        }
+    }
+
+    /// The original bytes of `range`, non UTF-8 bytes are returned unchanged.
+    pub fn raw(&self, range: std::ops::Range<usize>) -> &'a [u8] {
+        &self.raw[self.raw_offset(range.start)..self.raw_offset(range.end)]
+    }
+}
+
+/// Finds the label of a `declare(encoding='...')` that opens the script, if there is one.
+///
+/// PHP only accepts the encoding directive as the very first statement, so this only looks
+/// past the open tag and any whitespace before it.
+pub(crate) fn declared_encoding(script: &[u8]) -> Option<String> {
+    fn skip_whitespace(s: &[u8]) -> &[u8] {
+        let start = s
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(s.len());
+        &s[start..]
+    }
+
+    fn expect<'s>(s: &'s [u8], word: &str) -> Option<&'s [u8]> {
+        let s = skip_whitespace(s);
+        if s.len() >= word.len() && s[..word.len()].eq_ignore_ascii_case(word.as_bytes()) {
+            Some(&s[word.len()..])
+        } else {
+            None
+        }
+    }
+
+    let rest = expect(script, "<?php")?;
+    let rest = expect(rest, "declare")?;
+    let rest = expect(rest, "(")?;
+    let rest = expect(rest, "encoding")?;
+    let rest = skip_whitespace(expect(rest, "=")?);
+
+    let quote = *rest.first()?;
+    if quote != b'\'' && quote != b'"' {
+        return None;
+    }
+    let len = rest[1..].iter().position(|&b| b == quote)?;
+    Some(String::from_utf8_lossy(&rest[1..1 + len]).into_owned())
+}
@@ -4,27 +4,49 @@ use std::{
 };
 
 use self::{
-    cursor::{Cursor, END_OF_FILE},
-    token::{AccessType, Numeric, StringType, Token, TokenType},
+    cursor::{is_line_ending, line_ending_len, Cursor},
+    encoding::{Encoding, RawSource},
+    token::{AccessType, Numeric, StringType, Token, TokenType, LF},
 };
 
 use crate::token;
 
-use super::ast::keyword::{Keyword, MAX_KEYWORD_LENGTH};
+use super::{
+    ast::{keyword::Keyword, reserved::ReservedIdent},
+    experiments::{Experiment, Experiments},
+};
 
 pub(crate) mod cursor;
+pub mod encoding;
+pub mod stream;
 pub mod token;
 
-/// A trait that may be used to implement future implementations of PHP.
-pub(crate) trait Tokenizer<'a> {
-    fn lex(&mut self, cursor: &'a mut Cursor) -> Result<Token, Error>;
-}
-
 /// The basic PHP Lexer, Serves the syntax of PHP 7.3+
 impl Cursor<'_> {
     fn eat(&mut self) -> Result<Option<Token>, Error> {
+        if self.is_eof() {
+            return Ok(None);
+        }
         let start_pos = self.get_pos();
 
+        if let Some(tag) = self.eat_open_tag() {
+            let text = self.slice_from(start_pos).to_string();
+            return token!(start_pos, self.get_pos(), tag, Some(text));
+        }
+
+        if self.html {
+            let text = self.eat_inline_html();
+            return token!(start_pos, self.get_pos(), TokenType::InlineHtml, Some(text));
+        }
+
+        if let Some(after) = self.rest().strip_prefix("?>") {
+            // the close tag takes a single line ending after it.
+            self.peek_bytes(2 + line_ending_len(after).unwrap_or(0));
+            self.html = true;
+            let text = self.slice_from(start_pos).to_string();
+            return token!(start_pos, self.get_pos(), TokenType::CloseTag, Some(text));
+        }
+
         if let Some(spaces) = self.eat_whitespace()? {
             return token!(
                 start_pos,
@@ -34,42 +56,33 @@ impl Cursor<'_> {
             );
         }
 
+        if let Some(lf) = self.eat_line_ending() {
+            return token!(start_pos, self.get_pos(), TokenType::LF(lf));
+        }
+
         if let Some(comment) = self.eat_comment()? {
             return token!(start_pos, self.get_pos(), TokenType::Comment, Some(comment));
         }
 
-        if let Some(operator) = self.eat_operator()? {
+        if let Some((variant, body)) = self.eat_heredoc()? {
             return token!(
                 start_pos,
                 self.get_pos(),
-                TokenType::Operator,
-                Some(operator)
+                TokenType::StringLit(variant),
+                Some(body)
             );
         }
 
-        if let Some(keyword) = self.eat_keyword()? {
-            return token!(start_pos, self.get_pos(), TokenType::Keyword(keyword), None);
-        }
-
-        if let Some(boolean) = self.eat_boolean()? {
-            return token!(start_pos, self.get_pos(), TokenType::Boolean, Some(boolean));
-        }
-
-        if let Some(identifier) = self.eat_identifier()? {
+        if let Some(n) = self.eat_number()? {
             return token!(
                 start_pos,
                 self.get_pos(),
-                TokenType::Identifier,
-                Some(identifier)
+                TokenType::NumericalLit(n),
+                Some(self.slice_from(start_pos).to_string())
             );
         }
 
-        if let Some(n) = self.eat_number()? {
-            return token!(start_pos, self.get_pos(), TokenType::NumericalLit(n));
-        }
-
         if let Some((var, string)) = self.eat_string()? {
-            self.peek(); // what?
             return token!(
                 start_pos,
                 self.get_pos(),
@@ -78,6 +91,51 @@ impl Cursor<'_> {
             );
         }
 
+        if let Some(cast) = self.eat_cast() {
+            return token!(start_pos, self.get_pos(), TokenType::Operator, Some(cast));
+        }
+
+        if let Some(operator) = self.eat_operator() {
+            let kind = match operator {
+                "->" => TokenType::Accessor(AccessType::ReferenceMember),
+                "?->" => TokenType::Accessor(AccessType::NullsafeMember),
+                _ => TokenType::Operator,
+            };
+            return token!(start_pos, self.get_pos(), kind, Some(operator.to_string()));
+        }
+
+        let start_line = self.get_line();
+        if let Some(word) = self.eat_word() {
+            if let Ok(keyword) = Keyword::from_str(&word.to_ascii_lowercase()) {
+                // `async` and `await` are plain names in PHP.
+                let gated = matches!(keyword, Keyword::Async | Keyword::Await)
+                    && !self.experiments.is_enabled(Experiment::AsyncAwait);
+                if !gated && (keyword != Keyword::Enum || self.at_enum_name()) {
+                    return token!(start_pos, self.get_pos(), TokenType::Keyword(keyword), None);
+                }
+            }
+
+            if word.eq_ignore_ascii_case("true") || word.eq_ignore_ascii_case("false") {
+                return token!(start_pos, self.get_pos(), TokenType::Boolean, Some(word));
+            }
+
+            if let Ok(reserved) = ReservedIdent::from_str(&word) {
+                // `__LINE__` is resolved here, where line endings are already known.
+                let value = match reserved {
+                    ReservedIdent::MagicLine => (start_line + 1).to_string(),
+                    _ => word,
+                };
+                return token!(
+                    start_pos,
+                    self.get_pos(),
+                    TokenType::ReservedIdent(reserved),
+                    Some(value)
+                );
+            }
+
+            return token!(start_pos, self.get_pos(), TokenType::Identifier, Some(word));
+        }
+
         if let Some(token_type) = self.eat_value_reserved()? {
             return token!(start_pos, self.get_pos(), token_type.0, Some(token_type.1));
         }
@@ -89,157 +147,389 @@ impl Cursor<'_> {
         }
 
         self.peek();
-        return Err(Error::new(
+        Err(Error::new(
             ErrorKind::InvalidData,
             format!(
                 "Failed to parse a token from buffer: {} until {}",
                 start_pos,
                 self.get_pos()
             ),
-        ));
+        ))
     }
 
     fn eat_comment(&mut self) -> Result<Option<String>, Error> {
-        return Ok(match self.first()? {
+        Ok(match self.first()? {
             '/' => {
                 // check the next character
                 if self.second()? == '/' {
-                    Some(self.eat_while(|c| c != '\n')?)
+                    Some(self.eat_line_comment())
                 } else if self.second()? == '*' {
-                    // eat the comment
-                    let comment = self.eat_while_cursor(|cursor, c| {
-                        if c == '*' {
-                            if cursor.first().unwrap() == '/' {
-                                cursor.eat();
-                                return false;
-                            } else {
-                                return true;
-                            }
-                        } else {
-                            return true;
-                        }
-                    })?;
-                    Some(comment)
+                    // an unterminated comment runs until the end of the file.
+                    let start = self.get_pos();
+                    let len = self.rest()[2..]
+                        .find("*/")
+                        .map_or(self.rest().len(), |end| end + 4);
+                    self.peek_bytes(len);
+                    Some(self.slice_from(start).to_string())
                 } else {
                     None
                 }
             }
+            // `#[` starts an attribute rather than a comment.
+            '#' if !self.rest().starts_with("#[") => Some(self.eat_line_comment()),
             _ => None,
-        });
+        })
+    }
+
+    /// Eats a `//` or `#` comment, which ends at a line ending or before a `?>`.
+    fn eat_line_comment(&mut self) -> String {
+        let rest = self.rest();
+        let len = rest.find(is_line_ending).unwrap_or(rest.len());
+        let len = rest[..len].find("?>").unwrap_or(len);
+        let start = self.get_pos();
+        self.peek_bytes(len);
+        self.slice_from(start).to_string()
+    }
+
+    /// Eats `<?php` or `<?=`, which start PHP code. The whitespace after `<?php` belongs to it.
+    fn eat_open_tag(&mut self) -> Option<TokenType> {
+        let (tag, len) = open_tag(self.rest())?;
+        self.peek_bytes(len);
+        self.html = false;
+        Some(tag)
+    }
+
+    /// Eats the text outside of PHP code, up to the next open tag.
+    fn eat_inline_html(&mut self) -> String {
+        let rest = self.rest();
+        let len = rest
+            .match_indices("<?")
+            .map(|(i, _)| i)
+            .find(|&i| open_tag(&rest[i..]).is_some())
+            .unwrap_or(rest.len());
+        let start = self.get_pos();
+        self.peek_bytes(len);
+        self.slice_from(start).to_string()
     }
 
     /// This may be misleading,
-    /// because it eats ALL whitespace until a char is not whitespace
+    /// because it eats ALL whitespace until a char is not whitespace or a line ending.
     fn eat_whitespace(&mut self) -> Result<Option<String>, Error> {
-        let segment = self.eat_while(|c| c.is_whitespace())?;
-        return if segment.is_empty() {
+        let segment = self.eat_while(|c| c.is_whitespace() && !is_line_ending(c))?;
+        if segment.is_empty() {
             Ok(None)
         } else {
             Ok(Some(segment))
-        };
+        }
     }
 
-    fn eat_identifier(&mut self) -> Result<Option<String>, Error> {
-        Ok(match self.first()? {
-            // 'A'..='z' can't be used here as it includes a plethora of reserved characters that are used elsewhere
-            '_' | 'a'..='z' | 'A'..='Z' => Some(
-                self.eat_while(|c: char| !c.is_whitespace() && (c.is_alphanumeric() || c == '_'))?,
-            ),
-            _ => None,
-        })
+    fn eat_line_ending(&mut self) -> Option<LF> {
+        let lf = match line_ending_len(self.rest())? {
+            2 => LF::CRLF,
+            _ if self.rest().starts_with('\r') => LF::CR,
+            _ => LF::LF,
+        };
+        self.peek_inc(matches!(lf, LF::CRLF) as usize);
+        Some(lf)
     }
 
-    fn eat_number(&mut self) -> Result<Option<Numeric>, Error> {
-        Ok(match self.first()? {
-            // there is an issue with leading floats where they are parsed as accessors right now.
-            // we should leave this to the parser.
-            '0'..='9' => {
-                // do this in the background,
-                // todo ACTUALLY IMPLEMENT THIS
-                self.eat_while(|c: char| c.is_digit(10) || c == '.');
-                Some(Numeric::Int(0))
-            }
-            _ => None,
-        })
-    }
+    /// Eats a heredoc or nowdoc string, returning the body with the closing indentation removed.
+    ///
+    /// The closing identifier may be indented (PHP 7.3+), in which case that indentation is
+    /// removed from every line of the body. The line ending before the closing identifier is not
+    /// part of the body, whichever style of line ending is used.
+    fn eat_heredoc(&mut self) -> Result<Option<(StringType, String)>, Error> {
+        let rest = self.rest();
+        let Some(header) = rest.strip_prefix("<<<") else {
+            return Ok(None);
+        };
 
-    /// Eats a keyword but does not parse it.
-    fn eat_keyword(&mut self) -> Result<Option<Keyword>, Error> {
-        let mut segment = String::new();
-        for i in 0..MAX_KEYWORD_LENGTH {
-            segment.push(self.nth_char(i)?);
+        let header = header.trim_start_matches([' ', '\t']);
+        let (variant, quote) = match header.chars().next() {
+            Some('\'') => (StringType::NowDoc, "'"),
+            Some('"') => (StringType::HereDoc, "\""),
+            _ => (StringType::HereDoc, ""),
+        };
+        let header = &header[quote.len()..];
+        let label_len = header
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(header.len());
+        let label = &header[..label_len];
+        let Some(after_label) = header[label_len..].strip_prefix(quote) else {
+            return Ok(None);
+        };
+        let Some(lf_len) = line_ending_len(after_label) else {
+            return Ok(None);
+        };
+        if label.is_empty() || label.starts_with(|c: char| c.is_ascii_digit()) {
+            return Ok(None);
+        }
 
-            if let Ok(keyword) = Keyword::from_str(&segment) {
-                if self.nth_char(i + 1)?.is_whitespace() {
-                    self.peek_inc(i);
-                    return Ok(Some(keyword));
-                } else {
-                    return Ok(None);
+        let body_start = rest.len() - after_label.len() + lf_len;
+        let mut lines: Vec<&str> = Vec::new();
+        let mut offset = body_start;
+        loop {
+            let line = &rest[offset..];
+            let line_len = line.find(is_line_ending).unwrap_or(line.len());
+            let text = &line[..line_len];
+            let indent = text.len() - text.trim_start_matches([' ', '\t']).len();
+
+            if let Some(after) = text[indent..].strip_prefix(label) {
+                if !after.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    let indentation = &text[..indent];
+                    let mut body = String::new();
+                    for (i, line) in lines.iter().enumerate() {
+                        let content = line.trim_end_matches(is_line_ending);
+                        if !content.is_empty() && !content.starts_with(indentation) {
+                            return Err(Error::new(
+                                ErrorKind::InvalidData,
+                                format!(
+                                    "Invalid body indentation level in heredoc `{}`, expecting at least {} characters",
+                                    label, indent
+                                ),
+                            ));
+                        }
+                        body.push_str(content.get(indent..).unwrap_or(""));
+                        // the final line ending belongs to the closing identifier.
+                        if i + 1 < lines.len() {
+                            body.push_str(&line[content.len()..]);
+                        }
+                    }
+
+                    self.peek_bytes(offset + indent + label.len());
+                    return Ok(Some((variant, body)));
+                }
+            }
+
+            match line_ending_len(&line[line_len..]) {
+                Some(len) => {
+                    lines.push(&line[..line_len + len]);
+                    offset += line_len + len;
+                }
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        format!(
+                            "Unterminated heredoc, missing closing identifier `{}`",
+                            label
+                        ),
+                    ))
                 }
             }
         }
+    }
 
-        return Ok(None);
+    /// Whether the `enum` just eaten starts an enum declaration, which is when whitespace and
+    /// a name other than `extends` or `implements` follow, as in PHP's lexer. At most 43 bytes
+    /// are looked at, well within what the streaming lexer keeps past a token.
+    fn at_enum_name(&self) -> bool {
+        let rest = self.rest();
+        let after = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+        let spaces = rest.len() - after.len();
+        if spaces == 0 || spaces > 32 || !after.starts_with(is_word_start) {
+            return false;
+        }
+        let word: String = after
+            .chars()
+            .take_while(|c| is_word_char(*c))
+            .take(11)
+            .collect();
+        !word.eq_ignore_ascii_case("extends") && !word.eq_ignore_ascii_case("implements")
     }
 
-    fn eat_operator(&mut self) -> Result<Option<String>, Error> {
-        Ok(match self.first()? {
-            '+' | '-' | '*' | '/' | '%' | '=' | '<' | '>' | '&' | '|' | '^' | '~' => {
-                self.peek();
-                Some(self.get_prev().to_string())
-            }
-            'o' => {
-                if self.nth_char(1)? == 'r' {
-                    self.peek_inc(2);
-                    Some("or".to_string())
-                } else {
-                    None
+    /// Eats an identifier or keyword, following `[a-zA-Z_\x80-\xff][a-zA-Z0-9_\x80-\xff]*`.
+    fn eat_word(&mut self) -> Option<String> {
+        if !self.rest().starts_with(is_word_start) {
+            return None;
+        }
+        let len = self
+            .rest()
+            .find(|c: char| !is_word_char(c))
+            .unwrap_or(self.rest().len());
+        let word = self.rest()[..len].to_string();
+        self.peek_bytes(len);
+        Some(word)
+    }
+
+    /// Eats an integer or float literal, the raw text is kept as the token value.
+    ///
+    /// Integers that do not fit in an `i64` become floats, as they do in PHP.
+    fn eat_number(&mut self) -> Result<Option<Numeric>, Error> {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let leading_dot = bytes[0] == b'.' && bytes.get(1).is_some_and(u8::is_ascii_digit);
+        if !bytes[0].is_ascii_digit() && !leading_dot {
+            return Ok(None);
+        }
+
+        let radix = match rest.get(..2).map(str::to_ascii_lowercase).as_deref() {
+            Some("0x") => 16,
+            Some("0o") => 8,
+            Some("0b") => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            let len = digits_len(&rest[2..], radix);
+            if len > 0 {
+                let digits = strip_separators(&rest[2..2 + len]);
+                self.peek_bytes(2 + len);
+                if let Some(long) = self.eat_long_int(&digits, radix)? {
+                    return Ok(Some(long));
                 }
+                return Ok(Some(int_or_float(&digits, radix)));
             }
-            'a' => {
-                if self.nth_char(1)? == 'n' && self.nth_char(2)? == 'd' {
-                    self.peek_inc(3);
-                    Some("and".to_string())
-                } else {
-                    None
+        }
+
+        let mut len = digits_len(rest, 10);
+        let mut is_float = false;
+        if rest[len..].starts_with('.') {
+            is_float = true;
+            len += 1 + digits_len(&rest[len + 1..], 10);
+        }
+        let exponent = &rest[len..];
+        if exponent.starts_with(['e', 'E']) {
+            let sign = exponent[1..].starts_with(['+', '-']) as usize;
+            let exp_len = digits_len(&exponent[1 + sign..], 10);
+            if exp_len > 0 {
+                is_float = true;
+                len += 1 + sign + exp_len;
+            }
+        }
+
+        let text = strip_separators(&rest[..len]);
+        self.peek_bytes(len);
+        if is_float {
+            return text.parse().map(|f| Some(Numeric::Float(f))).map_err(|_| {
+                Error::new(ErrorKind::InvalidData, format!("Invalid float `{}`", text))
+            });
+        }
+
+        // a leading zero is the legacy octal prefix.
+        if text.len() > 1 && text.starts_with('0') {
+            if text.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+                if let Some(long) = self.eat_long_int(&text[1..], 8)? {
+                    return Ok(Some(long));
                 }
+                return Ok(Some(int_or_float(&text[1..], 8)));
             }
-            _ => None,
-        })
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid numeric literal `{}`", text),
+            ));
+        }
+        if let Some(long) = self.eat_long_int(&text, 10)? {
+            return Ok(Some(long));
+        }
+        Ok(Some(int_or_float(&text, 10)))
     }
 
-    fn eat_boolean(&mut self) -> Result<Option<String>, Error> {
-        // there is probably a better way to do this.
-        let mut segment = String::new();
-        for i in 0..4 {
-            segment.push(self.nth_char(i)?);
+    /// Eats the `n` suffix of a long integer literal, `123n`, after its digits.
+    fn eat_long_int(&mut self, digits: &str, radix: u32) -> Result<Option<Numeric>, Error> {
+        let rest = self.rest();
+        if !rest.starts_with('n') || rest[1..].starts_with(is_word_char) {
+            return Ok(None);
+        }
+        if !self.experiments.is_enabled(Experiment::LongInt) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                Experiment::LongInt.required("long integer literals"),
+            ));
+        }
+        self.peek();
+        match i128::from_str_radix(digits, radix) {
+            Ok(n) => Ok(Some(Numeric::LInt(n))),
+            Err(_) => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Long integer literal `{}n` is out of range", digits),
+            )),
+        }
+    }
 
-            if segment == "true" || segment == "false" {
-                self.peek_inc(i);
-                return Ok(Some(segment));
-            }
+    /// Eats a cast such as `(int)` or `( integer )`, returning its normalized form, `(int)`.
+    fn eat_cast(&mut self) -> Option<String> {
+        let inner = self.rest().strip_prefix('(')?;
+        let word = inner.trim_start_matches([' ', '\t']);
+        let len = word
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(word.len());
+        let after = word[len..].trim_start_matches([' ', '\t']);
+        if !after.starts_with(')') {
+            return None;
         }
-        return Ok(None);
+
+        let cast = match word[..len].to_ascii_lowercase().as_str() {
+            "int" | "integer" => "(int)",
+            "bool" | "boolean" => "(bool)",
+            "float" | "double" | "real" => "(float)",
+            "string" | "binary" => "(string)",
+            "array" => "(array)",
+            "object" => "(object)",
+            "unset" => "(unset)",
+            _ => return None,
+        };
+        self.peek_bytes(self.rest().len() - after.len() + 1);
+        Some(cast.to_string())
+    }
+
+    fn eat_operator(&mut self) -> Option<&'static str> {
+        let operator = OPERATORS
+            .iter()
+            .find(|operator| self.rest().starts_with(*operator))?;
+        self.peek_bytes(operator.len());
+        Some(operator)
     }
 
+    /// Eats a quoted string, returning the body between the quotes with escape sequences left
+    /// untouched, they are processed by the parser.
     fn eat_string(&mut self) -> Result<Option<(StringType, String)>, Error> {
-        if self.first()? != '"' && self.first()? != '\'' && self.first()? != '`' {
-            return Ok(None);
-        } else {
-            let first = self.peek().unwrap();
-            let variant = match first {
-                '"' => StringType::Double,
-                '\'' => StringType::Single,
-                _ => unreachable!(),
-            };
-            return Ok(Some((variant, self.eat_while(|c| c != first)?)));
+        let quote = self.first()?;
+        let variant = match quote {
+            '"' => StringType::Double,
+            '\'' => StringType::Single,
+            '`' => StringType::Backtick,
+            _ => return Ok(None),
+        };
+
+        let body = &self.rest()[1..];
+        let mut chars = body.char_indices();
+        // the depth of `{$...}` interpolation, which may contain quotes of its own.
+        let mut depth = 0usize;
+        let mut inner_quote = None;
+        while let Some((i, c)) = chars.next() {
+            if c == '\\' {
+                chars.next();
+                continue;
+            }
+            match inner_quote {
+                Some(q) if c == q => inner_quote = None,
+                Some(_) => {}
+                None if depth > 0 && (c == '\'' || c == '"') => inner_quote = Some(c),
+                None if c == '{'
+                    && quote != '\''
+                    && (depth > 0 || body[i + 1..].starts_with('$')) =>
+                {
+                    depth += 1
+                }
+                None if c == '}' && depth > 0 => depth -= 1,
+                None if c == quote && depth == 0 => {
+                    let body = body[..i].to_string();
+                    self.peek_bytes(i + 2);
+                    return Ok(Some((variant, body)));
+                }
+                None => {}
+            }
         }
+
+        Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("Unterminated string, missing closing `{}`", quote),
+        ))
     }
 
     fn eat_value_reserved(&mut self) -> Result<Option<(TokenType, String)>, Error> {
         Ok(match self.first()? {
             ':' => {
-                if self.second()? == ':' {
+                if self.rest().starts_with("::") {
                     self.peek_inc(1);
                     Some((
                         TokenType::Accessor(AccessType::StaticMember),
@@ -250,6 +540,10 @@ impl Cursor<'_> {
                     Some((TokenType::Colon, ":".to_string()))
                 }
             }
+            '#' if self.rest().starts_with("#[") => {
+                self.peek_inc(1);
+                Some((TokenType::AttributeStart, "#[".to_string()))
+            }
             _ => None,
         })
     }
@@ -273,22 +567,146 @@ impl Cursor<'_> {
     }
 }
 
+/// Operators, longest first so that the longest match wins.
+const OPERATORS: [&str; 47] = [
+    "<<=", ">>=", "**=", "...", "<=>", "===", "!==", "??=", "?->", "**", "++", "--", "->", "=>",
+    "==", "!=", "<>", "<=", ">=", "&&", "||", "??", "+=", "-=", "*=", "/=", ".=", "%=", "&=", "|=",
+    "^=", "<<", ">>", "+", "-", "*", "/", "%", "=", "<", ">", "&", "|", "^", "~", "!", "@",
+];
+
+pub(crate) fn is_word_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic() || !c.is_ascii()
+}
+
+pub(crate) fn is_word_char(c: char) -> bool {
+    is_word_start(c) || c.is_ascii_digit()
+}
+
+/// The length of the digits in `radix` at the start of `input`, including `_` separators.
+fn digits_len(input: &str, radix: u32) -> usize {
+    input
+        .find(|c: char| !(c.is_digit(radix) || c == '_'))
+        .unwrap_or(input.len())
+}
+
+fn strip_separators(digits: &str) -> String {
+    digits.chars().filter(|&c| c != '_').collect()
+}
+
+fn int_or_float(digits: &str, radix: u32) -> Numeric {
+    match i64::from_str_radix(digits, radix) {
+        Ok(n) => Numeric::Int(n),
+        Err(_) => Numeric::Float(
+            digits
+                .chars()
+                .filter_map(|c| c.to_digit(radix))
+                .fold(0.0, |acc, d| acc * radix as f64 + d as f64),
+        ),
+    }
+}
+
+/// The open tag at the start of `input` and its length, `<?php` must be followed by whitespace
+/// or the end of the input.
+fn open_tag(input: &str) -> Option<(TokenType, usize)> {
+    if input.starts_with("<?=") {
+        return Some((TokenType::EchoTag, 3));
+    }
+    if !input.get(..5)?.eq_ignore_ascii_case("<?php") {
+        return None;
+    }
+    let after = &input[5..];
+    match after.chars().next() {
+        None => Some((TokenType::OpenTag, 5)),
+        Some(c) if is_line_ending(c) => Some((TokenType::OpenTag, 5 + line_ending_len(after)?)),
+        Some(' ' | '\t') => Some((TokenType::OpenTag, 6)),
+        Some(_) => None,
+    }
+}
+
 pub struct Lexer<'a> {
     cursor: Cursor<'a>,
+    source: RawSource<'a>,
+    /// Whether the end of the script was reached, reading past it is an error.
+    finished: bool,
 }
 
 impl<'a> Lexer<'a> {
+    /// Creates a lexer over a whole script. As in a PHP file, the script starts with inline
+    /// HTML up to the first `<?php` or `<?=`, and the text after a `?>` is inline HTML again.
     pub fn new(script: &'a str) -> Self {
         Self {
-            cursor: Cursor::new(script),
+            cursor: Cursor::new(script).in_html(true),
+            source: RawSource::utf8(script),
+            finished: false,
+        }
+    }
+
+    /// Creates a lexer over PHP code that is not preceded by `<?php`, such as a snippet or the
+    /// `{$expr}` of an interpolated string. It is lexed like the rest of a script after that.
+    pub fn snippet(code: &'a str) -> Self {
+        Self {
+            cursor: Cursor::new(code),
+            source: RawSource::utf8(code),
+            finished: false,
         }
     }
-    /// Consumes the next possible token(s).
+
+    /// Lexes the syntax of the enabled `experiments` as well, which is an error otherwise.
+    pub fn with_experiments(mut self, experiments: Experiments) -> Self {
+        self.cursor.experiments = experiments;
+        self
+    }
+
+    /// Creates a lexer over the raw bytes of a whole script, as PHP source is not required to be
+    /// UTF-8. The script starts with inline HTML, as with `new`.
+    ///
+    /// A leading UTF-8 byte order mark is stripped, see `has_bom`, and a leading
+    /// `declare(encoding='...')` is honoured. Bytes that are not valid UTF-8 are decoded as
+    /// Latin-1, so lexing never fails because of them, and `raw` returns them unchanged.
+    ///
+    /// Token spans are offsets into `text`, the decoded script.
+    pub fn from_bytes(script: &'a [u8]) -> Result<Self, Error> {
+        let (text, source) = RawSource::decode(script)?;
+        Ok(Self {
+            cursor: Cursor::new(text).in_html(true),
+            source,
+            finished: false,
+        })
+    }
+
+    /// The decoded script that token spans refer to.
+    pub fn text(&self) -> &str {
+        self.cursor.input()
+    }
+
+    /// The encoding the script was decoded from.
+    pub fn encoding(&self) -> Encoding {
+        self.source.encoding
+    }
+
+    /// Whether a UTF-8 byte order mark was stripped from the start of the script.
+    pub fn has_bom(&self) -> bool {
+        self.source.bom
+    }
+
+    /// The original bytes of a token, exactly as they appear in the script.
+    pub fn raw(&self, token: &Token) -> &'a [u8] {
+        self.source.raw(token.range())
+    }
+    /// The byte offset into `text` the next token starts at.
+    pub fn offset(&self) -> usize {
+        self.cursor.get_pos()
+    }
+
+    /// Consumes the next possible token(s), giving `None` at the end of the script and an
+    /// `UnexpectedEof` error when called again after that.
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Option<Token>, Error> {
-        if let Some(v) = self.cursor.eat()? {
-            return Ok(Some(v));
-        } else {
-            return Ok(None);
+        if self.finished {
+            return Err(Error::from(ErrorKind::UnexpectedEof));
         }
+        let token = self.cursor.eat()?;
+        self.finished = token.is_none();
+        Ok(token)
     }
 }
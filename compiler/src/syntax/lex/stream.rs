@@ -0,0 +1,213 @@
+use std::io::{Error, ErrorKind, Read};
+
+use crate::syntax::experiments::Experiments;
+
+use super::{
+    cursor::Cursor,
+    encoding::{declared_encoding, Encoding, UTF8_BOM},
+    token::Token,
+};
+
+/// The amount of bytes requested from the reader at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// How far past the end of a token the lexer may look before deciding what the token is.
+/// Keywords need the most, at most `MAX_KEYWORD_LENGTH` chars of up to four bytes each.
+const LOOKAHEAD: usize = 64;
+
+/// A lexer that pulls a script from any `io::Read`, rather than requiring all of it in memory.
+///
+/// Text is decoded into a refillable buffer, and the part of the buffer that has been lexed is
+/// discarded as tokens are yielded. Memory is bounded by the chunk size and the longest token in
+/// the script. Tokens, spans included, are identical to the ones `Lexer::from_bytes` produces
+/// for the same bytes.
+pub struct StreamLexer<R: Read> {
+    reader: R,
+    encoding: Encoding,
+    bom: bool,
+    /// Decoded text that has not been lexed yet starts at `start`.
+    buffer: String,
+    start: usize,
+    /// Bytes read that do not form a complete UTF-8 sequence yet.
+    pending: Vec<u8>,
+    /// The offset of `buffer[start]` within the whole decoded script.
+    offset: usize,
+    line: usize,
+    /// Whether `buffer[start]` is outside of PHP code.
+    html: bool,
+    exhausted: bool,
+    /// Whether the end of the script was reached, reading past it is an error.
+    finished: bool,
+    experiments: Experiments,
+}
+
+impl<R: Read> StreamLexer<R> {
+    /// Creates a streaming lexer, reading enough of the script to find a byte order mark
+    /// and a `declare(encoding='...')` directive.
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let mut lexer = Self {
+            reader,
+            encoding: Encoding::Utf8,
+            bom: false,
+            buffer: String::new(),
+            start: 0,
+            pending: Vec::new(),
+            offset: 0,
+            line: 0,
+            html: true,
+            exhausted: false,
+            finished: false,
+            experiments: Experiments::default(),
+        };
+
+        lexer.read_chunk()?;
+        lexer.bom = lexer.pending.starts_with(UTF8_BOM);
+        if lexer.bom {
+            lexer.pending.drain(..UTF8_BOM.len());
+        }
+        if let Some(label) = declared_encoding(&lexer.pending) {
+            lexer.encoding = Encoding::from_label(&label).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unsupported encoding [{}] in declare(encoding=...)", label),
+                )
+            })?;
+        }
+        lexer.decode_pending();
+
+        Ok(lexer)
+    }
+
+    /// Lexes the syntax of the enabled `experiments` as well, see `Lexer::with_experiments`.
+    pub fn with_experiments(mut self, experiments: Experiments) -> Self {
+        self.experiments = experiments;
+        self
+    }
+
+    /// The encoding the script is decoded from.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Whether a UTF-8 byte order mark was stripped from the start of the script.
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// The amount of decoded text currently held in memory.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Consumes the next possible token(s), reading more of the script when needed. Like
+    /// `Lexer::next`, it gives `None` at the end of the script and an error after that.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<Token>, Error> {
+        if self.finished {
+            return Err(Error::from(ErrorKind::UnexpectedEof));
+        }
+        loop {
+            let window = &self.buffer[self.start..];
+            let mut cursor = Cursor::new(window)
+                .at_line(self.line)
+                .in_html(self.html)
+                .with_experiments(self.experiments);
+            let result = cursor.eat();
+
+            // a token that ends close to the end of the buffer may continue in the next chunk,
+            // and so may an unterminated string or an error near the end of the buffer, so lex
+            // it again once more text is available. Any other error is final.
+            let settled = match &result {
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => false,
+                _ => cursor.get_pos() + LOOKAHEAD < window.len(),
+            };
+            if !settled && !self.exhausted {
+                self.fill()?;
+                continue;
+            }
+
+            let consumed = cursor.get_pos();
+            let line = cursor.get_line();
+            let html = cursor.is_html();
+            let token = result?.map(|mut token| {
+                token.1.start += self.offset;
+                token.1.end += self.offset;
+                token
+            });
+
+            self.start += consumed;
+            self.offset += consumed;
+            self.line = line;
+            self.html = html;
+            self.finished = token.is_none();
+            return Ok(token);
+        }
+    }
+
+    /// Reads and decodes another chunk, dropping the text that has already been lexed.
+    fn fill(&mut self) -> Result<(), Error> {
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+        self.read_chunk()?;
+        self.decode_pending();
+        Ok(())
+    }
+
+    fn read_chunk(&mut self) -> Result<(), Error> {
+        let len = self.pending.len();
+        self.pending.resize(len + CHUNK_SIZE, 0);
+        let mut filled = len;
+        while filled < self.pending.len() {
+            match self.reader.read(&mut self.pending[filled..]) {
+                Ok(0) => {
+                    self.exhausted = true;
+                    break;
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.pending.truncate(filled);
+        Ok(())
+    }
+
+    /// Moves every complete sequence of `pending` into the buffer, the same way
+    /// `Lexer::from_bytes` decodes a whole script.
+    fn decode_pending(&mut self) {
+        if self.encoding != Encoding::Utf8 {
+            let encoding = self.encoding;
+            self.buffer
+                .extend(self.pending.drain(..).map(|b| encoding.decode_byte(b)));
+            return;
+        }
+
+        let mut decoded = 0;
+        while decoded < self.pending.len() {
+            match std::str::from_utf8(&self.pending[decoded..]) {
+                Ok(valid) => {
+                    self.buffer.push_str(valid);
+                    decoded = self.pending.len();
+                }
+                Err(e) => {
+                    let valid = decoded + e.valid_up_to();
+                    // `valid_up_to` bytes are known to be UTF-8.
+                    self.buffer
+                        .push_str(std::str::from_utf8(&self.pending[decoded..valid]).unwrap());
+
+                    // a sequence cut off by the end of the chunk is finished by the next one.
+                    if e.error_len().is_none() && !self.exhausted {
+                        decoded = valid;
+                        break;
+                    }
+                    self.buffer
+                        .push(Encoding::Latin1.decode_byte(self.pending[valid]));
+                    decoded = valid + 1;
+                }
+            }
+        }
+        self.pending.drain(..decoded);
+    }
+}
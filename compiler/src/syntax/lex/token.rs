@@ -1,12 +1,16 @@
 use std::ops::Range;
 
-use crate::syntax::ast::{
-    keyword::Keyword,
-    reserved::{ReservedCall, ReservedIdent},
+use crate::syntax::{
+    ast::{
+        keyword::Keyword,
+        reserved::{ReservedCall, ReservedIdent},
+    },
+    source::Span,
 };
 
-#[derive(Debug, Clone)]
-pub struct Token(pub TokenType, pub Range<usize>, pub Option<String>);
+/// A single token, the span is the byte range of the token within the lexed source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token(pub TokenType, pub Span, pub Option<String>);
 
 impl Token {
     pub fn kind(&self) -> TokenType {
@@ -18,41 +22,56 @@ impl Token {
     }
 
     pub fn range(&self) -> Range<usize> {
-        self.1.clone()
+        self.1.range()
+    }
+
+    pub fn span(&self) -> Span {
+        self.1
     }
 }
 
 /// A simple utility macro to create a token from an expression, for example:
 ///
 /// ```rust no_run
-/// use crate::syntax::lex::token::token;
+/// use std::io::Error;
+/// use compiler::token;
+/// use compiler::syntax::lex::token::{Token, TokenType, LF};
 ///
-/// let lf_tk: Token = token!(1, 2, TokenType::LF, Some(LF::CRLF));
+/// let lf_tk: Result<Option<Token>, Error> = token!(1, 2, TokenType::LF(LF::CRLF));
 /// ```
 #[macro_export]
 macro_rules! token {
     ($start: expr, $end: expr, $t: expr, $v: expr) => {
-        Ok(Some(Token($t, $start..$end, $v)))
+        Ok(Some(Token(
+            $t,
+            $crate::syntax::source::Span::new($start, $end),
+            $v,
+        )))
     };
     ($start: expr, $end: expr, $t: expr) => {
-        Ok(Some(Token($t, $start..$end, None)))
+        Ok(Some(Token(
+            $t,
+            $crate::syntax::source::Span::new($start, $end),
+            None,
+        )))
     };
 }
 
 /// A enum representing a valid php numerical.
 /// ! WARNING, THIS COMPILER DIFFERS IN NUMERICAL IMPLMENTATION FROM PHP
 /// ! IN PHP INTS ARE TREATED AS FLOATS, THIS COMPILER RESPECTS THE DATA TYPE
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Numeric {
     Float(f64),
-    Int(i32),
+    Int(i64),
 
     /// LInt, similar to `BigInt` in javascript, allows precision on machines that allow it.
-    /// This numeric does NOT exist in regular php.
+    /// This numeric does NOT exist in regular php, it is written `123n` and needs the
+    /// `long-int` experiment.
     LInt(i128),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccessType {
     /// Another term used to refer to this access is "Scoped Resolution".
     /// However for simplicity, the token is named "StaticMember". This is however,
@@ -79,15 +98,21 @@ pub enum AccessType {
     /// //       ~~ Referenced Member
     /// ```
     ReferenceMember,
+    /// The nullsafe `?->`, which short circuits to `null` when the object is `null`.
+    NullsafeMember,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LF {
+    /// `\r\n`, used on windows.
     CRLF,
+    /// `\n`, used on unix.
     LF,
+    /// A lone `\r`, used by classic Mac OS. PHP still treats it as a line break.
+    CR,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StringType {
     /// A single qoute string. Allows use for multi-lined strings.
     Single,
@@ -123,6 +148,9 @@ pub enum StringType {
     /// END;
     /// ```
     NowDoc,
+    /// A backtick string, which is executed as a shell command and interpolated like a double
+    /// quoted string.
+    Backtick,
 }
 
 /// Please note that this lexical structure deviates from the original PHP implmentation
@@ -134,7 +162,7 @@ pub enum StringType {
 /// - default functions, like echo, and print are not
 ///   represented by their own token, but rather a value wrapped within a
 ///   `ReservedCall`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     /// End of File
     EOF,
@@ -151,6 +179,7 @@ pub enum TokenType {
 
     /// A function that is implemented within the language itself, such as:
     /// - `print`
+    ///
     /// or
     /// - `eval`
     ReservedCall(ReservedCall),
@@ -175,7 +204,9 @@ pub enum TokenType {
 
     /// An operator is a char or word that represents an operation.
     ///
-    /// ! THIS MAY BE CONFUSED WITH KEYWORDS "and" "or" AND "not" WHICH ARE
+    /// Casts are lexed as operators too, with a normalized value such as `(int)`.
+    ///
+    /// ! THIS MAY BE CONFUSED WITH KEYWORDS "and" "or" AND "xor" WHICH ARE
     /// ! CONSIDERED KEYWORDS DURING TOKENIZATION
     Operator,
 
@@ -197,10 +228,25 @@ pub enum TokenType {
     /// The end of statement token is referred to as the semi-colon: `;`.
     EOS,
 
-    /// The line feed, or "line break" is used to represent a char that signals the end of the file,
-    /// on windows this is `\r\n` (CRLF) while unix uses `\n` (LF)
+    /// `<?php` and the whitespace after it, which starts PHP code.
+    OpenTag,
+
+    /// `<?=`, which starts PHP code that echoes an expression, `<?= $a ?>`.
+    EchoTag,
+
+    /// `?>` and the line ending after it, which ends PHP code and also ends a statement.
+    CloseTag,
+
+    /// The text outside of PHP code, which is echoed as it is.
+    InlineHtml,
+
+    /// The line feed, or "line break" is used to represent a char that signals the end of the line,
+    /// on windows this is `\r\n` (CRLF) while unix uses `\n` (LF). A lone `\r` (CR) is also accepted.
     LF(LF),
 
+    /// The `#[` that opens a group of attributes, closed by a `RightBracket`.
+    AttributeStart,
+
     /// The `[` character that signals a return type, an array, etc.
     LeftBracket,
 
@@ -0,0 +1,168 @@
+//! Rewrites of syntax into simpler syntax with the same meaning, so that later stages only
+//! have to handle the simpler forms.
+
+use super::{
+    ast::{
+        expr::{Arg, BinaryOp, CallArgs, ClassRef, Expr, ExprKind, Literal, MatchArm},
+        name::{Name, NameKind, ResolvedName},
+    },
+    source::Span,
+};
+
+/// Lowers `match (subject) { ... }` into nested ternaries over `===`.
+///
+/// The subject is evaluated once into a temporary, named after the position of the match so
+/// nested matches do not clash, and compared against the conditions of each arm in order. The
+/// default arm is taken when no condition is identical, wherever it is written, and without
+/// one an `UnhandledMatchError` is thrown. Matches nested in the arms are left as they are.
+pub fn lower_match(subject: Expr, arms: Vec<MatchArm>, span: Span) -> Expr {
+    let temp = || Expr::new(ExprKind::Variable(format!("match@{}", span.start)), span);
+    let assign = Expr::new(
+        ExprKind::Assign {
+            target: Box::new(temp()),
+            value: Box::new(subject),
+        },
+        span,
+    );
+
+    let mut default = None;
+    let mut arms_with_conditions = Vec::new();
+    for arm in arms {
+        match arm.conditions {
+            Some(conditions) => arms_with_conditions.push((conditions, arm.body)),
+            None => default = Some(arm.body),
+        }
+    }
+    let mut lowered = default.unwrap_or_else(|| unhandled_match(temp(), span));
+
+    // built from the last arm outwards, so the first comparison is the one that stores the
+    // subject in the temporary.
+    let mut subject = Some(assign);
+    for (i, (conditions, body)) in arms_with_conditions.into_iter().enumerate().rev() {
+        let cond = conditions
+            .into_iter()
+            .enumerate()
+            .map(|(j, condition)| {
+                let left = match (i, j) {
+                    (0, 0) => subject.take().unwrap(),
+                    _ => temp(),
+                };
+                binary(BinaryOp::Identical, left, condition, span)
+            })
+            .reduce(|left, right| binary(BinaryOp::BooleanOr, left, right, span))
+            .unwrap();
+        lowered = Expr::new(
+            ExprKind::Ternary {
+                cond: Box::new(cond),
+                then: Some(Box::new(body)),
+                otherwise: Box::new(lowered),
+            },
+            span,
+        );
+    }
+
+    // a match with only a default arm still evaluates its subject.
+    if let Some(assign) = subject {
+        let false_ = Expr::new(ExprKind::Literal(Literal::Bool(false)), span);
+        let null = Expr::new(ExprKind::Literal(Literal::Null), span);
+        lowered = Expr::new(
+            ExprKind::Ternary {
+                cond: Box::new(binary(BinaryOp::BooleanAnd, assign, false_, span)),
+                then: Some(Box::new(null)),
+                otherwise: Box::new(lowered),
+            },
+            span,
+        );
+    }
+    lowered
+}
+
+/// `throw new \UnhandledMatchError(...)`, with the message PHP uses, `Unhandled match case 5`
+/// for scalars and `Unhandled match case of type Foo` for arrays and objects.
+fn unhandled_match(value: Expr, span: Span) -> Expr {
+    let string = |text: &str| Expr::new(ExprKind::Literal(Literal::String(text.into())), span);
+    let describe = Expr::new(
+        ExprKind::Ternary {
+            cond: Box::new(binary(
+                BinaryOp::BooleanOr,
+                call("is_array", vec![value.clone()], span),
+                call("is_object", vec![value.clone()], span),
+                span,
+            )),
+            then: Some(Box::new(binary(
+                BinaryOp::Concat,
+                string("of type "),
+                call("get_debug_type", vec![value.clone()], span),
+                span,
+            ))),
+            otherwise: Box::new(call(
+                "var_export",
+                vec![
+                    value,
+                    Expr::new(ExprKind::Literal(Literal::Bool(true)), span),
+                ],
+                span,
+            )),
+        },
+        span,
+    );
+    let message = binary(
+        BinaryOp::Concat,
+        string("Unhandled match case "),
+        describe,
+        span,
+    );
+    let error = Expr::new(
+        ExprKind::New {
+            class: ClassRef::Name(global_name("UnhandledMatchError", span)),
+            args: Some(vec![Arg {
+                name: None,
+                value: message,
+                unpack: false,
+                span,
+            }]),
+        },
+        span,
+    );
+    Expr::new(ExprKind::Throw(Box::new(error)), span)
+}
+
+fn binary(op: BinaryOp, left: Expr, right: Expr, span: Span) -> Expr {
+    Expr::new(
+        ExprKind::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        },
+        span,
+    )
+}
+
+/// A call to a global function, already resolved.
+fn call(function: &str, args: Vec<Expr>, span: Span) -> Expr {
+    let args = args
+        .into_iter()
+        .map(|value| Arg {
+            name: None,
+            value,
+            unpack: false,
+            span,
+        })
+        .collect();
+    Expr::new(
+        ExprKind::Call {
+            callee: Box::new(Expr::new(
+                ExprKind::ConstFetch(global_name(function, span)),
+                span,
+            )),
+            args: CallArgs::List(args),
+        },
+        span,
+    )
+}
+
+fn global_name(name: &str, span: Span) -> Name {
+    let mut name = Name::new(vec![name.to_string()], NameKind::FullyQualified, span);
+    name.resolved = Some(Box::new(ResolvedName::FullyQualified(name.parts.clone())));
+    name
+}
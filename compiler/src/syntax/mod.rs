@@ -1,2 +1,11 @@
 pub mod ast;
+pub mod cst;
+pub mod diagnostic;
+pub mod experiments;
+pub mod json;
 pub mod lex;
+pub mod lower;
+pub mod parse;
+pub mod print;
+pub mod resolve;
+pub mod source;
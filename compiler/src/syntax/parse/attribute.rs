@@ -0,0 +1,170 @@
+use crate::syntax::{
+    ast::{
+        attribute::{Attribute, AttributeGroup},
+        expr::{CallArgs, ClassRef, Expr, ExprKind, MemberName},
+    },
+    diagnostic::Diagnostic,
+    lex::token::TokenType,
+};
+
+use super::{PResult, Parser};
+
+impl Parser<'_> {
+    /// Any number of attribute groups, `#[A, B(1)] #[C]`, before a declaration.
+    pub(crate) fn parse_attributes(&mut self) -> PResult<Vec<AttributeGroup>> {
+        let mut groups = Vec::new();
+        while self.at(&TokenType::AttributeStart) {
+            groups.push(self.parse_attribute_group()?);
+        }
+        Ok(groups)
+    }
+
+    /// `#[A, B(1)]`, with a trailing comma allowed.
+    fn parse_attribute_group(&mut self) -> PResult<AttributeGroup> {
+        let open = self.bump();
+        let mut attributes = Vec::new();
+        loop {
+            attributes.push(self.parse_attribute()?);
+            if self.eat(&TokenType::Comma).is_none() || self.at(&TokenType::RightBracket) {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightBracket, "`]`")?;
+        Ok(AttributeGroup {
+            attributes,
+            span: self.span_from(open.1),
+        })
+    }
+
+    fn parse_attribute(&mut self) -> PResult<Attribute> {
+        if !self.at_name() {
+            return Err(self.unexpected("an attribute name"));
+        }
+        let name = self.parse_name()?;
+        let args = if self.at(&TokenType::LeftParenthesis) {
+            match self.parse_call_args()? {
+                CallArgs::List(args) => args,
+                CallArgs::FirstClassCallable(span) => {
+                    return Err(Diagnostic::error(
+                        "cannot create a closure from an attribute",
+                        span,
+                    ))
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        for arg in &args {
+            if arg.unpack {
+                return Err(Diagnostic::error(
+                    "cannot use unpacking in attribute argument list",
+                    arg.span,
+                ));
+            }
+            check_constant_expr(&arg.value)?;
+        }
+        Ok(Attribute {
+            span: self.span_from(name.span),
+            name,
+            args,
+        })
+    }
+}
+
+/// Rejects expressions that can not be evaluated when the script is compiled, such as
+/// variables and calls, in the places PHP requires a constant expression.
+pub(crate) fn check_constant_expr(expr: &Expr) -> PResult<()> {
+    let invalid = || {
+        Err(Diagnostic::error(
+            "constant expression contains invalid operations",
+            expr.span,
+        ))
+    };
+    match &expr.kind {
+        ExprKind::Literal(_) | ExprKind::ConstFetch(_) | ExprKind::ReservedConst(_) => Ok(()),
+        ExprKind::Array { items, .. } => {
+            for item in items {
+                if item.by_ref {
+                    return invalid();
+                }
+                if let Some(key) = &item.key {
+                    check_constant_expr(key)?;
+                }
+                check_constant_expr(&item.value)?;
+            }
+            Ok(())
+        }
+        ExprKind::ClassConstFetch {
+            class: ClassRef::Name(class),
+            constant,
+        } => {
+            if class.last().eq_ignore_ascii_case("static") {
+                return Err(Diagnostic::error(
+                    "\"static::\" is not allowed in compile-time constants",
+                    expr.span,
+                ));
+            }
+            match constant {
+                MemberName::Ident(_) => Ok(()),
+                MemberName::Expr(constant) => check_constant_expr(constant),
+            }
+        }
+        ExprKind::ArrayDim {
+            array,
+            index: Some(index),
+        } => {
+            check_constant_expr(array)?;
+            check_constant_expr(index)
+        }
+        // properties of enum cases, `Suit::Hearts->value`.
+        ExprKind::PropertyFetch {
+            object,
+            property: MemberName::Ident(_),
+            ..
+        } => check_constant_expr(object),
+        ExprKind::Unary { expr, .. } => check_constant_expr(expr),
+        ExprKind::Binary { left, right, .. } => {
+            check_constant_expr(left)?;
+            check_constant_expr(right)
+        }
+        ExprKind::Ternary {
+            cond,
+            then,
+            otherwise,
+        } => {
+            check_constant_expr(cond)?;
+            if let Some(then) = then {
+                check_constant_expr(then)?;
+            }
+            check_constant_expr(otherwise)
+        }
+        ExprKind::New { class, args } => {
+            match class {
+                ClassRef::Name(_) => {}
+                ClassRef::Expr(_) => {
+                    return Err(Diagnostic::error(
+                        "cannot use dynamic class name in constant expression",
+                        expr.span,
+                    ))
+                }
+                ClassRef::Anonymous(_) => {
+                    return Err(Diagnostic::error(
+                        "cannot use anonymous class in constant expression",
+                        expr.span,
+                    ))
+                }
+            }
+            for arg in args.iter().flatten() {
+                if arg.unpack {
+                    return Err(Diagnostic::error(
+                        "argument unpacking in constant expressions is not supported",
+                        arg.span,
+                    ));
+                }
+                check_constant_expr(&arg.value)?;
+            }
+            Ok(())
+        }
+        _ => invalid(),
+    }
+}
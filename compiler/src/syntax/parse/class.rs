@@ -0,0 +1,812 @@
+use std::collections::HashSet;
+
+use crate::syntax::{
+    ast::{
+        class::{
+            has_modifier, AdaptationKind, ClassConst, ClassKind, ClassLike, ClassMember, EnumCase,
+            MemberKind, Method, Modifier, Property, PropertyItem, TraitAdaptation, TraitUse,
+        },
+        keyword::Keyword,
+        name::{Ident, Name},
+        stmt::ConstItem,
+        types::{BuiltinType, Type, TypeHint},
+    },
+    diagnostic::Diagnostic,
+    lex::token::{AccessType, TokenType},
+    source::Span,
+};
+
+use super::{PResult, Parser};
+
+/// Names that can not be used for a class, interface or trait, as they are reserved for types.
+const RESERVED_CLASS_NAMES: [&str; 15] = [
+    "self", "parent", "static", "int", "float", "bool", "string", "true", "false", "null", "void",
+    "never", "iterable", "object", "mixed",
+];
+
+/// Magic methods an enum can not declare, as enum cases can not be constructed, cloned,
+/// serialized or given state.
+const ENUM_FORBIDDEN_METHODS: [&str; 14] = [
+    "__construct",
+    "__destruct",
+    "__clone",
+    "__get",
+    "__set",
+    "__unset",
+    "__isset",
+    "__tostring",
+    "__debuginfo",
+    "__serialize",
+    "__unserialize",
+    "__sleep",
+    "__wakeup",
+    "__set_state",
+];
+
+impl Parser<'_> {
+    /// A class, interface, trait or enum declaration, including any `abstract`, `final` and
+    /// `readonly` modifiers before it.
+    pub(crate) fn parse_class_like(&mut self) -> PResult<ClassLike> {
+        let start = self.current_span();
+        let modifiers = self.parse_modifiers()?;
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::Keyword(Keyword::Class)) => ClassKind::Class,
+            Some(TokenType::Keyword(Keyword::Interface)) => ClassKind::Interface,
+            Some(TokenType::Keyword(Keyword::Trait)) => ClassKind::Trait,
+            Some(TokenType::Keyword(Keyword::Enum)) => ClassKind::Enum,
+            _ => return Err(self.unexpected("`class`")),
+        };
+        self.bump();
+
+        for modifier in &modifiers {
+            let allowed = kind == ClassKind::Class
+                && matches!(
+                    modifier.keyword,
+                    Keyword::Abstract | Keyword::Final | Keyword::ReadOnly
+                );
+            if !allowed {
+                return Err(Diagnostic::error(
+                    format!(
+                        "cannot use the {} modifier on {}",
+                        modifier.keyword,
+                        kind.with_article()
+                    ),
+                    modifier.span,
+                ));
+            }
+        }
+        if has_modifier(&modifiers, Keyword::Abstract) && has_modifier(&modifiers, Keyword::Final) {
+            return Err(Diagnostic::error(
+                "cannot use the final modifier on an abstract class",
+                self.span_from(start),
+            ));
+        }
+
+        let name = self.parse_class_name()?;
+        self.parse_class_rest(kind, Some(name), modifiers, start)
+    }
+
+    fn parse_class_name(&mut self) -> PResult<Ident> {
+        if !self.at(&TokenType::Identifier) {
+            return Err(self.unexpected("a class name"));
+        }
+        let token = self.bump();
+        let name = self.text_of(token.1);
+        if RESERVED_CLASS_NAMES.contains(&name.to_ascii_lowercase().as_str()) {
+            return Err(Diagnostic::error(
+                format!("cannot use '{}' as a class name as it is reserved", name),
+                token.1,
+            ));
+        }
+        Ok(Ident::new(name, token.1))
+    }
+
+    /// Everything after the name of a class-like, the backing type of an enum, `extends`,
+    /// `implements` and the body. Anonymous classes continue here after their constructor
+    /// arguments.
+    pub(crate) fn parse_class_rest(
+        &mut self,
+        kind: ClassKind,
+        name: Option<Ident>,
+        modifiers: Vec<Modifier>,
+        start: Span,
+    ) -> PResult<ClassLike> {
+        let backing_type = if kind == ClassKind::Enum && self.eat(&TokenType::Colon).is_some() {
+            let ty = self.parse_type()?;
+            if !matches!(
+                ty.to_type(),
+                Type::Builtin(BuiltinType::Int | BuiltinType::String)
+            ) {
+                return Err(Diagnostic::error(
+                    format!(
+                        "enum backing type must be int or string, {} given",
+                        ty.to_type()
+                    ),
+                    ty.span,
+                ));
+            }
+            Some(ty)
+        } else {
+            None
+        };
+
+        let mut extends = Vec::new();
+        if self.at_keyword(Keyword::Extends) {
+            let token = self.bump();
+            match kind {
+                ClassKind::Class => extends.push(self.parse_name()?),
+                ClassKind::Interface => extends = self.parse_name_list()?,
+                _ => {
+                    return Err(Diagnostic::error(
+                        format!("{} can not extend another", kind.with_article()),
+                        token.1,
+                    ))
+                }
+            }
+        }
+
+        let mut implements = Vec::new();
+        if self.at_keyword(Keyword::Implements) {
+            let token = self.bump();
+            if !matches!(kind, ClassKind::Class | ClassKind::Enum) {
+                return Err(Diagnostic::error(
+                    format!("{} can not implement interfaces", kind.with_article()),
+                    token.1,
+                ));
+            }
+            implements = self.parse_name_list()?;
+        }
+
+        let class_name = name.as_ref().map_or("class@anonymous", Ident::as_str);
+        let context = ClassContext {
+            kind,
+            name: class_name,
+            is_abstract: has_modifier(&modifiers, Keyword::Abstract),
+            is_backed: backing_type.is_some(),
+        };
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+        let mut members = Vec::new();
+        let mut declared = Declared::default();
+        while self.eat(&TokenType::RightBrace).is_none() {
+            if self.is_eof() {
+                self.unclosed();
+                break;
+            }
+            let pos = self.pos;
+            let member_start = self.current_span();
+            let member = self.parse_member(&context).unwrap_or_else(|error| {
+                self.recover(error, pos);
+                ClassMember {
+                    kind: MemberKind::Error,
+                    span: self.span_from(member_start),
+                }
+            });
+            if let Err(error) = declared.add(&context, &member) {
+                self.report(error);
+            }
+            members.push(member);
+        }
+
+        Ok(ClassLike {
+            kind,
+            name,
+            modifiers,
+            extends,
+            implements,
+            backing_type,
+            members,
+            attributes: Vec::new(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// `A, B\C`
+    pub(crate) fn parse_name_list(&mut self) -> PResult<Vec<Name>> {
+        let mut names = vec![self.parse_name()?];
+        while self.eat(&TokenType::Comma).is_some() {
+            names.push(self.parse_name()?);
+        }
+        Ok(names)
+    }
+
+    /// Modifier keywords in any order, rejecting duplicates and conflicting visibilities.
+    /// Which modifiers are allowed is left to the caller.
+    pub(crate) fn parse_modifiers(&mut self) -> PResult<Vec<Modifier>> {
+        let mut modifiers: Vec<Modifier> = Vec::new();
+        while let Some(TokenType::Keyword(
+            keyword @ (Keyword::Public
+            | Keyword::Protected
+            | Keyword::Private
+            | Keyword::Static
+            | Keyword::Abstract
+            | Keyword::Final
+            | Keyword::ReadOnly
+            | Keyword::Var),
+        )) = self.nth_kind(0)
+        {
+            let modifier = Modifier {
+                keyword: *keyword,
+                span: self.bump().1,
+            };
+            if has_modifier(&modifiers, modifier.keyword) {
+                return Err(Diagnostic::error(
+                    format!("multiple {} modifiers are not allowed", modifier.keyword),
+                    modifier.span,
+                ));
+            }
+            if modifier.is_visibility() && modifiers.iter().any(Modifier::is_visibility) {
+                return Err(Diagnostic::error(
+                    "multiple access type modifiers are not allowed",
+                    modifier.span,
+                ));
+            }
+            modifiers.push(modifier);
+        }
+        Ok(modifiers)
+    }
+
+    fn parse_member(&mut self, class: &ClassContext) -> PResult<ClassMember> {
+        let start = self.current_span();
+        let attributes = self.parse_attributes()?;
+        if self.at_keyword(Keyword::Use) {
+            if !attributes.is_empty() {
+                return Err(self.unexpected("a class member"));
+            }
+            let trait_use = self.parse_trait_use()?;
+            if class.kind == ClassKind::Interface {
+                return Err(Diagnostic::error(
+                    format!("cannot use traits inside of interface {}", class.name),
+                    self.span_from(start),
+                ));
+            }
+            return Ok(ClassMember {
+                kind: MemberKind::TraitUse(trait_use),
+                span: self.span_from(start),
+            });
+        }
+
+        let modifiers = self.parse_modifiers()?;
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::Keyword(Keyword::Case)) => {
+                if let Some(modifier) = modifiers.first() {
+                    return Err(Diagnostic::error(
+                        format!(
+                            "cannot use the {} modifier on an enum case",
+                            modifier.keyword
+                        ),
+                        modifier.span,
+                    ));
+                }
+                let mut case = self.parse_enum_case(class)?;
+                case.attributes = attributes;
+                MemberKind::EnumCase(case)
+            }
+            Some(TokenType::Keyword(Keyword::Const)) => {
+                self.bump();
+                let mut consts = self.parse_class_const(class, modifiers)?;
+                consts.attributes = attributes;
+                MemberKind::Const(consts)
+            }
+            Some(TokenType::Keyword(Keyword::Function)) => {
+                self.bump();
+                let mut method = self.parse_method(class, modifiers, start)?;
+                method.function.attributes = attributes;
+                MemberKind::Method(method)
+            }
+            _ if self.nth_kind(1) == Some(&TokenType::Keyword(Keyword::Function))
+                && self.at_async()? =>
+            {
+                self.bump();
+                self.bump();
+                let mut method = self.parse_method(class, modifiers, start)?;
+                method.function.is_async = true;
+                method.function.attributes = attributes;
+                MemberKind::Method(method)
+            }
+            _ => {
+                let mut property = self.parse_property(class, modifiers)?;
+                property.attributes = attributes;
+                MemberKind::Property(property)
+            }
+        };
+
+        Ok(ClassMember {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// `case Hearts;`, or `case Hearts = 'H';` in a backed enum.
+    fn parse_enum_case(&mut self, class: &ClassContext) -> PResult<EnumCase> {
+        let keyword = self.bump().1;
+        if class.kind != ClassKind::Enum {
+            return Err(Diagnostic::error("case can only be used in enums", keyword));
+        }
+        let name = self.parse_word("a case name")?;
+        let value = if self.eat_op("=").is_some() {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        match (&value, class.is_backed) {
+            (Some(value), false) => {
+                return Err(Diagnostic::error(
+                    format!(
+                        "case {} of non-backed enum {} must not have a value",
+                        name, class.name
+                    ),
+                    value.span,
+                ))
+            }
+            (None, true) => {
+                return Err(Diagnostic::error(
+                    format!(
+                        "case {} of backed enum {} must have a value",
+                        name, class.name
+                    ),
+                    name.span,
+                ))
+            }
+            _ => {}
+        }
+        self.expect_semicolon()?;
+        Ok(EnumCase {
+            name,
+            value,
+            attributes: Vec::new(),
+        })
+    }
+
+    /// `const int A = 1, B = 2;`, after the `const` keyword.
+    fn parse_class_const(
+        &mut self,
+        class: &ClassContext,
+        modifiers: Vec<Modifier>,
+    ) -> PResult<ClassConst> {
+        for modifier in &modifiers {
+            if matches!(
+                modifier.keyword,
+                Keyword::Static | Keyword::Abstract | Keyword::ReadOnly | Keyword::Var
+            ) {
+                return Err(Diagnostic::error(
+                    format!(
+                        "cannot use the {} modifier on a class constant",
+                        modifier.keyword
+                    ),
+                    modifier.span,
+                ));
+            }
+        }
+        if has_modifier(&modifiers, Keyword::Private) && has_modifier(&modifiers, Keyword::Final) {
+            return Err(Diagnostic::error(
+                "private constant cannot be final as it is not visible to other classes",
+                self.prev_span(),
+            ));
+        }
+
+        // the type is optional, `const A = 1` has a name directly followed by `=`.
+        let ty = if self.at_word() && self.nth(1).is_some_and(|t| is_assign(&t.0, &t.2)) {
+            None
+        } else {
+            Some(self.parse_type()?)
+        };
+
+        let mut consts = Vec::new();
+        loop {
+            let start = self.current_span();
+            let name = self.parse_word("a constant name")?;
+            if name.as_str().eq_ignore_ascii_case("class") {
+                return Err(Diagnostic::error(
+                    "a class constant must not be called 'class', it is reserved for class name fetching",
+                    name.span,
+                ));
+            }
+            if let Some(ty) = &ty {
+                check_member_type(ty, &format!("class constant {}::{}", class.name, name))?;
+            }
+            self.expect(&TokenType::Operator, "`=`").and_then(|token| {
+                match token.2.as_deref() {
+                    Some("=") => Ok(()),
+                    _ => Err(Diagnostic::error("expected `=`", token.1)),
+                }
+            })?;
+            let value = self.parse_expr()?;
+            consts.push(ConstItem {
+                name,
+                value,
+                span: self.span_from(start),
+            });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect_semicolon()?;
+
+        Ok(ClassConst {
+            modifiers,
+            ty,
+            consts,
+            attributes: Vec::new(),
+        })
+    }
+
+    /// A method, after the `function` keyword.
+    fn parse_method(
+        &mut self,
+        class: &ClassContext,
+        modifiers: Vec<Modifier>,
+        start: Span,
+    ) -> PResult<Method> {
+        for modifier in &modifiers {
+            if matches!(modifier.keyword, Keyword::ReadOnly | Keyword::Var) {
+                return Err(Diagnostic::error(
+                    format!("cannot use the {} modifier on a method", modifier.keyword),
+                    modifier.span,
+                ));
+            }
+        }
+
+        let function = self.parse_function_rest(start)?;
+        let name = format!("{}::{}()", class.name, function.name);
+        let is_abstract = has_modifier(&modifiers, Keyword::Abstract);
+
+        if is_abstract && has_modifier(&modifiers, Keyword::Final) {
+            return Err(Diagnostic::error(
+                "cannot use the final modifier on an abstract method",
+                function.name.span,
+            ));
+        }
+        if is_abstract
+            && has_modifier(&modifiers, Keyword::Private)
+            && class.kind != ClassKind::Trait
+        {
+            return Err(Diagnostic::error(
+                format!("abstract function {} cannot be declared private", name),
+                function.name.span,
+            ));
+        }
+
+        if class.kind == ClassKind::Interface {
+            if function.body.is_some() {
+                return Err(Diagnostic::error(
+                    format!("interface function {} cannot contain body", name),
+                    function.name.span,
+                ));
+            }
+            if modifiers
+                .iter()
+                .any(|m| matches!(m.keyword, Keyword::Protected | Keyword::Private))
+            {
+                return Err(Diagnostic::error(
+                    format!("access type for interface method {} must be public", name),
+                    function.name.span,
+                ));
+            }
+        } else if is_abstract {
+            if function.body.is_some() {
+                return Err(Diagnostic::error(
+                    format!("abstract function {} cannot contain body", name),
+                    function.name.span,
+                ));
+            }
+            if matches!(class.kind, ClassKind::Class | ClassKind::Enum) && !class.is_abstract {
+                return Err(Diagnostic::error(
+                    format!(
+                        "{} {} contains abstract method {} and must therefore be declared abstract",
+                        class.kind.as_str(),
+                        class.name,
+                        name
+                    ),
+                    function.name.span,
+                ));
+            }
+        } else if function.body.is_none() {
+            return Err(Diagnostic::error(
+                format!("non-abstract method {} must contain body", name),
+                function.name.span,
+            ));
+        }
+
+        if class.kind == ClassKind::Enum {
+            let method = function.name.as_str().to_ascii_lowercase();
+            if ENUM_FORBIDDEN_METHODS.contains(&method.as_str()) {
+                return Err(Diagnostic::error(
+                    format!(
+                        "enum {} cannot include magic method {}",
+                        class.name, function.name
+                    ),
+                    function.name.span,
+                ));
+            }
+            if method == "cases" || (class.is_backed && (method == "from" || method == "tryfrom")) {
+                return Err(Diagnostic::error(
+                    format!("cannot redeclare {}", name),
+                    function.name.span,
+                ));
+            }
+        }
+
+        let is_constructor = function.name.as_str().eq_ignore_ascii_case("__construct");
+        for param in &function.params {
+            if !param.is_promoted() {
+                continue;
+            }
+            if !is_constructor {
+                return Err(Diagnostic::error(
+                    "cannot declare promoted property outside a constructor",
+                    param.span,
+                ));
+            }
+            if is_abstract || class.kind == ClassKind::Interface {
+                return Err(Diagnostic::error(
+                    "cannot declare promoted property in an abstract constructor",
+                    param.span,
+                ));
+            }
+        }
+
+        Ok(Method {
+            modifiers,
+            function,
+        })
+    }
+
+    /// `public int $a = 1, $b;`
+    fn parse_property(
+        &mut self,
+        class: &ClassContext,
+        modifiers: Vec<Modifier>,
+    ) -> PResult<Property> {
+        if modifiers.is_empty() {
+            return Err(self.unexpected("a class member"));
+        }
+        for modifier in &modifiers {
+            if matches!(modifier.keyword, Keyword::Abstract | Keyword::Final) {
+                return Err(Diagnostic::error(
+                    format!("properties cannot be declared {}", modifier.keyword),
+                    modifier.span,
+                ));
+            }
+        }
+        if class.kind == ClassKind::Interface {
+            return Err(Diagnostic::error(
+                "interfaces may not include properties",
+                self.current_span(),
+            ));
+        }
+        if class.kind == ClassKind::Enum {
+            return Err(Diagnostic::error(
+                format!("enum {} cannot include properties", class.name),
+                self.current_span(),
+            ));
+        }
+
+        let ty = if self.at(&TokenType::Variable) {
+            None
+        } else {
+            Some(self.parse_type()?)
+        };
+        let readonly = has_modifier(&modifiers, Keyword::ReadOnly);
+
+        let mut props = Vec::new();
+        loop {
+            let start = self.current_span();
+            let name = self.parse_var_name()?;
+            let display = format!("{}::${}", class.name, name);
+            if let Some(ty) = &ty {
+                check_member_type(ty, &format!("property {}", display))?;
+            }
+            if readonly && ty.is_none() {
+                return Err(Diagnostic::error(
+                    format!("readonly property {} must have type", display),
+                    name.span,
+                ));
+            }
+            if readonly && has_modifier(&modifiers, Keyword::Static) {
+                return Err(Diagnostic::error(
+                    format!("static property {} cannot be readonly", display),
+                    name.span,
+                ));
+            }
+            let default = if self.eat_op("=").is_some() {
+                if readonly {
+                    return Err(Diagnostic::error(
+                        format!("readonly property {} cannot have default value", display),
+                        name.span,
+                    ));
+                }
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
+            props.push(PropertyItem {
+                name,
+                default,
+                span: self.span_from(start),
+            });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect_semicolon()?;
+
+        Ok(Property {
+            modifiers,
+            ty,
+            props,
+            attributes: Vec::new(),
+        })
+    }
+
+    /// `use A, B;` or `use A, B { ... }` inside a class.
+    fn parse_trait_use(&mut self) -> PResult<TraitUse> {
+        self.bump();
+        let traits = self.parse_name_list()?;
+        let mut adaptations = Vec::new();
+        if self.eat(&TokenType::EOS).is_none() {
+            self.expect(&TokenType::LeftBrace, "`;` or `{`")?;
+            while self.eat(&TokenType::RightBrace).is_none() {
+                adaptations.push(self.parse_trait_adaptation()?);
+            }
+        }
+        Ok(TraitUse {
+            traits,
+            adaptations,
+        })
+    }
+
+    /// `A::foo insteadof B;`, `A::foo as protected bar;`, `foo as bar;` or `foo as private;`
+    fn parse_trait_adaptation(&mut self) -> PResult<TraitAdaptation> {
+        let start = self.current_span();
+        let trait_name = if self.at_name()
+            && self.nth_kind(1) == Some(&TokenType::Accessor(AccessType::StaticMember))
+            || self.at(&TokenType::Backslash)
+        {
+            let name = self.parse_name()?;
+            self.expect(&TokenType::Accessor(AccessType::StaticMember), "`::`")?;
+            Some(name)
+        } else {
+            None
+        };
+        let method = self.parse_word("a method name")?;
+
+        let kind = if self.at_keyword(Keyword::InsteadOf) {
+            let token = self.bump();
+            let Some(trait_name) = trait_name else {
+                return Err(Diagnostic::error(
+                    "`insteadof` requires the trait of the method, `A::foo insteadof B`",
+                    token.1,
+                ));
+            };
+            AdaptationKind::InsteadOf {
+                trait_name,
+                method,
+                instead_of: self.parse_name_list()?,
+            }
+        } else {
+            self.expect(&TokenType::Keyword(Keyword::As), "`as` or `insteadof`")?;
+            let visibility = match self.nth_kind(0) {
+                Some(TokenType::Keyword(
+                    keyword @ (Keyword::Public | Keyword::Protected | Keyword::Private),
+                )) => Some(Modifier {
+                    keyword: *keyword,
+                    span: self.bump().1,
+                }),
+                _ => None,
+            };
+            let alias = if visibility.is_none() || self.at_word() {
+                Some(self.parse_word("an alias")?)
+            } else {
+                None
+            };
+            AdaptationKind::Alias {
+                trait_name,
+                method,
+                visibility,
+                alias,
+            }
+        };
+        self.expect_semicolon()?;
+
+        Ok(TraitAdaptation {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+}
+
+/// The class-like whose members are being parsed, for validating them.
+struct ClassContext<'a> {
+    kind: ClassKind,
+    name: &'a str,
+    is_abstract: bool,
+    is_backed: bool,
+}
+
+/// The names of the methods, properties and constants declared so far in a class body, methods
+/// by lowercase name as they are looked up case-insensitively. Enum cases are constants.
+#[derive(Default)]
+struct Declared {
+    methods: HashSet<String>,
+    props: HashSet<String>,
+    consts: HashSet<String>,
+}
+
+impl Declared {
+    /// Adds the names a member declares, failing on the first one declared before.
+    fn add(&mut self, class: &ClassContext, member: &ClassMember) -> PResult<()> {
+        let name = class.name;
+        match &member.kind {
+            MemberKind::Method(method) => {
+                let function = &method.function;
+                if !self
+                    .methods
+                    .insert(function.name.as_str().to_ascii_lowercase())
+                {
+                    return Err(Diagnostic::error(
+                        format!("cannot redeclare {}::{}()", name, function.name),
+                        function.name.span,
+                    ));
+                }
+                for param in function.params.iter().filter(|param| param.is_promoted()) {
+                    self.add_prop(name, &param.name)?;
+                }
+            }
+            MemberKind::Property(property) => {
+                for item in &property.props {
+                    self.add_prop(name, &item.name)?;
+                }
+            }
+            MemberKind::Const(consts) => {
+                for item in &consts.consts {
+                    self.add_const(name, &item.name)?;
+                }
+            }
+            MemberKind::EnumCase(case) => self.add_const(name, &case.name)?,
+            MemberKind::TraitUse(_) | MemberKind::Error => {}
+        }
+        Ok(())
+    }
+
+    fn add_prop(&mut self, class: &str, prop: &Ident) -> PResult<()> {
+        match self.props.insert(prop.as_str().to_string()) {
+            true => Ok(()),
+            false => Err(Diagnostic::error(
+                format!("cannot redeclare {}::${}", class, prop),
+                prop.span,
+            )),
+        }
+    }
+
+    fn add_const(&mut self, class: &str, constant: &Ident) -> PResult<()> {
+        match self.consts.insert(constant.as_str().to_string()) {
+            true => Ok(()),
+            false => Err(Diagnostic::error(
+                format!("cannot redefine class constant {}::{}", class, constant),
+                constant.span,
+            )),
+        }
+    }
+}
+
+fn is_assign(kind: &TokenType, value: &Option<String>) -> bool {
+    *kind == TokenType::Operator && value.as_deref() == Some("=")
+}
+
+/// Properties and class constants hold values, so they can not be declared `void` or
+/// `never`, and `callable` can not be checked on assignment.
+fn check_member_type(ty: &TypeHint, member: &str) -> PResult<()> {
+    let model = ty.to_type();
+    let invalid = match model {
+        Type::Builtin(builtin @ (BuiltinType::Void | BuiltinType::Never)) => Some(builtin),
+        _ if model.contains(BuiltinType::Callable) => Some(BuiltinType::Callable),
+        _ => None,
+    };
+    match invalid {
+        Some(builtin) => Err(Diagnostic::error(
+            format!("{} cannot have type {}", member, builtin.as_str()),
+            ty.span,
+        )),
+        None => Ok(()),
+    }
+}
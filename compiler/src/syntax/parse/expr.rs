@@ -0,0 +1,1077 @@
+use std::str::FromStr;
+
+use crate::syntax::{
+    ast::{
+        class::ClassKind,
+        expr::{
+            Arg, ArrayItem, BinaryOp, CallArgs, CastKind, ClassRef, Expr, ExprKind, IncDecOp,
+            Literal, MatchArm, MemberName, UnaryOp,
+        },
+        keyword::Keyword,
+        name::{Name, NameKind},
+        reserved::ReservedCall,
+    },
+    diagnostic::Diagnostic,
+    experiments::Experiment,
+    lex::token::{AccessType, TokenType},
+    source::Span,
+};
+
+use super::{PResult, Parser};
+
+/// Binding powers of the operators, from the loosest to the tightest, following
+/// https://www.php.net/manual/en/language.operators.precedence.php
+pub(crate) mod bp {
+    pub const LOGICAL_OR: u8 = 1;
+    pub const LOGICAL_XOR: u8 = 2;
+    pub const LOGICAL_AND: u8 = 3;
+    pub const ASSIGN: u8 = 4;
+    pub const TERNARY: u8 = 5;
+    pub const COALESCE: u8 = 6;
+    pub const BOOLEAN_OR: u8 = 7;
+    pub const BOOLEAN_AND: u8 = 8;
+    pub const BIT_OR: u8 = 9;
+    pub const BIT_XOR: u8 = 10;
+    pub const BIT_AND: u8 = 11;
+    pub const EQUALITY: u8 = 12;
+    pub const COMPARISON: u8 = 13;
+    pub const CONCAT: u8 = 14;
+    pub const SHIFT: u8 = 15;
+    pub const ADDITIVE: u8 = 16;
+    pub const MULTIPLICATIVE: u8 = 17;
+    pub const NOT: u8 = 18;
+    pub const INSTANCEOF: u8 = 19;
+    pub const UNARY: u8 = 20;
+    pub const POW: u8 = 21;
+    pub const CLONE: u8 = 22;
+}
+
+/// An operator that follows its left operand.
+#[derive(Clone, Copy)]
+enum Infix {
+    Binary(BinaryOp),
+    Assign,
+    AssignOp(BinaryOp),
+    Ternary,
+    InstanceOf,
+}
+
+/// The last operator folded into the left operand, which decides whether the next operator
+/// may follow it without parentheses.
+#[derive(Clone, Copy)]
+enum Last {
+    NonAssoc(BinaryOp),
+    Ternary { short: bool },
+}
+
+impl Parser<'_> {
+    pub fn parse_expr(&mut self) -> PResult<Expr> {
+        self.parse_expr_bp(0)
+    }
+
+    /// Parses an expression made of operators that bind tighter than `min_bp`.
+    pub(crate) fn parse_expr_bp(&mut self, min_bp: u8) -> PResult<Expr> {
+        let mut left = self.parse_prefix()?;
+        let mut last = None;
+
+        while let Some((infix, lbp)) = self.infix() {
+            // `!$a = 1` is `!($a = 1)`, an assignment always takes a variable on its left no
+            // matter how tightly the operator before it binds.
+            if matches!(infix, Infix::Assign | Infix::AssignOp(_)) {
+                if !is_assignable(&left, matches!(infix, Infix::Assign)) {
+                    if lbp <= min_bp {
+                        break;
+                    }
+                    return Err(Diagnostic::error(
+                        "cannot assign to this expression",
+                        left.span,
+                    ));
+                }
+            } else if lbp <= min_bp {
+                break;
+            }
+
+            let op = self.bump();
+            let start = left.span;
+            let (kind, folded) = match infix {
+                Infix::Binary(op) => {
+                    if let (Some(Last::NonAssoc(prev)), true) = (last, is_non_assoc(lbp)) {
+                        if binding_power(prev) == lbp {
+                            return Err(Diagnostic::error(
+                                format!(
+                                    "`{}` is non-associative and can not follow `{}` without parentheses",
+                                    op.as_str(),
+                                    prev.as_str()
+                                ),
+                                self.prev_span(),
+                            ));
+                        }
+                    }
+                    let rbp = if matches!(op, BinaryOp::Pow | BinaryOp::Coalesce) {
+                        lbp - 1
+                    } else {
+                        lbp
+                    };
+                    let right = self.parse_expr_bp(rbp)?;
+                    let folded = is_non_assoc(lbp).then_some(Last::NonAssoc(op));
+                    (
+                        ExprKind::Binary {
+                            op,
+                            left: Box::new(left),
+                            right: Box::new(right),
+                        },
+                        folded,
+                    )
+                }
+                Infix::Assign => {
+                    let target = Box::new(into_list(left));
+                    if self.eat_op("&").is_some() {
+                        let value = Box::new(self.parse_expr_bp(bp::ASSIGN - 1)?);
+                        (ExprKind::AssignRef { target, value }, None)
+                    } else {
+                        let value = Box::new(self.parse_expr_bp(bp::ASSIGN - 1)?);
+                        (ExprKind::Assign { target, value }, None)
+                    }
+                }
+                Infix::AssignOp(op) => {
+                    let value = Box::new(self.parse_expr_bp(bp::ASSIGN - 1)?);
+                    (
+                        ExprKind::AssignOp {
+                            op,
+                            target: Box::new(left),
+                            value,
+                        },
+                        None,
+                    )
+                }
+                Infix::Ternary => {
+                    let short = self.at(&TokenType::Colon);
+                    if let Some(Last::Ternary { short: prev_short }) = last {
+                        if !(short && prev_short) {
+                            return Err(Diagnostic::error(
+                                "nested ternary expressions require parentheses, use `(a ? b : c) ? d : e` or `a ? b : (c ? d : e)`",
+                                op.1,
+                            ));
+                        }
+                    }
+                    let then = if short {
+                        None
+                    } else {
+                        Some(Box::new(self.parse_expr()?))
+                    };
+                    self.expect(&TokenType::Colon, "`:`")?;
+                    let otherwise = Box::new(self.parse_expr_bp(bp::TERNARY)?);
+                    (
+                        ExprKind::Ternary {
+                            cond: Box::new(left),
+                            then,
+                            otherwise,
+                        },
+                        Some(Last::Ternary { short }),
+                    )
+                }
+                Infix::InstanceOf => {
+                    let class = self.parse_instanceof_class()?;
+                    (
+                        ExprKind::InstanceOf {
+                            expr: Box::new(left),
+                            class,
+                        },
+                        None,
+                    )
+                }
+            };
+
+            left = Expr::new(kind, self.span_from(start));
+            last = folded;
+        }
+
+        Ok(left)
+    }
+
+    fn infix(&self) -> Option<(Infix, u8)> {
+        let token = self.peek()?;
+        let op = match &token.0 {
+            TokenType::Keyword(Keyword::Or) => {
+                return Some((Infix::Binary(BinaryOp::LogicalOr), bp::LOGICAL_OR))
+            }
+            TokenType::Keyword(Keyword::Xor) => {
+                return Some((Infix::Binary(BinaryOp::LogicalXor), bp::LOGICAL_XOR))
+            }
+            TokenType::Keyword(Keyword::And) => {
+                return Some((Infix::Binary(BinaryOp::LogicalAnd), bp::LOGICAL_AND))
+            }
+            TokenType::Keyword(Keyword::InstanceOf) => {
+                return Some((Infix::InstanceOf, bp::INSTANCEOF))
+            }
+            TokenType::QuestionMark => return Some((Infix::Ternary, bp::TERNARY)),
+            TokenType::Dot => return Some((Infix::Binary(BinaryOp::Concat), bp::CONCAT)),
+            TokenType::Operator => token.2.as_deref()?,
+            _ => return None,
+        };
+
+        if op == "=" {
+            return Some((Infix::Assign, bp::ASSIGN));
+        }
+        if let Some(op) = op.strip_suffix('=').and_then(compound_op) {
+            return Some((Infix::AssignOp(op), bp::ASSIGN));
+        }
+        let op = binary_op(op)?;
+        Some((Infix::Binary(op), binding_power(op)))
+    }
+
+    fn parse_prefix(&mut self) -> PResult<Expr> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("an expression"));
+        };
+        let start = token.1;
+
+        let kind = match &token.0 {
+            TokenType::Operator => {
+                let op = token.2.as_deref().unwrap_or_default();
+                let unary = match op {
+                    "!" => Some((UnaryOp::Not, bp::NOT)),
+                    "+" => Some((UnaryOp::Plus, bp::UNARY)),
+                    "-" => Some((UnaryOp::Minus, bp::UNARY)),
+                    "~" => Some((UnaryOp::BitNot, bp::UNARY)),
+                    _ => None,
+                };
+                if let Some((op, bp)) = unary {
+                    self.bump();
+                    let expr = Box::new(self.parse_expr_bp(bp)?);
+                    ExprKind::Unary { op, expr }
+                } else if op == "++" || op == "--" {
+                    self.bump();
+                    let expr = self.parse_expr_bp(bp::UNARY)?;
+                    if !is_assignable(&expr, false) {
+                        return Err(Diagnostic::error(
+                            format!("cannot use `{}` on this expression", op),
+                            expr.span,
+                        ));
+                    }
+                    let op = if op == "++" {
+                        IncDecOp::PreIncrement
+                    } else {
+                        IncDecOp::PreDecrement
+                    };
+                    ExprKind::IncDec {
+                        op,
+                        expr: Box::new(expr),
+                    }
+                } else if op == "@" {
+                    self.bump();
+                    ExprKind::ErrorSuppress(Box::new(self.parse_expr_bp(bp::UNARY)?))
+                } else if let Some(kind) = cast_kind(op) {
+                    self.bump();
+                    let expr = Box::new(self.parse_expr_bp(bp::UNARY)?);
+                    ExprKind::Cast { kind, expr }
+                } else {
+                    return Err(self.unexpected("an expression"));
+                }
+            }
+            TokenType::AttributeStart => {
+                let attributes = self.parse_attributes()?;
+                if !self.at_keyword(Keyword::Function)
+                    && !self.at_keyword(Keyword::Fn)
+                    && !self.at_keyword(Keyword::Static)
+                {
+                    return Err(self.unexpected("`function` or `fn`"));
+                }
+                let mut expr = self.parse_closure()?;
+                match &mut expr.kind {
+                    ExprKind::Closure(closure) => closure.attributes = attributes,
+                    ExprKind::ArrowFunction(function) => function.attributes = attributes,
+                    _ => unreachable!("parse_closure returns a closure or arrow function"),
+                }
+                expr.span = self.span_from(start);
+                return Ok(expr);
+            }
+            TokenType::Keyword(Keyword::New) => return self.parse_new(),
+            TokenType::Keyword(Keyword::Match) => return self.parse_match(),
+            TokenType::Keyword(Keyword::Function | Keyword::Fn) => return self.parse_closure(),
+            TokenType::Keyword(Keyword::Static)
+                if matches!(
+                    self.nth_kind(1),
+                    Some(TokenType::Keyword(
+                        Keyword::Function | Keyword::Fn | Keyword::Async
+                    ))
+                ) =>
+            {
+                return self.parse_closure()
+            }
+            TokenType::Keyword(Keyword::Clone) => {
+                self.bump();
+                ExprKind::Clone(Box::new(self.parse_expr_bp(bp::CLONE)?))
+            }
+            TokenType::Keyword(Keyword::Throw) => {
+                self.bump();
+                ExprKind::Throw(Box::new(self.parse_expr()?))
+            }
+            TokenType::Keyword(
+                keyword @ (Keyword::Include
+                | Keyword::IncludeOnce
+                | Keyword::Require
+                | Keyword::RequireOnce),
+            ) => {
+                let keyword = *keyword;
+                self.bump();
+                ExprKind::Include {
+                    keyword,
+                    path: Box::new(self.parse_expr()?),
+                }
+            }
+            TokenType::Keyword(Keyword::Yield) => {
+                self.bump();
+                if self.eat(&TokenType::Keyword(Keyword::From)).is_some() {
+                    ExprKind::YieldFrom(Box::new(self.parse_expr_bp(bp::LOGICAL_AND)?))
+                } else if self.ends_operand() {
+                    ExprKind::Yield {
+                        key: None,
+                        value: None,
+                    }
+                } else {
+                    let first = self.parse_expr_bp(bp::LOGICAL_AND)?;
+                    match self.eat_op("=>") {
+                        Some(_) => ExprKind::Yield {
+                            key: Some(Box::new(first)),
+                            value: Some(Box::new(self.parse_expr_bp(bp::LOGICAL_AND)?)),
+                        },
+                        None => ExprKind::Yield {
+                            key: None,
+                            value: Some(Box::new(first)),
+                        },
+                    }
+                }
+            }
+            TokenType::Identifier if self.text_of(start).eq_ignore_ascii_case("print") => {
+                self.bump();
+                ExprKind::Print(Box::new(self.parse_expr_bp(bp::LOGICAL_AND)?))
+            }
+            TokenType::Keyword(Keyword::Await) => {
+                self.bump();
+                ExprKind::Await(Box::new(self.parse_expr_bp(bp::UNARY)?))
+            }
+            // without the experiment `await` is a name, which no operand can follow.
+            TokenType::Identifier
+                if self.text_of(start).eq_ignore_ascii_case("await")
+                    && matches!(
+                        self.nth_kind(1),
+                        Some(
+                            TokenType::Variable
+                                | TokenType::Identifier
+                                | TokenType::Keyword(Keyword::New)
+                        )
+                    ) =>
+            {
+                let message = Experiment::AsyncAwait.required("`await` expressions");
+                return Err(Diagnostic::error(message, start));
+            }
+            _ if self.at_async()? => return self.parse_closure(),
+            _ => {
+                let primary = self.parse_primary()?;
+                return self.parse_postfix(primary);
+            }
+        };
+
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    /// Whether the next token ends an expression rather than starting one, so a `yield`
+    /// before it has no value, as in `$a = yield;` or `f(yield)`.
+    fn ends_operand(&self) -> bool {
+        matches!(
+            self.nth_kind(0),
+            None | Some(
+                TokenType::EOS
+                    | TokenType::RightParenthesis
+                    | TokenType::RightBracket
+                    | TokenType::RightBrace
+                    | TokenType::Comma
+                    | TokenType::Colon
+                    | TokenType::Keyword(Keyword::And | Keyword::Or | Keyword::Xor)
+            )
+        ) || self.at_op("=>")
+    }
+
+    fn parse_primary(&mut self) -> PResult<Expr> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("an expression"));
+        };
+        let start = token.1;
+
+        let kind = match &token.0 {
+            TokenType::Variable => return self.parse_variable(),
+            TokenType::NumericalLit(n) => {
+                self.bump();
+                ExprKind::Literal(Literal::Numeric(*n))
+            }
+            TokenType::Boolean => {
+                self.bump();
+                let value = self.text_of(start).eq_ignore_ascii_case("true");
+                ExprKind::Literal(Literal::Bool(value))
+            }
+            TokenType::StringLit(_) => {
+                self.bump();
+                return self.parse_string(token);
+            }
+            TokenType::ReservedIdent(reserved)
+                if self.nth_kind(1) != Some(&TokenType::Backslash) =>
+            {
+                self.bump();
+                ExprKind::ReservedConst(*reserved)
+            }
+            TokenType::LeftParenthesis => {
+                self.bump();
+                let expr = self.parse_expr()?;
+                self.expect(&TokenType::RightParenthesis, "`)`")?;
+                return Ok(expr);
+            }
+            TokenType::LeftBracket => {
+                self.bump();
+                self.parse_array(start, TokenType::RightBracket)?
+            }
+            // `static` is only a class name in `static::`.
+            TokenType::Keyword(Keyword::Static)
+                if self.nth_kind(1) == Some(&TokenType::Accessor(AccessType::StaticMember)) =>
+            {
+                self.bump();
+                let name = Name::new(vec!["static".to_string()], NameKind::Unqualified, start);
+                ExprKind::ConstFetch(name)
+            }
+            _ if self.at_name() => return self.parse_name_expr(),
+            _ => return Err(self.unexpected("an expression")),
+        };
+
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    /// A constant, or a name used by a call or static access, which `parse_postfix` turns
+    /// into a call or class reference.
+    fn parse_name_expr(&mut self) -> PResult<Expr> {
+        let name = self.parse_name()?;
+        let start = name.span;
+        if name.kind != NameKind::Unqualified {
+            return Ok(Expr::new(ExprKind::ConstFetch(name), start));
+        }
+
+        if name.last().eq_ignore_ascii_case("null") {
+            return Ok(Expr::new(ExprKind::Literal(Literal::Null), start));
+        }
+
+        let kind = match ReservedCall::from_str(name.last()) {
+            Ok(ReservedCall::Array) if self.at(&TokenType::LeftParenthesis) => {
+                self.bump();
+                self.parse_array(start, TokenType::RightParenthesis)?
+            }
+            Ok(ReservedCall::List) if self.at(&TokenType::LeftParenthesis) => {
+                self.bump();
+                let items = self.parse_array_items(TokenType::RightParenthesis)?;
+                ExprKind::List {
+                    items,
+                    short: false,
+                }
+            }
+            Ok(construct @ (ReservedCall::IsSet | ReservedCall::Empty | ReservedCall::Eval)) => {
+                let args = self.parse_construct_args()?;
+                ExprKind::Construct { construct, args }
+            }
+            Ok(construct @ (ReservedCall::Exit | ReservedCall::Die)) => {
+                let args = if self.at(&TokenType::LeftParenthesis) {
+                    self.parse_construct_args()?
+                } else {
+                    Vec::new()
+                };
+                ExprKind::Construct { construct, args }
+            }
+            _ => ExprKind::ConstFetch(name),
+        };
+
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    /// `(a, b)` after `isset`, `empty`, `eval`, `exit` or `die`.
+    pub(crate) fn parse_construct_args(&mut self) -> PResult<Vec<Expr>> {
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let mut args = Vec::new();
+        while !self.at(&TokenType::RightParenthesis) {
+            args.push(self.parse_expr()?);
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+        Ok(args)
+    }
+
+    /// Member access, calls, array offsets and postfix `++` and `--`.
+    fn parse_postfix(&mut self, mut expr: Expr) -> PResult<Expr> {
+        loop {
+            let start = expr.span;
+            let kind = match self.nth_kind(0) {
+                Some(TokenType::LeftBracket) => {
+                    self.bump();
+                    let index = if self.at(&TokenType::RightBracket) {
+                        None
+                    } else {
+                        Some(Box::new(self.parse_expr()?))
+                    };
+                    self.expect(&TokenType::RightBracket, "`]`")?;
+                    ExprKind::ArrayDim {
+                        array: Box::new(expr),
+                        index,
+                    }
+                }
+                Some(TokenType::Accessor(
+                    access @ (AccessType::ReferenceMember | AccessType::NullsafeMember),
+                )) => {
+                    let nullsafe = *access == AccessType::NullsafeMember;
+                    self.bump();
+                    let member = self.parse_member_name()?;
+                    if self.at(&TokenType::LeftParenthesis) {
+                        ExprKind::MethodCall {
+                            object: Box::new(expr),
+                            method: member,
+                            args: self.parse_call_args()?,
+                            nullsafe,
+                        }
+                    } else {
+                        ExprKind::PropertyFetch {
+                            object: Box::new(expr),
+                            property: member,
+                            nullsafe,
+                        }
+                    }
+                }
+                Some(TokenType::Accessor(AccessType::StaticMember)) => {
+                    self.bump();
+                    let class = match expr.kind {
+                        ExprKind::ConstFetch(name) => ClassRef::Name(name),
+                        kind => ClassRef::Expr(Box::new(Expr::new(kind, start))),
+                    };
+                    if self.at(&TokenType::Variable) {
+                        ExprKind::StaticPropertyFetch {
+                            class,
+                            property: Box::new(self.parse_variable()?),
+                        }
+                    } else {
+                        let member = self.parse_member_name()?;
+                        if self.at(&TokenType::LeftParenthesis) {
+                            ExprKind::StaticCall {
+                                class,
+                                method: member,
+                                args: self.parse_call_args()?,
+                            }
+                        } else {
+                            ExprKind::ClassConstFetch {
+                                class,
+                                constant: member,
+                            }
+                        }
+                    }
+                }
+                Some(TokenType::LeftParenthesis) => ExprKind::Call {
+                    args: self.parse_call_args()?,
+                    callee: Box::new(expr),
+                },
+                Some(TokenType::Operator) if self.at_op("++") || self.at_op("--") => {
+                    if !is_assignable(&expr, false) {
+                        break;
+                    }
+                    let op = if self.bump().2.as_deref() == Some("++") {
+                        IncDecOp::PostIncrement
+                    } else {
+                        IncDecOp::PostDecrement
+                    };
+                    ExprKind::IncDec {
+                        op,
+                        expr: Box::new(expr),
+                    }
+                }
+                _ => break,
+            };
+            expr = Expr::new(kind, self.span_from(start));
+        }
+
+        Ok(expr)
+    }
+
+    /// `$a`, `$$a` or `${expr}`, the name must directly follow the `$`.
+    pub(crate) fn parse_variable(&mut self) -> PResult<Expr> {
+        let dollar = self.expect(&TokenType::Variable, "a variable")?;
+        let adjacent = self
+            .peek()
+            .is_some_and(|token| token.1.start == dollar.1.end);
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::Variable) if adjacent => {
+                ExprKind::VariableVariable(Box::new(self.parse_variable()?))
+            }
+            Some(TokenType::LeftBrace) if adjacent => {
+                self.bump();
+                let expr = self.parse_expr()?;
+                self.expect(&TokenType::RightBrace, "`}`")?;
+                ExprKind::VariableVariable(Box::new(expr))
+            }
+            _ if adjacent && self.at_word() => {
+                let name = self.bump();
+                ExprKind::Variable(self.text_of(name.1).to_string())
+            }
+            _ => return Err(self.unexpected("a variable name after `$`")),
+        };
+        Ok(Expr::new(kind, self.span_from(dollar.1)))
+    }
+
+    /// The name after `->` or `::`, `b`, `$b` or `{expr}`.
+    fn parse_member_name(&mut self) -> PResult<MemberName> {
+        match self.nth_kind(0) {
+            Some(TokenType::Variable) => Ok(MemberName::Expr(Box::new(self.parse_variable()?))),
+            Some(TokenType::LeftBrace) => {
+                self.bump();
+                let expr = self.parse_expr()?;
+                self.expect(&TokenType::RightBrace, "`}`")?;
+                Ok(MemberName::Expr(Box::new(expr)))
+            }
+            _ => Ok(MemberName::Ident(self.parse_word("a member name")?)),
+        }
+    }
+
+    /// `match (subject) { a, b => x, default => y }`, trailing commas are allowed after the
+    /// conditions of an arm and after the last arm.
+    fn parse_match(&mut self) -> PResult<Expr> {
+        let start = self.bump().1;
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let subject = self.parse_expr()?;
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+
+        let mut arms: Vec<MatchArm> = Vec::new();
+        while !self.at(&TokenType::RightBrace) {
+            let arm_start = self.current_span();
+            let conditions = if self.eat(&TokenType::Keyword(Keyword::Default)).is_some() {
+                if arms.iter().any(|arm| arm.conditions.is_none()) {
+                    return Err(Diagnostic::error(
+                        "match expressions may only contain one default arm",
+                        self.span_from(arm_start),
+                    ));
+                }
+                self.eat(&TokenType::Comma);
+                None
+            } else {
+                let mut conditions = vec![self.parse_expr()?];
+                while self.eat(&TokenType::Comma).is_some() && !self.at_op("=>") {
+                    conditions.push(self.parse_expr()?);
+                }
+                Some(conditions)
+            };
+            if self.eat_op("=>").is_none() {
+                return Err(self.unexpected("`=>`"));
+            }
+            let body = self.parse_expr()?;
+            arms.push(MatchArm {
+                conditions,
+                body,
+                span: self.span_from(arm_start),
+            });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightBrace, "`}`")?;
+
+        Ok(Expr::new(
+            ExprKind::Match {
+                subject: Box::new(subject),
+                arms,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    /// The arguments of a call, including the first-class callable syntax `foo(...)`.
+    pub(crate) fn parse_call_args(&mut self) -> PResult<CallArgs> {
+        let open = self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        if self.at_op("...") && self.nth_kind(1) == Some(&TokenType::RightParenthesis) {
+            self.pos += 2;
+            return Ok(CallArgs::FirstClassCallable(self.span_from(open.1)));
+        }
+
+        let mut args: Vec<Arg> = Vec::new();
+        while !self.at(&TokenType::RightParenthesis) {
+            let arg = self.parse_arg()?;
+            let named = args.iter().any(|arg| arg.name.is_some());
+            let message = match &arg.name {
+                Some(name)
+                    if args
+                        .iter()
+                        .any(|a| a.name.as_ref().is_some_and(|a| a.name == name.name)) =>
+                {
+                    Some(format!(
+                        "named parameter ${} overwrites previous argument",
+                        name
+                    ))
+                }
+                Some(_) => None,
+                None if arg.unpack && named => {
+                    Some("cannot use argument unpacking after named arguments".to_string())
+                }
+                None if arg.unpack => None,
+                None if named => {
+                    Some("cannot use positional argument after named argument".to_string())
+                }
+                None if args.iter().any(|arg| arg.unpack) => {
+                    Some("cannot use positional argument after argument unpacking".to_string())
+                }
+                None => None,
+            };
+            if let Some(message) = message {
+                return Err(Diagnostic::error(message, arg.span));
+            }
+            args.push(arg);
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+        Ok(CallArgs::List(args))
+    }
+
+    fn parse_arg(&mut self) -> PResult<Arg> {
+        let start = self.current_span();
+        let unpack = self.eat_op("...").is_some();
+        let name = if !unpack && self.at_word() && self.nth_kind(1) == Some(&TokenType::Colon) {
+            let name = self.parse_word("an argument name")?;
+            self.bump();
+            Some(name)
+        } else {
+            None
+        };
+        let value = self.parse_expr()?;
+        Ok(Arg {
+            name,
+            value,
+            unpack,
+            span: self.span_from(start),
+        })
+    }
+
+    /// `new Foo`, `new Foo(...)`, `new $class` or `new (expr)`. The result is not dereferenced
+    /// any further, `(new Foo)->bar()` needs the parentheses.
+    fn parse_new(&mut self) -> PResult<Expr> {
+        let new = self.bump();
+        let class = match self.nth_kind(0) {
+            Some(TokenType::Variable) => ClassRef::Expr(Box::new(self.parse_new_class_expr()?)),
+            Some(TokenType::LeftParenthesis) => {
+                self.bump();
+                let expr = self.parse_expr()?;
+                self.expect(&TokenType::RightParenthesis, "`)`")?;
+                ClassRef::Expr(Box::new(expr))
+            }
+            Some(TokenType::Keyword(Keyword::Static)) => {
+                let token = self.bump();
+                ClassRef::Name(Name::new(
+                    vec!["static".to_string()],
+                    NameKind::Unqualified,
+                    token.1,
+                ))
+            }
+            Some(TokenType::Keyword(Keyword::Class | Keyword::ReadOnly))
+            | Some(TokenType::AttributeStart) => return self.parse_anonymous_class(new.1),
+            _ if self.at_name() => ClassRef::Name(self.parse_name()?),
+            _ => return Err(self.unexpected("a class name")),
+        };
+
+        let args = self.parse_new_args()?;
+
+        Ok(Expr::new(
+            ExprKind::New { class, args },
+            self.span_from(new.1),
+        ))
+    }
+
+    /// The optional constructor arguments of `new`.
+    fn parse_new_args(&mut self) -> PResult<Option<Vec<Arg>>> {
+        if !self.at(&TokenType::LeftParenthesis) {
+            return Ok(None);
+        }
+        match self.parse_call_args()? {
+            CallArgs::List(args) => Ok(Some(args)),
+            CallArgs::FirstClassCallable(span) => Err(Diagnostic::error(
+                "cannot create a closure from `new`",
+                span,
+            )),
+        }
+    }
+
+    /// `new #[A] class(...) extends B implements C { ... }`, after the `new` keyword. The
+    /// constructor arguments come before the rest of the declaration.
+    fn parse_anonymous_class(&mut self, start: Span) -> PResult<Expr> {
+        let class_start = self.current_span();
+        let attributes = self.parse_attributes()?;
+        let modifiers = self.parse_modifiers()?;
+        if let Some(modifier) = modifiers.iter().find(|m| m.keyword != Keyword::ReadOnly) {
+            return Err(Diagnostic::error(
+                format!(
+                    "cannot use the {} modifier on an anonymous class",
+                    modifier.keyword
+                ),
+                modifier.span,
+            ));
+        }
+        self.expect(&TokenType::Keyword(Keyword::Class), "`class`")?;
+        let args = self.parse_new_args()?;
+        let mut class = self.parse_class_rest(ClassKind::Class, None, modifiers, class_start)?;
+        class.attributes = attributes;
+
+        Ok(Expr::new(
+            ExprKind::New {
+                class: ClassRef::Anonymous(Box::new(class)),
+                args,
+            },
+            self.span_from(start),
+        ))
+    }
+
+    /// The variable class of `new $a->b[0]`, which may not contain calls.
+    fn parse_new_class_expr(&mut self) -> PResult<Expr> {
+        let mut expr = self.parse_variable()?;
+        loop {
+            let start = expr.span;
+            let kind = match self.nth_kind(0) {
+                Some(TokenType::LeftBracket) => {
+                    self.bump();
+                    let index = self.parse_expr()?;
+                    self.expect(&TokenType::RightBracket, "`]`")?;
+                    ExprKind::ArrayDim {
+                        array: Box::new(expr),
+                        index: Some(Box::new(index)),
+                    }
+                }
+                Some(TokenType::Accessor(
+                    access @ (AccessType::ReferenceMember | AccessType::NullsafeMember),
+                )) => {
+                    let nullsafe = *access == AccessType::NullsafeMember;
+                    self.bump();
+                    ExprKind::PropertyFetch {
+                        object: Box::new(expr),
+                        property: self.parse_member_name()?,
+                        nullsafe,
+                    }
+                }
+                Some(TokenType::Accessor(AccessType::StaticMember))
+                    if self.nth_kind(1) == Some(&TokenType::Variable) =>
+                {
+                    self.bump();
+                    ExprKind::StaticPropertyFetch {
+                        class: ClassRef::Expr(Box::new(expr)),
+                        property: Box::new(self.parse_variable()?),
+                    }
+                }
+                _ => break,
+            };
+            expr = Expr::new(kind, self.span_from(start));
+        }
+        Ok(expr)
+    }
+
+    /// The right side of `instanceof`, a class name or an expression holding one.
+    fn parse_instanceof_class(&mut self) -> PResult<ClassRef> {
+        if self.at_keyword(Keyword::Static) {
+            let token = self.bump();
+            let name = Name::new(vec!["static".to_string()], NameKind::Unqualified, token.1);
+            return Ok(ClassRef::Name(name));
+        }
+        if self.at_name() {
+            return Ok(ClassRef::Name(self.parse_name()?));
+        }
+        Ok(ClassRef::Expr(Box::new(
+            self.parse_expr_bp(bp::INSTANCEOF)?,
+        )))
+    }
+
+    /// The rest of `[...]` or `array(...)` after the opening token. Skipped elements, as in
+    /// `[$a, , $b]`, are only allowed when destructuring, so they make the result a `List`.
+    fn parse_array(&mut self, start: Span, close: TokenType) -> PResult<ExprKind> {
+        let short = close == TokenType::RightBracket;
+        let items = self.parse_array_items(close)?;
+        if items.iter().any(Option::is_none) {
+            if !short {
+                return Err(Diagnostic::error(
+                    "cannot use empty array elements in arrays",
+                    self.span_from(start),
+                ));
+            }
+            return Ok(ExprKind::List { items, short });
+        }
+
+        Ok(ExprKind::Array {
+            items: items.into_iter().flatten().collect(),
+            short,
+        })
+    }
+
+    fn parse_array_items(&mut self, close: TokenType) -> PResult<Vec<Option<ArrayItem>>> {
+        let mut items = Vec::new();
+        while !self.at(&close) {
+            if self.eat(&TokenType::Comma).is_some() {
+                items.push(None);
+                continue;
+            }
+            items.push(Some(self.parse_array_item()?));
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        let what = match close {
+            TokenType::RightBracket => "`]`",
+            _ => "`)`",
+        };
+        self.expect(&close, what)?;
+        Ok(items)
+    }
+
+    fn parse_array_item(&mut self) -> PResult<ArrayItem> {
+        let start = self.current_span();
+        if self.eat_op("...").is_some() {
+            let value = self.parse_expr()?;
+            return Ok(ArrayItem {
+                key: None,
+                value,
+                by_ref: false,
+                unpack: true,
+                span: self.span_from(start),
+            });
+        }
+
+        let mut key = None;
+        let mut by_ref = self.eat_op("&").is_some();
+        let mut value = self.parse_expr()?;
+        if !by_ref && self.eat_op("=>").is_some() {
+            key = Some(value);
+            by_ref = self.eat_op("&").is_some();
+            value = self.parse_expr()?;
+        }
+
+        Ok(ArrayItem {
+            key,
+            value,
+            by_ref,
+            unpack: false,
+            span: self.span_from(start),
+        })
+    }
+}
+
+fn binary_op(op: &str) -> Option<BinaryOp> {
+    Some(match op {
+        "+" => BinaryOp::Add,
+        "-" => BinaryOp::Sub,
+        "*" => BinaryOp::Mul,
+        "/" => BinaryOp::Div,
+        "%" => BinaryOp::Mod,
+        "**" => BinaryOp::Pow,
+        "&" => BinaryOp::BitAnd,
+        "|" => BinaryOp::BitOr,
+        "^" => BinaryOp::BitXor,
+        "<<" => BinaryOp::ShiftLeft,
+        ">>" => BinaryOp::ShiftRight,
+        "&&" => BinaryOp::BooleanAnd,
+        "||" => BinaryOp::BooleanOr,
+        "==" => BinaryOp::Equal,
+        "!=" | "<>" => BinaryOp::NotEqual,
+        "===" => BinaryOp::Identical,
+        "!==" => BinaryOp::NotIdentical,
+        "<" => BinaryOp::Less,
+        "<=" => BinaryOp::LessOrEqual,
+        ">" => BinaryOp::Greater,
+        ">=" => BinaryOp::GreaterOrEqual,
+        "<=>" => BinaryOp::Spaceship,
+        "??" => BinaryOp::Coalesce,
+        _ => return None,
+    })
+}
+
+/// The operator of a compound assignment, without the trailing `=`.
+fn compound_op(op: &str) -> Option<BinaryOp> {
+    match op {
+        "." => Some(BinaryOp::Concat),
+        "+" | "-" | "*" | "/" | "%" | "**" | "&" | "|" | "^" | "<<" | ">>" | "??" => binary_op(op),
+        _ => None,
+    }
+}
+
+pub(crate) fn binding_power(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::LogicalOr => bp::LOGICAL_OR,
+        BinaryOp::LogicalXor => bp::LOGICAL_XOR,
+        BinaryOp::LogicalAnd => bp::LOGICAL_AND,
+        BinaryOp::Coalesce => bp::COALESCE,
+        BinaryOp::BooleanOr => bp::BOOLEAN_OR,
+        BinaryOp::BooleanAnd => bp::BOOLEAN_AND,
+        BinaryOp::BitOr => bp::BIT_OR,
+        BinaryOp::BitXor => bp::BIT_XOR,
+        BinaryOp::BitAnd => bp::BIT_AND,
+        BinaryOp::Equal
+        | BinaryOp::NotEqual
+        | BinaryOp::Identical
+        | BinaryOp::NotIdentical
+        | BinaryOp::Spaceship => bp::EQUALITY,
+        BinaryOp::Less | BinaryOp::LessOrEqual | BinaryOp::Greater | BinaryOp::GreaterOrEqual => {
+            bp::COMPARISON
+        }
+        BinaryOp::Concat => bp::CONCAT,
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => bp::SHIFT,
+        BinaryOp::Add | BinaryOp::Sub => bp::ADDITIVE,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => bp::MULTIPLICATIVE,
+        BinaryOp::Pow => bp::POW,
+    }
+}
+
+/// Comparisons can not be chained, `$a == $b == $c` is a syntax error.
+fn is_non_assoc(bp: u8) -> bool {
+    bp == bp::EQUALITY || bp == bp::COMPARISON
+}
+
+fn cast_kind(op: &str) -> Option<CastKind> {
+    Some(match op {
+        "(int)" => CastKind::Int,
+        "(float)" => CastKind::Float,
+        "(string)" => CastKind::String,
+        "(bool)" => CastKind::Bool,
+        "(array)" => CastKind::Array,
+        "(object)" => CastKind::Object,
+        "(unset)" => CastKind::Unset,
+        _ => return None,
+    })
+}
+
+/// Whether `expr` can be written to. Arrays and lists are only destructured by a plain `=`.
+pub(crate) fn is_assignable(expr: &Expr, destructure: bool) -> bool {
+    match &expr.kind {
+        ExprKind::Variable(_)
+        | ExprKind::VariableVariable(_)
+        | ExprKind::ArrayDim { .. }
+        | ExprKind::StaticPropertyFetch { .. } => true,
+        ExprKind::PropertyFetch { nullsafe, .. } => !nullsafe,
+        ExprKind::List { .. } => destructure,
+        ExprKind::Array { short, .. } => destructure && *short,
+        _ => false,
+    }
+}
+
+/// Turns a short array on the left of `=` into the `List` it destructures into.
+pub(crate) fn into_list(expr: Expr) -> Expr {
+    let span = expr.span;
+    let (items, short) = match expr.kind {
+        ExprKind::Array { items, short: true } => (items.into_iter().map(Some).collect(), true),
+        ExprKind::List { items, short } => (items, short),
+        kind => return Expr::new(kind, span),
+    };
+    let items = items.into_iter().map(|item| item.map(list_item)).collect();
+    Expr::new(ExprKind::List { items, short }, span)
+}
+
+fn list_item(item: ArrayItem) -> ArrayItem {
+    ArrayItem {
+        value: into_list(item.value),
+        ..item
+    }
+}
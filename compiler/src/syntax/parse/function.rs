@@ -0,0 +1,354 @@
+use crate::syntax::{
+    ast::{
+        expr::{Expr, ExprKind},
+        function::{ArrowFunction, Closure, ClosureUse, FunctionDecl, Param},
+        keyword::Keyword,
+        name::Ident,
+        stmt::Block,
+        types::{BuiltinType, Type, TypeHint},
+    },
+    diagnostic::Diagnostic,
+    experiments::Experiment,
+    lex::token::TokenType,
+    source::Span,
+};
+
+use super::{jump::check_jumps, PResult, Parser};
+
+impl Parser<'_> {
+    /// The rest of a function or method after the `function` keyword, `&name(...): T { ... }`.
+    /// The body is `None` when the declaration ends with `;`, which only abstract and
+    /// interface methods may do.
+    pub(crate) fn parse_function_rest(&mut self, start: Span) -> PResult<FunctionDecl> {
+        let by_ref = self.eat_op("&").is_some();
+        let name = self.parse_word("a function name")?;
+        let params = self.parse_params()?;
+        let return_type = self.parse_return_type()?;
+        let body = if self.eat(&TokenType::EOS).is_some() {
+            None
+        } else {
+            Some(self.parse_function_body()?)
+        };
+
+        Ok(FunctionDecl {
+            name,
+            is_async: false,
+            by_ref,
+            params,
+            return_type,
+            body,
+            attributes: Vec::new(),
+            span: self.span_from(start),
+        })
+    }
+
+    /// A function declared as a statement, `function foo() { ... }`, after the `function`
+    /// keyword. Unlike methods, it must have a body, and it has no class for a `static` return
+    /// type to refer to.
+    pub(crate) fn parse_function_decl(&mut self, start: Span) -> PResult<FunctionDecl> {
+        if !self.at(&TokenType::Identifier) && !self.at_op("&") {
+            return Err(self.unexpected("a function name"));
+        }
+        let function = self.parse_function_rest(start)?;
+        if function.body.is_none() {
+            return Err(Diagnostic::error(
+                format!("function {}() must contain body", function.name),
+                self.prev_span(),
+            ));
+        }
+        if let Some(ty) = &function.return_type {
+            if ty.to_type().contains(BuiltinType::Static) {
+                return Err(Diagnostic::error(
+                    "cannot use \"static\" when no class scope is active",
+                    ty.span,
+                ));
+            }
+        }
+        Ok(function)
+    }
+
+    /// Whether the next tokens are `async function` or `async fn`. Without the `async-await`
+    /// experiment `async` is lexed as a name, and using it like this fails.
+    pub(crate) fn at_async(&self) -> PResult<bool> {
+        if !matches!(
+            self.nth_kind(1),
+            Some(TokenType::Keyword(Keyword::Function | Keyword::Fn))
+        ) {
+            return Ok(false);
+        }
+        match self.nth_kind(0) {
+            Some(TokenType::Keyword(Keyword::Async)) => Ok(true),
+            Some(TokenType::Identifier)
+                if self
+                    .text_of(self.current_span())
+                    .eq_ignore_ascii_case("async") =>
+            {
+                let span = self.current_span();
+                self.require(Experiment::AsyncAwait, "async functions", span)?;
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// `function (...) use (...): T { ... }` or `fn (...) => expr`, both optionally `static`
+    /// and `async`.
+    pub(crate) fn parse_closure(&mut self) -> PResult<Expr> {
+        let start = self.current_span();
+        let is_static = self.eat(&TokenType::Keyword(Keyword::Static)).is_some();
+        let is_async = self.eat(&TokenType::Keyword(Keyword::Async)).is_some();
+        if self.eat(&TokenType::Keyword(Keyword::Fn)).is_some() {
+            return self.parse_arrow_function(start, is_static, is_async);
+        }
+        self.expect(&TokenType::Keyword(Keyword::Function), "`function` or `fn`")?;
+
+        let by_ref = self.eat_op("&").is_some();
+        let params = self.parse_params()?;
+        let uses = if self.eat(&TokenType::Keyword(Keyword::Use)).is_some() {
+            self.parse_closure_uses(&params)?
+        } else {
+            Vec::new()
+        };
+        let return_type = self.parse_return_type()?;
+        let body = self.parse_function_body()?;
+
+        let span = self.span_from(start);
+        let closure = Closure {
+            is_static,
+            is_async,
+            by_ref,
+            params,
+            uses,
+            return_type,
+            body,
+            attributes: Vec::new(),
+            span,
+        };
+        Ok(Expr::new(ExprKind::Closure(Box::new(closure)), span))
+    }
+
+    /// `fn (...) => expr`, after the `fn` keyword. The body extends as far as an expression
+    /// can, like the operand of `throw`.
+    fn parse_arrow_function(
+        &mut self,
+        start: Span,
+        is_static: bool,
+        is_async: bool,
+    ) -> PResult<Expr> {
+        let by_ref = self.eat_op("&").is_some();
+        let params = self.parse_params()?;
+        let return_type = self.parse_return_type()?;
+        if self.eat_op("=>").is_none() {
+            return Err(self.unexpected("`=>`"));
+        }
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let body = self.parse_expr();
+        self.loop_depth = loop_depth;
+
+        let span = self.span_from(start);
+        let function = ArrowFunction {
+            is_static,
+            is_async,
+            by_ref,
+            params,
+            return_type,
+            body: Box::new(body?),
+            attributes: Vec::new(),
+            span,
+        };
+        Ok(Expr::new(ExprKind::ArrowFunction(Box::new(function)), span))
+    }
+
+    /// `($a, &$b)` after the `use` of a closure, with a trailing comma allowed.
+    fn parse_closure_uses(&mut self, params: &[Param]) -> PResult<Vec<ClosureUse>> {
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let mut uses: Vec<ClosureUse> = Vec::new();
+        while !self.at(&TokenType::RightParenthesis) {
+            let start = self.current_span();
+            let by_ref = self.eat_op("&").is_some();
+            let name = self.parse_var_name()?;
+            let span = self.span_from(start);
+            if name.as_str() == "this" {
+                return Err(Diagnostic::error(
+                    "cannot use $this as lexical variable",
+                    span,
+                ));
+            }
+            if is_superglobal(name.as_str()) {
+                return Err(Diagnostic::error(
+                    format!("cannot use auto-global ${} as lexical variable", name),
+                    span,
+                ));
+            }
+            if params.iter().any(|param| param.name.name == name.name) {
+                return Err(Diagnostic::error(
+                    format!("cannot use lexical variable ${} as a parameter name", name),
+                    span,
+                ));
+            }
+            if uses.iter().any(|used| used.name.name == name.name) {
+                return Err(Diagnostic::error(
+                    format!("cannot use variable ${} twice", name),
+                    span,
+                ));
+            }
+            uses.push(ClosureUse { name, by_ref, span });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+        Ok(uses)
+    }
+
+    /// The body of a function, where `break` and `continue` can not reach the loops around
+    /// the declaration.
+    pub(crate) fn parse_function_body(&mut self) -> PResult<Block> {
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let body = self.parse_block();
+        self.loop_depth = loop_depth;
+        let body = body?;
+        for error in check_jumps(&body.stmts) {
+            self.report(error);
+        }
+        Ok(body)
+    }
+
+    /// `: T` after the parameters of a function.
+    pub(crate) fn parse_return_type(&mut self) -> PResult<Option<TypeHint>> {
+        if self.eat(&TokenType::Colon).is_some() {
+            Ok(Some(self.parse_type()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// `(int $a = 1, &...$rest)`, with a trailing comma allowed.
+    pub(crate) fn parse_params(&mut self) -> PResult<Vec<Param>> {
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let mut params: Vec<Param> = Vec::new();
+        while !self.at(&TokenType::RightParenthesis) {
+            let param = self.parse_param()?;
+            if let Some(variadic) = params.iter().find(|param| param.variadic) {
+                return Err(Diagnostic::error(
+                    "only the last parameter can be variadic",
+                    variadic.span,
+                ));
+            }
+            if param.name.as_str() == "this" {
+                return Err(Diagnostic::error(
+                    "cannot use $this as parameter",
+                    param.name.span,
+                ));
+            }
+            if params.iter().any(|p| p.name.name == param.name.name) {
+                return Err(Diagnostic::error(
+                    format!("redefinition of parameter ${}", param.name),
+                    param.name.span,
+                ));
+            }
+            params.push(param);
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+        Ok(params)
+    }
+
+    fn parse_param(&mut self) -> PResult<Param> {
+        let start = self.current_span();
+        let attributes = self.parse_attributes()?;
+        let modifiers = self.parse_modifiers()?;
+        if let Some(modifier) = modifiers.iter().find(|m| {
+            !matches!(
+                m.keyword,
+                Keyword::Public | Keyword::Protected | Keyword::Private | Keyword::ReadOnly
+            )
+        }) {
+            return Err(Diagnostic::error(
+                format!(
+                    "cannot use the {} modifier on a parameter",
+                    modifier.keyword
+                ),
+                modifier.span,
+            ));
+        }
+
+        let ty = if self.at_type() {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        if let Some(ty) = &ty {
+            if let Type::Builtin(builtin @ (BuiltinType::Void | BuiltinType::Never)) = ty.to_type()
+            {
+                return Err(Diagnostic::error(
+                    format!("{} cannot be used as a parameter type", builtin.as_str()),
+                    ty.span,
+                ));
+            }
+        }
+        let by_ref = self.eat_op("&").is_some();
+        let variadic = self.eat_op("...").is_some();
+        let name = self.parse_var_name()?;
+        let default = if self.eat_op("=").is_some() {
+            if variadic {
+                return Err(Diagnostic::error(
+                    "variadic parameter cannot have a default value",
+                    self.prev_span(),
+                ));
+            }
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        if variadic && !modifiers.is_empty() {
+            return Err(Diagnostic::error(
+                "cannot declare variadic promoted property",
+                self.span_from(start),
+            ));
+        }
+
+        Ok(Param {
+            name,
+            ty,
+            default,
+            by_ref,
+            variadic,
+            modifiers,
+            attributes,
+            span: self.span_from(start),
+        })
+    }
+
+    /// `$name`, returned without the `$` but with a span that covers it.
+    pub(crate) fn parse_var_name(&mut self) -> PResult<Ident> {
+        let dollar = self.expect(&TokenType::Variable, "a variable")?;
+        let adjacent = self
+            .peek()
+            .is_some_and(|token| token.1.start == dollar.1.end);
+        if !adjacent || !self.at_word() {
+            return Err(self.unexpected("a variable name after `$`"));
+        }
+        let name = self.bump();
+        Ok(Ident::new(self.text_of(name.1), dollar.1.to(name.1)))
+    }
+}
+
+/// The variables that are available in every scope, which closures can not capture.
+fn is_superglobal(name: &str) -> bool {
+    matches!(
+        name,
+        "GLOBALS"
+            | "_SERVER"
+            | "_GET"
+            | "_POST"
+            | "_FILES"
+            | "_COOKIE"
+            | "_SESSION"
+            | "_REQUEST"
+            | "_ENV"
+    )
+}
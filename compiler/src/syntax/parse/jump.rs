@@ -0,0 +1,159 @@
+//! The rules on `goto`, labels and leaving `finally` blocks, which need a whole function
+//! body to check as a `goto` may come before its label.
+
+use std::collections::HashMap;
+
+use crate::syntax::{
+    ast::{
+        name::Ident,
+        stmt::{Stmt, StmtKind},
+    },
+    diagnostic::Diagnostic,
+    source::Span,
+};
+
+/// A statement around a label, `goto` or `break`, told apart by the order they start in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// A loop or `switch`.
+    Loop(usize),
+    Finally(usize),
+}
+
+#[derive(Default)]
+struct Jumps<'a> {
+    path: Vec<Scope>,
+    scopes: usize,
+    labels: HashMap<&'a str, Vec<Scope>>,
+    gotos: Vec<(&'a Ident, Vec<Scope>)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Checks the jumps of the statements of a function or of the top level of a script. The
+/// functions and classes declared inside are checked on their own.
+///
+/// Labels are unique within the function, a `goto` may not jump into a loop, a `switch` or
+/// a `finally` block and neither `goto` nor `break` and `continue` may jump out of a
+/// `finally` block.
+pub(crate) fn check_jumps(stmts: &[Stmt]) -> Vec<Diagnostic> {
+    let mut jumps = Jumps::default();
+    jumps.stmts(stmts);
+
+    for (label, from) in std::mem::take(&mut jumps.gotos) {
+        let Some(to) = jumps.labels.get(label.name.as_str()) else {
+            jumps.error(
+                format!("'goto' to undefined label '{}'", label.name),
+                label.span,
+            );
+            continue;
+        };
+        let shared = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+        let message = if to[shared..].iter().any(|s| matches!(s, Scope::Loop(_))) {
+            "'goto' into loop or switch statement is disallowed"
+        } else if to[shared..].iter().any(|s| matches!(s, Scope::Finally(_))) {
+            "jump into a finally block is disallowed"
+        } else if from[shared..]
+            .iter()
+            .any(|s| matches!(s, Scope::Finally(_)))
+        {
+            "jump out of a finally block is disallowed"
+        } else {
+            continue;
+        };
+        jumps.error(message, label.span);
+    }
+    jumps.diagnostics
+}
+
+impl<'a> Jumps<'a> {
+    fn error(&mut self, message: impl Into<String>, span: Span) {
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+
+    fn within(&mut self, scope: fn(usize) -> Scope, stmts: impl FnOnce(&mut Self)) {
+        self.scopes += 1;
+        self.path.push(scope(self.scopes));
+        stmts(self);
+        self.path.pop();
+    }
+
+    fn stmts(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match &stmt.kind {
+            StmtKind::If {
+                then,
+                elseifs,
+                otherwise,
+                ..
+            } => {
+                self.stmts(&then.stmts);
+                for elseif in elseifs {
+                    self.stmts(&elseif.body.stmts);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.stmts(&otherwise.stmts);
+                }
+            }
+            StmtKind::While { body, .. }
+            | StmtKind::DoWhile { body, .. }
+            | StmtKind::For { body, .. }
+            | StmtKind::Foreach { body, .. } => {
+                self.within(Scope::Loop, |jumps| jumps.stmts(&body.stmts))
+            }
+            StmtKind::Switch { cases, .. } => self.within(Scope::Loop, |jumps| {
+                for case in cases {
+                    jumps.stmts(&case.body);
+                }
+            }),
+            StmtKind::Try {
+                body,
+                catches,
+                finally,
+            } => {
+                self.stmts(&body.stmts);
+                for catch in catches {
+                    self.stmts(&catch.body.stmts);
+                }
+                if let Some(finally) = finally {
+                    self.within(Scope::Finally, |jumps| jumps.stmts(&finally.stmts));
+                }
+            }
+            StmtKind::Break(levels) | StmtKind::Continue(levels) => {
+                let levels = levels.unwrap_or(1) as usize;
+                let loops = self.path.iter().enumerate().rev();
+                let mut loops = loops.filter(|(_, s)| matches!(s, Scope::Loop(_)));
+                // too many levels are reported by the parser.
+                if let Some((index, _)) = loops.nth(levels - 1) {
+                    let path = &self.path[index..];
+                    if path.iter().any(|s| matches!(s, Scope::Finally(_))) {
+                        self.error("jump out of a finally block is disallowed", stmt.span);
+                    }
+                }
+            }
+            StmtKind::Goto(label) => self.gotos.push((label, self.path.clone())),
+            StmtKind::Label(label) => {
+                if self.labels.contains_key(label.name.as_str()) {
+                    self.error(
+                        format!("label '{}' already defined", label.name),
+                        label.span,
+                    );
+                } else {
+                    self.labels.insert(&label.name, self.path.clone());
+                }
+            }
+            StmtKind::Declare {
+                body: Some(body), ..
+            }
+            | StmtKind::Block(body) => self.stmts(&body.stmts),
+            StmtKind::Namespace {
+                body: Some(body), ..
+            } => self.stmts(&body.stmts),
+            _ => {}
+        }
+    }
+}
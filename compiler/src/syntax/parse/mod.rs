@@ -0,0 +1,476 @@
+use std::io::{Error, ErrorKind};
+
+use super::{
+    ast::{
+        expr::Expr,
+        keyword::Keyword,
+        name::{Ident, Name, NameKind},
+        stmt::Script,
+    },
+    diagnostic::Diagnostic,
+    experiments::{Experiment, Experiments},
+    lex::{
+        token::{Token, TokenType},
+        Lexer,
+    },
+    source::Span,
+};
+
+mod attribute;
+mod class;
+pub(crate) mod expr;
+mod function;
+mod jump;
+mod namespace;
+mod stmt;
+mod string;
+mod types;
+
+pub type PResult<T> = Result<T, Diagnostic>;
+
+/// Keywords that start a statement or class member, where skipping the tokens of a broken
+/// statement stops so the next one can be parsed.
+const SYNC_KEYWORDS: [Keyword; 24] = [
+    Keyword::Abstract,
+    Keyword::Class,
+    Keyword::Const,
+    Keyword::Declare,
+    Keyword::Do,
+    Keyword::Enum,
+    Keyword::Final,
+    Keyword::For,
+    Keyword::ForEach,
+    Keyword::Function,
+    Keyword::Global,
+    Keyword::GoTo,
+    Keyword::If,
+    Keyword::Interface,
+    Keyword::Namespace,
+    Keyword::Private,
+    Keyword::Protected,
+    Keyword::Public,
+    Keyword::Return,
+    Keyword::Switch,
+    Keyword::Trait,
+    Keyword::Try,
+    Keyword::Use,
+    Keyword::While,
+];
+
+/// A recursive descent parser over the tokens of a script.
+///
+/// Whitespace, comments and line endings are dropped before parsing, so every token the parser
+/// sees is significant. Token spans still point into the original script.
+///
+/// A syntax error abandons the statement or class member it is found in. The error is
+/// recorded, the rest of that statement is skipped and an `Error` node takes its place, so
+/// every error in a script is reported in one pass.
+pub struct Parser<'a> {
+    /// The text the token spans point into, starting at `offset` in the whole script.
+    text: &'a str,
+    offset: usize,
+    tokens: Vec<Token>,
+    pos: usize,
+    /// How many loops and switches enclose the current statement, within the current function.
+    loop_depth: u32,
+    /// The token position of the very first statement of the script, or of the statement
+    /// after a run of `declare` statements, where `strict_types` may be declared.
+    first_stmt: Option<usize>,
+    /// The errors recovered from so far, in the order they were found.
+    diagnostics: Vec<Diagnostic>,
+    /// The lexer error that cut the tokens short, reported after the errors before it.
+    lex_error: Option<Diagnostic>,
+    experiments: Experiments,
+}
+
+impl<'a> Parser<'a> {
+    /// Lexes the whole script, failing on the first lexer error. The script starts with
+    /// inline HTML, see `Lexer::new`.
+    pub fn new(script: &'a str) -> PResult<Self> {
+        Self::with_experiments(script, Experiments::default())
+    }
+
+    /// Lexes the whole script, accepting the syntax of the enabled `experiments` as well.
+    pub fn with_experiments(script: &'a str, experiments: Experiments) -> PResult<Self> {
+        Self::recovering(Lexer::new(script), script, 0, experiments).checked()
+    }
+
+    /// Creates a parser for PHP code without a `<?php` that starts at `offset` in a larger
+    /// script, such as the `{$expr}` of an interpolated string, so that spans still point into
+    /// the whole script.
+    pub(crate) fn with_offset(
+        text: &'a str,
+        offset: usize,
+        experiments: Experiments,
+    ) -> PResult<Self> {
+        Self::recovering(Lexer::snippet(text), text, offset, experiments).checked()
+    }
+
+    /// Fails with the lexer error that cut the tokens short, if any.
+    fn checked(mut self) -> PResult<Self> {
+        match self.lex_error.take() {
+            Some(error) => Err(error),
+            None => Ok(self),
+        }
+    }
+
+    /// Creates a parser over the tokens of `lexer`, which lexes `text`, that keeps going after
+    /// errors. A lexer error can not be recovered from, so only the tokens before it are
+    /// parsed.
+    fn recovering(
+        lexer: Lexer<'a>,
+        text: &'a str,
+        offset: usize,
+        experiments: Experiments,
+    ) -> Self {
+        let mut lexer = lexer.with_experiments(experiments);
+        let mut tokens = Vec::new();
+        let mut lex_error = None;
+        loop {
+            let start = lexer.offset();
+            match lexer.next() {
+                Ok(Some(mut token)) => {
+                    token.0 = match token.0 {
+                        TokenType::Whitespace
+                        | TokenType::Comment
+                        | TokenType::LF(_)
+                        | TokenType::OpenTag => continue,
+                        // as in PHP, `?>` ends a statement.
+                        TokenType::CloseTag => TokenType::EOS,
+                        kind => kind,
+                    };
+                    token.1 = Span::new(token.1.start + offset, token.1.end + offset);
+                    tokens.push(token);
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    let span = Span::new(start + offset, lexer.offset().max(start + 1) + offset);
+                    lex_error = Some(self::lex_error(e, span));
+                    break;
+                }
+            }
+        }
+
+        Self {
+            text,
+            offset,
+            tokens,
+            pos: 0,
+            loop_depth: 0,
+            first_stmt: None,
+            diagnostics: Vec::new(),
+            lex_error,
+            experiments,
+        }
+    }
+
+    /// Fails on the syntax of `experiment`, `what` at `span`, unless it is enabled.
+    pub(crate) fn require(&self, experiment: Experiment, what: &str, span: Span) -> PResult<()> {
+        match self.experiments.is_enabled(experiment) {
+            true => Ok(()),
+            false => Err(Diagnostic::error(experiment.required(what), span)),
+        }
+    }
+
+    /// Records an error, unless it is only there because a lexer error ended the tokens
+    /// early.
+    pub(crate) fn report(&mut self, error: Diagnostic) {
+        if self.lex_error.is_none() || !self.is_eof() {
+            self.diagnostics.push(error);
+        }
+    }
+
+    /// Reports a block, class or namespace body that is still open at the end of the script.
+    /// Every body around it ends there too, so only the innermost one is reported.
+    pub(crate) fn unclosed(&mut self) {
+        let error = self.unexpected("`}`");
+        if self.diagnostics.last().map(|last| last.span) != Some(error.span) {
+            self.report(error);
+        }
+    }
+
+    /// Records an error and skips the rest of the statement or member that started at token
+    /// `start`, up to and including its `;`, up to the `}` closing the enclosing block, or
+    /// to the next keyword that starts a statement. At least one token is skipped when the
+    /// error is at the very start, so parsing always moves forward.
+    pub(crate) fn recover(&mut self, error: Diagnostic, start: usize) {
+        self.report(error);
+        if self.pos == start && !self.is_eof() {
+            self.bump();
+        }
+        // an error found once the statement was complete, such as a function without a
+        // body, leaves nothing to skip.
+        if matches!(
+            self.tokens.get(self.pos.wrapping_sub(1)).map(|t| &t.0),
+            Some(TokenType::EOS | TokenType::RightBrace)
+        ) {
+            return;
+        }
+
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match &token.0 {
+                TokenType::EOS if depth == 0 => {
+                    self.bump();
+                    return;
+                }
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => return,
+                TokenType::RightBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.bump();
+                        return;
+                    }
+                }
+                TokenType::Keyword(keyword) if depth == 0 && SYNC_KEYWORDS.contains(keyword) => {
+                    return
+                }
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    /// Fails unless every token has been consumed.
+    pub fn expect_eof(&self) -> PResult<()> {
+        if self.is_eof() {
+            Ok(())
+        } else {
+            Err(self.unexpected("end of file"))
+        }
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    pub(crate) fn nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    pub(crate) fn nth_kind(&self, n: usize) -> Option<&TokenType> {
+        self.nth(n).map(|token| &token.0)
+    }
+
+    /// Consumes the next token, which must exist.
+    pub(crate) fn bump(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        self.pos += 1;
+        token
+    }
+
+    pub(crate) fn at(&self, kind: &TokenType) -> bool {
+        self.nth_kind(0) == Some(kind)
+    }
+
+    pub(crate) fn at_op(&self, op: &str) -> bool {
+        is_op(self.peek(), op)
+    }
+
+    pub(crate) fn at_keyword(&self, keyword: Keyword) -> bool {
+        self.at(&TokenType::Keyword(keyword))
+    }
+
+    pub(crate) fn eat(&mut self, kind: &TokenType) -> Option<Token> {
+        self.at(kind).then(|| self.bump())
+    }
+
+    pub(crate) fn eat_op(&mut self, op: &str) -> Option<Token> {
+        self.at_op(op).then(|| self.bump())
+    }
+
+    /// Consumes a token of the given kind, `what` describes it in the error otherwise.
+    pub(crate) fn expect(&mut self, kind: &TokenType, what: &str) -> PResult<Token> {
+        self.eat(kind).ok_or_else(|| self.unexpected(what))
+    }
+
+    /// An `expected ..., found ...` error at the next token.
+    pub(crate) fn unexpected(&self, expected: &str) -> Diagnostic {
+        Diagnostic::error(
+            format!(
+                "expected {}, found {}",
+                expected,
+                self.describe(self.peek())
+            ),
+            self.current_span(),
+        )
+    }
+
+    fn describe(&self, token: Option<&Token>) -> String {
+        let Some(token) = token else {
+            return "end of file".to_string();
+        };
+        let text = self.text_of(token.1);
+        match token.0 {
+            TokenType::Keyword(_) => format!("keyword `{}`", text),
+            TokenType::Identifier | TokenType::ReservedIdent(_) => {
+                format!("identifier `{}`", text)
+            }
+            TokenType::NumericalLit(_) => format!("number `{}`", text),
+            TokenType::StringLit(_) => "string".to_string(),
+            _ => format!("`{}`", text),
+        }
+    }
+
+    /// The source text of a span.
+    pub(crate) fn text_of(&self, span: Span) -> &'a str {
+        &self.text[span.start - self.offset..span.end - self.offset]
+    }
+
+    /// The span of the next token, or an empty span at the end of the input.
+    pub(crate) fn current_span(&self) -> Span {
+        match self.peek() {
+            Some(token) => token.1,
+            None => {
+                let end = self.offset + self.text.len();
+                Span::new(end, end)
+            }
+        }
+    }
+
+    /// The span of the last consumed token.
+    pub(crate) fn prev_span(&self) -> Span {
+        match self.pos.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(token) => token.1,
+            None => Span::new(self.offset, self.offset),
+        }
+    }
+
+    /// A span from `start` until the end of the last consumed token.
+    pub(crate) fn span_from(&self, start: Span) -> Span {
+        start.to(self.prev_span())
+    }
+
+    /// Whether the next token can be used as a name, which includes keywords as PHP allows
+    /// them as member names and in namespaced names.
+    pub(crate) fn at_word(&self) -> bool {
+        self.peek().is_some_and(is_word)
+    }
+
+    /// An identifier that may also be a keyword, such as a method name or `Foo::class`.
+    pub(crate) fn parse_word(&mut self, what: &str) -> PResult<Ident> {
+        if !self.at_word() {
+            return Err(self.unexpected(what));
+        }
+        let token = self.bump();
+        Ok(Ident::new(self.text_of(token.1), token.1))
+    }
+
+    /// Whether a name starts at the next token, `Foo`, `\Foo` or `namespace\Foo`.
+    pub(crate) fn at_name(&self) -> bool {
+        match self.nth_kind(0) {
+            Some(TokenType::Identifier | TokenType::ReservedIdent(_)) => true,
+            Some(TokenType::Backslash) => self.nth(1).is_some_and(is_word),
+            Some(TokenType::Keyword(Keyword::Namespace)) => {
+                self.nth_kind(1) == Some(&TokenType::Backslash)
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses a possibly namespaced name. Keywords are only allowed after a `\`.
+    pub(crate) fn parse_name(&mut self) -> PResult<Name> {
+        let start = self.current_span();
+        let mut kind = NameKind::Unqualified;
+        if self.eat(&TokenType::Backslash).is_some() {
+            kind = NameKind::FullyQualified;
+        } else if self.at_keyword(Keyword::Namespace)
+            && self.nth_kind(1) == Some(&TokenType::Backslash)
+        {
+            self.pos += 2;
+            kind = NameKind::Relative;
+        }
+
+        let first = match self.nth_kind(0) {
+            Some(TokenType::Identifier | TokenType::ReservedIdent(_)) => self.bump(),
+            Some(_) if kind != NameKind::Unqualified && self.at_word() => self.bump(),
+            _ => return Err(self.unexpected("a name")),
+        };
+        let mut parts = vec![self.text_of(first.1).to_string()];
+        while self.at(&TokenType::Backslash) && self.nth(1).is_some_and(is_word) {
+            self.pos += 1;
+            let part = self.bump();
+            parts.push(self.text_of(part.1).to_string());
+        }
+
+        if kind == NameKind::Unqualified && parts.len() > 1 {
+            kind = NameKind::Qualified;
+        }
+        Ok(Name::new(parts, kind, self.span_from(start)))
+    }
+}
+
+/// Parses PHP code that holds a single expression.
+pub fn parse_expr(code: &str) -> PResult<Expr> {
+    let mut parser = Parser::with_offset(code, 0, Experiments::default())?;
+    let expr = parser.parse_expr()?;
+    parser.expect_eof()?;
+    Ok(expr)
+}
+
+/// Parses a whole script into its statements, failing on the first error. Like a PHP file,
+/// the script is inline HTML up to its first `<?php`.
+pub fn parse_script(script: &str) -> PResult<Script> {
+    Parser::new(script)?.parse_script()
+}
+
+/// Parses a whole script, recovering from syntax errors. The statements and class members
+/// that failed to parse are `Error` nodes in the script, and every error is returned in the
+/// order they appear.
+pub fn parse_script_recovering(script: &str) -> (Script, Vec<Diagnostic>) {
+    parse_script_with(script, Experiments::default())
+}
+
+/// Like `parse_script_recovering`, accepting the syntax of the enabled `experiments` as well.
+pub fn parse_script_with(script: &str, experiments: Experiments) -> (Script, Vec<Diagnostic>) {
+    let mut parser = Parser::recovering(Lexer::new(script), script, 0, experiments);
+    let script = parser.parse_script_partial();
+    (script, parser.diagnostics)
+}
+
+/// Parses PHP code that is not preceded by `<?php`, as `php -r` runs it, failing on the first
+/// error. Otherwise it is parsed like a whole script.
+pub fn parse_snippet(code: &str) -> PResult<Script> {
+    Parser::with_offset(code, 0, Experiments::default())?.parse_script()
+}
+
+/// Like `parse_snippet`, recovering from syntax errors as `parse_script_recovering` does.
+pub fn parse_snippet_recovering(code: &str) -> (Script, Vec<Diagnostic>) {
+    parse_snippet_with(code, Experiments::default())
+}
+
+/// Like `parse_snippet_recovering`, accepting the syntax of the enabled `experiments` as well.
+pub fn parse_snippet_with(code: &str, experiments: Experiments) -> (Script, Vec<Diagnostic>) {
+    let mut parser = Parser::recovering(Lexer::snippet(code), code, 0, experiments);
+    let script = parser.parse_script_partial();
+    (script, parser.diagnostics)
+}
+
+pub(crate) fn is_op(token: Option<&Token>, op: &str) -> bool {
+    token.is_some_and(|token| token.0 == TokenType::Operator && token.2.as_deref() == Some(op))
+}
+
+fn is_word(token: &Token) -> bool {
+    matches!(
+        token.0,
+        TokenType::Identifier
+            | TokenType::Keyword(_)
+            | TokenType::ReservedIdent(_)
+            | TokenType::Boolean
+    )
+}
+
+fn lex_error(error: Error, span: Span) -> Diagnostic {
+    match error.kind() {
+        // an unterminated string says what it is missing.
+        ErrorKind::UnexpectedEof if error.get_ref().is_none() => {
+            Diagnostic::error("unexpected end of file", span)
+        }
+        _ => Diagnostic::error(error.to_string(), span),
+    }
+}
@@ -0,0 +1,200 @@
+use crate::syntax::{
+    ast::{
+        keyword::Keyword,
+        name::{Ident, NameKind},
+        stmt::{Block, Stmt, StmtKind, UseItem, UseKind},
+    },
+    diagnostic::Diagnostic,
+    lex::token::TokenType,
+};
+
+use super::{PResult, Parser};
+
+impl Parser<'_> {
+    /// `namespace Foo;`, `namespace Foo { ... }` or `namespace { ... }`.
+    pub(crate) fn parse_namespace(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let name = if self.at(&TokenType::LeftBrace) {
+            None
+        } else {
+            let name = self.parse_name()?;
+            if name.kind != NameKind::Unqualified && name.kind != NameKind::Qualified {
+                return Err(Diagnostic::error(
+                    format!("'{}' is not a valid namespace name", name),
+                    name.span,
+                ));
+            }
+            Some(name)
+        };
+
+        if name.is_some() && self.eat(&TokenType::EOS).is_some() {
+            return Ok(StmtKind::Namespace { name, body: None });
+        }
+        let open = self.expect(&TokenType::LeftBrace, "`{` or `;`")?;
+        let mut stmts = Vec::new();
+        while self.eat(&TokenType::RightBrace).is_none() {
+            if self.is_eof() {
+                self.unclosed();
+                break;
+            }
+            if self.at_keyword(Keyword::Namespace)
+                && self.nth_kind(1) != Some(&TokenType::Backslash)
+            {
+                return Err(Diagnostic::error(
+                    "namespace declarations cannot be nested",
+                    self.current_span(),
+                ));
+            }
+            stmts.push(self.parse_stmt_recovering(true));
+        }
+
+        Ok(StmtKind::Namespace {
+            name,
+            body: Some(Block {
+                stmts,
+                span: self.span_from(open.1),
+            }),
+        })
+    }
+
+    /// `use A\B as C, D;`, `use function a\b;`, `use const A\B;` or the grouped form
+    /// `use A\{B, C as D, function e};`.
+    pub(crate) fn parse_use(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let kind = self.parse_use_kind().unwrap_or(UseKind::Normal);
+
+        let start = self.current_span();
+        let name = self.parse_name()?;
+        if name.kind == NameKind::Relative {
+            return Err(Diagnostic::error(
+                "`namespace\\` can not be used in a use statement",
+                name.span,
+            ));
+        }
+
+        if self.at(&TokenType::Backslash) && self.nth_kind(1) == Some(&TokenType::LeftBrace) {
+            self.pos += 2;
+            let mut uses = Vec::new();
+            while !self.at(&TokenType::RightBrace) {
+                let start = self.current_span();
+                let item_kind = self.parse_use_kind();
+                if let (Some(_), UseKind::Function | UseKind::Const) = (item_kind, kind) {
+                    return Err(Diagnostic::error(
+                        "the kind of an import can only be given once",
+                        self.prev_span(),
+                    ));
+                }
+                let name = self.parse_name()?;
+                if name.kind != NameKind::Unqualified && name.kind != NameKind::Qualified {
+                    return Err(Diagnostic::error(
+                        format!("expected a name relative to the group, found `{}`", name),
+                        name.span,
+                    ));
+                }
+                let alias = self.parse_use_alias()?;
+                uses.push(UseItem {
+                    name,
+                    alias,
+                    kind: item_kind,
+                    span: self.span_from(start),
+                });
+                if self.eat(&TokenType::Comma).is_none() {
+                    break;
+                }
+            }
+            self.expect(&TokenType::RightBrace, "`}`")?;
+            self.expect_semicolon()?;
+            return Ok(StmtKind::GroupUse {
+                prefix: name,
+                kind,
+                uses,
+            });
+        }
+
+        let alias = self.parse_use_alias()?;
+        let mut uses = vec![UseItem {
+            name,
+            alias,
+            kind: None,
+            span: self.span_from(start),
+        }];
+        while self.eat(&TokenType::Comma).is_some() {
+            let start = self.current_span();
+            let name = self.parse_name()?;
+            let alias = self.parse_use_alias()?;
+            uses.push(UseItem {
+                name,
+                alias,
+                kind: None,
+                span: self.span_from(start),
+            });
+        }
+        self.expect_semicolon()?;
+        Ok(StmtKind::Use { kind, uses })
+    }
+
+    fn parse_use_kind(&mut self) -> Option<UseKind> {
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::Keyword(Keyword::Function)) => UseKind::Function,
+            Some(TokenType::Keyword(Keyword::Const)) => UseKind::Const,
+            _ => return None,
+        };
+        self.bump();
+        Some(kind)
+    }
+
+    /// `as Alias` after an imported name.
+    fn parse_use_alias(&mut self) -> PResult<Option<Ident>> {
+        if self.eat(&TokenType::Keyword(Keyword::As)).is_none() {
+            return Ok(None);
+        }
+        if !self.at(&TokenType::Identifier) {
+            return Err(self.unexpected("an alias"));
+        }
+        self.parse_word("an alias").map(Some)
+    }
+}
+
+/// Checks where namespace declarations appear in a script. Braced and unbraced declarations
+/// can not be mixed, an unbraced declaration must come before any other code except
+/// `declare`, and no code may appear outside of braced declarations.
+pub(crate) fn check_namespaces(stmts: &[Stmt]) -> PResult<()> {
+    let mut braced = None;
+    let mut code_before = None;
+    for stmt in stmts {
+        let StmtKind::Namespace { body, .. } = &stmt.kind else {
+            if !matches!(stmt.kind, StmtKind::Declare { .. } | StmtKind::Nop) {
+                code_before = code_before.or(Some(stmt.span));
+                if braced == Some(true) {
+                    return Err(Diagnostic::error(
+                        "no code may exist outside of namespace {}",
+                        stmt.span,
+                    ));
+                }
+            }
+            continue;
+        };
+
+        let is_braced = body.is_some();
+        if braced.is_some_and(|braced| braced != is_braced) {
+            return Err(Diagnostic::error(
+                "cannot mix bracketed namespace declarations with unbracketed namespace declarations",
+                stmt.span,
+            ));
+        }
+        if let (Some(code), true) = (code_before, is_braced) {
+            return Err(Diagnostic::error(
+                "no code may exist outside of namespace {}",
+                code,
+            ));
+        }
+        if let (Some(_), None) = (code_before, braced) {
+            return Err(Diagnostic::error(
+                "namespace declaration statement has to be the very first statement or after any declare call in the script",
+                stmt.span,
+            ));
+        }
+        braced = Some(is_braced);
+    }
+    Ok(())
+}
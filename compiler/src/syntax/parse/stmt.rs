@@ -0,0 +1,805 @@
+use crate::syntax::{
+    ast::{
+        expr::{Expr, ExprKind, Literal},
+        keyword::Keyword,
+        stmt::{
+            Block, Catch, ConstItem, DeclareDirective, ElseIf, Script, StaticVar, Stmt, StmtKind,
+            SwitchCase,
+        },
+    },
+    diagnostic::Diagnostic,
+    experiments::Experiment,
+    lex::token::{Numeric, TokenType},
+    source::Span,
+};
+
+use super::{
+    expr::{into_list, is_assignable},
+    jump::check_jumps,
+    namespace::check_namespaces,
+    PResult, Parser,
+};
+
+impl Parser<'_> {
+    /// Parses every statement until the end of the input, failing on the first error.
+    pub fn parse_script(&mut self) -> PResult<Script> {
+        let script = self.parse_script_partial();
+        match std::mem::take(&mut self.diagnostics).into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(script),
+        }
+    }
+
+    /// Parses every statement until the end of the input, recovering from errors.
+    pub(crate) fn parse_script_partial(&mut self) -> Script {
+        let mut stmts: Vec<Stmt> = Vec::new();
+        while !self.is_eof() {
+            let declares = stmts
+                .iter()
+                .all(|stmt| matches!(stmt.kind, StmtKind::Declare { .. }));
+            self.first_stmt = declares.then_some(self.pos);
+            stmts.push(self.parse_stmt_recovering(true));
+        }
+        if let Err(error) = check_namespaces(&stmts) {
+            self.diagnostics.push(error);
+        }
+        self.diagnostics.extend(check_jumps(&stmts));
+        self.diagnostics.extend(self.lex_error.take());
+        Script {
+            stmts,
+            span: Span::new(self.offset, self.offset + self.text.len()),
+        }
+    }
+
+    /// A statement, or an `Error` statement covering the skipped tokens when it fails to
+    /// parse. `top` allows namespace declarations and imports.
+    pub(crate) fn parse_stmt_recovering(&mut self, top: bool) -> Stmt {
+        let pos = self.pos;
+        let start = self.current_span();
+        let stmt = match top {
+            true => self.parse_top_stmt(),
+            false => self.parse_stmt(),
+        };
+        stmt.unwrap_or_else(|error| {
+            self.recover(error, pos);
+            Stmt::new(StmtKind::Error, self.span_from(start))
+        })
+    }
+
+    /// `{ ... }`. A block left open at the end of the script is reported and closed there.
+    pub(crate) fn parse_block(&mut self) -> PResult<Block> {
+        let open = self.expect(&TokenType::LeftBrace, "`{`")?;
+        let mut stmts = Vec::new();
+        while self.eat(&TokenType::RightBrace).is_none() {
+            if self.is_eof() {
+                self.unclosed();
+                break;
+            }
+            stmts.push(self.parse_stmt_recovering(false));
+        }
+        Ok(Block {
+            stmts,
+            span: self.span_from(open.1),
+        })
+    }
+
+    /// A statement at the top level of a script or namespace, which may also be a namespace
+    /// declaration or an import.
+    pub(crate) fn parse_top_stmt(&mut self) -> PResult<Stmt> {
+        let start = self.current_span();
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::Keyword(Keyword::Namespace))
+                if self.nth_kind(1) != Some(&TokenType::Backslash) =>
+            {
+                self.parse_namespace()?
+            }
+            Some(TokenType::Keyword(Keyword::Use)) => self.parse_use()?,
+            Some(TokenType::Keyword(Keyword::Const)) => self.parse_const()?,
+            _ => return self.parse_stmt(),
+        };
+        Ok(Stmt::new(kind, self.span_from(start)))
+    }
+
+    pub(crate) fn parse_stmt(&mut self) -> PResult<Stmt> {
+        let start = self.current_span();
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::LeftBrace) => StmtKind::Block(self.parse_block()?),
+            Some(TokenType::AttributeStart) => self.parse_attributed_stmt(start)?,
+            Some(TokenType::EOS) => {
+                let end = self.bump();
+                // a `?>` that does not end a statement goes with the text after it.
+                match self.text_of(end.1).starts_with("?>") && self.at(&TokenType::InlineHtml) {
+                    true => StmtKind::InlineHtml(self.bump().2.unwrap_or_default()),
+                    false => StmtKind::Nop,
+                }
+            }
+            Some(TokenType::InlineHtml) => StmtKind::InlineHtml(self.bump().2.unwrap_or_default()),
+            Some(TokenType::Keyword(keyword)) => match keyword {
+                Keyword::If => self.parse_if()?,
+                Keyword::While => self.parse_while()?,
+                Keyword::Do => self.parse_do_while()?,
+                Keyword::For => self.parse_for()?,
+                Keyword::ForEach => self.parse_foreach()?,
+                Keyword::Switch => self.parse_switch()?,
+                Keyword::Try => self.parse_try()?,
+                Keyword::Break | Keyword::Continue => self.parse_break_continue()?,
+                Keyword::Return => {
+                    self.bump();
+                    let value = if self.at(&TokenType::EOS) {
+                        None
+                    } else {
+                        Some(self.parse_expr()?)
+                    };
+                    self.expect_semicolon()?;
+                    StmtKind::Return(value)
+                }
+                Keyword::Global => {
+                    self.bump();
+                    let mut vars = vec![self.parse_variable()?];
+                    while self.eat(&TokenType::Comma).is_some() {
+                        vars.push(self.parse_variable()?);
+                    }
+                    self.expect_semicolon()?;
+                    StmtKind::Global(vars)
+                }
+                Keyword::Static if self.nth_kind(1) == Some(&TokenType::Variable) => {
+                    self.parse_static_vars()?
+                }
+                Keyword::Declare => self.parse_declare()?,
+                Keyword::Const => {
+                    let what = "constants declared inside functions and blocks";
+                    self.require(Experiment::LocalConst, what, start)?;
+                    self.parse_const()?
+                }
+                Keyword::GoTo => {
+                    self.bump();
+                    if !self.at(&TokenType::Identifier) {
+                        return Err(self.unexpected("a label"));
+                    }
+                    let label = self.parse_word("a label")?;
+                    self.expect_semicolon()?;
+                    StmtKind::Goto(label)
+                }
+                Keyword::Namespace if self.nth_kind(1) != Some(&TokenType::Backslash) => {
+                    return Err(self.top_level_only(start))
+                }
+                Keyword::Use => return Err(self.top_level_only(start)),
+                Keyword::Function if self.at_function_decl(0) => {
+                    self.bump();
+                    StmtKind::Function(self.parse_function_decl(start)?)
+                }
+                Keyword::Async if self.at_function_decl(1) => {
+                    self.bump();
+                    self.bump();
+                    let mut function = self.parse_function_decl(start)?;
+                    function.is_async = true;
+                    StmtKind::Function(function)
+                }
+                Keyword::Abstract
+                | Keyword::Final
+                | Keyword::ReadOnly
+                | Keyword::Class
+                | Keyword::Interface
+                | Keyword::Trait
+                | Keyword::Enum => StmtKind::ClassLike(self.parse_class_like()?),
+                Keyword::Else
+                | Keyword::Elseif
+                | Keyword::EndIf
+                | Keyword::EndWhile
+                | Keyword::EndFor
+                | Keyword::EndForEach
+                | Keyword::EndSwitch
+                | Keyword::EndDeclare
+                | Keyword::Case
+                | Keyword::Default => return Err(self.unexpected("a statement")),
+                _ => self.parse_expr_stmt()?,
+            },
+            Some(TokenType::Identifier) if self.nth_kind(1) == Some(&TokenType::Colon) => {
+                let label = self.parse_word("a label")?;
+                self.bump();
+                StmtKind::Label(label)
+            }
+            Some(TokenType::Identifier) if self.text_of(start).eq_ignore_ascii_case("echo") => {
+                self.parse_echo()?
+            }
+            Some(TokenType::EchoTag) => self.parse_echo()?,
+            Some(TokenType::Identifier)
+                if self.text_of(start).eq_ignore_ascii_case("unset")
+                    && self.nth_kind(1) == Some(&TokenType::LeftParenthesis) =>
+            {
+                self.bump();
+                let vars = self.parse_construct_args()?;
+                if let Some(var) = vars.iter().find(|var| !is_assignable(var, false)) {
+                    return Err(Diagnostic::error("cannot unset this expression", var.span));
+                }
+                self.expect_semicolon()?;
+                StmtKind::Unset(vars)
+            }
+            _ => self.parse_expr_stmt()?,
+        };
+
+        Ok(Stmt::new(kind, self.span_from(start)))
+    }
+
+    /// A function or class-like declaration after its attributes. Closures can have
+    /// attributes too, so anything else is parsed again as an expression statement.
+    fn parse_attributed_stmt(&mut self, start: Span) -> PResult<StmtKind> {
+        let pos = self.pos;
+        let attributes = self.parse_attributes()?;
+        let kind = match self.nth_kind(0) {
+            Some(TokenType::Keyword(Keyword::Function)) if self.at_function_decl(0) => {
+                self.bump();
+                let mut function = self.parse_function_decl(start)?;
+                function.attributes = attributes;
+                StmtKind::Function(function)
+            }
+            Some(TokenType::Keyword(Keyword::Async)) if self.at_function_decl(1) => {
+                self.bump();
+                self.bump();
+                let mut function = self.parse_function_decl(start)?;
+                function.is_async = true;
+                function.attributes = attributes;
+                StmtKind::Function(function)
+            }
+            Some(TokenType::Keyword(
+                Keyword::Abstract
+                | Keyword::Final
+                | Keyword::ReadOnly
+                | Keyword::Class
+                | Keyword::Interface
+                | Keyword::Trait
+                | Keyword::Enum,
+            )) => {
+                let mut class = self.parse_class_like()?;
+                class.attributes = attributes;
+                class.span = self.span_from(start);
+                StmtKind::ClassLike(class)
+            }
+            _ => {
+                self.pos = pos;
+                self.parse_expr_stmt()?
+            }
+        };
+        Ok(kind)
+    }
+
+    /// Whether the `n`th token starts a named function, `function foo` or `function &foo`,
+    /// rather than a closure.
+    fn at_function_decl(&self, n: usize) -> bool {
+        self.nth_kind(n) == Some(&TokenType::Keyword(Keyword::Function))
+            && (self.nth_kind(n + 1) == Some(&TokenType::Identifier)
+                || super::is_op(self.nth(n + 1), "&")
+                    && self.nth_kind(n + 2) == Some(&TokenType::Identifier))
+    }
+
+    /// A `namespace` or `use` declaration inside a block, where they are not allowed.
+    fn top_level_only(&self, start: Span) -> Diagnostic {
+        let what = match self.at_keyword(Keyword::Use) {
+            true => "use statements",
+            false => "namespace declarations",
+        };
+        Diagnostic::error(
+            format!("{} are only allowed at the top level of a script", what),
+            start,
+        )
+    }
+
+    /// `echo $a, $b;`, or `<?= $a ?>` which is the same.
+    fn parse_echo(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let mut exprs = vec![self.parse_expr()?];
+        while self.eat(&TokenType::Comma).is_some() {
+            exprs.push(self.parse_expr()?);
+        }
+        self.expect_semicolon()?;
+        Ok(StmtKind::Echo(exprs))
+    }
+
+    pub(crate) fn expect_semicolon(&mut self) -> PResult<()> {
+        self.expect(&TokenType::EOS, "`;`")?;
+        Ok(())
+    }
+
+    fn parse_expr_stmt(&mut self) -> PResult<StmtKind> {
+        let expr = self.parse_expr()?;
+        self.expect_semicolon()?;
+        Ok(StmtKind::Expr(expr))
+    }
+
+    /// `(expr)`, the condition of a control structure.
+    fn parse_condition(&mut self) -> PResult<Expr> {
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let cond = self.parse_expr()?;
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+        Ok(cond)
+    }
+
+    /// The body of a control structure, either a block or a single statement, which is kept
+    /// as a block of its own.
+    fn parse_body(&mut self) -> PResult<Block> {
+        if self.at(&TokenType::LeftBrace) {
+            return self.parse_block();
+        }
+        let stmt = self.parse_stmt()?;
+        Ok(Block {
+            span: stmt.span,
+            stmts: vec![stmt],
+        })
+    }
+
+    /// The statements of an alternative syntax body, after its `:` and until one of the
+    /// keywords that may end it, which is left for the caller.
+    fn parse_alt_block(&mut self, ends: &[Keyword]) -> PResult<Block> {
+        let start = self.prev_span();
+        let mut stmts = Vec::new();
+        while !ends.iter().any(|end| self.at_keyword(*end)) {
+            if self.is_eof() {
+                return Err(self.unexpected(&format!("`{}`", ends[ends.len() - 1])));
+            }
+            stmts.push(self.parse_stmt_recovering(false));
+        }
+        Ok(Block {
+            stmts,
+            span: self.span_from(start),
+        })
+    }
+
+    /// `endwhile;` and the like, closing an alternative syntax body.
+    fn expect_end(&mut self, end: Keyword) -> PResult<()> {
+        self.expect(&TokenType::Keyword(end), &format!("`{}`", end))?;
+        self.expect_semicolon()
+    }
+
+    /// The body of a loop or `switch`, which `break` and `continue` may leave.
+    fn in_loop<T>(&mut self, parse: impl FnOnce(&mut Self) -> PResult<T>) -> PResult<T> {
+        self.loop_depth += 1;
+        let result = parse(self);
+        self.loop_depth -= 1;
+        result
+    }
+
+    /// The body of a loop, `stmt` or `: ... end;`.
+    fn parse_loop_body(&mut self, end: Keyword) -> PResult<Block> {
+        self.in_loop(|p| {
+            if p.eat(&TokenType::Colon).is_none() {
+                return p.parse_body();
+            }
+            let body = p.parse_alt_block(&[end])?;
+            p.expect_end(end)?;
+            Ok(body)
+        })
+    }
+
+    /// `if (a) ... elseif (b) ... else ...`, or the alternative syntax closed by `endif;`.
+    /// `else if` is an `else` holding another `if`.
+    fn parse_if(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let cond = self.parse_condition()?;
+        if self.eat(&TokenType::Colon).is_some() {
+            return self.parse_alt_if(cond);
+        }
+
+        let then = self.parse_body()?;
+        let mut elseifs = Vec::new();
+        while self.at_keyword(Keyword::Elseif) {
+            let start = self.bump().1;
+            let cond = self.parse_condition()?;
+            let body = self.parse_body()?;
+            elseifs.push(ElseIf {
+                cond,
+                body,
+                span: self.span_from(start),
+            });
+        }
+        let otherwise = match self.eat(&TokenType::Keyword(Keyword::Else)) {
+            Some(_) => Some(self.parse_body()?),
+            None => None,
+        };
+
+        Ok(StmtKind::If {
+            cond,
+            then,
+            elseifs,
+            otherwise,
+        })
+    }
+
+    fn parse_alt_if(&mut self, cond: Expr) -> PResult<StmtKind> {
+        const ENDS: [Keyword; 3] = [Keyword::Elseif, Keyword::Else, Keyword::EndIf];
+        let then = self.parse_alt_block(&ENDS)?;
+        let mut elseifs = Vec::new();
+        while self.at_keyword(Keyword::Elseif) {
+            let start = self.bump().1;
+            let cond = self.parse_condition()?;
+            self.expect(&TokenType::Colon, "`:`")?;
+            let body = self.parse_alt_block(&ENDS)?;
+            elseifs.push(ElseIf {
+                cond,
+                body,
+                span: self.span_from(start),
+            });
+        }
+        let otherwise = match self.eat(&TokenType::Keyword(Keyword::Else)) {
+            Some(_) => {
+                self.expect(&TokenType::Colon, "`:`")?;
+                Some(self.parse_alt_block(&[Keyword::EndIf])?)
+            }
+            None => None,
+        };
+        self.expect_end(Keyword::EndIf)?;
+
+        Ok(StmtKind::If {
+            cond,
+            then,
+            elseifs,
+            otherwise,
+        })
+    }
+
+    fn parse_while(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let cond = self.parse_condition()?;
+        let body = self.parse_loop_body(Keyword::EndWhile)?;
+        Ok(StmtKind::While { cond, body })
+    }
+
+    /// `do ... while (cond);`, which has no alternative syntax.
+    fn parse_do_while(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let body = self.in_loop(Self::parse_body)?;
+        self.expect(&TokenType::Keyword(Keyword::While), "`while`")?;
+        let cond = self.parse_condition()?;
+        self.expect_semicolon()?;
+        Ok(StmtKind::DoWhile { body, cond })
+    }
+
+    /// `for (init; cond; step)`, where each part may be empty.
+    fn parse_for(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let init = self.parse_for_exprs(TokenType::EOS, "`;`")?;
+        let cond = self.parse_for_exprs(TokenType::EOS, "`;`")?;
+        let step = self.parse_for_exprs(TokenType::RightParenthesis, "`)`")?;
+        let body = self.parse_loop_body(Keyword::EndFor)?;
+        Ok(StmtKind::For {
+            init,
+            cond,
+            step,
+            body,
+        })
+    }
+
+    fn parse_for_exprs(&mut self, end: TokenType, what: &str) -> PResult<Vec<Expr>> {
+        let mut exprs = Vec::new();
+        if self.eat(&end).is_some() {
+            return Ok(exprs);
+        }
+        loop {
+            exprs.push(self.parse_expr()?);
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&end, what)?;
+        Ok(exprs)
+    }
+
+    /// `foreach ($subject as $value)`, `foreach ($subject as $key => &$value)` or with a
+    /// destructured value, `foreach ($subject as [$a, $b])`.
+    fn parse_foreach(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let subject = self.parse_expr()?;
+        self.expect(&TokenType::Keyword(Keyword::As), "`as`")?;
+
+        let mut key = None;
+        let mut by_ref = self.eat_op("&").is_some();
+        let mut value = self.parse_expr()?;
+        if !by_ref && self.eat_op("=>").is_some() {
+            if !is_assignable(&value, false) {
+                return Err(Diagnostic::error(
+                    "the key of a foreach must be a variable",
+                    value.span,
+                ));
+            }
+            key = Some(Box::new(value));
+            by_ref = self.eat_op("&").is_some();
+            value = self.parse_expr()?;
+        }
+        if !is_assignable(&value, !by_ref) {
+            return Err(Diagnostic::error(
+                "the value of a foreach must be a variable or a list",
+                value.span,
+            ));
+        }
+        let value = into_list(value);
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+
+        let body = self.parse_loop_body(Keyword::EndForEach)?;
+        Ok(StmtKind::Foreach {
+            subject,
+            key,
+            value,
+            by_ref,
+            body,
+        })
+    }
+
+    /// `switch (subject) { case a: ... default: ... }` or the alternative syntax closed by
+    /// `endswitch;`. Cases may also end with `;`.
+    fn parse_switch(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let subject = self.parse_condition()?;
+        let alt = self.eat(&TokenType::Colon).is_some();
+        if !alt {
+            self.expect(&TokenType::LeftBrace, "`{` or `:`")?;
+        }
+        let end = match alt {
+            true => TokenType::Keyword(Keyword::EndSwitch),
+            false => TokenType::RightBrace,
+        };
+        self.eat(&TokenType::EOS);
+
+        let cases = self.in_loop(|p| {
+            let mut cases: Vec<SwitchCase> = Vec::new();
+            while !p.at(&end) {
+                let start = p.current_span();
+                let cond = match p.nth_kind(0) {
+                    Some(TokenType::Keyword(Keyword::Case)) => {
+                        p.bump();
+                        Some(p.parse_expr()?)
+                    }
+                    Some(TokenType::Keyword(Keyword::Default)) => {
+                        p.bump();
+                        if cases.iter().any(|case| case.cond.is_none()) {
+                            return Err(Diagnostic::error(
+                                "switch statements may only contain one default clause",
+                                start,
+                            ));
+                        }
+                        None
+                    }
+                    _ if alt => return Err(p.unexpected("`case`, `default` or `endswitch`")),
+                    _ => return Err(p.unexpected("`case`, `default` or `}`")),
+                };
+                if p.eat(&TokenType::Colon).is_none() {
+                    p.expect(&TokenType::EOS, "`:`")?;
+                }
+
+                let mut body = Vec::new();
+                while !p.at(&end) && !p.at_keyword(Keyword::Case) && !p.at_keyword(Keyword::Default)
+                {
+                    if p.is_eof() {
+                        return Err(p.unexpected(if alt { "`endswitch`" } else { "`}`" }));
+                    }
+                    body.push(p.parse_stmt_recovering(false));
+                }
+                cases.push(SwitchCase {
+                    cond,
+                    body,
+                    span: p.span_from(start),
+                });
+            }
+            Ok(cases)
+        })?;
+
+        self.bump();
+        if alt {
+            self.expect_semicolon()?;
+        }
+        Ok(StmtKind::Switch { subject, cases })
+    }
+
+    /// `try { ... } catch (A | B $e) { ... } finally { ... }`. The variable of a catch may be
+    /// left out since PHP 8, and at least one catch or the finally is required.
+    fn parse_try(&mut self) -> PResult<StmtKind> {
+        let token = self.bump();
+        let body = self.parse_block()?;
+
+        let mut catches = Vec::new();
+        while self.at_keyword(Keyword::Catch) {
+            let start = self.bump().1;
+            self.expect(&TokenType::LeftParenthesis, "`(`")?;
+            let mut types = vec![self.parse_name()?];
+            while self.eat_op("|").is_some() {
+                types.push(self.parse_name()?);
+            }
+            let var = match self.at(&TokenType::Variable) {
+                true => Some(self.parse_var_name()?),
+                false => None,
+            };
+            self.expect(&TokenType::RightParenthesis, "`)`")?;
+            let body = self.parse_block()?;
+            catches.push(Catch {
+                types,
+                var,
+                body,
+                span: self.span_from(start),
+            });
+        }
+
+        let finally = match self.eat(&TokenType::Keyword(Keyword::Finally)) {
+            Some(_) => Some(self.parse_block()?),
+            None => None,
+        };
+        if catches.is_empty() && finally.is_none() {
+            return Err(Diagnostic::error(
+                "cannot use try without catch or finally",
+                self.span_from(token.1),
+            ));
+        }
+        Ok(StmtKind::Try {
+            body,
+            catches,
+            finally,
+        })
+    }
+
+    /// `break;`, `continue 2;` and the like, checked against the enclosing loops.
+    fn parse_break_continue(&mut self) -> PResult<StmtKind> {
+        let token = self.bump();
+        let keyword = match token.0 {
+            TokenType::Keyword(keyword) => keyword,
+            _ => unreachable!("only called at `break` or `continue`"),
+        };
+
+        let levels = match self.nth_kind(0) {
+            Some(TokenType::NumericalLit(Numeric::Int(n))) if *n >= 1 => {
+                let n = *n;
+                self.bump();
+                Some(u32::try_from(n).unwrap_or(u32::MAX))
+            }
+            Some(TokenType::EOS) => None,
+            _ => {
+                return Err(Diagnostic::error(
+                    format!("'{}' operator accepts only positive integers", keyword),
+                    self.current_span(),
+                ))
+            }
+        };
+        self.expect_semicolon()?;
+
+        let span = self.span_from(token.1);
+        if self.loop_depth == 0 {
+            return Err(Diagnostic::error(
+                format!("'{}' not in the 'loop' or 'switch' context", keyword),
+                span,
+            ));
+        }
+        if let Some(levels) = levels.filter(|levels| *levels > self.loop_depth) {
+            return Err(Diagnostic::error(
+                format!(
+                    "cannot '{}' {} level{}",
+                    keyword,
+                    levels,
+                    if levels == 1 { "" } else { "s" }
+                ),
+                span,
+            ));
+        }
+
+        Ok(match keyword {
+            Keyword::Break => StmtKind::Break(levels),
+            _ => StmtKind::Continue(levels),
+        })
+    }
+
+    /// `static $a = 1, $b;`
+    fn parse_static_vars(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let mut vars = Vec::new();
+        loop {
+            let start = self.current_span();
+            let name = self.parse_var_name()?;
+            let default = match self.eat_op("=") {
+                Some(_) => Some(self.parse_expr()?),
+                None => None,
+            };
+            vars.push(StaticVar {
+                name,
+                default,
+                span: self.span_from(start),
+            });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect_semicolon()?;
+        Ok(StmtKind::Static(vars))
+    }
+
+    /// `const A = 1, B = 2;` outside of a class.
+    fn parse_const(&mut self) -> PResult<StmtKind> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            let start = self.current_span();
+            let name = self.parse_word("a constant name")?;
+            if self.eat_op("=").is_none() {
+                return Err(self.unexpected("`=`"));
+            }
+            let value = self.parse_expr()?;
+            items.push(ConstItem {
+                name,
+                value,
+                span: self.span_from(start),
+            });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect_semicolon()?;
+        Ok(StmtKind::Const(items))
+    }
+
+    /// `declare(ticks=1);`, `declare(ticks=1) { ... }` or `declare(ticks=1): ... enddeclare;`
+    fn parse_declare(&mut self) -> PResult<StmtKind> {
+        let first = self.first_stmt == Some(self.pos);
+        self.bump();
+        self.expect(&TokenType::LeftParenthesis, "`(`")?;
+        let mut directives = Vec::new();
+        loop {
+            let start = self.current_span();
+            let name = self.parse_word("a directive name")?;
+            if self.eat_op("=").is_none() {
+                return Err(self.unexpected("`=`"));
+            }
+            let value = self.parse_expr()?;
+            directives.push(DeclareDirective {
+                name,
+                value,
+                span: self.span_from(start),
+            });
+            if self.eat(&TokenType::Comma).is_none() {
+                break;
+            }
+        }
+        self.expect(&TokenType::RightParenthesis, "`)`")?;
+
+        let body = if self.eat(&TokenType::EOS).is_some() {
+            None
+        } else if self.eat(&TokenType::Colon).is_some() {
+            let body = self.parse_alt_block(&[Keyword::EndDeclare])?;
+            self.expect_end(Keyword::EndDeclare)?;
+            Some(body)
+        } else {
+            Some(self.parse_body()?)
+        };
+        for directive in &directives {
+            self.check_directive(directive, first, body.is_some());
+        }
+        Ok(StmtKind::Declare { directives, body })
+    }
+
+    /// Reports a directive whose value or place is not allowed. Unknown directives are
+    /// ignored, as PHP only warns about them.
+    fn check_directive(&mut self, directive: &DeclareDirective, first: bool, block: bool) {
+        let value = match &directive.value.kind {
+            ExprKind::Literal(literal) => Some(literal),
+            _ => None,
+        };
+        let message = match directive.name.name.to_ascii_lowercase().as_str() {
+            "strict_types" if !first => {
+                "strict_types declaration must be the very first statement in the script"
+            }
+            "strict_types" if block => "strict_types declaration must not use block mode",
+            "strict_types" => match value {
+                Some(Literal::Numeric(Numeric::Int(0 | 1))) => return,
+                _ => "strict_types declaration must have 0 or 1 as its value",
+            },
+            "ticks" => match value {
+                Some(Literal::Numeric(Numeric::Int(_))) => return,
+                _ => "ticks declaration must have an integer as its value",
+            },
+            "encoding" if !first => {
+                "encoding declaration pragma must be the very first statement in the script"
+            }
+            "encoding" => match value {
+                Some(Literal::String(_)) => return,
+                _ => "encoding must be a literal",
+            },
+            _ => return,
+        };
+        self.report(Diagnostic::error(message, directive.span));
+    }
+}
@@ -0,0 +1,386 @@
+use crate::syntax::{
+    ast::{
+        expr::{Expr, ExprKind, Literal, MemberName, StringPart},
+        name::Ident,
+    },
+    diagnostic::Diagnostic,
+    experiments::Experiments,
+    lex::{
+        cursor::{is_line_ending, line_ending_len},
+        is_word_char, is_word_start,
+        token::{Numeric, StringType, Token, TokenType},
+    },
+    source::Span,
+};
+
+use super::{PResult, Parser};
+
+impl Parser<'_> {
+    /// Turns a string token into a literal, or an interpolated string when a double quoted
+    /// string, heredoc or backtick string contains variables.
+    pub(crate) fn parse_string(&mut self, token: Token) -> PResult<Expr> {
+        let Token(TokenType::StringLit(variant), span, body) = token else {
+            return Err(Diagnostic::error("expected a string", token.1));
+        };
+        let body = body.unwrap_or_default();
+
+        let kind = match variant {
+            StringType::Single => ExprKind::Literal(Literal::String(unescape_single(&body))),
+            StringType::NowDoc => ExprKind::Literal(Literal::String(body.into_bytes())),
+            StringType::Double | StringType::HereDoc | StringType::Backtick => {
+                let offsets = match variant {
+                    StringType::HereDoc => {
+                        let text = &self.text[span.start - self.offset..span.end - self.offset];
+                        Offsets::heredoc(text, span.start)
+                    }
+                    _ => Offsets(vec![(0, span.start + 1)]),
+                };
+                let mut parts = interpolate(&body, variant, &offsets, self.experiments)?;
+                if variant == StringType::Backtick {
+                    ExprKind::ShellExec(parts)
+                } else if parts.len() <= 1 && !matches!(parts.first(), Some(StringPart::Expr(_))) {
+                    let text = match parts.pop() {
+                        Some(StringPart::Literal(text, _)) => text,
+                        _ => Vec::new(),
+                    };
+                    ExprKind::Literal(Literal::String(text))
+                } else {
+                    ExprKind::Interpolated {
+                        kind: variant,
+                        parts,
+                    }
+                }
+            }
+        };
+
+        Ok(Expr::new(kind, span))
+    }
+}
+
+/// Processes the two escape sequences of single quoted strings, `\\` and `\'`.
+fn unescape_single(body: &str) -> Vec<u8> {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next @ ('\\' | '\'')) = chars.peek() {
+                out.push(next);
+                chars.next();
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out.into_bytes()
+}
+
+/// Where the text of a string body is in the script, as the offsets in the body and in the
+/// script where each line starts. A heredoc has the indentation removed from every line, so
+/// its lines start further into the script than into the body.
+struct Offsets(Vec<(usize, usize)>);
+
+impl Offsets {
+    /// The lines of the heredoc whose text, from `<<<` to the closing identifier, starts at
+    /// `start` in the script.
+    fn heredoc(text: &str, start: usize) -> Self {
+        let header = text.find(is_line_ending).unwrap_or(text.len());
+        let mut offset = header + line_ending_len(&text[header..]).unwrap_or(0);
+        let closing = text.rfind(is_line_ending).map_or(text.len(), |end| end + 1);
+        let indent = text[closing..].len() - text[closing..].trim_start_matches([' ', '\t']).len();
+
+        let mut lines = Vec::new();
+        let mut body = 0;
+        while offset < closing {
+            let line = &text[offset..closing];
+            let len = line.find(is_line_ending).unwrap_or(line.len());
+            let ending = line_ending_len(&line[len..]).unwrap_or(0);
+            let indent = indent.min(len);
+            lines.push((body, start + offset + indent));
+            body += len - indent + ending;
+            offset += len + ending;
+        }
+        if lines.is_empty() {
+            lines.push((0, start + offset));
+        }
+        Self(lines)
+    }
+
+    /// The offset in the script of the offset `i` in the body.
+    fn at(&self, i: usize) -> usize {
+        let line = self.0.partition_point(|&(body, _)| body <= i).max(1) - 1;
+        let (body, script) = self.0[line];
+        script + i - body
+    }
+}
+
+/// Splits the body of a double quoted string, heredoc or backtick string into literal text and
+/// interpolated expressions.
+fn interpolate(
+    body: &str,
+    kind: StringType,
+    offsets: &Offsets,
+    experiments: Experiments,
+) -> PResult<Vec<StringPart>> {
+    let mut parts = Vec::new();
+    let mut literal = Vec::new();
+    // where the text of `literal` starts in the body
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < body.len() {
+        let rest = &body[i..];
+        if rest.starts_with('\\') {
+            i += unescape(rest, kind, &mut literal).map_err(|message| {
+                Diagnostic::error(message, Span::new(offsets.at(i), offsets.at(i + 2)))
+            })?;
+            continue;
+        }
+
+        let part = if rest.starts_with("{$") {
+            let end = closing_brace(rest).ok_or_else(|| {
+                Diagnostic::error(
+                    "unterminated `{$` in string",
+                    Span::new(offsets.at(i), offsets.at(i + 2)),
+                )
+            })?;
+            let expr = sub_expr(&rest[1..end], offsets.at(i + 1), experiments)?;
+            Some((expr, end + 1))
+        } else if rest.starts_with("${") {
+            let end = closing_brace(&rest[1..])
+                .map(|end| end + 1)
+                .ok_or_else(|| {
+                    Diagnostic::error(
+                        "unterminated `${` in string",
+                        Span::new(offsets.at(i), offsets.at(i + 2)),
+                    )
+                })?;
+            let inner = &rest[2..end];
+            let span = Span::new(offsets.at(i), offsets.at(i + end + 1));
+            let kind = if !inner.is_empty()
+                && inner.starts_with(is_word_start)
+                && inner.chars().all(is_word_char)
+            {
+                ExprKind::Variable(inner.to_string())
+            } else {
+                ExprKind::VariableVariable(Box::new(sub_expr(
+                    inner,
+                    offsets.at(i + 2),
+                    experiments,
+                )?))
+            };
+            Some((Expr::new(kind, span), end + 1))
+        } else if rest.starts_with('$') && rest[1..].starts_with(is_word_start) {
+            Some(simple_interpolation(rest, offsets.at(i))?)
+        } else {
+            None
+        };
+
+        match part {
+            Some((expr, len)) => {
+                if !literal.is_empty() {
+                    let span = Span::new(offsets.at(start), offsets.at(i));
+                    parts.push(StringPart::Literal(std::mem::take(&mut literal), span));
+                }
+                parts.push(StringPart::Expr(expr));
+                i += len;
+                start = i;
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                literal.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                i += c.len_utf8();
+            }
+        }
+    }
+
+    if !literal.is_empty() {
+        parts.push(StringPart::Literal(
+            literal,
+            Span::new(offsets.at(start), offsets.at(body.len())),
+        ));
+    }
+    Ok(parts)
+}
+
+/// Parses the expression of `{$expr}` or `${expr}`, which starts at `offset` in the script.
+fn sub_expr(text: &str, offset: usize, experiments: Experiments) -> PResult<Expr> {
+    let mut parser = Parser::with_offset(text, offset, experiments)?;
+    let expr = parser.parse_expr()?;
+    parser.expect_eof()?;
+    Ok(expr)
+}
+
+/// `$name`, optionally followed by a single `[offset]` or `->property`, returning the
+/// expression and the length it takes up in the string.
+fn simple_interpolation(rest: &str, offset: usize) -> PResult<(Expr, usize)> {
+    let name_len = 1 + rest[1..]
+        .find(|c| !is_word_char(c))
+        .unwrap_or(rest.len() - 1);
+    let variable = Expr::new(
+        ExprKind::Variable(rest[1..name_len].to_string()),
+        Span::new(offset, offset + name_len),
+    );
+    let after = &rest[name_len..];
+
+    if let Some(index) = after.strip_prefix('[') {
+        let Some(close) = index.find(']') else {
+            return Ok((variable, name_len));
+        };
+        let key = &index[..close];
+        let key_offset = offset + name_len + 1;
+        let key_span = Span::new(key_offset, key_offset + key.len());
+        let digits = key.strip_prefix('-').unwrap_or(key);
+        let key = if let Some(name) = key.strip_prefix('$') {
+            if !name.starts_with(is_word_start) || !name.chars().all(is_word_char) {
+                return Err(Diagnostic::error(
+                    "invalid variable in string offset",
+                    key_span,
+                ));
+            }
+            ExprKind::Variable(name.to_string())
+        } else if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            match key.parse() {
+                // `"$a[01]"` uses the string "01" as the key, like PHP.
+                Ok(n) if !(digits.len() > 1 && digits.starts_with('0')) => {
+                    ExprKind::Literal(Literal::Numeric(Numeric::Int(n)))
+                }
+                _ => ExprKind::Literal(Literal::String(key.into())),
+            }
+        } else if key.starts_with(is_word_start) && key.chars().all(is_word_char) {
+            ExprKind::Literal(Literal::String(key.into()))
+        } else {
+            return Err(Diagnostic::error(
+                format!("unexpected `{}` in string offset", key),
+                key_span,
+            ));
+        };
+
+        let len = name_len + close + 2;
+        let expr = Expr::new(
+            ExprKind::ArrayDim {
+                array: Box::new(variable),
+                index: Some(Box::new(Expr::new(key, key_span))),
+            },
+            Span::new(offset, offset + len),
+        );
+        return Ok((expr, len));
+    }
+
+    if let Some(property) = after.strip_prefix("->") {
+        if property.starts_with(is_word_start) {
+            let prop_len = property
+                .find(|c| !is_word_char(c))
+                .unwrap_or(property.len());
+            let prop_offset = offset + name_len + 2;
+            let len = name_len + 2 + prop_len;
+            let expr = Expr::new(
+                ExprKind::PropertyFetch {
+                    object: Box::new(variable),
+                    property: MemberName::Ident(Ident::new(
+                        &property[..prop_len],
+                        Span::new(prop_offset, prop_offset + prop_len),
+                    )),
+                    nullsafe: false,
+                },
+                Span::new(offset, offset + len),
+            );
+            return Ok((expr, len));
+        }
+    }
+
+    Ok((variable, name_len))
+}
+
+/// The index of the `}` closing the `{` that `text` starts with, skipping quoted strings.
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(_) if c == '\\' => {
+                chars.next();
+            }
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+/// Processes the escape sequence at the start of `rest`, returning how many bytes it used.
+/// Octal and `\x` escapes give a single byte, which need not be valid UTF-8 on its own.
+/// Unknown escape sequences are kept as they are.
+fn unescape(rest: &str, kind: StringType, out: &mut Vec<u8>) -> Result<usize, String> {
+    let Some(c) = rest[1..].chars().next() else {
+        out.push(b'\\');
+        return Ok(1);
+    };
+
+    let simple = match c {
+        'n' => Some(b'\n'),
+        't' => Some(b'\t'),
+        'r' => Some(b'\r'),
+        'v' => Some(b'\x0B'),
+        'e' => Some(b'\x1B'),
+        'f' => Some(b'\x0C'),
+        '\\' => Some(b'\\'),
+        '$' => Some(b'$'),
+        '"' if kind == StringType::Double => Some(b'"'),
+        '`' if kind == StringType::Backtick => Some(b'`'),
+        _ => None,
+    };
+    if let Some(byte) = simple {
+        out.push(byte);
+        return Ok(2);
+    }
+
+    match c {
+        '0'..='7' => {
+            let len = rest[1..]
+                .bytes()
+                .take(3)
+                .take_while(|b| (b'0'..=b'7').contains(b))
+                .count();
+            let value = u32::from_str_radix(&rest[1..1 + len], 8).unwrap_or_default();
+            out.push((value & 0xFF) as u8);
+            Ok(1 + len)
+        }
+        'x' if rest[2..].starts_with(|c: char| c.is_ascii_hexdigit()) => {
+            let len = rest[2..]
+                .bytes()
+                .take(2)
+                .take_while(u8::is_ascii_hexdigit)
+                .count();
+            let value = u8::from_str_radix(&rest[2..2 + len], 16).unwrap_or_default();
+            out.push(value);
+            Ok(2 + len)
+        }
+        'u' if rest[2..].starts_with('{') => {
+            let close = rest
+                .find('}')
+                .ok_or_else(|| "unterminated `\\u{` escape sequence".to_string())?;
+            let c = u32::from_str_radix(&rest[3..close], 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| "invalid UTF-8 codepoint escape sequence".to_string())?;
+            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            Ok(close + 1)
+        }
+        _ => {
+            out.push(b'\\');
+            Ok(1)
+        }
+    }
+}
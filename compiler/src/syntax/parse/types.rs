@@ -0,0 +1,246 @@
+use crate::syntax::{
+    ast::{
+        keyword::Keyword,
+        name::{Name, NameKind},
+        types::{BuiltinType, Type, TypeHint, TypeHintKind},
+    },
+    diagnostic::Diagnostic,
+    lex::token::TokenType,
+};
+
+use super::{is_op, PResult, Parser};
+
+impl Parser<'_> {
+    /// Whether a type starts at the next token, as types are optional everywhere they appear.
+    pub(crate) fn at_type(&self) -> bool {
+        self.at(&TokenType::QuestionMark)
+            || self.at(&TokenType::LeftParenthesis)
+            || self.at_keyword(Keyword::Static)
+            || self.at(&TokenType::Boolean)
+            || self.at_name()
+    }
+
+    /// `T`, `?T`, `A|B`, `A&B` or the DNF form `(A&B)|C`, rejecting the combinations PHP
+    /// refuses to compile.
+    pub(crate) fn parse_type(&mut self) -> PResult<TypeHint> {
+        let ty = self.parse_type_hint()?;
+        check_type(&ty)?;
+        Ok(ty)
+    }
+
+    fn parse_type_hint(&mut self) -> PResult<TypeHint> {
+        let start = self.current_span();
+        if self.eat(&TokenType::QuestionMark).is_some() {
+            let inner = self.parse_single_type()?;
+            return Ok(TypeHint {
+                kind: TypeHintKind::Nullable(Box::new(inner)),
+                span: self.span_from(start),
+            });
+        }
+
+        let (first, first_grouped) = self.parse_union_member()?;
+        if !self.at_op("|") {
+            return Ok(first);
+        }
+        let mut members = vec![(first, first_grouped)];
+        while self.eat_op("|").is_some() {
+            members.push(self.parse_union_member()?);
+        }
+        let mut types = Vec::with_capacity(members.len());
+        for (member, grouped) in members {
+            // PHP only accepts an intersection in a union in parentheses, `(A&B)|C`.
+            if !grouped && matches!(member.kind, TypeHintKind::Intersection(_)) {
+                return Err(Diagnostic::error(
+                    format!(
+                        "intersection type {} must be parenthesized in a union",
+                        member.to_type()
+                    ),
+                    member.span,
+                ));
+            }
+            types.push(member);
+        }
+        Ok(TypeHint {
+            kind: TypeHintKind::Union(types),
+            span: self.span_from(start),
+        })
+    }
+
+    /// A member of a union and whether it was written in parentheses.
+    fn parse_union_member(&mut self) -> PResult<(TypeHint, bool)> {
+        let grouped = self.at(&TokenType::LeftParenthesis);
+        Ok((self.parse_intersection_type()?, grouped))
+    }
+
+    /// A member of a union, `A`, `A&B` or `(A&B)`.
+    fn parse_intersection_type(&mut self) -> PResult<TypeHint> {
+        let start = self.current_span();
+        if self.eat(&TokenType::LeftParenthesis).is_some() {
+            let inner = self.parse_intersection_type()?;
+            self.expect(&TokenType::RightParenthesis, "`)`")?;
+            return Ok(inner);
+        }
+
+        let first = self.parse_single_type()?;
+        if !self.at_intersection() {
+            return Ok(first);
+        }
+        let mut types = vec![first];
+        while self.at_intersection() {
+            self.bump();
+            types.push(self.parse_single_type()?);
+        }
+        Ok(TypeHint {
+            kind: TypeHintKind::Intersection(types),
+            span: self.span_from(start),
+        })
+    }
+
+    /// An `&` that continues an intersection, rather than one that makes a parameter
+    /// by-reference, `A&B $x` against `A &$x` or `A &...$x`.
+    fn at_intersection(&self) -> bool {
+        self.at_op("&")
+            && !matches!(self.nth_kind(1), Some(TokenType::Variable))
+            && !is_op(self.nth(1), "...")
+    }
+
+    fn parse_single_type(&mut self) -> PResult<TypeHint> {
+        // `static`, `false` and `true` are not lexed as identifiers.
+        let name = if self.at_keyword(Keyword::Static) || self.at(&TokenType::Boolean) {
+            let token = self.bump();
+            let text = self.text_of(token.1).to_string();
+            Name::new(vec![text], NameKind::Unqualified, token.1)
+        } else if self.at_name() {
+            self.parse_name()?
+        } else {
+            return Err(self.unexpected("a type"));
+        };
+        Ok(TypeHint {
+            span: name.span,
+            kind: TypeHintKind::Named(name),
+        })
+    }
+}
+
+/// The checks PHP performs on a type declaration on its own, wherever it is written.
+fn check_type(hint: &TypeHint) -> PResult<()> {
+    match &hint.kind {
+        TypeHintKind::Named(_) => Ok(()),
+        TypeHintKind::Nullable(inner) => match inner.to_type() {
+            Type::Builtin(BuiltinType::Mixed) => Err(Diagnostic::error(
+                "type mixed cannot be marked as nullable since mixed already includes null",
+                hint.span,
+            )),
+            Type::Builtin(BuiltinType::Null) => Err(Diagnostic::error(
+                "null cannot be marked as nullable",
+                hint.span,
+            )),
+            Type::Builtin(builtin) if builtin.is_standalone() => Err(Diagnostic::error(
+                format!(
+                    "type {} can only be used as a standalone type",
+                    builtin.as_str()
+                ),
+                hint.span,
+            )),
+            _ => Ok(()),
+        },
+        TypeHintKind::Intersection(members) => {
+            let mut seen: Vec<Type> = Vec::new();
+            for member in members {
+                let ty = member.to_type();
+                if !matches!(ty, Type::Class(_)) {
+                    return Err(Diagnostic::error(
+                        format!("type {} cannot be part of an intersection type", ty),
+                        member.span,
+                    ));
+                }
+                check_duplicate(&seen, &ty, member)?;
+                seen.push(ty);
+            }
+            Ok(())
+        }
+        TypeHintKind::Union(members) => {
+            let mut seen: Vec<Type> = Vec::new();
+            for member in members {
+                check_type(member)?;
+                let ty = member.to_type();
+                if let Type::Builtin(builtin) = ty {
+                    if builtin.is_standalone() {
+                        return Err(Diagnostic::error(
+                            format!("type {} can only be used as a standalone type", ty),
+                            member.span,
+                        ));
+                    }
+                }
+                check_duplicate(&seen, &ty, member)?;
+                seen.push(ty);
+            }
+            check_redundant_union(&seen, hint)
+        }
+    }
+}
+
+fn check_duplicate(seen: &[Type], ty: &Type, member: &TypeHint) -> PResult<()> {
+    // `A&B` and `B&A` are the same type, so both sides are compared in both directions.
+    let duplicate = seen.iter().any(|other| match (other, ty) {
+        (Type::Intersection(_), Type::Intersection(_)) => {
+            other.is_subtype_of(ty) && ty.is_subtype_of(other)
+        }
+        _ => other.to_string().eq_ignore_ascii_case(&ty.to_string()),
+    });
+    if duplicate {
+        return Err(Diagnostic::error(
+            format!("duplicate type {} is redundant", ty),
+            member.span,
+        ));
+    }
+    Ok(())
+}
+
+/// Unions whose members overlap, such as `bool|false`, `iterable|array` or `(A&B)|A`.
+fn check_redundant_union(types: &[Type], hint: &TypeHint) -> PResult<()> {
+    let union = Type::Union(types.to_vec());
+    let has = |builtin| types.contains(&Type::Builtin(builtin));
+    let message = if has(BuiltinType::True) && has(BuiltinType::False) {
+        format!(
+            "type {} contains both true and false, bool should be used instead",
+            union
+        )
+    } else if has(BuiltinType::Bool) && (has(BuiltinType::True) || has(BuiltinType::False)) {
+        let redundant = if has(BuiltinType::True) {
+            "true"
+        } else {
+            "false"
+        };
+        format!("duplicate type {} is redundant", redundant)
+    } else if has(BuiltinType::Iterable) && has(BuiltinType::Array) {
+        format!(
+            "type {} contains both iterable and array, which is redundant",
+            union
+        )
+    } else if has(BuiltinType::Object) && types.iter().any(|ty| matches!(ty, Type::Class(_))) {
+        format!(
+            "type {} contains both object and a class type, which is redundant",
+            union
+        )
+    } else {
+        let mut redundant = None;
+        for (i, narrow) in types.iter().enumerate() {
+            if let Some(wide) = types.iter().enumerate().find_map(|(j, wide)| {
+                (i != j && matches!(narrow, Type::Intersection(_)) && narrow.is_subtype_of(wide))
+                    .then_some(wide)
+            }) {
+                redundant = Some(format!(
+                    "type {} is redundant as it is more restrictive than type {}",
+                    narrow, wide
+                ));
+                break;
+            }
+        }
+        match redundant {
+            Some(message) => message,
+            None => return Ok(()),
+        }
+    };
+    Err(Diagnostic::error(message, hint.span))
+}
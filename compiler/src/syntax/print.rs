@@ -0,0 +1,1475 @@
+//! Code generation, which writes a tree back out as PHP source.
+//!
+//! The output is laid out from scratch following a `PrintStyle`, so comments, blank lines and
+//! the original formatting are not kept. Parentheses are not part of the tree either, they are
+//! added wherever an expression would otherwise be parsed differently, following the same
+//! binding powers as the parser. Parsing the output gives back the same tree, except that the
+//! alternative syntax of control structures is written with braces and bodies without braces
+//! gain them.
+
+use super::{
+    ast::{
+        attribute::AttributeGroup,
+        class::{AdaptationKind, ClassLike, ClassMember, MemberKind, Modifier},
+        expr::{
+            Arg, ArrayItem, BinaryOp, CallArgs, ClassRef, Expr, ExprKind, IncDecOp, Literal,
+            MatchArm, MemberName, StringPart, UnaryOp,
+        },
+        function::{ClosureUse, FunctionDecl, Param},
+        name::{Ident, Name},
+        stmt::{Block, ConstItem, Script, Stmt, StmtKind, UseItem, UseKind},
+        types::{TypeHint, TypeHintKind},
+    },
+    lex::{is_word_char, is_word_start, token::Numeric},
+    parse::expr::{binding_power, bp},
+};
+
+/// How the printer lays out the code it writes. The default follows PSR-12.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintStyle {
+    /// One level of indentation.
+    pub indent: String,
+    pub newline: String,
+    /// Puts the opening brace of classes, functions and methods on a line of its own, rather
+    /// than at the end of the declaration. Control structures, closures and anonymous classes
+    /// always keep it on the same line.
+    pub declaration_brace_on_new_line: bool,
+    /// Writes every array as `[...]` and every `list(...)` as `[...]`, rather than the way
+    /// they were written.
+    pub short_arrays: bool,
+    /// Starts the output with `<?php`.
+    pub open_tag: bool,
+}
+
+impl Default for PrintStyle {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_string(),
+            newline: "\n".to_string(),
+            declaration_brace_on_new_line: true,
+            short_arrays: false,
+            open_tag: false,
+        }
+    }
+}
+
+/// Writes a whole script, ending with a line break.
+pub fn print_script(script: &Script, style: &PrintStyle) -> String {
+    let mut printer = Printer::new(style);
+    if style.open_tag {
+        printer.write("<?php");
+        printer.blank_line();
+    }
+    printer.stmt_list(&script.stmts);
+    if !script.stmts.is_empty() {
+        printer.write(&style.newline);
+    }
+    printer.out
+}
+
+/// Writes a single statement, indented as if it was at the top level of a script.
+pub fn print_stmt(stmt: &Stmt, style: &PrintStyle) -> String {
+    let mut printer = Printer::new(style);
+    printer.stmt(stmt);
+    printer.out
+}
+
+/// Writes a single expression, without a trailing `;`.
+pub fn print_expr(expr: &Expr, style: &PrintStyle) -> String {
+    let mut printer = Printer::new(style);
+    printer.expr(expr, 0, 0);
+    printer.out
+}
+
+/// The binding power of anything that can never be split up by a surrounding operator, such as
+/// a variable, a call or a parenthesized expression.
+const ATOM: u8 = u8::MAX;
+
+/// Which operand of a binary operator may be another use of the same operator without
+/// parentheses.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Assoc {
+    Left,
+    Right,
+    /// Comparisons, which can not be chained at all.
+    None,
+}
+
+fn assoc(op: BinaryOp) -> Assoc {
+    match binding_power(op) {
+        bp::EQUALITY | bp::COMPARISON => Assoc::None,
+        _ if matches!(op, BinaryOp::Pow | BinaryOp::Coalesce) => Assoc::Right,
+        _ => Assoc::Left,
+    }
+}
+
+/// How an expression written without parentheses holds up next to operators, as the pair of
+/// the binding power of its own operator and the binding power its rightmost operand is parsed
+/// with.
+///
+/// The first decides which operands it can be, an assignment can not be an operand of `+`. The
+/// second decides what may follow it, `-$a` followed by `** 2` would take the `** 2` into its
+/// operand, so it needs parentheses as the left operand of `**`. Prefix operators can start any
+/// operand, so only the second one matters for them.
+fn precedence(expr: &Expr) -> (u8, u8) {
+    match &expr.kind {
+        ExprKind::Binary { op, .. } => {
+            let bp = binding_power(*op);
+            match assoc(*op) {
+                Assoc::Right => (bp, bp - 1),
+                _ => (bp, bp),
+            }
+        }
+        ExprKind::Assign { .. } | ExprKind::AssignRef { .. } | ExprKind::AssignOp { .. } => {
+            (bp::ASSIGN, bp::ASSIGN - 1)
+        }
+        ExprKind::Ternary { .. } => (bp::TERNARY, bp::TERNARY),
+        ExprKind::InstanceOf { .. } => (bp::INSTANCEOF, bp::INSTANCEOF),
+        ExprKind::Unary {
+            op: UnaryOp::Not, ..
+        } => (ATOM, bp::NOT),
+        ExprKind::Unary { .. }
+        | ExprKind::Cast { .. }
+        | ExprKind::ErrorSuppress(_)
+        | ExprKind::Await(_)
+        | ExprKind::IncDec {
+            op: IncDecOp::PreIncrement | IncDecOp::PreDecrement,
+            ..
+        } => (ATOM, bp::UNARY),
+        ExprKind::Literal(Literal::Numeric(n)) if is_negative(*n) => (ATOM, bp::UNARY),
+        ExprKind::Clone(_) => (ATOM, bp::CLONE),
+        ExprKind::Print(_) => (ATOM, bp::LOGICAL_AND),
+        ExprKind::Throw(_) | ExprKind::Include { .. } | ExprKind::ArrowFunction(_) => (ATOM, 0),
+        // the operand of `yield` stops at `and`, like the one of `print`.
+        ExprKind::Yield { value: None, .. } => (bp::ASSIGN, ATOM),
+        ExprKind::Yield { .. } | ExprKind::YieldFrom(_) => (bp::ASSIGN, bp::LOGICAL_AND),
+        _ => (ATOM, ATOM),
+    }
+}
+
+fn is_negative(n: Numeric) -> bool {
+    match n {
+        Numeric::Int(n) => n < 0,
+        Numeric::LInt(n) => n < 0,
+        Numeric::Float(f) => f.is_sign_negative() && !f.is_nan(),
+    }
+}
+
+/// Whether `expr` can be followed by `->`, `[` or `::` without parentheses, or by `(` when
+/// `call` is set.
+fn is_dereferenceable(expr: &Expr, call: bool) -> bool {
+    match &expr.kind {
+        ExprKind::Variable(_)
+        | ExprKind::VariableVariable(_)
+        | ExprKind::ArrayDim { .. }
+        | ExprKind::StaticPropertyFetch { .. }
+        | ExprKind::Call { .. }
+        | ExprKind::MethodCall { .. }
+        | ExprKind::StaticCall { .. }
+        | ExprKind::ConstFetch(_)
+        | ExprKind::ReservedConst(_)
+        | ExprKind::Array { .. }
+        | ExprKind::Literal(Literal::String(_))
+        | ExprKind::Interpolated { .. } => true,
+        // `$a->b()` and `A::B()` are method calls, not calls of what the fetch evaluates to.
+        ExprKind::PropertyFetch { .. } | ExprKind::ClassConstFetch { .. } => !call,
+        _ => false,
+    }
+}
+
+/// Whether `expr` can be the class of `new` without parentheses, `new $a->b['c']`.
+fn is_new_class(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Variable(_) | ExprKind::VariableVariable(_) => true,
+        ExprKind::ArrayDim {
+            array,
+            index: Some(_),
+        } => is_new_class(array),
+        ExprKind::PropertyFetch { object, .. } => is_new_class(object),
+        ExprKind::StaticPropertyFetch {
+            class: ClassRef::Expr(class),
+            ..
+        } => is_new_class(class),
+        _ => false,
+    }
+}
+
+/// Whether `expr` is written starting with a `$`, so it can be interpolated as `{$...}`.
+fn starts_with_variable(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Variable(_) | ExprKind::VariableVariable(_) => true,
+        ExprKind::ArrayDim { array: inner, .. }
+        | ExprKind::PropertyFetch { object: inner, .. }
+        | ExprKind::MethodCall { object: inner, .. } => starts_with_variable(inner),
+        ExprKind::Call { callee, .. } => {
+            is_dereferenceable(callee, true) && starts_with_variable(callee)
+        }
+        ExprKind::StaticPropertyFetch {
+            class: ClassRef::Expr(class),
+            ..
+        }
+        | ExprKind::StaticCall {
+            class: ClassRef::Expr(class),
+            ..
+        } => starts_with_variable(class),
+        _ => false,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(is_word_start) && name.chars().all(is_word_char)
+}
+
+/// Declarations are kept apart from the statements around them by a blank line.
+fn is_declaration(stmt: &Stmt) -> bool {
+    matches!(
+        stmt.kind,
+        StmtKind::Function(_) | StmtKind::ClassLike(_) | StmtKind::Namespace { .. }
+    )
+}
+
+struct Printer<'a> {
+    style: &'a PrintStyle,
+    out: String,
+    depth: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(style: &'a PrintStyle) -> Self {
+        Self {
+            style,
+            out: String::new(),
+            depth: 0,
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Starts a new line at the current indentation.
+    fn line(&mut self) {
+        self.out.push_str(&self.style.newline);
+        for _ in 0..self.depth {
+            self.out.push_str(&self.style.indent);
+        }
+    }
+
+    /// Leaves an empty line, without trailing whitespace, and starts the next one.
+    fn blank_line(&mut self) {
+        self.out.push_str(&self.style.newline);
+        self.line();
+    }
+
+    fn comma_list<T>(&mut self, items: &[T], mut item: impl FnMut(&mut Self, &T)) {
+        for (i, it) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            item(self, it);
+        }
+    }
+
+    // statements
+
+    /// Statements one per line, the first one on the current line.
+    fn stmt_list(&mut self, stmts: &[Stmt]) {
+        for (i, stmt) in stmts.iter().enumerate() {
+            if i > 0 {
+                let prev = &stmts[i - 1];
+                let is_use = |stmt: &Stmt| {
+                    matches!(stmt.kind, StmtKind::Use { .. } | StmtKind::GroupUse { .. })
+                };
+                if is_declaration(prev) || is_declaration(stmt) || is_use(prev) != is_use(stmt) {
+                    self.blank_line();
+                } else {
+                    self.line();
+                }
+            }
+            self.stmt(stmt);
+        }
+    }
+
+    /// `{`, the statements indented on lines of their own, and `}`.
+    fn block(&mut self, block: &Block) {
+        self.write("{");
+        self.depth += 1;
+        if !block.stmts.is_empty() {
+            self.line();
+            self.stmt_list(&block.stmts);
+        }
+        self.depth -= 1;
+        self.line();
+        self.write("}");
+    }
+
+    /// The opening brace of a class or function body, on its own line when the style asks
+    /// for it.
+    fn declaration_brace(&mut self) {
+        if self.style.declaration_brace_on_new_line {
+            self.line();
+        } else {
+            self.write(" ");
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => {
+                self.expr(expr, 0, 0);
+                self.write(";");
+            }
+            StmtKind::Echo(exprs) => {
+                self.write("echo ");
+                self.comma_list(exprs, |p, expr| p.expr(expr, 0, 0));
+                self.write(";");
+            }
+            StmtKind::InlineHtml(text) => {
+                self.write("?>");
+                // `?>` takes the line ending right after it, so a leading one needs another.
+                if text.starts_with(['\n', '\r']) {
+                    self.write(&self.style.newline);
+                }
+                self.write(text);
+                self.write("<?php");
+            }
+            StmtKind::Return(value) => {
+                self.write("return");
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.expr(value, 0, 0);
+                }
+                self.write(";");
+            }
+            StmtKind::If {
+                cond,
+                then,
+                elseifs,
+                otherwise,
+            } => {
+                self.write("if (");
+                self.expr(cond, 0, 0);
+                self.write(") ");
+                self.block(then);
+                for elseif in elseifs {
+                    self.write(" elseif (");
+                    self.expr(&elseif.cond, 0, 0);
+                    self.write(") ");
+                    self.block(&elseif.body);
+                }
+                if let Some(otherwise) = otherwise {
+                    self.write(" else ");
+                    match otherwise.stmts.as_slice() {
+                        // `else if` is parsed as an `else` block holding the `if`.
+                        [stmt @ Stmt {
+                            kind: StmtKind::If { .. },
+                            ..
+                        }] => self.stmt(stmt),
+                        _ => self.block(otherwise),
+                    }
+                }
+            }
+            StmtKind::While { cond, body } => {
+                self.write("while (");
+                self.expr(cond, 0, 0);
+                self.write(") ");
+                self.block(body);
+            }
+            StmtKind::DoWhile { body, cond } => {
+                self.write("do ");
+                self.block(body);
+                self.write(" while (");
+                self.expr(cond, 0, 0);
+                self.write(");");
+            }
+            StmtKind::For {
+                init,
+                cond,
+                step,
+                body,
+            } => {
+                self.write("for (");
+                for (i, exprs) in [init, cond, step].into_iter().enumerate() {
+                    if i > 0 {
+                        self.write(if exprs.is_empty() { ";" } else { "; " });
+                    }
+                    self.comma_list(exprs, |p, expr| p.expr(expr, 0, 0));
+                }
+                self.write(") ");
+                self.block(body);
+            }
+            StmtKind::Foreach {
+                subject,
+                key,
+                value,
+                by_ref,
+                body,
+            } => {
+                self.write("foreach (");
+                self.expr(subject, 0, 0);
+                self.write(" as ");
+                if let Some(key) = key {
+                    self.expr(key, 0, 0);
+                    self.write(" => ");
+                }
+                if *by_ref {
+                    self.write("&");
+                }
+                self.expr(value, 0, 0);
+                self.write(") ");
+                self.block(body);
+            }
+            StmtKind::Switch { subject, cases } => {
+                self.write("switch (");
+                self.expr(subject, 0, 0);
+                self.write(") {");
+                self.depth += 1;
+                for case in cases {
+                    self.line();
+                    match &case.cond {
+                        Some(cond) => {
+                            self.write("case ");
+                            self.expr(cond, 0, 0);
+                            self.write(":");
+                        }
+                        None => self.write("default:"),
+                    }
+                    if !case.body.is_empty() {
+                        self.depth += 1;
+                        self.line();
+                        self.stmt_list(&case.body);
+                        self.depth -= 1;
+                    }
+                }
+                self.depth -= 1;
+                self.line();
+                self.write("}");
+            }
+            StmtKind::Break(levels) | StmtKind::Continue(levels) => {
+                self.write(match stmt.kind {
+                    StmtKind::Break(_) => "break",
+                    _ => "continue",
+                });
+                if let Some(levels) = levels {
+                    self.write(&format!(" {}", levels));
+                }
+                self.write(";");
+            }
+            StmtKind::Global(vars) => {
+                self.write("global ");
+                self.comma_list(vars, |p, var| p.expr(var, 0, 0));
+                self.write(";");
+            }
+            StmtKind::Static(vars) => {
+                self.write("static ");
+                self.comma_list(vars, |p, var| {
+                    p.variable(&var.name.name);
+                    if let Some(default) = &var.default {
+                        p.write(" = ");
+                        p.expr(default, 0, 0);
+                    }
+                });
+                self.write(";");
+            }
+            StmtKind::Unset(vars) => {
+                self.write("unset(");
+                self.comma_list(vars, |p, var| p.expr(var, 0, 0));
+                self.write(");");
+            }
+            StmtKind::Declare { directives, body } => {
+                self.write("declare(");
+                self.comma_list(directives, |p, directive| {
+                    p.write(directive.name.as_str());
+                    p.write("=");
+                    p.expr(&directive.value, 0, 0);
+                });
+                self.write(")");
+                match body {
+                    Some(body) => {
+                        self.write(" ");
+                        self.block(body);
+                    }
+                    None => self.write(";"),
+                }
+            }
+            StmtKind::Goto(label) => {
+                self.write("goto ");
+                self.write(label.as_str());
+                self.write(";");
+            }
+            StmtKind::Label(label) => {
+                self.write(label.as_str());
+                self.write(":");
+            }
+            StmtKind::Namespace { name, body } => {
+                self.write("namespace");
+                if let Some(name) = name {
+                    self.write(" ");
+                    self.name(name);
+                }
+                match body {
+                    Some(body) => {
+                        self.write(" ");
+                        self.block(body);
+                    }
+                    None => self.write(";"),
+                }
+            }
+            StmtKind::Use { kind, uses } => {
+                self.write("use ");
+                self.use_kind(*kind);
+                self.comma_list(uses, Self::use_item);
+                self.write(";");
+            }
+            StmtKind::GroupUse { prefix, kind, uses } => {
+                self.write("use ");
+                self.use_kind(*kind);
+                self.name(prefix);
+                self.write("\\{");
+                self.comma_list(uses, |p, item| {
+                    if let Some(kind) = item.kind {
+                        p.use_kind(kind);
+                    }
+                    p.use_item(item);
+                });
+                self.write("};");
+            }
+            StmtKind::Const(consts) => {
+                self.write("const ");
+                self.comma_list(consts, Self::const_item);
+                self.write(";");
+            }
+            StmtKind::Function(function) => {
+                self.attribute_lines(&function.attributes);
+                self.function_decl(function);
+            }
+            StmtKind::ClassLike(class) => {
+                self.attribute_lines(&class.attributes);
+                self.class_like(class);
+            }
+            StmtKind::Try {
+                body,
+                catches,
+                finally,
+            } => {
+                self.write("try ");
+                self.block(body);
+                for catch in catches {
+                    self.write(" catch (");
+                    for (i, ty) in catch.types.iter().enumerate() {
+                        if i > 0 {
+                            self.write(" | ");
+                        }
+                        self.name(ty);
+                    }
+                    if let Some(var) = &catch.var {
+                        self.write(" ");
+                        self.variable(&var.name);
+                    }
+                    self.write(") ");
+                    self.block(&catch.body);
+                }
+                if let Some(finally) = finally {
+                    self.write(" finally ");
+                    self.block(finally);
+                }
+            }
+            StmtKind::Block(block) => self.block(block),
+            StmtKind::Nop => self.write(";"),
+            StmtKind::HaltCompiler(rest) => {
+                self.write("__halt_compiler();");
+                self.write(rest);
+            }
+            // nothing is left of a statement that failed to parse, a comment keeps its place.
+            StmtKind::Error => self.write("/* syntax error */"),
+        }
+    }
+
+    fn use_kind(&mut self, kind: UseKind) {
+        match kind {
+            UseKind::Normal => {}
+            UseKind::Function => self.write("function "),
+            UseKind::Const => self.write("const "),
+        }
+    }
+
+    fn use_item(&mut self, item: &UseItem) {
+        self.name(&item.name);
+        if let Some(alias) = &item.alias {
+            self.write(" as ");
+            self.write(alias.as_str());
+        }
+    }
+
+    fn const_item(&mut self, item: &ConstItem) {
+        self.write(item.name.as_str());
+        self.write(" = ");
+        self.expr(&item.value, 0, 0);
+    }
+
+    // declarations
+
+    /// Attribute groups on lines of their own, before a declaration.
+    fn attribute_lines(&mut self, groups: &[AttributeGroup]) {
+        for group in groups {
+            self.attribute_group(group);
+            self.line();
+        }
+    }
+
+    /// Attribute groups on the same line as what they apply to, each followed by a space.
+    fn attributes_inline(&mut self, groups: &[AttributeGroup]) {
+        for group in groups {
+            self.attribute_group(group);
+            self.write(" ");
+        }
+    }
+
+    fn attribute_group(&mut self, group: &AttributeGroup) {
+        self.write("#[");
+        self.comma_list(&group.attributes, |p, attribute| {
+            p.name(&attribute.name);
+            if !attribute.args.is_empty() {
+                p.args(&attribute.args);
+            }
+        });
+        self.write("]");
+    }
+
+    fn modifiers(&mut self, modifiers: &[Modifier]) {
+        for modifier in modifiers {
+            self.write(modifier.keyword.as_str());
+            self.write(" ");
+        }
+    }
+
+    fn function_decl(&mut self, function: &FunctionDecl) {
+        if function.is_async {
+            self.write("async ");
+        }
+        self.write("function ");
+        if function.by_ref {
+            self.write("&");
+        }
+        self.write(function.name.as_str());
+        self.params(&function.params);
+        self.return_type(&function.return_type);
+        match &function.body {
+            Some(body) => {
+                self.declaration_brace();
+                self.block(body);
+            }
+            None => self.write(";"),
+        }
+    }
+
+    fn params(&mut self, params: &[Param]) {
+        self.write("(");
+        self.comma_list(params, |p, param| {
+            p.attributes_inline(&param.attributes);
+            p.modifiers(&param.modifiers);
+            if let Some(ty) = &param.ty {
+                p.type_hint(ty);
+                p.write(" ");
+            }
+            if param.by_ref {
+                p.write("&");
+            }
+            if param.variadic {
+                p.write("...");
+            }
+            p.variable(&param.name.name);
+            if let Some(default) = &param.default {
+                p.write(" = ");
+                p.expr(default, 0, 0);
+            }
+        });
+        self.write(")");
+    }
+
+    fn return_type(&mut self, ty: &Option<TypeHint>) {
+        if let Some(ty) = ty {
+            self.write(": ");
+            self.type_hint(ty);
+        }
+    }
+
+    fn closure_uses(&mut self, uses: &[ClosureUse]) {
+        if uses.is_empty() {
+            return;
+        }
+        self.write(" use (");
+        self.comma_list(uses, |p, var| {
+            if var.by_ref {
+                p.write("&");
+            }
+            p.variable(&var.name.name);
+        });
+        self.write(")");
+    }
+
+    fn class_like(&mut self, class: &ClassLike) {
+        self.modifiers(&class.modifiers);
+        self.write(class.kind.as_str());
+        if let Some(name) = &class.name {
+            self.write(" ");
+            self.write(name.as_str());
+        }
+        self.class_rest(class);
+    }
+
+    /// Everything after the name of a class-like, from the backing type of an enum to the
+    /// closing brace.
+    fn class_rest(&mut self, class: &ClassLike) {
+        if let Some(ty) = &class.backing_type {
+            self.write(": ");
+            self.type_hint(ty);
+        }
+        if !class.extends.is_empty() {
+            self.write(" extends ");
+            self.comma_list(&class.extends, Self::name);
+        }
+        if !class.implements.is_empty() {
+            self.write(" implements ");
+            self.comma_list(&class.implements, Self::name);
+        }
+        match class.name {
+            Some(_) => self.declaration_brace(),
+            None => self.write(" "),
+        }
+
+        self.write("{");
+        self.depth += 1;
+        for (i, member) in class.members.iter().enumerate() {
+            let separate = i > 0 && {
+                let prev = &class.members[i - 1].kind;
+                matches!(prev, MemberKind::Method(_))
+                    || std::mem::discriminant(prev) != std::mem::discriminant(&member.kind)
+            };
+            if separate {
+                self.blank_line();
+            } else {
+                self.line();
+            }
+            self.class_member(member);
+        }
+        self.depth -= 1;
+        self.line();
+        self.write("}");
+    }
+
+    fn class_member(&mut self, member: &ClassMember) {
+        match &member.kind {
+            MemberKind::Property(property) => {
+                self.attribute_lines(&property.attributes);
+                self.modifiers(&property.modifiers);
+                if let Some(ty) = &property.ty {
+                    self.type_hint(ty);
+                    self.write(" ");
+                }
+                self.comma_list(&property.props, |p, prop| {
+                    p.variable(&prop.name.name);
+                    if let Some(default) = &prop.default {
+                        p.write(" = ");
+                        p.expr(default, 0, 0);
+                    }
+                });
+                self.write(";");
+            }
+            MemberKind::Const(constant) => {
+                self.attribute_lines(&constant.attributes);
+                self.modifiers(&constant.modifiers);
+                self.write("const ");
+                if let Some(ty) = &constant.ty {
+                    self.type_hint(ty);
+                    self.write(" ");
+                }
+                self.comma_list(&constant.consts, Self::const_item);
+                self.write(";");
+            }
+            MemberKind::Method(method) => {
+                self.attribute_lines(&method.function.attributes);
+                self.modifiers(&method.modifiers);
+                self.function_decl(&method.function);
+            }
+            MemberKind::TraitUse(trait_use) => {
+                self.write("use ");
+                self.comma_list(&trait_use.traits, Self::name);
+                if trait_use.adaptations.is_empty() {
+                    self.write(";");
+                    return;
+                }
+                self.write(" {");
+                self.depth += 1;
+                for adaptation in &trait_use.adaptations {
+                    self.line();
+                    match &adaptation.kind {
+                        AdaptationKind::InsteadOf {
+                            trait_name,
+                            method,
+                            instead_of,
+                        } => {
+                            self.name(trait_name);
+                            self.write("::");
+                            self.write(method.as_str());
+                            self.write(" insteadof ");
+                            self.comma_list(instead_of, Self::name);
+                        }
+                        AdaptationKind::Alias {
+                            trait_name,
+                            method,
+                            visibility,
+                            alias,
+                        } => {
+                            if let Some(trait_name) = trait_name {
+                                self.name(trait_name);
+                                self.write("::");
+                            }
+                            self.write(method.as_str());
+                            self.write(" as");
+                            if let Some(visibility) = visibility {
+                                self.write(" ");
+                                self.write(visibility.keyword.as_str());
+                            }
+                            if let Some(alias) = alias {
+                                self.write(" ");
+                                self.write(alias.as_str());
+                            }
+                        }
+                    }
+                    self.write(";");
+                }
+                self.depth -= 1;
+                self.line();
+                self.write("}");
+            }
+            MemberKind::EnumCase(case) => {
+                self.attribute_lines(&case.attributes);
+                self.write("case ");
+                self.write(case.name.as_str());
+                if let Some(value) = &case.value {
+                    self.write(" = ");
+                    self.expr(value, 0, 0);
+                }
+                self.write(";");
+            }
+            MemberKind::Error => self.write("/* syntax error */"),
+        }
+    }
+
+    fn type_hint(&mut self, ty: &TypeHint) {
+        match &ty.kind {
+            TypeHintKind::Named(name) => self.name(name),
+            TypeHintKind::Nullable(inner) => {
+                self.write("?");
+                self.type_hint(inner);
+            }
+            TypeHintKind::Union(types) => {
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        self.write("|");
+                    }
+                    if let TypeHintKind::Intersection(_) = ty.kind {
+                        self.write("(");
+                        self.type_hint(ty);
+                        self.write(")");
+                    } else {
+                        self.type_hint(ty);
+                    }
+                }
+            }
+            TypeHintKind::Intersection(types) => {
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        self.write("&");
+                    }
+                    self.type_hint(ty);
+                }
+            }
+        }
+    }
+
+    fn name(&mut self, name: &Name) {
+        self.write(&name.to_string());
+    }
+
+    fn ident(&mut self, ident: &Ident) {
+        self.write(ident.as_str());
+    }
+
+    /// `$name`, or `${'name'}` for the names the compiler makes up that are not identifiers.
+    fn variable(&mut self, name: &str) {
+        if is_identifier(name) {
+            self.write("$");
+            self.write(name);
+        } else {
+            self.write("${");
+            self.single_quoted(name);
+            self.write("}");
+        }
+    }
+
+    // expressions
+
+    /// Writes `expr` as an operand that must bind at least as tightly as `min`, followed by
+    /// operators that bind as tightly as `trail`, in parentheses when it does not hold up
+    /// on its own.
+    fn expr(&mut self, expr: &Expr, min: u8, trail: u8) {
+        let (binds, reaches) = precedence(expr);
+        if binds < min || reaches < trail {
+            self.write("(");
+            self.expr_kind(expr, 0);
+            self.write(")");
+        } else {
+            self.expr_kind(expr, trail);
+        }
+    }
+
+    /// Writes the operand of `->`, `[`, `::` or of a call, in parentheses unless it can be
+    /// followed by them directly.
+    fn postfix_operand(&mut self, expr: &Expr, call: bool) {
+        if is_dereferenceable(expr, call) {
+            self.expr_kind(expr, ATOM);
+        } else {
+            self.write("(");
+            self.expr_kind(expr, 0);
+            self.write(")");
+        }
+    }
+
+    fn expr_kind(&mut self, expr: &Expr, trail: u8) {
+        match &expr.kind {
+            ExprKind::Literal(literal) => self.literal(literal),
+            ExprKind::Interpolated { parts, .. } => self.interpolated(parts, '"'),
+            ExprKind::ShellExec(parts) => self.interpolated(parts, '`'),
+            ExprKind::Variable(name) => self.variable(name),
+            ExprKind::VariableVariable(inner) => match inner.kind {
+                ExprKind::Variable(_) | ExprKind::VariableVariable(_) => {
+                    self.write("$");
+                    self.expr_kind(inner, ATOM);
+                }
+                _ => {
+                    self.write("${");
+                    self.expr(inner, 0, 0);
+                    self.write("}");
+                }
+            },
+            ExprKind::Array { items, short } => {
+                let (open, close) = self.array_delimiters(*short, "array(");
+                self.write(open);
+                self.comma_list(items, Self::array_item);
+                self.write(close);
+            }
+            ExprKind::List { items, short } => {
+                let (open, close) = self.array_delimiters(*short, "list(");
+                self.write(open);
+                self.comma_list(items, |p, item| {
+                    if let Some(item) = item {
+                        p.array_item(item);
+                    }
+                });
+                // a skipped last element needs a comma of its own, `[$a, , ]`.
+                if matches!(items.last(), Some(None)) {
+                    self.write(",");
+                }
+                self.write(close);
+            }
+            ExprKind::ConstFetch(name) => self.name(name),
+            ExprKind::ReservedConst(reserved) => self.write(reserved.as_str()),
+            ExprKind::ClassConstFetch { class, constant } => {
+                self.static_class(class);
+                self.write("::");
+                self.static_member(constant);
+            }
+            ExprKind::StaticPropertyFetch { class, property } => {
+                self.static_class(class);
+                self.write("::");
+                self.expr(property, ATOM, 0);
+            }
+            ExprKind::PropertyFetch {
+                object,
+                property,
+                nullsafe,
+            } => {
+                self.postfix_operand(object, false);
+                self.write(if *nullsafe { "?->" } else { "->" });
+                self.member(property);
+            }
+            ExprKind::ArrayDim { array, index } => {
+                self.postfix_operand(array, false);
+                self.write("[");
+                if let Some(index) = index {
+                    self.expr(index, 0, 0);
+                }
+                self.write("]");
+            }
+            ExprKind::Call { callee, args } => {
+                match &callee.kind {
+                    ExprKind::ConstFetch(name) => self.name(name),
+                    _ => self.postfix_operand(callee, true),
+                }
+                self.call_args(args);
+            }
+            ExprKind::MethodCall {
+                object,
+                method,
+                args,
+                nullsafe,
+            } => {
+                self.postfix_operand(object, false);
+                self.write(if *nullsafe { "?->" } else { "->" });
+                self.member(method);
+                self.call_args(args);
+            }
+            ExprKind::StaticCall {
+                class,
+                method,
+                args,
+            } => {
+                self.static_class(class);
+                self.write("::");
+                self.static_member(method);
+                self.call_args(args);
+            }
+            ExprKind::New { class, args } => {
+                self.write("new ");
+                match class {
+                    ClassRef::Name(name) => self.name(name),
+                    ClassRef::Expr(class) if is_new_class(class) => self.expr_kind(class, ATOM),
+                    ClassRef::Expr(class) => {
+                        self.write("(");
+                        self.expr(class, 0, 0);
+                        self.write(")");
+                    }
+                    ClassRef::Anonymous(class) => {
+                        self.attributes_inline(&class.attributes);
+                        self.modifiers(&class.modifiers);
+                        self.write("class");
+                        if let Some(args) = args {
+                            self.args(args);
+                        }
+                        self.class_rest(class);
+                        return;
+                    }
+                }
+                if let Some(args) = args {
+                    self.args(args);
+                }
+            }
+            ExprKind::Unary { op, expr: operand } => {
+                self.write(op.as_str());
+                let operand_bp = match op {
+                    UnaryOp::Not => bp::NOT,
+                    _ => bp::UNARY,
+                };
+                let start = self.out.len();
+                self.expr(operand, operand_bp + 1, trail);
+                // `- -$a` is not `--$a`.
+                if matches!(op, UnaryOp::Plus | UnaryOp::Minus)
+                    && self.out[start..].starts_with(['+', '-'])
+                {
+                    self.out.insert(start, ' ');
+                }
+            }
+            ExprKind::Binary { op, left, right } => {
+                let bp = binding_power(*op);
+                let (left_min, right_min) = match assoc(*op) {
+                    Assoc::Left => (bp, bp + 1),
+                    Assoc::Right => (bp + 1, bp),
+                    Assoc::None => (bp + 1, bp + 1),
+                };
+                self.expr(left, left_min, bp);
+                self.write(" ");
+                self.write(op.as_str());
+                self.write(" ");
+                self.expr(right, right_min, trail);
+            }
+            ExprKind::IncDec { op, expr: operand } => match op {
+                IncDecOp::PreIncrement | IncDecOp::PreDecrement => {
+                    self.write(if *op == IncDecOp::PreIncrement {
+                        "++"
+                    } else {
+                        "--"
+                    });
+                    self.expr(operand, bp::UNARY + 1, trail);
+                }
+                IncDecOp::PostIncrement | IncDecOp::PostDecrement => {
+                    self.postfix_operand(operand, false);
+                    self.write(if *op == IncDecOp::PostIncrement {
+                        "++"
+                    } else {
+                        "--"
+                    });
+                }
+            },
+            ExprKind::Assign { target, value }
+            | ExprKind::AssignRef { target, value }
+            | ExprKind::AssignOp { target, value, .. } => {
+                self.expr(target, bp::ASSIGN + 1, bp::ASSIGN);
+                match &expr.kind {
+                    ExprKind::Assign { .. } => self.write(" = "),
+                    ExprKind::AssignRef { .. } => self.write(" = &"),
+                    ExprKind::AssignOp { op, .. } => {
+                        self.write(" ");
+                        self.write(op.as_str());
+                        self.write("= ");
+                    }
+                    _ => unreachable!("only assignments are matched"),
+                }
+                self.expr(value, bp::ASSIGN, trail);
+            }
+            ExprKind::Ternary {
+                cond,
+                then,
+                otherwise,
+            } => {
+                // nested ternaries always need parentheses.
+                self.expr(cond, bp::TERNARY + 1, bp::TERNARY);
+                match then {
+                    Some(then) => {
+                        self.write(" ? ");
+                        self.expr(then, 0, 0);
+                        self.write(" : ");
+                    }
+                    None => self.write(" ?: "),
+                }
+                self.expr(otherwise, bp::TERNARY + 1, trail);
+            }
+            ExprKind::Cast {
+                kind,
+                expr: operand,
+            } => {
+                self.write("(");
+                self.write(kind.as_str());
+                self.write(") ");
+                self.expr(operand, bp::UNARY + 1, trail);
+            }
+            ExprKind::InstanceOf {
+                expr: operand,
+                class,
+            } => {
+                self.expr(operand, bp::INSTANCEOF, bp::INSTANCEOF);
+                self.write(" instanceof ");
+                match class {
+                    ClassRef::Name(name) => self.name(name),
+                    ClassRef::Expr(class) => self.expr(class, bp::INSTANCEOF + 1, trail),
+                    ClassRef::Anonymous(class) => {
+                        self.write("(");
+                        self.write("new class");
+                        self.class_rest(class);
+                        self.write(")");
+                    }
+                }
+            }
+            ExprKind::Clone(operand) => {
+                self.write("clone ");
+                self.expr(operand, bp::CLONE + 1, trail);
+            }
+            ExprKind::Print(operand) => {
+                self.write("print ");
+                self.expr(operand, bp::LOGICAL_AND + 1, trail);
+            }
+            ExprKind::ErrorSuppress(operand) => {
+                self.write("@");
+                self.expr(operand, bp::UNARY + 1, trail);
+            }
+            ExprKind::Await(operand) => {
+                self.write("await ");
+                self.expr(operand, bp::UNARY + 1, trail);
+            }
+            ExprKind::Construct { construct, args } => {
+                self.write(construct.as_str());
+                self.write("(");
+                self.comma_list(args, |p, arg| p.expr(arg, 0, 0));
+                self.write(")");
+            }
+            ExprKind::Include { keyword, path } => {
+                self.write(keyword.as_str());
+                self.write(" ");
+                self.expr(path, 0, trail);
+            }
+            ExprKind::Closure(closure) => {
+                self.attributes_inline(&closure.attributes);
+                if closure.is_static {
+                    self.write("static ");
+                }
+                if closure.is_async {
+                    self.write("async ");
+                }
+                self.write("function ");
+                if closure.by_ref {
+                    self.write("&");
+                }
+                self.params(&closure.params);
+                self.closure_uses(&closure.uses);
+                self.return_type(&closure.return_type);
+                self.write(" ");
+                self.block(&closure.body);
+            }
+            ExprKind::ArrowFunction(function) => {
+                self.attributes_inline(&function.attributes);
+                if function.is_static {
+                    self.write("static ");
+                }
+                if function.is_async {
+                    self.write("async ");
+                }
+                self.write("fn ");
+                if function.by_ref {
+                    self.write("&");
+                }
+                self.params(&function.params);
+                self.return_type(&function.return_type);
+                self.write(" => ");
+                self.expr(&function.body, 0, trail);
+            }
+            ExprKind::Match { subject, arms } => {
+                self.write("match (");
+                self.expr(subject, 0, 0);
+                self.write(") {");
+                self.depth += 1;
+                for arm in arms {
+                    self.line();
+                    self.match_arm(arm);
+                }
+                self.depth -= 1;
+                self.line();
+                self.write("}");
+            }
+            ExprKind::Throw(operand) => {
+                self.write("throw ");
+                self.expr(operand, 0, trail);
+            }
+            ExprKind::Yield { key, value } => {
+                self.write("yield");
+                if let Some(key) = key {
+                    self.write(" ");
+                    self.expr(key, bp::LOGICAL_AND + 1, 0);
+                    self.write(" =>");
+                }
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.expr(value, bp::LOGICAL_AND + 1, trail);
+                }
+            }
+            ExprKind::YieldFrom(operand) => {
+                self.write("yield from ");
+                self.expr(operand, bp::LOGICAL_AND + 1, trail);
+            }
+        }
+    }
+
+    fn array_delimiters(&self, short: bool, long: &'static str) -> (&'static str, &'static str) {
+        if short || self.style.short_arrays {
+            ("[", "]")
+        } else {
+            (long, ")")
+        }
+    }
+
+    fn array_item(&mut self, item: &ArrayItem) {
+        if item.unpack {
+            self.write("...");
+        }
+        if let Some(key) = &item.key {
+            self.expr(key, 0, 0);
+            self.write(" => ");
+        }
+        if item.by_ref {
+            self.write("&");
+        }
+        self.expr(&item.value, 0, 0);
+    }
+
+    fn match_arm(&mut self, arm: &MatchArm) {
+        match &arm.conditions {
+            Some(conditions) => self.comma_list(conditions, |p, cond| p.expr(cond, 0, 0)),
+            None => self.write("default"),
+        }
+        self.write(" => ");
+        self.expr(&arm.body, 0, 0);
+        self.write(",");
+    }
+
+    /// The class of a static access, before `::`.
+    fn static_class(&mut self, class: &ClassRef) {
+        match class {
+            ClassRef::Name(name) => self.name(name),
+            ClassRef::Expr(class) => self.postfix_operand(class, false),
+            ClassRef::Anonymous(class) => {
+                self.write("(new class");
+                self.class_rest(class);
+                self.write(")");
+            }
+        }
+    }
+
+    /// The member after `->`, where a variable can be written as is, `$a->$b`.
+    fn member(&mut self, member: &MemberName) {
+        match member {
+            MemberName::Ident(ident) => self.ident(ident),
+            MemberName::Expr(expr)
+                if matches!(
+                    expr.kind,
+                    ExprKind::Variable(_) | ExprKind::VariableVariable(_)
+                ) =>
+            {
+                self.expr_kind(expr, ATOM)
+            }
+            MemberName::Expr(expr) => {
+                self.write("{");
+                self.expr(expr, 0, 0);
+                self.write("}");
+            }
+        }
+    }
+
+    /// The member after `::`, where a variable would be a static property, so expressions
+    /// always need braces, `A::{$b}()`.
+    fn static_member(&mut self, member: &MemberName) {
+        match member {
+            MemberName::Ident(ident) => self.ident(ident),
+            MemberName::Expr(expr) => {
+                self.write("{");
+                self.expr(expr, 0, 0);
+                self.write("}");
+            }
+        }
+    }
+
+    fn call_args(&mut self, args: &CallArgs) {
+        match args {
+            CallArgs::List(args) => self.args(args),
+            CallArgs::FirstClassCallable(_) => self.write("(...)"),
+        }
+    }
+
+    fn args(&mut self, args: &[Arg]) {
+        self.write("(");
+        self.comma_list(args, |p, arg| {
+            if let Some(name) = &arg.name {
+                p.ident(name);
+                p.write(": ");
+            }
+            if arg.unpack {
+                p.write("...");
+            }
+            p.expr(&arg.value, 0, 0);
+        });
+        self.write(")");
+    }
+
+    fn literal(&mut self, literal: &Literal) {
+        match literal {
+            Literal::Null => self.write("null"),
+            Literal::Bool(value) => self.write(if *value { "true" } else { "false" }),
+            Literal::Numeric(Numeric::Int(n)) => self.write(&n.to_string()),
+            Literal::Numeric(Numeric::LInt(n)) => self.write(&format!("{}n", n)),
+            Literal::Numeric(Numeric::Float(f)) => {
+                if f.is_nan() {
+                    self.write("NAN");
+                } else if f.is_infinite() {
+                    self.write(if *f > 0.0 { "INF" } else { "-INF" });
+                } else {
+                    // `{:?}` always keeps a `.` or an exponent, so the literal stays a float.
+                    self.write(&format!("{:?}", f));
+                }
+            }
+            Literal::String(text) => match std::str::from_utf8(text) {
+                Ok(text) => self.single_quoted(text),
+                // single quoted strings have no escapes for bytes that are not UTF-8.
+                Err(_) => {
+                    self.write("\"");
+                    self.escaped(text, '"');
+                    self.write("\"");
+                }
+            },
+        }
+    }
+
+    fn single_quoted(&mut self, text: &str) {
+        self.write("'");
+        for c in text.chars() {
+            if matches!(c, '\\' | '\'') {
+                self.out.push('\\');
+            }
+            self.out.push(c);
+        }
+        self.write("'");
+    }
+
+    /// A double quoted or backtick string, with its expressions as `{$...}`. Expressions that
+    /// do not start with a variable can not be interpolated, so the string is then written as
+    /// a concatenation instead.
+    fn interpolated(&mut self, parts: &[StringPart], quote: char) {
+        let interpolable = parts.iter().all(|part| match part {
+            StringPart::Literal(..) => true,
+            StringPart::Expr(expr) => starts_with_variable(expr),
+        });
+        if interpolable {
+            self.out.push(quote);
+            for part in parts {
+                match part {
+                    StringPart::Literal(text, _) => self.escaped(text, quote),
+                    StringPart::Expr(expr) => {
+                        self.write("{");
+                        self.expr(expr, 0, 0);
+                        self.write("}");
+                    }
+                }
+            }
+            self.out.push(quote);
+            return;
+        }
+
+        self.write(if quote == '`' { "\\shell_exec(" } else { "(" });
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                self.write(" . ");
+            }
+            match part {
+                StringPart::Literal(text, _) => {
+                    self.write("\"");
+                    self.escaped(text, '"');
+                    self.write("\"");
+                }
+                StringPart::Expr(expr) => self.expr(expr, bp::CONCAT + 1, bp::CONCAT),
+            }
+        }
+        self.write(")");
+    }
+
+    /// The text of a double quoted or backtick string, with everything that would be
+    /// interpolated or is not printable escaped, bytes that are not UTF-8 included.
+    fn escaped(&mut self, text: &[u8], quote: char) {
+        for chunk in text.utf8_chunks() {
+            for c in chunk.valid().chars() {
+                self.escaped_char(c, quote);
+            }
+            for byte in chunk.invalid() {
+                self.write(&format!("\\x{:02X}", byte));
+            }
+        }
+    }
+
+    fn escaped_char(&mut self, c: char, quote: char) {
+        match c {
+            '\\' | '$' => {
+                self.out.push('\\');
+                self.out.push(c);
+            }
+            _ if c == quote => {
+                self.out.push('\\');
+                self.out.push(c);
+            }
+            '\n' => self.write("\\n"),
+            '\t' => self.write("\\t"),
+            '\r' => self.write("\\r"),
+            '\x0B' => self.write("\\v"),
+            '\x1B' => self.write("\\e"),
+            '\x0C' => self.write("\\f"),
+            _ if c.is_ascii_control() => self.write(&format!("\\x{:02X}", c as u32)),
+            _ => self.out.push(c),
+        }
+    }
+}
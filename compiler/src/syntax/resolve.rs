@@ -0,0 +1,581 @@
+//! Name resolution, which turns every class, function and constant name into its fully
+//! qualified form, following the namespace and `use` imports in effect where it is written.
+
+use std::collections::HashMap;
+
+use super::{
+    ast::{
+        attribute::{AttributeFlags, AttributeGroup, AttributeTarget},
+        class::{has_modifier, ClassConst, ClassKind, ClassLike, EnumCase, Method, Property},
+        expr::{BinaryOp, ClassRef, Expr, ExprKind, Literal, MatchArm, MemberName, UnaryOp},
+        function::{ArrowFunction, Closure, FunctionDecl, Param},
+        keyword::Keyword,
+        name::{Name, NameKind, ResolvedName},
+        stmt::{Script, Stmt, StmtKind, UseItem, UseKind},
+        types::{BuiltinType, TypeHint, TypeHintKind},
+        visit_mut::{
+            walk_arrow_function, walk_class_const, walk_class_like, walk_closure, walk_enum_case,
+            walk_expr, walk_function_decl, walk_param, walk_property, walk_stmt, walk_type_hint,
+            VisitorMut,
+        },
+    },
+    diagnostic::Diagnostic,
+    lex::token::Numeric,
+    print::{print_expr, PrintStyle},
+    source::Span,
+};
+
+/// The namespace and imports in effect at a point of a script, which decide what the names
+/// written there refer to.
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    namespace: Vec<String>,
+    /// Imported classes and namespaces, by lowercase alias.
+    classes: HashMap<String, Vec<String>>,
+    /// Imported functions, by lowercase alias.
+    functions: HashMap<String, Vec<String>>,
+    /// Imported constants, by alias. Constant names are case-sensitive.
+    consts: HashMap<String, Vec<String>>,
+}
+
+impl Scope {
+    /// A scope without imports, inside the given namespace. The global namespace is empty.
+    pub fn new(namespace: Vec<String>) -> Self {
+        Self {
+            namespace,
+            ..Self::default()
+        }
+    }
+
+    pub fn namespace(&self) -> &[String] {
+        &self.namespace
+    }
+
+    /// Imports `parts` under `alias`, failing with a message when the alias is already taken.
+    pub fn import(&mut self, kind: UseKind, parts: Vec<String>, alias: &str) -> Result<(), String> {
+        if kind == UseKind::Normal && is_special_class(alias) {
+            return Err(format!(
+                "cannot use {} as {} because '{}' is a special class name",
+                parts.join("\\"),
+                alias,
+                alias
+            ));
+        }
+        let (imports, key) = match kind {
+            UseKind::Normal => (&mut self.classes, alias.to_ascii_lowercase()),
+            UseKind::Function => (&mut self.functions, alias.to_ascii_lowercase()),
+            UseKind::Const => (&mut self.consts, alias.to_string()),
+        };
+        if imports.contains_key(&key) {
+            return Err(format!(
+                "cannot use {} as {} because the name is already in use",
+                parts.join("\\"),
+                alias
+            ));
+        }
+        imports.insert(key, parts);
+        Ok(())
+    }
+
+    /// The fully qualified name of a class declared here, `Foo` in `namespace A` is `A\Foo`.
+    pub fn qualify(&self, name: &str) -> Vec<String> {
+        let mut parts = self.namespace.clone();
+        parts.push(name.to_string());
+        parts
+    }
+
+    /// Resolves a class name. `self`, `parent` and `static` depend on the class they are
+    /// used in, so they are left unresolved.
+    pub fn resolve_class(&self, name: &Name) -> Option<ResolvedName> {
+        if name.kind == NameKind::Unqualified {
+            if name.is_special_class() {
+                return None;
+            }
+            let parts = match self.classes.get(&name.last().to_ascii_lowercase()) {
+                Some(import) => import.clone(),
+                None => self.qualify(name.last()),
+            };
+            return Some(ResolvedName::FullyQualified(parts));
+        }
+        Some(ResolvedName::FullyQualified(self.resolve_qualified(name)))
+    }
+
+    /// Resolves a function name. An unqualified name that is not imported falls back to the
+    /// global function when the namespace does not define it.
+    pub fn resolve_function(&self, name: &Name) -> ResolvedName {
+        let import = self.functions.get(&name.last().to_ascii_lowercase());
+        self.resolve_with_fallback(name, import)
+    }
+
+    /// Resolves a constant name, with the same fallback as functions.
+    pub fn resolve_const(&self, name: &Name) -> ResolvedName {
+        let import = self.consts.get(name.last());
+        self.resolve_with_fallback(name, import)
+    }
+
+    fn resolve_with_fallback(&self, name: &Name, import: Option<&Vec<String>>) -> ResolvedName {
+        if name.kind != NameKind::Unqualified {
+            return ResolvedName::FullyQualified(self.resolve_qualified(name));
+        }
+        match import {
+            Some(import) => ResolvedName::FullyQualified(import.clone()),
+            None if self.namespace.is_empty() => {
+                ResolvedName::FullyQualified(vec![name.last().to_string()])
+            }
+            None => ResolvedName::Fallback {
+                namespaced: self.qualify(name.last()),
+                global: name.last().to_string(),
+            },
+        }
+    }
+
+    /// A name with a namespace part, whose first part may be an imported namespace.
+    fn resolve_qualified(&self, name: &Name) -> Vec<String> {
+        match name.kind {
+            NameKind::FullyQualified => name.parts.clone(),
+            NameKind::Relative => {
+                let mut parts = self.namespace.clone();
+                parts.extend(name.parts.iter().cloned());
+                parts
+            }
+            _ => {
+                let (first, rest) = name.parts.split_first().expect("names are not empty");
+                let mut parts = match self.classes.get(&first.to_ascii_lowercase()) {
+                    Some(import) => import.clone(),
+                    None => self.qualify(first),
+                };
+                parts.extend(rest.iter().cloned());
+                parts
+            }
+        }
+    }
+}
+
+/// Resolves every class, function and constant name in `script`, filling in
+/// `Name::resolved`. Conflicting imports and declarations are reported, along with imports
+/// that have no effect and attributes applied where their class does not allow them.
+pub fn resolve_names(script: &mut Script) -> Vec<Diagnostic> {
+    let mut resolver = Resolver::default();
+    resolver.visit_script(script);
+    resolver.check_attributes();
+    resolver.diagnostics
+}
+
+#[derive(Default)]
+struct Resolver {
+    scope: Scope,
+    diagnostics: Vec<Diagnostic>,
+    /// Every attribute applied in the script, checked once all attribute classes are known.
+    attribute_uses: Vec<AttributeUse>,
+    /// The flags of the attribute classes declared in the script, by lowercase fully
+    /// qualified name.
+    attribute_classes: HashMap<String, AttributeFlags>,
+}
+
+/// An attribute applied to a declaration.
+struct AttributeUse {
+    /// The fully qualified name of the attribute class, without the leading `\`.
+    name: String,
+    target: AttributeTarget,
+    /// Whether the declaration is a promoted constructor parameter, which is a property as
+    /// well as a parameter.
+    promoted: bool,
+    /// Whether this is the second attribute of its class on the declaration, the one PHP
+    /// reports when the class is not repeatable.
+    repeated: bool,
+    span: Span,
+}
+
+impl Resolver {
+    fn import(&mut self, kind: UseKind, parts: Vec<String>, item: &mut UseItem) {
+        let alias = match &item.alias {
+            Some(alias) => alias.name.clone(),
+            None => item.name.last().to_string(),
+        };
+        if kind == UseKind::Normal
+            && parts.len() == 1
+            && self.scope.namespace.is_empty()
+            && alias == parts[0]
+        {
+            self.diagnostics.push(Diagnostic::warning(
+                format!(
+                    "the use statement with non-compound name '{}' has no effect",
+                    alias
+                ),
+                item.span,
+            ));
+            return;
+        }
+
+        item.name.resolved = Some(Box::new(ResolvedName::FullyQualified(parts.clone())));
+        if let Err(message) = self.scope.import(kind, parts, &alias) {
+            self.diagnostics.push(Diagnostic::error(message, item.span));
+        }
+    }
+
+    /// Reports a class or function declared under a name that an import already uses for
+    /// something else.
+    fn check_declaration(&mut self, kind: &str, name: &str, span: Span) {
+        let imports = match kind {
+            "function" => &self.scope.functions,
+            _ => &self.scope.classes,
+        };
+        let imported = imports.get(&name.to_ascii_lowercase());
+        let declared = self.scope.qualify(name);
+        let conflicts = imported.is_some_and(|imported| {
+            !imported
+                .join("\\")
+                .eq_ignore_ascii_case(&declared.join("\\"))
+        });
+        if conflicts {
+            self.diagnostics.push(Diagnostic::error(
+                format!(
+                    "cannot declare {} {} because the name is already in use",
+                    kind,
+                    declared.join("\\")
+                ),
+                span,
+            ));
+        }
+    }
+
+    /// Warns about a constant condition that an earlier arm of the same match already
+    /// handles, as `match` compares with `===` and takes the first arm that is identical.
+    fn check_match_arms(&mut self, arms: &[MatchArm]) {
+        let mut seen = Vec::new();
+        for condition in arms.iter().flat_map(|arm| arm.conditions.iter().flatten()) {
+            let Some(key) = constant_key(condition) else {
+                continue;
+            };
+            if seen.contains(&key) {
+                self.diagnostics.push(Diagnostic::warning(
+                    format!(
+                        "duplicate match arm condition {} is never reached",
+                        print_expr(condition, &PrintStyle::default())
+                    ),
+                    condition.span,
+                ));
+            } else {
+                seen.push(key);
+            }
+        }
+    }
+
+    /// Reports attributes whose class does not allow the kind of declaration they are applied
+    /// to, or that are repeated without `Attribute::IS_REPEATABLE`. Attribute classes that are
+    /// neither built in nor declared in the script are left for the runtime to check.
+    fn check_attributes(&mut self) {
+        for attribute in &self.attribute_uses {
+            let flags = match self
+                .attribute_classes
+                .get(&attribute.name.to_ascii_lowercase())
+            {
+                Some(flags) => *flags,
+                None if !attribute.name.contains('\\') => {
+                    match AttributeFlags::builtin(&attribute.name) {
+                        Some(flags) => flags,
+                        None => continue,
+                    }
+                }
+                None => continue,
+            };
+            let allowed = flags.allows(attribute.target)
+                || attribute.promoted && flags.allows(AttributeTarget::Property);
+            if !allowed {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "attribute \"{}\" cannot target {} (allowed targets: {})",
+                        attribute.name,
+                        attribute.target.as_str(),
+                        flags.targets()
+                    ),
+                    attribute.span,
+                ));
+            } else if attribute.repeated && !flags.is_repeatable() {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("attribute \"{}\" must not be repeated", attribute.name),
+                    attribute.span,
+                ));
+            }
+        }
+    }
+
+    /// Records the flags of a class declared with `#[Attribute]`.
+    fn attribute_class(&mut self, class: &ClassLike) {
+        let attribute = class
+            .attributes
+            .iter()
+            .flat_map(|group| &group.attributes)
+            .find(|attribute| is_attribute_class(&attribute.name));
+        let (Some(attribute), Some(name)) = (attribute, &class.name) else {
+            return;
+        };
+        if class.kind != ClassKind::Class || has_modifier(&class.modifiers, Keyword::Abstract) {
+            let kind = match class.kind {
+                ClassKind::Class => "abstract class",
+                kind => kind.as_str(),
+            };
+            self.diagnostics.push(Diagnostic::error(
+                format!("cannot apply #[\\Attribute] to {} {}", kind, name),
+                attribute.span,
+            ));
+            return;
+        }
+        let flags = match attribute.args.first() {
+            Some(arg) => match attribute_flags(&arg.value) {
+                Some(flags) => flags,
+                None => return,
+            },
+            None => AttributeFlags::TARGET_ALL,
+        };
+        let name = self.scope.qualify(&name.name).join("\\");
+        self.attribute_classes
+            .insert(name.to_ascii_lowercase(), AttributeFlags(flags));
+    }
+
+    fn class_name(&mut self, name: &mut Name) {
+        name.resolved = self.scope.resolve_class(name).map(Box::new);
+    }
+
+    /// Resolves the attributes of a declaration and records them for `check_attributes`.
+    fn attributes(
+        &mut self,
+        groups: &mut [AttributeGroup],
+        target: AttributeTarget,
+        promoted: bool,
+    ) {
+        let first = self.attribute_uses.len();
+        for group in groups {
+            for attribute in &mut group.attributes {
+                self.class_name(&mut attribute.name);
+                for arg in &mut attribute.args {
+                    self.visit_arg(arg);
+                }
+                let Some(ResolvedName::FullyQualified(parts)) = attribute.name.resolved.as_deref()
+                else {
+                    continue;
+                };
+                self.attribute_uses.push(AttributeUse {
+                    name: parts.join("\\"),
+                    target,
+                    promoted,
+                    repeated: false,
+                    span: attribute.span,
+                });
+            }
+        }
+        let uses = &mut self.attribute_uses[first..];
+        for i in 0..uses.len() {
+            let earlier = uses[..i]
+                .iter()
+                .filter(|other| other.name.eq_ignore_ascii_case(&uses[i].name))
+                .count();
+            uses[i].repeated = earlier == 1;
+        }
+    }
+}
+
+/// Attributes are resolved along with the declaration they belong to, which decides what
+/// they target, so the rest of the tree is walked with `visit_attribute_group` left out.
+impl VisitorMut for Resolver {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Namespace { name, body } => {
+                let namespace = name.as_ref().map_or(Vec::new(), |name| name.parts.clone());
+                self.scope = Scope::new(namespace);
+                if let Some(body) = body {
+                    self.visit_block(body);
+                    self.scope = Scope::default();
+                }
+            }
+            StmtKind::Use { kind, uses } => {
+                for item in uses {
+                    let parts = item.name.parts.clone();
+                    self.import(item.kind.unwrap_or(*kind), parts, item);
+                }
+            }
+            StmtKind::GroupUse { prefix, kind, uses } => {
+                for item in uses {
+                    let mut parts = prefix.parts.clone();
+                    parts.extend(item.name.parts.iter().cloned());
+                    self.import(item.kind.unwrap_or(*kind), parts, item);
+                }
+            }
+            StmtKind::Function(function) => {
+                self.check_declaration("function", &function.name.name, function.name.span);
+                self.visit_function_decl(function);
+            }
+            StmtKind::ClassLike(class) => {
+                if let Some(name) = &class.name {
+                    self.check_declaration(class.kind.as_str(), &name.name, name.span);
+                }
+                self.visit_class_like(class);
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::ConstFetch(name) => {
+                name.resolved = Some(Box::new(self.scope.resolve_const(name)))
+            }
+            ExprKind::Call { callee, args } => {
+                match &mut callee.kind {
+                    ExprKind::ConstFetch(name) => {
+                        name.resolved = Some(Box::new(self.scope.resolve_function(name)))
+                    }
+                    _ => self.visit_expr(callee),
+                }
+                self.visit_call_args(args);
+            }
+            ExprKind::Match { .. } => {
+                walk_expr(self, expr);
+                if let ExprKind::Match { arms, .. } = &expr.kind {
+                    self.check_match_arms(arms);
+                }
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn visit_class_like(&mut self, class: &mut ClassLike) {
+        self.attributes(&mut class.attributes, AttributeTarget::Class, false);
+        self.attribute_class(class);
+        walk_class_like(self, class);
+    }
+
+    fn visit_property(&mut self, property: &mut Property) {
+        self.attributes(&mut property.attributes, AttributeTarget::Property, false);
+        walk_property(self, property);
+    }
+
+    fn visit_class_const(&mut self, constant: &mut ClassConst) {
+        let target = AttributeTarget::ClassConstant;
+        self.attributes(&mut constant.attributes, target, false);
+        walk_class_const(self, constant);
+    }
+
+    fn visit_enum_case(&mut self, case: &mut EnumCase) {
+        let target = AttributeTarget::ClassConstant;
+        self.attributes(&mut case.attributes, target, false);
+        walk_enum_case(self, case);
+    }
+
+    fn visit_method(&mut self, method: &mut Method) {
+        let target = AttributeTarget::Method;
+        self.attributes(&mut method.function.attributes, target, false);
+        walk_function_decl(self, &mut method.function);
+    }
+
+    fn visit_function_decl(&mut self, function: &mut FunctionDecl) {
+        let target = AttributeTarget::Function;
+        self.attributes(&mut function.attributes, target, false);
+        walk_function_decl(self, function);
+    }
+
+    fn visit_param(&mut self, param: &mut Param) {
+        let promoted = param.is_promoted();
+        self.attributes(&mut param.attributes, AttributeTarget::Parameter, promoted);
+        walk_param(self, param);
+    }
+
+    fn visit_closure(&mut self, closure: &mut Closure) {
+        self.attributes(&mut closure.attributes, AttributeTarget::Function, false);
+        walk_closure(self, closure);
+    }
+
+    fn visit_arrow_function(&mut self, function: &mut ArrowFunction) {
+        self.attributes(&mut function.attributes, AttributeTarget::Function, false);
+        walk_arrow_function(self, function);
+    }
+
+    fn visit_attribute_group(&mut self, _group: &mut AttributeGroup) {}
+
+    fn visit_type_hint(&mut self, ty: &mut TypeHint) {
+        match &mut ty.kind {
+            TypeHintKind::Named(name) if BuiltinType::from_name(name).is_some() => {}
+            _ => walk_type_hint(self, ty),
+        }
+    }
+
+    /// Names that reach here are class names, as the names of functions, constants,
+    /// namespaces and imports are all handled by the node they belong to.
+    fn visit_name(&mut self, name: &mut Name) {
+        self.class_name(name);
+    }
+}
+
+/// Whether an attribute is `#[Attribute]`, which makes the class it is applied to an
+/// attribute class.
+fn is_attribute_class(name: &Name) -> bool {
+    matches!(
+        name.resolved.as_deref(),
+        Some(ResolvedName::FullyQualified(parts)) if parts.len() == 1
+            && parts[0].eq_ignore_ascii_case("Attribute")
+    )
+}
+
+/// The value of the flags given to `#[Attribute(...)]`, which are usually `Attribute::*`
+/// constants combined with `|`.
+fn attribute_flags(expr: &Expr) -> Option<u32> {
+    match &expr.kind {
+        ExprKind::Literal(Literal::Numeric(Numeric::Int(value))) => u32::try_from(*value).ok(),
+        ExprKind::ClassConstFetch {
+            class: ClassRef::Name(class),
+            constant: MemberName::Ident(constant),
+        } if is_attribute_class(class) => AttributeFlags::constant(constant.as_str()),
+        ExprKind::Binary {
+            op: BinaryOp::BitOr,
+            left,
+            right,
+        } => Some(attribute_flags(left)? | attribute_flags(right)?),
+        _ => None,
+    }
+}
+
+fn is_special_class(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "self" | "parent" | "static"
+    )
+}
+
+/// A canonical form of a condition whose value is known at compile time, two conditions with
+/// the same key are always identical. It is only for comparing, not for messages.
+fn constant_key(expr: &Expr) -> Option<String> {
+    match &expr.kind {
+        ExprKind::Literal(literal) => Some(match literal {
+            Literal::Null => "null".to_string(),
+            Literal::Bool(value) => value.to_string(),
+            Literal::Numeric(Numeric::Int(value)) => value.to_string(),
+            Literal::Numeric(Numeric::LInt(value)) => value.to_string(),
+            Literal::Numeric(Numeric::Float(value)) => format!("{:?}", value),
+            Literal::String(string) => format!("'{}'", String::from_utf8_lossy(string)),
+        }),
+        ExprKind::Unary {
+            op: UnaryOp::Minus,
+            expr,
+        } => match &expr.kind {
+            ExprKind::Literal(Literal::Numeric(_)) => {
+                constant_key(expr).map(|key| format!("-{}", key))
+            }
+            _ => None,
+        },
+        ExprKind::ConstFetch(name) => Some(match &name.resolved {
+            Some(resolved) => resolved.to_string(),
+            None => name.to_string(),
+        }),
+        ExprKind::ClassConstFetch {
+            class: ClassRef::Name(class),
+            constant: MemberName::Ident(constant),
+        } if !class.is_special_class() => {
+            let class = match &class.resolved {
+                Some(resolved) => resolved.to_string(),
+                None => class.to_string(),
+            };
+            Some(format!("{}::{}", class.to_ascii_lowercase(), constant))
+        }
+        _ => None,
+    }
+}
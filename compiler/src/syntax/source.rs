@@ -0,0 +1,200 @@
+use std::{fmt, ops::Range};
+
+/// A region of a source file, in bytes.
+///
+/// Spans are always relative to the start of the file they were lexed from, and can be used
+/// to slice the original source directly. Use a `SourceFile` to turn them into lines and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The smallest span that covers both `self` and `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Span::new(range.start, range.end)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// A resolved position within a `SourceFile`.
+///
+/// All fields are zero based, which is what the language server protocol expects.
+/// The `Display` implementation prints the one based `line:column` pair used in diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    /// The column counted in chars (unicode scalar values).
+    pub column: usize,
+    /// The column counted in UTF-16 code units, as used by LSP clients.
+    pub utf16_column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
+
+/// A single script along with the byte offset of every line start.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    name: String,
+    src: String,
+    /// The byte offset of the start of each line, the first line always starts at `0`.
+    /// Lines may end with `\r\n`, `\n` or a lone `\r`.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, src: impl Into<String>) -> Self {
+        let src = src.into();
+        let bytes = src.as_bytes();
+        let line_starts = std::iter::once(0)
+            .chain(bytes.iter().enumerate().filter_map(|(i, b)| match b {
+                b'\n' => Some(i + 1),
+                // a `\r\n` pair only ends one line.
+                b'\r' if bytes.get(i + 1) != Some(&b'\n') => Some(i + 1),
+                _ => None,
+            }))
+            .collect();
+
+        Self {
+            name: name.into(),
+            src,
+            line_starts,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The source text covered by `span`.
+    pub fn slice(&self, span: Span) -> &str {
+        &self.src[span.range()]
+    }
+
+    /// Gets the text of the given zero based line, without its line ending.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.src.len());
+        Some(self.src[start..end].trim_end_matches(['\r', '\n']))
+    }
+
+    /// The zero based line containing the byte `offset`.
+    pub fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+
+    /// Resolves a byte offset to a `Location`.
+    ///
+    /// Offsets past the end of the file are clamped to the end, and offsets that fall inside a
+    /// multi-byte char resolve to the start of that char.
+    pub fn lookup(&self, offset: usize) -> Location {
+        let mut offset = offset.min(self.src.len());
+        while !self.src.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_index(offset);
+        let prefix = &self.src[self.line_starts[line]..offset];
+        Location {
+            line,
+            column: prefix.chars().count(),
+            utf16_column: prefix.encode_utf16().count(),
+        }
+    }
+
+    /// Resolves both ends of a span.
+    pub fn lookup_span(&self, span: Span) -> (Location, Location) {
+        (self.lookup(span.start), self.lookup(span.end))
+    }
+
+    /// The byte offset of a zero based line and utf-16 column, the inverse of `lookup` for
+    /// positions sent by LSP clients.
+    pub fn offset_of_utf16(&self, line: usize, utf16_column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line)?;
+        let text = self.line(line)?;
+        let mut units = 0;
+        for (i, c) in text.char_indices() {
+            if units >= utf16_column {
+                return Some(start + i);
+            }
+            units += c.len_utf16();
+        }
+        Some(start + text.len())
+    }
+}
+
+/// An identifier for a file registered in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(usize);
+
+/// Owns every `SourceFile` that is part of a compilation.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, src: impl Into<String>) -> FileId {
+        self.files.push(SourceFile::new(name, src));
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files.iter().enumerate().map(|(i, f)| (FileId(i), f))
+    }
+}
@@ -1 +1,2 @@
+mod runtime;
 mod syntax;
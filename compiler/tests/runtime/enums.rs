@@ -0,0 +1,207 @@
+use compiler::{
+    runtime::{
+        enums::{BackingValue, EnumClass},
+        vm::ScriptError,
+        RuntimeError,
+    },
+    syntax::{ast::stmt::StmtKind, parse::parse_snippet},
+};
+
+use super::{output, run};
+
+#[track_caller]
+fn declare(source: &str) -> Result<EnumClass, String> {
+    let script = match parse_snippet(source) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    };
+    match &script.stmts[0].kind {
+        StmtKind::ClassLike(class) => EnumClass::new(class).map_err(|e| e.message),
+        other => panic!("expected an enum, found {:?}", other),
+    }
+}
+
+fn string(value: &str) -> BackingValue {
+    BackingValue::String(value.into())
+}
+
+#[test]
+pub fn cases_and_identity() {
+    let suit = declare("enum Suit { case Hearts; case Spades; }").unwrap();
+    let names: Vec<&str> = suit.cases().iter().map(|case| case.name()).collect();
+    assert_eq!(names, ["Hearts", "Spades"]);
+
+    let hearts = suit.case("Hearts").unwrap();
+    assert_eq!(hearts, suit.cases()[0]);
+    assert_ne!(hearts, suit.cases()[1]);
+    assert_eq!(hearts.value(), None);
+    assert_eq!(hearts.to_string(), "Suit::Hearts");
+    assert!(suit.case("hearts").is_none());
+
+    // cases of another enum are never identical, even with the same name.
+    let other = declare("enum Suit { case Hearts; case Spades; }").unwrap();
+    assert_ne!(hearts, other.case("Hearts").unwrap());
+}
+
+#[test]
+pub fn backed_enums() {
+    let suit = declare(
+        "enum Suit: string {
+            const PREFIX = 'S';
+            case Hearts = 'H';
+            case Spades = self::PREFIX;
+            case Clubs = 'C' . 1;
+        }",
+    )
+    .unwrap();
+    assert!(suit.is_backed());
+    assert_eq!(suit.from(string("H")).unwrap().name(), "Hearts");
+    assert_eq!(
+        suit.from(string("S")).unwrap(),
+        suit.case("Spades").unwrap()
+    );
+    assert_eq!(suit.case("Clubs").unwrap().value(), Some(&string("C1")));
+    assert_eq!(
+        suit.from(string("X")),
+        Err(RuntimeError::new(
+            "ValueError",
+            "\"X\" is not a valid backing value for enum Suit"
+        ))
+    );
+    assert_eq!(suit.try_from(string("X")), Ok(None));
+
+    let status = declare("enum Status: int { case Active = 1; case Banned = -1; }").unwrap();
+    assert_eq!(
+        status.try_from(string("-1")).unwrap().unwrap().name(),
+        "Banned"
+    );
+    assert_eq!(
+        status.from(BackingValue::Int(2)).unwrap_err().message,
+        "2 is not a valid backing value for enum Status"
+    );
+    assert_eq!(
+        status.from(string("one")).unwrap_err(),
+        RuntimeError::new(
+            "TypeError",
+            "Status::from(): Argument #1 ($value) must be of type int, string given"
+        )
+    );
+
+    let pure = declare("enum Pure { case A; }").unwrap();
+    assert_eq!(
+        pure.from(BackingValue::Int(1)).unwrap_err().message,
+        "Call to undefined method Pure::from()"
+    );
+}
+
+#[test]
+pub fn cases_are_readonly() {
+    let suit = declare("enum Suit: int { case Hearts = 1; }").unwrap();
+    let hearts = suit.case("Hearts").unwrap();
+    assert_eq!(
+        hearts.set_property("value").unwrap_err().message,
+        "Cannot modify readonly property Suit::$value"
+    );
+    assert_eq!(
+        hearts.set_property("name").unwrap_err().message,
+        "Cannot modify readonly property Suit::$name"
+    );
+    assert_eq!(
+        hearts.set_property("color").unwrap_err().message,
+        "Cannot create dynamic property Suit::$color"
+    );
+}
+
+#[test]
+pub fn invalid_case_values() {
+    assert_eq!(
+        declare("enum A: int { case B = 'b'; }").unwrap_err(),
+        "enum case type string does not match enum backing type int"
+    );
+    assert_eq!(
+        declare("enum A: int { case B = 1; case C = 1; }").unwrap_err(),
+        "duplicate value in enum A for cases B and C"
+    );
+    assert_eq!(
+        declare("enum A: int { case B = f(); }").unwrap_err(),
+        "enum case value must be compile-time evaluatable"
+    );
+    assert_eq!(
+        declare("enum A: int { const X = self::Y; const Y = self::X; case B = self::X; }")
+            .unwrap_err(),
+        "enum case value must be compile-time evaluatable"
+    );
+}
+
+#[track_caller]
+fn uncaught(source: &str, class: &str, message: &str) {
+    let (_, result) = run(source);
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(class, message))),
+        "while running `{}`",
+        source
+    );
+}
+
+#[test]
+pub fn enums_in_scripts() {
+    let source = r#"
+        enum Suit: string {
+            case Hearts = 'H';
+            case Spades = 'S';
+
+            public function color(): string {
+                return $this === self::Hearts ? 'Red' : 'Black';
+            }
+        }
+        foreach (Suit::cases() as $case) {
+            echo $case->name, '=', $case->value, ' ', $case->color(), "\n";
+        }
+        echo Suit::from('S') === Suit::Spades ? 'same' : 'other', "\n";
+        echo Suit::tryFrom('X') === null ? 'none' : 'some', "\n";
+        echo Suit::Hearts instanceof BackedEnum ? 'backed' : 'pure', "\n";
+    "#;
+    assert_eq!(
+        output(source),
+        "Hearts=H Red\nSpades=S Black\nsame\nnone\nbacked\n"
+    );
+
+    uncaught(
+        "enum Status: int { case Active = 1; } Status::from(2);",
+        "ValueError",
+        "2 is not a valid backing value for enum Status",
+    );
+    uncaught(
+        "enum Pure { case A; } Pure::from(1);",
+        "Error",
+        "Call to undefined method Pure::from()",
+    );
+
+    let (_, result) = run("enum A: int { case B = 1; case C = 1; }");
+    assert!(matches!(result, Err(ScriptError::Compile(_))));
+}
+
+#[test]
+pub fn case_properties_are_readonly_in_scripts() {
+    uncaught(
+        "enum Suit { case Hearts; } $case = Suit::Hearts; $case->name = 'x';",
+        "Error",
+        "Cannot modify readonly property Suit::$name",
+    );
+    uncaught(
+        "enum Suit: int { case Hearts = 1; } Suit::Hearts->value++;",
+        "Error",
+        "Cannot modify readonly property Suit::$value",
+    );
+    uncaught(
+        "enum Suit { case Hearts; } unset(Suit::Hearts->name);",
+        "Error",
+        "Cannot unset readonly property Suit::$name",
+    );
+    uncaught(
+        "enum Suit { case Hearts; } Suit::Hearts->color = 'red';",
+        "Error",
+        "Cannot create dynamic property Suit::$color",
+    );
+}
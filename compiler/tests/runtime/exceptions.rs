@@ -0,0 +1,218 @@
+use compiler::runtime::{vm::ScriptError, RuntimeError};
+
+use super::{output, run};
+
+#[test]
+pub fn multi_catch_and_catch_without_variable() {
+    let source = r#"
+        function check($e) {
+            try {
+                throw $e;
+            } catch (InvalidArgumentException | OutOfRangeException $caught) {
+                return "logic " . $caught->getMessage();
+            } catch (RuntimeException) {
+                return "runtime";
+            } catch (Throwable $t) {
+                return "other " . get_class($t);
+            }
+        }
+        echo check(new InvalidArgumentException("a")), ", ";
+        echo check(new OutOfRangeException("b")), ", ";
+        echo check(new UnderflowException("c")), ", ";
+        echo check(new TypeError("d"));
+    "#;
+    assert_eq!(output(source), "logic a, logic b, runtime, other TypeError");
+}
+
+#[test]
+pub fn finally_runs_in_every_frame_while_unwinding() {
+    let source = r#"
+        function inner() {
+            try { throw new LogicException("deep"); }
+            finally { echo "inner "; }
+        }
+        function middle() {
+            try { inner(); }
+            catch (RuntimeException $e) { echo "wrong "; }
+            finally { echo "middle "; }
+        }
+        try { middle(); }
+        catch (LogicException $e) { echo "caught ", $e->getMessage(); }
+        finally { echo " outer"; }
+    "#;
+    assert_eq!(output(source), "inner middle caught deep outer");
+}
+
+#[test]
+pub fn finally_runs_on_return_and_break() {
+    let source = r#"
+        function f() {
+            try { return "returned"; }
+            finally { echo "finally "; }
+        }
+        echo f(), "\n";
+        foreach ([1, 2, 3] as $i) {
+            foreach ([1, 2] as $j) {
+                try {
+                    if ($i == 2) { continue 2; }
+                    if ($i == 3) { break 2; }
+                    echo "$i.$j ";
+                } finally {
+                    echo "f ";
+                }
+            }
+        }
+    "#;
+    assert_eq!(output(source), "finally returned\n1.1 f 1.2 f f f ");
+}
+
+#[test]
+pub fn return_in_finally_overrides() {
+    let source = r#"
+        function discard() {
+            try { throw new Exception("lost"); }
+            finally { return "finally wins"; }
+        }
+        function replace() {
+            try { return "try"; }
+            finally { return "finally"; }
+        }
+        echo discard(), ", ", replace();
+    "#;
+    assert_eq!(output(source), "finally wins, finally");
+}
+
+#[test]
+pub fn throw_in_finally_replaces_the_exception() {
+    let source = r#"
+        try {
+            try { throw new Exception("first"); }
+            finally { throw new Exception("second"); }
+        } catch (Exception $e) {
+            echo $e->getMessage();
+        }
+    "#;
+    assert_eq!(output(source), "second");
+}
+
+#[test]
+pub fn rethrow_with_previous() {
+    let source = r#"
+        class AppException extends RuntimeException {
+            public function __construct($message, $previous) {
+                parent::__construct("app: " . $message, 42, $previous);
+            }
+        }
+        try {
+            try { intdiv(1, 0); }
+            catch (DivisionByZeroError $e) { throw new AppException("failed", $e); }
+        } catch (Exception $e) {
+            echo $e->getMessage(), " ", $e->getCode(), " ";
+            echo get_class($e->getPrevious()), ": ", $e->getPrevious()->getMessage();
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "app: failed 42 DivisionByZeroError: Division by zero"
+    );
+}
+
+#[test]
+pub fn engine_errors_are_catchable() {
+    let source = r#"
+        $attempts = [
+            fn() => undefined_function(),
+            fn() => new Missing(),
+            fn() => null->method(),
+            fn() => 1 % 0,
+            fn() => [] + 1,
+        ];
+        foreach ($attempts as $attempt) {
+            try { $attempt(); }
+            catch (Error $e) { echo get_class($e), ": ", $e->getMessage(), "\n"; }
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "Error: Call to undefined function undefined_function()\n\
+         Error: Class \"Missing\" not found\n\
+         Error: Call to a member function method() on null\n\
+         DivisionByZeroError: Modulo by zero\n\
+         TypeError: Unsupported operand types: array + int\n"
+    );
+}
+
+#[test]
+pub fn unhandled_match() {
+    let source = r#"
+        foreach ([5, "x", null, [1, 2], new stdClass()] as $value) {
+            try { match ($value) { 1 => 2 }; }
+            catch (UnhandledMatchError $e) { echo $e->getMessage(), "\n"; }
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "Unhandled match case 5\nUnhandled match case 'x'\nUnhandled match case NULL\n\
+         Unhandled match case of type array\nUnhandled match case of type stdClass\n"
+    );
+}
+
+#[test]
+pub fn exception_details() {
+    let source = "\n$e = new Exception(\"message\");\necho $e->getLine(), \" \", $e;";
+    assert_eq!(
+        output(source),
+        "2 Exception: message in test.php:2\nStack trace:\n#0 {main}"
+    );
+}
+
+#[test]
+pub fn global_exception_handler() {
+    let source = r#"
+        set_exception_handler(function (Throwable $e) {
+            echo "handled ", $e->getMessage();
+        });
+        try {
+            echo "start ";
+        } finally {
+            echo "finally ";
+        }
+        throw new Exception("late");
+        echo "unreachable";
+    "#;
+    assert_eq!(output(source), "start finally handled late");
+
+    let source = r#"
+        set_exception_handler(fn($e) => print("first"));
+        restore_exception_handler();
+        throw new Exception("nobody");
+    "#;
+    let (_, result) = run(source);
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "Exception",
+            "nobody"
+        )))
+    );
+}
+
+#[test]
+pub fn uncaught_exceptions() {
+    let (output, result) = run(
+        "function f() { try { throw new DomainException('oops'); } finally { echo 'finally '; } }
+        try { f(); } catch (LogicException $e) { echo 'caught '; throw $e; }
+        echo 'after';",
+    );
+    assert_eq!(output, "finally caught ");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "DomainException",
+            "oops"
+        )))
+    );
+
+    let (_, result) = run("try { $a; }");
+    assert!(matches!(result, Err(ScriptError::Compile(_))));
+}
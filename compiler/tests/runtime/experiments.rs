@@ -0,0 +1,121 @@
+use compiler::{
+    runtime::vm::{ScriptError, Vm},
+    syntax::{
+        experiments::{Experiment, Experiments},
+        source::SourceFile,
+    },
+};
+
+use super::{output, run};
+
+/// Runs a script with `experiments` enabled, giving what it echoed and how it ended.
+fn run_with(source: &str, experiments: Experiments) -> (String, Result<(), ScriptError>) {
+    let mut vm = Vm::new().with_experiments(experiments);
+    let result = vm.run_snippet(SourceFile::new("test.php", source));
+    (String::from_utf8_lossy(vm.output()).into_owned(), result)
+}
+
+/// The messages of the compile errors of a script.
+fn compile_errors(result: Result<(), ScriptError>) -> Vec<String> {
+    match result {
+        Err(ScriptError::Compile(diagnostics)) => {
+            diagnostics.into_iter().map(|d| d.message).collect()
+        }
+        other => panic!("expected compile errors, found {:?}", other),
+    }
+}
+
+#[test]
+pub fn async_functions_run_when_awaited() {
+    let experiments = Experiments::default().with(Experiment::AsyncAwait);
+    let source = r#"
+        async function fetch($id) {
+            echo "fetch $id\n";
+            return "row $id";
+        }
+        async function both(): string {
+            $a = await fetch(1);
+            $b = await fetch(2);
+            return "$a, $b";
+        }
+        $pending = both();
+        echo "started\n";
+        echo await $pending, "\n";
+        echo await $pending, "\n";
+        echo await 42, "\n";
+
+        async function three() { echo "three\n"; return 3; }
+        $double = async fn($x) => 2 * await $x;
+        echo await $double(three()), "\n";
+
+        class Repo {
+            public async function find() { throw new RuntimeException("missing"); }
+        }
+        $find = (new Repo)->find();
+        try {
+            await $find;
+        } catch (RuntimeException $e) {
+            echo "caught ", $e->getMessage(), "\n";
+        }
+    "#;
+    let (echoed, result) = run_with(source, experiments);
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        echoed,
+        "started\nfetch 1\nfetch 2\nrow 1, row 2\nrow 1, row 2\n42\nthree\n6\ncaught missing\n"
+    );
+
+    let (_, result) = run_with("function f() { await g(); }", experiments);
+    assert_eq!(
+        compile_errors(result),
+        [
+            "the \"await\" expression can only be used inside an async function or at the top \
+          level of a script"
+        ]
+    );
+    let (_, result) = run_with("async function f() { yield 1; }", experiments);
+    assert_eq!(
+        compile_errors(result),
+        ["the \"yield\" expression can not be used inside an async function"]
+    );
+}
+
+#[test]
+pub fn experiments_are_off_by_default() {
+    // PHP scripts may use the names of the experimental keywords.
+    assert_eq!(
+        output("function await($x) { return $x; } const ASYNC = 1; echo await(ASYNC);"),
+        "1"
+    );
+
+    let (_, result) = run("async function f() {}");
+    assert_eq!(
+        compile_errors(result),
+        ["async functions require the `async-await` experiment, which is not enabled"]
+    );
+    let (_, result) = run("echo 10n;");
+    assert_eq!(
+        compile_errors(result),
+        ["long integer literals require the `long-int` experiment, which is not enabled"]
+    );
+}
+
+#[test]
+pub fn local_constants_and_long_ints() {
+    let source = r#"
+        function setup() {
+            const LIMIT = 10;
+            if (LIMIT > 5) {
+                const LARGE = true;
+            }
+        }
+        setup();
+        var_dump(LIMIT, LARGE, 9223372036854775807n, 9223372036854775808n);
+    "#;
+    let (echoed, result) = run_with(source, Experiments::all());
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        echoed,
+        "int(10)\nbool(true)\nint(9223372036854775807)\nfloat(9.223372036854776E+18)\n"
+    );
+}
@@ -0,0 +1,276 @@
+use compiler::runtime::{vm::ScriptError, RuntimeError};
+
+use super::{output, run};
+
+#[test]
+pub fn generators_are_lazy() {
+    let source = r#"
+        function numbers($n) {
+            echo "start ";
+            for ($i = 1; $i <= $n; $i++) {
+                echo "[$i] ";
+                yield $i;
+            }
+            echo "end ";
+        }
+        $gen = numbers(3);
+        echo get_class($gen), " ", $gen instanceof Traversable ? "traversable " : "";
+        foreach ($gen as $key => $value) {
+            echo "$key=$value ";
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "Generator traversable start [1] 0=1 [2] 1=2 [3] 2=3 end "
+    );
+}
+
+#[test]
+pub fn keys_and_pipelines() {
+    let source = r#"
+        function pairs() {
+            yield 'a' => 1;
+            yield 2;
+            yield 10 => 3;
+            yield 4;
+            yield;
+        }
+        foreach (pairs() as $k => $v) echo var_export($k, true), "=>", var_export($v, true), " ";
+        echo "\n";
+
+        function map(callable $f, iterable $items) {
+            foreach ($items as $key => $item) yield $key => $f($item);
+        }
+        function take($n, $items) {
+            foreach ($items as $item) {
+                if ($n-- <= 0) return;
+                yield $item;
+            }
+        }
+        function naturals() {
+            $i = 0;
+            while (true) yield $i++;
+        }
+        $squares = map(fn($x) => $x * $x, naturals());
+        echo implode(",", iterator_to_array(take(5, $squares), false));
+    "#;
+    assert_eq!(
+        output(source),
+        "'a'=>1 0=>2 10=>3 11=>4 12=>NULL \n0,1,4,9,16"
+    );
+}
+
+#[test]
+pub fn send_and_get_return() {
+    let source = r#"
+        function logger() {
+            $count = 0;
+            while (true) {
+                $line = yield $count;
+                if ($line === null) return $count;
+                echo "log: $line\n";
+                $count++;
+            }
+        }
+        $log = logger();
+        var_dump($log->current());
+        var_dump($log->send("one"));
+        var_dump($log->send("two"));
+        $log->next();
+        var_dump($log->valid(), $log->current(), $log->getReturn());
+        try {
+            logger()->getReturn();
+        } catch (Exception $e) {
+            echo $e->getMessage(), "\n";
+        }
+        $fresh = logger();
+        var_dump($fresh->send("first"));
+    "#;
+    assert_eq!(
+        output(source),
+        "int(0)\nlog: one\nint(1)\nlog: two\nint(2)\nbool(false)\nNULL\nint(2)\n\
+         Cannot get return value of a generator that hasn't returned\nlog: first\nint(1)\n"
+    );
+}
+
+#[test]
+pub fn yield_from_delegates() {
+    let source = r#"
+        class Letters implements IteratorAggregate {
+            public function getIterator(): Iterator { yield 'x' => 'y'; }
+        }
+        function inner() {
+            $got = yield 1;
+            echo "inner got $got\n";
+            yield 2;
+            return "inner done";
+        }
+        function outer() {
+            yield 0;
+            $result = yield from inner();
+            echo "result: $result\n";
+            yield from [10 => 'a', 11 => 'b'];
+            yield from new Letters();
+            return yield 3;
+        }
+        $gen = outer();
+        foreach ($gen as $k => $v) {
+            echo "$k => $v\n";
+            if ($v === 1) $gen->send("hello");
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "0 => 0\n0 => 1\ninner got hello\nresult: inner done\n\
+         10 => a\n11 => b\nx => y\n1 => 3\n"
+    );
+}
+
+#[test]
+pub fn throw_into_generators() {
+    let source = r#"
+        function worker() {
+            while (true) {
+                try {
+                    $job = yield;
+                    echo "job $job\n";
+                } catch (RuntimeException $e) {
+                    echo "recovered from ", $e->getMessage(), "\n";
+                    yield "retry";
+                } finally {
+                    echo "cleanup\n";
+                }
+            }
+        }
+        $w = worker();
+        $w->send("a");
+        echo $w->throw(new RuntimeException("failure")), "\n";
+        function delegating() {
+            try {
+                yield from worker();
+            } catch (LogicException $e) {
+                echo "outer caught ", $e->getMessage(), "\n";
+            }
+        }
+        $d = delegating();
+        $d->current();
+        $d->throw(new LogicException("bad"));
+        var_dump($d->valid());
+        try {
+            $d->throw(new LogicException("again"));
+        } catch (LogicException $e) {
+            echo "caller caught ", $e->getMessage(), "\n";
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "job a\ncleanup\nrecovered from failure\nretry\ncleanup\nouter caught bad\n\
+         bool(false)\ncaller caught again\ncleanup\n"
+    );
+}
+
+#[test]
+pub fn destroyed_generators_run_finally() {
+    let source = r#"
+        function gen($name) {
+            try {
+                echo "start $name\n";
+                yield 1;
+                yield 2;
+            } finally {
+                echo "finally $name\n";
+            }
+        }
+        $g = gen("a");
+        $g->current();
+        unset($g);
+        echo "after unset\n";
+        $g = gen("b");
+        foreach ($g as $value) { break; }
+        $g = null;
+        // a generator that never started is not in its try yet.
+        $g = gen("c");
+        unset($g);
+        function local() { $g = gen("d"); $g->current(); }
+        local();
+        $g = gen("e");
+        $g->current();
+        echo "end\n";
+    "#;
+    assert_eq!(
+        output(source),
+        "start a\nfinally a\nafter unset\nstart b\nfinally b\nstart d\nfinally d\n\
+         start e\nend\nfinally e\n"
+    );
+
+    let source = r#"
+        function gen() {
+            try { yield; } finally { throw new Exception("from finally"); }
+        }
+        try {
+            $g = gen();
+            $g->current();
+            unset($g);
+        } catch (Exception $e) {
+            echo "caught ", $e->getMessage();
+        }
+    "#;
+    assert_eq!(output(source), "caught from finally");
+}
+
+#[test]
+pub fn generator_errors() {
+    let source = r#"
+        function gen() { yield 1; yield 2; }
+        $g = gen();
+        foreach ($g as $v) {}
+        try {
+            foreach ($g as $v) {}
+        } catch (Exception $e) {
+            echo $e->getMessage(), "\n";
+        }
+        try {
+            new Generator();
+        } catch (Error $e) {
+            echo $e->getMessage(), "\n";
+        }
+        function bad() { yield from 1; }
+        try {
+            bad()->current();
+        } catch (Error $e) {
+            echo $e->getMessage(), "\n";
+        }
+        function typed(): iterable { yield 1; return "ignored"; }
+        var_dump(typed()->current());
+    "#;
+    assert_eq!(
+        output(source),
+        "Cannot rewind a generator that was already run\n\
+         The \"Generator\" class is reserved for internal use and cannot be manually instantiated\n\
+         Can use \"yield from\" only with arrays and Traversables\nint(1)\n"
+    );
+
+    let (_, result) = run(
+        "function f() { yield 1; throw new DomainException('inside'); } \
+         foreach (f() as $v) echo $v;",
+    );
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "DomainException",
+            "inside"
+        )))
+    );
+    let (_, result) = run("class G extends Generator {} (new G)->current();");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "Error",
+            "Class G cannot extend final class Generator"
+        )))
+    );
+    for source in ["yield 1;", "function f(): int { yield 1; }"] {
+        let (_, result) = run(source);
+        assert!(matches!(result, Err(ScriptError::Compile(_))), "{}", source);
+    }
+}
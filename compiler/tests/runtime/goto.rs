@@ -0,0 +1,60 @@
+use super::output;
+
+#[test]
+pub fn goto_forwards_and_backwards() {
+    let source = r#"
+        $i = 0;
+        again:
+        $i++;
+        if ($i < 3) goto again;
+        echo $i;
+        goto end;
+        echo "skipped";
+        end:
+        echo " done";
+    "#;
+    assert_eq!(output(source), "3 done");
+}
+
+#[test]
+pub fn goto_out_of_loops() {
+    let source = r#"
+        function find($rows, $wanted) {
+            foreach ($rows as $y => $row) {
+                foreach ($row as $x => $value) {
+                    if ($value == $wanted) goto found;
+                }
+            }
+            return "missing";
+            found:
+            return "$x,$y";
+        }
+        echo find([[1, 2], [3, 4]], 3), " ", find([[1]], 5), " ";
+        switch (1) {
+            case 1:
+                while (true) { goto out; }
+        }
+        out:
+        echo "out";
+    "#;
+    assert_eq!(output(source), "0,1 missing out");
+}
+
+#[test]
+pub fn goto_out_of_try_runs_finally() {
+    let source = r#"
+        function f() {
+            try {
+                try { goto done; }
+                finally { echo "inner "; }
+            } finally {
+                echo "outer ";
+            }
+            echo "skipped";
+            done:
+            return "done";
+        }
+        echo f();
+    "#;
+    assert_eq!(output(source), "inner outer done");
+}
@@ -0,0 +1,29 @@
+mod enums;
+mod exceptions;
+mod experiments;
+mod generators;
+mod goto;
+mod reflection;
+mod types;
+mod vm;
+
+use compiler::{
+    runtime::vm::{ScriptError, Vm},
+    syntax::source::SourceFile,
+};
+
+/// Runs PHP code without a `<?php`, giving what it echoed and how it ended.
+pub fn run(source: &str) -> (String, Result<(), ScriptError>) {
+    let mut vm = Vm::new();
+    let result = vm.run_snippet(SourceFile::new("test.php", source));
+    (String::from_utf8_lossy(vm.output()).into_owned(), result)
+}
+
+/// Runs a script that must succeed, giving what it echoed.
+#[track_caller]
+pub fn output(source: &str) -> String {
+    match run(source) {
+        (output, Ok(())) => output,
+        (output, Err(e)) => panic!("`{}` failed with {} after echoing {:?}", source, e, output),
+    }
+}
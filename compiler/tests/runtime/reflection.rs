@@ -0,0 +1,113 @@
+use compiler::runtime::{vm::ScriptError, RuntimeError};
+
+use super::{output, run};
+
+#[test]
+pub fn attributes_of_classes_methods_and_functions() {
+    let source = r#"
+        namespace App;
+
+        #[\Attribute(\Attribute::TARGET_CLASS | \Attribute::TARGET_METHOD)]
+        class Route {
+            public function __construct(public string $path, public array $methods = ['GET']) {}
+        }
+
+        #[\Attribute]
+        class Tag {}
+
+        #[Route('/users'), Tag]
+        class Users {
+            const PREFIX = '/users/';
+
+            #[Route(self::PREFIX . '{id}', methods: ['GET', 'HEAD'])]
+            public function show() {}
+        }
+
+        #[Tag]
+        function helper() {}
+
+        $class = new \ReflectionClass(Users::class);
+        foreach ($class->getAttributes() as $attribute) {
+            echo $attribute->getName(), "\n";
+        }
+        $route = $class->getAttributes(Route::class)[0]->newInstance();
+        echo $route->path, ' ', implode(',', $route->methods), "\n";
+
+        $method = new \ReflectionMethod(new Users(), 'show');
+        $attribute = $method->getAttributes()[0];
+        var_export($attribute->getArguments());
+        echo "\n", $attribute->newInstance()->path, "\n";
+        echo count($class->getMethod('show')->getAttributes(Tag::class)), "\n";
+
+        $function = new \ReflectionFunction('App\helper');
+        echo $function->getName(), ' ', count($function->getAttributes()), "\n";
+    "#;
+    assert_eq!(
+        output(source),
+        "App\\Route\nApp\\Tag\n/users GET\n\
+         array (\n  0 => '/users/{id}',\n  'methods' => \n  array (\n    0 => 'GET',\n    1 => 'HEAD',\n  ),\n)\n\
+         /users/{id}\n0\nApp\\helper 1\n"
+    );
+}
+
+#[test]
+pub fn attribute_errors() {
+    let (_, result) = run("#[Missing] class A {}
+        (new ReflectionClass('A'))->getAttributes()[0]->newInstance();");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "Error",
+            "Attribute class \"Missing\" not found"
+        )))
+    );
+
+    let (_, result) = run("class Plain {} #[Plain] function f() {}
+        (new ReflectionFunction('f'))->getAttributes()[0]->newInstance();");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "Error",
+            "Attempting to use non-attribute class \"Plain\" as attribute"
+        )))
+    );
+
+    let (_, result) = run("new ReflectionClass('Nope');");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "ReflectionException",
+            "Class \"Nope\" does not exist"
+        )))
+    );
+
+    let source = r#"
+        try {
+            (new ReflectionAttribute())->getName();
+        } catch (Error $e) {
+            echo $e->getMessage(), "\n";
+        }
+        class A {
+            static function make() { return new ReflectionAttribute(); }
+        }
+        try {
+            A::make();
+        } catch (Error $e) {
+            echo $e->getMessage(), "\n";
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "Call to private ReflectionAttribute::__construct() from global scope\n\
+         Call to private ReflectionAttribute::__construct() from scope A\n"
+    );
+
+    let (_, result) = run("class R extends ReflectionAttribute {} (new R)->newInstance();");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "Error",
+            "Class R cannot extend final class ReflectionAttribute"
+        )))
+    );
+}
@@ -0,0 +1,171 @@
+use super::output;
+
+/// The message of the `TypeError` a script throws.
+#[track_caller]
+fn type_error(source: &str) -> String {
+    let source = format!(
+        "try {{ {} }} catch (TypeError $e) {{ echo get_class($e), ': ', $e->getMessage(); }}",
+        source
+    );
+    output(&source)
+}
+
+#[test]
+pub fn weak_mode_coerces_scalars() {
+    let source = r#"
+        function i(int $a) { var_dump($a); }
+        function f(float $a) { var_dump($a); }
+        function s(string $a) { var_dump($a); }
+        function b(bool $a) { var_dump($a); }
+        i("12"); i(3.0); i(true);
+        f(2); f("1.5");
+        s(7); s(1.5); s(false);
+        b(0); b("yes");
+    "#;
+    assert_eq!(
+        output(source),
+        "int(12)\nint(3)\nint(1)\nfloat(2)\nfloat(1.5)\nstring(1) \"7\"\nstring(3) \"1.5\"\n\
+         string(0) \"\"\nbool(false)\nbool(true)\n"
+    );
+    assert_eq!(
+        type_error(r#"function i(int $a) {} i("abc");"#),
+        "TypeError: i(): Argument #1 ($a) must be of type int, string given"
+    );
+    assert_eq!(
+        type_error("function s(string $a) {} s(null);"),
+        "TypeError: s(): Argument #1 ($a) must be of type string, null given"
+    );
+}
+
+#[test]
+pub fn strict_mode_only_widens_ints() {
+    let source = r#"
+        declare(strict_types=1);
+        function f(float $a) { var_dump($a); }
+        f(2);
+        function i(int $a) {}
+        try { i("1"); } catch (TypeError $e) { echo $e->getMessage(), "\n"; }
+        function r($a): string { return $a; }
+        try { r(1); } catch (TypeError $e) { echo $e->getMessage(), "\n"; }
+        // callbacks from the runtime are passed in weak mode.
+        var_dump(array_map(fn(int $a) => $a, ["4"]));
+    "#;
+    assert_eq!(
+        output(source),
+        "float(2)\ni(): Argument #1 ($a) must be of type int, string given\n\
+         r(): Return value must be of type string, int returned\n\
+         array(1) {\n  [0]=>\n  int(4)\n}\n"
+    );
+}
+
+#[test]
+pub fn builtin_arguments() {
+    let source = r#"
+        declare(strict_types=1);
+        try { echo strlen(5); } catch (TypeError $e) { echo $e->getMessage(), "\n"; }
+        try { str_repeat("a", "2"); } catch (TypeError $e) { echo $e->getMessage(), "\n"; }
+        try { strlen(null); } catch (TypeError $e) { echo $e->getMessage(), "\n"; }
+        echo round(2), " ", floor(1.5), "\n";
+    "#;
+    assert_eq!(
+        output(source),
+        "strlen(): Argument #1 ($string) must be of type string, int given\n\
+         str_repeat(): Argument #2 ($times) must be of type int, string given\n\
+         strlen(): Argument #1 ($string) must be of type string, null given\n\
+         2 1\n"
+    );
+
+    // weak mode converts what it can, and still lets null through.
+    let source = r#"
+        echo strlen(12345), " ", str_repeat("ab", "2"), " ", strlen(null), "\n";
+        try { strlen([]); } catch (TypeError $e) { echo $e->getMessage(), "\n"; }
+        try { intdiv("x", 1); } catch (TypeError $e) { echo $e->getMessage(); }
+    "#;
+    assert_eq!(
+        output(source),
+        "5 abab 0\n\
+         strlen(): Argument #1 ($string) must be of type string, array given\n\
+         intdiv(): Argument #1 ($num1) must be of type int, string given"
+    );
+}
+
+#[test]
+pub fn return_types() {
+    let source = r#"
+        function n($a): ?int { return $a; }
+        function v(): void { return; }
+        function u($a): int|string { return $a; }
+        var_dump(n(null), n("5"), v(), u(1.5), u(2.0));
+    "#;
+    assert_eq!(
+        output(source),
+        "NULL\nint(5)\nNULL\nstring(3) \"1.5\"\nint(2)\n"
+    );
+    assert_eq!(
+        type_error("function f(): int { return []; } f();"),
+        "TypeError: f(): Return value must be of type int, array returned"
+    );
+    assert_eq!(
+        type_error("function f(): never { } f();"),
+        "TypeError: f(): never-returning function must not implicitly return"
+    );
+}
+
+#[test]
+pub fn class_and_nullable_types() {
+    let source = r#"
+        interface Shape {}
+        class Square implements Shape {
+            public function with(self $other): static { return $this; }
+        }
+        class Named { public function __toString(): string { return "named"; } }
+        function area(Shape $shape, ?string $label = null, int $size = null) {
+            echo get_class($shape), " ", var_export($label, true), " ", var_export($size, true), "\n";
+        }
+        area(new Square);
+        area(new Square, new Named, null);
+        echo get_class((new Square)->with(new Square)), "\n";
+        function sum(int ...$numbers) { return array_sum($numbers); }
+        echo sum(1, "2", 3.0), "\n";
+    "#;
+    assert_eq!(
+        output(source),
+        "Square NULL NULL\nSquare 'named' NULL\nSquare\n6\n"
+    );
+    assert_eq!(
+        type_error("class A {} function f(A $a) {} f(new stdClass);"),
+        "TypeError: f(): Argument #1 ($a) must be of type A, stdClass given"
+    );
+    assert_eq!(
+        type_error("function sum(int ...$n) {} sum(1, 'x');"),
+        "TypeError: sum(): Argument #2 ($n) must be of type int, string given"
+    );
+}
+
+#[test]
+pub fn tick_functions() {
+    let source = r#"
+        declare(ticks=1);
+        function tick($name) { echo "[$name]"; }
+        register_tick_function('tick', 't');
+        $a = 1;
+        echo "a";
+        unregister_tick_function('tick');
+        echo "b";
+    "#;
+    assert_eq!(output(source), "[t][t]a[t]b");
+
+    let source = r#"
+        register_tick_function(function () { echo "."; });
+        $a = 1;
+        declare(ticks=2) {
+            $a = 2;
+            $a = 3;
+            $a = 4;
+            $a = 5;
+        }
+        $a = 6;
+        echo $a;
+    "#;
+    assert_eq!(output(source), "..6");
+}
@@ -0,0 +1,254 @@
+use compiler::{
+    runtime::{
+        vm::{ScriptError, Vm},
+        RuntimeError,
+    },
+    syntax::source::SourceFile,
+};
+
+use super::{output, run};
+
+#[test]
+pub fn functions_and_closures() {
+    let source = r#"
+        function fact($n) { return $n <= 1 ? 1 : $n * fact($n - 1); }
+        function greet($name, $greeting = "Hello") { return "$greeting, $name!"; }
+        function add_one(&$x) { $x++; }
+        echo fact(5), " ", greet("a"), " ", greet("b", "Hi"), "\n";
+        $n = 1;
+        add_one($n);
+        $times = 3;
+        $scale = function ($x) use ($times) { return $x * $times; };
+        $offset = fn($x) => $x + $n;
+        echo $n, " ", $scale(2), " ", $offset(10), " ", implode(",", array_map($scale, [1, 2]));
+    "#;
+    assert_eq!(output(source), "120 Hello, a! Hi, b!\n2 6 12 3,6");
+}
+
+#[test]
+pub fn classes_and_objects() {
+    let source = r#"
+        interface Shape { public function area(); }
+        abstract class Base implements Shape {
+            public static $count = 0;
+            public function __construct(protected string $name) { static::$count++; }
+            public function describe() { return $this->name . " " . $this->area(); }
+        }
+        class Square extends Base {
+            const SIDES = 4;
+            public function __construct(private $side) { parent::__construct("square"); }
+            public function area() { return $this->side ** 2; }
+        }
+        $shapes = [new Square(2), new Square(3)];
+        foreach ($shapes as $shape) {
+            echo $shape->describe(), "\n";
+        }
+        var_dump($shapes[0] instanceof Shape, Base::$count, Square::SIDES);
+    "#;
+    assert_eq!(
+        output(source),
+        "square 4\nsquare 9\nbool(true)\nint(2)\nint(4)\n"
+    );
+}
+
+#[test]
+pub fn class_declaration_errors() {
+    let cases = [
+        (
+            "final class A {} class B extends A {}",
+            "Class B cannot extend final class A",
+        ),
+        (
+            "class A { final function f() {} } class B extends A { function F() {} }",
+            "Cannot override final method A::f()",
+        ),
+        (
+            "class A { final function f() {} } trait T { function f() {} } class B extends A { use T; }",
+            "Cannot override final method A::f()",
+        ),
+        (
+            "abstract class A { abstract function f(); } class B extends A {}",
+            "Class B contains 1 abstract method and must therefore be declared abstract or \
+             implement the remaining methods (A::f)",
+        ),
+        (
+            "interface I { function f(); function g(); } interface J extends I { function h(); }
+            abstract class A implements J { function g() {} abstract function i(); }
+            class B extends A { function f() {} }",
+            "Class B contains 2 abstract methods and must therefore be declared abstract or \
+             implement the remaining methods (A::i, J::h)",
+        ),
+        (
+            "interface I { function a(); function b(); function c(); function d(); }
+            enum E implements I {}",
+            "Enum E contains 4 abstract methods and must therefore be declared abstract or \
+             implement the remaining methods (I::a, I::b, I::c, ...)",
+        ),
+    ];
+    for (source, message) in cases {
+        let (_, result) = run(source);
+        assert_eq!(
+            result,
+            Err(ScriptError::Uncaught(RuntimeError::new("Error", message))),
+            "{}",
+            source
+        );
+    }
+
+    // an inherited method implements both an interface method and an abstract trait method.
+    let source = r#"
+        interface I { function f(); }
+        trait T { abstract function f(); function g() { return $this->f(); } }
+        class A { function f() { return "A::f"; } }
+        final class B extends A implements I { use T; }
+        echo (new B)->g();
+    "#;
+    assert_eq!(output(source), "A::f");
+}
+
+#[test]
+pub fn property_visibility() {
+    let source = r#"
+        class A {
+            private $secret = "a";
+            protected $shared = "b";
+            private static $count = 0;
+            public function reveal() { return $this->secret . self::$count; }
+        }
+        class B extends A {
+            public function share() { return $this->shared; }
+        }
+        class Magic {
+            private $hidden = "real";
+            public function __get($name) { return "magic $name"; }
+        }
+        $b = new B;
+        echo $b->reveal(), $b->share(), (new Magic)->hidden, "\n";
+        foreach ([
+            fn () => $b->secret,
+            fn () => $b->shared,
+            function () use ($b) { $b->shared = 1; },
+            function () use ($b) { unset($b->secret); },
+            fn () => A::$count,
+        ] as $access) {
+            try {
+                $access();
+            } catch (Error $e) {
+                echo $e->getMessage(), "\n";
+            }
+        }
+    "#;
+    assert_eq!(
+        output(source),
+        "a0bmagic hidden\n\
+         Cannot access private property B::$secret\n\
+         Cannot access protected property B::$shared\n\
+         Cannot access protected property B::$shared\n\
+         Cannot access private property B::$secret\n\
+         Cannot access private property A::$count\n"
+    );
+}
+
+#[test]
+pub fn loops_break_and_continue() {
+    let source = r#"
+        foreach ([1, 2, 3] as $i) {
+            foreach (['a', 'b'] as $key => $j) {
+                if ($i == 2) { continue 2; }
+                if ($i == 3) { break 2; }
+                echo "$i$j$key ";
+            }
+        }
+        $i = 0;
+        while (true) {
+            if (++$i > 3) { break; }
+            echo $i;
+        }
+    "#;
+    assert_eq!(output(source), "1a0 1b1 123");
+}
+
+#[test]
+pub fn inline_html() {
+    let source = "<?php $items = ['a', 'b']; ?>
+<ul>
+<?php foreach ($items as $item): ?>
+  <li><?= $item ?></li>
+<?php endforeach; ?>
+</ul>
+";
+    assert_eq!(output(source), "<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>\n");
+}
+
+#[test]
+pub fn scripts_start_with_html() {
+    let page = |source: &str| {
+        let mut vm = Vm::new();
+        match vm.run(SourceFile::new("page.php", source)) {
+            Ok(()) => String::from_utf8_lossy(vm.output()).into_owned(),
+            Err(e) => panic!("`{}` failed with {}", source, e),
+        }
+    };
+    assert_eq!(page("<html><?php echo 1; ?></html>"), "<html>1</html>");
+    assert_eq!(page("Hello <?= \"world\" ?>!"), "Hello world!");
+    assert_eq!(page("echo 1;\n"), "echo 1;\n");
+    let source = "<ul>
+<?php foreach (['a', 'b'] as $item): ?>
+  <li><?= $item ?></li>
+<?php endforeach; ?>
+</ul>
+";
+    assert_eq!(page(source), "<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>\n");
+}
+
+#[test]
+pub fn byte_strings() {
+    assert_eq!(
+        output(
+            r#"echo strlen("\xff"), " ", strlen("é"), " ", strlen("\u{1F600}"), "\n";
+            var_dump("é"[0] === "\xc3", "\xff" === "\377", "\xff" == "\xfe");
+            echo strlen(substr("é", 0, 1) . "\x80"), " ", strtoupper("\xe9a"[1]), "\n";"#
+        ),
+        "1 2 4\nbool(true)\nbool(true)\nbool(false)\n2 A\n"
+    );
+
+    // what is echoed keeps the bytes as they are.
+    let mut vm = Vm::new();
+    let source = r#"echo "\xff\0", substr("é", 1), sprintf("%c", 0xfe);"#;
+    vm.run_snippet(SourceFile::new("test.php", source)).unwrap();
+    assert_eq!(vm.output(), b"\xff\0\xa9\xfe");
+}
+
+#[test]
+pub fn uncaught_exceptions() {
+    let (echoed, result) = run(
+        "echo 'before '; function f() { throw new DomainException('oops'); } f(); echo 'after';",
+    );
+    assert_eq!(echoed, "before ");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "DomainException",
+            "oops"
+        )))
+    );
+
+    let (_, result) = run("throw 1;");
+    assert_eq!(
+        result,
+        Err(ScriptError::Uncaught(RuntimeError::new(
+            "Error",
+            "Can only throw objects"
+        )))
+    );
+
+    let source = r#"
+        set_exception_handler(function (Throwable $e) {
+            echo "handled ", $e->getMessage();
+        });
+        echo "start ";
+        intdiv(1, 0);
+        echo "unreachable";
+    "#;
+    assert_eq!(output(source), "start handled Division by zero");
+}
@@ -0,0 +1,60 @@
+use compiler::syntax::{
+    ast::{
+        class::{has_modifier, Modifier},
+        keyword::Keyword,
+        name::{Name, NameKind},
+    },
+    source::Span,
+};
+
+fn name(parts: &[&str], kind: NameKind) -> Name {
+    Name::new(
+        parts.iter().map(|p| p.to_string()).collect(),
+        kind,
+        Span::default(),
+    )
+}
+
+#[test]
+pub fn names_display_as_written() {
+    assert_eq!(name(&["Foo"], NameKind::Unqualified).to_string(), "Foo");
+    assert_eq!(
+        name(&["Foo", "Bar"], NameKind::Qualified).to_string(),
+        "Foo\\Bar"
+    );
+    assert_eq!(
+        name(&["Foo", "Bar"], NameKind::FullyQualified).to_string(),
+        "\\Foo\\Bar"
+    );
+    assert_eq!(
+        name(&["Foo"], NameKind::Relative).to_string(),
+        "namespace\\Foo"
+    );
+}
+
+#[test]
+pub fn special_class_names() {
+    assert!(name(&["self"], NameKind::Unqualified).is_special_class());
+    assert!(name(&["STATIC"], NameKind::Unqualified).is_special_class());
+    assert!(!name(&["parent"], NameKind::FullyQualified).is_special_class());
+    assert!(!name(&["Parent", "Foo"], NameKind::Qualified).is_special_class());
+}
+
+#[test]
+pub fn modifiers_are_keywords() {
+    let modifiers = [
+        Modifier {
+            keyword: Keyword::Private,
+            span: Span::new(0, 7),
+        },
+        Modifier {
+            keyword: Keyword::ReadOnly,
+            span: Span::new(8, 16),
+        },
+    ];
+
+    assert!(has_modifier(&modifiers, Keyword::ReadOnly));
+    assert!(!has_modifier(&modifiers, Keyword::Static));
+    assert!(modifiers[0].is_visibility());
+    assert!(!modifiers[1].is_visibility());
+}
@@ -0,0 +1,249 @@
+use compiler::syntax::{
+    ast::{
+        attribute::AttributeGroup,
+        class::MemberKind,
+        expr::{ClassRef, ExprKind},
+        stmt::StmtKind,
+    },
+    diagnostic::Diagnostic,
+    parse::parse_snippet,
+    resolve::resolve_names,
+};
+
+use super::parse::script_error;
+
+/// The attribute names of each group, `#[A, B] #[C]` is `[["A", "B"], ["C"]]`.
+fn names(groups: &[AttributeGroup]) -> Vec<Vec<String>> {
+    groups
+        .iter()
+        .map(|group| {
+            group
+                .attributes
+                .iter()
+                .map(|attribute| attribute.name.to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// The messages reported when resolving a script.
+#[track_caller]
+fn check(source: &str) -> Vec<String> {
+    let mut script = match parse_snippet(source) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    };
+    resolve_names(&mut script)
+        .into_iter()
+        .map(|diagnostic: Diagnostic| diagnostic.message)
+        .collect()
+}
+
+#[test]
+pub fn declarations() {
+    let script = parse_snippet(
+        "#[A, B(1, name: 'x'),] #[\\C\\D]
+        final class Foo {
+            #[E] public int $a, $b;
+            #[F] const G = 1;
+            #[H] public function __construct(#[I] private $c, #[J, K] $d) {}
+        }
+        #[L] function f() {}
+        #[M] enum Suit { #[N] case Hearts; }",
+    )
+    .unwrap();
+
+    let StmtKind::ClassLike(class) = &script.stmts[0].kind else {
+        panic!("expected a class, found {:?}", script.stmts[0].kind);
+    };
+    assert_eq!(names(&class.attributes), [vec!["A", "B"], vec!["\\C\\D"]]);
+    assert_eq!(class.attributes[0].attributes[1].args.len(), 2);
+    assert_eq!(class.span.start, 0);
+    let mut members = class.members.iter().map(|member| &member.kind);
+    let Some(MemberKind::Property(property)) = members.next() else {
+        panic!("expected a property");
+    };
+    assert_eq!(names(&property.attributes), [["E"]]);
+    let Some(MemberKind::Const(constant)) = members.next() else {
+        panic!("expected a constant");
+    };
+    assert_eq!(names(&constant.attributes), [["F"]]);
+    let Some(MemberKind::Method(method)) = members.next() else {
+        panic!("expected a method");
+    };
+    assert_eq!(names(&method.function.attributes), [["H"]]);
+    assert_eq!(names(&method.function.params[0].attributes), [["I"]]);
+    assert_eq!(names(&method.function.params[1].attributes), [["J", "K"]]);
+
+    let StmtKind::Function(function) = &script.stmts[1].kind else {
+        panic!("expected a function, found {:?}", script.stmts[1].kind);
+    };
+    assert_eq!(names(&function.attributes), [["L"]]);
+
+    let StmtKind::ClassLike(class) = &script.stmts[2].kind else {
+        panic!("expected an enum, found {:?}", script.stmts[2].kind);
+    };
+    assert_eq!(names(&class.attributes), [["M"]]);
+    let MemberKind::EnumCase(case) = &class.members[0].kind else {
+        panic!("expected a case");
+    };
+    assert_eq!(names(&case.attributes), [["N"]]);
+}
+
+#[test]
+pub fn expressions() {
+    let script = parse_snippet(
+        "#[A] function () {};
+        $f = #[B] static fn () => 1;
+        $o = new #[C] class {};",
+    )
+    .unwrap();
+    let exprs: Vec<_> = script
+        .stmts
+        .iter()
+        .map(|stmt| match &stmt.kind {
+            StmtKind::Expr(expr) => match &expr.kind {
+                ExprKind::Assign { value, .. } => &value.kind,
+                kind => kind,
+            },
+            other => panic!("expected an expression, found {:?}", other),
+        })
+        .collect();
+
+    let ExprKind::Closure(closure) = exprs[0] else {
+        panic!("expected a closure, found {:?}", exprs[0]);
+    };
+    assert_eq!(names(&closure.attributes), [["A"]]);
+    let ExprKind::ArrowFunction(function) = exprs[1] else {
+        panic!("expected an arrow function, found {:?}", exprs[1]);
+    };
+    assert_eq!(names(&function.attributes), [["B"]]);
+    assert!(function.is_static);
+    let ExprKind::New {
+        class: ClassRef::Anonymous(class),
+        ..
+    } = exprs[2]
+    else {
+        panic!("expected an anonymous class, found {:?}", exprs[2]);
+    };
+    assert_eq!(names(&class.attributes), [["C"]]);
+}
+
+#[test]
+pub fn constant_arguments() {
+    assert!(parse_snippet(
+        "#[A(1 + 2, [1, 'a' => B], C::D, E::F->value, new G(h: 1), PHP_VERSION, true ? 1 : 2)]
+        function f() {}"
+    )
+    .is_ok());
+    assert_eq!(
+        script_error("#[A($a)] function f() {}"),
+        "constant expression contains invalid operations"
+    );
+    assert_eq!(
+        script_error("#[A(f())] function f() {}"),
+        "constant expression contains invalid operations"
+    );
+    assert_eq!(
+        script_error("#[A(static::B)] function f() {}"),
+        "\"static::\" is not allowed in compile-time constants"
+    );
+    assert_eq!(
+        script_error("#[A(new $b)] function f() {}"),
+        "cannot use dynamic class name in constant expression"
+    );
+    assert_eq!(
+        script_error("#[A(...[1])] function f() {}"),
+        "cannot use unpacking in attribute argument list"
+    );
+    assert_eq!(
+        script_error("#[A(...)] function f() {}"),
+        "cannot create a closure from an attribute"
+    );
+}
+
+#[test]
+pub fn invalid_attributes() {
+    assert_eq!(
+        script_error("#[] function f() {}"),
+        "expected an attribute name, found `]`"
+    );
+    assert_eq!(
+        script_error("#[A function f() {}"),
+        "expected `]`, found keyword `function`"
+    );
+    assert_eq!(
+        script_error("#[A] echo 1;"),
+        "expected `function` or `fn`, found identifier `echo`"
+    );
+    assert_eq!(
+        script_error("#[A] $a = 1;"),
+        "expected `function` or `fn`, found `$`"
+    );
+    assert_eq!(
+        script_error("class A { #[B] use T; }"),
+        "expected a class member, found keyword `use`"
+    );
+}
+
+#[test]
+pub fn targets() {
+    assert_eq!(
+        check("#[Attribute] function f() {}"),
+        ["attribute \"Attribute\" cannot target function (allowed targets: class)"]
+    );
+    assert_eq!(
+        check("class A { #[Override] public $a; #[ReturnTypeWillChange] function f() {} }"),
+        ["attribute \"Override\" cannot target property (allowed targets: method)"]
+    );
+    assert_eq!(
+        check(
+            "namespace App;
+            use Attribute;
+            #[Attribute(Attribute::TARGET_METHOD | Attribute::TARGET_PROPERTY)]
+            class Route {}
+            #[Route] class A { #[Route] function f(#[Route] $a) {} }
+            class B { function __construct(#[\\App\\Route] public $a) {} }"
+        ),
+        [
+            "attribute \"App\\Route\" cannot target class (allowed targets: method, property)",
+            "attribute \"App\\Route\" cannot target parameter (allowed targets: method, property)",
+        ]
+    );
+    // attribute classes may be declared after their first use, and unknown attribute
+    // classes are only checked when they are instantiated.
+    assert!(check(
+        "#[Tag] #[Unknown] #[Unknown] function f() {}
+        #[Attribute(Attribute::TARGET_FUNCTION)] class Tag {}"
+    )
+    .is_empty());
+}
+
+#[test]
+pub fn repeated() {
+    assert_eq!(
+        check("class A { #[Override] #[\\Override] function f() {} }"),
+        ["attribute \"Override\" must not be repeated"]
+    );
+    assert!(check(
+        "#[Attribute(Attribute::TARGET_ALL | Attribute::IS_REPEATABLE)] class Tag {}
+        #[Tag(1), Tag(2)] #[Tag] function f() {}"
+    )
+    .is_empty());
+    assert_eq!(
+        check("#[Attribute(1)] class Tag {} #[Tag, Tag] #[Tag] class A {}"),
+        ["attribute \"Tag\" must not be repeated"]
+    );
+}
+
+#[test]
+pub fn attribute_classes() {
+    assert_eq!(
+        check("#[Attribute] interface A {}"),
+        ["cannot apply #[\\Attribute] to interface A"]
+    );
+    assert_eq!(
+        check("#[Attribute] abstract class A {}"),
+        ["cannot apply #[\\Attribute] to abstract class A"]
+    );
+}
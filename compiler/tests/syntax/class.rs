@@ -0,0 +1,428 @@
+use compiler::syntax::{
+    ast::{
+        class::{AdaptationKind, ClassKind, ClassLike, MemberKind},
+        expr::{ClassRef, ExprKind},
+        keyword::Keyword,
+        stmt::StmtKind,
+        types::{TypeHint, TypeHintKind},
+    },
+    parse::{parse_expr, parse_snippet},
+};
+
+use super::{
+    parse::{assert_script_error, sexp},
+    TEST_SCRIPT,
+};
+
+#[track_caller]
+fn parse_class(source: &str) -> ClassLike {
+    let script = match parse_snippet(source) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    };
+    match script.stmts.into_iter().next().map(|stmt| stmt.kind) {
+        Some(StmtKind::ClassLike(class)) => class,
+        other => panic!("`{}` parsed as {:?}", source, other),
+    }
+}
+
+fn type_str(ty: &TypeHint) -> String {
+    match &ty.kind {
+        TypeHintKind::Named(name) => name.to_string(),
+        TypeHintKind::Nullable(inner) => format!("?{}", type_str(inner)),
+        TypeHintKind::Union(types) => types.iter().map(type_str).collect::<Vec<_>>().join("|"),
+        TypeHintKind::Intersection(types) => {
+            let types: Vec<String> = types.iter().map(type_str).collect();
+            format!("({})", types.join("&"))
+        }
+    }
+}
+
+#[test]
+pub fn test_script_class() {
+    let script = match parse_snippet(TEST_SCRIPT) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse the test script: {}", e),
+    };
+    assert_eq!(script.stmts.len(), 4);
+    let Some(StmtKind::ClassLike(class)) = script.stmts.into_iter().nth(1).map(|stmt| stmt.kind)
+    else {
+        panic!("expected the class to be the second statement");
+    };
+
+    assert_eq!(class.kind, ClassKind::Class);
+    assert_eq!(class.name.as_ref().unwrap().as_str(), "Foo");
+    assert_eq!(class.members.len(), 3);
+
+    let MemberKind::Property(bar) = &class.members[0].kind else {
+        panic!("expected a property, found {:?}", class.members[0].kind);
+    };
+    assert_eq!(bar.modifiers[0].keyword, Keyword::Private);
+    assert_eq!(bar.props[0].name.as_str(), "bar");
+    assert!(bar.ty.is_none() && bar.props[0].default.is_none());
+
+    let MemberKind::Method(ctor) = &class.members[1].kind else {
+        panic!("expected a method, found {:?}", class.members[1].kind);
+    };
+    assert_eq!(ctor.function.name.as_str(), "__construct");
+    assert_eq!(
+        type_str(ctor.function.params[0].ty.as_ref().unwrap()),
+        "Mixed"
+    );
+    assert_eq!(ctor.function.body.as_ref().unwrap().stmts.len(), 1);
+
+    let MemberKind::Method(method) = &class.members[2].kind else {
+        panic!("expected a method, found {:?}", class.members[2].kind);
+    };
+    let body = method.function.body.as_ref().unwrap();
+    match &body.stmts[0].kind {
+        StmtKind::Return(Some(value)) => assert_eq!(sexp(value), "\"baz\""),
+        other => panic!("expected a return, found {:?}", other),
+    }
+
+    // the class spans from `class` to its closing brace.
+    assert_eq!(
+        &TEST_SCRIPT[class.span.start..class.span.end],
+        &TEST_SCRIPT[TEST_SCRIPT.find("class Foo").unwrap()..TEST_SCRIPT.find("\n\n$foo").unwrap()]
+    );
+}
+
+#[test]
+pub fn properties_and_constants() {
+    let class = parse_class(
+        "final class A extends B implements C, \\D\\E {
+            public const X = 1, Y = self::X + 1;
+            final protected const int|string Z = 'z';
+            public static ?int $count = 0, $other;
+            protected readonly array $items;
+            var $legacy = [1, 2];
+        }",
+    );
+    assert_eq!(class.modifiers[0].keyword, Keyword::Final);
+    assert_eq!(class.extends[0].to_string(), "B");
+    let implements: Vec<String> = class.implements.iter().map(|n| n.to_string()).collect();
+    assert_eq!(implements, ["C", "\\D\\E"]);
+
+    let MemberKind::Const(untyped) = &class.members[0].kind else {
+        panic!("expected a constant, found {:?}", class.members[0].kind);
+    };
+    assert!(untyped.ty.is_none());
+    assert_eq!(untyped.consts[1].name.as_str(), "Y");
+    assert_eq!(sexp(&untyped.consts[1].value), "(+ (:: self X) 1)");
+
+    let MemberKind::Const(typed) = &class.members[1].kind else {
+        panic!("expected a constant, found {:?}", class.members[1].kind);
+    };
+    assert_eq!(type_str(typed.ty.as_ref().unwrap()), "int|string");
+
+    let MemberKind::Property(count) = &class.members[2].kind else {
+        panic!("expected a property, found {:?}", class.members[2].kind);
+    };
+    assert_eq!(type_str(count.ty.as_ref().unwrap()), "?int");
+    assert_eq!(count.props.len(), 2);
+    assert_eq!(sexp(count.props[0].default.as_ref().unwrap()), "0");
+    assert!(count.props[1].default.is_none());
+
+    let MemberKind::Property(legacy) = &class.members[4].kind else {
+        panic!("expected a property, found {:?}", class.members[4].kind);
+    };
+    assert_eq!(legacy.modifiers[0].keyword, Keyword::Var);
+}
+
+#[test]
+pub fn methods_and_promotion() {
+    let class = parse_class(
+        "abstract class Point {
+            public function __construct(
+                private readonly int $x = 0,
+                protected (A&B)|null $y = null,
+                $z = 1,
+            ) {}
+            abstract protected function area(): float;
+            public static function &make(int ...$values): static { return new static(); }
+        }",
+    );
+    let MemberKind::Method(ctor) = &class.members[0].kind else {
+        panic!("expected a method, found {:?}", class.members[0].kind);
+    };
+    let params = &ctor.function.params;
+    assert_eq!(params.len(), 3);
+    assert!(params[0].is_promoted() && params[1].is_promoted() && !params[2].is_promoted());
+    assert_eq!(type_str(params[1].ty.as_ref().unwrap()), "(A&B)|null");
+
+    let MemberKind::Method(area) = &class.members[1].kind else {
+        panic!("expected a method, found {:?}", class.members[1].kind);
+    };
+    assert!(area.function.body.is_none());
+    assert_eq!(
+        type_str(area.function.return_type.as_ref().unwrap()),
+        "float"
+    );
+
+    let MemberKind::Method(make) = &class.members[2].kind else {
+        panic!("expected a method, found {:?}", class.members[2].kind);
+    };
+    assert!(make.function.by_ref && make.function.params[0].variadic);
+}
+
+#[test]
+pub fn interfaces_and_traits() {
+    let interface =
+        parse_class("interface A extends B, C { const X = 1; public function foo(int $a): void; }");
+    assert_eq!(interface.kind, ClassKind::Interface);
+    assert_eq!(interface.extends.len(), 2);
+
+    let class = parse_class(
+        "class A {
+            use B, C {
+                B::foo insteadof C;
+                C::foo as protected bar;
+                baz as private;
+                qux as quux;
+            }
+            use D;
+        }",
+    );
+    let MemberKind::TraitUse(trait_use) = &class.members[0].kind else {
+        panic!("expected a trait use, found {:?}", class.members[0].kind);
+    };
+    assert_eq!(trait_use.traits.len(), 2);
+    let adaptations: Vec<String> = trait_use
+        .adaptations
+        .iter()
+        .map(|adaptation| match &adaptation.kind {
+            AdaptationKind::InsteadOf {
+                trait_name,
+                method,
+                instead_of,
+            } => format!("{}::{} insteadof {}", trait_name, method, instead_of[0]),
+            AdaptationKind::Alias {
+                trait_name,
+                method,
+                visibility,
+                alias,
+            } => format!(
+                "{}{} as {} {}",
+                trait_name
+                    .as_ref()
+                    .map_or(String::new(), |n| format!("{}::", n)),
+                method,
+                visibility.map_or("_".to_string(), |v| v.keyword.to_string()),
+                alias.as_ref().map_or("_", |a| a.as_str())
+            ),
+        })
+        .collect();
+    assert_eq!(
+        adaptations,
+        [
+            "B::foo insteadof C",
+            "C::foo as protected bar",
+            "baz as private _",
+            "qux as _ quux"
+        ]
+    );
+
+    let tr = parse_class("trait T { abstract private function foo(); public $a; }");
+    assert_eq!(tr.kind, ClassKind::Trait);
+}
+
+#[test]
+pub fn anonymous_classes() {
+    let expr =
+        parse_expr("new readonly class(1, $a) extends B implements C { public int $x; }").unwrap();
+    let ExprKind::New {
+        class: ClassRef::Anonymous(class),
+        args,
+    } = &expr.kind
+    else {
+        panic!("expected an anonymous class, found {:?}", expr.kind);
+    };
+    assert!(class.name.is_none());
+    assert_eq!(args.as_ref().unwrap().len(), 2);
+    assert_eq!(class.extends[0].to_string(), "B");
+    assert_eq!(class.members.len(), 1);
+}
+
+#[test]
+pub fn invalid_declarations() {
+    assert_script_error("class int {}", "cannot use 'int' as a class name");
+    assert_script_error(
+        "abstract final class A {}",
+        "final modifier on an abstract class",
+    );
+    assert_script_error(
+        "final interface A {}",
+        "cannot use the final modifier on an interface",
+    );
+    assert_script_error("trait A extends B {}", "a trait can not extend another");
+    assert_script_error(
+        "interface A implements B {}",
+        "can not implement interfaces",
+    );
+    assert_script_error("class A { public public $a; }", "multiple public modifiers");
+    assert_script_error(
+        "class A { public private $a; }",
+        "multiple access type modifiers",
+    );
+    assert_script_error("class A { $a; }", "expected a class member");
+    assert_script_error(
+        "class A { abstract function foo(); }",
+        "class A contains abstract method A::foo() and must therefore be declared abstract",
+    );
+    assert_script_error(
+        "abstract class A { abstract function foo() {} }",
+        "abstract function A::foo() cannot contain body",
+    );
+    assert_script_error(
+        "class A { function foo(); }",
+        "non-abstract method A::foo() must contain body",
+    );
+    assert_script_error(
+        "interface A { function foo() {} }",
+        "interface function A::foo() cannot contain body",
+    );
+    assert_script_error("interface A { private function foo(); }", "must be public");
+    assert_script_error(
+        "interface A { public $a; }",
+        "interfaces may not include properties",
+    );
+    assert_script_error(
+        "class A { public readonly $a; }",
+        "readonly property A::$a must have type",
+    );
+    assert_script_error(
+        "class A { public readonly int $a = 1; }",
+        "cannot have default value",
+    );
+    assert_script_error(
+        "class A { final $a; }",
+        "properties cannot be declared final",
+    );
+    assert_script_error(
+        "class A { static const X = 1; }",
+        "static modifier on a class constant",
+    );
+    assert_script_error("class A { const class = 1; }", "must not be called 'class'");
+    assert_script_error(
+        "class A { function foo(private $a) {} }",
+        "cannot declare promoted property outside a constructor",
+    );
+    assert_script_error(
+        "class A { function foo($a, $a) {} }",
+        "redefinition of parameter $a",
+    );
+    assert_script_error(
+        "class A { function foo(...$a, $b) {} }",
+        "only the last parameter can be variadic",
+    );
+    assert_script_error(
+        "class A { use B { foo insteadof C; } }",
+        "`insteadof` requires the trait",
+    );
+}
+
+#[test]
+pub fn enums() {
+    let class = parse_class(
+        "enum Suit: string implements HasColor, \\JsonSerializable {
+            use Helpers;
+            case Hearts = 'H';
+            case Spades = 'S';
+            const Wild = self::Spades;
+            public function color(): string { return 'Red'; }
+            public static function fromChar(string $c): self { return self::from($c); }
+        }",
+    );
+    assert_eq!(class.kind, ClassKind::Enum);
+    assert_eq!(class.name.as_ref().unwrap().as_str(), "Suit");
+    assert_eq!(type_str(class.backing_type.as_ref().unwrap()), "string");
+    assert_eq!(class.implements.len(), 2);
+    let MemberKind::EnumCase(case) = &class.members[1].kind else {
+        panic!("expected a case, found {:?}", class.members[1].kind);
+    };
+    assert_eq!(case.name.as_str(), "Hearts");
+    assert_eq!(sexp(case.value.as_ref().unwrap()), "\"H\"");
+    assert!(matches!(class.members[3].kind, MemberKind::Const(_)));
+    assert!(matches!(class.members[5].kind, MemberKind::Method(_)));
+
+    let class = parse_class("enum Status { case Active; case Inactive; }");
+    assert!(class.backing_type.is_none());
+    assert_eq!(class.members.len(), 2);
+
+    // `enum` is only a keyword when a name follows.
+    let script = parse_snippet("function enum() {} enum(); class Enum extends Base {} new Enum;");
+    assert_eq!(script.unwrap().stmts.len(), 4);
+}
+
+#[test]
+pub fn invalid_enums() {
+    assert_script_error(
+        "enum A: float {}",
+        "enum backing type must be int or string, float given",
+    );
+    assert_script_error("enum A extends B {}", "an enum can not extend another");
+    assert_script_error(
+        "final enum A {}",
+        "cannot use the final modifier on an enum",
+    );
+    assert_script_error(
+        "enum A { case B = 1; }",
+        "case B of non-backed enum A must not have a value",
+    );
+    assert_script_error(
+        "enum A: int { case B; }",
+        "case B of backed enum A must have a value",
+    );
+    assert_script_error("class A { case B; }", "case can only be used in enums");
+    assert_script_error(
+        "enum A { public case B; }",
+        "cannot use the public modifier on an enum case",
+    );
+    assert_script_error("enum A { public $a; }", "enum A cannot include properties");
+    assert_script_error(
+        "enum A { public function __construct() {} }",
+        "enum A cannot include magic method __construct",
+    );
+    assert_script_error(
+        "enum A { public static function cases(): array {} }",
+        "cannot redeclare A::cases()",
+    );
+    assert_script_error(
+        "enum A: int { case B = 1; public static function tryFrom($v) {} }",
+        "cannot redeclare A::tryFrom()",
+    );
+    assert_script_error(
+        "enum A { abstract function foo(); }",
+        "enum A contains abstract method A::foo() and must therefore be declared abstract",
+    );
+}
+
+#[test]
+pub fn redeclared_members() {
+    assert_script_error(
+        "class A { function f() {} function F() {} }",
+        "cannot redeclare A::F()",
+    );
+    assert_script_error(
+        "class A { public $x; protected int $y, $x; }",
+        "cannot redeclare A::$x",
+    );
+    assert_script_error(
+        "class A { public $x; function __construct(private $x) {} }",
+        "cannot redeclare A::$x",
+    );
+    assert_script_error(
+        "interface A { const X = 1; const Y = 2, X = 3; }",
+        "cannot redefine class constant A::X",
+    );
+    assert_script_error(
+        "enum A: int { case X = 1; case X = 2; }",
+        "cannot redefine class constant A::X",
+    );
+    assert_script_error(
+        "enum A { case X; const X = 1; }",
+        "cannot redefine class constant A::X",
+    );
+    // constants and properties are case-sensitive, and the three kinds are apart.
+    parse_class("class A { const X = 1; const x = 2; public $X; public $x; function X() {} }");
+}
@@ -0,0 +1,291 @@
+use std::rc::Rc;
+
+use compiler::syntax::{
+    ast::{
+        expr::Expr,
+        function::FunctionDecl,
+        keyword::Keyword,
+        stmt::{Stmt, StmtKind},
+    },
+    cst::{
+        view::AstRef, GreenElement, GreenNode, GreenToken, SyntaxElement, SyntaxKind, SyntaxNode,
+        SyntaxTree,
+    },
+    experiments::{Experiment, Experiments},
+    lex::token::{Numeric, TokenType},
+};
+
+use super::parse::sexp;
+
+const SCRIPT: &str = "<?php\r
+// Greets someone.\r
+function greet(string $name /* who */) {\r
+    echo 'Hello, ' . $name;   // trailing\r
+\r
+    return strlen( $name ) ;\r
+}\r
+\r
+/** Doc. */\r
+#[Pure]\r
+class A { public $b = [1, 2, ]; }\r
+";
+
+#[test]
+pub fn lossless() {
+    for text in [
+        SCRIPT,
+        "",
+        "  \n\t",
+        "<?php $a = 1 +;\nfunction f( { }\nclass { echo 2; }",
+        "<?php $a = \"x $b {$c->d}\"; $e = <<<EOT\n  $f\n  EOT;\n",
+        "<?php $a = 'unterminated",
+        "<ul>\n<?php foreach ($items as $item): ?>\n  <li><?= $item ?></li>\n<?php endforeach ?>\n</ul>\n",
+    ] {
+        let tree = SyntaxTree::parse(text);
+        let root = tree.root();
+        assert_eq!(root.text(), text);
+        assert_eq!(root.kind(), SyntaxKind::Script);
+        assert_eq!(root.span().end, text.len());
+        let tokens: String = root
+            .tokens()
+            .map(|token| token.text().to_string())
+            .collect();
+        assert_eq!(tokens, text);
+    }
+}
+
+#[test]
+pub fn trivia() {
+    let tree = SyntaxTree::parse(SCRIPT);
+    let root = tree.root();
+    let stmts: Vec<_> = root.children().collect();
+    assert_eq!(stmts.len(), 2);
+    assert!(stmts.iter().all(|stmt| stmt.kind() == SyntaxKind::Stmt));
+    assert!(stmts[1].text().starts_with("#[Pure]"));
+
+    // Comments and blank lines between statements belong to the script.
+    let comments: Vec<_> = root
+        .children_with_tokens()
+        .filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some(token),
+            _ => None,
+        })
+        .filter(|token| token.token() == Some(&TokenType::Comment))
+        .map(|token| token.text().to_string())
+        .collect();
+    assert_eq!(comments, ["// Greets someone.", "/** Doc. */"]);
+
+    // No node starts or ends with trivia, and the ones inside a node stay in it.
+    for node in root.descendants().skip(1) {
+        let tokens: Vec<_> = node.tokens().collect();
+        assert!(!tokens.first().unwrap().is_trivia(), "{node:?}");
+        assert!(!tokens.last().unwrap().is_trivia(), "{node:?}");
+    }
+    let param = root
+        .descendants()
+        .find(|node| node.kind() == SyntaxKind::Param)
+        .unwrap();
+    assert_eq!(param.text(), "string $name");
+    let function = param.parent().unwrap();
+    assert_eq!(function.kind(), SyntaxKind::FunctionDecl);
+    assert!(function.text().contains("/* who */"));
+    assert!(function.text().contains("// trailing\r\n\r\n"));
+    assert_eq!(
+        param
+            .ancestors()
+            .map(|node| node.kind())
+            .collect::<Vec<_>>(),
+        [
+            SyntaxKind::Param,
+            SyntaxKind::FunctionDecl,
+            SyntaxKind::Stmt,
+            SyntaxKind::Script
+        ]
+    );
+}
+
+#[test]
+pub fn views() {
+    let tree = SyntaxTree::parse(SCRIPT);
+    let root = tree.root();
+    assert!(tree.diagnostics().is_empty());
+    assert!(matches!(tree.ast(&root), Some(AstRef::Script(_))));
+
+    let call = root
+        .descendants()
+        .find(|node| node.text() == "strlen( $name )")
+        .unwrap();
+    let expr: &Expr = tree.view(&call).unwrap();
+    assert_eq!(sexp(expr), "(call strlen $name)");
+    assert!(tree.view::<Stmt>(&call).is_none());
+    assert_eq!(tree.syntax(expr), Some(call));
+
+    let StmtKind::Function(function) = &tree.script().stmts[0].kind else {
+        panic!("expected a function");
+    };
+    let node = tree.syntax(function).unwrap();
+    assert_eq!(node.kind(), SyntaxKind::FunctionDecl);
+    assert_eq!(tree.view::<FunctionDecl>(&node), Some(function));
+    let ret = tree
+        .syntax(&function.body.as_ref().unwrap().stmts[1])
+        .unwrap();
+    assert_eq!(ret.text(), "return strlen( $name ) ;");
+}
+
+#[test]
+pub fn links() {
+    for text in [
+        SCRIPT,
+        "<?php #[A] function f(#[B] $x): A|B { return [[$x]]; }",
+        "<?php f(A); $a = $b = $c; $a = $a;",
+        "<?php class C { const A = 1, B = 2; public int $a, $b; }",
+        "<?php $a = 1 +;\nfunction f( { }\nclass { echo 2; }",
+        "<p><?= $a ?></p>",
+    ] {
+        let tree = SyntaxTree::parse(text);
+        for node in tree.root().descendants() {
+            let ast = tree.ast(&node).unwrap();
+            assert_eq!(ast.kind(), node.kind());
+            assert_eq!(ast.span(), node.span());
+            assert_eq!(tree.syntax(ast), Some(node));
+        }
+    }
+
+    // The same text twice gives two nodes, each with its own typed node.
+    let tree = SyntaxTree::parse("<?php $a = $a;");
+    let vars: Vec<_> = tree
+        .root()
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::Expr && node.text() == "$a")
+        .collect();
+    assert_eq!(vars.len(), 2);
+    let (left, right) = (tree.ast(&vars[0]).unwrap(), tree.ast(&vars[1]).unwrap());
+    assert_ne!(left.span(), right.span());
+    assert_eq!(tree.syntax(right).as_ref(), Some(&vars[1]));
+
+    // The nodes of an edited tree are not views of the typed tree it was edited from.
+    let tree = SyntaxTree::parse(SCRIPT);
+    let call = tree
+        .root()
+        .descendants()
+        .find(|node| node.text() == "strlen( $name )")
+        .unwrap();
+    let edited = call.replace_with(GreenNode::lex(
+        SyntaxKind::Expr,
+        "sizeof( $name )",
+        tree.experiments(),
+    ));
+    let edited = SyntaxNode::new_root(Rc::new(edited));
+    let node = edited
+        .descendants()
+        .find(|node| node.text() == "sizeof( $name )")
+        .unwrap();
+    assert_eq!(node.span(), call.span());
+    assert!(tree.ast(&node).is_none());
+    assert!(tree.ast(&edited).is_none());
+}
+
+#[test]
+pub fn experiments() {
+    let text = "<?php async function f() { return await g(); }";
+    assert!(!SyntaxTree::parse(text).diagnostics().is_empty());
+
+    let tree = SyntaxTree::parse_with(text, Experiments::default().with(Experiment::AsyncAwait));
+    assert!(tree.diagnostics().is_empty());
+    assert_eq!(tree.root().text(), text);
+    let function = tree
+        .root()
+        .descendants()
+        .find(|node| node.kind() == SyntaxKind::FunctionDecl)
+        .unwrap();
+    assert!(function.text().starts_with("async function"));
+    let await_ = function
+        .descendants()
+        .find(|node| node.text() == "await g()")
+        .unwrap();
+    assert_eq!(
+        tree.view::<Expr>(&await_).map(sexp).as_deref(),
+        Some("(await (call g))")
+    );
+}
+
+#[test]
+pub fn edits() {
+    let tree = SyntaxTree::parse(SCRIPT);
+    let root = tree.root();
+
+    // Renames the function by replacing a single token.
+    let name = root.tokens().find(|token| token.text() == "greet").unwrap();
+    let renamed = name.replace_with(GreenToken::new(Some(TokenType::Identifier), "welcome"));
+    assert_eq!(renamed.to_string(), SCRIPT.replace("greet", "welcome"));
+
+    // Rewrites an expression, leaving the comments and spacing around it alone.
+    let call = root
+        .descendants()
+        .find(|node| node.text() == "strlen( $name )")
+        .unwrap();
+    let edited = call.replace_with(GreenNode::lex(
+        SyntaxKind::Expr,
+        "mb_strlen($name)",
+        tree.experiments(),
+    ));
+    let text = edited.to_string();
+    assert_eq!(text, SCRIPT.replace("strlen( $name )", "mb_strlen($name)"));
+
+    // Untouched subtrees are shared with the original tree.
+    let class = |green: &GreenNode| green.children().last().cloned();
+    let (before, after) = (class(tree.green()), class(&edited));
+    assert_eq!(before, after);
+    let edited = SyntaxTree::parse(&text);
+    assert!(edited.diagnostics().is_empty());
+    let StmtKind::Function(function) = &edited.script().stmts[0].kind else {
+        panic!("expected a function");
+    };
+    let Some(StmtKind::Return(Some(value))) =
+        function.body.as_ref().map(|body| &body.stmts[1].kind)
+    else {
+        panic!("expected a return");
+    };
+    assert_eq!(sexp(value), "(call mb_strlen $name)");
+}
+
+#[test]
+pub fn edits_with_experiments() {
+    let experiments = Experiments::default()
+        .with(Experiment::AsyncAwait)
+        .with(Experiment::LongInt);
+    let text = "<?php async function f() { return await g(1n); }";
+    let tree = SyntaxTree::parse_with(text, experiments);
+    assert_eq!(tree.experiments(), experiments);
+    let call = tree
+        .root()
+        .descendants()
+        .find(|node| node.text() == "await g(1n)")
+        .unwrap();
+
+    // The new code is lexed with the experiments of the tree it goes into.
+    let green = GreenNode::lex(SyntaxKind::Expr, "await h(123n)", tree.experiments());
+    let tokens: Vec<_> = green
+        .children()
+        .iter()
+        .filter_map(|child| match child {
+            GreenElement::Token(token) => token.token().cloned(),
+            GreenElement::Node(_) => None,
+        })
+        .collect();
+    assert!(tokens.contains(&TokenType::Keyword(Keyword::Await)));
+    assert!(tokens.contains(&TokenType::NumericalLit(Numeric::LInt(123))));
+
+    let edited = call.replace_with(green);
+    let edited = SyntaxTree::parse_with(&edited.to_string(), tree.experiments());
+    assert!(edited.diagnostics().is_empty());
+    let await_ = edited
+        .root()
+        .descendants()
+        .find(|node| node.text() == "await h(123n)")
+        .unwrap();
+    assert_eq!(
+        edited.view::<Expr>(&await_).map(sexp).as_deref(),
+        Some("(await (call h 123))")
+    );
+}
@@ -0,0 +1,73 @@
+use compiler::syntax::lex::{
+    encoding::Encoding,
+    token::{Token, TokenType},
+    Lexer,
+};
+
+fn tokens(lexer: &mut Lexer) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    while let Ok(Some(token)) = lexer.next() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn string_literal(tokens: &[Token]) -> &Token {
+    tokens
+        .iter()
+        .find(|t| matches!(t.kind(), TokenType::StringLit(_)))
+        .expect("a string literal")
+}
+
+#[test]
+pub fn latin1_literals_keep_their_bytes() {
+    let script = b"<?php\n$s = 'caf\xe9';\n";
+    let mut lexer = Lexer::from_bytes(script).unwrap();
+    assert_eq!(lexer.encoding(), Encoding::Utf8);
+
+    let tokens = tokens(&mut lexer);
+    let literal = string_literal(&tokens);
+    assert_eq!(literal.value().as_deref(), Some("café"));
+    assert_eq!(lexer.raw(literal), b"'caf\xe9'");
+
+    // tokens after the decoded byte still map back to the right bytes.
+    let eos = tokens
+        .iter()
+        .rev()
+        .find(|t| matches!(t.kind(), TokenType::EOS));
+    assert_eq!(lexer.raw(eos.unwrap()), b";");
+}
+
+#[test]
+pub fn declared_encoding_is_honoured() {
+    let script = b"<?php declare(encoding='windows-1252');\n$s = '\x80';\n";
+    let mut lexer = Lexer::from_bytes(script).unwrap();
+    assert_eq!(lexer.encoding(), Encoding::Windows1252);
+
+    let tokens = tokens(&mut lexer);
+    let literal = string_literal(&tokens[10..]);
+    assert_eq!(literal.value().as_deref(), Some("€"));
+    assert_eq!(lexer.raw(literal), b"'\x80'");
+}
+
+#[test]
+pub fn unsupported_encoding_is_reported() {
+    let error = Lexer::from_bytes(b"<?php declare(encoding='EBCDIC');")
+        .err()
+        .expect("EBCDIC is not supported");
+    assert!(error.to_string().contains("EBCDIC"));
+}
+
+#[test]
+pub fn byte_order_mark_is_stripped() {
+    let mut lexer = Lexer::from_bytes(b"\xef\xbb\xbf<?php\n$a;\n").unwrap();
+    assert!(lexer.has_bom());
+    assert!(lexer.text().starts_with("<?php"));
+
+    let tokens = tokens(&mut lexer);
+    assert_eq!(tokens[0].span().start, 0);
+    assert_eq!(lexer.raw(&tokens[0]), b"<?php\n");
+
+    let without = Lexer::from_bytes(b"<?php\n").unwrap();
+    assert!(!without.has_bom());
+}
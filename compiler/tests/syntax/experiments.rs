@@ -0,0 +1,201 @@
+use compiler::syntax::{
+    ast::{expr::ExprKind, keyword::Keyword, stmt::StmtKind},
+    diagnostic::Diagnostic,
+    experiments::{Experiment, Experiments},
+    lex::{
+        token::{Numeric, Token, TokenType},
+        Lexer,
+    },
+    parse::{parse_snippet, parse_snippet_with},
+    print::{print_script, PrintStyle},
+};
+
+use super::stmt::stmt_sexp;
+
+fn tokens(source: &str, experiments: Experiments) -> Vec<Token> {
+    let mut lexer = Lexer::snippet(source).with_experiments(experiments);
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next().unwrap() {
+        if token.0 != TokenType::Whitespace {
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+/// The errors of a script parsed with `experiments`.
+fn errors(source: &str, experiments: Experiments) -> Vec<String> {
+    let (_, diagnostics) = parse_snippet_with(source, experiments);
+    diagnostics
+        .into_iter()
+        .map(|d: Diagnostic| d.message)
+        .collect()
+}
+
+#[test]
+pub fn experiment_names() {
+    for experiment in Experiment::ALL {
+        assert_eq!(
+            experiment.name().parse::<Experiment>().ok(),
+            Some(experiment)
+        );
+    }
+    assert!("fibers".parse::<Experiment>().is_err());
+
+    let experiments = Experiments::default().with(Experiment::LongInt);
+    assert!(experiments.is_enabled(Experiment::LongInt));
+    assert!(!experiments.is_enabled(Experiment::AsyncAwait));
+    assert!(Experiment::ALL
+        .into_iter()
+        .all(|experiment| Experiments::all().is_enabled(experiment)));
+}
+
+#[test]
+pub fn async_and_await_are_names_by_default() {
+    let kinds: Vec<TokenType> = tokens("async await", Experiments::default())
+        .into_iter()
+        .map(|token| token.0)
+        .collect();
+    assert_eq!(kinds, [TokenType::Identifier, TokenType::Identifier]);
+    let kinds: Vec<TokenType> = tokens("async await", Experiments::all())
+        .into_iter()
+        .map(|token| token.0)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenType::Keyword(Keyword::Async),
+            TokenType::Keyword(Keyword::Await)
+        ]
+    );
+
+    // PHP code that uses them as names keeps working.
+    let script = parse_snippet("function await($a) {} await(1); const ASYNC = async::X;").unwrap();
+    assert_eq!(script.stmts.len(), 3);
+
+    let message = "require the `async-await` experiment, which is not enabled";
+    for source in [
+        "async function f() {}",
+        "$f = async fn() => 1;",
+        "class A { public async function f() {} }",
+        "$a = await $b;",
+        "await f();",
+    ] {
+        let errors = errors(source, Experiments::default());
+        assert!(
+            errors.iter().any(|error| error.ends_with(message)),
+            "`{}` failed with {:?}",
+            source,
+            errors
+        );
+    }
+}
+
+#[test]
+pub fn async_functions() {
+    let experiments = Experiments::default().with(Experiment::AsyncAwait);
+    let source = "async function f() { return await g(); }
+        $a = static async fn() => await $b + 1;
+        class A { public async function m() {} }";
+    let (script, diagnostics) = parse_snippet_with(source, experiments);
+    assert_eq!(diagnostics, []);
+
+    let StmtKind::Function(function) = &script.stmts[0].kind else {
+        panic!("expected a function, found {:?}", script.stmts[0].kind);
+    };
+    assert!(function.is_async);
+    assert_eq!(
+        stmt_sexp(&function.body.as_ref().unwrap().stmts[0]),
+        "(return (await (call g)))"
+    );
+    let StmtKind::Expr(assign) = &script.stmts[1].kind else {
+        panic!("expected an assignment");
+    };
+    let ExprKind::Assign { value, .. } = &assign.kind else {
+        panic!("expected an assignment");
+    };
+    let ExprKind::ArrowFunction(arrow) = &value.kind else {
+        panic!("expected an arrow function");
+    };
+    assert!(arrow.is_static && arrow.is_async);
+    assert_eq!(
+        stmt_sexp(&script.stmts[1]),
+        "(= $a (static fn () (+ (await $b) 1)))"
+    );
+
+    let printed = print_script(&script, &PrintStyle::default());
+    assert!(printed.contains("async function f()"), "{}", printed);
+    assert!(
+        printed.contains("static async fn () => await $b + 1"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("public async function m()"), "{}", printed);
+    let (reparsed, diagnostics) = parse_snippet_with(&printed, experiments);
+    assert_eq!(diagnostics, []);
+    assert_eq!(print_script(&reparsed, &PrintStyle::default()), printed);
+}
+
+#[test]
+pub fn long_int_literals() {
+    let message = "long integer literals require the `long-int` experiment, which is not enabled";
+    assert_eq!(errors("$a = 123n;", Experiments::default()), [message]);
+
+    let experiments = Experiments::default().with(Experiment::LongInt);
+    let literals: Vec<TokenType> = tokens(
+        "123n 0x10n 0b11n 017n 1_000n 170141183460469231731687303715884105727n 12",
+        experiments,
+    )
+    .into_iter()
+    .map(|token| token.0)
+    .collect();
+    assert_eq!(
+        literals,
+        [
+            TokenType::NumericalLit(Numeric::LInt(123)),
+            TokenType::NumericalLit(Numeric::LInt(16)),
+            TokenType::NumericalLit(Numeric::LInt(3)),
+            TokenType::NumericalLit(Numeric::LInt(15)),
+            TokenType::NumericalLit(Numeric::LInt(1000)),
+            TokenType::NumericalLit(Numeric::LInt(i128::MAX)),
+            TokenType::NumericalLit(Numeric::Int(12)),
+        ]
+    );
+    assert_eq!(
+        errors(
+            "$a = 170141183460469231731687303715884105728n;",
+            experiments
+        ),
+        ["Long integer literal `170141183460469231731687303715884105728n` is out of range"]
+    );
+
+    // `n` only ends an integer literal.
+    for source in ["$a = 1.5n;", "$a = 12nd;"] {
+        assert!(!errors(source, experiments).is_empty(), "{}", source);
+    }
+    let (script, _) = parse_snippet_with("$a = -9n;", experiments);
+    let printed = print_script(&script, &PrintStyle::default());
+    assert!(printed.contains("$a = -9n;"), "{}", printed);
+}
+
+#[test]
+pub fn local_const_statements() {
+    let message = "constants declared inside functions and blocks require the `local-const` \
+                   experiment, which is not enabled";
+    for source in [
+        "function f() { const A = 1; }",
+        "if (true) { const A = 1; }",
+    ] {
+        assert_eq!(
+            errors(source, Experiments::default()),
+            [message],
+            "{}",
+            source
+        );
+    }
+    let experiments = Experiments::default().with(Experiment::LocalConst);
+    assert_eq!(
+        errors("function f() { const A = 1; }", experiments),
+        [] as [&str; 0]
+    );
+}
@@ -0,0 +1,10 @@
+<?php
+#[Attribute(Attribute::TARGET_CLASS | Attribute::IS_REPEATABLE)]
+final class Route {}
+
+#[Route('/'), \App\Cached,]
+function index(#[SensitiveParameter] $password) {}
+
+# a comment, not an attribute
+$a = [1, #[Pure] fn($x) => $x];
+$b = $c[0];
@@ -0,0 +1,17 @@
+<?php
+use rsphp\Mixed;
+
+class Foo {
+    private $bar;
+
+    public function __construct(Mixed $bar) {
+        $this->bar = $bar;
+    }
+
+    public function bar() {
+        return "baz";
+    }
+}
+
+$foo = new Foo("bar");
+echo $foo->bar();
@@ -0,0 +1,15 @@
+<?php
+enum Suit: string implements HasColor
+{
+    case Hearts = 'H';
+}
+
+enum
+    Status {}
+
+function enum() {}
+enum();
+class Enum extends Base {}
+class Sub extends Enum implements Foo {}
+$enum = Enum::class;
+new enum;
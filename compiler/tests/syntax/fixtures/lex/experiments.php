@@ -0,0 +1,6 @@
+<?php
+// experiments: async-await, long-int
+async function fetch($id) {
+    return await load($id, 123n) + 170141183460469231731687303715884105727n;
+}
+$fetch = async fn () => await fetch(0n);
@@ -0,0 +1,5 @@
+<!DOCTYPE html>
+<p>Hello <?= "world" ?>!</p>
+<?php if ($ok): ?>
+<b>yes</b>
+<?php endif ?>
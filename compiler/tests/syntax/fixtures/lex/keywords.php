@@ -0,0 +1,61 @@
+<?php
+abstract
+and
+as
+async
+await
+break
+case
+catch
+class
+clone
+const
+continue
+declare
+default
+do
+else
+elseif
+enddeclare
+endfor
+endforeach
+endif
+endswitch
+endwhile
+extends
+final
+finally
+fn
+for
+foreach
+function
+global
+goto
+if
+implements
+include
+include_once
+instanceof
+insteadof
+interface
+match
+namespace
+new
+or
+private
+protected
+public
+readonly
+require
+require_once
+return
+static
+switch
+throw
+trait
+try
+use
+var
+while
+yield
+from
@@ -0,0 +1,2 @@
+<?php declare(encoding='ISO-8859-1');
+$caf = 'dj vu';
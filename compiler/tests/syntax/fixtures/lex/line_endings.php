@@ -0,0 +1,12 @@
+<?php
+// crlf comment
+$a = __LINE__;
+$b = <<<END
+    one
+
+      two
+    END;
+$c = <<<"END"
+  lf
+  END;// cr comment$d = __line__;$e = <<<'END'  cr $a  END;
+$f = __LINE__;
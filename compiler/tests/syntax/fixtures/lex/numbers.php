@@ -0,0 +1,10 @@
+<?php
+$int = 42;
+$float = 3.14;
+$exp = 1e10;
+$hex = 0x1F;
+$oct = 0o17;
+$bin = 0b101;
+$sep = 1_000_000;
+$large = 9223372036854775807;
+$larger = 170141183460469231731687303715884105727;
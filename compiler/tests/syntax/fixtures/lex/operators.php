@@ -0,0 +1,10 @@
+<?php
+# a hash comment
+$a **= 2 ** 3 <=> $b;
+$c = $a ?? $b ??= $d?->e;
+$f = (int) $a . ( string )$b . (float)1.5;
+$g = $a === $b || $a !== $c && !$d;
+$h = $a <<= 1 >> 2 . .5;
+foo(...$args, name: "it's \"quoted\" {$x["k"]}");
+$i = `ls $dir` xor $j AND $k Or $l;
+$m = @$n++ + --$o;
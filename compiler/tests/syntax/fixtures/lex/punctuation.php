@@ -0,0 +1,10 @@
+<?php
+$a = [1, 2];
+$b = ($a[0] + $a[1]) * 3 / 4 % 5 - 6;
+$c = $a ? $b : $c;
+$d = Foo::BAR;
+$e = $obj->member;
+\Foo\bar();
+{ $x . $y; }
+$f = $a & $b | $c ^ ~$d;
+$g = $a < $b and $c > $d or $e;
@@ -0,0 +1,19 @@
+<?php
+$t = true;
+$f = false;
+$arr = array(1, 2);
+list($a, $b) = $arr;
+isset($a);
+empty($a);
+unset($a);
+eval('1;');
+die();
+exit(1);
+echo PHP_VERSION, PHP_MAJOR_VERSION, PHP_MINOR_VERSION, PHP_RELEASE_VERSION, PHP_VERSION_ID;
+echo PHP_EXTRA_VERSION, PHP_ZTS, PHP_DEBUG, PHP_MAXPATHLEN, PHP_OS, PHP_OS_FAMILY, PHP_SAPI, PHP_EOL;
+echo PHP_INT_MAX, PHP_INT_MIN, PHP_FLOAT_DIG, PHP_FLOAT_EPSILON, PHP_FLOAT_MIN, PHP_FLOAT_MAX;
+echo DEFAULT_INCLUDE_PATH, PEAR_INSTALL_DIR, PEAR_EXTENSION_DIR, PHP_EXTENSION_DIR, PHP_PREFIX;
+echo PHP_BINDIR, PHP_BINARY, PHP_MANDIR, PHP_LIBDIR, PHP_DATADIR, PHP_LOCALSTATEDIR;
+echo PHP_CONFIG_FILE_PATH, PHP_CONFIG_FILE_SCAN_DIR, PHP_SHLIB_SUFFIX, PHP_FD_SETSIZE;
+echo __CLASS__, __DIR__, __FILE__, __FUNCTION__, __LINE__, __METHOD__, __NAMESPACE__, __TRAIT__;
+__halt_compiler();
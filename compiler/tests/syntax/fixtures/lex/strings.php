@@ -0,0 +1,11 @@
+<?php
+$single = 'a single quoted string';
+$double = "a double quoted $string";
+$here = <<<END
+  a
+ b
+c
+END;
+$now = <<<'END'
+ This is a $string that will not have \x41 any parsing
+END;
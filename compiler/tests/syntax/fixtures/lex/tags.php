@@ -0,0 +1,7 @@
+<?php $title = "tags"; ?>
+<?xml version="1.0"?>
+<p><?= $title ?></p>
+<?PHP if ($a): // ends here ?>
+  yes
+<?php endif ?>
+<?php # trailing ?>done
@@ -0,0 +1,7 @@
+<?php
+// a line comment
+/* a block
+   comment */
+$a = 1;	$b = 2;
+$c = 3;
+
@@ -0,0 +1,2 @@
+<?php
+$s = 'caf €';
@@ -0,0 +1,143 @@
+use compiler::syntax::{
+    ast::{
+        expr::{ExprKind, Literal},
+        stmt::StmtKind,
+    },
+    parse::{parse_expr, parse_snippet},
+};
+
+use super::parse::{assert_parses, assert_script_error, sexp};
+
+#[test]
+pub fn function_declarations() {
+    let script = parse_snippet(
+        "function &total(int|float $a = 0, ?array &$b = null, string ...$rest): int {
+            return $a;
+        }
+        function noop() {}",
+    )
+    .unwrap();
+    let StmtKind::Function(total) = &script.stmts[0].kind else {
+        panic!("expected a function, found {:?}", script.stmts[0].kind);
+    };
+    assert_eq!(total.name.as_str(), "total");
+    assert!(total.by_ref);
+    let params = &total.params;
+    assert_eq!(params.len(), 3);
+    assert_eq!(params[0].default.as_ref().map(sexp).as_deref(), Some("0"));
+    assert!(params[1].by_ref && !params[1].variadic);
+    assert!(params[2].variadic && params[2].default.is_none());
+    assert!(total.return_type.is_some());
+    assert_eq!(total.body.as_ref().unwrap().stmts.len(), 1);
+
+    let StmtKind::Function(noop) = &script.stmts[1].kind else {
+        panic!("expected a function, found {:?}", script.stmts[1].kind);
+    };
+    assert!(noop.params.is_empty() && noop.return_type.is_none());
+}
+
+#[test]
+pub fn closures() {
+    assert_parses(
+        "function ($a, $b) use ($c, &$d) { return $a; }",
+        "(function ($a $b) use ($c &$d) {1 stmts})",
+    );
+    assert_parses(
+        "static function () use ($a,) {}",
+        "(static function () use ($a) {0 stmts})",
+    );
+    assert_parses(
+        "$f = function () {}",
+        "(= $f (function () use () {0 stmts}))",
+    );
+
+    let expr = parse_expr("function &(int ...$a) use ($b): static {}").unwrap();
+    let ExprKind::Closure(closure) = &expr.kind else {
+        panic!("expected a closure, found {:?}", expr.kind);
+    };
+    assert!(closure.by_ref && closure.params[0].variadic && closure.return_type.is_some());
+    assert_eq!(closure.span, expr.span);
+}
+
+#[test]
+pub fn arrow_functions() {
+    assert_parses("fn ($x) => $x + $y", "(fn ($x) (+ $x $y))");
+    assert_parses("static fn () => 1", "(static fn () 1)");
+    assert_parses(
+        "fn ($x) => fn ($y) => $x * $y",
+        "(fn ($x) (fn ($y) (* $x $y)))",
+    );
+    assert_parses(
+        "array_map(fn ($x) => $x, $a)",
+        "(call array_map (fn ($x) $x) $a)",
+    );
+    // the body extends as far as an expression can.
+    assert_parses("fn () => $a and $b", "(fn () (and $a $b))");
+
+    let expr = parse_expr("fn &(array $a): ?int => $a[0] ?? null").unwrap();
+    let ExprKind::ArrowFunction(function) = &expr.kind else {
+        panic!("expected an arrow function, found {:?}", expr.kind);
+    };
+    assert!(function.by_ref && function.return_type.is_some());
+    assert!(matches!(
+        &function.body.kind,
+        ExprKind::Binary { right, .. } if right.kind == ExprKind::Literal(Literal::Null)
+    ));
+}
+
+#[test]
+pub fn call_sites() {
+    assert_parses(
+        "foo(1, ...$a, ...$b, name: 2)",
+        "(call foo 1 ...$a ...$b name: 2)",
+    );
+    assert_parses("foo(class: 1, list: 2)", "(call foo class: 1 list: 2)");
+    assert_parses("strlen(...)", "(call strlen ...)");
+    assert_parses("$obj->method(...)", "(->call $obj method ...)");
+    assert_parses("Foo::bar(...)", "(::call Foo bar ...)");
+
+    assert_script_error(
+        "foo(a: 1, 2);",
+        "cannot use positional argument after named argument",
+    );
+    assert_script_error(
+        "foo(...$a, 2);",
+        "cannot use positional argument after argument unpacking",
+    );
+    assert_script_error(
+        "foo(a: 1, ...$b);",
+        "cannot use argument unpacking after named arguments",
+    );
+    assert_script_error(
+        "foo(a: 1, a: 2);",
+        "named parameter $a overwrites previous argument",
+    );
+    assert_script_error("new Foo(...);", "cannot create a closure from `new`");
+}
+
+#[test]
+pub fn invalid_functions() {
+    assert_script_error("function foo();", "function foo() must contain body");
+    assert_script_error("function foo($this) {}", "cannot use $this as parameter");
+    assert_script_error(
+        "function () use ($this) {};",
+        "cannot use $this as lexical variable",
+    );
+    assert_script_error(
+        "function ($a) use ($a) {};",
+        "cannot use lexical variable $a as a parameter name",
+    );
+    assert_script_error(
+        "function () use ($a, $a) {};",
+        "cannot use variable $a twice",
+    );
+    assert_script_error(
+        "function () use ($_GET) {};",
+        "cannot use auto-global $_GET as lexical variable",
+    );
+    assert_script_error("fn ($a) { return $a; };", "expected `=>`");
+    assert_script_error(
+        "while (1) { $f = function () { break; }; }",
+        "'break' not in the 'loop' or 'switch' context",
+    );
+}
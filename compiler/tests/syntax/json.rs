@@ -0,0 +1,287 @@
+use compiler::syntax::{
+    json::{export_expr, export_script},
+    parse::{parse_expr, parse_snippet},
+    source::SourceFile,
+};
+
+fn export(script: &str) -> String {
+    export_script(
+        &parse_snippet(script).unwrap(),
+        &SourceFile::new("test.php", script),
+    )
+}
+
+fn expr(expr: &str) -> String {
+    export_expr(
+        &parse_expr(expr).unwrap(),
+        &SourceFile::new("test.php", expr),
+    )
+}
+
+/// The node types in the output, in order.
+fn node_types(json: &str) -> Vec<&str> {
+    json.lines()
+        .filter_map(|line| line.trim().strip_prefix("\"nodeType\": \""))
+        .map(|rest| rest.trim_end_matches(['"', ',']))
+        .collect()
+}
+
+#[test]
+pub fn layout() {
+    assert_eq!(
+        export("echo 1;\n$a = [];"),
+        r#"[
+    {
+        "nodeType": "Stmt_Echo",
+        "exprs": [
+            {
+                "nodeType": "Scalar_Int",
+                "value": 1,
+                "attributes": {
+                    "startLine": 1,
+                    "startFilePos": 5,
+                    "endLine": 1,
+                    "endFilePos": 5
+                }
+            }
+        ],
+        "attributes": {
+            "startLine": 1,
+            "startFilePos": 0,
+            "endLine": 1,
+            "endFilePos": 6
+        }
+    },
+    {
+        "nodeType": "Stmt_Expression",
+        "expr": {
+            "nodeType": "Expr_Assign",
+            "var": {
+                "nodeType": "Expr_Variable",
+                "name": "a",
+                "attributes": {
+                    "startLine": 2,
+                    "startFilePos": 8,
+                    "endLine": 2,
+                    "endFilePos": 9
+                }
+            },
+            "expr": {
+                "nodeType": "Expr_Array",
+                "items": [],
+                "attributes": {
+                    "startLine": 2,
+                    "startFilePos": 13,
+                    "endLine": 2,
+                    "endFilePos": 14
+                }
+            },
+            "attributes": {
+                "startLine": 2,
+                "startFilePos": 8,
+                "endLine": 2,
+                "endFilePos": 14
+            }
+        },
+        "attributes": {
+            "startLine": 2,
+            "startFilePos": 8,
+            "endLine": 2,
+            "endFilePos": 15
+        }
+    }
+]"#
+    );
+    assert_eq!(export(""), "[]");
+}
+
+#[test]
+pub fn scalars() {
+    let json = expr("[1.5, 1e25, 'a/\"é\\n', TRUE, null, __LINE__, PHP_EOL, \"x$y\"]");
+    assert!(json.contains(r#""value": 1.5,"#));
+    assert!(json.contains(r#""value": 1.0e+25,"#));
+    assert!(json.contains(r#""value": "a\/\"\u00e9\\n","#));
+    assert!(json.contains(r#""name": "TRUE","#));
+    assert!(json.contains(r#""name": "null","#));
+    assert!(json.contains(r#""name": "PHP_EOL","#));
+    assert_eq!(
+        node_types(&json),
+        [
+            "Expr_Array",
+            "ArrayItem",
+            "Scalar_Float",
+            "ArrayItem",
+            "Scalar_Float",
+            "ArrayItem",
+            "Scalar_String",
+            "ArrayItem",
+            "Expr_ConstFetch",
+            "Name",
+            "ArrayItem",
+            "Expr_ConstFetch",
+            "Name",
+            "ArrayItem",
+            "Scalar_MagicConst_Line",
+            "ArrayItem",
+            "Expr_ConstFetch",
+            "Name",
+            "ArrayItem",
+            "Scalar_InterpolatedString",
+            "InterpolatedStringPart",
+            "Expr_Variable",
+        ]
+    );
+}
+
+#[test]
+pub fn expressions() {
+    assert_eq!(
+        node_types(&expr("$a ??= \\Foo\\bar(...$b) . $c?->d::E")),
+        [
+            "Expr_AssignOp_Coalesce",
+            "Expr_Variable",
+            "Expr_BinaryOp_Concat",
+            "Expr_FuncCall",
+            "Name_FullyQualified",
+            "Arg",
+            "Expr_Variable",
+            "Expr_ClassConstFetch",
+            "Expr_NullsafePropertyFetch",
+            "Expr_Variable",
+            "Identifier",
+            "Identifier",
+        ]
+    );
+    assert_eq!(
+        node_types(&expr(
+            "fn(int ...$a): ?A => isset($a[0]) <=> (int) strlen(...)"
+        )),
+        [
+            "Expr_ArrowFunction",
+            "Param",
+            "Identifier",
+            "Expr_Variable",
+            "NullableType",
+            "Name",
+            "Expr_BinaryOp_Spaceship",
+            "Expr_Isset",
+            "Expr_ArrayDimFetch",
+            "Expr_Variable",
+            "Scalar_Int",
+            "Expr_Cast_Int",
+            "Expr_FuncCall",
+            "Name",
+            "VariadicPlaceholder",
+        ]
+    );
+}
+
+#[test]
+pub fn statements() {
+    let json = export(
+        "namespace A;
+        use B\\C as D;
+        use function e;
+        #[F]
+        abstract class G extends H implements I {
+            use J { k as protected; }
+            public static ?int $l = 1;
+            abstract protected function m(self $n);
+        }
+        namespace O;
+        foreach ($p as $q => &$r) { break 1; }",
+    );
+    assert_eq!(
+        node_types(&json),
+        [
+            "Stmt_Namespace",
+            "Name",
+            "Stmt_Use",
+            "UseItem",
+            "Name",
+            "Identifier",
+            "Stmt_Use",
+            "UseItem",
+            "Name",
+            "Stmt_Class",
+            "AttributeGroup",
+            "Attribute",
+            "Name",
+            "Identifier",
+            "Name",
+            "Name",
+            "Stmt_TraitUse",
+            "Name",
+            "Stmt_TraitUseAdaptation_Alias",
+            "Identifier",
+            "Stmt_Property",
+            "NullableType",
+            "Identifier",
+            "PropertyItem",
+            "VarLikeIdentifier",
+            "Scalar_Int",
+            "Stmt_ClassMethod",
+            "Identifier",
+            "Param",
+            "Name",
+            "Expr_Variable",
+            "Stmt_Namespace",
+            "Name",
+            "Stmt_Foreach",
+            "Expr_Variable",
+            "Expr_Variable",
+            "Expr_Variable",
+            "Stmt_Break",
+            "Scalar_Int",
+        ]
+    );
+    // `abstract` and `public static`, `abstract protected`, then `protected` on the alias.
+    for flags in [
+        "\"flags\": 16,",
+        "\"flags\": 9,",
+        "\"flags\": 18,",
+        "\"newModifier\": 2,",
+    ] {
+        assert!(json.contains(flags), "{flags}");
+    }
+    // The first namespace covers the statements after it, up to the second one.
+    assert!(json.contains("\"endLine\": 9,\n            \"endFilePos\""));
+    assert!(json.contains("\"byRef\": true,"));
+}
+
+/// The value and the first and last offset of every `InterpolatedStringPart`.
+fn string_parts(json: &str) -> Vec<(String, usize, usize)> {
+    let lines: Vec<_> = json.lines().map(str::trim).collect();
+    let number = |line: &str, field: &str| -> usize {
+        let rest = line.strip_prefix(field).unwrap();
+        rest.trim_end_matches(',').parse().unwrap()
+    };
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| **line == "\"nodeType\": \"InterpolatedStringPart\",")
+        .map(|(i, _)| {
+            let value = lines[i + 1].strip_prefix("\"value\": ").unwrap();
+            (
+                value.trim_end_matches(',').to_string(),
+                number(lines[i + 4], "\"startFilePos\": "),
+                number(lines[i + 6], "\"endFilePos\": "),
+            )
+        })
+        .collect()
+}
+
+#[test]
+pub fn string_part_spans() {
+    let part = |value: &str, start, end| (value.to_string(), start, end);
+    assert_eq!(
+        string_parts(&expr("\"a\\n$b c{$d}\"")),
+        [part("\"a\\n\"", 1, 3), part("\" c\"", 6, 7)]
+    );
+
+    // The indentation removed from a heredoc is skipped over.
+    assert_eq!(
+        string_parts(&export("$x = <<<EOT\n  a $b\n  c\n  EOT;\n")),
+        [part("\"a \"", 14, 15), part("\"\\nc\"", 18, 21)]
+    );
+}
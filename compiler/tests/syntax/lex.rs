@@ -0,0 +1,166 @@
+//! Golden snapshot tests for the lexer.
+//!
+//! Every `.php` file in `tests/syntax/fixtures/lex` is lexed and the resulting token listing is
+//! compared against the `.tokens` file with the same name. After an intentional change to the
+//! lexer, regenerate the expectations with:
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test --test mod
+//! ```
+//!
+//! A fixture lexes the syntax of experiments when it names them in a header comment on one of
+//! its first two lines, `// experiments: async-await, long-int`.
+use std::{
+    fmt::Write,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use compiler::syntax::{
+    experiments::{Experiment, Experiments},
+    lex::Lexer,
+    source::SourceFile,
+};
+
+const FIXTURES: &str = "tests/syntax/fixtures/lex";
+
+/// Lexes the bytes of `script` into the textual listing stored in the `.tokens` files.
+///
+/// Each token is written on its own line as `line:column start..end Kind "value"`, where the
+/// location is resolved through a `SourceFile`. A lexer error is recorded
+/// inline and lexing resumes after it, except for an unexpected end of file, which ends the listing.
+fn render(script: &[u8]) -> String {
+    let mut lexer = match Lexer::from_bytes(script) {
+        Ok(lexer) => lexer.with_experiments(experiments(script)),
+        Err(e) => return format!("error: {}\n", e),
+    };
+    let file = SourceFile::new("fixture.php", lexer.text());
+    let mut out = String::new();
+    if lexer.has_bom() {
+        out.push_str("<bom>\n");
+    }
+
+    // guards against a lexer that stops advancing after an error.
+    for _ in 0..=script.len() {
+        match lexer.next() {
+            Ok(Some(token)) => {
+                let span = token.span();
+                let location = file.lookup(span.start);
+                write!(out, "{} {} {:?}", location, span, token.kind()).unwrap();
+                if let Some(value) = token.value() {
+                    write!(out, " {:?}", value).unwrap();
+                }
+                out.push('\n');
+            }
+            Ok(None) => break,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && e.get_ref().is_none() => {
+                out.push_str("<eof>\n");
+                break;
+            }
+            Err(e) => writeln!(out, "error: {}", e).unwrap(),
+        }
+    }
+
+    out
+}
+
+/// The experiments named by the `// experiments:` header of a fixture.
+fn experiments(script: &[u8]) -> Experiments {
+    let text = String::from_utf8_lossy(script);
+    let Some(header) = text
+        .lines()
+        .take(2)
+        .find_map(|line| line.trim().strip_prefix("// experiments:"))
+    else {
+        return Experiments::default();
+    };
+    header
+        .split(',')
+        .map(|name| {
+            name.trim()
+                .parse::<Experiment>()
+                .unwrap_or_else(|_| panic!("unknown experiment `{}`", name.trim()))
+        })
+        .fold(Experiments::default(), Experiments::with)
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES);
+    let mut inputs: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "php"))
+        .collect();
+    inputs.sort();
+    inputs
+}
+
+/// Points at the first line where `expected` and `actual` disagree.
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (None, None) => return "listings only differ in whitespace".to_string(),
+            (e, a) => {
+                return format!(
+                    "line {}:\n    expected: {}\n    actual:   {}",
+                    line,
+                    e.unwrap_or("<nothing>"),
+                    a.unwrap_or("<nothing>")
+                )
+            }
+        }
+    }
+}
+
+#[test]
+pub fn golden_tokens() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let inputs = fixtures();
+    assert!(
+        !inputs.is_empty(),
+        "no lexer fixtures found in {}",
+        FIXTURES
+    );
+
+    let mut failures = Vec::new();
+    for input in inputs {
+        let script = fs::read(&input).unwrap();
+        let actual = render(&script);
+        let expected_path = input.with_extension("tokens");
+
+        if update {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{} does not match, {}",
+                expected_path.display(),
+                first_difference(&expected, &actual)
+            )),
+            Err(_) => failures.push(format!("{} is missing", expected_path.display())),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} lexer snapshot(s) failed (rerun with UPDATE_GOLDEN=1 to accept):\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+pub fn reading_past_the_end() {
+    let mut lexer = Lexer::snippet("echo");
+    assert!(lexer.next().unwrap().is_some());
+    assert!(lexer.next().unwrap().is_none());
+    assert_eq!(lexer.next().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,58 @@
+use compiler::syntax::{
+    ast::expr::{Expr, ExprKind},
+    lower::lower_match,
+    parse::parse_expr,
+};
+
+use super::parse::sexp;
+
+#[track_caller]
+fn assert_lowers(source: &str, expected: &str) {
+    let expr = match parse_expr(source) {
+        Ok(expr) => expr,
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    };
+    let lowered = lower(expr);
+    assert_eq!(sexp(&lowered), expected, "while lowering `{}`", source);
+}
+
+fn lower(expr: Expr) -> Expr {
+    match expr.kind {
+        ExprKind::Match { subject, arms } => lower_match(*subject, arms, expr.span),
+        other => panic!("expected a match, found {:?}", other),
+    }
+}
+
+#[test]
+pub fn match_compares_strictly_in_order() {
+    assert_lowers(
+        "match ($a) { 1, '1' => 'x', default => 'y', 2 => 'z' }",
+        r#"(? (|| (=== (= $match@0 $a) 1) (=== $match@0 "1")) "x" (? (=== $match@0 2) "z" "y"))"#,
+    );
+    assert_lowers(
+        "match (true) { $a > 1 => $b, $c => $d, default => $e }",
+        "(? (=== (= $match@0 true) (> $a 1)) $b (? (=== $match@0 $c) $d $e))",
+    );
+}
+
+#[test]
+pub fn unhandled_match_throws() {
+    assert_lowers(
+        "match (f()) { 1 => 2 }",
+        r#"(? (=== (= $match@0 (call f)) 1) 2 (throw (new \UnhandledMatchError (. "Unhandled match case " (? (|| (call \is_array $match@0) (call \is_object $match@0)) (. "of type " (call \get_debug_type $match@0)) (call \var_export $match@0 true))))))"#,
+    );
+}
+
+#[test]
+pub fn subject_is_evaluated_once() {
+    assert_lowers(
+        "match (f()) { default => 1 }",
+        "(? (&& (= $match@0 (call f)) false) null 1)",
+    );
+    // nested matches get temporaries of their own.
+    let expr = parse_expr("match ($a) { 1 => match ($b) { default => 2 } }").unwrap();
+    let ExprKind::Ternary { then, .. } = lower(expr).kind else {
+        panic!("expected a ternary");
+    };
+    assert!(sexp(&lower(*then.unwrap())).contains("$match@18"));
+}
@@ -1,6 +1,26 @@
+mod ast;
+mod attribute;
+mod class;
+mod cst;
+mod encoding;
+mod experiments;
+mod function;
+mod json;
+mod lex;
+mod lower;
+mod parse;
+mod print;
+mod recovery;
+mod resolve;
+mod source;
+mod stmt;
+mod stream;
+mod types;
+mod visit;
+
 use compiler::syntax::lex::Lexer;
 
-const TEST_SCRIPT: &'static str = r#"
+const TEST_SCRIPT: &str = r#"
 <?php
 use rsphp\Mixed;
 
@@ -0,0 +1,579 @@
+use compiler::syntax::{
+    ast::{
+        expr::{
+            ArrayItem, CallArgs, ClassRef, Expr, ExprKind, IncDecOp, Literal, MemberName,
+            StringPart,
+        },
+        name::Name,
+    },
+    lex::{
+        token::{Numeric, StringType, Token, TokenType},
+        Lexer,
+    },
+    parse::{parse_expr, parse_snippet},
+    print::{print_expr, PrintStyle},
+};
+
+/// Renders an expression as an s-expression, which makes the shape of the tree easy to assert.
+pub fn sexp(expr: &Expr) -> String {
+    match &expr.kind {
+        ExprKind::Literal(Literal::Null) => "null".to_string(),
+        ExprKind::Literal(Literal::Bool(b)) => b.to_string(),
+        ExprKind::Literal(Literal::Numeric(Numeric::Int(n))) => n.to_string(),
+        ExprKind::Literal(Literal::Numeric(Numeric::Float(f))) => format!("{:?}", f),
+        ExprKind::Literal(Literal::Numeric(Numeric::LInt(n))) => n.to_string(),
+        ExprKind::Literal(Literal::String(s)) => format!("{:?}", String::from_utf8_lossy(s)),
+        ExprKind::Interpolated { parts, .. } => format!("(interp{})", parts_sexp(parts)),
+        ExprKind::ShellExec(parts) => format!("(shell{})", parts_sexp(parts)),
+        ExprKind::Variable(name) => format!("${}", name),
+        ExprKind::VariableVariable(expr) => format!("(${})", sexp(expr)),
+        ExprKind::Array { items, .. } => {
+            let items: Vec<String> = items.iter().map(item_sexp).collect();
+            format!("[{}]", items.join(" "))
+        }
+        ExprKind::List { items, .. } => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| item.as_ref().map_or("_".to_string(), item_sexp))
+                .collect();
+            format!("(list {})", items.join(" "))
+        }
+        ExprKind::ConstFetch(name) => name_sexp(name),
+        ExprKind::ReservedConst(reserved) => reserved.as_str().to_string(),
+        ExprKind::ClassConstFetch { class, constant } => {
+            format!("(:: {} {})", class_sexp(class), member_sexp(constant))
+        }
+        ExprKind::StaticPropertyFetch { class, property } => {
+            format!("(:: {} {})", class_sexp(class), sexp(property))
+        }
+        ExprKind::PropertyFetch {
+            object,
+            property,
+            nullsafe,
+        } => format!(
+            "({} {} {})",
+            if *nullsafe { "?->" } else { "->" },
+            sexp(object),
+            member_sexp(property)
+        ),
+        ExprKind::ArrayDim { array, index } => match index {
+            Some(index) => format!("([] {} {})", sexp(array), sexp(index)),
+            None => format!("([] {})", sexp(array)),
+        },
+        ExprKind::Call { callee, args } => format!("(call {}{})", sexp(callee), args_sexp(args)),
+        ExprKind::MethodCall {
+            object,
+            method,
+            args,
+            nullsafe,
+        } => format!(
+            "({} {} {}{})",
+            if *nullsafe { "?->call" } else { "->call" },
+            sexp(object),
+            member_sexp(method),
+            args_sexp(args)
+        ),
+        ExprKind::StaticCall {
+            class,
+            method,
+            args,
+        } => format!(
+            "(::call {} {}{})",
+            class_sexp(class),
+            member_sexp(method),
+            args_sexp(args)
+        ),
+        ExprKind::New { class, args } => match args {
+            Some(args) => format!(
+                "(new {}{})",
+                class_sexp(class),
+                args_sexp(&CallArgs::List(args.clone()))
+            ),
+            None => format!("(new {})", class_sexp(class)),
+        },
+        ExprKind::Unary { op, expr } => format!("({} {})", op.as_str(), sexp(expr)),
+        ExprKind::Binary { op, left, right } => {
+            format!("({} {} {})", op.as_str(), sexp(left), sexp(right))
+        }
+        ExprKind::IncDec { op, expr } => {
+            let op = match op {
+                IncDecOp::PreIncrement => "pre++",
+                IncDecOp::PreDecrement => "pre--",
+                IncDecOp::PostIncrement => "post++",
+                IncDecOp::PostDecrement => "post--",
+            };
+            format!("({} {})", op, sexp(expr))
+        }
+        ExprKind::Assign { target, value } => format!("(= {} {})", sexp(target), sexp(value)),
+        ExprKind::AssignRef { target, value } => {
+            format!("(=& {} {})", sexp(target), sexp(value))
+        }
+        ExprKind::AssignOp { op, target, value } => {
+            format!("({}= {} {})", op.as_str(), sexp(target), sexp(value))
+        }
+        ExprKind::Ternary {
+            cond,
+            then,
+            otherwise,
+        } => match then {
+            Some(then) => format!("(? {} {} {})", sexp(cond), sexp(then), sexp(otherwise)),
+            None => format!("(?: {} {})", sexp(cond), sexp(otherwise)),
+        },
+        ExprKind::Cast { kind, expr } => format!("(({}) {})", kind.as_str(), sexp(expr)),
+        ExprKind::InstanceOf { expr, class } => {
+            format!("(instanceof {} {})", sexp(expr), class_sexp(class))
+        }
+        ExprKind::Clone(expr) => format!("(clone {})", sexp(expr)),
+        ExprKind::Print(expr) => format!("(print {})", sexp(expr)),
+        ExprKind::ErrorSuppress(expr) => format!("(@ {})", sexp(expr)),
+        ExprKind::Await(expr) => format!("(await {})", sexp(expr)),
+        ExprKind::Construct { construct, args } => {
+            let args: Vec<String> = args.iter().map(|arg| format!(" {}", sexp(arg))).collect();
+            format!("({}{})", construct.as_str(), args.concat())
+        }
+        ExprKind::Include { keyword, path } => format!("({} {})", keyword, sexp(path)),
+        ExprKind::Throw(expr) => format!("(throw {})", sexp(expr)),
+        ExprKind::Yield { key, value } => match (key, value) {
+            (Some(key), Some(value)) => format!("(yield {} => {})", sexp(key), sexp(value)),
+            (_, Some(value)) => format!("(yield {})", sexp(value)),
+            _ => "(yield)".to_string(),
+        },
+        ExprKind::YieldFrom(expr) => format!("(yield-from {})", sexp(expr)),
+        ExprKind::Match { subject, arms } => {
+            let arms: Vec<String> = arms
+                .iter()
+                .map(|arm| {
+                    let conditions = match &arm.conditions {
+                        Some(conditions) => {
+                            conditions.iter().map(sexp).collect::<Vec<_>>().join(" ")
+                        }
+                        None => "default".to_string(),
+                    };
+                    format!("({} => {})", conditions, sexp(&arm.body))
+                })
+                .collect();
+            format!("(match {} {})", sexp(subject), arms.join(" "))
+        }
+        ExprKind::Closure(closure) => {
+            let params: Vec<String> = closure
+                .params
+                .iter()
+                .map(|p| format!("${}", p.name))
+                .collect();
+            let uses: Vec<String> = closure
+                .uses
+                .iter()
+                .map(|u| format!("{}${}", if u.by_ref { "&" } else { "" }, u.name))
+                .collect();
+            format!(
+                "({}function ({}) use ({}) {{{} stmts}})",
+                if closure.is_static { "static " } else { "" },
+                params.join(" "),
+                uses.join(" "),
+                closure.body.stmts.len()
+            )
+        }
+        ExprKind::ArrowFunction(function) => {
+            let params: Vec<String> = function
+                .params
+                .iter()
+                .map(|p| format!("${}", p.name))
+                .collect();
+            format!(
+                "({}fn ({}) {})",
+                if function.is_static { "static " } else { "" },
+                params.join(" "),
+                sexp(&function.body)
+            )
+        }
+    }
+}
+
+fn name_sexp(name: &Name) -> String {
+    name.to_string()
+}
+
+fn class_sexp(class: &ClassRef) -> String {
+    match class {
+        ClassRef::Name(name) => name_sexp(name),
+        ClassRef::Expr(expr) => sexp(expr),
+        ClassRef::Anonymous(_) => "class".to_string(),
+    }
+}
+
+fn member_sexp(member: &MemberName) -> String {
+    match member {
+        MemberName::Ident(ident) => ident.to_string(),
+        MemberName::Expr(expr) => sexp(expr),
+    }
+}
+
+fn args_sexp(args: &CallArgs) -> String {
+    match args {
+        CallArgs::FirstClassCallable(_) => " ...".to_string(),
+        CallArgs::List(args) => args
+            .iter()
+            .map(|arg| {
+                let mut out = " ".to_string();
+                if let Some(name) = &arg.name {
+                    out.push_str(&format!("{}: ", name));
+                }
+                if arg.unpack {
+                    out.push_str("...");
+                }
+                out.push_str(&sexp(&arg.value));
+                out
+            })
+            .collect(),
+    }
+}
+
+fn item_sexp(item: &ArrayItem) -> String {
+    let mut out = String::new();
+    if item.unpack {
+        out.push_str("...");
+    }
+    if let Some(key) = &item.key {
+        out.push_str(&format!("{} => ", sexp(key)));
+    }
+    if item.by_ref {
+        out.push('&');
+    }
+    out.push_str(&sexp(&item.value));
+    out
+}
+
+fn parts_sexp(parts: &[StringPart]) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            StringPart::Literal(text, _) => format!(" {:?}", String::from_utf8_lossy(text)),
+            StringPart::Expr(expr) => format!(" {}", sexp(expr)),
+        })
+        .collect()
+}
+
+/// Checks that `source` parses as an expression whose s-expression is `expected`.
+#[track_caller]
+pub fn assert_parses(source: &str, expected: &str) {
+    match parse_expr(source) {
+        Ok(expr) => assert_eq!(sexp(&expr), expected, "while parsing `{}`", source),
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    }
+}
+
+/// Checks that `source` fails to parse as an expression, with an error containing `expected`.
+#[track_caller]
+pub fn assert_error(source: &str, expected: &str) {
+    match parse_expr(source) {
+        Ok(expr) => panic!("`{}` parsed as {}", source, sexp(&expr)),
+        Err(e) => assert_contains(source, &e.message, expected),
+    }
+}
+
+/// The error `source` fails to parse with as a script.
+#[track_caller]
+pub fn script_error(source: &str) -> String {
+    match parse_snippet(source) {
+        Ok(script) => panic!("`{}` parsed as {:?}", source, script.stmts),
+        Err(e) => e.message,
+    }
+}
+
+/// Checks that `source` fails to parse as a script, with an error containing `expected`.
+#[track_caller]
+pub fn assert_script_error(source: &str, expected: &str) {
+    assert_contains(source, &script_error(source), expected);
+}
+
+#[track_caller]
+fn assert_contains(source: &str, message: &str, expected: &str) {
+    assert!(
+        message.contains(expected),
+        "error for `{}` was `{}`, expected it to contain `{}`",
+        source,
+        message,
+        expected
+    );
+}
+
+#[test]
+pub fn arithmetic_precedence() {
+    assert_parses("1 + 2 * 3", "(+ 1 (* 2 3))");
+    assert_parses("1 - 2 - 3", "(- (- 1 2) 3)");
+    assert_parses("(1 + 2) * 3", "(* (+ 1 2) 3)");
+    assert_parses("2 ** 3 ** 2", "(** 2 (** 3 2))");
+    assert_parses("-2 ** 2", "(- (** 2 2))");
+    assert_parses("2 ** -1", "(** 2 (- 1))");
+    assert_parses("1 << 2 + 3", "(<< 1 (+ 2 3))");
+    assert_parses("$a % 2 == 0", "(== (% $a 2) 0)");
+}
+
+#[test]
+pub fn concat_binds_looser_than_arithmetic() {
+    // PHP 8 gives `+` and `-` a higher precedence than `.`.
+    assert_parses("'a' . 1 + 2", "(. \"a\" (+ 1 2))");
+    assert_parses("'a' . 1 << 2", "(. \"a\" (<< 1 2))");
+    assert_parses("$a . $b . $c", "(. (. $a $b) $c)");
+}
+
+#[test]
+pub fn logical_operators() {
+    assert_parses("$a || $b && $c", "(|| $a (&& $b $c))");
+    assert_parses("$a or $b xor $c and $d", "(or $a (xor $b (and $c $d)))");
+    assert_parses("$a = true and false", "(and (= $a true) false)");
+    assert_parses("$a = true && false", "(= $a (&& true false))");
+    assert_parses("$a & $b | $c ^ ~$d", "(| (& $a $b) (^ $c (~ $d)))");
+    assert_parses("!$a && $b", "(&& (! $a) $b)");
+    assert_parses("!$a instanceof B", "(! (instanceof $a B))");
+}
+
+#[test]
+pub fn right_associative_operators() {
+    assert_parses("$a ?? $b ?? $c", "(?? $a (?? $b $c))");
+    assert_parses("$a = $b = 1", "(= $a (= $b 1))");
+    assert_parses("$a += $b -= 1", "(+= $a (-= $b 1))");
+    assert_parses("$a ??= []", "(??= $a [])");
+    assert_parses("$a .= 'b'", "(.= $a \"b\")");
+    assert_parses("$a **= 2", "(**= $a 2)");
+    assert_parses("$a = &$b", "(=& $a $b)");
+    assert_parses("$a ?? $b || $c", "(?? $a (|| $b $c))");
+}
+
+#[test]
+pub fn assignment_binds_to_variables() {
+    assert_parses("!$a = foo()", "(! (= $a (call foo)))");
+    assert_parses("$a && $b = 1", "(&& $a (= $b 1))");
+    assert_parses("[$a, [$b, $c]] = $d", "(= (list $a (list $b $c)) $d)");
+    assert_parses("[, $b] = $d", "(= (list _ $b) $d)");
+    assert_parses("list('k' => $a) = $d", "(= (list \"k\" => $a) $d)");
+    assert_parses("$a->b[] = 1", "(= ([] (-> $a b)) 1)");
+    assert_error("1 + 2 = 3", "cannot assign");
+    assert_error("$a?->b = 1", "cannot assign");
+}
+
+#[test]
+pub fn comparisons_are_non_associative() {
+    assert_parses("$a == $b && $b == $c", "(&& (== $a $b) (== $b $c))");
+    assert_parses("$a < $b == $c", "(== (< $a $b) $c)");
+    assert_parses("($a == $b) == $c", "(== (== $a $b) $c)");
+    assert_parses("1 <=> 2", "(<=> 1 2)");
+    assert_error("$a == $b == $c", "non-associative");
+    assert_error("$a === $b != $c", "non-associative");
+    assert_error("$a < $b > $c", "non-associative");
+}
+
+#[test]
+pub fn ternaries() {
+    assert_parses("$a ? $b : $c", "(? $a $b $c)");
+    assert_parses("$a ?: $b", "(?: $a $b)");
+    assert_parses("$a ?: $b ?: $c", "(?: (?: $a $b) $c)");
+    assert_parses("$a ? $b ? 1 : 2 : 3", "(? $a (? $b 1 2) 3)");
+    assert_parses("($a ? 1 : 2) ? 3 : 4", "(? (? $a 1 2) 3 4)");
+    assert_parses("$a ? 1 : ($b ? 2 : 3)", "(? $a 1 (? $b 2 3))");
+    assert_parses("$a = $b ? 1 : 2", "(= $a (? $b 1 2))");
+    assert_parses("$a ?? $b ? 1 : 2", "(? (?? $a $b) 1 2)");
+    assert_error("$a ? 1 : $b ? 2 : 3", "require parentheses");
+    assert_error("$a ?: 1 ? 2 : 3", "require parentheses");
+    assert_error("$a ? 1 : 2 ?: 3", "require parentheses");
+}
+
+#[test]
+pub fn increments_and_casts() {
+    assert_parses("++$a", "(pre++ $a)");
+    assert_parses("$a--", "(post-- $a)");
+    assert_parses("-$a++", "(- (post++ $a))");
+    assert_parses("++$a->b", "(pre++ (-> $a b))");
+    assert_parses("$a++ + ++$b", "(+ (post++ $a) (pre++ $b))");
+    assert_parses("(int) $a + 1", "(+ ((int) $a) 1)");
+    assert_parses("( integer )$a", "((int) $a)");
+    assert_parses("(bool) (double) $a", "((bool) ((float) $a))");
+    assert_parses("(string) $a . 'x'", "(. ((string) $a) \"x\")");
+    assert_error("++1", "cannot use `++`");
+}
+
+#[test]
+pub fn prefix_keywords() {
+    assert_parses("clone $a->b", "(clone (-> $a b))");
+    assert_parses("clone $a + 1", "(+ (clone $a) 1)");
+    assert_parses("new Foo", "(new Foo)");
+    assert_parses("new Foo(1, 2)", "(new Foo 1 2)");
+    assert_parses("new \\Foo\\Bar()", "(new \\Foo\\Bar)");
+    assert_parses("new $class($a)", "(new $class $a)");
+    assert_parses("new $a->b['c']", "(new ([] (-> $a b) \"c\"))");
+    assert_parses("new static", "(new static)");
+    assert_parses("(new Foo)->bar()", "(->call (new Foo) bar)");
+    assert_parses("$a instanceof Foo", "(instanceof $a Foo)");
+    assert_parses("$a instanceof $b", "(instanceof $a $b)");
+    assert_parses("print $a and $b", "(and (print $a) $b)");
+    assert_parses("print $a . $b", "(print (. $a $b))");
+    assert_parses("@foo() ?: 1", "(?: (@ (call foo)) 1)");
+    assert_parses("@$a['b']", "(@ ([] $a \"b\"))");
+    assert_parses("throw $e", "(throw $e)");
+    assert_parses("$a ?? throw new E", "(?? $a (throw (new E)))");
+    assert_parses("include 'a' . 'b'", "(include (. \"a\" \"b\"))");
+}
+
+#[test]
+pub fn yields() {
+    assert_parses("yield", "(yield)");
+    assert_parses("$a = yield", "(= $a (yield))");
+    assert_parses("f(yield, yield $a)", "(call f (yield) (yield $a))");
+    assert_parses("yield $a + 1", "(yield (+ $a 1))");
+    assert_parses("yield $k => $v", "(yield $k => $v)");
+    assert_parses("$a = yield $k => $v", "(= $a (yield $k => $v))");
+    assert_parses("yield $a = 1", "(yield (= $a 1))");
+    assert_parses("yield $a and $b", "(and (yield $a) $b)");
+    assert_parses("yield from $a", "(yield-from $a)");
+    assert_parses("yield from f() or $b", "(or (yield-from (call f)) $b)");
+    assert_parses("$r = yield from gen()", "(= $r (yield-from (call gen)))");
+}
+
+#[test]
+pub fn member_access_and_calls() {
+    assert_parses("$a->b->c", "(-> (-> $a b) c)");
+    assert_parses("$a?->b()", "(?->call $a b)");
+    assert_parses("$a->$b", "(-> $a $b)");
+    assert_parses("$a->{'b' . $c}", "(-> $a (. \"b\" $c))");
+    assert_parses("$a->class", "(-> $a class)");
+    assert_parses("Foo::BAR", "(:: Foo BAR)");
+    assert_parses("Foo::class", "(:: Foo class)");
+    assert_parses("Foo::$bar", "(:: Foo $bar)");
+    assert_parses("static::create()", "(::call static create)");
+    assert_parses("$obj::create()", "(::call $obj create)");
+    assert_parses("foo(...)", "(call foo ...)");
+    assert_parses("foo(...$rest, name: 1)", "(call foo ...$rest name: 1)");
+    assert_parses("foo(1, 2,)", "(call foo 1 2)");
+    assert_parses("$fn()()", "(call (call $fn))");
+    assert_parses("$a[0][]", "([] ([] $a 0))");
+    assert_parses("$$a", "($$a)");
+    assert_parses("${'a' . 'b'}", "($(. \"a\" \"b\"))");
+}
+
+#[test]
+pub fn literals_and_constructs() {
+    assert_parses("null", "null");
+    assert_parses("NULL", "null");
+    assert_parses("TRUE", "true");
+    assert_parses("0x1F + 0b11 + 017 + 1_000", "(+ (+ (+ 31 3) 15) 1000)");
+    assert_parses("1.5e3", "1500.0");
+    assert_parses("PHP_EOL", "PHP_EOL");
+    assert_parses("\\Foo\\BAR", "\\Foo\\BAR");
+    assert_parses("namespace\\foo()", "(call namespace\\foo)");
+    assert_parses("[1, 'a' => 2, ...$b, &$c]", "[1 \"a\" => 2 ...$b &$c]");
+    assert_parses("array(1, 2)", "[1 2]");
+    assert_parses("isset($a, $b['c'])", "(isset $a ([] $b \"c\"))");
+    assert_parses("empty($a)", "(empty $a)");
+    assert_parses("exit", "(exit)");
+    assert_parses("die('x')", "(die \"x\")");
+    assert_error("array(1, , 2)", "empty array elements");
+}
+
+#[test]
+pub fn strings() {
+    assert_parses(r"'it\'s a \\ \n'", r#""it's a \\ \\n""#);
+    assert_parses(
+        r#""tab\tdollar\$ \x41\101\u{1F600}""#,
+        r#""tab\tdollar$ AA😀""#,
+    );
+    assert_parses(r#""a $b c""#, r#"(interp "a " $b " c")"#);
+    assert_parses(
+        r#""$a[0] $a[b] $a[$c] $a->b""#,
+        r#"(interp ([] $a 0) " " ([] $a "b") " " ([] $a $c) " " (-> $a b))"#,
+    );
+    assert_parses(r#""{$a->b()} ${c}""#, r#"(interp (->call $a b) " " $c)"#);
+    assert_parses(r#""{$a["k"]}""#, r#"(interp ([] $a "k"))"#);
+    assert_parses("`ls $dir`", r#"(shell "ls " $dir)"#);
+    assert_parses("\"$\"", r#""$""#);
+}
+
+#[test]
+pub fn byte_strings() {
+    // the lexer keeps the escapes as written, so the string is still valid UTF-8 there.
+    let source = r#""\xff\101\u{e9}$a\0""#;
+    let Some(Token(TokenType::StringLit(StringType::Double), _, Some(body))) =
+        Lexer::snippet(source).next().unwrap()
+    else {
+        panic!("expected a double quoted string");
+    };
+    assert_eq!(body, r"\xff\101\u{e9}$a\0");
+
+    let ExprKind::Interpolated { parts, .. } = parse_expr(source).unwrap().kind else {
+        panic!("expected an interpolated string");
+    };
+    let literals: Vec<&[u8]> = parts
+        .iter()
+        .filter_map(|part| match part {
+            StringPart::Literal(bytes, _) => Some(bytes.as_slice()),
+            StringPart::Expr(_) => None,
+        })
+        .collect();
+    assert_eq!(literals, [&b"\xffA\xc3\xa9"[..], b"\0"]);
+
+    let expr = parse_expr(r#""\xc3\251\x80""#).unwrap();
+    assert_eq!(
+        expr.kind,
+        ExprKind::Literal(Literal::String(b"\xc3\xa9\x80".to_vec()))
+    );
+    // bytes that are not UTF-8 can only be written as escapes.
+    let printed = print_expr(&expr, &PrintStyle::default());
+    assert_eq!(printed, r#""é\x80""#);
+    assert_eq!(parse_expr(&printed).unwrap().kind, expr.kind);
+}
+
+#[test]
+pub fn match_expressions() {
+    assert_parses(
+        "match ($a) { 1, 2 => 'low', 3, => 'three', default => 'high', }",
+        r#"(match $a (1 2 => "low") (3 => "three") (default => "high"))"#,
+    );
+    assert_parses(
+        "match (true) { $a > 1 => $b, default, => throw $e }",
+        "(match true ((> $a 1) => $b) (default => (throw $e)))",
+    );
+    assert_parses("match ($a) {}", "(match $a )");
+    assert_parses(
+        "$x = match ($a) { 1 => 2 } + 1",
+        "(= $x (+ (match $a (1 => 2)) 1))",
+    );
+    assert_error(
+        "match ($a) { default => 1, default => 2 }",
+        "match expressions may only contain one default arm",
+    );
+    assert_error("match ($a) { 1 }", "expected `=>`, found `}`");
+    assert_error("match { 1 => 2 }", "expected `(`");
+}
+
+#[test]
+pub fn errors_point_at_the_problem() {
+    let e = parse_expr("1 +").unwrap_err();
+    assert_eq!(e.message, "expected an expression, found end of file");
+    assert_eq!(e.span.start, 3);
+
+    let e = parse_expr("($a").unwrap_err();
+    assert_eq!(e.message, "expected `)`, found end of file");
+
+    let e = parse_expr("$a $b").unwrap_err();
+    assert_eq!(e.message, "expected end of file, found `$`");
+    assert_eq!(e.span.start, 3);
+
+    let e = parse_expr("\"unterminated").unwrap_err();
+    assert!(e.message.contains("Unterminated string"), "{}", e.message);
+}
+
+#[test]
+pub fn spans_cover_the_expression() {
+    let source = "foo($a + 1)";
+    let expr = parse_expr(source).unwrap();
+    assert_eq!(&source[expr.span.range()], source);
+
+    let source = "\"x {$a->b}\"";
+    let expr = parse_expr(source).unwrap();
+    let ExprKind::Interpolated { parts, .. } = expr.kind else {
+        panic!("expected an interpolated string");
+    };
+    let StringPart::Expr(inner) = &parts[1] else {
+        panic!("expected an expression part");
+    };
+    assert_eq!(&source[inner.span.range()], "$a->b");
+}
@@ -0,0 +1,322 @@
+use compiler::syntax::{
+    ast::expr::{BinaryOp, Expr, ExprKind, Literal, UnaryOp},
+    lex::token::Numeric,
+    parse::{parse_expr, parse_snippet},
+    print::{print_expr, print_script, PrintStyle},
+    source::Span,
+};
+
+use super::parse::sexp;
+
+/// Prints an expression and checks that it parses back to the same tree.
+#[track_caller]
+fn reprint(source: &str) -> String {
+    let expr = parse_expr(source).unwrap();
+    let printed = print_expr(&expr, &PrintStyle::default());
+    let reparsed = match parse_expr(&printed) {
+        Ok(expr) => expr,
+        Err(e) => panic!(
+            "`{}` printed as `{}`, which fails with {}",
+            source, printed, e
+        ),
+    };
+    assert_eq!(
+        sexp(&reparsed),
+        sexp(&expr),
+        "`{}` printed as `{}`",
+        source,
+        printed
+    );
+    printed
+}
+
+/// Prints a script twice over, checking that the output is stable.
+#[track_caller]
+fn round_trip(source: &str, style: &PrintStyle) -> String {
+    let printed = print_script(&parse_snippet(source).unwrap(), style);
+    let script = match parse_snippet(&printed) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse the printed script: {}\n{}", e, printed),
+    };
+    assert_eq!(print_script(&script, style), printed);
+    printed
+}
+
+fn int(n: i64) -> Expr {
+    Expr::new(
+        ExprKind::Literal(Literal::Numeric(Numeric::Int(n))),
+        Span::new(0, 0),
+    )
+}
+
+fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    let kind = ExprKind::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    };
+    Expr::new(kind, Span::new(0, 0))
+}
+
+#[test]
+pub fn parentheses() {
+    for (source, expected) in [
+        ("(($a + $b)) * $c", "($a + $b) * $c"),
+        ("$a + ($b * $c)", "$a + $b * $c"),
+        ("$a - ($b - $c)", "$a - ($b - $c)"),
+        ("($a - $b) - $c", "$a - $b - $c"),
+        ("$a ** ($b ** $c)", "$a ** $b ** $c"),
+        ("($a ** $b) ** $c", "($a ** $b) ** $c"),
+        ("($a ?? $b) ?? $c", "($a ?? $b) ?? $c"),
+        ("-$a ** 2", "-$a ** 2"),
+        ("(-$a) ** 2", "(-$a) ** 2"),
+        ("-(-$a)", "- -$a"),
+        ("-(--$a)", "- --$a"),
+        ("!($a instanceof B)", "!$a instanceof B"),
+        ("(!$a) instanceof B", "(!$a) instanceof B"),
+        ("!($a && $b)", "!($a && $b)"),
+        ("($a == $b) == $c", "($a == $b) == $c"),
+        ("$a < ($b < $c)", "$a < ($b < $c)"),
+        ("($a and $b) or $c", "$a and $b or $c"),
+        ("$a = ($b or $c)", "$a = ($b or $c)"),
+        ("($a = $b) or $c", "$a = $b or $c"),
+        ("$a = $b = $c + 1", "$a = $b = $c + 1"),
+        ("($a = 1) + 2", "($a = 1) + 2"),
+        ("$a + ($b = 2)", "$a + ($b = 2)"),
+        ("!$a = 1", "!($a = 1)"),
+        ("($a ? $b : $c) ? $d : $e", "($a ? $b : $c) ? $d : $e"),
+        ("$a ? $b : ($c ? $d : $e)", "$a ? $b : ($c ? $d : $e)"),
+        ("$a ?: $b", "$a ?: $b"),
+        ("($a ?? $b) ? $c : $d", "$a ?? $b ? $c : $d"),
+        ("(print $a) . $b", "(print $a) . $b"),
+        ("print ($a . $b)", "print $a . $b"),
+        ("(clone $a)->b", "(clone $a)->b"),
+        ("(int) ($a + $b)", "(int) ($a + $b)"),
+        ("((int) $a) + $b", "(int) $a + $b"),
+        ("@($a . $b)", "@($a . $b)"),
+        ("$a ?? throw new E()", "$a ?? throw new E()"),
+        ("(fn() => $a) + 1", "(fn () => $a) + 1"),
+        ("fn() => $a + 1", "fn () => $a + 1"),
+        ("$a instanceof $b", "$a instanceof $b"),
+        ("yield ($a and $b)", "yield ($a and $b)"),
+        ("(yield $a) and $b", "yield $a and $b"),
+        ("yield $k => ($a or $b)", "yield $k => ($a or $b)"),
+        ("$a = yield from ($b xor $c)", "$a = yield from ($b xor $c)"),
+        ("(yield $a) + 1", "(yield $a) + 1"),
+        ("f(yield)", "f(yield)"),
+        ("yield $a = 1", "yield $a = 1"),
+    ] {
+        assert_eq!(reprint(source), expected, "while printing `{}`", source);
+    }
+}
+
+#[test]
+pub fn postfix_operands() {
+    for (source, expected) in [
+        ("(new Foo)->bar()", "(new Foo)->bar()"),
+        ("(new Foo())['a']", "(new Foo())['a']"),
+        ("(function () {})()", "(function () {\n})()"),
+        ("($a->b)()", "($a->b)()"),
+        ("$a->b()", "$a->b()"),
+        ("(A::B)()", "(A::B)()"),
+        ("A::{'b'}()", "A::{'b'}()"),
+        ("A::$b()", "A::$b()"),
+        ("$a::$b", "$a::$b"),
+        ("$a->$b->{$c . 'd'}", "$a->$b->{$c . 'd'}"),
+        ("${'a' . $b}[0]", "${'a' . $b}[0]"),
+        ("$$a[0]", "$$a[0]"),
+        ("${$a[0]}", "${$a[0]}"),
+        ("[1, 2][0]", "[1, 2][0]"),
+        ("'abc'[0]", "'abc'[0]"),
+        ("($a ?? $b)[0]", "($a ?? $b)[0]"),
+        ("($a + 1)++", "($a + 1)++"),
+        ("new $a->b['c']", "new $a->b['c']"),
+        ("new ($a . 'B')", "new ($a . 'B')"),
+        ("strlen(...)", "strlen(...)"),
+    ] {
+        // `($a + 1)++` does not parse, it is only printed.
+        if source.ends_with("++") {
+            continue;
+        }
+        assert_eq!(reprint(source), expected, "while printing `{}`", source);
+    }
+}
+
+#[test]
+pub fn literals() {
+    for (source, expected) in [
+        ("'it\\'s \\\\ $a'", "'it\\'s \\\\ $a'"),
+        ("\"a\\tb\\n\"", "'a\tb\n'"),
+        (
+            "\"$a \\$b {$c->d[0]} ${e} \\\"\"",
+            "\"{$a} \\$b {$c->d[0]} {$e} \\\"\"",
+        ),
+        ("\"a{$b}\\x01\"", "\"a{$b}\\x01\""),
+        ("`ls $dir`", "`ls {$dir}`"),
+        ("1.5", "1.5"),
+        ("1e100", "1e100"),
+        ("10.0", "10.0"),
+        ("0x1F", "31"),
+        ("[1, 'a' => &$b, ...$c]", "[1, 'a' => &$b, ...$c]"),
+        ("array(1, 2)", "array(1, 2)"),
+        ("PHP_EOL . __LINE__", "PHP_EOL . __LINE__"),
+        ("isset($a, $b) && empty($c)", "isset($a, $b) && empty($c)"),
+    ] {
+        assert_eq!(reprint(source), expected, "while printing `{}`", source);
+    }
+}
+
+#[test]
+pub fn built_fragments() {
+    let style = PrintStyle::default();
+    // a fragment built by hand has no parentheses to begin with.
+    let sum = binary(BinaryOp::Add, int(1), int(2));
+    let product = binary(BinaryOp::Mul, sum.clone(), int(3));
+    assert_eq!(print_expr(&product, &style), "(1 + 2) * 3");
+    let product = binary(BinaryOp::Mul, int(3), sum);
+    assert_eq!(print_expr(&product, &style), "3 * (1 + 2)");
+
+    // a negative literal is a `-` followed by the number once printed.
+    let power = binary(BinaryOp::Pow, int(-2), int(2));
+    assert_eq!(print_expr(&power, &style), "(-2) ** 2");
+    let negated = Expr::new(
+        ExprKind::Unary {
+            op: UnaryOp::Minus,
+            expr: Box::new(int(-2)),
+        },
+        Span::new(0, 0),
+    );
+    assert_eq!(print_expr(&negated, &style), "- -2");
+
+    // a call in a string can not be interpolated.
+    let mut string = parse_expr("\"a{$b}c\"").unwrap();
+    if let ExprKind::Interpolated { parts, .. } = &mut string.kind {
+        parts[1] = compiler::syntax::ast::expr::StringPart::Expr(parse_expr("f($b)").unwrap());
+    }
+    assert_eq!(print_expr(&string, &style), "(\"a\" . f($b) . \"c\")");
+}
+
+const SCRIPT: &str = "namespace App\\Http;
+use Foo\\Bar as Baz, Qux;
+use function strlen;
+use Foo\\{A, function b, const C};
+#[Route('/'), Tag]
+abstract class Controller extends Base implements \\Countable, Stringable {
+    use Greets, Waves { Greets::hi insteadof Waves; Waves::hi as protected wave; }
+    public const int A = 1, B = 2;
+    private static ?array $cache = null, $other;
+    public function __construct(private readonly int $a = 1, #[Sensitive] string ...$rest) {}
+    abstract protected function &f(A&B $x, (A&B)|null $y): static;
+    public function count(): int { return count($this->cache ?? []); }
+}
+interface I extends J, K { function f(); }
+enum Suit: string implements I { case Hearts = 'H'; case Spades = 'S'; const X = self::Hearts; }
+function g(int|string $a, &$b, $c = [1, 2]): ?int {
+    static $calls = 0, $last;
+    global $config;
+    if ($a) { echo $a, $b; } elseif ($b) { return; } else if ($c) { $d = 1; } else { unset($c); }
+    while ($i < 10) $i++;
+    do { --$i; } while ($i > 0);
+    for ($i = 0, $j = 1; $i < 10; $i++) { continue; }
+    for (;;) { break; }
+    foreach ($items as $key => &$value) { break 1; }
+    foreach ($pairs as [$x, , 'y' => $y]) {}
+    list($a, list(, $b)) = $c;
+    switch ($a) { case 1: case 2: $b = 1; break; default: $b = 2; }
+    declare(ticks=1) { $x = 1; }
+    $f = static function &($x) use ($a, &$b): int { return $x; };
+    $g = fn(int $x): int => $x * 2;
+    $h = match (true) { $a > 1, $a < -1 => 'far', default => 'near', };
+    $o = new #[Anon] class($a) extends Base { public $p = 1; };
+    $v = $a?->b?->c($d, ...$e) ?? A::$b::C;
+    $w = f(name: 1, other: \"x{$y}\");
+    goto end;
+    end:
+    {
+        ;
+    }
+    return $a <=> $b;
+}
+declare(ticks=1);";
+
+#[test]
+pub fn scripts() {
+    let printed = round_trip(SCRIPT, &PrintStyle::default());
+    assert!(printed.starts_with(
+        "namespace App\\Http;
+
+use Foo\\Bar as Baz, Qux;
+use function strlen;
+use Foo\\{A, function b, const C};
+
+#[Route('/'), Tag]
+abstract class Controller extends Base implements \\Countable, Stringable
+{
+    use Greets, Waves {
+        Greets::hi insteadof Waves;
+        Waves::hi as protected wave;
+    }
+
+    public const int A = 1, B = 2;
+
+    private static ?array $cache = null, $other;
+
+    public function __construct(private readonly int $a = 1, #[Sensitive] string ...$rest)
+    {
+    }
+"
+    ));
+    assert!(printed.contains(
+        "    } elseif ($b) {
+        return;
+    } else if ($c) {
+        $d = 1;
+    } else {
+        unset($c);
+    }
+"
+    ));
+    assert!(printed.contains(
+        "    switch ($a) {
+        case 1:
+        case 2:
+            $b = 1;
+            break;
+        default:
+            $b = 2;
+    }
+"
+    ));
+    assert!(printed.contains(
+        "    $h = match (true) {
+        $a > 1, $a < -1 => 'far',
+        default => 'near',
+    };
+"
+    ));
+    assert!(printed.ends_with("    return $a <=> $b;\n}\n\ndeclare(ticks=1);\n"));
+}
+
+#[test]
+pub fn styles() {
+    let style = PrintStyle {
+        indent: "\t".to_string(),
+        newline: "\r\n".to_string(),
+        declaration_brace_on_new_line: false,
+        short_arrays: true,
+        open_tag: false,
+    };
+    let printed = round_trip(SCRIPT, &style);
+    assert!(printed.contains("abstract class Controller extends Base implements \\Countable, Stringable {\r\n\tuse Greets"));
+    assert!(printed.contains("\r\n\t[$a, [, $b]] = $c;\r\n"));
+    assert!(
+        !printed.contains('\n') || printed.matches('\n').count() == printed.matches("\r\n").count()
+    );
+
+    let style = PrintStyle {
+        open_tag: true,
+        ..PrintStyle::default()
+    };
+    let script = parse_snippet("echo 1;").unwrap();
+    assert_eq!(print_script(&script, &style), "<?php\n\necho 1;\n");
+}
@@ -0,0 +1,150 @@
+use compiler::syntax::{
+    ast::{
+        class::MemberKind,
+        stmt::{Stmt, StmtKind},
+    },
+    parse::{parse_snippet, parse_snippet_recovering},
+};
+
+/// The messages of every error in a script, along with the text each one points at.
+#[track_caller]
+fn errors(source: &str) -> Vec<(String, &str)> {
+    let (_, diagnostics) = parse_snippet_recovering(source);
+    diagnostics
+        .into_iter()
+        .map(|d| (d.message, &source[d.span.start..d.span.end]))
+        .collect()
+}
+
+/// A short name for the kind of each statement.
+fn kinds(stmts: &[Stmt]) -> Vec<&'static str> {
+    stmts
+        .iter()
+        .map(|stmt| match &stmt.kind {
+            StmtKind::Expr(_) => "expr",
+            StmtKind::If { .. } => "if",
+            StmtKind::Function(_) => "function",
+            StmtKind::ClassLike(_) => "class",
+            StmtKind::Return(_) => "return",
+            StmtKind::Error => "error",
+            other => panic!("unexpected statement {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+pub fn statements() {
+    let source = "$a = ;
+        $b = 1;
+        foo(;
+        if ($c) { $d = ); $e = 2; }";
+    assert_eq!(
+        errors(source),
+        [
+            ("expected an expression, found `;`".to_string(), ";"),
+            ("expected an expression, found `;`".to_string(), ";"),
+            ("expected an expression, found `)`".to_string(), ")"),
+        ]
+    );
+
+    let (script, _) = parse_snippet_recovering(source);
+    assert_eq!(kinds(&script.stmts), ["error", "expr", "error", "if"]);
+    let span = script.stmts[0].span;
+    assert_eq!(&source[span.start..span.end], "$a = ;");
+    let StmtKind::If { then, .. } = &script.stmts[3].kind else {
+        panic!("expected an if");
+    };
+    assert_eq!(kinds(&then.stmts), ["error", "expr"]);
+
+    // the first error is the one `parse_snippet` fails with.
+    assert_eq!(
+        parse_snippet(source).unwrap_err().message,
+        "expected an expression, found `;`"
+    );
+}
+
+#[test]
+pub fn synchronization_points() {
+    // a missing `;` after a closing brace does not swallow the next statement.
+    let source = "$f = function () {} $g = 2;";
+    let (script, diagnostics) = parse_snippet_recovering(source);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "expected `;`, found `$`");
+    assert_eq!(kinds(&script.stmts), ["error", "expr"]);
+
+    // nor does one before a keyword that starts a statement.
+    let (script, diagnostics) = parse_snippet_recovering("$a = 1 + \n if ($b) {} return;");
+    assert_eq!(
+        diagnostics[0].message,
+        "expected an expression, found keyword `if`"
+    );
+    assert_eq!(kinds(&script.stmts), ["error", "if", "return"]);
+
+    // braces skipped over are balanced, a stray `}` is skipped on its own.
+    let (script, diagnostics) = parse_snippet_recovering("foo(1 { $a; } 2); } $b;");
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|d| &d.message[..])
+            .collect::<Vec<_>>(),
+        [
+            "expected `)`, found `{`",
+            "expected `;`, found `)`",
+            "expected an expression, found `}`",
+        ]
+    );
+    assert_eq!(kinds(&script.stmts), ["error", "error", "error", "expr"]);
+}
+
+#[test]
+pub fn class_members() {
+    let source = "class A {
+            public function f() { return 1 }
+            public $a = ;
+            const B = 2;
+            private private $c;
+            function g() {}
+        }
+        $h = 3;";
+    assert_eq!(
+        errors(source),
+        [
+            ("expected `;`, found `}`".to_string(), "}"),
+            ("expected an expression, found `;`".to_string(), ";"),
+            (
+                "multiple private modifiers are not allowed".to_string(),
+                "private"
+            ),
+        ]
+    );
+
+    let (script, _) = parse_snippet_recovering(source);
+    assert_eq!(kinds(&script.stmts), ["class", "expr"]);
+    let StmtKind::ClassLike(class) = &script.stmts[0].kind else {
+        panic!("expected a class");
+    };
+    let members: Vec<_> = class
+        .members
+        .iter()
+        .map(|member| match member.kind {
+            MemberKind::Method(_) => "method",
+            MemberKind::Const(_) => "const",
+            MemberKind::Error => "error",
+            _ => "other",
+        })
+        .collect();
+    assert_eq!(members, ["method", "error", "const", "error", "method"]);
+}
+
+#[test]
+pub fn end_of_file() {
+    // the nested blocks are all open at the end, but the error is only reported once.
+    let (script, diagnostics) = parse_snippet_recovering("function f() { if ($a) { $b = 1;");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "expected `}`, found end of file");
+    assert_eq!(kinds(&script.stmts), ["function"]);
+
+    let (script, diagnostics) = parse_snippet_recovering("$a = 1; $b = \"abc");
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(kinds(&script.stmts)[0], "expr");
+}
@@ -0,0 +1,345 @@
+use compiler::syntax::{
+    ast::{
+        class::MemberKind,
+        expr::{ClassRef, Expr, ExprKind},
+        name::Name,
+        stmt::{Script, Stmt, StmtKind, UseKind},
+        types::TypeHintKind,
+    },
+    diagnostic::{Diagnostic, Severity},
+    parse::parse_snippet,
+    resolve::resolve_names,
+};
+
+#[track_caller]
+fn resolve(source: &str) -> (Script, Vec<Diagnostic>) {
+    let mut script = match parse_snippet(source) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    };
+    let diagnostics = resolve_names(&mut script);
+    (script, diagnostics)
+}
+
+/// The statements of a script, looking inside braced namespaces.
+fn flatten(stmts: &[Stmt]) -> Vec<&Stmt> {
+    stmts
+        .iter()
+        .flat_map(|stmt| match &stmt.kind {
+            StmtKind::Namespace {
+                body: Some(body), ..
+            } => flatten(&body.stmts),
+            _ => vec![stmt],
+        })
+        .collect()
+}
+
+/// The name used by an expression statement, `new A`, `A::B`, `f()` or `C`.
+fn expr_name(expr: &Expr) -> &Name {
+    match &expr.kind {
+        ExprKind::New {
+            class: ClassRef::Name(name),
+            ..
+        }
+        | ExprKind::ClassConstFetch {
+            class: ClassRef::Name(name),
+            ..
+        }
+        | ExprKind::ConstFetch(name) => name,
+        ExprKind::Call { callee, .. } => expr_name(callee),
+        ExprKind::InstanceOf {
+            class: ClassRef::Name(name),
+            ..
+        } => name,
+        other => panic!("no name in {:?}", other),
+    }
+}
+
+/// The resolved form of the name in every expression statement, in order.
+#[track_caller]
+fn assert_resolves(source: &str, expected: &[&str]) {
+    let (script, diagnostics) = resolve(source);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let resolved: Vec<String> = flatten(&script.stmts)
+        .into_iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Expr(expr) => Some(expr_name(expr)),
+            _ => None,
+        })
+        .map(|name| {
+            name.resolved
+                .as_ref()
+                .map_or("-".to_string(), |r| r.to_string())
+        })
+        .collect();
+    assert_eq!(resolved, expected, "while resolving `{}`", source);
+}
+
+#[track_caller]
+fn assert_diagnostic(source: &str, severity: Severity, expected: &str) {
+    let (_, diagnostics) = resolve(source);
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.severity == severity && d.message.contains(expected)),
+        "diagnostics for `{}` were {:?}, expected `{}`",
+        source,
+        diagnostics,
+        expected
+    );
+}
+
+#[test]
+pub fn namespace_declarations() {
+    let (script, _) = resolve("namespace A\\B; $a; namespace C; $b;");
+    match &script.stmts[0].kind {
+        StmtKind::Namespace { name, body: None } => {
+            assert_eq!(name.as_ref().unwrap().to_string(), "A\\B")
+        }
+        other => panic!("expected a namespace, found {:?}", other),
+    }
+    assert_eq!(script.stmts.len(), 4);
+
+    let (script, _) = resolve("namespace A { $a; } namespace { $b; }");
+    match &script.stmts[1].kind {
+        StmtKind::Namespace {
+            name: None,
+            body: Some(body),
+        } => assert_eq!(body.stmts.len(), 1),
+        other => panic!("expected a global namespace block, found {:?}", other),
+    }
+}
+
+#[test]
+pub fn use_declarations() {
+    let (script, _) = resolve(
+        "use A\\B, \\C as D;
+        use function f\\g as h;
+        use const X\\Y;
+        use A\\{B\\C, D as E, function f, const G};",
+    );
+    let StmtKind::Use { kind, uses } = &script.stmts[0].kind else {
+        panic!("expected a use, found {:?}", script.stmts[0].kind);
+    };
+    assert_eq!(*kind, UseKind::Normal);
+    assert_eq!(uses[1].name.to_string(), "\\C");
+    assert_eq!(uses[1].alias.as_ref().unwrap().as_str(), "D");
+
+    let StmtKind::Use { kind, .. } = &script.stmts[1].kind else {
+        panic!("expected a use, found {:?}", script.stmts[1].kind);
+    };
+    assert_eq!(*kind, UseKind::Function);
+
+    let StmtKind::GroupUse { prefix, kind, uses } = &script.stmts[3].kind else {
+        panic!("expected a group use, found {:?}", script.stmts[3].kind);
+    };
+    assert_eq!(prefix.to_string(), "A");
+    assert_eq!(*kind, UseKind::Normal);
+    let kinds: Vec<Option<UseKind>> = uses.iter().map(|item| item.kind).collect();
+    assert_eq!(
+        kinds,
+        [None, None, Some(UseKind::Function), Some(UseKind::Const)]
+    );
+}
+
+#[test]
+pub fn class_names() {
+    assert_resolves(
+        "namespace App;
+        use Lib\\Http;
+        use Lib\\Model\\User as Account;
+        new Foo;
+        new Account;
+        new account;
+        Http\\Request::class;
+        \\Other\\Thing::X;
+        new namespace\\Sub\\Foo;
+        $a instanceof Bar;
+        static::X;",
+        &[
+            "\\App\\Foo",
+            "\\Lib\\Model\\User",
+            "\\Lib\\Model\\User",
+            "\\Lib\\Http\\Request",
+            "\\Other\\Thing",
+            "\\App\\Sub\\Foo",
+            "\\App\\Bar",
+            "-",
+        ],
+    );
+}
+
+#[test]
+pub fn functions_and_constants_fall_back_to_global() {
+    assert_resolves(
+        "namespace App;
+        use function Lib\\render;
+        use const Lib\\VERSION;
+        strlen('a');
+        render();
+        Render();
+        VERSION;
+        version;
+        Sub\\helper();
+        \\strlen('a');",
+        &[
+            "\\App\\strlen or \\strlen",
+            "\\Lib\\render",
+            "\\Lib\\render",
+            "\\Lib\\VERSION",
+            "\\App\\version or \\version",
+            "\\App\\Sub\\helper",
+            "\\strlen",
+        ],
+    );
+    assert_resolves("strlen('a'); FOO;", &["\\strlen", "\\FOO"]);
+}
+
+#[test]
+pub fn const_declarations() {
+    assert_resolves(
+        "namespace App;
+        const LIMIT = 10, NAME = 'app';
+        LIMIT;",
+        &["\\App\\LIMIT or \\LIMIT"],
+    );
+    let (script, _) = resolve("const A = 1, B = A + 1;");
+    let StmtKind::Const(items) = &script.stmts[0].kind else {
+        panic!(
+            "expected a const statement, found {:?}",
+            script.stmts[0].kind
+        );
+    };
+    let names: Vec<&str> = items.iter().map(|item| item.name.name.as_str()).collect();
+    assert_eq!(names, ["A", "B"]);
+    // only the top level of a script or namespace declares constants.
+    assert!(parse_snippet("function f() { const A = 1; }").is_err());
+}
+
+#[test]
+pub fn imports_are_per_namespace() {
+    assert_resolves(
+        "namespace A { use X\\Y; new Y; }
+        namespace B { new Y; }
+        namespace { new Y; }",
+        &["\\X\\Y", "\\B\\Y", "\\Y"],
+    );
+}
+
+#[test]
+pub fn declarations_and_types() {
+    let (script, diagnostics) = resolve(
+        "namespace App;
+        use Lib\\Base;
+        class Foo extends Base implements \\Countable {
+            use Helpers;
+            public function bar(?Baz $a, int|Qux $b): self {}
+        }",
+    );
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let StmtKind::ClassLike(class) = &script.stmts[2].kind else {
+        panic!("expected a class, found {:?}", script.stmts[2].kind);
+    };
+    let resolved = |name: &Name| name.resolved.as_ref().unwrap().to_string();
+    assert_eq!(resolved(&class.extends[0]), "\\Lib\\Base");
+    assert_eq!(resolved(&class.implements[0]), "\\Countable");
+
+    let MemberKind::TraitUse(trait_use) = &class.members[0].kind else {
+        panic!("expected a trait use, found {:?}", class.members[0].kind);
+    };
+    assert_eq!(resolved(&trait_use.traits[0]), "\\App\\Helpers");
+
+    let MemberKind::Method(method) = &class.members[1].kind else {
+        panic!("expected a method, found {:?}", class.members[1].kind);
+    };
+    let params = &method.function.params;
+    let TypeHintKind::Nullable(baz) = &params[0].ty.as_ref().unwrap().kind else {
+        panic!("expected a nullable type");
+    };
+    let TypeHintKind::Named(baz) = &baz.kind else {
+        panic!("expected a named type");
+    };
+    assert_eq!(resolved(baz), "\\App\\Baz");
+    let TypeHintKind::Union(types) = &params[1].ty.as_ref().unwrap().kind else {
+        panic!("expected a union type");
+    };
+    let names: Vec<Option<String>> = types
+        .iter()
+        .map(|ty| match &ty.kind {
+            TypeHintKind::Named(name) => name.resolved.as_ref().map(|r| r.to_string()),
+            _ => panic!("expected a named type"),
+        })
+        .collect();
+    assert_eq!(names, [None, Some("\\App\\Qux".to_string())]);
+}
+
+#[test]
+pub fn conflicting_imports() {
+    assert_diagnostic(
+        "use A\\B; use C\\B;",
+        Severity::Error,
+        "cannot use C\\B as B because the name is already in use",
+    );
+    assert_diagnostic(
+        "namespace N; use A\\Foo; class Foo {}",
+        Severity::Error,
+        "cannot declare class N\\Foo because the name is already in use",
+    );
+    assert_diagnostic(
+        "use A\\B as self;",
+        Severity::Error,
+        "'self' is a special class name",
+    );
+    assert_diagnostic(
+        "use Foo;",
+        Severity::Warning,
+        "the use statement with non-compound name 'Foo' has no effect",
+    );
+    // functions and classes have separate imports.
+    let (_, diagnostics) = resolve("use A\\b; use function C\\b;");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+pub fn duplicate_match_arms() {
+    let (_, diagnostics) = resolve(
+        "namespace App;
+        match ($a) { 1, 2 => 'a', 1 => 'b', default => 'c' };
+        match ($a) { 1, 1.0, '1', -1 => 'a' };
+        match ($a) { FOO => 1, \\App\\FOO => 2, namespace\\FOO => 3, Foo::BAR => 4, \\App\\foo::BAR => 5 };
+        match ($a) { $b => 1, $b => 2, f() => 3, f() => 4 };",
+    );
+    let warnings: Vec<&str> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "duplicate match arm condition 1 is never reached",
+            "duplicate match arm condition namespace\\FOO is never reached",
+            "duplicate match arm condition \\App\\foo::BAR is never reached",
+        ]
+    );
+}
+
+#[test]
+pub fn invalid_namespaces() {
+    let error = |source: &str| parse_snippet(source).unwrap_err().message;
+    assert_eq!(
+        error("namespace A; namespace B {}"),
+        "cannot mix bracketed namespace declarations with unbracketed namespace declarations"
+    );
+    assert!(error("$a; namespace A;").starts_with("namespace declaration statement has to be"));
+    assert_eq!(
+        error("namespace A {} $a;"),
+        "no code may exist outside of namespace {}"
+    );
+    assert_eq!(
+        error("namespace A { namespace B {} }"),
+        "namespace declarations cannot be nested"
+    );
+    assert!(error("while (1) { use A; }").starts_with("use statements are only allowed"));
+    assert!(parse_snippet("declare(strict_types=1); namespace A;").is_ok());
+}
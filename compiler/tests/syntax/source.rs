@@ -0,0 +1,90 @@
+use compiler::syntax::{
+    lex::Lexer,
+    source::{SourceFile, SourceMap, Span},
+};
+
+const SCRIPT: &str = "<?php\n$naïve = 'ü';\n$emoji = '🐘';\n";
+
+#[test]
+pub fn token_spans_slice_the_source() {
+    let file = SourceFile::new("spans.php", SCRIPT);
+    let mut lexer = Lexer::new(file.src());
+    let mut end = 0;
+    let mut strings = Vec::new();
+
+    while let Ok(Some(token)) = lexer.next() {
+        assert_eq!(token.span().start, end, "tokens must be contiguous");
+        end = token.span().end;
+
+        let text = file.slice(token.span());
+        match token.value() {
+            Some(value) if text.starts_with('\'') => {
+                assert_eq!(text, format!("'{}'", value));
+                strings.push(value);
+            }
+            Some(value) => assert_eq!(text, value),
+            None => {}
+        }
+    }
+
+    assert_eq!(strings, ["ü", "🐘"]);
+}
+
+#[test]
+pub fn lookup_lines_and_columns() {
+    let file = SourceFile::new("spans.php", SCRIPT);
+    assert_eq!(file.line_count(), 4);
+    assert_eq!(file.line(1), Some("$naïve = 'ü';"));
+    assert_eq!(file.line(4), None);
+
+    let equals = SCRIPT.find('=').unwrap();
+    let location = file.lookup(equals);
+    assert_eq!(
+        (location.line, location.column, location.utf16_column),
+        (1, 7, 7)
+    );
+    assert_eq!(location.to_string(), "2:8");
+
+    // the elephant is a single char but two utf-16 code units.
+    let quote = SCRIPT.rfind('\'').unwrap();
+    let location = file.lookup(quote);
+    assert_eq!(
+        (location.line, location.column, location.utf16_column),
+        (2, 11, 12)
+    );
+    assert_eq!(file.offset_of_utf16(2, 12), Some(quote));
+
+    // offsets inside a multi-byte char resolve to the start of the char.
+    let elephant = SCRIPT.find('🐘').unwrap();
+    assert_eq!(file.lookup(elephant + 2), file.lookup(elephant));
+
+    let (start, end) = file.lookup_span(Span::new(0, SCRIPT.len()));
+    assert_eq!((start.line, start.column), (0, 0));
+    assert_eq!((end.line, end.column), (3, 0));
+}
+
+#[test]
+pub fn source_map_keeps_files_apart() {
+    let mut map = SourceMap::new();
+    let a = map.add("a.php", "<?php\n");
+    let b = map.add("b.php", "<?php\n\n\n");
+
+    assert_eq!(map.get(a).name(), "a.php");
+    assert_eq!(map.get(b).line_count(), 4);
+    assert_eq!(map.files().count(), 2);
+}
+
+#[test]
+pub fn mixed_line_endings() {
+    let file = SourceFile::new("mixed.php", "<?php\r\n$a;\r$b;\n$c;");
+    assert_eq!(file.line_count(), 4);
+    assert_eq!(file.line(0), Some("<?php"));
+    assert_eq!(file.line(1), Some("$a;"));
+    assert_eq!(file.line(2), Some("$b;"));
+    assert_eq!(file.line(3), Some("$c;"));
+
+    // the `\n` of a `\r\n` pair is still part of the first line.
+    assert_eq!(file.lookup(6).line, 0);
+    assert_eq!(file.lookup(7).line, 1);
+    assert_eq!(file.lookup(file.src().find("$c").unwrap()).line, 3);
+}
@@ -0,0 +1,397 @@
+use compiler::syntax::{
+    ast::{
+        expr::Expr,
+        stmt::{Block, Stmt, StmtKind},
+    },
+    parse::parse_snippet,
+    print::{print_script, PrintStyle},
+};
+
+use super::parse::{assert_script_error, sexp};
+
+/// Renders a statement as an s-expression, in the style of `parse::sexp`.
+pub fn stmt_sexp(stmt: &Stmt) -> String {
+    match &stmt.kind {
+        StmtKind::Expr(expr) => sexp(expr),
+        StmtKind::Echo(exprs) => format!("(echo {})", exprs_sexp(exprs)),
+        StmtKind::Return(Some(expr)) => format!("(return {})", sexp(expr)),
+        StmtKind::Return(None) => "(return)".to_string(),
+        StmtKind::If {
+            cond,
+            then,
+            elseifs,
+            otherwise,
+        } => {
+            let mut out = format!("(if {} {}", sexp(cond), block_sexp(then));
+            for elseif in elseifs {
+                out += &format!(
+                    " (elseif {} {})",
+                    sexp(&elseif.cond),
+                    block_sexp(&elseif.body)
+                );
+            }
+            if let Some(otherwise) = otherwise {
+                out += &format!(" (else {})", block_sexp(otherwise));
+            }
+            out + ")"
+        }
+        StmtKind::While { cond, body } => format!("(while {} {})", sexp(cond), block_sexp(body)),
+        StmtKind::DoWhile { body, cond } => format!("(do {} {})", block_sexp(body), sexp(cond)),
+        StmtKind::For {
+            init,
+            cond,
+            step,
+            body,
+        } => format!(
+            "(for ({}) ({}) ({}) {})",
+            exprs_sexp(init),
+            exprs_sexp(cond),
+            exprs_sexp(step),
+            block_sexp(body)
+        ),
+        StmtKind::Foreach {
+            subject,
+            key,
+            value,
+            by_ref,
+            body,
+        } => format!(
+            "(foreach {} {}{}{} {})",
+            sexp(subject),
+            key.as_ref()
+                .map_or(String::new(), |key| format!("{} => ", sexp(key))),
+            if *by_ref { "&" } else { "" },
+            sexp(value),
+            block_sexp(body)
+        ),
+        StmtKind::Switch { subject, cases } => {
+            let cases: Vec<String> = cases
+                .iter()
+                .map(|case| {
+                    let body: Vec<String> = case.body.iter().map(stmt_sexp).collect();
+                    match &case.cond {
+                        Some(cond) => format!("(case {} {})", sexp(cond), body.join(" ")),
+                        None => format!("(default {})", body.join(" ")),
+                    }
+                })
+                .collect();
+            format!("(switch {} {})", sexp(subject), cases.join(" "))
+        }
+        StmtKind::Break(levels) => {
+            levels.map_or("(break)".to_string(), |n| format!("(break {})", n))
+        }
+        StmtKind::Continue(levels) => {
+            levels.map_or("(continue)".to_string(), |n| format!("(continue {})", n))
+        }
+        StmtKind::Global(vars) => format!("(global {})", exprs_sexp(vars)),
+        StmtKind::Static(vars) => {
+            let vars: Vec<String> = vars
+                .iter()
+                .map(|var| match &var.default {
+                    Some(default) => format!("(= ${} {})", var.name, sexp(default)),
+                    None => format!("${}", var.name),
+                })
+                .collect();
+            format!("(static {})", vars.join(" "))
+        }
+        StmtKind::Unset(vars) => format!("(unset {})", exprs_sexp(vars)),
+        StmtKind::Declare { directives, body } => {
+            let directives: Vec<String> = directives
+                .iter()
+                .map(|d| format!("{}={}", d.name, sexp(&d.value)))
+                .collect();
+            match body {
+                Some(body) => format!("(declare {} {})", directives.join(" "), block_sexp(body)),
+                None => format!("(declare {})", directives.join(" ")),
+            }
+        }
+        StmtKind::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            let mut out = format!("(try {}", block_sexp(body));
+            for catch in catches {
+                let types: Vec<String> = catch.types.iter().map(|t| t.to_string()).collect();
+                out += &format!(" (catch {}", types.join("|"));
+                if let Some(var) = &catch.var {
+                    out += &format!(" ${}", var);
+                }
+                out += &format!(" {})", block_sexp(&catch.body));
+            }
+            if let Some(finally) = finally {
+                out += &format!(" (finally {})", block_sexp(finally));
+            }
+            out + ")"
+        }
+        StmtKind::Goto(label) => format!("(goto {})", label),
+        StmtKind::Label(label) => format!("(label {})", label),
+        StmtKind::Block(block) => block_sexp(block),
+        StmtKind::Nop => "nop".to_string(),
+        StmtKind::InlineHtml(html) => format!("(html {:?})", html),
+        other => format!("{:?}", other),
+    }
+}
+
+fn block_sexp(block: &Block) -> String {
+    let stmts: Vec<String> = block.stmts.iter().map(stmt_sexp).collect();
+    format!("{{{}}}", stmts.join(" "))
+}
+
+fn exprs_sexp(exprs: &[Expr]) -> String {
+    exprs.iter().map(sexp).collect::<Vec<_>>().join(" ")
+}
+
+#[track_caller]
+fn assert_parses(source: &str, expected: &str) {
+    match parse_snippet(source) {
+        Ok(script) => {
+            let stmts: Vec<String> = script.stmts.iter().map(stmt_sexp).collect();
+            assert_eq!(stmts.join(" "), expected, "while parsing `{}`", source);
+        }
+        Err(e) => panic!("failed to parse `{}`: {}", source, e),
+    }
+}
+
+#[test]
+pub fn if_statements() {
+    assert_parses("if ($a) $b;", "(if $a {$b})");
+    assert_parses(
+        "if ($a) { $b; } elseif ($c) { $d; } else { $e; }",
+        "(if $a {$b} (elseif $c {$d}) (else {$e}))",
+    );
+    assert_parses(
+        "if ($a) $b; else if ($c) $d;",
+        "(if $a {$b} (else {(if $c {$d})}))",
+    );
+    assert_parses(
+        "if ($a): $b; $c; elseif ($d): else: $e; endif;",
+        "(if $a {$b $c} (elseif $d {}) (else {$e}))",
+    );
+}
+
+#[test]
+pub fn loops() {
+    assert_parses("while ($a) { $a--; }", "(while $a {(post-- $a)})");
+    assert_parses("while ($a): $a--; endwhile;", "(while $a {(post-- $a)})");
+    assert_parses("do $a++; while ($a < 3);", "(do {(post++ $a)} (< $a 3))");
+    assert_parses(
+        "for ($i = 0, $j = 1; $i < 3; $i++) {}",
+        "(for ((= $i 0) (= $j 1)) ((< $i 3)) ((post++ $i)) {})",
+    );
+    assert_parses("for (;;): break; endfor;", "(for () () () {(break)})");
+}
+
+#[test]
+pub fn foreach_loops() {
+    assert_parses("foreach ($a as $v) {}", "(foreach $a $v {})");
+    assert_parses("foreach ($a as $k => &$v) {}", "(foreach $a $k => &$v {})");
+    assert_parses("foreach ($a as &$v): endforeach;", "(foreach $a &$v {})");
+    assert_parses(
+        "foreach ($a as $k => [$x, 'y' => $y]);",
+        "(foreach $a $k => (list $x \"y\" => $y) {nop})",
+    );
+    assert_script_error(
+        "foreach ($a as f()) {}",
+        "the value of a foreach must be a variable",
+    );
+    assert_script_error(
+        "foreach ($a as &[$x]) {}",
+        "the value of a foreach must be a variable",
+    );
+}
+
+#[test]
+pub fn switch_statements() {
+    assert_parses(
+        "switch ($a) { case 1: case 2; $b; break; default: $c; }",
+        "(switch $a (case 1 ) (case 2 $b (break)) (default $c))",
+    );
+    assert_parses(
+        "switch ($a):; case 1: continue; endswitch;",
+        "(switch $a (case 1 (continue)))",
+    );
+    assert_script_error(
+        "switch ($a) { default: default: }",
+        "switch statements may only contain one default clause",
+    );
+}
+
+#[test]
+pub fn break_and_continue_levels() {
+    assert_parses(
+        "while (1) { foreach ($a as $b) { break 2; } continue 1; }",
+        "(while 1 {(foreach $a $b {(break 2)}) (continue 1)})",
+    );
+    assert_script_error("break;", "'break' not in the 'loop' or 'switch' context");
+    assert_script_error("while (1) { continue 2; }", "cannot 'continue' 2 levels");
+    assert_script_error(
+        "while (1) { break 0; }",
+        "'break' operator accepts only positive integers",
+    );
+    assert_script_error(
+        "while (1) { class A { function f() { break; } } }",
+        "'break' not in the 'loop' or 'switch' context",
+    );
+}
+
+#[test]
+pub fn simple_statements() {
+    assert_parses("return;", "(return)");
+    assert_parses("return $a + 1;", "(return (+ $a 1))");
+    assert_parses("global $a, $$b;", "(global $a ($$b))");
+    assert_parses("static $a = 1, $b;", "(static (= $a 1) $b)");
+    assert_parses("static::foo();", "(::call static foo)");
+    assert_parses("echo $a, 'b';", "(echo $a \"b\")");
+    assert_parses("unset($a, $b['c'],);", "(unset $a ([] $b \"c\"))");
+    assert_parses("goto end; $a; end: $b;", "(goto end) $a (label end) $b");
+    assert_parses("{ $a; } ;", "{$a} nop");
+    assert_script_error("unset(f());", "cannot unset this expression");
+    assert_script_error("endif;", "expected a statement, found keyword `endif`");
+}
+
+#[test]
+pub fn open_and_close_tags() {
+    assert_parses("<?php echo 1;", "(echo 1)");
+    assert_parses(
+        "<?php echo 1 ?>\n<b><?= $a, $b ?></b>\n",
+        "(echo 1) (html \"<b>\") (echo $a $b) (html \"</b>\\n\")",
+    );
+    assert_parses(
+        "<?php if ($a): ?>yes<?php endif;",
+        "(if $a {(html \"yes\")})",
+    );
+    assert_parses("<?php // a comment ?>text", "(html \"text\")");
+    assert_script_error("<?= ?>", "expected an expression");
+
+    let file = "<?php
+
+namespace App;
+
+function title() { return 'Hi'; }
+?>
+<h1><?= title() ?></h1>
+";
+    let script = parse_snippet(file).unwrap();
+    assert_eq!(script.stmts.len(), 5);
+    let style = PrintStyle {
+        open_tag: true,
+        ..PrintStyle::default()
+    };
+    let printed = print_script(&script, &style);
+    let reparsed = parse_snippet(&printed).unwrap();
+    assert_eq!(print_script(&reparsed, &style), printed);
+}
+
+#[test]
+pub fn try_statements() {
+    assert_parses(
+        "try { $a; } catch (A | \\B\\C $e) { $b; } catch (D) {} finally { $c; }",
+        "(try {$a} (catch A|\\B\\C $e {$b}) (catch D {}) (finally {$c}))",
+    );
+    assert_parses("try {} finally {}", "(try {} (finally {}))");
+    assert_script_error("try { $a; }", "cannot use try without catch or finally");
+    assert_script_error("try {} catch ($e) {}", "expected a name");
+    assert_script_error("try {} catch (A |) {}", "expected a name");
+}
+
+#[test]
+pub fn goto_and_labels() {
+    assert_parses(
+        "a: while (1) { goto b; } b: goto a;",
+        "(label a) (while 1 {(goto b)}) (label b) (goto a)",
+    );
+    assert_parses(
+        "while (1) { switch (1) { case 1: goto out; } } out:",
+        "(while 1 {(switch 1 (case 1 (goto out)))}) (label out)",
+    );
+    assert_parses(
+        "try { goto done; } finally { a: goto a; } done:",
+        "(try {(goto done)} (finally {(label a) (goto a)})) (label done)",
+    );
+    // every function has labels of its own.
+    assert!(parse_snippet("function f() { a: } $f = function () { a: goto a; }; a:").is_ok());
+    assert_script_error("goto nowhere;", "'goto' to undefined label 'nowhere'");
+    assert_script_error("a: a:", "label 'a' already defined");
+    assert_script_error("if (1) { a: } else { a: }", "label 'a' already defined");
+    assert_script_error(
+        "goto inside; while (1) { inside: }",
+        "'goto' into loop or switch statement is disallowed",
+    );
+    assert_script_error(
+        "switch (1) { case 1: inside: } goto inside;",
+        "'goto' into loop or switch statement is disallowed",
+    );
+    assert_script_error(
+        "foreach ($a as $b) { x: } foreach ($a as $b) { goto x; }",
+        "'goto' into loop or switch statement is disallowed",
+    );
+    assert_script_error(
+        "try {} finally { goto out; } out:",
+        "jump out of a finally block is disallowed",
+    );
+    assert_script_error(
+        "try { goto in; } finally { in: }",
+        "jump into a finally block is disallowed",
+    );
+    assert_script_error(
+        "while (1) { try {} finally { break; } }",
+        "jump out of a finally block is disallowed",
+    );
+    assert_script_error(
+        "while (1) { try {} finally { while (1) { continue 2; } } }",
+        "jump out of a finally block is disallowed",
+    );
+    assert_script_error(
+        "function f() { goto a; } a:",
+        "'goto' to undefined label 'a'",
+    );
+}
+
+#[test]
+pub fn declare_statements() {
+    assert_parses("declare(strict_types=1);", "(declare strict_types=1)");
+    assert_parses("declare(ticks=1) { $a; }", "(declare ticks=1 {$a})");
+    assert_parses(
+        "declare(ticks=1): $a; enddeclare;",
+        "(declare ticks=1 {$a})",
+    );
+    assert_parses(
+        "declare(encoding='UTF-8'); declare(strict_types=0); $a;",
+        "(declare encoding=\"UTF-8\") (declare strict_types=0) $a",
+    );
+    assert_script_error(
+        "$a; declare(strict_types=1);",
+        "strict_types declaration must be the very first statement in the script",
+    );
+    assert_script_error(
+        "function f() { declare(strict_types=1); }",
+        "strict_types declaration must be the very first statement in the script",
+    );
+    assert_script_error(
+        "declare(strict_types=1) { $a; }",
+        "strict_types declaration must not use block mode",
+    );
+    assert_script_error(
+        "declare(strict_types=2);",
+        "strict_types declaration must have 0 or 1 as its value",
+    );
+    assert_script_error(
+        "declare(ticks='1');",
+        "ticks declaration must have an integer as its value",
+    );
+    assert_script_error(
+        "$a; declare(encoding='UTF-8');",
+        "encoding declaration pragma must be the very first statement in the script",
+    );
+}
+
+#[test]
+pub fn unterminated_alternative_syntax() {
+    assert_script_error("if ($a): $b;", "expected `endif`, found end of file");
+    assert_script_error(
+        "while ($a): $b; endif;",
+        "expected a statement, found keyword `endif`",
+    );
+    assert_script_error("if ($a): $b; endif", "expected `;`, found end of file");
+}
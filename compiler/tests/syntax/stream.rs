@@ -0,0 +1,138 @@
+use std::{
+    fs,
+    io::{Error, ErrorKind, Read},
+    path::Path,
+};
+
+use compiler::syntax::lex::{stream::StreamLexer, Lexer};
+
+/// A reader that hands out at most `step` bytes per read, to cut tokens at every possible place.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    step: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let n = self.step.min(buf.len()).min(self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+/// Collects the debug output of every token, and the final error, if it isn't the end of file.
+fn collect(mut next: impl FnMut() -> Result<Option<String>, Error>) -> Vec<String> {
+    let mut out = Vec::new();
+    loop {
+        match next() {
+            Ok(Some(token)) => out.push(token),
+            Ok(None) => break,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && e.get_ref().is_none() => break,
+            Err(e) => {
+                out.push(format!("error: {}", e));
+                break;
+            }
+        }
+    }
+    out
+}
+
+fn in_memory(script: &[u8]) -> Vec<String> {
+    let mut lexer = Lexer::from_bytes(script).unwrap();
+    collect(|| Ok(lexer.next()?.map(|t| format!("{:?}", t))))
+}
+
+fn streamed(script: &[u8], step: usize) -> Vec<String> {
+    let mut lexer = StreamLexer::new(Trickle {
+        bytes: script,
+        step,
+    })
+    .unwrap();
+    collect(|| Ok(lexer.next()?.map(|t| format!("{:?}", t))))
+}
+
+#[test]
+pub fn stream_matches_in_memory_lexer() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/syntax/fixtures/lex");
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "php") {
+            continue;
+        }
+
+        let script = fs::read(&path).unwrap();
+        let expected = in_memory(&script);
+        for step in [1, 3, 7, 4096] {
+            assert_eq!(
+                streamed(&script, step),
+                expected,
+                "{} read {} bytes at a time",
+                path.display(),
+                step
+            );
+        }
+    }
+}
+
+#[test]
+pub fn stream_memory_is_bounded() {
+    let statement = "$foo = new Foo('bar'); // ünïcödé\r\n";
+    let script = format!("<?php\n{}", statement.repeat(5_000));
+
+    let mut lexer = StreamLexer::new(script.as_bytes()).unwrap();
+    let mut count = 0;
+    let mut max_buffered = 0;
+    while let Ok(Some(_)) = lexer.next() {
+        count += 1;
+        max_buffered = max_buffered.max(lexer.buffered());
+    }
+
+    assert_eq!(count, in_memory(script.as_bytes()).len());
+    assert!(
+        max_buffered < 32 * 1024,
+        "buffered {} bytes of a {} byte script",
+        max_buffered,
+        script.len()
+    );
+}
+
+#[test]
+pub fn stream_stops_reading_at_an_error() {
+    let script = format!(
+        "<?php\n$a = 0189;\n{}",
+        "$foo = new Foo('bar');\n".repeat(5_000)
+    );
+    let mut lexer = StreamLexer::new(script.as_bytes()).unwrap();
+    let error = loop {
+        match lexer.next() {
+            Ok(Some(_)) => {}
+            Ok(None) => panic!("expected an error"),
+            Err(e) => break e,
+        }
+    };
+    assert_eq!(error.to_string(), "Invalid numeric literal `0189`");
+    assert!(
+        lexer.buffered() < 16 * 1024,
+        "buffered {} bytes of a {} byte script",
+        lexer.buffered(),
+        script.len()
+    );
+
+    // a string longer than a chunk is only unterminated once the whole script is read.
+    let long = format!(
+        "<?php\n$a = '{}';\n$b = '{}",
+        "a".repeat(20_000),
+        "b".repeat(20_000)
+    );
+    for script in [&long[..long.len() - 20_001], &long] {
+        assert_eq!(
+            streamed(script.as_bytes(), 4096),
+            in_memory(script.as_bytes())
+        );
+    }
+    assert_eq!(
+        streamed(long.as_bytes(), 4096).last().unwrap(),
+        "error: Unterminated string, missing closing `'`"
+    );
+}
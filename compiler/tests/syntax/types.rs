@@ -0,0 +1,173 @@
+use compiler::syntax::{
+    ast::{
+        stmt::StmtKind,
+        types::{BuiltinType, Type, TypeHint},
+    },
+    parse::parse_snippet,
+    resolve::resolve_names,
+};
+
+use super::parse::script_error;
+
+/// The return type of `function f(): T {}`.
+#[track_caller]
+fn parse_type(ty: &str) -> TypeHint {
+    let source = format!("function f(): {} {{}}", ty);
+    let script = match parse_snippet(&source) {
+        Ok(script) => script,
+        Err(e) => panic!("failed to parse `{}`: {}", ty, e),
+    };
+    match script.stmts.into_iter().next().map(|stmt| stmt.kind) {
+        Some(StmtKind::Function(function)) => function.return_type.unwrap(),
+        other => panic!("expected a function, found {:?}", other),
+    }
+}
+
+#[track_caller]
+fn assert_type(ty: &str, expected: &str) {
+    assert_eq!(parse_type(ty).to_type().to_string(), expected);
+}
+
+#[track_caller]
+fn assert_type_error(ty: &str, expected: &str) {
+    assert_eq!(
+        script_error(&format!("function f(): {} {{}}", ty)),
+        expected
+    );
+}
+
+#[test]
+pub fn type_model() {
+    assert_eq!(parse_type("INT").to_type(), Type::Builtin(BuiltinType::Int));
+    assert_eq!(parse_type("\\int").to_type(), Type::Class("\\int".into()));
+    assert_eq!(
+        parse_type("?Foo").to_type(),
+        Type::Union(vec![
+            Type::Class("Foo".into()),
+            Type::Builtin(BuiltinType::Null)
+        ])
+    );
+    assert_type("?self", "?self");
+    assert_type("int|string|null", "int|string|null");
+    assert_type("A&B", "A&B");
+    assert_type("(A&B)|null", "(A&B)|null");
+    assert_type("(A&B)|(C&D)|false", "(A&B)|(C&D)|false");
+    assert_type("true", "true");
+    assert_type("never", "never");
+
+    let ty = parse_type("?int").to_type();
+    assert!(ty.allows_null() && ty.contains(BuiltinType::Int));
+    assert!(!parse_type("int|false").to_type().allows_null());
+    assert!(parse_type("mixed").to_type().allows_null());
+}
+
+#[test]
+pub fn subtypes() {
+    let ty = |source| parse_type(source).to_type();
+    assert!(ty("int").is_subtype_of(&ty("?int")));
+    assert!(ty("false").is_subtype_of(&ty("bool")));
+    assert!(ty("A&B").is_subtype_of(&ty("B")));
+    assert!(ty("A|int").is_subtype_of(&ty("object|int")));
+    assert!(ty("array").is_subtype_of(&ty("mixed")));
+    assert!(!ty("B").is_subtype_of(&ty("A&B")));
+    assert!(!ty("?int").is_subtype_of(&ty("int")));
+}
+
+#[test]
+pub fn resolved_class_types() {
+    let mut script =
+        parse_snippet("namespace App; use Lib\\Foo; function f(Foo&Bar $a) {}").unwrap();
+    resolve_names(&mut script);
+    let StmtKind::Function(function) = &script.stmts[2].kind else {
+        panic!("expected a function, found {:?}", script.stmts[2].kind);
+    };
+    let ty = function.params[0].ty.as_ref().unwrap().to_type();
+    assert_eq!(ty.to_string(), "\\Lib\\Foo&\\App\\Bar");
+}
+
+#[test]
+pub fn invalid_types() {
+    assert_type_error("int|INT", "duplicate type int is redundant");
+    assert_type_error("Foo|?int", "expected a type, found `?`");
+    assert_type_error("A|a", "duplicate type a is redundant");
+    assert_type_error("(A&B)|(B&A)", "duplicate type B&A is redundant");
+    assert_type_error("A&A", "duplicate type A is redundant");
+    assert_type_error(
+        "?mixed",
+        "type mixed cannot be marked as nullable since mixed already includes null",
+    );
+    assert_type_error("?null", "null cannot be marked as nullable");
+    assert_type_error("?void", "type void can only be used as a standalone type");
+    assert_type_error(
+        "int|void",
+        "type void can only be used as a standalone type",
+    );
+    assert_type_error(
+        "mixed|null",
+        "type mixed can only be used as a standalone type",
+    );
+    assert_type_error("A&int", "type int cannot be part of an intersection type");
+    assert_type_error("A&self", "type self cannot be part of an intersection type");
+    assert_type_error(
+        "true|false",
+        "type true|false contains both true and false, bool should be used instead",
+    );
+    assert_type_error("bool|false", "duplicate type false is redundant");
+    assert_type_error(
+        "iterable|array",
+        "type iterable|array contains both iterable and array, which is redundant",
+    );
+    assert_type_error(
+        "object|Foo",
+        "type object|Foo contains both object and a class type, which is redundant",
+    );
+    assert_type_error(
+        "A&B|null",
+        "intersection type A&B must be parenthesized in a union",
+    );
+    assert_type_error(
+        "null|A&B",
+        "intersection type A&B must be parenthesized in a union",
+    );
+    assert_type_error(
+        "(A&B)|A",
+        "type A&B is redundant as it is more restrictive than type A",
+    );
+}
+
+#[test]
+pub fn types_in_context() {
+    assert!(parse_snippet("function f(): void {} function g(): never {}").is_ok());
+    assert_eq!(
+        script_error("function f(void $a) {}"),
+        "void cannot be used as a parameter type"
+    );
+    assert_eq!(
+        script_error("function f(never $a) {}"),
+        "never cannot be used as a parameter type"
+    );
+    assert_eq!(
+        script_error("class A { public void $a; }"),
+        "property A::$a cannot have type void"
+    );
+    assert_eq!(
+        script_error("class A { public ?callable $a; }"),
+        "property A::$a cannot have type callable"
+    );
+    assert_eq!(
+        script_error("class A { const never B = 1; }"),
+        "class constant A::B cannot have type never"
+    );
+    assert!(parse_snippet("class A { public ?A $a; const int|string B = 1; }").is_ok());
+
+    assert!(parse_snippet("class A { function f(): static|null {} }").is_ok());
+    assert!(parse_snippet("$f = function (): static {};").is_ok());
+    assert_eq!(
+        script_error("function f(): static {}"),
+        "cannot use \"static\" when no class scope is active"
+    );
+    assert_eq!(
+        script_error("function f(): ?static {}"),
+        "cannot use \"static\" when no class scope is active"
+    );
+}
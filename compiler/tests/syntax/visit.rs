@@ -0,0 +1,136 @@
+use compiler::syntax::{
+    ast::{
+        expr::{BinaryOp, Expr, ExprKind, Literal},
+        fold::{self, Fold},
+        name::Name,
+        stmt::StmtKind,
+        visit::{self, Visitor},
+        visit_mut::{self, VisitorMut},
+    },
+    lex::token::Numeric,
+    parse::parse_snippet,
+};
+
+use super::parse::sexp;
+
+const SCRIPT: &str = "namespace App;
+    #[Route('/')]
+    class A extends B implements C {
+        public function f(D $d = E, int ...$rest): ?F {
+            $g = fn($h) => $h + $i;
+            return [$d, G::H, new class { function k() { return $l; } }];
+        }
+    }
+    function m() { if ($n() instanceof O) { echo \"{$p}\"; } }";
+
+/// Every variable in a script, in source order.
+struct Variables<'ast>(Vec<&'ast str>);
+
+impl<'ast> Visitor<'ast> for Variables<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let ExprKind::Variable(name) = &expr.kind {
+            self.0.push(name);
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// Every name in a script, in source order.
+#[derive(Default)]
+struct Names(Vec<String>);
+
+impl Visitor<'_> for Names {
+    fn visit_name(&mut self, name: &Name) {
+        self.0.push(name.to_string());
+    }
+}
+
+#[test]
+pub fn visitor() {
+    let script = parse_snippet(SCRIPT).unwrap();
+    let mut variables = Variables(Vec::new());
+    variables.visit_script(&script);
+    assert_eq!(variables.0, ["g", "h", "i", "d", "l", "n", "p"]);
+
+    let mut names = Names::default();
+    names.visit_script(&script);
+    assert_eq!(
+        names.0,
+        ["App", "Route", "B", "C", "D", "E", "int", "F", "G", "O"]
+    );
+}
+
+/// Renames one variable everywhere.
+struct Rename(&'static str, &'static str);
+
+impl VisitorMut for Rename {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let ExprKind::Variable(name) = &mut expr.kind {
+            if name == self.0 {
+                *name = self.1.to_string();
+            }
+        }
+        visit_mut::walk_expr(self, expr);
+    }
+}
+
+#[test]
+pub fn visitor_mut() {
+    let mut script = parse_snippet(SCRIPT).unwrap();
+    Rename("h", "x").visit_script(&mut script);
+    let mut variables = Variables(Vec::new());
+    variables.visit_script(&script);
+    assert_eq!(variables.0, ["g", "x", "i", "d", "l", "n", "p"]);
+}
+
+/// Replaces additions of two integer literals by their sum, innermost first.
+struct ConstantFolder;
+
+impl Fold for ConstantFolder {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        let expr = fold::fold_expr(self, expr);
+        let ExprKind::Binary {
+            op: BinaryOp::Add,
+            left,
+            right,
+        } = &expr.kind
+        else {
+            return expr;
+        };
+        match (&left.kind, &right.kind) {
+            (
+                ExprKind::Literal(Literal::Numeric(Numeric::Int(a))),
+                ExprKind::Literal(Literal::Numeric(Numeric::Int(b))),
+            ) => Expr::new(
+                ExprKind::Literal(Literal::Numeric(Numeric::Int(a.wrapping_add(*b)))),
+                expr.span,
+            ),
+            _ => expr,
+        }
+    }
+}
+
+#[test]
+pub fn fold() {
+    let script = parse_snippet(
+        "$a = 1 + 2 + 3;
+        function f($b = 4 + 5) { return [fn() => 6 + 7 + $c]; }",
+    )
+    .unwrap();
+    let script = ConstantFolder.fold_script(script);
+
+    let StmtKind::Expr(assign) = &script.stmts[0].kind else {
+        panic!("expected an expression");
+    };
+    assert_eq!(sexp(assign), "(= $a 6)");
+    let StmtKind::Function(function) = &script.stmts[1].kind else {
+        panic!("expected a function");
+    };
+    assert_eq!(sexp(function.params[0].default.as_ref().unwrap()), "9");
+    let Some(StmtKind::Return(Some(value))) =
+        function.body.as_ref().map(|body| &body.stmts[0].kind)
+    else {
+        panic!("expected a return");
+    };
+    assert_eq!(sexp(value), "[(fn () (+ 13 $c))]");
+}